@@ -121,6 +121,13 @@ func main() {
 		fmt.Fprintf(w, strings.TrimSpace(healthMsg))
 	})
 
+	// Server time for client clock-skew checks (NTP-style: clients halve the round trip).
+	r.Get("/time", func(w http.ResponseWriter, r *http.Request) {
+		w.Header().Set("Content-Type", "application/json")
+		w.Header().Set("Cache-Control", "no-store")
+		fmt.Fprintf(w, `{"unix_ms":%d}`, time.Now().UnixMilli())
+	})
+
 	// == Authentication Routes ==
 	r.Route("/auth", func(r chi.Router) {
 		r.Post("/login", authHandler.Login)
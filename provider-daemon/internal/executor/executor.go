@@ -144,6 +144,9 @@ func (se *ScriptExecutor) Execute(ctx context.Context, task *models.Task, worksp
 	return result
 }
 
+// JobIDLabel marks task containers with the job they run, so tools on the host can tell them apart from other containers.
+const JobIDLabel = "com.dantegpu.job-id"
+
 // DockerExecutor implements the Executor interface for running tasks in Docker containers.
 type DockerExecutor struct {
 	cli           *client.Client
@@ -287,6 +290,7 @@ func (de *DockerExecutor) Execute(ctx context.Context, task *models.Task, worksp
 		Tty:          false,        // No TTY for non-interactive tasks
 		AttachStdout: true,
 		AttachStderr: true,
+		Labels:       map[string]string{JobIDLabel: task.JobID},
 	}
 
 	hostConfig := &container.HostConfig{
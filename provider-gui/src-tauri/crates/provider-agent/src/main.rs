@@ -0,0 +1,129 @@
+// Headless provider agent: the GUI's daemon supervision, telemetry history and status views
+// without Tauri, for Linux servers managed by systemd (see packaging/).
+//
+// Logs go to stdout/stderr, which systemd hands to the journal. SIGTERM (systemctl stop) stops
+// the daemon, the sampler and the local API before exiting.
+
+use clap::Parser;
+use dante_provider_core::daemon_cli::DaemonCli;
+use dante_provider_core::local_api::{self, ApiState};
+use dante_provider_core::storage::TelemetryStore;
+use dante_provider_core::supervisor::{Supervisor, SupervisorConfig};
+use dante_provider_core::telemetry::TelemetrySampler;
+use dante_provider_core::LogSink;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+
+const DATABASE_FILE_NAME: &str = "provider.db";
+const RETENTION_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Parser, Debug)]
+#[command(name = "provider-agent", version, about = "Runs the Dante provider daemon headlessly")]
+struct Args {
+    /// Path to the provider daemon binary.
+    #[arg(long, env = "DANTE_DAEMON_PATH", default_value = "/usr/bin/provider-daemon")]
+    daemon: PathBuf,
+    /// Extra arguments for the long-running daemon process (repeatable).
+    #[arg(long = "daemon-arg", env = "DANTE_DAEMON_ARGS", value_delimiter = ' ')]
+    daemon_args: Vec<String>,
+    /// Directory for the agent's SQLite store.
+    #[arg(long, env = "DANTE_DATA_DIR", default_value = "/var/lib/dante-provider")]
+    data_dir: PathBuf,
+    /// Address for the local status API; keep it on loopback, it has no authentication.
+    #[arg(long, env = "DANTE_LISTEN", default_value = "127.0.0.1:7611")]
+    listen: SocketAddr,
+    /// Seconds between telemetry samples.
+    #[arg(long, env = "DANTE_SAMPLE_INTERVAL_SECS", default_value_t = 10)]
+    sample_interval_secs: u64,
+    /// Days of telemetry history to keep.
+    #[arg(long, env = "DANTE_TELEMETRY_RETENTION_DAYS", default_value_t = 30)]
+    telemetry_retention_days: u32,
+}
+
+struct StdoutSink;
+
+impl LogSink for StdoutSink {
+    fn log(&self, level: &str, message: String) {
+        match level {
+            "error" | "stderr" => eprintln!("[{}] {}", level, message),
+            _ => println!("[{}] {}", level, message),
+        }
+    }
+}
+
+async fn wait_for_signal() {
+    #[cfg(unix)]
+    {
+        let mut term = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()).expect("failed to install SIGTERM handler");
+        tokio::select! {
+            _ = term.recv() => {}
+            _ = tokio::signal::ctrl_c() => {}
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = tokio::signal::ctrl_c().await;
+}
+
+#[tokio::main]
+async fn main() {
+    let args = Args::parse();
+    let sink: Arc<dyn LogSink> = Arc::new(StdoutSink);
+
+    if !args.listen.ip().is_loopback() {
+        sink.log("error", format!("Local API is listening on non-loopback address {}; it has no authentication.", args.listen));
+    }
+
+    let store = match TelemetryStore::open(&args.data_dir.join(DATABASE_FILE_NAME)) {
+        Ok(store) => Arc::new(store),
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+    let supervisor = Arc::new(Supervisor::new(SupervisorConfig::new(&args.daemon, args.daemon_args.clone())));
+    let sampler = Arc::new(TelemetrySampler::new(DaemonCli::new(&args.daemon), store.clone()));
+    let api_state = ApiState { supervisor: supervisor.status_handle(), latest: sampler.latest_handle() };
+
+    let supervisor_task = tokio::spawn({
+        let (supervisor, sink, shutdown) = (supervisor.clone(), sink.clone(), shutdown_rx.clone());
+        async move { supervisor.run(sink, shutdown).await }
+    });
+    let sampler_task = tokio::spawn({
+        let (sampler, sink, shutdown) = (sampler.clone(), sink.clone(), shutdown_rx.clone());
+        let interval = Duration::from_secs(args.sample_interval_secs.max(1));
+        async move { sampler.run(interval, sink, shutdown).await }
+    });
+    let api_task = tokio::spawn({
+        let (sink, shutdown, listen) = (sink.clone(), shutdown_rx.clone(), args.listen);
+        async move {
+            if let Err(e) = local_api::serve(listen, api_state, shutdown).await {
+                sink.log("error", e);
+            }
+        }
+    });
+    let retention_task = tokio::spawn({
+        let (store, sink, retention_days) = (store.clone(), sink.clone(), args.telemetry_retention_days);
+        async move {
+            loop {
+                match store.prune_telemetry(retention_days) {
+                    Ok(0) => {}
+                    Ok(n) => sink.log("status", format!("Pruned {} telemetry samples older than {} days.", n, retention_days)),
+                    Err(e) => sink.log("error", e.to_string()),
+                }
+                tokio::time::sleep(RETENTION_INTERVAL).await;
+            }
+        }
+    });
+
+    sink.log("status", format!("Provider agent started; local API on http://{}.", args.listen));
+    wait_for_signal().await;
+    sink.log("status", "Shutting down.".to_string());
+    let _ = shutdown_tx.send(true);
+    retention_task.abort();
+    let _ = tokio::join!(supervisor_task, sampler_task, api_task);
+}
@@ -0,0 +1,42 @@
+// One-shot invocations of the provider daemon's CLI, which answers each request with JSON on
+// stdout. The GUI goes through the Tauri sidecar instead; this is the path-based equivalent.
+
+use serde::de::DeserializeOwned;
+use std::path::PathBuf;
+use tokio::process::Command;
+
+use crate::error::ProviderError;
+
+#[derive(Debug, Clone)]
+pub struct DaemonCli {
+    program: PathBuf,
+}
+
+impl DaemonCli {
+    pub fn new(program: impl Into<PathBuf>) -> Self {
+        DaemonCli { program: program.into() }
+    }
+
+    pub fn program(&self) -> &PathBuf {
+        &self.program
+    }
+
+    pub async fn json<T: DeserializeOwned>(&self, args: &[&str]) -> Result<T, ProviderError> {
+        let output = Command::new(&self.program)
+            .args(args)
+            .kill_on_drop(true)
+            .output()
+            .await
+            .map_err(|e| ProviderError::DaemonUnavailable { program: self.program.display().to_string(), message: e.to_string() })?;
+        let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if !output.status.success() {
+            return Err(ProviderError::DaemonFailed {
+                command: format!("{:?}", args),
+                status: output.status.to_string(),
+                stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+                stdout,
+            });
+        }
+        serde_json::from_str(&stdout).map_err(|e| ProviderError::InvalidResponse { command: format!("{:?}", args), message: e.to_string(), output: stdout })
+    }
+}
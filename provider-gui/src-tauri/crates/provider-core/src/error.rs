@@ -0,0 +1,47 @@
+// Errors from the shared services.
+//
+// Tauri commands still return `Result<T, String>`, so `?` converts these into their message;
+// the tagged form is what the agent's API and typed frontend bindings see.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ProviderError {
+    // The daemon binary couldn't be started.
+    DaemonUnavailable { program: String, message: String },
+    // The daemon ran but exited unsuccessfully.
+    DaemonFailed { command: String, status: String, stderr: String, stdout: String },
+    // The daemon's output didn't match the expected JSON.
+    InvalidResponse { command: String, message: String, output: String },
+    Storage { message: String },
+}
+
+impl fmt::Display for ProviderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProviderError::DaemonUnavailable { program, message } => write!(f, "Failed to run {}: {}", program, message),
+            ProviderError::DaemonFailed { command, status, stderr, stdout } => {
+                write!(f, "Daemon command {} failed with status {}: stderr: '{}', stdout: '{}'", command, status, stderr, stdout)
+            }
+            ProviderError::InvalidResponse { command, message, output } => {
+                write!(f, "Failed to parse JSON from daemon for {}: {}. Output: '{}'", command, message, output)
+            }
+            ProviderError::Storage { message } => f.write_str(message),
+        }
+    }
+}
+
+impl std::error::Error for ProviderError {}
+
+impl From<ProviderError> for String {
+    fn from(e: ProviderError) -> String {
+        e.to_string()
+    }
+}
+
+pub(crate) fn storage(context: &str, e: impl fmt::Display) -> ProviderError {
+    ProviderError::Storage { message: format!("{}: {}", context, e) }
+}
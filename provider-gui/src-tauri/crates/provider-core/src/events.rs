@@ -0,0 +1,15 @@
+// Common shape for events received from outside the daemon, such as the platform's event feed.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub struct EventEnvelope {
+    pub id: String,
+    pub source: String, // "platform_feed"
+    pub kind: String,
+    pub occurred_at: Option<String>,
+    pub received_at: String,
+    pub payload: Value,
+}
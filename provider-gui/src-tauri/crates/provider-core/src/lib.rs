@@ -0,0 +1,22 @@
+// Provider-side models and services that don't depend on Tauri.
+//
+// The desktop GUI and the headless `provider-agent` both build on these: the data types of the
+// daemon's JSON contract, invoking the daemon's CLI, keeping the daemon process running,
+// sampling telemetry into the local SQLite store and (for the agent) serving a small local HTTP
+// API in place of the GUI.
+
+pub mod daemon_cli;
+pub mod error;
+pub mod events;
+pub mod local_api;
+pub mod models;
+pub mod money;
+pub mod storage;
+pub mod supervisor;
+pub mod telemetry;
+pub mod units;
+
+// Where services report progress; the GUI forwards to its log view, the agent to stdout/journald.
+pub trait LogSink: Send + Sync + 'static {
+    fn log(&self, level: &str, message: String);
+}
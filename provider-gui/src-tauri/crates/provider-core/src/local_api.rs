@@ -0,0 +1,51 @@
+// Local HTTP API for headless hosts, standing in for the GUI's status views.
+//
+// Read-only and unauthenticated, so it should only ever listen on a loopback address:
+//   GET /health    supervisor state and when telemetry was last sampled
+//   GET /v1/gpus   latest GPU telemetry
+//   GET /v1/jobs   latest job list
+
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::{Json, Router};
+use serde_json::{json, Value};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use tokio::sync::watch;
+
+use crate::supervisor::SupervisorStatus;
+use crate::telemetry::LatestSample;
+
+#[derive(Clone)]
+pub struct ApiState {
+    pub supervisor: Arc<Mutex<SupervisorStatus>>,
+    pub latest: Arc<Mutex<LatestSample>>,
+}
+
+async fn health(State(state): State<ApiState>) -> (StatusCode, Json<Value>) {
+    let daemon = state.supervisor.lock().unwrap().clone();
+    let latest = state.latest.lock().unwrap();
+    let code = if daemon.running { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    (code, Json(json!({ "daemon": daemon, "last_sampled_at": latest.sampled_at, "last_error": latest.last_error })))
+}
+
+async fn gpus(State(state): State<ApiState>) -> Json<Value> {
+    Json(json!(state.latest.lock().unwrap().gpus))
+}
+
+async fn jobs(State(state): State<ApiState>) -> Json<Value> {
+    Json(json!(state.latest.lock().unwrap().jobs))
+}
+
+pub async fn serve(addr: SocketAddr, state: ApiState, mut shutdown: watch::Receiver<bool>) -> Result<(), String> {
+    let app = Router::new().route("/health", get(health)).route("/v1/gpus", get(gpus)).route("/v1/jobs", get(jobs)).with_state(state);
+    axum::Server::try_bind(&addr)
+        .map_err(|e| format!("Failed to bind local API on {}: {}", addr, e))?
+        .serve(app.into_make_service())
+        .with_graceful_shutdown(async move {
+            let _ = shutdown.changed().await;
+        })
+        .await
+        .map_err(|e| format!("Local API failed: {}", e))
+}
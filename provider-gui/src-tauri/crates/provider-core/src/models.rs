@@ -0,0 +1,239 @@
+// Data exchanged between the GUI, the headless agent and the daemon's CLI.
+//
+// These are the daemon's JSON contract: field names and enum spellings must not change without
+// a matching daemon change. New fields are added with `#[serde(default)]` so older daemons
+// still parse. `tests/contract.rs` pins the wire format.
+
+use serde::{Deserialize, Serialize};
+
+use crate::money::{self, Decimal};
+use crate::units::{DgpuPerHour, MegaBytes, Watts};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub struct GpuInfo {
+    pub id: String,
+    pub name: String,
+    pub model: String,
+    pub vram_total_mb: MegaBytes,
+    pub vram_free_mb: MegaBytes,
+    pub utilization_gpu_percent: Option<u32>,
+    pub temperature_c: Option<u32>,
+    pub power_draw_w: Option<Watts>,
+    pub is_available_for_rent: bool,
+    pub current_hourly_rate_dgpu: Option<DgpuPerHour>,
+    #[serde(default)]
+    pub rental_mode: RentalMode,
+    #[serde(default)]
+    pub ecc_corrected_errors: Option<u64>, // Cumulative since the driver loaded; None without ECC
+    #[serde(default)]
+    pub memory_clock_mhz: Option<u32>,
+    #[serde(default)]
+    pub fan_speed_percent: Option<u32>, // None for passively cooled cards or when unreadable
+    // Negotiated PCIe link next to what card and slot support. Most cards drop to Gen1 at idle
+    // to save power, so only a loaded reading says much about the link.
+    #[serde(default)]
+    pub pcie_link_generation: Option<u32>,
+    #[serde(default)]
+    pub pcie_link_generation_max: Option<u32>,
+    #[serde(default)]
+    pub pcie_link_width: Option<u32>, // Lanes
+    #[serde(default)]
+    pub pcie_link_width_max: Option<u32>,
+    #[serde(default)]
+    pub throttle_reasons: Option<u64>, // NVML clocks throttle reasons bitmask, as currently active
+    // Hardware identity, from daemons that report it. `id` is the daemon's enumeration index and
+    // can move between cards; see `stable_id`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uuid: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pci_bus_id: Option<String>,
+    // The provider's name for the card, set by the GUI. `name` stays the product name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nickname: Option<String>,
+    // Set by the GUI on GPUs it read from nvidia-smi because the daemon couldn't list them. They
+    // carry no rental state and are kept out of telemetry and listing decisions.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub fallback_sourced: bool,
+}
+
+impl GpuInfo {
+    // An ID that stays with the physical card across reboots, driver updates and re-enumeration:
+    // the board UUID, else the PCI slot, else (older daemons) the daemon's own ID.
+    pub fn stable_id(&self) -> String {
+        if let Some(uuid) = self.uuid.as_deref().map(str::trim).filter(|u| !u.is_empty()) {
+            return uuid.to_string();
+        }
+        match self.pci_bus_id.as_deref().and_then(normalize_pci_bus_id) {
+            Some(bus) => format!("PCI-{}", bus),
+            None => self.id.clone(),
+        }
+    }
+}
+
+// "00000000:01:00.0" (nvidia-smi) and "0000:01:00.0" (sysfs) name the same slot; both become
+// the 4-digit-domain, lowercase form.
+pub fn normalize_pci_bus_id(raw: &str) -> Option<String> {
+    let raw = raw.trim().to_ascii_lowercase();
+    let (domain, rest) = match raw.matches(':').count() {
+        2 => raw.split_once(':')?,
+        1 => ("0", raw.as_str()),
+        _ => return None,
+    };
+    let domain = u32::from_str_radix(domain, 16).ok()?;
+    Some(format!("{:04x}:{}", domain, rest))
+}
+
+// Translation between the daemon's GPU IDs and stable IDs for one enumeration of the GPUs.
+// Everything stored or shown uses stable IDs; daemon calls taking `--gpu-id` translate back.
+#[derive(Debug, Clone, Default)]
+pub struct GpuIdMap {
+    pairs: Vec<(String, String)>, // (daemon ID, stable ID)
+}
+
+impl GpuIdMap {
+    // Replaces each GPU's `id` with its stable ID and returns the mapping.
+    pub fn stabilize(gpus: &mut [GpuInfo]) -> Self {
+        let mut pairs = Vec::new();
+        for gpu in gpus {
+            let stable = gpu.stable_id();
+            pairs.push((std::mem::replace(&mut gpu.id, stable.clone()), stable));
+        }
+        GpuIdMap { pairs }
+    }
+
+    pub fn pairs(&self) -> &[(String, String)] {
+        &self.pairs
+    }
+
+    // Unknown IDs pass through unchanged.
+    pub fn stable<'a>(&'a self, daemon_id: &'a str) -> &'a str {
+        self.pairs.iter().find(|(d, _)| d == daemon_id).map_or(daemon_id, |(_, s)| s)
+    }
+
+    pub fn daemon<'a>(&'a self, stable_id: &'a str) -> &'a str {
+        self.pairs.iter().find(|(_, s)| s == stable_id).map_or(stable_id, |(d, _)| d)
+    }
+
+    pub fn stabilize_jobs(&self, jobs: &mut [LocalJob]) {
+        for job in jobs {
+            if let Some(id) = job.gpu_id.as_mut() {
+                *id = self.stable(id).to_string();
+            }
+        }
+    }
+}
+
+// Spot listings are interruptible: cheaper for renters, and the provider can reclaim the GPU.
+// Reserved listings are guaranteed for the rental's duration.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[serde(rename_all = "snake_case")]
+pub enum RentalMode {
+    Spot,
+    #[default]
+    Reserved,
+}
+
+impl RentalMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RentalMode::Spot => "spot",
+            RentalMode::Reserved => "reserved",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub struct ProviderSettings {
+    pub default_hourly_rate_dgpu: DgpuPerHour,
+    pub preferred_currency: String,
+    pub min_job_duration_minutes: u32,
+    pub max_concurrent_jobs: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[serde(rename_all = "snake_case")]
+pub enum JobCategory {
+    Training,
+    Inference,
+    Rendering,
+    DataProcessing,
+    CryptoMining,
+    Other,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub struct LocalJob {
+    pub id: String,
+    pub name: String,
+    pub status: String, // 'running' | 'completed' | 'failed' | 'queued'
+    pub progress_percent: f32,
+    pub submitted_at: String,
+    pub started_at: Option<String>,
+    pub completed_at: Option<String>,
+    #[serde(default, with = "money::optional_amount")]
+    #[cfg_attr(feature = "specta", specta(type = Option<f64>))]
+    pub estimated_cost_dgpu: Option<Decimal>,
+    #[serde(default)]
+    pub renter_id: Option<String>, // Only persisted locally when the privacy settings allow it
+    #[serde(default)]
+    pub category: Option<JobCategory>,
+    #[serde(default)]
+    pub framework: Option<String>,
+    #[serde(default)]
+    pub max_duration_minutes: Option<u32>, // Duration the renter requested, if any
+    #[serde(default)]
+    pub gpu_id: Option<String>,
+    // The mode the rental was taken under, which the GPU's listing may have changed since.
+    #[serde(default)]
+    pub rental_mode: Option<RentalMode>,
+    // Added by the GUI from its own records; the daemon never sends these.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attestation: Option<JobAttestation>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hardware: Option<JobHardwareEvidence>,
+}
+
+// The GPU's PCIe link and throttling as observed while a job ran, kept as evidence should the
+// renter dispute the job's performance.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub struct JobHardwareEvidence {
+    pub samples: u32,
+    pub thermal_throttled_samples: u32,
+    pub power_throttled_samples: u32,
+    // Lowest link seen while the GPU was loaded.
+    pub min_pcie_link_generation: Option<u32>,
+    pub min_pcie_link_width: Option<u32>,
+    pub max_temperature_c: Option<u32>,
+    pub first_sampled_at: String,
+    pub last_sampled_at: String,
+}
+
+// Where a finished job's proof-of-compute submission stands with the platform.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[serde(rename_all = "snake_case")]
+pub enum AttestationState {
+    Pending,   // Waiting for proofs to be collected and submitted
+    Invalid,   // The daemon's proofs failed local validation; not submitted
+    Submitted, // Awaiting the platform's verdict
+    Accepted,
+    Rejected,
+    Failed, // Submission kept failing and was given up
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub struct JobAttestation {
+    pub state: AttestationState,
+    pub proofs: u32,
+    pub attempts: u32,
+    pub submitted_at: Option<String>,
+    pub decided_at: Option<String>,
+    pub detail: Option<String>, // Validation issues, the last error or the rejection reason
+}
@@ -0,0 +1,133 @@
+// DGPU amounts as fixed-point decimals, and how they are rounded.
+//
+// Binary floats can't hold most decimal amounts exactly, and an `f32` keeps only about seven
+// significant digits, so a balance in the thousands loses its fourth decimal. Amounts are held as
+// `Decimal` and rounded once, by the provider's `RoundingPolicy`, where they leave the app
+// (quotes, exports). On the wire they stay JSON numbers so the daemon and the frontend need no
+// change; the serde shims below also accept amounts sent as strings, which keep full precision.
+
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::RoundingStrategy;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+pub use rust_decimal::Decimal;
+
+pub const MAX_DECIMAL_PLACES: u32 = 18;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum RoundingMode {
+    #[default]
+    HalfEven, // Ties to the even digit, so rounding errors don't accumulate in one direction
+    HalfUp,   // Ties away from zero
+    Down,     // Towards zero
+    Up,       // Away from zero
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(default)]
+pub struct RoundingPolicy {
+    pub decimal_places: u32,
+    pub mode: RoundingMode,
+}
+
+impl Default for RoundingPolicy {
+    fn default() -> Self {
+        RoundingPolicy { decimal_places: 6, mode: RoundingMode::HalfEven }
+    }
+}
+
+impl RoundingPolicy {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.decimal_places > MAX_DECIMAL_PLACES {
+            return Err(format!("Amounts can be kept to at most {} decimal places.", MAX_DECIMAL_PLACES));
+        }
+        Ok(())
+    }
+
+    pub fn round(&self, amount: Decimal) -> Decimal {
+        let strategy = match self.mode {
+            RoundingMode::HalfEven => RoundingStrategy::MidpointNearestEven,
+            RoundingMode::HalfUp => RoundingStrategy::MidpointAwayFromZero,
+            RoundingMode::Down => RoundingStrategy::ToZero,
+            RoundingMode::Up => RoundingStrategy::AwayFromZero,
+        };
+        amount.round_dp_with_strategy(self.decimal_places, strategy)
+    }
+
+    // Rounded and written with exactly `decimal_places` digits after the point.
+    pub fn format(&self, amount: Decimal) -> String {
+        format!("{:.*}", self.decimal_places as usize, self.round(amount))
+    }
+}
+
+// The decimal a float was written as: 0.1 becomes 0.1, not the nearest binary fraction. NaN,
+// infinities and values outside Decimal's range are errors; read as zero they would pass for a
+// real amount.
+pub fn from_f64(value: f64) -> Result<Decimal, String> {
+    parse_float(value.is_finite(), value.to_string())
+}
+
+pub fn from_f32(value: f32) -> Result<Decimal, String> {
+    parse_float(value.is_finite(), value.to_string())
+}
+
+fn parse_float(finite: bool, written: String) -> Result<Decimal, String> {
+    if !finite {
+        return Err(format!("{} is not an amount", written));
+    }
+    Decimal::from_str(&written).map_err(|_| format!("{} is out of range for an amount", written))
+}
+
+// For arithmetic that is statistical rather than billing (averages, ratios, charts).
+pub fn to_f64(amount: Decimal) -> f64 {
+    amount.to_f64().unwrap_or(0.0)
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum WireAmount {
+    Number(f64),
+    Text(String),
+}
+
+impl WireAmount {
+    fn into_decimal<E: serde::de::Error>(self) -> Result<Decimal, E> {
+        match self {
+            WireAmount::Number(n) => from_f64(n).map_err(E::custom),
+            WireAmount::Text(s) => Decimal::from_str(s.trim()).map_err(|e| E::custom(format!("invalid amount '{}': {}", s, e))),
+        }
+    }
+}
+
+// `#[serde(with = "money::amount")]`: written as a JSON number, read from a number or a string.
+pub mod amount {
+    use super::*;
+    use serde::{Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(amount: &Decimal, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_f64(to_f64(*amount))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Decimal, D::Error> {
+        WireAmount::deserialize(deserializer)?.into_decimal()
+    }
+}
+
+// As `amount`, for `Option<Decimal>`; null stays null.
+pub mod optional_amount {
+    use super::*;
+    use serde::{Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(amount: &Option<Decimal>, serializer: S) -> Result<S::Ok, S::Error> {
+        match amount {
+            Some(amount) => serializer.serialize_some(&to_f64(*amount)),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Decimal>, D::Error> {
+        Option::<WireAmount>::deserialize(deserializer)?.map(WireAmount::into_decimal).transpose()
+    }
+}
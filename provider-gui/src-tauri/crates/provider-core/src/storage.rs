@@ -0,0 +1,212 @@
+// Telemetry and job history tables, shared by the GUI's `provider.db` and the agent's own
+// database so either can be inspected with the same queries.
+//
+// `migrate` creates and upgrades only these tables; the GUI layers its own tables on top.
+//
+// DGPU amounts and rates are stored as TEXT, the decimal as written (see `money`): a REAL column
+// would round them through an f64. Sums and averages over them are taken in Rust.
+
+use rusqlite::types::{Type, ValueRef};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::error::{self, ProviderError};
+use crate::models::{GpuIdMap, GpuInfo, LocalJob};
+use crate::money::{self, Decimal};
+use crate::units::Watts;
+
+pub const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS telemetry_samples (
+    recorded_at INTEGER NOT NULL,
+    gpu_id TEXT NOT NULL,
+    utilization_gpu_percent INTEGER,
+    temperature_c INTEGER,
+    power_draw_w INTEGER,
+    vram_free_mb INTEGER NOT NULL,
+    is_available_for_rent INTEGER NOT NULL,
+    hourly_rate_dgpu TEXT
+);
+CREATE INDEX IF NOT EXISTS idx_telemetry_recorded_at ON telemetry_samples (recorded_at);
+CREATE INDEX IF NOT EXISTS idx_telemetry_gpu ON telemetry_samples (gpu_id, recorded_at);
+
+CREATE TABLE IF NOT EXISTS jobs (
+    job_id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    status TEXT NOT NULL,
+    submitted_at TEXT NOT NULL,
+    started_at TEXT,
+    completed_at TEXT,
+    estimated_cost_dgpu TEXT,
+    renter_id TEXT,
+    updated_at INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_jobs_updated_at ON jobs (updated_at);
+";
+
+// Columns added to tables created by earlier versions. SQLite has no ADD COLUMN IF NOT EXISTS,
+// so "duplicate column" failures are expected and ignored.
+pub const ADDED_COLUMNS: &[&str] = &[
+    "ALTER TABLE jobs ADD COLUMN gpu_id TEXT",
+    "ALTER TABLE telemetry_samples ADD COLUMN ecc_corrected_errors INTEGER",
+    "ALTER TABLE telemetry_samples ADD COLUMN memory_clock_mhz INTEGER",
+];
+
+// Amount columns earlier versions created as REAL.
+const TEXT_AMOUNT_COLUMNS: &[(&str, &str)] = &[("telemetry_samples", "hourly_rate_dgpu"), ("jobs", "estimated_cost_dgpu")];
+
+pub fn migrate(conn: &Connection) -> Result<(), ProviderError> {
+    conn.execute_batch(SCHEMA).map_err(|e| error::storage("Failed to initialise database schema", e))?;
+    for statement in ADDED_COLUMNS {
+        if let Err(e) = conn.execute(statement, []) {
+            if !e.to_string().contains("duplicate column") {
+                return Err(error::storage("Failed to migrate database schema", e));
+            }
+        }
+    }
+    for (table, column) in TEXT_AMOUNT_COLUMNS {
+        retype_as_text(conn, table, column, "TEXT")?;
+    }
+    Ok(())
+}
+
+// Gives a REAL column of an existing table the declared type `definition` (a TEXT type), keeping
+// its values. Column affinity would turn decimal text back into a float on every insert, so the
+// column is replaced rather than just written differently. A no-op once done.
+pub fn retype_as_text(conn: &Connection, table: &str, column: &str, definition: &str) -> Result<(), ProviderError> {
+    let declared: Option<String> = conn
+        .query_row("SELECT type FROM pragma_table_info(?1) WHERE name = ?2", params![table, column], |row| row.get(0))
+        .optional()
+        .map_err(|e| error::storage("Failed to read database schema", e))?;
+    if !declared.is_some_and(|t| t.eq_ignore_ascii_case("REAL")) {
+        return Ok(());
+    }
+    conn.execute_batch(&format!(
+        "SAVEPOINT retype;
+         ALTER TABLE {0} ADD COLUMN {1}_text {2};
+         UPDATE {0} SET {1}_text = CAST({1} AS TEXT);
+         ALTER TABLE {0} DROP COLUMN {1};
+         ALTER TABLE {0} RENAME COLUMN {1}_text TO {1};
+         RELEASE retype;",
+        table, column, definition
+    ))
+    .map_err(|e| error::storage(&format!("Failed to store {}.{} as text", table, column), e))
+}
+
+// An amount column (see the module comment). Also reads numbers, as aggregates return them.
+pub fn amount_at(row: &Row, index: usize) -> rusqlite::Result<Option<Decimal>> {
+    let invalid = |e: String| rusqlite::Error::FromSqlConversionFailure(index, Type::Text, e.into());
+    match row.get_ref(index)? {
+        ValueRef::Null => Ok(None),
+        ValueRef::Integer(n) => Ok(Some(Decimal::from(n))),
+        ValueRef::Real(n) => money::from_f64(n).map(Some).map_err(invalid),
+        ValueRef::Text(text) => {
+            let text = String::from_utf8_lossy(text);
+            Decimal::from_str(text.trim()).map(Some).map_err(|e| invalid(format!("invalid amount '{}': {}", text, e)))
+        }
+        ValueRef::Blob(_) => Err(rusqlite::Error::InvalidColumnType(index, "amount".to_string(), Type::Blob)),
+    }
+}
+
+pub fn unix_now() -> i64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs() as i64).unwrap_or(0)
+}
+
+pub fn insert_telemetry(conn: &Connection, recorded_at: i64, gpu: &GpuInfo) -> Result<(), ProviderError> {
+    conn.execute(
+        "INSERT INTO telemetry_samples (recorded_at, gpu_id, utilization_gpu_percent, temperature_c, power_draw_w, vram_free_mb, is_available_for_rent, hourly_rate_dgpu, ecc_corrected_errors, memory_clock_mhz)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        params![
+            recorded_at,
+            gpu.id,
+            gpu.utilization_gpu_percent,
+            gpu.temperature_c,
+            gpu.power_draw_w.map(Watts::as_watts),
+            gpu.vram_free_mb.as_mb(),
+            gpu.is_available_for_rent,
+            gpu.current_hourly_rate_dgpu.map(|r| r.as_decimal().to_string()),
+            gpu.ecc_corrected_errors.map(|n| n as i64),
+            gpu.memory_clock_mhz
+        ],
+    )
+    .map(|_| ())
+    .map_err(|e| error::storage("Failed to record telemetry", e))
+}
+
+// Moves history recorded under `from` to `to`: rows written by versions that keyed GPUs by the
+// daemon's enumeration ID. Returns the number of rows moved.
+pub fn rekey_gpu(conn: &Connection, from: &str, to: &str) -> Result<usize, ProviderError> {
+    let mut moved = 0;
+    for statement in ["UPDATE telemetry_samples SET gpu_id = ?2 WHERE gpu_id = ?1", "UPDATE jobs SET gpu_id = ?2 WHERE gpu_id = ?1"] {
+        moved += conn.execute(statement, params![from, to]).map_err(|e| error::storage(&format!("Failed to move history of GPU {}", from), e))?;
+    }
+    Ok(moved)
+}
+
+// Renter IDs are only written when `store_renter_id` is set (the GUI's privacy setting).
+pub fn upsert_job(conn: &Connection, updated_at: i64, job: &LocalJob, store_renter_id: bool) -> Result<(), ProviderError> {
+    let renter_id = if store_renter_id { job.renter_id.as_deref() } else { None };
+    conn.execute(
+        "INSERT INTO jobs (job_id, name, status, submitted_at, started_at, completed_at, estimated_cost_dgpu, renter_id, updated_at, gpu_id)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+         ON CONFLICT(job_id) DO UPDATE SET
+            name = excluded.name, status = excluded.status, started_at = excluded.started_at,
+            completed_at = excluded.completed_at, estimated_cost_dgpu = excluded.estimated_cost_dgpu,
+            renter_id = excluded.renter_id, updated_at = excluded.updated_at, gpu_id = excluded.gpu_id",
+        params![job.id, job.name, job.status, job.submitted_at, job.started_at, job.completed_at, job.estimated_cost_dgpu.map(|a| a.to_string()), renter_id, updated_at, job.gpu_id],
+    )
+    .map(|_| ())
+    .map_err(|e| error::storage(&format!("Failed to record job {}", job.id), e))
+}
+
+// Standalone store used by the headless agent, which has no other tables.
+pub struct TelemetryStore {
+    conn: Mutex<Connection>,
+}
+
+impl TelemetryStore {
+    pub fn open(path: &Path) -> Result<Self, ProviderError> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| error::storage(&format!("Failed to create {}", dir.display()), e))?;
+        }
+        let conn = Connection::open(path).map_err(|e| error::storage(&format!("Failed to open {}", path.display()), e))?;
+        migrate(&conn)?;
+        Ok(TelemetryStore { conn: Mutex::new(conn) })
+    }
+
+    pub fn record(&self, gpus: &[GpuInfo], jobs: &[LocalJob]) -> Result<(), ProviderError> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction().map_err(|e| error::storage("Failed to start transaction", e))?;
+        let now = unix_now();
+        for gpu in gpus {
+            insert_telemetry(&tx, now, gpu)?;
+        }
+        for job in jobs {
+            upsert_job(&tx, now, job, false)?;
+        }
+        tx.commit().map_err(|e| error::storage("Failed to commit telemetry", e))
+    }
+
+    // Moves any history still keyed by daemon IDs onto the stable IDs in `ids`.
+    pub fn adopt_stable_ids(&self, ids: &GpuIdMap) -> Result<usize, ProviderError> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction().map_err(|e| error::storage("Failed to start transaction", e))?;
+        let mut moved = 0;
+        for (daemon_id, stable_id) in ids.pairs().iter().filter(|(d, s)| d != s) {
+            moved += rekey_gpu(&tx, daemon_id, stable_id)?;
+        }
+        tx.commit().map_err(|e| error::storage("Failed to commit GPU ID migration", e))?;
+        Ok(moved)
+    }
+
+    pub fn prune_telemetry(&self, retention_days: u32) -> Result<usize, ProviderError> {
+        let cutoff = unix_now() - retention_days as i64 * 24 * 60 * 60;
+        self.conn
+            .lock()
+            .unwrap()
+            .execute("DELETE FROM telemetry_samples WHERE recorded_at < ?1", params![cutoff])
+            .map_err(|e| error::storage("Failed to prune telemetry", e))
+    }
+}
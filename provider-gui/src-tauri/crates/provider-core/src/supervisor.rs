@@ -0,0 +1,157 @@
+// Keeps the long-running daemon process alive outside the GUI.
+//
+// The daemon is restarted with exponential backoff when it exits; the backoff resets once a run
+// has lasted `stable_after`. Its stdout/stderr are forwarded line by line to the log sink. On
+// shutdown the daemon gets SIGTERM and `stop_timeout` to exit before it is killed.
+
+use serde::Serialize;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::process::{Child, Command};
+use tokio::sync::watch;
+
+use crate::storage::unix_now;
+use crate::LogSink;
+
+#[derive(Debug, Clone)]
+pub struct SupervisorConfig {
+    pub program: PathBuf,
+    pub args: Vec<String>,
+    pub min_backoff: Duration,
+    pub max_backoff: Duration,
+    pub stable_after: Duration,
+    pub stop_timeout: Duration,
+}
+
+impl SupervisorConfig {
+    pub fn new(program: impl Into<PathBuf>, args: Vec<String>) -> Self {
+        SupervisorConfig {
+            program: program.into(),
+            args,
+            min_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(60),
+            stable_after: Duration::from_secs(5 * 60),
+            stop_timeout: Duration::from_secs(20),
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct SupervisorStatus {
+    pub running: bool,
+    pub pid: Option<u32>,
+    pub started_at: Option<i64>,
+    pub restarts: u32,
+    pub last_exit: Option<String>,
+}
+
+pub struct Supervisor {
+    config: SupervisorConfig,
+    status: Arc<Mutex<SupervisorStatus>>,
+}
+
+fn forward<R: AsyncRead + Unpin + Send + 'static>(reader: Option<R>, level: &'static str, sink: Arc<dyn LogSink>) {
+    let Some(reader) = reader else { return };
+    tokio::spawn(async move {
+        let mut lines = BufReader::new(reader).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            sink.log(level, line);
+        }
+    });
+}
+
+async fn terminate(child: &mut Child, timeout: Duration, sink: &Arc<dyn LogSink>) {
+    #[cfg(unix)]
+    if let Some(pid) = child.id() {
+        let _ = Command::new("kill").arg("-TERM").arg(pid.to_string()).status().await;
+        if tokio::time::timeout(timeout, child.wait()).await.is_ok() {
+            return;
+        }
+        sink.log("error", format!("Daemon did not exit within {}s of SIGTERM; killing it.", timeout.as_secs()));
+    }
+    #[cfg(not(unix))]
+    let _ = timeout;
+    let _ = child.kill().await;
+}
+
+impl Supervisor {
+    pub fn new(config: SupervisorConfig) -> Self {
+        Supervisor { config, status: Arc::new(Mutex::new(SupervisorStatus::default())) }
+    }
+
+    pub fn status(&self) -> SupervisorStatus {
+        self.status.lock().unwrap().clone()
+    }
+
+    // Shared handle for readers such as the local API.
+    pub fn status_handle(&self) -> Arc<Mutex<SupervisorStatus>> {
+        self.status.clone()
+    }
+
+    // Runs until `shutdown` flips to true, then stops the daemon and returns.
+    pub async fn run(&self, sink: Arc<dyn LogSink>, mut shutdown: watch::Receiver<bool>) {
+        let mut backoff = self.config.min_backoff;
+        while !*shutdown.borrow() {
+            let spawned = Command::new(&self.config.program)
+                .args(&self.config.args)
+                .stdin(Stdio::null())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .kill_on_drop(true)
+                .spawn();
+            match spawned {
+                Ok(mut child) => {
+                    forward(child.stdout.take(), "stdout", sink.clone());
+                    forward(child.stderr.take(), "stderr", sink.clone());
+                    {
+                        let mut status = self.status.lock().unwrap();
+                        status.running = true;
+                        status.pid = child.id();
+                        status.started_at = Some(unix_now());
+                    }
+                    sink.log("status", format!("Daemon started (pid {}).", child.id().map(|p| p.to_string()).unwrap_or_else(|| "?".to_string())));
+                    let started = Instant::now();
+                    let exit = tokio::select! {
+                        exit = child.wait() => Some(exit),
+                        _ = shutdown.changed() => None,
+                    };
+                    let Some(exit) = exit else {
+                        terminate(&mut child, self.config.stop_timeout, &sink).await;
+                        let mut status = self.status.lock().unwrap();
+                        status.running = false;
+                        status.pid = None;
+                        status.last_exit = Some("stopped".to_string());
+                        sink.log("status", "Daemon stopped.".to_string());
+                        break;
+                    };
+                    let description = match exit {
+                        Ok(code) => code.to_string(),
+                        Err(e) => format!("wait failed: {}", e),
+                    };
+                    {
+                        let mut status = self.status.lock().unwrap();
+                        status.running = false;
+                        status.pid = None;
+                        status.last_exit = Some(description.clone());
+                    }
+                    if started.elapsed() >= self.config.stable_after {
+                        backoff = self.config.min_backoff;
+                    }
+                    sink.log("error", format!("Daemon exited ({}); restarting in {}s.", description, backoff.as_secs()));
+                }
+                Err(e) => {
+                    sink.log("error", format!("Failed to start {}: {}; retrying in {}s.", self.config.program.display(), e, backoff.as_secs()));
+                }
+            }
+            tokio::select! {
+                _ = tokio::time::sleep(backoff) => {}
+                _ = shutdown.changed() => break,
+            }
+            backoff = (backoff * 2).min(self.config.max_backoff);
+            self.status.lock().unwrap().restarts += 1;
+        }
+    }
+}
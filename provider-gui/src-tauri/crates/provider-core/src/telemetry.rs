@@ -0,0 +1,72 @@
+// Periodic sampling of GPU telemetry and job state from the daemon into the local store.
+//
+// The GUI samples from its own polling loop, which also drives the UI; this is the agent's
+// equivalent. The latest sample is kept in memory for the local API.
+
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::watch;
+
+use crate::daemon_cli::DaemonCli;
+use crate::error::ProviderError;
+use crate::models::{GpuIdMap, GpuInfo, LocalJob};
+use crate::storage::{unix_now, TelemetryStore};
+use crate::LogSink;
+
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct LatestSample {
+    pub sampled_at: Option<i64>,
+    pub gpus: Vec<GpuInfo>,
+    pub jobs: Vec<LocalJob>,
+    pub last_error: Option<ProviderError>,
+}
+
+pub struct TelemetrySampler {
+    cli: DaemonCli,
+    store: Arc<TelemetryStore>,
+    latest: Arc<Mutex<LatestSample>>,
+    ids_adopted: AtomicBool,
+}
+
+impl TelemetrySampler {
+    pub fn new(cli: DaemonCli, store: Arc<TelemetryStore>) -> Self {
+        TelemetrySampler { cli, store, latest: Arc::new(Mutex::new(LatestSample::default())), ids_adopted: AtomicBool::new(false) }
+    }
+
+    pub fn latest_handle(&self) -> Arc<Mutex<LatestSample>> {
+        self.latest.clone()
+    }
+
+    pub async fn sample_once(&self) -> Result<(), ProviderError> {
+        let mut gpus: Vec<GpuInfo> = self.cli.json(&["--get-gpus-json"]).await?;
+        let mut jobs: Vec<LocalJob> = self.cli.json(&["--get-local-jobs-json"]).await?;
+        let ids = GpuIdMap::stabilize(&mut gpus);
+        ids.stabilize_jobs(&mut jobs);
+        if !self.ids_adopted.swap(true, Ordering::Relaxed) {
+            self.store.adopt_stable_ids(&ids)?;
+        }
+        self.store.record(&gpus, &jobs)?;
+        *self.latest.lock().unwrap() = LatestSample { sampled_at: Some(unix_now()), gpus, jobs, last_error: None };
+        Ok(())
+    }
+
+    // Samples every `interval` until `shutdown` flips to true. Failures are logged once per
+    // distinct error so a stopped daemon doesn't flood the journal.
+    pub async fn run(&self, interval: Duration, sink: Arc<dyn LogSink>, mut shutdown: watch::Receiver<bool>) {
+        while !*shutdown.borrow() {
+            if let Err(e) = self.sample_once().await {
+                let mut latest = self.latest.lock().unwrap();
+                if latest.last_error.as_ref() != Some(&e) {
+                    sink.log("error", format!("Telemetry sample failed: {}", e));
+                }
+                latest.last_error = Some(e);
+            }
+            tokio::select! {
+                _ = tokio::time::sleep(interval) => {}
+                _ = shutdown.changed() => break,
+            }
+        }
+    }
+}
@@ -0,0 +1,239 @@
+// Measurement newtypes for the quantities most easily mixed up: memory sizes, power and rates.
+//
+// The daemon reports VRAM in megabytes and rates per hour, while people think in gigabytes and
+// the market sometimes quotes per minute; a bare number lost its unit somewhere between the two
+// more than once. Each type serializes as the bare number (`#[serde(transparent)]`), so the
+// daemon contract is unchanged, and conversions go through named helpers. The inner values are
+// private so a bare number can't be pulled out and mixed with one in another unit; arithmetic
+// and ordering are only defined between values of the same type.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, Div, Mul, Sub};
+
+use crate::money::{self, Decimal};
+
+const MB_PER_GB: f64 = 1024.0;
+const BYTES_PER_MB: u64 = 1024 * 1024;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[serde(transparent)]
+pub struct MegaBytes(u32);
+
+impl MegaBytes {
+    pub const fn new(mb: u32) -> Self {
+        MegaBytes(mb)
+    }
+
+    pub fn as_mb(self) -> u32 {
+        self.0
+    }
+
+    pub fn from_bytes(bytes: u64) -> Self {
+        MegaBytes((bytes / BYTES_PER_MB).min(u64::from(u32::MAX)) as u32)
+    }
+
+    pub fn from_gb(gb: f64) -> Self {
+        MegaBytes((gb * MB_PER_GB).round().max(0.0) as u32)
+    }
+
+    pub fn as_gb(self) -> f64 {
+        f64::from(self.0) / MB_PER_GB
+    }
+
+    pub fn bytes(self) -> u64 {
+        u64::from(self.0) * BYTES_PER_MB
+    }
+}
+
+impl Add for MegaBytes {
+    type Output = MegaBytes;
+
+    fn add(self, other: MegaBytes) -> MegaBytes {
+        MegaBytes(self.0.saturating_add(other.0))
+    }
+}
+
+impl Sub for MegaBytes {
+    type Output = MegaBytes;
+
+    fn sub(self, other: MegaBytes) -> MegaBytes {
+        MegaBytes(self.0.saturating_sub(other.0))
+    }
+}
+
+// Splitting a size, e.g. VRAM per process.
+impl Div<u32> for MegaBytes {
+    type Output = MegaBytes;
+
+    fn div(self, divisor: u32) -> MegaBytes {
+        MegaBytes(self.0 / divisor)
+    }
+}
+
+impl Sum for MegaBytes {
+    fn sum<I: Iterator<Item = MegaBytes>>(iter: I) -> MegaBytes {
+        iter.fold(MegaBytes(0), Add::add)
+    }
+}
+
+// "512 MB" below a gigabyte, "23.9 GB" from there on.
+impl fmt::Display for MegaBytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f64::from(self.0) < MB_PER_GB {
+            write!(f, "{} MB", self.0)
+        } else {
+            write!(f, "{:.1} GB", self.as_gb())
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[serde(transparent)]
+pub struct Watts(u32);
+
+impl Watts {
+    pub const fn new(watts: u32) -> Self {
+        Watts(watts)
+    }
+
+    pub fn as_watts(self) -> u32 {
+        self.0
+    }
+
+    pub fn as_kilowatts(self) -> f64 {
+        f64::from(self.0) / 1000.0
+    }
+
+    pub fn kilowatt_hours(self, hours: f64) -> f64 {
+        self.as_kilowatts() * hours
+    }
+}
+
+impl Add for Watts {
+    type Output = Watts;
+
+    fn add(self, other: Watts) -> Watts {
+        Watts(self.0.saturating_add(other.0))
+    }
+}
+
+impl Sub for Watts {
+    type Output = Watts;
+
+    fn sub(self, other: Watts) -> Watts {
+        Watts(self.0.saturating_sub(other.0))
+    }
+}
+
+impl Sum for Watts {
+    fn sum<I: Iterator<Item = Watts>>(iter: I) -> Watts {
+        iter.fold(Watts(0), Add::add)
+    }
+}
+
+impl fmt::Display for Watts {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} W", self.0)
+    }
+}
+
+// A rental rate in DGPU per hour, the unit the daemon and platform use. Rates are amounts and
+// held as exact decimals (see `money`); on the wire they are plain JSON numbers.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[serde(transparent)]
+pub struct DgpuPerHour(
+    #[serde(with = "money::amount")]
+    #[cfg_attr(feature = "specta", specta(type = f64))]
+    Decimal,
+);
+
+impl DgpuPerHour {
+    pub const fn new(rate: Decimal) -> Self {
+        DgpuPerHour(rate)
+    }
+
+    pub fn from_per_minute(rate: Decimal) -> Self {
+        DgpuPerHour(rate * Decimal::from(60))
+    }
+
+    pub fn per_minute(self) -> Decimal {
+        self.0 / Decimal::from(60)
+    }
+
+    // What `hours` at this rate come to, as an exact amount.
+    pub fn cost(self, hours: Decimal) -> Decimal {
+        self.0 * hours
+    }
+
+    pub fn as_decimal(self) -> Decimal {
+        self.0
+    }
+
+    // For statistics (percentiles, elasticity), never for amounts.
+    pub fn as_f64(self) -> f64 {
+        money::to_f64(self.0)
+    }
+
+    pub fn is_zero(self) -> bool {
+        self.0.is_zero()
+    }
+}
+
+impl Add for DgpuPerHour {
+    type Output = DgpuPerHour;
+
+    fn add(self, other: DgpuPerHour) -> DgpuPerHour {
+        DgpuPerHour(self.0 + other.0)
+    }
+}
+
+impl Sub for DgpuPerHour {
+    type Output = DgpuPerHour;
+
+    fn sub(self, other: DgpuPerHour) -> DgpuPerHour {
+        DgpuPerHour(self.0 - other.0)
+    }
+}
+
+// Scaling by a multiplier, e.g. surge pricing.
+impl Mul<Decimal> for DgpuPerHour {
+    type Output = DgpuPerHour;
+
+    fn mul(self, factor: Decimal) -> DgpuPerHour {
+        DgpuPerHour(self.0 * factor)
+    }
+}
+
+impl Div<Decimal> for DgpuPerHour {
+    type Output = DgpuPerHour;
+
+    fn div(self, divisor: Decimal) -> DgpuPerHour {
+        DgpuPerHour(self.0 / divisor)
+    }
+}
+
+// One rate relative to another, e.g. against the market median.
+impl Div for DgpuPerHour {
+    type Output = Decimal;
+
+    fn div(self, other: DgpuPerHour) -> Decimal {
+        self.0 / other.0
+    }
+}
+
+impl Sum for DgpuPerHour {
+    fn sum<I: Iterator<Item = DgpuPerHour>>(iter: I) -> DgpuPerHour {
+        iter.fold(DgpuPerHour::default(), Add::add)
+    }
+}
+
+impl fmt::Display for DgpuPerHour {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:.4} DGPU/hr", self.0)
+    }
+}
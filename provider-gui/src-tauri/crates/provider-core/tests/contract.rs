@@ -0,0 +1,258 @@
+// Pins the JSON exchanged with the daemon. A failure here means a GUI or agent change would
+// break against the daemon (or vice versa); update the daemon alongside the fixture, never just
+// the fixture.
+
+use dante_provider_core::error::ProviderError;
+use dante_provider_core::events::EventEnvelope;
+use dante_provider_core::models::{AttestationState, GpuInfo, JobCategory, LocalJob, ProviderSettings, RentalMode};
+use dante_provider_core::money::{self, Decimal};
+use dante_provider_core::units::{DgpuPerHour, MegaBytes, Watts};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::fmt::Debug;
+
+// Fixture -> type -> JSON must reproduce the fixture exactly, and survive a second pass.
+fn assert_round_trip<T: Serialize + DeserializeOwned + PartialEq + Debug>(fixture: Value) -> T {
+    let parsed: T = serde_json::from_value(fixture.clone()).expect("fixture should deserialize");
+    let serialized = serde_json::to_value(&parsed).expect("value should serialize");
+    assert_eq!(serialized, fixture);
+    let reparsed: T = serde_json::from_value(serialized).expect("serialized value should deserialize");
+    assert_eq!(reparsed, parsed);
+    parsed
+}
+
+fn gpu_fixture() -> Value {
+    json!({
+        "id": "GPU-0",
+        "name": "NVIDIA GeForce RTX 4090",
+        "model": "RTX 4090",
+        "vram_total_mb": 24564,
+        "vram_free_mb": 20480,
+        "utilization_gpu_percent": 37,
+        "temperature_c": 61,
+        "power_draw_w": 280,
+        "is_available_for_rent": true,
+        "current_hourly_rate_dgpu": 0.5,
+        "rental_mode": "spot",
+        "ecc_corrected_errors": null,
+        "memory_clock_mhz": 10501,
+        "fan_speed_percent": 45,
+        "pcie_link_generation": 4,
+        "pcie_link_generation_max": 4,
+        "pcie_link_width": 16,
+        "pcie_link_width_max": 16,
+        "throttle_reasons": 4
+    })
+}
+
+fn job_fixture() -> Value {
+    json!({
+        "id": "job-42",
+        "name": "llama-finetune",
+        "status": "running",
+        "progress_percent": 12.5,
+        "submitted_at": "2024-05-01T10:00:00Z",
+        "started_at": "2024-05-01T10:01:00Z",
+        "completed_at": null,
+        "estimated_cost_dgpu": 3.25,
+        "renter_id": "renter-7",
+        "category": "data_processing",
+        "framework": "pytorch",
+        "max_duration_minutes": 240,
+        "gpu_id": "GPU-0",
+        "rental_mode": "spot"
+    })
+}
+
+#[test]
+fn gpu_info_round_trips() {
+    let gpu: GpuInfo = assert_round_trip(gpu_fixture());
+    assert_eq!(gpu.rental_mode, RentalMode::Spot);
+}
+
+// The unit newtypes are transparent: the daemon still sees bare numbers.
+#[test]
+fn gpu_info_units_are_bare_numbers() {
+    let gpu: GpuInfo = serde_json::from_value(gpu_fixture()).unwrap();
+    assert_eq!(gpu.vram_total_mb, MegaBytes::new(24564));
+    assert_eq!(gpu.power_draw_w, Some(Watts::new(280)));
+    assert_eq!(gpu.current_hourly_rate_dgpu, Some(DgpuPerHour::new(Decimal::new(5, 1))));
+    assert_eq!(gpu.vram_total_mb.to_string(), "24.0 GB");
+    assert_eq!(MegaBytes::new(512).to_string(), "512 MB");
+    assert_eq!(DgpuPerHour::from_per_minute(Decimal::new(1, 2)).to_string(), "0.6000 DGPU/hr");
+}
+
+// Rates are exact decimals like other amounts; a string keeps digits a float would round.
+#[test]
+fn gpu_rates_are_exact_decimals() {
+    let mut fixture = gpu_fixture();
+    fixture["current_hourly_rate_dgpu"] = json!("0.123456789012345678");
+    let gpu: GpuInfo = serde_json::from_value(fixture).unwrap();
+    assert_eq!(gpu.current_hourly_rate_dgpu, Some(DgpuPerHour::new(Decimal::new(123_456_789_012_345_678, 18))));
+
+    let mut fixture = gpu_fixture();
+    fixture["current_hourly_rate_dgpu"] = json!(0.1);
+    let gpu: GpuInfo = serde_json::from_value(fixture).unwrap();
+    assert_eq!(gpu.current_hourly_rate_dgpu, Some(DgpuPerHour::new(Decimal::new(1, 1))));
+}
+
+// Arithmetic stays within a unit; sizes and power never go below zero.
+#[test]
+fn unit_arithmetic_keeps_the_unit() {
+    assert_eq!(MegaBytes::new(512) - MegaBytes::new(1024), MegaBytes::new(0));
+    assert_eq!(MegaBytes::new(24564) / 3, MegaBytes::new(8188));
+    assert_eq!([Watts::new(280), Watts::new(30)].into_iter().sum::<Watts>(), Watts::new(310));
+    let rate = DgpuPerHour::new(Decimal::new(45, 2));
+    assert_eq!(rate * Decimal::new(2, 0), DgpuPerHour::new(Decimal::new(90, 2)));
+    assert_eq!(rate - DgpuPerHour::new(Decimal::new(5, 2)), DgpuPerHour::new(Decimal::new(40, 2)));
+    assert_eq!(rate / DgpuPerHour::new(Decimal::new(90, 2)), Decimal::new(5, 1));
+    assert!(rate < DgpuPerHour::new(Decimal::new(5, 1)));
+}
+
+#[test]
+fn non_finite_floats_are_not_amounts() {
+    assert!(money::from_f64(f64::NAN).is_err());
+    assert!(money::from_f64(f64::INFINITY).is_err());
+    assert!(money::from_f64(1e300).is_err());
+    assert_eq!(money::from_f64(0.1), Ok(Decimal::new(1, 1)));
+}
+
+// Older daemons don't send the hardware identity; it stays off the wire rather than going out as
+// null, and the stable ID falls back from UUID to PCI slot to the daemon's ID.
+#[test]
+fn gpu_identity_is_optional() {
+    let mut fixture = gpu_fixture();
+    let gpu: GpuInfo = assert_round_trip(fixture.clone());
+    assert_eq!(gpu.stable_id(), "GPU-0");
+
+    fixture["pci_bus_id"] = json!("00000000:01:00.0");
+    let gpu: GpuInfo = assert_round_trip(fixture.clone());
+    assert_eq!(gpu.stable_id(), "PCI-0000:01:00.0");
+
+    fixture["uuid"] = json!("GPU-5f1c2a9e-0b7d-4c3e-9a61-2d8e4f7b1c30");
+    let gpu: GpuInfo = assert_round_trip(fixture);
+    assert_eq!(gpu.stable_id(), "GPU-5f1c2a9e-0b7d-4c3e-9a61-2d8e4f7b1c30");
+}
+
+#[test]
+fn gpu_info_accepts_older_daemon_output() {
+    let mut fixture = gpu_fixture();
+    let object = fixture.as_object_mut().unwrap();
+    for field in ["rental_mode", "ecc_corrected_errors", "memory_clock_mhz", "fan_speed_percent"] {
+        object.remove(field);
+    }
+    let gpu: GpuInfo = serde_json::from_value(fixture).unwrap();
+    assert_eq!(gpu.rental_mode, RentalMode::Reserved);
+    assert_eq!(gpu.fan_speed_percent, None);
+}
+
+#[test]
+fn local_job_round_trips() {
+    let job: LocalJob = assert_round_trip(job_fixture());
+    assert_eq!(job.category, Some(JobCategory::DataProcessing));
+}
+
+#[test]
+fn local_job_accepts_older_daemon_output() {
+    let mut fixture = job_fixture();
+    let object = fixture.as_object_mut().unwrap();
+    for field in ["renter_id", "category", "framework", "max_duration_minutes", "gpu_id", "rental_mode"] {
+        object.remove(field);
+    }
+    let job: LocalJob = serde_json::from_value(fixture).unwrap();
+    assert_eq!(job.category, None);
+    assert_eq!(job.gpu_id, None);
+    assert_eq!(job.rental_mode, None);
+}
+
+#[test]
+fn local_job_amounts_are_exact_decimals() {
+    let job: LocalJob = serde_json::from_value(job_fixture()).unwrap();
+    assert_eq!(job.estimated_cost_dgpu, Some(Decimal::new(325, 2)));
+
+    // A daemon may send amounts as strings to keep digits a float would drop.
+    let mut fixture = job_fixture();
+    fixture["estimated_cost_dgpu"] = json!("1234.567891");
+    let job: LocalJob = serde_json::from_value(fixture).unwrap();
+    assert_eq!(job.estimated_cost_dgpu, Some(Decimal::new(1_234_567_891, 6)));
+    assert_eq!(serde_json::to_value(&job).unwrap()["estimated_cost_dgpu"], json!(1234.567891));
+
+    let mut fixture = job_fixture();
+    fixture["estimated_cost_dgpu"] = Value::Null;
+    let job: LocalJob = assert_round_trip(fixture);
+    assert_eq!(job.estimated_cost_dgpu, None);
+}
+
+#[test]
+fn local_job_gui_annotations_are_gui_only() {
+    let job: LocalJob = serde_json::from_value(job_fixture()).unwrap();
+    assert_eq!(job.attestation, None);
+    assert_eq!(job.hardware, None);
+    let serialized = serde_json::to_value(&job).unwrap();
+    assert!(serialized.get("attestation").is_none() && serialized.get("hardware").is_none());
+
+    let mut fixture = job_fixture();
+    fixture["attestation"] = json!({
+        "state": "submitted",
+        "proofs": 2,
+        "attempts": 1,
+        "submitted_at": "2024-05-01T12:00:00Z",
+        "decided_at": null,
+        "detail": null
+    });
+    let job: LocalJob = assert_round_trip(fixture);
+    assert_eq!(job.attestation.map(|a| a.state), Some(AttestationState::Submitted));
+}
+
+#[test]
+fn provider_settings_round_trip() {
+    assert_round_trip::<ProviderSettings>(json!({
+        "default_hourly_rate_dgpu": 0.75,
+        "preferred_currency": "dGPU",
+        "min_job_duration_minutes": 15,
+        "max_concurrent_jobs": 2
+    }));
+}
+
+#[test]
+fn enum_spellings_are_stable() {
+    assert_eq!(serde_json::to_value(RentalMode::Spot).unwrap(), json!("spot"));
+    assert_eq!(serde_json::to_value(RentalMode::Reserved).unwrap(), json!("reserved"));
+    assert_eq!(RentalMode::Spot.as_str(), "spot");
+    let categories = [
+        (JobCategory::Training, "training"),
+        (JobCategory::Inference, "inference"),
+        (JobCategory::Rendering, "rendering"),
+        (JobCategory::DataProcessing, "data_processing"),
+        (JobCategory::CryptoMining, "crypto_mining"),
+        (JobCategory::Other, "other"),
+    ];
+    for (category, spelling) in categories {
+        assert_eq!(serde_json::to_value(category).unwrap(), json!(spelling));
+    }
+}
+
+#[test]
+fn event_envelope_round_trips() {
+    assert_round_trip::<EventEnvelope>(json!({
+        "id": "evt-1001",
+        "source": "platform_feed",
+        "kind": "payment_settled",
+        "occurred_at": "2024-05-01T10:05:00Z",
+        "received_at": "2024-05-01T10:05:01Z",
+        "payload": { "amount_dgpu": 4.2, "job_id": "job-42" }
+    }));
+}
+
+#[test]
+fn provider_error_is_tagged() {
+    let error: ProviderError = assert_round_trip(json!({
+        "kind": "daemon_failed",
+        "command": "[\"--get-gpus-json\"]",
+        "status": "exit status: 1",
+        "stderr": "no NVIDIA driver",
+        "stdout": ""
+    }));
+    assert!(String::from(error).starts_with("Daemon command [\"--get-gpus-json\"] failed"));
+}
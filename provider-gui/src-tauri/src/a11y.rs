@@ -0,0 +1,80 @@
+// Screen-reader announcements.
+//
+// `daemon_log` is too noisy and too technical to read aloud, so the events a provider needs to
+// hear about (the daemon going up or down, jobs finishing, alerts) are also summarised in one
+// short sentence on the `a11y_announcement` channel. Each carries a severity and the ARIA live
+// region politeness to use: `assertive` interrupts the reader and is kept for things that need
+// action now. Repeats of the same sentence within a few seconds are dropped, since a screen
+// reader would read every one.
+
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{Manager, Runtime};
+
+use crate::get_timestamp;
+
+const REPEAT_WINDOW: Duration = Duration::from_secs(10);
+
+static ANNOUNCEMENT_COUNTER: AtomicU64 = AtomicU64::new(0);
+static LAST_ANNOUNCEMENT: Mutex<Option<(String, Instant)>> = Mutex::new(None);
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Info,
+    Success,
+    Warning,
+    Critical,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Politeness {
+    Polite,
+    Assertive,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct A11yAnnouncement {
+    pub id: u64,
+    pub category: String, // "daemon" | "job" | "alert" | "shortcut"
+    pub message: String,
+    pub severity: Severity,
+    pub politeness: Politeness,
+    pub timestamp: String,
+}
+
+pub fn announce<R: Runtime>(manager: &impl Manager<R>, category: &str, severity: Severity, message: String) {
+    {
+        let mut last = LAST_ANNOUNCEMENT.lock().unwrap();
+        if last.as_ref().is_some_and(|(text, at)| *text == message && at.elapsed() < REPEAT_WINDOW) {
+            return;
+        }
+        *last = Some((message.clone(), Instant::now()));
+    }
+    let announcement = A11yAnnouncement {
+        id: ANNOUNCEMENT_COUNTER.fetch_add(1, Ordering::Relaxed) + 1,
+        category: category.to_string(),
+        message,
+        severity,
+        politeness: if severity == Severity::Critical { Politeness::Assertive } else { Politeness::Polite },
+        timestamp: get_timestamp(),
+    };
+    if let Err(e) = manager.emit_all("a11y_announcement", announcement) {
+        eprintln!("Failed to emit a11y_announcement event: {}", e);
+    }
+}
+
+// Called with each daemon status the GUI records.
+pub fn daemon_status<R: Runtime>(manager: &impl Manager<R>, status: &str) {
+    let (severity, message) = match status {
+        "online" => (Severity::Success, "Provider daemon is online."),
+        "stopping" => (Severity::Info, "Provider daemon is stopping."),
+        "offline" => (Severity::Info, "Provider daemon is offline."),
+        "error" => (Severity::Critical, "Provider daemon stopped unexpectedly."),
+        _ => return,
+    };
+    announce(manager, "daemon", severity, message.to_string());
+}
@@ -0,0 +1,225 @@
+// Provider account profiles for operators who run several platform accounts from one machine.
+//
+// Each profile has its own API token (in the keychain under an account-scoped key), payout
+// wallet and display name. Cached platform data and the local history database live in a
+// per-account directory so switching accounts never shows another account's reputation, reviews,
+// jobs or earnings. With no profiles configured, the unscoped keychain keys and the app data
+// directory are used as before. The daemon gets the account's API token in its environment,
+// never on the command line.
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager, State};
+
+use crate::announcements::AnnouncementState;
+use crate::feature_flags::FeatureFlagState;
+use crate::org::OrgState;
+use crate::reputation::ReputationState;
+use crate::storage::Storage;
+use crate::{api_client, emit_log_entry, event_feed, get_timestamp, invoke_daemon_cli_with_env, machine_identity, secrets};
+
+const ACCOUNTS_FILE_NAME: &str = "accounts.json";
+// Names the variable the daemon reads the account's API token from.
+const TOKEN_ENV: &str = "DANTE_ACCOUNT_API_TOKEN";
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Account {
+    pub id: String,
+    pub display_name: String,
+    pub wallet_address: String,
+    pub created_at: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct AccountList {
+    pub active_account_id: Option<String>,
+    pub accounts: Vec<Account>,
+}
+
+pub struct AccountsState {
+    data: Mutex<AccountList>,
+    path: Option<PathBuf>,
+}
+
+impl AccountsState {
+    pub fn load(app_handle: &AppHandle) -> Self {
+        let path = app_handle.path_resolver().app_data_dir().map(|dir| dir.join(ACCOUNTS_FILE_NAME));
+        let data = path
+            .as_ref()
+            .and_then(|p| fs::read_to_string(p).ok())
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        AccountsState { data: Mutex::new(data), path }
+    }
+
+    fn update<T>(&self, f: impl FnOnce(&mut AccountList) -> Result<T, String>) -> Result<T, String> {
+        let mut data = self.data.lock().unwrap();
+        let mut updated = data.clone();
+        let result = f(&mut updated)?;
+        let path = self.path.as_ref().ok_or_else(|| "App data directory is unavailable.".to_string())?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        }
+        let json = serde_json::to_string_pretty(&updated).map_err(|e| format!("Failed to serialize accounts: {}", e))?;
+        fs::write(path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        *data = updated;
+        Ok(result)
+    }
+
+    fn active(&self) -> Option<Account> {
+        let data = self.data.lock().unwrap();
+        let id = data.active_account_id.as_ref()?;
+        data.accounts.iter().find(|a| &a.id == id).cloned()
+    }
+}
+
+pub fn active_account_id(app_handle: &AppHandle) -> Option<String> {
+    app_handle.try_state::<AccountsState>()?.active().map(|a| a.id)
+}
+
+pub fn list(app_handle: &AppHandle) -> AccountList {
+    app_handle.try_state::<AccountsState>().map(|s| s.data.lock().unwrap().clone()).unwrap_or_default()
+}
+
+// Keychain key for `key` under account `id`, e.g. "account.acct-1700000000000.platform.token".
+pub fn scoped_secret_key(id: &str, key: &str) -> String {
+    format!("account.{}.{}", id, key)
+}
+
+// Keychain key for `key` under the active account.
+pub fn secret_key(app_handle: &AppHandle, key: &str) -> String {
+    match active_account_id(app_handle) {
+        Some(id) => scoped_secret_key(&id, key),
+        None => key.to_string(),
+    }
+}
+
+// Directory for data belonging to account `id` under the app data directory `base`.
+pub fn account_dir(base: &Path, id: &str) -> PathBuf {
+    base.join("accounts").join(id)
+}
+
+// Directory for cached platform data belonging to the active account.
+pub fn data_dir(app_handle: &AppHandle) -> Option<PathBuf> {
+    let base = app_handle.path_resolver().app_data_dir()?;
+    Some(match active_account_id(app_handle) {
+        Some(id) => account_dir(&base, &id),
+        None => base,
+    })
+}
+
+// Tells the daemon which account it is earning for; it may be offline, in which case the
+// profile is pushed again on the next switch.
+async fn push_to_daemon(app_handle: &AppHandle, account: &Account) {
+    let token = secrets::get(&secret_key(app_handle, api_client::TOKEN_KEY)).ok().flatten().unwrap_or_default();
+    let payload = json!({ "account_id": account.id, "wallet_address": account.wallet_address, "api_token_env": TOKEN_ENV }).to_string();
+    let env = HashMap::from([(TOKEN_ENV.to_string(), token)]);
+    if let Err(e) = invoke_daemon_cli_with_env::<serde_json::Value>(app_handle, &["--set-account-json", &payload], env).await {
+        emit_log_entry(app_handle, "error", format!("Failed to switch the daemon to account '{}': {}", account.display_name, e));
+    }
+}
+
+// Points caches, local history and the daemon at the newly active account, or at the unscoped
+// data when there is none.
+async fn activate(app_handle: &AppHandle, account: Option<&Account>) {
+    app_handle.state::<ReputationState>().reload(app_handle);
+    app_handle.state::<OrgState>().reload(app_handle);
+    app_handle.state::<AnnouncementState>().reload(app_handle);
+    app_handle.state::<FeatureFlagState>().reload(app_handle);
+    if let Some(storage) = app_handle.try_state::<Storage>() {
+        if let Err(e) = storage.reopen(app_handle) {
+            let name = account.map_or("(none)", |a| a.display_name.as_str());
+            emit_log_entry(app_handle, "error", format!("Failed to open local history for account '{}': {}", name, e));
+        }
+    }
+    event_feed::restart();
+    if let Some(account) = account {
+        push_to_daemon(app_handle, account).await;
+    }
+    machine_identity::sync_on_start(app_handle);
+}
+
+// Replaces the profiles with those from a backup, switching to its active account if that
+// differs from the current one.
+pub async fn restore(app_handle: &AppHandle, list: AccountList) -> Result<(), String> {
+    let state = app_handle.state::<AccountsState>();
+    let switched = state.data.lock().unwrap().active_account_id != list.active_account_id;
+    state.update(|data| {
+        *data = list;
+        Ok(())
+    })?;
+    if switched {
+        activate(app_handle, state.active().as_ref()).await;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn list_accounts(state: State<'_, AccountsState>) -> Result<AccountList, String> {
+    Ok(state.data.lock().unwrap().clone())
+}
+
+#[tauri::command]
+pub async fn add_account(
+    state: State<'_, AccountsState>,
+    display_name: String,
+    wallet_address: String,
+    api_token: String,
+) -> Result<Account, String> {
+    let display_name = display_name.trim().to_string();
+    if display_name.is_empty() {
+        return Err("Account name must not be empty.".to_string());
+    }
+    let millis = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis()).unwrap_or(0);
+    let account = Account {
+        id: format!("acct-{}", millis),
+        display_name,
+        wallet_address: wallet_address.trim().to_string(),
+        created_at: get_timestamp(),
+    };
+    secrets::set(&scoped_secret_key(&account.id, api_client::TOKEN_KEY), api_token.trim())?;
+    state.update(|data| {
+        if data.accounts.iter().any(|a| a.display_name.eq_ignore_ascii_case(&account.display_name)) {
+            return Err(format!("An account named '{}' already exists.", account.display_name));
+        }
+        data.accounts.push(account.clone());
+        Ok(())
+    })?;
+    Ok(account)
+}
+
+#[tauri::command]
+pub async fn remove_account(state: State<'_, AccountsState>, account_id: String) -> Result<(), String> {
+    state.update(|data| {
+        if data.active_account_id.as_deref() == Some(account_id.as_str()) {
+            return Err("Switch to another account before removing the active one.".to_string());
+        }
+        let before = data.accounts.len();
+        data.accounts.retain(|a| a.id != account_id);
+        if data.accounts.len() == before {
+            return Err(format!("No account with ID '{}'.", account_id));
+        }
+        Ok(())
+    })?;
+    secrets::delete(&scoped_secret_key(&account_id, api_client::TOKEN_KEY))
+}
+
+#[tauri::command]
+pub async fn switch_account(app_handle: AppHandle, state: State<'_, AccountsState>, account_id: String) -> Result<Account, String> {
+    let account = state.update(|data| {
+        let account = data.accounts.iter().find(|a| a.id == account_id).cloned().ok_or_else(|| format!("No account with ID '{}'.", account_id))?;
+        data.active_account_id = Some(account.id.clone());
+        Ok(account)
+    })?;
+    activate(&app_handle, Some(&account)).await;
+    emit_log_entry(&app_handle, "status", format!("Switched to account '{}'.", account.display_name));
+    if let Err(e) = app_handle.emit_all("account_switched", &account) {
+        eprintln!("Failed to emit account_switched event: {}", e);
+    }
+    Ok(account)
+}
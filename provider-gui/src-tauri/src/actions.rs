@@ -0,0 +1,166 @@
+// Registry of user-invokable actions.
+//
+// Every operation the app offers is described here with metadata and an argument schema so
+// the frontend can build a searchable command palette and scripts can drive the app through
+// a single `invoke_action(id, args)` entry point instead of knowing each Tauri command.
+
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::fs;
+use tauri::{AppHandle, Manager};
+
+use crate::units::DgpuPerHour;
+use crate::{app_settings, emit_log_entry, DaemonState, GpuInfo};
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ActionArg {
+    pub name: &'static str,
+    pub arg_type: &'static str, // "string" | "number" | "boolean"
+    pub required: bool,
+    pub description: &'static str,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ActionDescriptor {
+    pub id: &'static str,
+    pub title: &'static str,
+    pub description: &'static str,
+    pub category: &'static str,
+    pub keywords: &'static [&'static str],
+    pub args: Vec<ActionArg>,
+}
+
+impl ActionDescriptor {
+    fn matches(&self, query: &str) -> bool {
+        let query = query.to_lowercase();
+        self.id.contains(&query)
+            || self.title.to_lowercase().contains(&query)
+            || self.description.to_lowercase().contains(&query)
+            || self.keywords.iter().any(|k| k.contains(&query))
+    }
+}
+
+fn registry() -> Vec<ActionDescriptor> {
+    vec![
+        ActionDescriptor {
+            id: "daemon.start",
+            title: "Start daemon",
+            description: "Start the provider daemon sidecar.",
+            category: "daemon",
+            keywords: &["run", "online", "launch"],
+            args: vec![],
+        },
+        ActionDescriptor {
+            id: "daemon.stop",
+            title: "Stop daemon",
+            description: "Stop the provider daemon sidecar.",
+            category: "daemon",
+            keywords: &["kill", "offline", "halt"],
+            args: vec![],
+        },
+        ActionDescriptor {
+            id: "gpus.refresh",
+            title: "Refresh GPUs",
+            description: "Re-detect GPUs through the daemon.",
+            category: "gpus",
+            keywords: &["detect", "reload", "scan"],
+            args: vec![],
+        },
+        ActionDescriptor {
+            id: "gpus.set_availability",
+            title: "Toggle GPU availability",
+            description: "Make a GPU available or unavailable for rent at its current rate.",
+            category: "gpus",
+            keywords: &["toggle", "rent", "enable", "disable"],
+            args: vec![
+                ActionArg { name: "gpu_id", arg_type: "string", required: true, description: "Stable GPU identifier, as in the GPU list." },
+                ActionArg { name: "available", arg_type: "boolean", required: false, description: "Target state; toggles when omitted." },
+            ],
+        },
+        ActionDescriptor {
+            id: "logs.export",
+            title: "Export logs",
+            description: "Write the in-memory log buffer to a file as JSON.",
+            category: "logs",
+            keywords: &["save", "download", "support"],
+            args: vec![
+                ActionArg { name: "path", arg_type: "string", required: true, description: "Destination file path." },
+            ],
+        },
+        ActionDescriptor {
+            id: "settings.toggle_lightweight",
+            title: "Toggle lightweight mode",
+            description: "Switch the GUI between normal and low-resource operation.",
+            category: "settings",
+            keywords: &["low", "resource", "performance", "battery"],
+            args: vec![],
+        },
+    ]
+}
+
+fn required_str<'a>(args: &'a Value, name: &str) -> Result<&'a str, String> {
+    args.get(name)
+        .and_then(Value::as_str)
+        .ok_or_else(|| format!("Missing or invalid string argument '{}'.", name))
+}
+
+async fn run_action(app_handle: &AppHandle, id: &str, args: &Value) -> Result<Value, String> {
+    match id {
+        "daemon.start" => crate::start_daemon(app_handle.clone(), app_handle.state::<DaemonState>()).await.map(Value::from),
+        "daemon.stop" => crate::stop_daemon(app_handle.clone(), app_handle.state::<DaemonState>()).await.map(Value::from),
+        "gpus.refresh" => {
+            let gpus = crate::get_detected_gpus(app_handle.clone()).await?;
+            serde_json::to_value(gpus).map_err(|e| e.to_string())
+        }
+        "gpus.set_availability" => {
+            let gpu_id = required_str(args, "gpu_id")?;
+            let gpus: Vec<GpuInfo> = crate::get_detected_gpus(app_handle.clone()).await?;
+            let gpu = gpus
+                .into_iter()
+                .find(|g| g.id == gpu_id)
+                .ok_or_else(|| format!("GPU '{}' not found.", gpu_id))?;
+            let available = args.get("available").and_then(Value::as_bool).unwrap_or(!gpu.is_available_for_rent);
+            let rate = match gpu.current_hourly_rate_dgpu {
+                Some(rate) => rate,
+                None => crate::get_provider_settings(app_handle.clone()).await?.default_hourly_rate_dgpu,
+            };
+            // Never list a card for free because no rate was ever set.
+            if available && rate <= DgpuPerHour::default() {
+                return Err(format!("GPU '{}' has no hourly rate; set one or a default rate before listing it.", gpu_id));
+            }
+            let updated = crate::set_gpu_rental_config(app_handle.clone(), gpu.id, rate, available, None, None).await?;
+            serde_json::to_value(updated).map_err(|e| e.to_string())
+        }
+        "logs.export" => {
+            let path = required_str(args, "path")?;
+            let logs = crate::get_recent_logs(app_handle.state::<DaemonState>()).await?;
+            let json = serde_json::to_string_pretty(&logs).map_err(|e| format!("Failed to serialize logs: {}", e))?;
+            fs::write(path, json).map_err(|e| format!("Failed to write logs to {}: {}", path, e))?;
+            Ok(json!({ "path": path, "entries": logs.len() }))
+        }
+        "settings.toggle_lightweight" => {
+            let state = app_handle.state::<app_settings::AppSettingsState>();
+            let mut settings = state.get();
+            settings.lightweight_mode = !settings.lightweight_mode;
+            let updated = app_settings::update_app_settings(app_handle.clone(), state, settings).await?;
+            serde_json::to_value(updated).map_err(|e| e.to_string())
+        }
+        _ => Err(format!("Unknown action '{}'.", id)),
+    }
+}
+
+#[tauri::command]
+pub async fn list_actions(query: Option<String>) -> Result<Vec<ActionDescriptor>, String> {
+    let actions = registry();
+    Ok(match query.as_deref().map(str::trim) {
+        Some(q) if !q.is_empty() => actions.into_iter().filter(|a| a.matches(q)).collect(),
+        _ => actions,
+    })
+}
+
+#[tauri::command]
+pub async fn invoke_action(app_handle: AppHandle, id: String, args: Option<Value>) -> Result<Value, String> {
+    let args = args.unwrap_or(Value::Null);
+    emit_log_entry(&app_handle, "status", format!("Invoking action '{}'", id));
+    run_action(&app_handle, &id, &args).await
+}
@@ -0,0 +1,606 @@
+// Earnings analytics over the local event ledger.
+//
+// Compares the most recent day against a trailing baseline and raises an `earnings_anomaly`
+// notification when accepted jobs or effective hourly earnings drop sharply, together with
+// the likely causes we can check from here.
+
+use chrono::{Datelike, TimeZone, Timelike};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, UNIX_EPOCH};
+use tauri::{AppHandle, Manager};
+
+use crate::market::MarketState;
+use crate::money::{self, Decimal};
+use crate::pricing;
+use crate::polling::PollingState;
+use crate::storage::{unix_now, Storage};
+use crate::units::DgpuPerHour;
+use crate::{a11y, app_settings, clock, connectivity, emit_log_entry, get_detected_gpus, get_timestamp, graphql, locale, lockdown, plugins, shutdown, GpuInfo, LocalJob};
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+const DAY_SECS: i64 = 24 * 60 * 60;
+const BASELINE_DAYS: i64 = 7;
+// Alert when the last day falls below this percentage of the baseline daily average.
+const DROP_PERCENT: i64 = 50;
+// Rates this far above the market median are named as a likely cause.
+const ABOVE_MEDIAN_PERCENT: i64 = 120;
+// Too little history makes every quiet day look like an anomaly.
+const MIN_BASELINE_JOBS_PER_DAY: f64 = 2.0;
+// A drop stays visible in the trailing day for hours; alert about each metric at most this often.
+const ALERT_COOLDOWN: Duration = Duration::from_secs(24 * 60 * 60);
+const SLOW_PLATFORM_RTT_MS: i64 = 1_000;
+// Sustained load on a GPU with no job running is the usual sign of a cryptojacker or a job
+// container that outlived its rental.
+const UNEXPLAINED_LOAD_PERCENT: u32 = 80;
+const UNEXPLAINED_LOAD_FOR: Duration = Duration::from_secs(15 * 60);
+
+// GPUs currently under unexplained load, since when, and whether that was already reported.
+static UNEXPLAINED_LOAD: Mutex<Vec<(String, Instant, bool)>> = Mutex::new(Vec::new());
+// When each anomaly metric was last alerted about.
+static LAST_ALERTED: Mutex<Vec<(String, Instant)>> = Mutex::new(Vec::new());
+
+#[derive(Serialize, Debug, Clone)]
+pub struct EarningsWindow {
+    pub jobs: i64,
+    #[serde(with = "money::amount")]
+    pub earnings_dgpu: Decimal,
+    #[serde(with = "money::amount")]
+    pub hourly_earnings_dgpu: Decimal,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct EarningsAnomaly {
+    pub detected_at: String,
+    pub metrics: Vec<String>, // "accepted_jobs" | "hourly_earnings"
+    pub recent: EarningsWindow,
+    pub baseline: EarningsWindow, // Daily average over the trailing window
+    pub probable_causes: Vec<String>,
+}
+
+fn window(storage: &Storage, from: i64, to: i64, days: i64) -> Result<EarningsWindow, String> {
+    let (jobs, earnings) = storage.ledger_totals("job_completed", from, to)?;
+    Ok(EarningsWindow {
+        jobs: (jobs as f64 / days as f64).round() as i64,
+        earnings_dgpu: earnings / Decimal::from(days),
+        hourly_earnings_dgpu: earnings / Decimal::from(days * 24),
+    })
+}
+
+async fn probable_causes(app_handle: &AppHandle) -> Vec<String> {
+    let mut causes = Vec::new();
+    let locale = locale::resolve(app_handle);
+    if let Ok(gpus) = get_detected_gpus(app_handle.clone()).await {
+        let market = app_handle.state::<MarketState>();
+        for gpu in &gpus {
+            if let (Some(rate), Some(median)) = (gpu.current_hourly_rate_dgpu, market.floor_for(&gpu.model).and_then(|f| f.median_dgpu_per_hour)) {
+                if rate > median * Decimal::new(ABOVE_MEDIAN_PERCENT, 2) {
+                    causes.push(format!(
+                        "{} is priced at {} DGPU/hr, above the {} market median.",
+                        gpu.name,
+                        locale.number(rate.as_f64(), 2),
+                        locale.number(median.as_f64(), 2)
+                    ));
+                }
+            }
+            if !gpu.is_available_for_rent {
+                causes.push(format!("{} is not listed for rent.", gpu.name));
+            }
+        }
+    }
+    for gpu_id in app_handle.state::<PollingState>().overheated() {
+        causes.push(format!("GPU {} is overheating and may be throttled or quarantined by the daemon.", gpu_id));
+    }
+    match clock::measure(app_handle).await {
+        Err(e) => causes.push(format!("The platform is unreachable: {}", e)),
+        Ok(skew) if skew.round_trip_ms > SLOW_PLATFORM_RTT_MS => {
+            causes.push(format!("Connectivity to the platform is degraded ({} ms round trip).", skew.round_trip_ms))
+        }
+        Ok(_) => {}
+    }
+    causes
+}
+
+// Share of the trailing week in which the GPU reported telemetry, as a proxy for uptime, less
+// connectivity incidents (telemetry keeps being recorded locally while the platform is out of
+// reach). Only meaningful when history is being stored; returns None otherwise.
+pub fn uptime_percent(app_handle: &AppHandle, gpu_id: &str) -> Option<f32> {
+    const BUCKET_SECS: i64 = 5 * 60;
+    let storage = app_handle.try_state::<Storage>()?;
+    let now = unix_now();
+    let from = now - BASELINE_DAYS * DAY_SECS;
+    let covered = storage.sampled_buckets(gpu_id, from, now, BUCKET_SECS).ok()?;
+    let outage = connectivity::outage_secs(&storage, from, now).unwrap_or(0);
+    let online = (covered as i64 * BUCKET_SECS - outage).max(0);
+    (covered > 0).then(|| (online as f32 / (BASELINE_DAYS * DAY_SECS) as f32 * 100.0).min(100.0))
+}
+
+pub async fn detect_anomaly(app_handle: &AppHandle) -> Result<Option<EarningsAnomaly>, String> {
+    let storage = app_handle.try_state::<Storage>().ok_or_else(|| "Local storage is unavailable.".to_string())?;
+    let now = unix_now();
+    let recent = window(&storage, now - DAY_SECS, now, 1)?;
+    let baseline = window(&storage, now - (BASELINE_DAYS + 1) * DAY_SECS, now - DAY_SECS, BASELINE_DAYS)?;
+    if (baseline.jobs as f64) < MIN_BASELINE_JOBS_PER_DAY {
+        return Ok(None);
+    }
+
+    let mut metrics = Vec::new();
+    let drop_ratio = Decimal::new(DROP_PERCENT, 2);
+    if Decimal::from(recent.jobs) < Decimal::from(baseline.jobs) * drop_ratio {
+        metrics.push("accepted_jobs".to_string());
+    }
+    if recent.hourly_earnings_dgpu < baseline.hourly_earnings_dgpu * drop_ratio {
+        metrics.push("hourly_earnings".to_string());
+    }
+    if metrics.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(EarningsAnomaly {
+        detected_at: get_timestamp(),
+        metrics,
+        recent,
+        baseline,
+        probable_causes: probable_causes(app_handle).await,
+    }))
+}
+
+pub fn spawn_anomaly_monitor(app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            if !shutdown::sleep(CHECK_INTERVAL).await {
+                break;
+            }
+            match detect_anomaly(&app_handle).await {
+                Ok(Some(anomaly)) if alert_due(&anomaly.metrics) => {
+                    let locale = locale::resolve(&app_handle);
+                    a11y::announce(&app_handle, "alert", a11y::Severity::Warning, "Earnings have dropped compared with recent weeks.".to_string());
+                    emit_log_entry(
+                        &app_handle,
+                        "error",
+                        format!(
+                            "Earnings dropped versus the {}-day baseline ({}): {} per hour, down from {}.",
+                            BASELINE_DAYS,
+                            anomaly.metrics.join(", "),
+                            locale.currency(money::to_f64(anomaly.recent.hourly_earnings_dgpu), "DGPU"),
+                            locale.currency(money::to_f64(anomaly.baseline.hourly_earnings_dgpu), "DGPU")
+                        ),
+                    );
+                    let payload = json!(anomaly);
+                    plugins::notify(&app_handle, "earnings_anomaly", &payload, true);
+                    if let Err(e) = app_handle.emit_all("earnings_anomaly", anomaly) {
+                        eprintln!("Failed to emit earnings_anomaly event: {}", e);
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => eprintln!("Earnings anomaly check failed: {}", e),
+            }
+        }
+    });
+}
+
+// Whether any of `metrics` is out of its cooldown; if so, all of them are marked as alerted now.
+fn alert_due(metrics: &[String]) -> bool {
+    let mut last_alerted = LAST_ALERTED.lock().unwrap();
+    let due = metrics.iter().any(|metric| !last_alerted.iter().any(|(m, at)| m == metric && at.elapsed() < ALERT_COOLDOWN));
+    if due {
+        last_alerted.retain(|(m, _)| !metrics.contains(m));
+        last_alerted.extend(metrics.iter().map(|m| (m.clone(), Instant::now())));
+    }
+    due
+}
+
+// Flags possible compromise from one poll's GPUs and jobs, and engages lockdown if allowed.
+// Jobs without a GPU assignment (older daemons) could be on any card, so they explain all load.
+// Only listed GPUs are judged: an unlisted card is the owner's to use, and the app has no way to
+// tell their own workloads from an intruder's.
+pub fn check_unexplained_load(app_handle: &AppHandle, gpus: &[GpuInfo], jobs: &[LocalJob]) {
+    let running: Vec<&LocalJob> = jobs.iter().filter(|j| j.status == "running").collect();
+    let mut tracked = UNEXPLAINED_LOAD.lock().unwrap();
+    for gpu in gpus {
+        let busy = gpu.is_available_for_rent && gpu.utilization_gpu_percent.is_some_and(|u| u >= UNEXPLAINED_LOAD_PERCENT);
+        let explained = running.iter().any(|j| j.gpu_id.as_deref().is_none_or(|id| id == gpu.id));
+        if !busy || explained {
+            tracked.retain(|(id, _, _)| id != &gpu.id);
+            continue;
+        }
+        let Some(index) = tracked.iter().position(|(id, _, _)| id == &gpu.id) else {
+            tracked.push((gpu.id.clone(), Instant::now(), false));
+            continue;
+        };
+        let entry = &mut tracked[index];
+        if entry.2 || entry.1.elapsed() < UNEXPLAINED_LOAD_FOR {
+            continue;
+        }
+        entry.2 = true;
+        let reason = format!(
+            "{} has been at {}% utilization for {} minutes with no job running on it.",
+            gpu.name,
+            gpu.utilization_gpu_percent.unwrap_or_default(),
+            UNEXPLAINED_LOAD_FOR.as_secs() / 60
+        );
+        emit_log_entry(app_handle, "error", format!("Possible compromise: {}", reason));
+        a11y::announce(app_handle, "alert", a11y::Severity::Critical, format!("Security warning: {}", reason));
+        let payload = json!({ "gpu_id": gpu.id, "kind": "unexplained_gpu_load", "message": reason, "detected_at": get_timestamp() });
+        plugins::notify(app_handle, "security_anomaly", &payload, true);
+        if let Err(e) = app_handle.emit_all("security_anomaly", payload) {
+            eprintln!("Failed to emit security_anomaly event: {}", e);
+        }
+        lockdown::engage_automatically(app_handle, reason);
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct DiscountUsage {
+    pub discount_id: String,
+    pub name: String,
+    pub active: bool,
+    pub expired: bool,
+    pub redemptions: u32,
+    pub max_redemptions: Option<u32>,
+    #[serde(with = "money::amount")]
+    pub discounted_dgpu: Decimal,
+    // Total discounted so far relative to job earnings over the last week, as a rough cost gauge.
+    pub share_of_earnings_percent: Option<f64>,
+}
+
+#[tauri::command]
+pub async fn get_discount_usage(app_handle: AppHandle) -> Result<Vec<DiscountUsage>, String> {
+    let discounts = pricing::list(&app_handle).await?;
+    let earnings = app_handle.try_state::<Storage>().and_then(|storage| {
+        let now = unix_now();
+        storage.ledger_totals("job_completed", now - BASELINE_DAYS * DAY_SECS, now).ok().map(|(_, earned)| earned)
+    });
+    Ok(discounts
+        .into_iter()
+        .map(|d| DiscountUsage {
+            expired: d.is_expired(),
+            share_of_earnings_percent: earnings.filter(|e| !e.is_zero()).map(|e| money::to_f64(d.discounted_dgpu / e * Decimal::ONE_HUNDRED)),
+            discount_id: d.id,
+            name: d.rule.name,
+            active: d.active,
+            redemptions: d.redemptions,
+            max_redemptions: d.rule.max_redemptions,
+            discounted_dgpu: d.discounted_dgpu,
+        })
+        .collect())
+}
+
+// When renters ask for this model (or any GPU), by region, weekday and UTC hour, to help pick
+// listing hours and off-peak discounts.
+#[tauri::command]
+pub async fn get_demand_heatmap(
+    app_handle: AppHandle,
+    model: Option<String>,
+    days: Option<u32>,
+) -> Result<Vec<graphql::demand_heatmap::DemandHeatmapDemandHeatmap>, String> {
+    let days = days.unwrap_or(BASELINE_DAYS as u32).clamp(1, 90);
+    graphql::demand_heatmap(&app_handle, model.as_deref(), i64::from(days)).await
+}
+
+#[tauri::command]
+pub async fn check_earnings_anomaly(app_handle: AppHandle) -> Result<Option<EarningsAnomaly>, String> {
+    detect_anomaly(&app_handle).await
+}
+
+// A rate schedule to replay history under: `base_rate_dgpu` everywhere except inside the UTC
+// hour windows, which may wrap past midnight.
+#[derive(Deserialize, Debug, Clone)]
+pub struct RateCurve {
+    pub base_rate_dgpu: DgpuPerHour,
+    #[serde(default)]
+    pub windows: Vec<RateWindow>,
+    // Only these GPUs; all with history when empty.
+    #[serde(default)]
+    pub gpu_ids: Vec<String>,
+    #[serde(default)]
+    pub days: Option<u32>,
+    // Percentage change in rented hours per 1% change in price. Renter demand elasticity isn't
+    // observable from one rig, so the caller can try different values.
+    #[serde(default)]
+    pub price_elasticity: Option<f64>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct RateWindow {
+    pub start_hour_utc: u8,
+    pub end_hour_utc: u8,
+    pub rate_dgpu: DgpuPerHour,
+}
+
+impl RateCurve {
+    fn rate_at(&self, hour_of_day: u8) -> DgpuPerHour {
+        self.windows
+            .iter()
+            .find(|w| {
+                if w.start_hour_utc <= w.end_hour_utc {
+                    (w.start_hour_utc..w.end_hour_utc).contains(&hour_of_day)
+                } else {
+                    hour_of_day >= w.start_hour_utc || hour_of_day < w.end_hour_utc
+                }
+            })
+            .map_or(self.base_rate_dgpu, |w| w.rate_dgpu)
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct GpuSimulation {
+    pub gpu_id: String,
+    pub hours_observed: usize,
+    pub rented_hours_actual: f64,
+    pub rented_hours_simulated: f64,
+    #[serde(with = "money::amount")]
+    pub earnings_actual_dgpu: Decimal,
+    #[serde(with = "money::amount")]
+    pub earnings_simulated_dgpu: Decimal,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct PricingSimulation {
+    pub from: String,
+    pub to: String,
+    pub price_elasticity: f64,
+    pub gpus: Vec<GpuSimulation>,
+    #[serde(with = "money::amount")]
+    pub earnings_actual_dgpu: Decimal,
+    #[serde(with = "money::amount")]
+    pub earnings_simulated_dgpu: Decimal,
+    pub change_percent: Option<f64>,
+    pub assumptions: Vec<String>,
+}
+
+const DEFAULT_PRICE_ELASTICITY: f64 = -1.0;
+// A GPU at or above this utilization is treated as rented.
+pub const BUSY_UTILIZATION_PERCENT: u32 = 20;
+const MAX_SIMULATION_DAYS: u32 = 90;
+
+fn format_unix(secs: i64) -> String {
+    humantime::format_rfc3339_seconds(UNIX_EPOCH + Duration::from_secs(secs.max(0) as u64)).to_string()
+}
+
+// Replays stored telemetry under a different rate curve. Each observed GPU-hour keeps its
+// listing state; rented time scales with (new rate / old rate)^elasticity, capped at the time
+// the GPU was listed.
+#[tauri::command]
+pub async fn simulate_pricing(app_handle: AppHandle, rate_curve: RateCurve) -> Result<PricingSimulation, String> {
+    let storage = app_handle.try_state::<Storage>().ok_or_else(|| "Local storage is unavailable.".to_string())?;
+    let days = rate_curve.days.unwrap_or(BASELINE_DAYS as u32).clamp(1, MAX_SIMULATION_DAYS);
+    let elasticity = rate_curve.price_elasticity.unwrap_or(DEFAULT_PRICE_ELASTICITY);
+    let to = unix_now();
+    let from = to - i64::from(days) * DAY_SECS;
+
+    let mut by_gpu: Vec<GpuSimulation> = Vec::new();
+    for hour in storage.hourly_usage(from, to, BUSY_UTILIZATION_PERCENT)? {
+        if !rate_curve.gpu_ids.is_empty() && !rate_curve.gpu_ids.contains(&hour.gpu_id) {
+            continue;
+        }
+        let Some(old_rate) = hour.hourly_rate_dgpu.filter(|r| !r.is_zero()) else { continue };
+        let new_rate = rate_curve.rate_at(((hour.hour_start % DAY_SECS) / 3600) as u8);
+        let rented = hour.busy_fraction.min(hour.listed_fraction);
+        // Demand response is a model, so it runs on floats; the earnings it implies are exact.
+        let simulated = if new_rate.is_zero() {
+            0.0
+        } else {
+            (rented * (new_rate.as_f64() / old_rate.as_f64()).powf(elasticity)).min(hour.listed_fraction)
+        };
+        let earned_actual = old_rate.cost(money::from_f64(rented)?);
+        let earned_simulated = new_rate.cost(money::from_f64(simulated)?);
+
+        let index = match by_gpu.iter().position(|g| g.gpu_id == hour.gpu_id) {
+            Some(index) => index,
+            None => {
+                by_gpu.push(GpuSimulation {
+                    gpu_id: hour.gpu_id.clone(),
+                    hours_observed: 0,
+                    rented_hours_actual: 0.0,
+                    rented_hours_simulated: 0.0,
+                    earnings_actual_dgpu: Decimal::ZERO,
+                    earnings_simulated_dgpu: Decimal::ZERO,
+                });
+                by_gpu.len() - 1
+            }
+        };
+        let gpu = &mut by_gpu[index];
+        gpu.hours_observed += 1;
+        gpu.rented_hours_actual += rented;
+        gpu.rented_hours_simulated += simulated;
+        gpu.earnings_actual_dgpu += earned_actual;
+        gpu.earnings_simulated_dgpu += earned_simulated;
+    }
+    if by_gpu.is_empty() {
+        return Err("No stored telemetry with rates for this period; enable telemetry history to use the simulator.".to_string());
+    }
+
+    let earnings_actual_dgpu: Decimal = by_gpu.iter().map(|g| g.earnings_actual_dgpu).sum();
+    let earnings_simulated_dgpu: Decimal = by_gpu.iter().map(|g| g.earnings_simulated_dgpu).sum();
+    Ok(PricingSimulation {
+        from: format_unix(from),
+        to: format_unix(to),
+        price_elasticity: elasticity,
+        gpus: by_gpu,
+        earnings_actual_dgpu,
+        earnings_simulated_dgpu,
+        change_percent: (!earnings_actual_dgpu.is_zero())
+            .then(|| money::to_f64((earnings_simulated_dgpu / earnings_actual_dgpu - Decimal::ONE) * Decimal::ONE_HUNDRED)),
+        assumptions: vec![
+            format!("A GPU-hour counts as rented in proportion to samples at or above {}% utilization.", BUSY_UTILIZATION_PERCENT),
+            "Earnings are rate × rented hours, before platform fees and discounts; they are not taken from payouts.".to_string(),
+            format!("Rented time scales with (new rate / old rate)^{} and never exceeds the time the GPU was listed.", elasticity),
+            "Listing state is kept as it was; only hours with stored telemetry and a known rate are replayed.".to_string(),
+            "Market conditions (competing offers, demand shifts) are assumed unchanged by the new rate.".to_string(),
+        ],
+    })
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ForecastPoint {
+    pub hour_start: String,
+    pub expected_jobs: f64,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct DemandForecast {
+    pub gpu_model: String,
+    pub history_days: i64,
+    pub jobs_observed: usize,
+    pub baseline_jobs_per_hour: f64,
+    pub points: Vec<ForecastPoint>,
+}
+
+impl DemandForecast {
+    // Expected arrivals in the next hour relative to the average hour; None without history.
+    pub fn next_hour_ratio(&self) -> Option<f64> {
+        let next = self.points.first()?;
+        (self.baseline_jobs_per_hour > 0.0).then(|| next.expected_jobs / self.baseline_jobs_per_hour)
+    }
+}
+
+const FORECAST_HISTORY_DAYS: i64 = 28;
+const MAX_FORECAST_HOURS: u32 = 7 * 24;
+
+// Monday = 0, in UTC.
+fn weekday(unix: i64) -> usize {
+    ((unix.div_euclid(DAY_SECS) + 3).rem_euclid(7)) as usize
+}
+
+fn hour_of_day(unix: i64) -> usize {
+    (unix.rem_euclid(DAY_SECS) / 3600) as usize
+}
+
+// Multiplicative seasonal model over local job arrivals on GPUs of `gpu_model`: the average
+// hourly arrival rate scaled by an hour-of-day and a day-of-week factor, each estimated
+// independently from the trailing four weeks.
+pub async fn demand_forecast(app_handle: &AppHandle, gpu_model: &str, horizon_hours: u32) -> Result<DemandForecast, String> {
+    let storage = app_handle.try_state::<Storage>().ok_or_else(|| "Local storage is unavailable.".to_string())?;
+    let gpu_ids: Vec<String> = get_detected_gpus(app_handle.clone()).await?.into_iter().filter(|g| g.model == gpu_model).map(|g| g.id).collect();
+    let now = unix_now();
+    let arrivals = storage.job_arrivals(&gpu_ids, now - FORECAST_HISTORY_DAYS * DAY_SECS)?;
+
+    // Only count the days we actually have history for, so a new install isn't diluted.
+    let history_days = arrivals.iter().min().map_or(0, |first| ((now - first) / DAY_SECS + 1).min(FORECAST_HISTORY_DAYS));
+    let mut by_hour = [0.0_f64; 24];
+    let mut by_weekday = [0.0_f64; 7];
+    for t in &arrivals {
+        by_hour[hour_of_day(*t)] += 1.0;
+        by_weekday[weekday(*t)] += 1.0;
+    }
+    // How many of each weekday the history covers; with under a week of history some are absent.
+    let mut weekday_days = [0.0_f64; 7];
+    for day in 0..history_days {
+        weekday_days[weekday(now - day * DAY_SECS)] += 1.0;
+    }
+    let level = if history_days > 0 { arrivals.len() as f64 / (history_days * 24) as f64 } else { 0.0 };
+    let days = history_days.max(1) as f64;
+
+    let first_hour = (now / 3600 + 1) * 3600;
+    let points = (0..i64::from(horizon_hours.clamp(1, MAX_FORECAST_HOURS)))
+        .map(|i| {
+            let t = first_hour + i * 3600;
+            let expected_jobs = if level > 0.0 {
+                let hour_factor = by_hour[hour_of_day(t)] / days / level;
+                // A weekday with no history yet is assumed average.
+                let covered = weekday_days[weekday(t)];
+                let weekday_factor = if covered > 0.0 { by_weekday[weekday(t)] / (covered * 24.0) / level } else { 1.0 };
+                level * hour_factor * weekday_factor
+            } else {
+                0.0
+            };
+            ForecastPoint { hour_start: format_unix(t), expected_jobs }
+        })
+        .collect();
+    Ok(DemandForecast { gpu_model: gpu_model.to_string(), history_days, jobs_observed: arrivals.len(), baseline_jobs_per_hour: level, points })
+}
+
+#[tauri::command]
+pub async fn get_demand_forecast(app_handle: AppHandle, gpu_model: String, horizon: Option<u32>) -> Result<DemandForecast, String> {
+    demand_forecast(&app_handle, &gpu_model, horizon.unwrap_or(24)).await
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct UtilizationBucket {
+    pub weekday: u32, // Monday = 0, in the heatmap's time zone
+    pub hour: u32,
+    // Hours with telemetry; for the rest of the range the GUI wasn't running.
+    pub observed_hours: f64,
+    pub rented_hours: f64,
+    pub idle_hours: f64, // Listed for rent, not rented
+    pub unlisted_hours: f64,
+    pub rented_percent: Option<f64>, // Of observed hours; None when nothing was observed
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct UtilizationHeatmap {
+    pub gpu_id: String,
+    pub from: i64,
+    pub to: i64,
+    pub rented_from: String,             // "jobs" | "utilization"
+    // The rental schedule's, so the heatmap lines up with the windows it is used to tune; UTC
+    // without a schedule.
+    pub timezone: String,
+    pub buckets: Vec<UtilizationBucket>, // All 168 hours of the week, Monday 00:00 first
+}
+
+const DEFAULT_HEATMAP_DAYS: i64 = 28;
+
+fn overlap_secs(intervals: &[(i64, i64)], from: i64, to: i64) -> i64 {
+    intervals.iter().map(|&(start, end)| (end.min(to) - start.max(from)).max(0)).sum()
+}
+
+// Rented vs idle time by hour of the week. Listing state comes from telemetry; rented time from
+// stored job history, or, when the provider doesn't keep job history, from hours the GPU was
+// under load (see `BUSY_UTILIZATION_PERCENT`).
+#[tauri::command]
+pub async fn get_utilization_heatmap(app_handle: AppHandle, gpu_id: String, from: Option<i64>, to: Option<i64>) -> Result<UtilizationHeatmap, String> {
+    let storage = app_handle.try_state::<Storage>().ok_or_else(|| "Local storage is unavailable.".to_string())?;
+    let to = to.unwrap_or_else(unix_now);
+    let from = from.unwrap_or(to - DEFAULT_HEATMAP_DAYS * DAY_SECS);
+    if from >= to {
+        return Err("The start of the range must be before its end.".to_string());
+    }
+    let hours: Vec<_> = storage.hourly_usage(from, to, BUSY_UTILIZATION_PERCENT)?.into_iter().filter(|h| h.gpu_id == gpu_id).collect();
+    let settings = app_settings::current(&app_handle);
+    let from_jobs = settings.privacy.store_job_metadata;
+    let tz = match &settings.rental_schedule {
+        Some(schedule) => schedule.tz()?,
+        None => Tz::UTC,
+    };
+    let intervals = if from_jobs { storage.job_intervals(&gpu_id, from, to)? } else { Vec::new() };
+
+    let mut buckets: Vec<UtilizationBucket> = (0..7 * 24)
+        .map(|i| UtilizationBucket {
+            weekday: i / 24,
+            hour: i % 24,
+            observed_hours: 0.0,
+            rented_hours: 0.0,
+            idle_hours: 0.0,
+            unlisted_hours: 0.0,
+            rented_percent: None,
+        })
+        .collect();
+    for usage in &hours {
+        let rented = if from_jobs {
+            (overlap_secs(&intervals, usage.hour_start, usage.hour_start + 3600) as f64 / 3600.0).min(1.0)
+        } else {
+            usage.busy_fraction
+        };
+        // Hours are whole in UTC; in zones with a half-hour offset each lands in the local hour it starts in.
+        let Some(local) = tz.timestamp_opt(usage.hour_start, 0).single() else { continue };
+        let bucket = &mut buckets[local.weekday().num_days_from_monday() as usize * 24 + local.hour() as usize];
+        bucket.observed_hours += 1.0;
+        bucket.rented_hours += rented;
+        bucket.idle_hours += (usage.listed_fraction - rented).max(0.0);
+        bucket.unlisted_hours += 1.0 - usage.listed_fraction.max(rented);
+    }
+    for bucket in &mut buckets {
+        bucket.rented_percent = (bucket.observed_hours > 0.0).then(|| bucket.rented_hours / bucket.observed_hours * 100.0);
+    }
+    Ok(UtilizationHeatmap {
+        gpu_id,
+        from,
+        to,
+        rented_from: if from_jobs { "jobs" } else { "utilization" }.to_string(),
+        timezone: tz.name().to_string(),
+        buckets,
+    })
+}
@@ -0,0 +1,323 @@
+// Platform announcements: maintenance windows, fee changes and required-upgrade notices.
+//
+// Announcements are fetched from the platform and also arrive live over the event feed; while
+// the feed is down they are polled instead, so a maintenance window isn't missed. They are
+// cached per account together with which ones the provider has read. An announcement with
+// the `blocks_rentals` severity (typically a maintenance window) tells the daemon's scheduler
+// not to start new rentals while it is in effect; the block is pushed when the window opens
+// and lifted when it closes. The same block is held outside the provider's own rental schedule
+// (see `schedule`), and while other parts of the app hold rentals through `hold`, e.g. to drain
+// the daemon before a restart (see `daemon_restart`). All of them go through `enforce_block`, so
+// none can lift a block another still needs.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager, State};
+
+use crate::{accounts, api_client, emit_log_entry, event_feed, invoke_daemon_cli_json_output, lockdown, plugins, schedule, shutdown};
+
+const CACHE_FILE_NAME: &str = "announcements.json";
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+const FALLBACK_POLL_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum AnnouncementSeverity {
+    #[default]
+    Info,
+    Warning,
+    BlocksRentals,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Announcement {
+    pub id: String,
+    pub kind: String, // "maintenance" | "fee_change" | "required_upgrade" | ...
+    #[serde(default)]
+    pub severity: AnnouncementSeverity,
+    pub title: String,
+    #[serde(default)]
+    pub body: String,
+    pub published_at: String,
+    // The window the announcement applies to; open-ended on either side when missing.
+    #[serde(default)]
+    pub starts_at: Option<String>,
+    #[serde(default)]
+    pub ends_at: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct AnnouncementView {
+    #[serde(flatten)]
+    pub announcement: Announcement,
+    pub read: bool,
+    pub in_effect: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+struct AnnouncementCache {
+    announcements: Vec<Announcement>,
+    read_ids: HashSet<String>,
+}
+
+pub struct AnnouncementState {
+    cache: Mutex<AnnouncementCache>,
+    path: Mutex<Option<PathBuf>>,
+    // What the daemon was last told, so the block is only pushed when it changes.
+    pushed_block: Mutex<Option<bool>>,
+    holds: Mutex<Vec<String>>, // Reasons, oldest first
+}
+
+fn parse_unix(ts: &str) -> Option<u64> {
+    humantime::parse_rfc3339_weak(ts).ok().and_then(|t| t.duration_since(UNIX_EPOCH).ok()).map(|d| d.as_secs())
+}
+
+fn now_unix() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+impl Announcement {
+    fn in_effect(&self, now: u64) -> bool {
+        let started = self.starts_at.as_deref().and_then(parse_unix).is_none_or(|t| t <= now);
+        let not_ended = self.ends_at.as_deref().and_then(parse_unix).is_none_or(|t| now < t);
+        started && not_ended
+    }
+
+    fn has_ended(&self, now: u64) -> bool {
+        self.ends_at.as_deref().and_then(parse_unix).is_some_and(|t| t <= now)
+    }
+}
+
+fn read_cache(app_handle: &AppHandle) -> (AnnouncementCache, Option<PathBuf>) {
+    let path = accounts::data_dir(app_handle).map(|dir| dir.join(CACHE_FILE_NAME));
+    let cache = path
+        .as_ref()
+        .and_then(|p| fs::read_to_string(p).ok())
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default();
+    (cache, path)
+}
+
+impl AnnouncementState {
+    pub fn load(app_handle: &AppHandle) -> Self {
+        let (cache, path) = read_cache(app_handle);
+        AnnouncementState { cache: Mutex::new(cache), path: Mutex::new(path), pushed_block: Mutex::new(None), holds: Mutex::new(Vec::new()) }
+    }
+
+    // Called after the active account changes.
+    pub fn reload(&self, app_handle: &AppHandle) {
+        let (cache, path) = read_cache(app_handle);
+        *self.cache.lock().unwrap() = cache;
+        *self.path.lock().unwrap() = path;
+    }
+
+    fn update<T>(&self, f: impl FnOnce(&mut AnnouncementCache) -> T) -> T {
+        let mut cache = self.cache.lock().unwrap();
+        let result = f(&mut cache);
+        if let Some(path) = &*self.path.lock().unwrap() {
+            if let Some(dir) = path.parent() {
+                let _ = fs::create_dir_all(dir);
+            }
+            if let Ok(json) = serde_json::to_string_pretty(&*cache) {
+                if let Err(e) = fs::write(path, json) {
+                    eprintln!("Failed to persist announcements: {}", e);
+                }
+            }
+        }
+        result
+    }
+
+    fn views(&self) -> Vec<AnnouncementView> {
+        let now = now_unix();
+        let cache = self.cache.lock().unwrap();
+        let mut views: Vec<AnnouncementView> = cache
+            .announcements
+            .iter()
+            .map(|a| AnnouncementView { read: cache.read_ids.contains(&a.id), in_effect: a.in_effect(now), announcement: a.clone() })
+            .collect();
+        views.sort_by(|a, b| b.announcement.published_at.cmp(&a.announcement.published_at));
+        views
+    }
+
+    // The rental-blocking announcement currently in effect, if any.
+    pub fn active_block(&self) -> Option<Announcement> {
+        let now = now_unix();
+        self.cache
+            .lock()
+            .unwrap()
+            .announcements
+            .iter()
+            .find(|a| a.severity == AnnouncementSeverity::BlocksRentals && a.in_effect(now))
+            .cloned()
+    }
+}
+
+fn store(app_handle: &AppHandle, latest: Vec<Announcement>) -> Vec<Announcement> {
+    let now = now_unix();
+    app_handle.state::<AnnouncementState>().update(|cache| {
+        let new: Vec<Announcement> = latest.iter().filter(|a| !cache.announcements.iter().any(|c| c.id == a.id)).cloned().collect();
+        for announcement in latest {
+            match cache.announcements.iter_mut().find(|c| c.id == announcement.id) {
+                Some(existing) => *existing = announcement,
+                None => cache.announcements.push(announcement),
+            }
+        }
+        // Finished announcements and their read markers are dropped once their window has passed.
+        cache.announcements.retain(|a| !a.has_ended(now));
+        let live: HashSet<String> = cache.announcements.iter().map(|a| a.id.clone()).collect();
+        cache.read_ids.retain(|id| live.contains(id));
+        new
+    })
+}
+
+fn announce(app_handle: &AppHandle, announcement: &Announcement) {
+    let entry_type = if announcement.severity == AnnouncementSeverity::Info { "status" } else { "error" };
+    emit_log_entry(app_handle, entry_type, format!("Platform announcement: {}", announcement.title));
+    plugins::notify(app_handle, "platform_announcement", &json!(announcement), false);
+    if let Err(e) = app_handle.emit_all("platform_announcement", announcement) {
+        eprintln!("Failed to emit platform_announcement event: {}", e);
+    }
+}
+
+// Called by the event feed for each pushed announcement.
+pub async fn receive(app_handle: &AppHandle, payload: &Value) {
+    let announcement: Announcement = match serde_json::from_value(payload.clone()) {
+        Ok(announcement) => announcement,
+        Err(e) => {
+            eprintln!("Malformed announcement on event feed: {}", e);
+            return;
+        }
+    };
+    for announcement in store(app_handle, vec![announcement]) {
+        announce(app_handle, &announcement);
+    }
+    enforce_block(app_handle).await;
+}
+
+async fn push_block(app_handle: &AppHandle, block: Option<&Announcement>, off_schedule: bool, hold: Option<&str>) -> Result<(), String> {
+    let payload = match block {
+        Some(a) => json!({ "blocked": true, "reason": a.title, "announcement_id": a.id, "until": a.ends_at }),
+        None if hold.is_some() => json!({ "blocked": true, "reason": hold }),
+        None if off_schedule => json!({ "blocked": true, "reason": "Outside the rental schedule", "until": schedule::status(app_handle).rentals_next_change }),
+        None => json!({ "blocked": false }),
+    }
+    .to_string();
+    invoke_daemon_cli_json_output::<Value>(app_handle, &["--set-rentals-blocked-json", &payload]).await.map(|_| ())
+}
+
+// Tells the daemon's scheduler to hold or resume new rentals when the block changes.
+pub async fn enforce_block(app_handle: &AppHandle) {
+    if let Err(e) = apply_block(app_handle).await {
+        eprintln!("Failed to push rental block to the daemon: {}", e);
+    }
+}
+
+async fn apply_block(app_handle: &AppHandle) -> Result<(), String> {
+    // Lockdown holds rentals itself and hands back to announcements when lifted.
+    if lockdown::is_active(app_handle) {
+        return Ok(());
+    }
+    let state = app_handle.state::<AnnouncementState>();
+    let block = state.active_block();
+    let off_schedule = !schedule::rentals_open(app_handle);
+    let hold = state.holds.lock().unwrap().first().cloned();
+    let blocked = block.is_some() || off_schedule || hold.is_some();
+    if *state.pushed_block.lock().unwrap() == Some(blocked) {
+        return Ok(());
+    }
+    push_block(app_handle, block.as_ref(), off_schedule, hold.as_deref()).await?;
+    *state.pushed_block.lock().unwrap() = Some(blocked);
+    let message = match (&block, &hold) {
+        (Some(a), _) => format!("New rentals are paused for platform maintenance: {}", a.title),
+        (None, Some(reason)) => format!("New rentals are held: {}", reason),
+        (None, None) if off_schedule => "New rentals are paused outside the rental schedule.".to_string(),
+        (None, None) => "New rentals are accepted again.".to_string(),
+    };
+    emit_log_entry(app_handle, "status", message);
+    if let Err(e) = app_handle.emit_all("rentals_blocked_changed", json!({ "blocked": blocked, "announcement": block, "off_schedule": off_schedule, "held_for": hold })) {
+        eprintln!("Failed to emit rentals_blocked_changed event: {}", e);
+    }
+    Ok(())
+}
+
+// Holds new rentals for `reason` until it is released, and fails if the daemon can't be told.
+pub async fn hold(app_handle: &AppHandle, reason: &str) -> Result<(), String> {
+    let state = app_handle.state::<AnnouncementState>();
+    state.holds.lock().unwrap().push(reason.to_string());
+    apply_block(app_handle).await
+}
+
+// Ends a hold; rentals resume unless something else still blocks them.
+pub async fn release(app_handle: &AppHandle, reason: &str) {
+    {
+        let state = app_handle.state::<AnnouncementState>();
+        let mut holds = state.holds.lock().unwrap();
+        if let Some(index) = holds.iter().position(|r| r == reason) {
+            holds.remove(index);
+        }
+    }
+    enforce_block(app_handle).await;
+}
+
+// The daemon forgets the block when it restarts, so it is re-sent on the next check.
+pub fn sync_on_start(app_handle: &AppHandle) {
+    *app_handle.state::<AnnouncementState>().pushed_block.lock().unwrap() = None;
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move { enforce_block(&app_handle).await });
+}
+
+// Fetches the current announcements and announces the ones not seen before.
+async fn refresh(app_handle: &AppHandle) -> Result<(), String> {
+    let latest = api_client::get_json::<Vec<Announcement>>(app_handle, "/api/v1/providers/me/announcements").await?;
+    for announcement in store(app_handle, latest) {
+        announce(app_handle, &announcement);
+    }
+    enforce_block(app_handle).await;
+    Ok(())
+}
+
+pub fn spawn_block_monitor(app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut last_poll: Option<Instant> = None;
+        loop {
+            if !event_feed::is_connected() && last_poll.is_none_or(|at| at.elapsed() >= FALLBACK_POLL_INTERVAL) {
+                last_poll = Some(Instant::now());
+                if let Err(e) = refresh(&app_handle).await {
+                    eprintln!("Announcement poll failed: {}", e);
+                }
+            }
+            enforce_block(&app_handle).await;
+            if !shutdown::sleep(CHECK_INTERVAL).await {
+                break;
+            }
+        }
+    });
+}
+
+// Falls back to the cached announcements when the platform can't be reached.
+#[tauri::command]
+pub async fn get_platform_announcements(app_handle: AppHandle, state: State<'_, AnnouncementState>) -> Result<Vec<AnnouncementView>, String> {
+    match refresh(&app_handle).await {
+        Ok(()) => {}
+        Err(e) if state.cache.lock().unwrap().announcements.is_empty() => return Err(e),
+        Err(e) => eprintln!("Showing cached announcements: {}", e),
+    }
+    Ok(state.views())
+}
+
+#[tauri::command]
+pub async fn mark_announcement_read(state: State<'_, AnnouncementState>, announcement_id: String, read: bool) -> Result<(), String> {
+    state.update(|cache| {
+        if read {
+            cache.read_ids.insert(announcement_id);
+        } else {
+            cache.read_ids.remove(&announcement_id);
+        }
+    });
+    Ok(())
+}
@@ -0,0 +1,302 @@
+// Client for the Dante platform API.
+//
+// Every platform call goes through here so they share the same behaviour:
+// - the provider's API token (in the OS keychain, scoped to the active account) is attached as a
+//   bearer token for authenticated endpoints;
+// - transient failures are retried with exponential backoff, honouring `Retry-After`; writes are
+//   only retried when the server can't have processed them (connection refused, 429, 503);
+// - identical GETs in flight at the same time share one request;
+// - after repeated failures the circuit opens and calls fail fast until a cooldown passes;
+// - per-endpoint metrics are kept for diagnostics.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager, State};
+use tokio::sync::OnceCell;
+
+use crate::http_client::{self, HttpError};
+use crate::{accounts, app_settings, emit_log_entry, fault_injection, secrets};
+
+pub const TOKEN_KEY: &str = "platform.token";
+
+const MAX_ATTEMPTS: u32 = 3;
+const BASE_BACKOFF: Duration = Duration::from_millis(500);
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
+const BREAKER_THRESHOLD: u32 = 5;
+const BREAKER_COOLDOWN: Duration = Duration::from_secs(30);
+
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct EndpointMetrics {
+    pub requests: u64,
+    pub failures: u64,
+    pub retries: u64,
+    pub deduplicated: u64,
+    pub rejected_by_breaker: u64,
+    pub average_latency_ms: u64,
+    pub last_status: Option<u16>,
+    pub last_error: Option<String>,
+    #[serde(skip)]
+    total_latency_ms: u64,
+}
+
+#[derive(Default)]
+struct Breaker {
+    consecutive_failures: u32,
+    open_until: Option<Instant>,
+}
+
+type Inflight = Arc<OnceCell<Result<String, String>>>;
+
+pub struct ApiClientState {
+    breaker: Mutex<Breaker>,
+    inflight: Mutex<HashMap<String, Inflight>>,
+    metrics: Mutex<HashMap<String, EndpointMetrics>>,
+}
+
+impl ApiClientState {
+    pub fn new() -> Self {
+        ApiClientState { breaker: Mutex::new(Breaker::default()), inflight: Mutex::new(HashMap::new()), metrics: Mutex::new(HashMap::new()) }
+    }
+
+    fn metric(&self, key: &str, f: impl FnOnce(&mut EndpointMetrics)) {
+        f(self.metrics.lock().unwrap().entry(key.to_string()).or_default());
+    }
+
+    fn check_breaker(&self) -> Result<(), String> {
+        match self.breaker.lock().unwrap().open_until {
+            Some(until) if until > Instant::now() => Err(format!(
+                "The platform API is unavailable after repeated failures; retrying in {} s.",
+                (until - Instant::now()).as_secs() + 1
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    // Returns true when this failure opened the circuit.
+    fn record_failure(&self) -> bool {
+        let mut breaker = self.breaker.lock().unwrap();
+        breaker.consecutive_failures += 1;
+        let was_open = breaker.open_until.is_some_and(|until| until > Instant::now());
+        if breaker.consecutive_failures >= BREAKER_THRESHOLD {
+            breaker.open_until = Some(Instant::now() + BREAKER_COOLDOWN);
+            return !was_open;
+        }
+        false
+    }
+
+    fn record_success(&self) {
+        *self.breaker.lock().unwrap() = Breaker::default();
+    }
+}
+
+pub fn endpoint(app_handle: &AppHandle, path: &str) -> String {
+    let platform_url = app_settings::current(app_handle).platform_url;
+    format!("{}{}", platform_url.trim_end_matches('/'), path)
+}
+
+// Percent-encodes a query or path component.
+pub fn encode_component(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+pub fn token(app_handle: &AppHandle) -> Result<String, String> {
+    secrets::get(&accounts::secret_key(app_handle, TOKEN_KEY))?.ok_or_else(|| "Sign in to the platform first (no API token stored).".to_string())
+}
+
+// Metrics are grouped by method and path without the query string.
+fn metrics_key(method: &reqwest::Method, path: &str) -> String {
+    format!("{} {}", method, path.split('?').next().unwrap_or(path))
+}
+
+enum Attempt {
+    Done(String),
+    Retry { after: Option<Duration>, error: String, safe_for_writes: bool },
+    Fail(String),
+}
+
+fn retry_after(response: &reqwest::Response) -> Option<Duration> {
+    let value = response.headers().get(reqwest::header::RETRY_AFTER)?.to_str().ok()?;
+    let delay = match value.trim().parse::<u64>() {
+        Ok(secs) => Duration::from_secs(secs),
+        // The other allowed form is an HTTP-date ("Wed, 21 Oct 2015 07:28:00 GMT").
+        Err(_) => httpdate::parse_http_date(value.trim()).ok()?.duration_since(SystemTime::now()).unwrap_or_default(),
+    };
+    Some(delay.min(MAX_RETRY_AFTER))
+}
+
+fn backoff(attempt: u32) -> Duration {
+    let jitter_ms = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.subsec_nanos() % 250).unwrap_or(0);
+    BASE_BACKOFF * 2u32.pow(attempt) + Duration::from_millis(u64::from(jitter_ms))
+}
+
+async fn attempt(app_handle: &AppHandle, method: &reqwest::Method, path: &str, auth: bool, body: Option<&serde_json::Value>) -> (Attempt, Option<u16>) {
+    let client = match http_client::client() {
+        Ok(client) => client,
+        Err(e) => return (Attempt::Fail(e), None),
+    };
+    let mut request = client.request(method.clone(), endpoint(app_handle, path));
+    if auth {
+        match token(app_handle) {
+            Ok(token) => request = request.bearer_auth(token),
+            Err(e) => return (Attempt::Fail(e), None),
+        }
+        if fault_injection::token_expired() {
+            let code = reqwest::StatusCode::UNAUTHORIZED.as_u16();
+            return (Attempt::Fail(HttpError::Status { code }.to_string()), Some(code));
+        }
+    }
+    if let Some(body) = body {
+        request = request.json(body);
+    }
+    match request.send().await {
+        Err(e) => match http_client::classify(&e) {
+            error @ HttpError::Connect { .. } => (Attempt::Retry { after: None, error: error.to_string(), safe_for_writes: true }, None),
+            error @ HttpError::Timeout => (Attempt::Retry { after: None, error: error.to_string(), safe_for_writes: false }, None),
+            error => (Attempt::Fail(error.to_string()), None),
+        },
+        Ok(response) => {
+            let status = response.status();
+            let code = Some(status.as_u16());
+            if status.is_success() {
+                match response.text().await {
+                    Ok(text) => (Attempt::Done(text), code),
+                    Err(e) => (Attempt::Fail(format!("Failed to read response: {}", e)), code),
+                }
+            } else if status == reqwest::StatusCode::TOO_MANY_REQUESTS || status.is_server_error() {
+                let safe_for_writes = status == reqwest::StatusCode::TOO_MANY_REQUESTS || status == reqwest::StatusCode::SERVICE_UNAVAILABLE;
+                (Attempt::Retry { after: retry_after(&response), error: HttpError::Status { code: status.as_u16() }.to_string(), safe_for_writes }, code)
+            } else {
+                (Attempt::Fail(HttpError::Status { code: status.as_u16() }.to_string()), code)
+            }
+        }
+    }
+}
+
+async fn execute(app_handle: &AppHandle, method: reqwest::Method, path: &str, auth: bool, body: Option<serde_json::Value>) -> Result<String, String> {
+    let state = app_handle.state::<ApiClientState>();
+    let key = metrics_key(&method, path);
+    if let Err(e) = state.check_breaker() {
+        state.metric(&key, |m| m.rejected_by_breaker += 1);
+        return Err(e);
+    }
+
+    let started = Instant::now();
+    let mut attempts = 0;
+    let (result, status) = loop {
+        let (outcome, status) = attempt(app_handle, &method, path, auth, body.as_ref()).await;
+        attempts += 1;
+        match outcome {
+            Attempt::Done(text) => {
+                state.record_success();
+                break (Ok(text), status);
+            }
+            Attempt::Fail(error) => {
+                // Client errors mean the API is up; only transport and server failures trip the breaker.
+                if status.is_some() {
+                    state.record_success();
+                }
+                break (Err(error), status);
+            }
+            Attempt::Retry { after, error, safe_for_writes } => {
+                if state.record_failure() {
+                    emit_log_entry(app_handle, "error", format!("Platform API is failing ({}); pausing requests for {} s.", error, BREAKER_COOLDOWN.as_secs()));
+                }
+                let retryable = method == reqwest::Method::GET || safe_for_writes;
+                if !retryable || attempts >= MAX_ATTEMPTS || state.check_breaker().is_err() {
+                    break (Err(error), status);
+                }
+                tokio::time::sleep(after.unwrap_or_else(|| backoff(attempts - 1))).await;
+            }
+        }
+    };
+
+    let elapsed_ms = started.elapsed().as_millis() as u64;
+    state.metric(&key, |m| {
+        m.requests += 1;
+        m.retries += u64::from(attempts - 1);
+        m.total_latency_ms += elapsed_ms;
+        m.average_latency_ms = m.total_latency_ms / m.requests;
+        m.last_status = status;
+        if let Err(e) = &result {
+            m.failures += 1;
+            m.last_error = Some(e.clone());
+        }
+    });
+    result.map_err(|e| format!("Platform request to {} failed: {}", path, e))
+}
+
+// GETs for the same path and credentials share a single request while one is in flight.
+async fn get_text(app_handle: &AppHandle, path: &str, auth: bool) -> Result<String, String> {
+    let state = app_handle.state::<ApiClientState>();
+    let dedup_key = format!("{}|{}", auth, path);
+    let (cell, joined) = {
+        let mut inflight = state.inflight.lock().unwrap();
+        match inflight.get(&dedup_key) {
+            Some(cell) => (cell.clone(), true),
+            None => {
+                let cell: Inflight = Arc::new(OnceCell::new());
+                inflight.insert(dedup_key.clone(), cell.clone());
+                (cell, false)
+            }
+        }
+    };
+    if joined {
+        state.metric(&metrics_key(&reqwest::Method::GET, path), |m| m.deduplicated += 1);
+    }
+    let result = cell.get_or_init(|| execute(app_handle, reqwest::Method::GET, path, auth, None)).await.clone();
+    let mut inflight = state.inflight.lock().unwrap();
+    if inflight.get(&dedup_key).is_some_and(|current| Arc::ptr_eq(current, &cell)) {
+        inflight.remove(&dedup_key);
+    }
+    result
+}
+
+fn parse<T: DeserializeOwned>(path: &str, text: &str) -> Result<T, String> {
+    serde_json::from_str(text).map_err(|e| format!("Invalid platform response from {}: {}", path, e))
+}
+
+pub async fn get_json<T: DeserializeOwned>(app_handle: &AppHandle, path: &str) -> Result<T, String> {
+    parse(path, &get_text(app_handle, path, true).await?)
+}
+
+// For endpoints that don't need a signed-in provider (market data, fee schedule).
+pub async fn get_public_json<T: DeserializeOwned>(app_handle: &AppHandle, path: &str) -> Result<T, String> {
+    parse(path, &get_public_text(app_handle, path).await?)
+}
+
+pub async fn get_public_text(app_handle: &AppHandle, path: &str) -> Result<String, String> {
+    get_text(app_handle, path, false).await
+}
+
+pub async fn send_json<B: Serialize, T: DeserializeOwned>(
+    app_handle: &AppHandle,
+    method: reqwest::Method,
+    path: &str,
+    body: &B,
+) -> Result<T, String> {
+    let body = serde_json::to_value(body).map_err(|e| format!("Failed to serialize request body: {}", e))?;
+    parse(path, &execute(app_handle, method, path, true, Some(body)).await?)
+}
+
+#[tauri::command]
+pub async fn set_platform_token(app_handle: AppHandle, token: Option<String>) -> Result<(), String> {
+    let key = accounts::secret_key(&app_handle, TOKEN_KEY);
+    match token.filter(|t| !t.trim().is_empty()) {
+        Some(token) => secrets::set(&key, token.trim()),
+        None => secrets::delete(&key),
+    }
+}
+
+#[tauri::command]
+pub async fn get_api_metrics(state: State<'_, ApiClientState>) -> Result<HashMap<String, EndpointMetrics>, String> {
+    Ok(state.metrics.lock().unwrap().clone())
+}
@@ -0,0 +1,282 @@
+// GUI-side settings, persisted as JSON in the app data directory.
+//
+// These govern the behaviour of the GUI process itself, as opposed to `ProviderSettings`
+// which belong to the daemon and are read/written through its CLI.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+
+use crate::approvals::RemoteApprovalConfig;
+use crate::checkpoints::CheckpointPolicy;
+use crate::daemon_resources::DaemonResourceLimits;
+use crate::daemon_restart::RestartSchedule;
+use crate::data_cap::DataCapConfig;
+use crate::emit_log_entry;
+use crate::failover::FailoverMode;
+use crate::gpu_processes::ForeignProcessPolicy;
+use crate::hooks::HookConfig;
+use crate::http_client::{self, ProxyConfig, TlsConfig};
+use crate::image_cache::ImageCachePolicy;
+use crate::image_registry::{self, RegistryConfig};
+use crate::inventory::OperatingCosts;
+use crate::job_policy::JobPolicy;
+use crate::lockdown::LockdownConfig;
+use crate::locale;
+use crate::money::RoundingPolicy;
+use crate::prefetch::PrefetchPolicy;
+use crate::redaction::{self, RedactionConfig};
+use crate::schedule::{self, ZonedSchedule};
+use crate::settings_history;
+use crate::shortcuts::{self, ShortcutBindings};
+use crate::pricing::SurgeRule;
+use crate::regions::RegionPreference;
+use crate::shutdown::ShutdownPolicy;
+use crate::sync::{self, SyncConfig};
+use crate::transport::TransportPreferences;
+use crate::wsl::WslDaemonConfig;
+use crate::zombie_jobs::ZombieJobPolicy;
+
+const SETTINGS_FILE_NAME: &str = "app_settings.json";
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct PrivacySettings {
+    pub store_telemetry_history: bool,
+    pub telemetry_retention_days: u32,
+    pub store_job_metadata: bool,
+    pub job_retention_days: u32,
+    pub store_renter_ids: bool,
+}
+
+impl Default for PrivacySettings {
+    fn default() -> Self {
+        PrivacySettings {
+            store_telemetry_history: true,
+            telemetry_retention_days: 30,
+            store_job_metadata: true,
+            job_retention_days: 365,
+            store_renter_ids: false,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct AppSettings {
+    // Lightweight mode for rigs running heavy training jobs next to the GUI: no telemetry
+    // history, slower polling, a small log buffer and no non-essential background work.
+    pub lightweight_mode: bool,
+    // User scripts run on lifecycle events (see `hooks`).
+    pub hooks: Vec<HookConfig>,
+    // Plugin IDs the user has approved (see `plugins`).
+    pub enabled_plugins: Vec<String>,
+    // Where to fetch updates to the known-issues database from, if anywhere.
+    pub known_issues_url: Option<String>,
+    // Opt-in crash/command-failure reporting to a self-hosted endpoint (see `crash_reports`).
+    pub crash_reporting_enabled: bool,
+    pub crash_report_endpoint: Option<String>,
+    // What the local store keeps and for how long (see `storage`).
+    pub privacy: PrivacySettings,
+    // Warn when non-Dante processes hold VRAM a queued job will need (see `gpu_processes`).
+    pub foreign_process_policy: ForeignProcessPolicy,
+    // Which job categories/frameworks/durations are accepted (see `job_policy`).
+    pub job_policy: JobPolicy,
+    // Platform API base URL, used for checks against platform services such as clock skew.
+    pub platform_url: String,
+    // Encrypted settings sync across machines (see `sync`). Credentials live in the keychain.
+    pub sync: SyncConfig,
+    // Approving manual-accept rental requests from a phone (see `approvals`).
+    pub remote_approval: RemoteApprovalConfig,
+    // What to do when a GPU faults mid-job and another local GPU could take over (see `failover`).
+    pub failover: FailoverMode,
+    // Disk budget and retention for job checkpoints (see `checkpoints`).
+    pub checkpoints: CheckpointPolicy,
+    // Monthly data cap on metered connections (see `data_cap`).
+    pub data_cap: DataCapConfig,
+    // Pinned and prioritized matchmaking regions (see `regions`).
+    pub region_preference: RegionPreference,
+    // Data-channel transports offered to renters (see `transport`).
+    pub transport_preferences: TransportPreferences,
+    // Proxy for the GUI's own HTTP calls; the password lives in the keychain (see `http_client`).
+    pub proxy: ProxyConfig,
+    // Extra CA bundle and certificate pins for platform/updater endpoints (see `http_client`).
+    pub tls: TlsConfig,
+    // Local feature flag values that win over the platform's (see `feature_flags`).
+    pub feature_flag_overrides: HashMap<String, bool>,
+    // Raising rates while forecast demand is high (see `pricing`).
+    pub surge_pricing: SurgeRule,
+    // Unlist a GPU whose fan appears to have failed (see `gpu_health`).
+    pub pause_on_fan_failure: bool,
+    // Running the daemon inside a WSL2 distribution on Windows (see `wsl`).
+    pub wsl_daemon: WslDaemonConfig,
+    // What happens to the daemon on quit, and how long quitting may take (see `shutdown`).
+    pub shutdown: ShutdownPolicy,
+    // Whether suspected compromise engages lockdown without asking (see `lockdown`).
+    pub lockdown: LockdownConfig,
+    // Rules for scrubbing secrets from log lines (see `redaction`).
+    pub log_redaction: RedactionConfig,
+    // When new rentals are accepted; always when unset (see `schedule`).
+    pub rental_schedule: Option<ZonedSchedule>,
+    // When notification plugins only get urgent events (see `schedule`).
+    pub quiet_hours: Option<ZonedSchedule>,
+    // BCP 47 tag for number/date formatting in exports, e.g. "de-DE"; the OS locale when unset.
+    pub locale: Option<String>,
+    // System-wide keyboard shortcuts (see `shortcuts`).
+    pub shortcuts: ShortcutBindings,
+    // Pre-pulling popular job images while idle (see `prefetch`).
+    pub prefetch: PrefetchPolicy,
+    // Storage budget and LRU eviction for job images (see `image_cache`).
+    pub image_cache: ImageCachePolicy,
+    // Registry mirror and pinned infrastructure image digests (see `image_registry`).
+    pub image_registry: RegistryConfig,
+    // Decimal places and rounding of DGPU amounts in quotes and exports (see `money`).
+    pub amount_rounding: RoundingPolicy,
+    // Electricity price and amortization period behind break-even rates (see `pricing`).
+    pub operating_costs: OperatingCosts,
+    // Drain-then-restart of the daemon at a set time; off when unset (see `daemon_restart`).
+    pub scheduled_restart: Option<RestartSchedule>,
+    // Memory and CPU limits on the daemon process (see `daemon_resources`).
+    pub daemon_resources: DaemonResourceLimits,
+    // When a running job counts as stuck (see `zombie_jobs`).
+    pub zombie_jobs: ZombieJobPolicy,
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        AppSettings {
+            lightweight_mode: false,
+            hooks: Vec::new(),
+            enabled_plugins: Vec::new(),
+            known_issues_url: None,
+            crash_reporting_enabled: false,
+            crash_report_endpoint: None,
+            privacy: PrivacySettings::default(),
+            foreign_process_policy: ForeignProcessPolicy::default(),
+            job_policy: JobPolicy::default(),
+            platform_url: "http://localhost:8080".to_string(),
+            sync: SyncConfig::default(),
+            remote_approval: RemoteApprovalConfig::default(),
+            failover: FailoverMode::default(),
+            checkpoints: CheckpointPolicy::default(),
+            data_cap: DataCapConfig::default(),
+            region_preference: RegionPreference::default(),
+            transport_preferences: TransportPreferences::default(),
+            proxy: ProxyConfig::default(),
+            tls: TlsConfig::default(),
+            feature_flag_overrides: HashMap::new(),
+            surge_pricing: SurgeRule::default(),
+            pause_on_fan_failure: true,
+            wsl_daemon: WslDaemonConfig::default(),
+            shutdown: ShutdownPolicy::default(),
+            lockdown: LockdownConfig::default(),
+            log_redaction: RedactionConfig::default(),
+            rental_schedule: None,
+            quiet_hours: None,
+            locale: None,
+            shortcuts: ShortcutBindings::default(),
+            prefetch: PrefetchPolicy::default(),
+            image_cache: ImageCachePolicy::default(),
+            image_registry: RegistryConfig::default(),
+            amount_rounding: RoundingPolicy::default(),
+            operating_costs: OperatingCosts::default(),
+            scheduled_restart: None,
+            daemon_resources: DaemonResourceLimits::default(),
+            zombie_jobs: ZombieJobPolicy::default(),
+        }
+    }
+}
+
+pub struct AppSettingsState {
+    pub settings: Mutex<AppSettings>,
+    path: Option<PathBuf>,
+}
+
+impl AppSettingsState {
+    pub fn load(app_handle: &AppHandle) -> Self {
+        let path = app_handle.path_resolver().app_data_dir().map(|dir| dir.join(SETTINGS_FILE_NAME));
+        let settings = path
+            .as_ref()
+            .and_then(|p| fs::read_to_string(p).ok())
+            .and_then(|contents| match serde_json::from_str(&contents) {
+                Ok(settings) => Some(settings),
+                Err(e) => {
+                    emit_log_entry(app_handle, "error", format!("Failed to parse {}: {}. Using defaults.", SETTINGS_FILE_NAME, e));
+                    None
+                }
+            })
+            .unwrap_or_default();
+
+        AppSettingsState {
+            settings: Mutex::new(settings),
+            path,
+        }
+    }
+
+    pub fn get(&self) -> AppSettings {
+        self.settings.lock().unwrap().clone()
+    }
+
+    fn save(&self, settings: &AppSettings) -> Result<(), String> {
+        let path = self.path.as_ref().ok_or_else(|| "App data directory is unavailable.".to_string())?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        }
+        let json = serde_json::to_string_pretty(settings)
+            .map_err(|e| format!("Failed to serialize app settings: {}", e))?;
+        fs::write(path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+}
+
+// Convenience accessor for background tasks; falls back to defaults before settings are loaded.
+pub fn current<R: tauri::Runtime>(manager: &impl Manager<R>) -> AppSettings {
+    manager
+        .try_state::<AppSettingsState>()
+        .map(|state| state.get())
+        .unwrap_or_default()
+}
+
+#[tauri::command]
+pub async fn get_app_settings(state: State<'_, AppSettingsState>) -> Result<AppSettings, String> {
+    Ok(state.get())
+}
+
+#[tauri::command]
+pub async fn update_app_settings(app_handle: AppHandle, state: State<'_, AppSettingsState>, settings: AppSettings) -> Result<AppSettings, String> {
+    redaction::validate(&settings.log_redaction)?;
+    schedule::validate(&settings)?;
+    locale::validate(settings.locale.as_deref())?;
+    shortcuts::validate(&settings.shortcuts)?;
+    image_registry::validate(&settings.image_registry)?;
+    settings.amount_rounding.validate()?;
+    settings.operating_costs.validate()?;
+    if let Some(restart) = &settings.scheduled_restart {
+        restart.validate()?;
+    }
+    settings.daemon_resources.validate()?;
+    settings.zombie_jobs.validate()?;
+    let shortcuts_changed = state.get().shortcuts != settings.shortcuts;
+    state.save(&settings)?;
+    *state.settings.lock().unwrap() = settings.clone();
+    if shortcuts_changed {
+        shortcuts::apply(&app_handle);
+    }
+    sync::note_local_change(&app_handle);
+    settings_history::note_change(&app_handle);
+    // Resolving a PAC route fetches the PAC file, so a slow or unreachable URL mustn't hold up
+    // the save; a route that can't be resolved is reported once it fails.
+    let handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = http_client::apply_settings(&handle).await {
+            emit_log_entry(&handle, "error", format!("Network settings not applied: {}", e));
+        }
+    });
+    emit_log_entry(&app_handle, "status", format!("App settings updated: {:?}", settings));
+    if let Err(e) = app_handle.emit_all("app_settings_changed", settings.clone()) {
+        eprintln!("Failed to emit app_settings_changed event: {}", e);
+    }
+    Ok(settings)
+}
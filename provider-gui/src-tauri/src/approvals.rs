@@ -0,0 +1,221 @@
+// Remote approval of rental requests from a phone, for providers running the daemon in
+// manual-accept mode.
+//
+// The GUI registers itself with the platform's push service, publishes each pending request
+// so the provider's phone gets a notification, and polls for decisions made there. A request
+// can be answered from either side; the first decision is applied and any later, conflicting
+// one is reported instead of applied, and the platform is told which decision won so the phone
+// can show the final outcome.
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, State};
+
+use crate::app_settings::{self, AppSettingsState};
+use crate::rejections::{self, RejectionReason};
+use crate::units::DgpuPerHour;
+use crate::{api_client, emit_log_entry, event_feed, get_timestamp, gpu_ids, invoke_daemon_cli_json_output, shutdown, sync};
+
+const RELAY_INTERVAL: Duration = Duration::from_secs(10);
+// Decisions are kept this long to settle late, conflicting answers from the other side.
+const DECISION_RETENTION: Duration = Duration::from_secs(24 * 60 * 60);
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct RemoteApprovalConfig {
+    pub enabled: bool,
+    // Assigned by the push service when this installation registers.
+    pub device_id: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RentalRequest {
+    pub request_id: String,
+    pub renter_display_name: Option<String>,
+    pub gpu_id: String,
+    pub hourly_rate_dgpu: DgpuPerHour,
+    pub requested_hours: Option<f32>,
+    pub expires_at: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DecisionSource {
+    Desktop,
+    Mobile,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ApprovalDecision {
+    pub request_id: String,
+    pub approve: bool,
+    pub source: DecisionSource,
+    pub decided_at: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct DecisionPage {
+    decisions: Vec<ApprovalDecision>,
+    cursor: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct DeviceRegistration {
+    device_id: String,
+}
+
+pub struct ApprovalState {
+    published: Mutex<HashSet<String>>,
+    decisions: Mutex<HashMap<String, (ApprovalDecision, Instant)>>,
+    cursor: Mutex<Option<String>>,
+}
+
+impl ApprovalState {
+    pub fn new() -> Self {
+        ApprovalState { published: Mutex::new(HashSet::new()), decisions: Mutex::new(HashMap::new()), cursor: Mutex::new(None) }
+    }
+}
+
+async fn pending_requests(app_handle: &AppHandle) -> Result<Vec<RentalRequest>, String> {
+    let mut requests: Vec<RentalRequest> = invoke_daemon_cli_json_output(app_handle, &["--get-pending-requests-json"]).await?;
+    for request in &mut requests {
+        request.gpu_id = gpu_ids::stable_id(&request.gpu_id);
+    }
+    Ok(requests)
+}
+
+// Applies `decision` unless the request was already decided. Returns the decision in effect.
+pub async fn apply(app_handle: &AppHandle, decision: ApprovalDecision) -> Result<ApprovalDecision, String> {
+    let state = app_handle.state::<ApprovalState>();
+    let existing = state.decisions.lock().unwrap().get(&decision.request_id).map(|(d, _)| d.clone());
+    if let Some(existing) = existing {
+        if existing.approve != decision.approve {
+            emit_log_entry(
+                app_handle,
+                "status",
+                format!(
+                    "Ignoring {:?} decision for request {}: it was already {} from {:?}.",
+                    decision.source,
+                    decision.request_id,
+                    if existing.approve { "approved" } else { "rejected" },
+                    existing.source
+                ),
+            );
+            if let Err(e) = app_handle.emit_all("approval_conflict", json!({ "applied": existing, "ignored": decision })) {
+                eprintln!("Failed to emit approval_conflict event: {}", e);
+            }
+        }
+        return Ok(existing);
+    }
+
+    let flag = if decision.approve { "--approve-request" } else { "--reject-request" };
+    invoke_daemon_cli_json_output::<serde_json::Value>(app_handle, &[flag, "--request-id", &decision.request_id]).await?;
+    state.decisions.lock().unwrap().insert(decision.request_id.clone(), (decision.clone(), Instant::now()));
+    if !decision.approve {
+        let detail = format!("Rejected from {:?}", decision.source);
+        rejections::record(app_handle, &decision.request_id, "", RejectionReason::Manual, &detail, "provider");
+    }
+    emit_log_entry(
+        app_handle,
+        "status",
+        format!("Rental request {} {} from {:?}.", decision.request_id, if decision.approve { "approved" } else { "rejected" }, decision.source),
+    );
+    if let Err(e) = app_handle.emit_all("rental_request_decided", &decision) {
+        eprintln!("Failed to emit rental_request_decided event: {}", e);
+    }
+
+    if app_settings::current(app_handle).remote_approval.enabled {
+        let path = format!("/api/v1/push/approval-requests/{}/resolution", decision.request_id);
+        if let Err(e) = api_client::send_json::<_, serde_json::Value>(app_handle, reqwest::Method::POST, &path, &decision).await {
+            eprintln!("Failed to report approval resolution: {}", e);
+        }
+    }
+    Ok(decision)
+}
+
+async fn relay_once(app_handle: &AppHandle, device_id: &str) -> Result<(), String> {
+    let state = app_handle.state::<ApprovalState>();
+    let pending = pending_requests(app_handle).await?;
+    for request in &pending {
+        if state.published.lock().unwrap().contains(&request.request_id) {
+            continue;
+        }
+        let body = json!({ "device_id": device_id, "request": request });
+        api_client::send_json::<_, serde_json::Value>(app_handle, reqwest::Method::POST, "/api/v1/push/approval-requests", &body).await?;
+        state.published.lock().unwrap().insert(request.request_id.clone());
+    }
+
+    // Decisions arrive over the event feed while it is connected.
+    if !event_feed::is_connected() {
+        poll_decisions(app_handle, device_id).await?;
+    }
+
+    // Requests the daemon no longer lists have expired or been handled; forget them.
+    let live: HashSet<&str> = pending.iter().map(|r| r.request_id.as_str()).collect();
+    state.published.lock().unwrap().retain(|id| live.contains(id.as_str()));
+    state.decisions.lock().unwrap().retain(|id, (_, decided)| live.contains(id.as_str()) || decided.elapsed() < DECISION_RETENTION);
+    Ok(())
+}
+
+async fn poll_decisions(app_handle: &AppHandle, device_id: &str) -> Result<(), String> {
+    let state = app_handle.state::<ApprovalState>();
+    let cursor = state.cursor.lock().unwrap().clone();
+    let device_id = api_client::encode_component(device_id);
+    let path = match &cursor {
+        Some(cursor) => format!("/api/v1/push/devices/{}/decisions?cursor={}", device_id, api_client::encode_component(cursor)),
+        None => format!("/api/v1/push/devices/{}/decisions", device_id),
+    };
+    let page: DecisionPage = api_client::get_json(app_handle, &path).await?;
+    // The cursor only moves once the whole page is applied; a failure refetches it next time,
+    // and decisions already applied are recognised as such.
+    for decision in page.decisions {
+        if let Err(e) = apply(app_handle, decision).await {
+            emit_log_entry(app_handle, "error", format!("Failed to apply a decision from the phone: {}", e));
+            return Ok(());
+        }
+    }
+    if page.cursor.is_some() {
+        *state.cursor.lock().unwrap() = page.cursor;
+    }
+    Ok(())
+}
+
+pub fn spawn_approval_relay(app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            if !shutdown::sleep(RELAY_INTERVAL).await {
+                break;
+            }
+            let config = app_settings::current(&app_handle).remote_approval;
+            let Some(device_id) = config.device_id.filter(|_| config.enabled) else { continue };
+            if let Err(e) = relay_once(&app_handle, &device_id).await {
+                eprintln!("Remote approval relay failed: {}", e);
+            }
+        }
+    });
+}
+
+#[tauri::command]
+pub async fn register_push_device(app_handle: AppHandle, state: State<'_, AppSettingsState>) -> Result<RemoteApprovalConfig, String> {
+    let body = json!({ "kind": "desktop", "name": sync::device_name(), "registered_at": get_timestamp() });
+    let registration: DeviceRegistration = api_client::send_json(&app_handle, reqwest::Method::POST, "/api/v1/push/devices", &body).await?;
+    let mut settings = state.get();
+    settings.remote_approval = RemoteApprovalConfig { enabled: true, device_id: Some(registration.device_id) };
+    let config = settings.remote_approval.clone();
+    app_settings::update_app_settings(app_handle, state, settings).await?;
+    Ok(config)
+}
+
+#[tauri::command]
+pub async fn get_pending_rental_requests(app_handle: AppHandle) -> Result<Vec<RentalRequest>, String> {
+    pending_requests(&app_handle).await
+}
+
+#[tauri::command]
+pub async fn decide_rental_request(app_handle: AppHandle, request_id: String, approve: bool) -> Result<ApprovalDecision, String> {
+    let decision = ApprovalDecision { request_id, approve, source: DecisionSource::Desktop, decided_at: get_timestamp() };
+    apply(&app_handle, decision).await
+}
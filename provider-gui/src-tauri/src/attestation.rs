@@ -0,0 +1,269 @@
+// Proof-of-compute attestation for finished jobs.
+//
+// While the `compute_attestation` flag is on, every job that completes gets an attestation
+// record. The daemon produces the proof artifacts (`--get-job-proofs-json`); they are checked
+// locally for shape before anything is sent, so a broken daemon build shows up here as `invalid`
+// rather than as a string of platform rejections. Valid proofs are submitted, failed submissions
+// are retried with backoff, and submitted proofs are polled until the platform decides. Records
+// live in the local database and are attached to `LocalJob::attestation` whenever jobs are
+// listed.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::time::{Duration, UNIX_EPOCH};
+use tauri::{AppHandle, Manager};
+
+use crate::feature_flags::{self, COMPUTE_ATTESTATION};
+use crate::storage::{unix_now, Storage};
+use crate::{api_client, emit_log_entry, get_timestamp, invoke_daemon_cli_json_output, shutdown};
+use dante_provider_core::models::{AttestationState, JobAttestation, LocalJob};
+
+const PROCESS_INTERVAL: Duration = Duration::from_secs(60);
+// Only jobs that finished this recently are attested when the flag is first switched on.
+const MAX_JOB_AGE_SECS: i64 = 24 * 60 * 60;
+const MAX_ATTEMPTS: u32 = 10;
+const MIN_RETRY_SECS: i64 = 60;
+const MAX_RETRY_SECS: i64 = 6 * 60 * 60;
+const VERDICT_POLL_SECS: i64 = 5 * 60;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AttestationRecord {
+    pub job_id: String,
+    pub attestation: JobAttestation,
+    // Unix seconds; when the record is next worked on.
+    pub next_attempt_at: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ProofArtifact {
+    pub job_id: String,
+    pub kind: String,
+    pub digest: String,    // Hex SHA-256 of the artifact
+    pub signature: String, // Hex signature by the daemon's attestation key
+    pub created_at: String,
+    #[serde(default)]
+    pub payload: Value,
+}
+
+#[derive(Deserialize, Debug)]
+struct Verdict {
+    status: String, // "pending" | "accepted" | "rejected"
+    #[serde(default)]
+    reason: Option<String>,
+}
+
+fn is_hex(value: &str) -> bool {
+    !value.is_empty() && value.len() % 2 == 0 && value.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+// Returns the problems found; an empty list means the proofs can be submitted.
+fn validate(job_id: &str, proofs: &[ProofArtifact]) -> Vec<String> {
+    if proofs.is_empty() {
+        return vec!["The daemon returned no proof artifacts.".to_string()];
+    }
+    let mut issues = Vec::new();
+    for (i, proof) in proofs.iter().enumerate() {
+        let label = format!("Proof {} ({})", i + 1, proof.kind);
+        if proof.job_id != job_id {
+            issues.push(format!("{} belongs to job '{}'.", label, proof.job_id));
+        }
+        if proof.kind.is_empty() || !proof.kind.chars().all(|c| c.is_ascii_lowercase() || c == '_') {
+            issues.push(format!("{} has an invalid kind.", label));
+        }
+        if proof.digest.len() != 64 || !is_hex(&proof.digest) {
+            issues.push(format!("{} digest is not a hex SHA-256.", label));
+        }
+        if !is_hex(&proof.signature) {
+            issues.push(format!("{} signature is missing or not hex.", label));
+        }
+        if humantime::parse_rfc3339_weak(&proof.created_at).is_err() {
+            issues.push(format!("{} has an unreadable timestamp '{}'.", label, proof.created_at));
+        }
+    }
+    issues
+}
+
+fn retry_delay(attempts: u32) -> i64 {
+    (MIN_RETRY_SECS << attempts.min(10)).min(MAX_RETRY_SECS)
+}
+
+fn completed_recently(job: &LocalJob, now: i64) -> bool {
+    job.completed_at
+        .as_deref()
+        .and_then(|ts| humantime::parse_rfc3339_weak(ts).ok())
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .is_some_and(|t| now - (t.as_secs() as i64) < MAX_JOB_AGE_SECS)
+}
+
+fn save(app_handle: &AppHandle, storage: &Storage, record: &AttestationRecord) {
+    if let Err(e) = storage.upsert_attestation(record) {
+        eprintln!("{}", e);
+        return;
+    }
+    if let Err(e) = app_handle.emit_all("attestation_updated", json!({ "job_id": record.job_id, "attestation": record.attestation })) {
+        eprintln!("Failed to emit attestation_updated event: {}", e);
+    }
+}
+
+// Starts tracking jobs that have just completed. Called with each polled job list.
+pub fn observe(app_handle: &AppHandle, jobs: &[LocalJob]) {
+    if !feature_flags::is_enabled(app_handle, COMPUTE_ATTESTATION) {
+        return;
+    }
+    let Some(storage) = app_handle.try_state::<Storage>() else { return };
+    let now = unix_now();
+    for job in jobs.iter().filter(|j| j.status == "completed" && completed_recently(j, now)) {
+        let record = AttestationRecord {
+            job_id: job.id.clone(),
+            attestation: JobAttestation { state: AttestationState::Pending, proofs: 0, attempts: 0, submitted_at: None, decided_at: None, detail: None },
+            next_attempt_at: now,
+        };
+        if let Err(e) = storage.insert_attestation(&record) {
+            eprintln!("{}", e);
+        }
+    }
+}
+
+// Attaches tracked attestation status to `jobs`.
+pub fn annotate(app_handle: &AppHandle, jobs: &mut [LocalJob]) {
+    let Some(storage) = app_handle.try_state::<Storage>() else { return };
+    let Ok(records) = storage.attestations() else { return };
+    for job in jobs.iter_mut() {
+        job.attestation = records.iter().find(|r| r.job_id == job.id).map(|r| r.attestation.clone());
+    }
+}
+
+fn submission_path(job_id: &str) -> String {
+    format!("/api/v1/providers/me/jobs/{}/attestations", api_client::encode_component(job_id))
+}
+
+fn apply_verdict(app_handle: &AppHandle, record: &mut AttestationRecord, verdict: Verdict) {
+    let now = unix_now();
+    match verdict.status.as_str() {
+        "accepted" => {
+            record.attestation.state = AttestationState::Accepted;
+            record.attestation.decided_at = Some(get_timestamp());
+            record.attestation.detail = None;
+        }
+        "rejected" => {
+            record.attestation.state = AttestationState::Rejected;
+            record.attestation.decided_at = Some(get_timestamp());
+            record.attestation.detail = verdict.reason.clone();
+            emit_log_entry(
+                app_handle,
+                "error",
+                format!("The platform rejected the compute proofs for job {}: {}", record.job_id, verdict.reason.as_deref().unwrap_or("no reason given")),
+            );
+        }
+        _ => {
+            record.attestation.state = AttestationState::Submitted;
+            record.next_attempt_at = now + VERDICT_POLL_SECS;
+        }
+    }
+}
+
+async fn submit(app_handle: &AppHandle, record: &mut AttestationRecord) {
+    let proofs = match invoke_daemon_cli_json_output::<Vec<ProofArtifact>>(app_handle, &["--get-job-proofs-json", "--job-id", &record.job_id]).await {
+        Ok(proofs) => proofs,
+        // Usually the daemon is offline; this isn't the platform's fault, so it doesn't use up attempts.
+        Err(e) => {
+            record.attestation.detail = Some(format!("Couldn't collect proofs: {}", e));
+            record.next_attempt_at = unix_now() + MIN_RETRY_SECS;
+            return;
+        }
+    };
+    record.attestation.proofs = proofs.len() as u32;
+    let issues = validate(&record.job_id, &proofs);
+    if !issues.is_empty() {
+        record.attestation.state = AttestationState::Invalid;
+        record.attestation.detail = Some(issues.join(" "));
+        emit_log_entry(app_handle, "error", format!("Compute proofs for job {} failed validation and weren't submitted: {}", record.job_id, issues.join(" ")));
+        return;
+    }
+
+    record.attestation.attempts += 1;
+    let body = json!({ "job_id": record.job_id, "proofs": proofs });
+    match api_client::send_json::<_, Verdict>(app_handle, reqwest::Method::POST, &submission_path(&record.job_id), &body).await {
+        Ok(verdict) => {
+            record.attestation.submitted_at = Some(get_timestamp());
+            apply_verdict(app_handle, record, verdict);
+        }
+        Err(e) if record.attestation.attempts >= MAX_ATTEMPTS => {
+            record.attestation.state = AttestationState::Failed;
+            record.attestation.detail = Some(e.clone());
+            emit_log_entry(app_handle, "error", format!("Gave up submitting compute proofs for job {} after {} attempts: {}", record.job_id, MAX_ATTEMPTS, e));
+        }
+        Err(e) => {
+            record.attestation.detail = Some(e);
+            record.next_attempt_at = unix_now() + retry_delay(record.attestation.attempts);
+        }
+    }
+}
+
+async fn poll_verdict(app_handle: &AppHandle, record: &mut AttestationRecord) {
+    match api_client::get_json::<Verdict>(app_handle, &submission_path(&record.job_id)).await {
+        Ok(verdict) => apply_verdict(app_handle, record, verdict),
+        Err(e) => {
+            record.attestation.detail = Some(e);
+            record.next_attempt_at = unix_now() + VERDICT_POLL_SECS;
+        }
+    }
+}
+
+async fn process(app_handle: &AppHandle) {
+    let Some(storage) = app_handle.try_state::<Storage>() else { return };
+    let records = match storage.attestations() {
+        Ok(records) => records,
+        Err(e) => {
+            eprintln!("{}", e);
+            return;
+        }
+    };
+    let now = unix_now();
+    for mut record in records.into_iter().filter(|r| r.next_attempt_at <= now) {
+        match record.attestation.state {
+            AttestationState::Pending => submit(app_handle, &mut record).await,
+            AttestationState::Submitted => poll_verdict(app_handle, &mut record).await,
+            _ => continue,
+        }
+        save(app_handle, &storage, &record);
+    }
+}
+
+pub fn spawn_attestation_task(app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            if feature_flags::is_enabled(&app_handle, COMPUTE_ATTESTATION) {
+                process(&app_handle).await;
+            }
+            if !shutdown::sleep(PROCESS_INTERVAL).await {
+                break;
+            }
+        }
+    });
+}
+
+#[tauri::command]
+pub async fn get_job_attestation(app_handle: AppHandle, job_id: String) -> Result<Option<JobAttestation>, String> {
+    let storage = app_handle.try_state::<Storage>().ok_or_else(|| "Local storage is unavailable.".to_string())?;
+    Ok(storage.attestations()?.into_iter().find(|r| r.job_id == job_id).map(|r| r.attestation))
+}
+
+// Queues an invalid, rejected or failed attestation for another submission, e.g. after a daemon
+// update that fixes its proofs.
+#[tauri::command]
+pub async fn retry_attestation(app_handle: AppHandle, job_id: String) -> Result<JobAttestation, String> {
+    let storage = app_handle.try_state::<Storage>().ok_or_else(|| "Local storage is unavailable.".to_string())?;
+    let mut record = storage
+        .attestations()?
+        .into_iter()
+        .find(|r| r.job_id == job_id)
+        .ok_or_else(|| format!("Job '{}' has no attestation record.", job_id))?;
+    if matches!(record.attestation.state, AttestationState::Accepted | AttestationState::Submitted) {
+        return Err(format!("The attestation for job '{}' was already submitted.", job_id));
+    }
+    record.attestation = JobAttestation { state: AttestationState::Pending, proofs: 0, attempts: 0, submitted_at: None, decided_at: None, detail: None };
+    record.next_attempt_at = unix_now();
+    save(&app_handle, &storage, &record);
+    Ok(record.attestation)
+}
@@ -0,0 +1,352 @@
+// Backup and restore of the GUI's local data.
+//
+// A backup is a gzipped tar archive with a `manifest.json` listing every entry alongside its
+// SHA-256, so a restore can verify integrity before touching anything. Restores can be run as
+// a dry run to preview what would be replaced. Creating one runs as a background task (see
+// `tasks`). Keychain secrets are only included on request, sealed with a passphrase (see
+// `crypto`); the archive itself is not encrypted.
+//
+// With account profiles, the backup holds the profile list, every account's history database
+// and each account's API token, and a restore puts each database back in its own account's
+// directory.
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+
+use crate::accounts::{self, AccountList};
+use crate::app_settings::{self, AppSettings, AppSettingsState};
+use crate::crypto::{self, EncryptedEnvelope};
+use crate::storage::{self, Storage};
+use crate::tasks::{self, TaskContext, TaskRequest};
+use crate::{api_client, emit_log_entry, get_provider_settings, secrets, get_timestamp, update_provider_settings, ProviderSettings};
+
+const BACKUP_FORMAT_VERSION: u32 = 1;
+const MANIFEST_NAME: &str = "manifest.json";
+
+const ENTRY_APP_SETTINGS: &str = "app_settings.json";
+const ENTRY_ACCOUNTS: &str = "accounts.json";
+// The database used without profiles; each account's is under `accounts/<id>/` like on disk.
+const ENTRY_DATABASE: &str = "provider.db";
+const ACCOUNT_DATABASES_PREFIX: &str = "accounts/";
+const ENTRY_DAEMON_SETTINGS: &str = "daemon_settings.json";
+const ENTRY_SECRETS: &str = "secrets.enc.json";
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BackupEntry {
+    pub name: String,
+    pub size: u64,
+    pub sha256: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BackupManifest {
+    pub format_version: u32,
+    pub created_at: String,
+    pub app_version: String,
+    pub includes_secrets: bool,
+    pub entries: Vec<BackupEntry>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct RestoreEntryPreview {
+    pub name: String,
+    pub size: u64,
+    pub integrity_ok: bool,
+    pub action: String, // "replace" | "skip"
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct RestorePreview {
+    pub manifest: BackupManifest,
+    pub entries: Vec<RestoreEntryPreview>,
+    pub valid: bool,
+    pub applied: bool,
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+// Keychain entries are only ever exported when explicitly requested.
+const SECRET_KEYS: &[&str] = &["sync.passphrase", "sync.credential", "platform.token", "proxy.password"];
+// Backups from before secrets were encrypted hold them in plain text under this prefix.
+const LEGACY_SECRETS_PREFIX: &str = "secrets/";
+const MIN_PASSPHRASE_CHARS: usize = 8;
+
+// The unscoped keys plus each account's own API token.
+fn secret_keys(accounts: &AccountList) -> Vec<String> {
+    SECRET_KEYS
+        .iter()
+        .map(|key| key.to_string())
+        .chain(accounts.accounts.iter().map(|a| accounts::scoped_secret_key(&a.id, api_client::TOKEN_KEY)))
+        .collect()
+}
+
+fn account_database_entry(id: &str) -> String {
+    format!("{}{}/{}", ACCOUNT_DATABASES_PREFIX, id, ENTRY_DATABASE)
+}
+
+// Where a database entry is restored to; None for entries naming an account the backup's
+// profile list doesn't have.
+fn database_dir(name: &str, base: &Path, accounts: &AccountList) -> Option<PathBuf> {
+    if name == ENTRY_DATABASE {
+        return Some(base.to_path_buf());
+    }
+    let id = name.strip_prefix(ACCOUNT_DATABASES_PREFIX)?.strip_suffix(&format!("/{}", ENTRY_DATABASE))?;
+    accounts.accounts.iter().any(|a| a.id == id).then(|| accounts::account_dir(base, id))
+}
+
+// The keychain entries as one JSON object, sealed under `passphrase`.
+fn seal_secrets(app_handle: &AppHandle, passphrase: &str) -> Result<EncryptedEnvelope, String> {
+    let mut values = HashMap::new();
+    for key in secret_keys(&accounts::list(app_handle)) {
+        if let Some(value) = secrets::get(&key)? {
+            values.insert(key, value);
+        }
+    }
+    let json = serde_json::to_vec(&values).map_err(|e| format!("Failed to serialize secrets: {}", e))?;
+    crypto::encrypt(&json, passphrase)
+}
+
+fn open_secrets(bytes: &[u8], passphrase: &str) -> Result<HashMap<String, String>, String> {
+    let envelope: EncryptedEnvelope = serde_json::from_slice(bytes).map_err(|e| format!("Invalid secrets entry in backup: {}", e))?;
+    let json = crypto::decrypt(&envelope, passphrase).map_err(|_| "Wrong backup passphrase, or the secrets entry is damaged.".to_string())?;
+    serde_json::from_slice(&json).map_err(|e| format!("Invalid secrets entry in backup: {}", e))
+}
+
+async fn collect_entries(app_handle: &AppHandle, secrets: Option<EncryptedEnvelope>) -> Result<Vec<(String, Vec<u8>)>, String> {
+    let mut entries = Vec::new();
+
+    let settings = app_settings::current(app_handle);
+    entries.push((
+        ENTRY_APP_SETTINGS.to_string(),
+        serde_json::to_vec_pretty(&settings).map_err(|e| format!("Failed to serialize app settings: {}", e))?,
+    ));
+
+    let profiles = accounts::list(app_handle);
+    if !profiles.accounts.is_empty() {
+        entries.push((
+            ENTRY_ACCOUNTS.to_string(),
+            serde_json::to_vec_pretty(&profiles).map_err(|e| format!("Failed to serialize accounts: {}", e))?,
+        ));
+    }
+
+    // Every account's history, not only the active one's, plus the database from before any
+    // profiles were set up.
+    if let (Some(storage), Some(base)) = (app_handle.try_state::<Storage>(), app_handle.path_resolver().app_data_dir()) {
+        let databases = std::iter::once((ENTRY_DATABASE.to_string(), base.clone()))
+            .chain(profiles.accounts.iter().map(|a| (account_database_entry(&a.id), accounts::account_dir(&base, &a.id))));
+        for (name, dir) in databases {
+            let path = storage::database_path(&dir);
+            if storage.is_live(&path) {
+                entries.push((name, storage.snapshot()?));
+            } else if path.exists() {
+                entries.push((name, storage::snapshot_file(&path)?));
+            }
+        }
+    }
+
+    // Daemon-side settings are included when the daemon is reachable; a backup taken while it
+    // is offline still captures everything the GUI owns.
+    match get_provider_settings(app_handle.clone()).await {
+        Ok(daemon_settings) => entries.push((
+            ENTRY_DAEMON_SETTINGS.to_string(),
+            serde_json::to_vec_pretty(&daemon_settings).map_err(|e| format!("Failed to serialize daemon settings: {}", e))?,
+        )),
+        Err(e) => emit_log_entry(app_handle, "status", format!("Backup will not include daemon settings: {}", e)),
+    }
+
+    if let Some(envelope) = secrets {
+        entries.push((ENTRY_SECRETS.to_string(), serde_json::to_vec(&envelope).map_err(|e| format!("Failed to serialize secrets: {}", e))?));
+    }
+    Ok(entries)
+}
+
+pub fn append_file(builder: &mut tar::Builder<GzEncoder<File>>, name: &str, bytes: &[u8]) -> Result<(), String> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(bytes.len() as u64);
+    header.set_mode(0o600);
+    header.set_cksum();
+    builder
+        .append_data(&mut header, name, bytes)
+        .map_err(|e| format!("Failed to add {} to backup: {}", name, e))
+}
+
+fn read_archive(path: &Path) -> Result<(BackupManifest, HashMap<String, Vec<u8>>), String> {
+    let file = File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let mut archive = tar::Archive::new(GzDecoder::new(file));
+    let mut files = HashMap::new();
+    for entry in archive.entries().map_err(|e| format!("Not a valid backup archive: {}", e))? {
+        let mut entry = entry.map_err(|e| format!("Corrupt backup archive: {}", e))?;
+        let name = entry
+            .path()
+            .map_err(|e| format!("Corrupt entry name in backup: {}", e))?
+            .to_string_lossy()
+            .to_string();
+        let mut bytes = Vec::new();
+        entry.read_to_end(&mut bytes).map_err(|e| format!("Failed to read {} from backup: {}", name, e))?;
+        files.insert(name, bytes);
+    }
+
+    let manifest_bytes = files
+        .remove(MANIFEST_NAME)
+        .ok_or_else(|| format!("Backup is missing {}.", MANIFEST_NAME))?;
+    let manifest: BackupManifest = serde_json::from_slice(&manifest_bytes).map_err(|e| format!("Invalid backup manifest: {}", e))?;
+    if manifest.format_version > BACKUP_FORMAT_VERSION {
+        return Err(format!(
+            "Backup format version {} is newer than this app supports ({}). Update the app first.",
+            manifest.format_version, BACKUP_FORMAT_VERSION
+        ));
+    }
+    Ok((manifest, files))
+}
+
+pub async fn write_backup(app_handle: AppHandle, task: TaskContext, path: String, secrets: Option<EncryptedEnvelope>) -> Result<BackupManifest, String> {
+    task.progress(None, "Collecting settings and local data")?;
+    let include_secrets = secrets.is_some();
+    let entries = collect_entries(&app_handle, secrets).await?;
+    let manifest = BackupManifest {
+        format_version: BACKUP_FORMAT_VERSION,
+        created_at: get_timestamp(),
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        includes_secrets: include_secrets,
+        entries: entries
+            .iter()
+            .map(|(name, bytes)| BackupEntry { name: name.clone(), size: bytes.len() as u64, sha256: sha256_hex(bytes) })
+            .collect(),
+    };
+
+    let file = File::create(&path).map_err(|e| format!("Failed to create {}: {}", path, e))?;
+    let mut builder = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+    let manifest_json = serde_json::to_vec_pretty(&manifest).map_err(|e| format!("Failed to serialize manifest: {}", e))?;
+    append_file(&mut builder, MANIFEST_NAME, &manifest_json)?;
+    let total_bytes: u64 = entries.iter().map(|(_, bytes)| bytes.len() as u64).sum::<u64>().max(1);
+    let mut written_bytes = 0;
+    for (name, bytes) in &entries {
+        task.progress(Some(100.0 * written_bytes as f32 / total_bytes as f32), format!("Writing {}", name))?;
+        append_file(&mut builder, name, bytes)?;
+        written_bytes += bytes.len() as u64;
+    }
+    builder
+        .into_inner()
+        .and_then(|gz| gz.finish())
+        .map_err(|e| format!("Failed to finish backup archive: {}", e))?;
+
+    emit_log_entry(&app_handle, "status", format!("Backup written to {} ({} entries).", path, manifest.entries.len()));
+    Ok(manifest)
+}
+
+// Returns the task ID; the `BackupManifest` is the task's result. Secrets need a passphrase,
+// which is needed again to restore them.
+#[tauri::command]
+pub async fn create_backup(app_handle: AppHandle, path: String, include_secrets: bool, passphrase: Option<String>) -> Result<String, String> {
+    let secrets = if include_secrets {
+        let passphrase = passphrase.unwrap_or_default();
+        if passphrase.chars().count() < MIN_PASSPHRASE_CHARS {
+            return Err(format!("Backing up secrets needs a passphrase of at least {} characters.", MIN_PASSPHRASE_CHARS));
+        }
+        Some(seal_secrets(&app_handle, &passphrase)?)
+    } else {
+        None
+    };
+    Ok(tasks::submit(&app_handle, TaskRequest::Backup { path, secrets }))
+}
+
+// Without a passphrase, the secrets in a backup are skipped and everything else is restored.
+#[tauri::command]
+pub async fn restore_backup(app_handle: AppHandle, path: String, dry_run: bool, passphrase: Option<String>) -> Result<RestorePreview, String> {
+    let (manifest, files) = read_archive(&PathBuf::from(&path))?;
+    let passphrase = passphrase.filter(|p| !p.is_empty());
+    let base = app_handle.path_resolver().app_data_dir().ok_or_else(|| "App data directory is unavailable.".to_string())?;
+    let restored_accounts: Option<AccountList> = files
+        .get(ENTRY_ACCOUNTS)
+        .map(|bytes| serde_json::from_slice(bytes).map_err(|e| format!("Invalid accounts in backup: {}", e)))
+        .transpose()?;
+    // Backups from before profiles carry no list; their secrets are matched against the current one.
+    let profiles = restored_accounts.clone().unwrap_or_else(|| accounts::list(&app_handle));
+    let restorable_keys = secret_keys(&profiles);
+
+    let entries: Vec<RestoreEntryPreview> = manifest
+        .entries
+        .iter()
+        .map(|entry| {
+            let integrity_ok = files.get(&entry.name).is_some_and(|bytes| sha256_hex(bytes) == entry.sha256);
+            let known = matches!(entry.name.as_str(), ENTRY_APP_SETTINGS | ENTRY_ACCOUNTS | ENTRY_DAEMON_SETTINGS)
+                || database_dir(&entry.name, &base, &profiles).is_some()
+                || (entry.name == ENTRY_SECRETS && passphrase.is_some())
+                || entry.name.strip_prefix(LEGACY_SECRETS_PREFIX).is_some_and(|key| SECRET_KEYS.contains(&key));
+            RestoreEntryPreview {
+                name: entry.name.clone(),
+                size: entry.size,
+                integrity_ok,
+                action: if known { "replace" } else { "skip" }.to_string(),
+            }
+        })
+        .collect();
+    let valid = entries.iter().all(|e| e.integrity_ok);
+
+    let mut preview = RestorePreview { manifest, entries, valid, applied: false };
+    if dry_run {
+        return Ok(preview);
+    }
+    if !valid {
+        return Err("Backup failed integrity checks; nothing was restored.".to_string());
+    }
+
+    // Parse everything before writing anything so a bad entry can't leave a half-restored state.
+    let settings: Option<AppSettings> = files
+        .get(ENTRY_APP_SETTINGS)
+        .map(|bytes| serde_json::from_slice(bytes).map_err(|e| format!("Invalid app settings in backup: {}", e)))
+        .transpose()?;
+    let daemon_settings: Option<ProviderSettings> = files
+        .get(ENTRY_DAEMON_SETTINGS)
+        .map(|bytes| serde_json::from_slice(bytes).map_err(|e| format!("Invalid daemon settings in backup: {}", e)))
+        .transpose()?;
+    let mut restored_secrets: HashMap<String, String> = SECRET_KEYS
+        .iter()
+        .filter_map(|key| files.get(&format!("{}{}", LEGACY_SECRETS_PREFIX, key)).map(|bytes| (key.to_string(), String::from_utf8_lossy(bytes).to_string())))
+        .collect();
+    match (files.get(ENTRY_SECRETS), passphrase.as_deref()) {
+        (Some(bytes), Some(passphrase)) => restored_secrets.extend(open_secrets(bytes, passphrase)?),
+        (Some(_), None) => emit_log_entry(&app_handle, "status", "The backup's secrets were skipped; restore with its passphrase to include them.".to_string()),
+        (None, _) => {}
+    }
+
+    if let Some(settings) = settings {
+        app_settings::update_app_settings(app_handle.clone(), app_handle.state::<AppSettingsState>(), settings).await?;
+    }
+    // Tokens go first so switching to the restored active account can hand its token to the daemon.
+    for key in &restorable_keys {
+        if let Some(value) = restored_secrets.get(key) {
+            secrets::set(key, value)?;
+        }
+    }
+    if let Some(restored_accounts) = restored_accounts {
+        accounts::restore(&app_handle, restored_accounts).await?;
+    }
+    for (name, bytes) in &files {
+        let Some(dir) = database_dir(name, &base, &profiles) else { continue };
+        let path = storage::database_path(&dir);
+        match app_handle.try_state::<Storage>() {
+            Some(storage) if storage.is_live(&path) => storage.restore(bytes)?,
+            _ => storage::restore_file(&path, bytes)?,
+        }
+    }
+    if let Some(daemon_settings) = daemon_settings {
+        if let Err(e) = update_provider_settings(app_handle.clone(), daemon_settings).await {
+            emit_log_entry(&app_handle, "error", format!("Restored local data, but the daemon rejected its settings: {}", e));
+        }
+    }
+
+    preview.applied = true;
+    emit_log_entry(&app_handle, "status", format!("Backup restored from {}.", path));
+    Ok(preview)
+}
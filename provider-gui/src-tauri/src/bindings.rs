@@ -0,0 +1,62 @@
+// TypeScript bindings for the frontend, generated from the Rust definitions.
+//
+// Coverage is partial: only the core daemon commands listed below (those marked
+// `#[specta::specta]`) and the handful of payload types appended after them are generated. Every
+// other command and event is still invoked with hand-written types in the frontend. Bring a
+// command in by marking it, deriving `specta::Type` on the types in its signature and adding it
+// to the list.
+//
+// Debug builds rewrite `src/bindings.ts` on startup, so `npm run tauri dev` after changing a
+// covered command or type brings the frontend's copy up to date; commit the regenerated file
+// with the change. Types that only travel as event payloads or errors never appear in a command
+// signature, so they are appended separately.
+
+use specta::ts::{self, BigIntExportBehavior, ExportConfiguration};
+use std::fs::OpenOptions;
+use std::io::Write;
+
+use dante_provider_core::error::ProviderError;
+use dante_provider_core::events::EventEnvelope;
+
+use crate::gpu_health::HardwareAlert;
+
+const BINDINGS_PATH: &str = "../src/bindings.ts";
+
+fn append_standalone_types(config: &ExportConfiguration) -> Result<(), String> {
+    let definitions = [
+        ts::export::<ProviderError>(config),
+        ts::export::<EventEnvelope>(config),
+        ts::export::<HardwareAlert>(config),
+    ];
+    let mut out = String::from("\n// Event payloads and errors\n\n");
+    for definition in definitions {
+        out.push_str(&definition.map_err(|e| e.to_string())?);
+        out.push('\n');
+    }
+    let mut file = OpenOptions::new().append(true).open(BINDINGS_PATH).map_err(|e| e.to_string())?;
+    file.write_all(out.as_bytes()).map_err(|e| e.to_string())
+}
+
+pub fn export() {
+    // Cumulative counters such as `ecc_corrected_errors` are u64; they stay well within a JS number.
+    let config = ExportConfiguration::default().bigint(BigIntExportBehavior::Number);
+    let commands = specta::collect_types![
+        crate::start_daemon,
+        crate::stop_daemon,
+        crate::get_detected_gpus,
+        crate::get_provider_settings,
+        crate::update_provider_settings,
+        crate::set_gpu_rental_config,
+        crate::get_local_jobs,
+        crate::get_network_status,
+        crate::run_network_test,
+        crate::run_benchmark,
+        crate::get_financial_summary
+    ];
+    let result = tauri_specta::ts::export_with_cfg(commands, config.clone(), BINDINGS_PATH)
+        .map_err(|e| e.to_string())
+        .and_then(|_| append_standalone_types(&config));
+    if let Err(e) = result {
+        eprintln!("Failed to export TypeScript bindings: {}", e);
+    }
+}
@@ -0,0 +1,266 @@
+// The capability manifest the daemon advertises to renters, and an editor for it.
+//
+// Matchmaking filters on what the manifest claims: CUDA compute capability per GPU, the CUDA and
+// driver versions, optional driver features (MIG, NVENC, GPUDirect RDMA), the container runtimes
+// jobs can use and which GPUs are NVLink-bridged. The daemon fills it from what it detects, and
+// providers may trim it (hide a runtime they don't want used, drop a flaky NVLink bridge) but
+// never claim more than the hardware has: every edit is checked against the detected manifest
+// before it is pushed. The preview shows the listing exactly as renters will see it, without the
+// provider's own GPU nicknames.
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::units::DgpuPerHour;
+use crate::{emit_log_entry, gpu_ids, invoke_daemon_cli_json_output, nvidia_smi, RentalMode};
+
+const KNOWN_DRIVER_FEATURES: &[&str] = &["mig", "nvenc", "nvdec", "gpudirect_rdma", "gpudirect_storage", "vgpu"];
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct GpuCapabilities {
+    pub gpu_id: String,
+    pub compute_capability: String, // e.g. "8.9"
+    #[serde(default)]
+    pub nvlink_peers: Vec<String>, // GPU IDs bridged to this one
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct CapabilityManifest {
+    pub cuda_version: Option<String>, // Highest CUDA version the driver supports, e.g. "12.4"
+    pub driver_version: Option<String>,
+    pub driver_features: Vec<String>,
+    pub container_runtimes: Vec<String>, // In order of preference, e.g. "nvidia", "runc"
+    pub gpus: Vec<GpuCapabilities>,
+}
+
+// Older daemons answer with neither; both are then empty.
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+struct DaemonCapabilities {
+    advertised: CapabilityManifest,
+    detected: CapabilityManifest,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct CapabilityEditor {
+    pub advertised: CapabilityManifest,
+    pub detected: CapabilityManifest,
+    pub issues: Vec<String>, // Claims in `advertised` the hardware doesn't back, e.g. after a card swap
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ListedGpu {
+    pub model: String,
+    pub vram_gb: f64,
+    pub compute_capability: Option<String>,
+    pub nvlink_peer_count: usize,
+    pub hourly_rate_dgpu: Option<DgpuPerHour>,
+    pub rental_mode: RentalMode,
+    pub available: bool,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ListingPreview {
+    pub gpus: Vec<ListedGpu>,
+    pub cuda_version: Option<String>,
+    pub driver_version: Option<String>,
+    pub driver_features: Vec<String>,
+    pub container_runtimes: Vec<String>,
+}
+
+// Manifests name GPUs by the daemon's IDs; the GUI uses stable ones (see `gpu_ids`).
+fn to_stable_ids(manifest: &mut CapabilityManifest) {
+    for gpu in &mut manifest.gpus {
+        gpu.gpu_id = gpu_ids::stable_id(&gpu.gpu_id);
+        for peer in &mut gpu.nvlink_peers {
+            *peer = gpu_ids::stable_id(peer);
+        }
+    }
+}
+
+async fn to_daemon_ids(app_handle: &AppHandle, manifest: &mut CapabilityManifest) {
+    for gpu in &mut manifest.gpus {
+        gpu.gpu_id = gpu_ids::daemon_id(app_handle, &gpu.gpu_id).await;
+        for peer in &mut gpu.nvlink_peers {
+            *peer = gpu_ids::daemon_id(app_handle, peer).await;
+        }
+    }
+}
+
+async fn fetch(app_handle: &AppHandle) -> Result<DaemonCapabilities, String> {
+    // List GPUs first so the daemon's IDs in the manifest can be translated.
+    nvidia_smi::detect_gpus(app_handle).await?;
+    let mut capabilities = invoke_daemon_cli_json_output::<DaemonCapabilities>(app_handle, &["--get-capabilities-json"]).await?;
+    to_stable_ids(&mut capabilities.advertised);
+    to_stable_ids(&mut capabilities.detected);
+    Ok(capabilities)
+}
+
+fn version(raw: &str) -> Option<Vec<u32>> {
+    raw.trim().split('.').map(|part| part.parse().ok()).collect()
+}
+
+// Everything `manifest` claims beyond `detected`.
+pub fn validate(manifest: &CapabilityManifest, detected: &CapabilityManifest) -> Vec<String> {
+    let mut issues = Vec::new();
+    match (manifest.cuda_version.as_deref(), detected.cuda_version.as_deref()) {
+        (Some(claimed), _) if version(claimed).is_none() => issues.push(format!("CUDA version '{}' isn't a version number.", claimed)),
+        (Some(claimed), Some(actual)) if version(claimed) > version(actual) => {
+            issues.push(format!("CUDA {} is advertised but the driver only supports CUDA {}.", claimed, actual))
+        }
+        (Some(claimed), None) => issues.push(format!("CUDA {} is advertised but no CUDA support was detected.", claimed)),
+        _ => {}
+    }
+    match (manifest.driver_version.as_deref(), detected.driver_version.as_deref()) {
+        (Some(claimed), Some(actual)) if claimed.trim() != actual.trim() => {
+            issues.push(format!("Driver {} is advertised but {} is installed.", claimed, actual))
+        }
+        (Some(claimed), None) => issues.push(format!("Driver {} is advertised but no driver was detected.", claimed)),
+        _ => {}
+    }
+    for feature in &manifest.driver_features {
+        if !KNOWN_DRIVER_FEATURES.contains(&feature.as_str()) {
+            issues.push(format!("'{}' isn't a known driver feature.", feature));
+        } else if !detected.driver_features.contains(feature) {
+            issues.push(format!("The driver feature '{}' wasn't detected on this rig.", feature));
+        }
+    }
+    for runtime in &manifest.container_runtimes {
+        if !detected.container_runtimes.contains(runtime) {
+            issues.push(format!("The container runtime '{}' isn't installed.", runtime));
+        }
+    }
+    for gpu in &manifest.gpus {
+        let Some(actual) = detected.gpus.iter().find(|g| g.gpu_id == gpu.gpu_id) else {
+            issues.push(format!("GPU {} isn't present.", gpu.gpu_id));
+            continue;
+        };
+        if gpu.compute_capability != actual.compute_capability {
+            issues.push(format!("GPU {} has compute capability {}, not {}.", gpu.gpu_id, actual.compute_capability, gpu.compute_capability));
+        }
+        for peer in gpu.nvlink_peers.iter().filter(|p| !actual.nvlink_peers.contains(p)) {
+            issues.push(format!("GPU {} has no NVLink bridge to {}.", gpu.gpu_id, peer));
+        }
+    }
+    issues
+}
+
+#[tauri::command]
+pub async fn get_capability_manifest(app_handle: AppHandle) -> Result<CapabilityEditor, String> {
+    let capabilities = fetch(&app_handle).await?;
+    let issues = validate(&capabilities.advertised, &capabilities.detected);
+    Ok(CapabilityEditor { advertised: capabilities.advertised, detected: capabilities.detected, issues })
+}
+
+#[tauri::command]
+pub async fn update_capability_manifest(app_handle: AppHandle, manifest: CapabilityManifest) -> Result<CapabilityManifest, String> {
+    let detected = fetch(&app_handle).await?.detected;
+    let issues = validate(&manifest, &detected);
+    if !issues.is_empty() {
+        return Err(issues.join(" "));
+    }
+    let mut outgoing = manifest.clone();
+    to_daemon_ids(&app_handle, &mut outgoing).await;
+    let json = serde_json::to_string(&outgoing).map_err(|e| format!("Failed to serialize capability manifest: {}", e))?;
+    invoke_daemon_cli_json_output::<serde_json::Value>(&app_handle, &["--set-capabilities-json", &json]).await?;
+    emit_log_entry(&app_handle, "status", "Capability manifest updated; renters see the new listing on the next heartbeat.".to_string());
+    Ok(manifest)
+}
+
+// The listing as renters will see it, for `manifest` (the currently advertised one by default).
+#[tauri::command]
+pub async fn preview_listing(app_handle: AppHandle, manifest: Option<CapabilityManifest>) -> Result<ListingPreview, String> {
+    let manifest = match manifest {
+        Some(manifest) => manifest,
+        None => fetch(&app_handle).await?.advertised,
+    };
+    let gpus = nvidia_smi::detect_gpus(&app_handle).await?;
+    Ok(ListingPreview {
+        gpus: gpus
+            .iter()
+            .map(|gpu| {
+                let capabilities = manifest.gpus.iter().find(|c| c.gpu_id == gpu.id);
+                ListedGpu {
+                    model: gpu.model.clone(),
+                    vram_gb: (gpu.vram_total_mb.as_gb() * 10.0).round() / 10.0,
+                    compute_capability: capabilities.map(|c| c.compute_capability.clone()),
+                    nvlink_peer_count: capabilities.map_or(0, |c| c.nvlink_peers.len()),
+                    hourly_rate_dgpu: gpu.current_hourly_rate_dgpu,
+                    rental_mode: gpu.rental_mode,
+                    available: gpu.is_available_for_rent,
+                }
+            })
+            .collect(),
+        cuda_version: manifest.cuda_version,
+        driver_version: manifest.driver_version,
+        driver_features: manifest.driver_features,
+        container_runtimes: manifest.container_runtimes,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gpu(id: &str, peers: &[&str]) -> GpuCapabilities {
+        GpuCapabilities { gpu_id: id.to_string(), compute_capability: "8.9".to_string(), nvlink_peers: peers.iter().map(|p| p.to_string()).collect() }
+    }
+
+    fn detected() -> CapabilityManifest {
+        CapabilityManifest {
+            cuda_version: Some("12.4".to_string()),
+            driver_version: Some("550.54.14".to_string()),
+            driver_features: vec!["nvenc".to_string()],
+            container_runtimes: vec!["nvidia".to_string(), "runc".to_string()],
+            gpus: vec![gpu("gpu-a", &["gpu-b"]), gpu("gpu-b", &["gpu-a"])],
+        }
+    }
+
+    #[test]
+    fn accepts_the_detected_manifest_and_trimmed_claims() {
+        assert!(validate(&detected(), &detected()).is_empty());
+        let trimmed = CapabilityManifest { container_runtimes: vec!["nvidia".to_string()], gpus: vec![gpu("gpu-a", &[])], ..detected() };
+        assert!(validate(&trimmed, &detected()).is_empty());
+    }
+
+    #[test]
+    fn compares_cuda_versions_numerically() {
+        let actual = CapabilityManifest { cuda_version: Some("12.10".to_string()), ..detected() };
+        let claim = |version: &str| CapabilityManifest { cuda_version: Some(version.to_string()), ..detected() };
+        assert!(validate(&claim("12.4"), &actual).is_empty());
+        assert!(validate(&claim("12.10"), &actual).is_empty());
+        assert_eq!(validate(&claim("12.11"), &actual).len(), 1);
+        assert_eq!(validate(&claim("13"), &actual).len(), 1);
+        assert_eq!(validate(&claim("twelve"), &actual).len(), 1);
+    }
+
+    #[test]
+    fn flags_versions_claimed_without_detection() {
+        let nothing_detected = CapabilityManifest { cuda_version: None, driver_version: None, ..detected() };
+        let issues = validate(&detected(), &nothing_detected);
+        assert_eq!(issues.len(), 2);
+        assert!(issues.iter().any(|i| i.contains("no CUDA support")));
+        assert!(issues.iter().any(|i| i.contains("no driver was detected")));
+    }
+
+    #[test]
+    fn flags_unknown_and_undetected_features() {
+        let manifest = CapabilityManifest { driver_features: vec!["nvenc".to_string(), "mig".to_string(), "warp_drive".to_string()], ..detected() };
+        let issues = validate(&manifest, &detected());
+        assert_eq!(issues.len(), 2);
+        assert!(issues.iter().any(|i| i.contains("'mig' wasn't detected")));
+        assert!(issues.iter().any(|i| i.contains("'warp_drive' isn't a known driver feature")));
+    }
+
+    #[test]
+    fn flags_nvlink_peers_without_a_bridge() {
+        let mut manifest = detected();
+        manifest.gpus[0].nvlink_peers.push("gpu-c".to_string());
+        manifest.gpus.push(gpu("gpu-c", &["gpu-a"]));
+        let issues = validate(&manifest, &detected());
+        assert!(issues.contains(&"GPU gpu-a has no NVLink bridge to gpu-c.".to_string()));
+        assert!(issues.contains(&"GPU gpu-c isn't present.".to_string()));
+        assert_eq!(issues.len(), 2);
+    }
+}
@@ -0,0 +1,166 @@
+// Disk budget and retention for job checkpoints.
+//
+// Checkpoints are written by the daemon for failover and resumable jobs and can take tens of
+// gigabytes each. Cleanup removes checkpoints past the retention period, then the oldest ones
+// until usage fits the budget, but never touches checkpoints of jobs that are still running or
+// queued, or that are under dispute and may be needed as evidence. If the set of protected jobs
+// can't be determined, nothing is deleted.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::AppHandle;
+
+use crate::{app_settings, emit_log_entry, get_local_jobs, invoke_daemon_cli_json_output, shutdown};
+
+const CLEANUP_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+const BYTES_PER_GB: u64 = 1024 * 1024 * 1024;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct CheckpointPolicy {
+    pub storage_budget_gb: Option<f32>, // None means unlimited
+    pub retention_days: u32,
+}
+
+impl Default for CheckpointPolicy {
+    fn default() -> Self {
+        CheckpointPolicy { storage_budget_gb: Some(200.0), retention_days: 14 }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Checkpoint {
+    pub checkpoint_id: String,
+    pub job_id: String,
+    pub size_bytes: u64,
+    pub created_at: String,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct JobCheckpointUsage {
+    pub job_id: String,
+    pub checkpoints: usize,
+    pub size_bytes: u64,
+    pub protected: bool, // Running, queued or disputed
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct CheckpointUsage {
+    pub total_bytes: u64,
+    pub budget_bytes: Option<u64>,
+    pub jobs: Vec<JobCheckpointUsage>,
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct CheckpointCleanupReport {
+    pub deleted: usize,
+    pub freed_bytes: u64,
+    pub expired: usize,
+    pub over_budget: usize,
+    pub remaining_bytes: u64,
+}
+
+fn parse_unix(ts: &str) -> Option<u64> {
+    humantime::parse_rfc3339_weak(ts).ok().and_then(|t| t.duration_since(UNIX_EPOCH).ok()).map(|d| d.as_secs())
+}
+
+async fn list(app_handle: &AppHandle) -> Result<Vec<Checkpoint>, String> {
+    invoke_daemon_cli_json_output(app_handle, &["--list-checkpoints-json"]).await
+}
+
+async fn protected_jobs(app_handle: &AppHandle) -> Result<HashSet<String>, String> {
+    let mut protected: HashSet<String> = get_local_jobs(app_handle.clone())
+        .await?
+        .into_iter()
+        .filter(|j| j.status == "running" || j.status == "queued")
+        .map(|j| j.id)
+        .collect();
+    let disputed: Vec<String> = invoke_daemon_cli_json_output(app_handle, &["--get-disputed-jobs-json"]).await?;
+    protected.extend(disputed);
+    Ok(protected)
+}
+
+pub async fn usage(app_handle: &AppHandle) -> Result<CheckpointUsage, String> {
+    let checkpoints = list(app_handle).await?;
+    let protected = protected_jobs(app_handle).await.unwrap_or_default();
+    let mut per_job: BTreeMap<String, JobCheckpointUsage> = BTreeMap::new();
+    for checkpoint in &checkpoints {
+        let entry = per_job.entry(checkpoint.job_id.clone()).or_insert_with(|| JobCheckpointUsage {
+            job_id: checkpoint.job_id.clone(),
+            checkpoints: 0,
+            size_bytes: 0,
+            protected: protected.contains(&checkpoint.job_id),
+        });
+        entry.checkpoints += 1;
+        entry.size_bytes += checkpoint.size_bytes;
+    }
+    let budget_bytes = app_settings::current(app_handle).checkpoints.storage_budget_gb.map(|gb| (gb as f64 * BYTES_PER_GB as f64) as u64);
+    let mut jobs: Vec<JobCheckpointUsage> = per_job.into_values().collect();
+    jobs.sort_by(|a, b| b.size_bytes.cmp(&a.size_bytes));
+    Ok(CheckpointUsage { total_bytes: checkpoints.iter().map(|c| c.size_bytes).sum(), budget_bytes, jobs })
+}
+
+pub async fn cleanup(app_handle: &AppHandle) -> Result<CheckpointCleanupReport, String> {
+    let policy = app_settings::current(app_handle).checkpoints;
+    let protected = protected_jobs(app_handle).await?;
+    let mut checkpoints = list(app_handle).await?;
+    // Oldest first, so budget enforcement drops the least useful checkpoints.
+    checkpoints.sort_by_key(|c| parse_unix(&c.created_at).unwrap_or(0));
+
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let retention_cutoff = now.saturating_sub(u64::from(policy.retention_days) * 24 * 60 * 60);
+    let budget = policy.storage_budget_gb.map(|gb| (gb as f64 * BYTES_PER_GB as f64) as u64);
+    let mut total: u64 = checkpoints.iter().map(|c| c.size_bytes).sum();
+    let mut report = CheckpointCleanupReport::default();
+
+    for checkpoint in checkpoints.iter().filter(|c| !protected.contains(&c.job_id)) {
+        let expired = parse_unix(&checkpoint.created_at).is_some_and(|t| t < retention_cutoff);
+        let over_budget = budget.is_some_and(|b| total > b);
+        if !expired && !over_budget {
+            continue;
+        }
+        invoke_daemon_cli_json_output::<serde_json::Value>(app_handle, &["--delete-checkpoint", "--checkpoint-id", &checkpoint.checkpoint_id]).await?;
+        total -= checkpoint.size_bytes;
+        report.deleted += 1;
+        report.freed_bytes += checkpoint.size_bytes;
+        if expired {
+            report.expired += 1;
+        } else {
+            report.over_budget += 1;
+        }
+    }
+    report.remaining_bytes = total;
+    if budget.is_some_and(|b| total > b) {
+        emit_log_entry(
+            app_handle,
+            "error",
+            format!("Checkpoints still use {} MB, over the storage budget; the rest belong to running or disputed jobs.", total / (1024 * 1024)),
+        );
+    }
+    Ok(report)
+}
+
+pub fn spawn_cleanup_task(app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            if !shutdown::sleep(CLEANUP_INTERVAL).await {
+                break;
+            }
+            match cleanup(&app_handle).await {
+                Ok(report) if report.deleted > 0 => emit_log_entry(
+                    &app_handle,
+                    "status",
+                    format!("Removed {} checkpoints, freeing {} MB.", report.deleted, report.freed_bytes / (1024 * 1024)),
+                ),
+                Ok(_) => {}
+                Err(e) => eprintln!("Checkpoint cleanup skipped: {}", e),
+            }
+        }
+    });
+}
+
+#[tauri::command]
+pub async fn clean_up_checkpoints_now(app_handle: AppHandle) -> Result<CheckpointCleanupReport, String> {
+    cleanup(&app_handle).await
+}
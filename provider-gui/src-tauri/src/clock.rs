@@ -0,0 +1,142 @@
+// Clock skew detection against the platform.
+//
+// Billing disputes often come down to a rig whose clock has drifted. We periodically ask the
+// platform for its time, estimate our offset NTP-style (assuming the server read its clock
+// halfway through the round trip) and warn when it exceeds a threshold. Locally generated
+// records carry a corrected timestamp alongside the local one.
+
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager, State};
+
+use crate::{app_settings, emit_log_entry, get_timestamp, http_client, shutdown};
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(30 * 60);
+pub const SKEW_WARNING_MS: i64 = 2_000;
+
+// Offset to add to local time to get platform time, readable without locking from any logger.
+static OFFSET_MS: AtomicI64 = AtomicI64::new(0);
+static MEASURED: AtomicBool = AtomicBool::new(false);
+
+#[derive(Deserialize)]
+struct PlatformTime {
+    unix_ms: i64,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ClockSkew {
+    pub offset_ms: i64, // Positive when the local clock is behind the platform
+    pub round_trip_ms: i64,
+    pub checked_at: String,
+    pub exceeds_threshold: bool,
+    pub threshold_ms: i64,
+}
+
+pub struct ClockState {
+    last: Mutex<Option<ClockSkew>>,
+}
+
+impl ClockState {
+    pub fn new() -> Self {
+        ClockState { last: Mutex::new(None) }
+    }
+}
+
+fn unix_ms(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH).map(|d| d.as_millis() as i64).unwrap_or(0)
+}
+
+// Platform time as unix seconds, or None until a measurement has succeeded.
+pub fn corrected_unix_secs() -> Option<i64> {
+    MEASURED
+        .load(Ordering::Relaxed)
+        .then(|| (unix_ms(SystemTime::now()) + OFFSET_MS.load(Ordering::Relaxed)).div_euclid(1000))
+}
+
+// Platform-corrected timestamp, or None until a measurement has succeeded.
+pub fn corrected_timestamp() -> Option<String> {
+    if !MEASURED.load(Ordering::Relaxed) {
+        return None;
+    }
+    let offset = OFFSET_MS.load(Ordering::Relaxed);
+    let now = SystemTime::now();
+    let corrected = if offset >= 0 {
+        now + Duration::from_millis(offset as u64)
+    } else {
+        now - Duration::from_millis(offset.unsigned_abs())
+    };
+    Some(humantime::format_rfc3339_millis(corrected).to_string())
+}
+
+pub async fn measure(app_handle: &AppHandle) -> Result<ClockSkew, String> {
+    let platform_url = app_settings::current(app_handle).platform_url;
+    let client = http_client::client()?;
+
+    let sent_at = SystemTime::now();
+    let started = Instant::now();
+    let response: PlatformTime = client
+        .get(format!("{}/time", platform_url.trim_end_matches('/')))
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| format!("Failed to reach the platform time endpoint: {}", e))?
+        .json()
+        .await
+        .map_err(|e| format!("Invalid response from the platform time endpoint: {}", e))?;
+    let round_trip_ms = started.elapsed().as_millis() as i64;
+
+    let midpoint = unix_ms(sent_at) + round_trip_ms / 2;
+    let offset_ms = response.unix_ms - midpoint;
+    OFFSET_MS.store(offset_ms, Ordering::Relaxed);
+    MEASURED.store(true, Ordering::Relaxed);
+
+    let skew = ClockSkew {
+        offset_ms,
+        round_trip_ms,
+        checked_at: get_timestamp(),
+        exceeds_threshold: offset_ms.abs() > SKEW_WARNING_MS,
+        threshold_ms: SKEW_WARNING_MS,
+    };
+    if let Some(state) = app_handle.try_state::<ClockState>() {
+        *state.last.lock().unwrap() = Some(skew.clone());
+    }
+    Ok(skew)
+}
+
+pub fn spawn_skew_monitor(app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            match measure(&app_handle).await {
+                Ok(skew) if skew.exceeds_threshold => {
+                    emit_log_entry(
+                        &app_handle,
+                        "error",
+                        format!(
+                            "System clock is off by {} ms from platform time. Enable automatic time sync (NTP) to avoid billing discrepancies.",
+                            skew.offset_ms
+                        ),
+                    );
+                    if let Err(e) = app_handle.emit_all("clock_skew_warning", skew) {
+                        eprintln!("Failed to emit clock_skew_warning event: {}", e);
+                    }
+                }
+                Ok(_) => {}
+                // Offline rigs are common; the preflight check surfaces this when it matters.
+                Err(e) => eprintln!("Clock skew check failed: {}", e),
+            }
+            if !shutdown::sleep(CHECK_INTERVAL).await {
+                break;
+            }
+        }
+    });
+}
+
+#[tauri::command]
+pub async fn get_clock_skew(app_handle: AppHandle, state: State<'_, ClockState>, refresh: bool) -> Result<Option<ClockSkew>, String> {
+    if refresh {
+        return measure(&app_handle).await.map(Some);
+    }
+    Ok(state.last.lock().unwrap().clone())
+}
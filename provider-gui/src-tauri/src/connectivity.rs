@@ -0,0 +1,344 @@
+// Detecting loss of platform connectivity and recording it as an incident.
+//
+// The platform is checked every few seconds; after several failures in a row the outage is
+// diagnosed with a ping ladder: the default gateway (is the local link up?), then public anchors
+// (is the ISP routing traffic?), then the platform itself. The interval is kept as an incident
+// that counts against uptime (see `analytics::uptime_percent`), and when connectivity returns
+// each job that was running during it gets a note with the outage window and cause (see
+// `job_notes`; the ping evidence stays local) and the job and balance state is reconciled with
+// the platform (see `reconcile`). An incident still open when the app closed is ended at the last
+// check that saw the outage, since nothing is known about the time after it.
+
+use chrono::{TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+
+use crate::storage::{unix_now, Storage};
+use crate::{app_settings, emit_log_entry, gpu_ids, http_client, job_notes, reconcile, shutdown};
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(20);
+const FAILURES_BEFORE_OUTAGE: u32 = 3;
+const PLATFORM_TIMEOUT: Duration = Duration::from_secs(5);
+const PUBLIC_ANCHORS: [&str; 2] = ["1.1.1.1", "8.8.8.8"];
+const HISTORY_DAYS: i64 = 30;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum OutageCause {
+    LocalLink, // The default gateway doesn't answer
+    Isp,       // The gateway answers, the internet doesn't
+    Platform,  // The internet answers, the platform doesn't
+}
+
+impl OutageCause {
+    fn label(self) -> &'static str {
+        match self {
+            OutageCause::LocalLink => "local network outage",
+            OutageCause::Isp => "ISP outage",
+            OutageCause::Platform => "platform outage",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ConnectivityIncident {
+    pub incident_id: String,
+    pub cause: OutageCause, // As diagnosed most recently
+    pub started_at: i64,    // unix seconds of the first failed check
+    pub ended_at: Option<i64>,
+    pub evidence: Vec<String>,
+    pub affected_jobs: Vec<String>,
+    #[serde(default)]
+    pub last_checked_at: Option<i64>, // unix seconds of the latest check that still failed
+}
+
+#[derive(Default)]
+struct Monitor {
+    failures: u32,
+    first_failure_at: Option<i64>,
+    running_jobs: Vec<String>,
+    open: Option<ConnectivityIncident>,
+}
+
+static MONITOR: Mutex<Option<Monitor>> = Mutex::new(None);
+
+async fn platform_reachable(app_handle: &AppHandle) -> Result<(), String> {
+    let platform_url = app_settings::current(app_handle).platform_url;
+    http_client::client()?
+        .get(format!("{}/time", platform_url.trim_end_matches('/')))
+        .timeout(PLATFORM_TIMEOUT)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+// The next hop for the default route, from the routing table.
+fn default_gateway() -> Option<String> {
+    if cfg!(target_os = "linux") {
+        // Destination and gateway are little-endian hex IPv4 addresses.
+        let table = std::fs::read_to_string("/proc/net/route").ok()?;
+        return table.lines().skip(1).find_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.get(1) != Some(&"00000000") {
+                return None;
+            }
+            let gateway = u32::from_str_radix(fields.get(2)?, 16).ok()?;
+            Some(std::net::Ipv4Addr::from(gateway.to_le_bytes()).to_string())
+        });
+    }
+    let output = std::process::Command::new("netstat").arg("-rn").output().ok()?;
+    String::from_utf8_lossy(&output.stdout).lines().find_map(|line| {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        match fields.as_slice() {
+            ["default", gateway, ..] if gateway.parse::<std::net::IpAddr>().is_ok() => Some(gateway.to_string()),
+            // Windows: destination, netmask, gateway, interface, metric.
+            ["0.0.0.0", "0.0.0.0", gateway, ..] if gateway.parse::<std::net::IpAddr>().is_ok() => Some(gateway.to_string()),
+            _ => None,
+        }
+    })
+}
+
+// Round trip of one echo request, or None without a reply within two seconds.
+fn ping(host: &str) -> Option<u64> {
+    let args: &[&str] = if cfg!(windows) {
+        &["-n", "1", "-w", "2000"]
+    } else if cfg!(target_os = "macos") {
+        &["-c", "1", "-t", "2"]
+    } else {
+        &["-c", "1", "-W", "2"]
+    };
+    let started = Instant::now();
+    let status = std::process::Command::new("ping").args(args).arg(host).output().ok()?.status;
+    status.success().then(|| started.elapsed().as_millis() as u64)
+}
+
+fn describe(host: &str, rtt: Option<u64>) -> String {
+    match rtt {
+        Some(ms) => format!("{} answered in {} ms", host, ms),
+        None => format!("{} did not answer", host),
+    }
+}
+
+async fn diagnose(platform_error: &str) -> (OutageCause, Vec<String>) {
+    let ladder = tauri::async_runtime::spawn_blocking(|| {
+        let gateway = default_gateway().map(|g| {
+            let rtt = ping(&g);
+            (g, rtt)
+        });
+        let anchors: Vec<(&str, Option<u64>)> = PUBLIC_ANCHORS.iter().map(|a| (*a, ping(a))).collect();
+        (gateway, anchors)
+    })
+    .await;
+    let Ok((gateway, anchors)) = ladder else {
+        return (OutageCause::Platform, vec![format!("Platform unreachable: {}", platform_error)]);
+    };
+
+    let mut evidence = Vec::new();
+    let gateway_up = match &gateway {
+        Some((g, rtt)) => {
+            evidence.push(format!("Default gateway {}", describe(g, *rtt)));
+            rtt.is_some()
+        }
+        None => {
+            evidence.push("No default route".to_string());
+            false
+        }
+    };
+    evidence.extend(anchors.iter().map(|(a, rtt)| describe(a, *rtt)));
+    evidence.push(format!("Platform unreachable: {}", platform_error));
+
+    // Anchors answering outranks a silent gateway: some routers ignore pings but route traffic.
+    let cause = if anchors.iter().any(|(_, rtt)| rtt.is_some()) {
+        OutageCause::Platform
+    } else if gateway_up {
+        OutageCause::Isp
+    } else {
+        OutageCause::LocalLink
+    };
+    (cause, evidence)
+}
+
+fn persist(app_handle: &AppHandle, incident: &ConnectivityIncident) {
+    if let Some(storage) = app_handle.try_state::<Storage>() {
+        if let Err(e) = storage.upsert_connectivity_incident(incident) {
+            eprintln!("{}", e);
+        }
+    }
+    if let Err(e) = app_handle.emit_all("connectivity_incident", incident.clone()) {
+        eprintln!("Failed to emit connectivity_incident event: {}", e);
+    }
+}
+
+fn format_time(unix: i64) -> String {
+    Utc.timestamp_opt(unix, 0).single().map_or_else(|| unix.to_string(), |t| t.format("%H:%M UTC").to_string())
+}
+
+async fn annotate_jobs(app_handle: &AppHandle, incident: &ConnectivityIncident) {
+    let Some(storage) = app_handle.try_state::<Storage>() else { return };
+    let summary = format!(
+        "Connectivity incident {}–{} ({}).",
+        format_time(incident.started_at),
+        incident.ended_at.map_or_else(|| "ongoing".to_string(), format_time),
+        incident.cause.label()
+    );
+    let text: String = summary.chars().take(job_notes::MAX_NOTE_CHARS).collect();
+    for job_id in &incident.affected_jobs {
+        match job_notes::send(app_handle, &storage, job_id.clone(), text.clone()).await {
+            Ok(note) if !note.delivered => eprintln!("Incident note for job {} was saved but not delivered.", job_id),
+            Ok(_) => {}
+            Err(e) => eprintln!("{}", e),
+        }
+    }
+}
+
+async fn running_jobs(app_handle: &AppHandle) -> Vec<String> {
+    gpu_ids::local_jobs(app_handle)
+        .await
+        .map(|jobs| jobs.into_iter().filter(|j| j.status == "running").map(|j| j.id).collect())
+        .unwrap_or_default()
+}
+
+async fn check(app_handle: &AppHandle) {
+    let result = platform_reachable(app_handle).await;
+    let now = unix_now();
+
+    if result.is_ok() {
+        let closed = {
+            let mut guard = MONITOR.lock().unwrap();
+            let monitor = guard.get_or_insert_with(Monitor::default);
+            let closed = monitor.open.take();
+            *monitor = Monitor::default();
+            closed
+        };
+        if let Some(mut incident) = closed {
+            incident.ended_at = Some(now);
+            persist(app_handle, &incident);
+            emit_log_entry(
+                app_handle,
+                "status",
+                format!("Connectivity restored after {} min ({}).", (now - incident.started_at + 59) / 60, incident.cause.label()),
+            );
+            annotate_jobs(app_handle, &incident).await;
+            reconcile::run(app_handle, &incident).await;
+        }
+        return;
+    }
+
+    let error = result.unwrap_err();
+    let first_failure = MONITOR.lock().unwrap().as_ref().is_none_or(|m| m.failures == 0);
+    if first_failure {
+        reconcile::capture(app_handle).await;
+    }
+    let running = running_jobs(app_handle).await;
+    let sustained = {
+        let mut guard = MONITOR.lock().unwrap();
+        let monitor = guard.get_or_insert_with(Monitor::default);
+        monitor.failures += 1;
+        monitor.first_failure_at.get_or_insert(now);
+        for job_id in running {
+            if !monitor.running_jobs.contains(&job_id) {
+                monitor.running_jobs.push(job_id);
+            }
+        }
+        monitor.failures >= FAILURES_BEFORE_OUTAGE
+    };
+    if !sustained {
+        return;
+    }
+
+    let (cause, evidence) = diagnose(&error).await;
+    let (incident, opened) = {
+        let mut guard = MONITOR.lock().unwrap();
+        let monitor = guard.get_or_insert_with(Monitor::default);
+        let started_at = monitor.first_failure_at.unwrap_or(now);
+        let opened = monitor.open.is_none();
+        let incident = monitor.open.get_or_insert_with(|| ConnectivityIncident {
+            incident_id: format!("connectivity-{}", started_at),
+            cause,
+            started_at,
+            ended_at: None,
+            evidence: Vec::new(),
+            affected_jobs: Vec::new(),
+            last_checked_at: None,
+        });
+        // Only a change in diagnosis adds evidence, so a long outage doesn't grow the record.
+        if opened || incident.cause != cause {
+            incident.cause = cause;
+            incident.evidence.extend(evidence);
+        }
+        incident.affected_jobs = monitor.running_jobs.clone();
+        incident.last_checked_at = Some(now);
+        (incident.clone(), opened)
+    };
+    persist(app_handle, &incident);
+    if opened {
+        emit_log_entry(app_handle, "error", format!("Lost connectivity to the platform: {}.", incident.cause.label()));
+    }
+}
+
+// Ends incidents left open by the previous run at their last failed check.
+fn close_stale(app_handle: &AppHandle) {
+    let Some(storage) = app_handle.try_state::<Storage>() else { return };
+    let now = unix_now();
+    let open = match storage.connectivity_incidents(now, now + 1) {
+        Ok(incidents) => incidents.into_iter().filter(|i| i.ended_at.is_none()),
+        Err(e) => {
+            eprintln!("{}", e);
+            return;
+        }
+    };
+    for mut incident in open {
+        incident.ended_at = Some(incident.last_checked_at.unwrap_or(incident.started_at).max(incident.started_at));
+        if let Err(e) = storage.upsert_connectivity_incident(&incident) {
+            eprintln!("{}", e);
+        }
+    }
+}
+
+pub fn spawn_connectivity_monitor(app_handle: AppHandle) {
+    close_stale(&app_handle);
+    tauri::async_runtime::spawn(async move {
+        loop {
+            if !shutdown::sleep(CHECK_INTERVAL).await {
+                break;
+            }
+            check(&app_handle).await;
+        }
+    });
+}
+
+// Total seconds of [from, to) covered by connectivity incidents. Overlapping incidents are
+// merged first so no second is counted twice.
+pub fn outage_secs(storage: &Storage, from: i64, to: i64) -> Result<i64, String> {
+    let mut intervals: Vec<(i64, i64)> = storage
+        .connectivity_incidents(from, to)?
+        .iter()
+        .map(|i| (i.started_at.max(from), i.ended_at.unwrap_or(to).min(to)))
+        .filter(|(start, end)| end > start)
+        .collect();
+    intervals.sort_unstable();
+    let mut total = 0;
+    let mut current: Option<(i64, i64)> = None;
+    for (start, end) in intervals {
+        match &mut current {
+            Some((_, current_end)) if start <= *current_end => *current_end = (*current_end).max(end),
+            _ => {
+                total += current.map_or(0, |(s, e)| e - s);
+                current = Some((start, end));
+            }
+        }
+    }
+    Ok(total + current.map_or(0, |(s, e)| e - s))
+}
+
+// Newest first, over the last 30 days.
+#[tauri::command]
+pub async fn get_connectivity_incidents(app_handle: AppHandle) -> Result<Vec<ConnectivityIncident>, String> {
+    let storage = app_handle.try_state::<Storage>().ok_or_else(|| "Local storage is unavailable.".to_string())?;
+    let now = unix_now();
+    storage.connectivity_incidents(now - HISTORY_DAYS * 24 * 60 * 60, now)
+}
@@ -0,0 +1,254 @@
+// Opt-in crash and command-failure reporting.
+//
+// When the user has enabled crash reporting, panics and failed daemon commands are written
+// to a local queue after scrubbing wallet addresses, tokens and usernames in paths. Queued
+// reports are uploaded to the configured self-hosted endpoint and can be inspected or
+// deleted by the user before that happens.
+//
+// A crash loop must not fill the disk or flood the endpoint: a report with the same signature
+// as a queued one only bumps that report's count, the queue keeps the newest `MAX_STORED_REPORTS`,
+// and uploads run at most once per `MIN_UPLOAD_INTERVAL` (remembered across restarts) with a
+// bounded batch.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::OnceLock;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager};
+
+use crate::app_settings::AppSettingsState;
+use crate::{app_settings, clock, emit_log_entry, get_timestamp, http_client, redaction};
+
+const REPORTS_DIR_NAME: &str = "crash_reports";
+const LAST_UPLOAD_FILE_NAME: &str = "last_upload";
+const MAX_STORED_REPORTS: usize = 50;
+const MAX_UPLOADS_PER_RUN: usize = 20;
+const MIN_UPLOAD_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+static REPORT_COUNTER: AtomicU32 = AtomicU32::new(0);
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CrashReport {
+    pub id: String,
+    pub kind: String, // "panic" | "command_failure"
+    pub created_at: String,
+    #[serde(default)]
+    pub corrected_created_at: Option<String>, // Platform time, if the clock offset was known
+    pub app_version: String,
+    pub os: String,
+    pub message: String,
+    pub context: Option<String>, // Panic location/backtrace or the failing command
+    // Same kind, message and place; repeats of a queued report are counted on it instead.
+    #[serde(default)]
+    pub signature: String,
+    #[serde(default = "one")]
+    pub occurrences: u32,
+    #[serde(default)]
+    pub last_seen_at: Option<String>,
+}
+
+fn one() -> u32 {
+    1
+}
+
+fn now_unix() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+// The backtrace below a panic's location differs with inlining and threads, so only the first
+// line of the context counts.
+fn signature(kind: &str, message: &str, context: Option<&str>) -> String {
+    let place = context.and_then(|c| c.lines().next()).unwrap_or_default();
+    let digest = Sha256::digest(format!("{}\n{}\n{}", kind, place, message).as_bytes());
+    hex::encode(&digest[..8])
+}
+
+fn reports_dir(app_handle: &AppHandle) -> Option<PathBuf> {
+    app_handle.path_resolver().app_data_dir().map(|dir| dir.join(REPORTS_DIR_NAME))
+}
+
+// Replaces anything that could identify the provider or grant access to their accounts.
+pub fn scrub(text: &str) -> String {
+    static RULES: OnceLock<Vec<(Regex, &'static str)>> = OnceLock::new();
+    let rules = RULES.get_or_init(|| {
+        // Home directories: keep the shape of the path, drop the username.
+        let mut rules = vec![(Regex::new(r"(/Users/|/home/|(?i:[A-Z]:\\Users\\))[^/\\\s]+").unwrap(), "${1}<user>")];
+        // Tokens, key=value secrets and wallet addresses, as in log redaction.
+        rules.extend(redaction::BUILTIN_RULES.iter().map(|(_, pattern, replacement)| (Regex::new(pattern).unwrap(), *replacement)));
+        rules
+    });
+    let mut scrubbed = text.to_string();
+    for (re, replacement) in rules {
+        scrubbed = re.replace_all(&scrubbed, *replacement).into_owned();
+    }
+    scrubbed
+}
+
+// Settings may be locked by the very code that panicked, so never block here.
+fn reporting_enabled(app_handle: &AppHandle) -> bool {
+    app_handle
+        .try_state::<AppSettingsState>()
+        .and_then(|state| state.settings.try_lock().ok().map(|s| s.crash_reporting_enabled))
+        .unwrap_or(false)
+}
+
+fn queue_report(app_handle: &AppHandle, kind: &str, message: &str, context: Option<String>) -> Result<(), String> {
+    let dir = reports_dir(app_handle).ok_or_else(|| "App data directory is unavailable.".to_string())?;
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+
+    let message = scrub(message);
+    let context = context.map(|c| scrub(&c));
+    let signature = signature(kind, &message, context.as_deref());
+    if let Some(mut existing) = read_reports(&dir).into_iter().find(|r| r.signature == signature) {
+        existing.occurrences = existing.occurrences.saturating_add(1);
+        existing.last_seen_at = Some(get_timestamp());
+        return write_report(&dir, &existing);
+    }
+
+    let millis = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis()).unwrap_or(0);
+    let report = CrashReport {
+        id: format!("{}-{}-{}", kind, millis, REPORT_COUNTER.fetch_add(1, Ordering::Relaxed)),
+        kind: kind.to_string(),
+        created_at: get_timestamp(),
+        corrected_created_at: clock::corrected_timestamp(),
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        os: format!("{} {}", std::env::consts::OS, std::env::consts::ARCH),
+        message,
+        context,
+        signature,
+        occurrences: 1,
+        last_seen_at: None,
+    };
+    write_report(&dir, &report)?;
+
+    // Oldest first; drop whatever is beyond the cap.
+    let reports = read_reports(&dir);
+    for old in reports.iter().take(reports.len().saturating_sub(MAX_STORED_REPORTS)) {
+        let _ = fs::remove_file(dir.join(format!("{}.json", old.id)));
+    }
+    Ok(())
+}
+
+fn write_report(dir: &Path, report: &CrashReport) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(report).map_err(|e| format!("Failed to serialize report: {}", e))?;
+    fs::write(dir.join(format!("{}.json", report.id)), json).map_err(|e| format!("Failed to write report: {}", e))
+}
+
+pub fn install_panic_hook(app_handle: AppHandle) {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        if reporting_enabled(&app_handle) {
+            let message = info
+                .payload()
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| info.payload().downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "panic with non-string payload".to_string());
+            let location = info.location().map(|l| format!("{}:{}", l.file(), l.line())).unwrap_or_default();
+            let backtrace = std::backtrace::Backtrace::force_capture();
+            let context = format!("at {}\n{}", location, backtrace);
+            if let Err(e) = queue_report(&app_handle, "panic", &message, Some(context)) {
+                eprintln!("Failed to record panic report: {}", e);
+            }
+        }
+        default_hook(info);
+    }));
+}
+
+pub fn record_command_failure(app_handle: &AppHandle, command: &str, error: &str) {
+    if !reporting_enabled(app_handle) {
+        return;
+    }
+    if let Err(e) = queue_report(app_handle, "command_failure", error, Some(command.to_string())) {
+        eprintln!("Failed to record command failure report: {}", e);
+    }
+}
+
+fn read_pending(app_handle: &AppHandle) -> Vec<CrashReport> {
+    reports_dir(app_handle).map(|dir| read_reports(&dir)).unwrap_or_default()
+}
+
+fn read_reports(dir: &Path) -> Vec<CrashReport> {
+    let Ok(entries) = fs::read_dir(dir) else { return Vec::new() };
+    let mut reports: Vec<CrashReport> = entries
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|e| fs::read_to_string(e.path()).ok())
+        .filter_map(|contents| serde_json::from_str(&contents).ok())
+        .collect();
+    reports.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+    reports
+}
+
+fn report_path(app_handle: &AppHandle, report_id: &str) -> Result<PathBuf, String> {
+    // IDs come from the frontend; refuse anything that could escape the reports directory.
+    if report_id.contains(['/', '\\']) || report_id.contains("..") {
+        return Err(format!("Invalid report ID '{}'.", report_id));
+    }
+    reports_dir(app_handle)
+        .map(|dir| dir.join(format!("{}.json", report_id)))
+        .ok_or_else(|| "App data directory is unavailable.".to_string())
+}
+
+#[tauri::command]
+pub async fn get_pending_reports(app_handle: AppHandle) -> Result<Vec<CrashReport>, String> {
+    Ok(read_pending(&app_handle))
+}
+
+#[tauri::command]
+pub async fn delete_report(app_handle: AppHandle, report_id: String) -> Result<(), String> {
+    let path = report_path(&app_handle, &report_id)?;
+    fs::remove_file(&path).map_err(|e| format!("Failed to delete report {}: {}", report_id, e))
+}
+
+#[tauri::command]
+pub async fn upload_pending_reports(app_handle: AppHandle) -> Result<usize, String> {
+    let settings = app_settings::current(&app_handle);
+    if !settings.crash_reporting_enabled {
+        return Err("Crash reporting is disabled.".to_string());
+    }
+    let endpoint = settings
+        .crash_report_endpoint
+        .ok_or_else(|| "No crash report endpoint is configured.".to_string())?;
+    let pending = read_pending(&app_handle);
+    if pending.is_empty() {
+        return Ok(0);
+    }
+    let dir = reports_dir(&app_handle).ok_or_else(|| "App data directory is unavailable.".to_string())?;
+    let last_upload_path = dir.join(LAST_UPLOAD_FILE_NAME);
+    let last_upload: Option<u64> = fs::read_to_string(&last_upload_path).ok().and_then(|s| s.trim().parse().ok());
+    if let Some(wait) = last_upload.map(|at| (at + MIN_UPLOAD_INTERVAL.as_secs()).saturating_sub(now_unix())).filter(|w| *w > 0) {
+        return Err(format!("Crash reports were uploaded recently; try again in {} minute(s).", (wait + 59) / 60));
+    }
+    let _ = fs::write(&last_upload_path, now_unix().to_string());
+
+    let client = http_client::client()?;
+    let mut uploaded = 0;
+    for report in pending.into_iter().take(MAX_UPLOADS_PER_RUN) {
+        let result = client
+            .post(&endpoint)
+            .json(&report)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status());
+        match result {
+            Ok(_) => {
+                let _ = fs::remove_file(report_path(&app_handle, &report.id)?);
+                uploaded += 1;
+            }
+            Err(e) => {
+                // Keep the rest queued; the endpoint is probably down.
+                emit_log_entry(&app_handle, "error", format!("Failed to upload crash report {}: {}", report.id, e));
+                break;
+            }
+        }
+    }
+    if uploaded > 0 {
+        emit_log_entry(&app_handle, "status", format!("Uploaded {} crash report(s).", uploaded));
+    }
+    Ok(uploaded)
+}
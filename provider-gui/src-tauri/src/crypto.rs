@@ -0,0 +1,62 @@
+// Passphrase-based encryption for data that leaves the machine (settings sync, backups).
+//
+// The key is derived with Argon2id from the passphrase and a random salt, and the payload is
+// sealed with ChaCha20-Poly1305. Salt and nonce travel with the ciphertext in a small JSON
+// envelope, hex-encoded.
+
+use argon2::Argon2;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use serde::{Deserialize, Serialize};
+
+const ENVELOPE_VERSION: u32 = 1;
+const SALT_LEN: usize = 16;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct EncryptedEnvelope {
+    pub version: u32,
+    pub salt: String,
+    pub nonce: String,
+    pub ciphertext: String,
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<[u8; 32], String> {
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| format!("Key derivation failed: {}", e))?;
+    Ok(key)
+}
+
+pub fn encrypt(plaintext: &[u8], passphrase: &str) -> Result<EncryptedEnvelope, String> {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let key = derive_key(passphrase, &salt)?;
+    let cipher = ChaCha20Poly1305::new(Key::from_slice(&key));
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = cipher.encrypt(&nonce, plaintext).map_err(|_| "Encryption failed.".to_string())?;
+    Ok(EncryptedEnvelope {
+        version: ENVELOPE_VERSION,
+        salt: hex::encode(salt),
+        nonce: hex::encode(nonce),
+        ciphertext: hex::encode(ciphertext),
+    })
+}
+
+pub fn decrypt(envelope: &EncryptedEnvelope, passphrase: &str) -> Result<Vec<u8>, String> {
+    if envelope.version != ENVELOPE_VERSION {
+        return Err(format!("Unsupported encryption envelope version {}.", envelope.version));
+    }
+    let salt = hex::decode(&envelope.salt).map_err(|_| "Corrupt envelope salt.".to_string())?;
+    let nonce = hex::decode(&envelope.nonce).map_err(|_| "Corrupt envelope nonce.".to_string())?;
+    let ciphertext = hex::decode(&envelope.ciphertext).map_err(|_| "Corrupt envelope ciphertext.".to_string())?;
+    if nonce.len() != 12 {
+        return Err("Corrupt envelope nonce.".to_string());
+    }
+    let key = derive_key(passphrase, &salt)?;
+    ChaCha20Poly1305::new(Key::from_slice(&key))
+        .decrypt(Nonce::from_slice(&nonce), ciphertext.as_ref())
+        // A wrong passphrase and tampered data are indistinguishable by design.
+        .map_err(|_| "Decryption failed: wrong passphrase or corrupted data.".to_string())
+}
@@ -0,0 +1,197 @@
+// Persistent control connection to the running daemon.
+//
+// Every daemon call used to spawn a fresh CLI process, which is slow when polling and races the
+// running daemon over its own state. When the `grpc_ipc` feature flag is on, the daemon is
+// started with a loopback control address and a per-launch bearer token in its environment
+// (`PROVIDERD_CONTROL_ADDR`, `PROVIDERD_CONTROL_TOKEN`) and serves `POST /v1/invoke`, which takes
+// the CLI arguments and answers with the JSON the CLI would have printed. One HTTP client with
+// keep-alive is reused for every call. The CLI is still used when the connection can't be
+// made: before the daemon listens, for a while after a connection failure, and for the rest of
+// a launch when the daemon doesn't serve the endpoint (older daemons). A call the daemon
+// received and failed is not retried through the CLI, since it may already have taken effect.
+// Calls are bounded by `CALL_TIMEOUT`, except the commands in `UNBOUNDED_COMMANDS`, which pull
+// images or move jobs and take as long as they take, as they always could through the CLI.
+
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::OsRng;
+use serde::Serialize;
+use serde_json::json;
+use std::collections::HashMap;
+use std::net::{Ipv4Addr, SocketAddr, TcpListener};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tauri::AppHandle;
+
+use crate::{emit_log_entry, feature_flags, wsl};
+
+const CALL_TIMEOUT: Duration = Duration::from_secs(30);
+const UNBOUNDED_COMMANDS: [&str; 4] = ["--pull-image-json", "--run-benchmark-json", "--checkpoint-job", "--restore-job"];
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(2);
+const RETRY_AFTER: Duration = Duration::from_secs(30);
+const ADDR_ENV: &str = "PROVIDERD_CONTROL_ADDR";
+const TOKEN_ENV: &str = "PROVIDERD_CONTROL_TOKEN";
+
+// The control endpoint of the current daemon launch.
+struct Endpoint {
+    addr: SocketAddr,
+    token: String,
+    unsupported: bool,
+    retry_at: Option<Instant>,
+    connected: bool,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct DaemonIpcStatus {
+    pub enabled: bool,
+    pub address: Option<String>,
+    pub connected: bool,
+    pub unsupported: bool,
+    pub socket_calls: u64,
+    pub cli_fallbacks: u64,
+}
+
+static ENDPOINT: Mutex<Option<Endpoint>> = Mutex::new(None);
+static CLIENT: OnceLock<Result<reqwest::Client, String>> = OnceLock::new();
+static SOCKET_CALLS: AtomicU64 = AtomicU64::new(0);
+static CLI_FALLBACKS: AtomicU64 = AtomicU64::new(0);
+
+// Never proxied, unlike platform traffic (see `http_client`).
+fn client() -> Result<&'static reqwest::Client, String> {
+    CLIENT
+        .get_or_init(|| {
+            reqwest::Client::builder()
+                .no_proxy()
+                .connect_timeout(CONNECT_TIMEOUT)
+                .pool_idle_timeout(None)
+                .tcp_keepalive(Duration::from_secs(30))
+                .build()
+                .map_err(|e| format!("Failed to create the daemon control client: {}", e))
+        })
+        .as_ref()
+        .map_err(|e| e.clone())
+}
+
+fn free_port() -> Option<u16> {
+    TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).and_then(|l| l.local_addr()).map(|a| a.port()).ok()
+}
+
+// Environment for a daemon about to be started; empty when the control connection is off.
+pub fn prepare(app_handle: &AppHandle) -> HashMap<String, String> {
+    let mut endpoint = ENDPOINT.lock().unwrap();
+    *endpoint = None;
+    if !feature_flags::is_enabled(app_handle, feature_flags::GRPC_IPC) {
+        return HashMap::new();
+    }
+    let Some(port) = free_port() else {
+        emit_log_entry(app_handle, "error", "No free local port for the daemon control connection; using the CLI.".to_string());
+        return HashMap::new();
+    };
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    let addr = SocketAddr::from((Ipv4Addr::LOCALHOST, port));
+    let token = hex::encode(bytes);
+    let mut env = HashMap::from([(ADDR_ENV.to_string(), addr.to_string()), (TOKEN_ENV.to_string(), token.clone())]);
+    if wsl::daemon_in_wsl(app_handle) {
+        // WSL only passes on the variables listed here; loopback is forwarded to the distribution.
+        env.insert("WSLENV".to_string(), format!("{}:{}", ADDR_ENV, TOKEN_ENV));
+    }
+    *endpoint = Some(Endpoint { addr, token, unsupported: false, retry_at: None, connected: false });
+    env
+}
+
+// Called when the daemon stops; the next launch gets a new endpoint.
+pub fn reset() {
+    *ENDPOINT.lock().unwrap() = None;
+}
+
+fn usable(app_handle: &AppHandle) -> Option<(SocketAddr, String)> {
+    let endpoint = ENDPOINT.lock().unwrap();
+    let endpoint = endpoint.as_ref()?;
+    if endpoint.unsupported || endpoint.retry_at.is_some_and(|t| Instant::now() < t) {
+        return None;
+    }
+    feature_flags::is_enabled(app_handle, feature_flags::GRPC_IPC).then(|| (endpoint.addr, endpoint.token.clone()))
+}
+
+fn update(f: impl FnOnce(&mut Endpoint)) {
+    if let Some(endpoint) = ENDPOINT.lock().unwrap().as_mut() {
+        f(endpoint);
+    }
+}
+
+// The daemon's raw JSON answer to `args` over the control connection, or None when the call
+// should go through the CLI instead.
+pub async fn invoke(app_handle: &AppHandle, args: &[&str]) -> Option<Result<String, String>> {
+    let (addr, token) = usable(app_handle)?;
+    let client = match client() {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("{}", e);
+            return None;
+        }
+    };
+    let mut request = client
+        .post(format!("http://{}/v1/invoke", addr))
+        .bearer_auth(&token)
+        .json(&json!({ "args": wsl::translate_args(app_handle, args) }));
+    if !args.first().is_some_and(|command| UNBOUNDED_COMMANDS.contains(command)) {
+        request = request.timeout(CALL_TIMEOUT);
+    }
+    let response = request.send().await;
+    let response = match response {
+        Ok(response) => response,
+        Err(e) if e.is_connect() => {
+            // Not listening (yet); nothing reached the daemon, so the CLI can take the call.
+            let was_connected = ENDPOINT.lock().unwrap().as_ref().is_some_and(|e| e.connected);
+            update(|endpoint| {
+                endpoint.connected = false;
+                endpoint.retry_at = Some(Instant::now() + RETRY_AFTER);
+            });
+            if was_connected {
+                emit_log_entry(app_handle, "error", format!("Lost the daemon control connection ({}); using the CLI for now.", e));
+            }
+            CLI_FALLBACKS.fetch_add(1, Ordering::Relaxed);
+            return None;
+        }
+        Err(e) => return Some(Err(format!("Daemon control call {:?} failed: {}", args, e))),
+    };
+    let status = response.status();
+    if matches!(status.as_u16(), 404 | 405) {
+        update(|endpoint| endpoint.unsupported = true);
+        emit_log_entry(app_handle, "status", "The daemon doesn't serve a control connection; using the CLI.".to_string());
+        CLI_FALLBACKS.fetch_add(1, Ordering::Relaxed);
+        return None;
+    }
+    let newly_connected = ENDPOINT.lock().unwrap().as_ref().is_some_and(|e| !e.connected);
+    update(|endpoint| {
+        endpoint.connected = true;
+        endpoint.retry_at = None;
+    });
+    if newly_connected {
+        emit_log_entry(app_handle, "status", format!("Connected to the daemon control endpoint at {}.", addr));
+    }
+    SOCKET_CALLS.fetch_add(1, Ordering::Relaxed);
+    let body = match response.text().await {
+        Ok(body) => body,
+        Err(e) => return Some(Err(format!("Failed to read the daemon's answer to {:?}: {}", args, e))),
+    };
+    if status.is_success() {
+        Some(Ok(body))
+    } else {
+        Some(Err(format!("Daemon command {:?} failed with status {}: {}", args, status, body.trim())))
+    }
+}
+
+#[tauri::command]
+pub async fn get_daemon_ipc_status(app_handle: AppHandle) -> Result<DaemonIpcStatus, String> {
+    let endpoint = ENDPOINT.lock().unwrap();
+    Ok(DaemonIpcStatus {
+        enabled: feature_flags::is_enabled(&app_handle, feature_flags::GRPC_IPC),
+        address: endpoint.as_ref().map(|e| e.addr.to_string()),
+        connected: endpoint.as_ref().is_some_and(|e| e.connected),
+        unsupported: endpoint.as_ref().is_some_and(|e| e.unsupported),
+        socket_calls: SOCKET_CALLS.load(Ordering::Relaxed),
+        cli_fallbacks: CLI_FALLBACKS.load(Ordering::Relaxed),
+    })
+}
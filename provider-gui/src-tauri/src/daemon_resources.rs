@@ -0,0 +1,237 @@
+// Memory and CPU watchdog on the daemon process.
+//
+// A daemon that leaks memory or spins a core doesn't fail, it slowly starves the host and the
+// jobs on it. The sidecar's resident memory and CPU share are sampled every few seconds; once
+// either stays above its limit for the configured period, a warning goes out, a diagnostic
+// bundle is written to the app data directory (the newest ten are kept; recent samples are part
+// of every bundle, see `diagnostics`) and, if enabled, the daemon is drained and restarted,
+// interrupting jobs still running after the grace period (see `daemon_restart`). Each episode is
+// acted on once, until usage drops back under the limits. A daemon running inside WSL isn't a
+// local process and isn't watched.
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use sysinfo::{Pid, System};
+use tauri::{AppHandle, Manager};
+
+use crate::daemon_restart::{self, RestartOutcome};
+use crate::storage::unix_now;
+use crate::tasks::{self, TaskRequest};
+use crate::{app_settings, emit_log_entry, get_timestamp, plugins, shutdown, wsl, DaemonState};
+
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(10);
+const MAX_SAMPLES: usize = 180; // Half an hour
+const SNAPSHOT_DIR_NAME: &str = "diagnostics";
+const SNAPSHOT_PREFIX: &str = "daemon-resources-";
+const MAX_SNAPSHOTS: usize = 10;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct DaemonResourceLimits {
+    pub enabled: bool,
+    pub max_rss_mb: u64,
+    pub max_cpu_percent: f32, // Share of the whole machine
+    // How long usage must stay above a limit before anything happens.
+    pub sustained_secs: u64,
+    // Drain and restart the daemon, besides warning.
+    pub restart: bool,
+    pub restart_grace_minutes: u32,
+}
+
+impl Default for DaemonResourceLimits {
+    fn default() -> Self {
+        DaemonResourceLimits { enabled: true, max_rss_mb: 4096, max_cpu_percent: 80.0, sustained_secs: 300, restart: false, restart_grace_minutes: 15 }
+    }
+}
+
+impl DaemonResourceLimits {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_rss_mb == 0 {
+            return Err("The daemon memory limit must be above zero.".to_string());
+        }
+        if !(self.max_cpu_percent > 0.0 && self.max_cpu_percent <= 100.0) {
+            return Err("The daemon CPU limit must be between 0 and 100%.".to_string());
+        }
+        if self.sustained_secs < SAMPLE_INTERVAL.as_secs() {
+            return Err(format!("Limits must be exceeded for at least {} seconds before acting.", SAMPLE_INTERVAL.as_secs()));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ResourceSample {
+    pub sampled_at: String,
+    pub rss_mb: u64,
+    pub cpu_percent: f32,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct DaemonResourceStatus {
+    pub pid: Option<u32>,
+    pub over_limit_since: Option<String>,
+    pub samples: Vec<ResourceSample>, // Oldest first
+}
+
+#[derive(Default)]
+struct Monitor {
+    pid: Option<u32>,
+    samples: VecDeque<ResourceSample>,
+    over_since: Option<(Instant, String)>,
+    acted: bool, // For the current episode
+}
+
+static MONITOR: Mutex<Option<Monitor>> = Mutex::new(None);
+
+fn daemon_pid(app_handle: &AppHandle) -> Option<u32> {
+    if wsl::daemon_in_wsl(app_handle) {
+        return None;
+    }
+    app_handle.state::<DaemonState>().process.lock().unwrap().as_ref().map(|child| child.pid())
+}
+
+fn sample(system: &mut System, pid: u32, cpus: f32) -> Option<ResourceSample> {
+    let pid = Pid::from_u32(pid);
+    if !system.refresh_process(pid) {
+        return None;
+    }
+    let process = system.process(pid)?;
+    Some(ResourceSample { sampled_at: get_timestamp(), rss_mb: process.memory() / (1024 * 1024), cpu_percent: process.cpu_usage() / cpus })
+}
+
+// Which limit `sample` exceeds, if any.
+fn breach(limits: &DaemonResourceLimits, sample: &ResourceSample) -> Option<String> {
+    if sample.rss_mb > limits.max_rss_mb {
+        Some(format!("is using {} MB of memory (limit {} MB)", sample.rss_mb, limits.max_rss_mb))
+    } else if sample.cpu_percent > limits.max_cpu_percent {
+        Some(format!("is using {:.0}% CPU (limit {:.0}%)", sample.cpu_percent, limits.max_cpu_percent))
+    } else {
+        None
+    }
+}
+
+// Deletes all but the newest `keep` watchdog bundles in `dir`. Bundles written by hand from the
+// diagnostics page are left alone.
+fn prune_snapshots(dir: &Path, keep: usize) {
+    let Ok(entries) = std::fs::read_dir(dir) else { return };
+    let mut bundles: Vec<(u64, PathBuf)> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let stamp = name.strip_prefix(SNAPSHOT_PREFIX)?.strip_suffix(".tar.gz")?.parse().ok()?;
+            Some((stamp, entry.path()))
+        })
+        .collect();
+    bundles.sort_unstable_by(|a, b| b.0.cmp(&a.0));
+    for (_, path) in bundles.into_iter().skip(keep) {
+        if let Err(e) = std::fs::remove_file(&path) {
+            eprintln!("Failed to remove {}: {}", path.display(), e);
+        }
+    }
+}
+
+fn snapshot(app_handle: &AppHandle) {
+    let Some(dir) = app_handle.path_resolver().app_data_dir().map(|dir| dir.join(SNAPSHOT_DIR_NAME)) else { return };
+    if let Err(e) = std::fs::create_dir_all(&dir) {
+        eprintln!("Failed to create {}: {}", dir.display(), e);
+        return;
+    }
+    prune_snapshots(&dir, MAX_SNAPSHOTS - 1);
+    let path = dir.join(format!("{}{}.tar.gz", SNAPSHOT_PREFIX, unix_now())).to_string_lossy().to_string();
+    tasks::submit(app_handle, TaskRequest::DiagnosticBundle { path, include_session_trace: false, include_notes: false });
+}
+
+fn restart(app_handle: &AppHandle, grace_minutes: u32) {
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        let grace = Duration::from_secs(u64::from(grace_minutes) * 60);
+        let result = daemon_restart::drain_and_restart(&app_handle, "Daemon resource limit exceeded", grace, true).await;
+        let message = match result {
+            Some((RestartOutcome::Restarted, _, _)) => "Daemon restarted after exceeding its resource limits.".to_string(),
+            Some((RestartOutcome::Forced, running, _)) => format!("Daemon restarted after exceeding its resource limits; {} running job(s) were interrupted.", running),
+            Some((RestartOutcome::Failed, _, detail)) => format!("Failed to restart the daemon after it exceeded its resource limits: {}", detail.unwrap_or_default()),
+            Some(_) | None => return,
+        };
+        emit_log_entry(&app_handle, "status", message);
+    });
+}
+
+fn act(app_handle: &AppHandle, limits: &DaemonResourceLimits, problem: &str, since: &str) {
+    let message = format!("The daemon {} and has been over its limits since {}.", problem, since);
+    emit_log_entry(app_handle, "error", message.clone());
+    let warning = json!({ "message": message, "since": since, "restarting": limits.restart, "detected_at": get_timestamp() });
+    plugins::notify(app_handle, "daemon_resource_warning", &warning, false);
+    if let Err(e) = app_handle.emit_all("daemon_resource_warning", warning) {
+        eprintln!("Failed to emit daemon_resource_warning event: {}", e);
+    }
+    snapshot(app_handle);
+    if limits.restart {
+        restart(app_handle, limits.restart_grace_minutes);
+    }
+}
+
+pub fn spawn_resource_monitor(app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut system = System::new();
+        let cpus = std::thread::available_parallelism().map_or(1, |n| n.get()) as f32;
+        loop {
+            let limits = app_settings::current(&app_handle).daemon_resources;
+            let pid = daemon_pid(&app_handle);
+            let latest = pid.and_then(|pid| sample(&mut system, pid, cpus));
+            let mut trip = None;
+            {
+                let mut monitor = MONITOR.lock().unwrap();
+                let monitor = monitor.get_or_insert_with(Monitor::default);
+                if monitor.pid != pid {
+                    *monitor = Monitor { pid, ..Monitor::default() };
+                }
+                if let Some(latest) = latest {
+                    match breach(&limits, &latest).filter(|_| limits.enabled) {
+                        Some(problem) => {
+                            let (started, since) = monitor.over_since.get_or_insert_with(|| (Instant::now(), latest.sampled_at.clone()));
+                            if !monitor.acted && started.elapsed() >= Duration::from_secs(limits.sustained_secs) {
+                                monitor.acted = true;
+                                trip = Some((problem, since.clone()));
+                            }
+                        }
+                        None => {
+                            monitor.over_since = None;
+                            monitor.acted = false;
+                        }
+                    }
+                    if monitor.samples.len() == MAX_SAMPLES {
+                        monitor.samples.pop_front();
+                    }
+                    monitor.samples.push_back(latest);
+                }
+            }
+            if let Some((problem, since)) = trip {
+                act(&app_handle, &limits, &problem, &since);
+            }
+            if !shutdown::sleep(SAMPLE_INTERVAL).await {
+                break;
+            }
+        }
+    });
+}
+
+pub fn status() -> DaemonResourceStatus {
+    let monitor = MONITOR.lock().unwrap();
+    match monitor.as_ref() {
+        Some(monitor) => DaemonResourceStatus {
+            pid: monitor.pid,
+            over_limit_since: monitor.over_since.as_ref().map(|(_, since)| since.clone()),
+            samples: monitor.samples.iter().cloned().collect(),
+        },
+        None => DaemonResourceStatus { pid: None, over_limit_since: None, samples: Vec::new() },
+    }
+}
+
+#[tauri::command]
+pub async fn get_daemon_resources() -> Result<DaemonResourceStatus, String> {
+    Ok(status())
+}
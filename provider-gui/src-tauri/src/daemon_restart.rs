@@ -0,0 +1,312 @@
+// Scheduled daemon restarts.
+//
+// Some providers restart the daemon nightly to clear slow leaks. At each scheduled time the
+// daemon is drained first: new rentals are held and running jobs get the grace period to finish.
+// If jobs are still running when it ends, the restart is skipped, or with `force_after_grace`
+// goes ahead and interrupts them. Restart times are wall-clock times in the schedule's own zone
+// and are resolved like rental schedule windows across DST changes (see `schedule`). Every
+// occurrence, including skipped ones, is logged and kept in local storage.
+
+use chrono::{DateTime, Datelike, NaiveTime, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+
+use crate::storage::{unix_now, Storage};
+use crate::{
+    announcements, app_settings, emit_log_entry, get_timestamp, gpu_ids, lockdown, schedule, shutdown, start_daemon, stop_daemon,
+    DaemonState,
+};
+
+// Restart times are exact; this only bounds how late a settings change is noticed.
+const MAX_SLEEP: Duration = Duration::from_secs(60);
+const DRAIN_POLL: Duration = Duration::from_secs(15);
+const STATUS_POLL: Duration = Duration::from_millis(250);
+const STOP_TIMEOUT: Duration = Duration::from_secs(60);
+const MAX_GRACE_MINUTES: u32 = 12 * 60;
+const HISTORY_LIMIT: usize = 100;
+
+// Set while a drain or restart is under way, from the scheduler or the resource watchdog.
+static RESTARTING: AtomicBool = AtomicBool::new(false);
+
+struct RestartGuard;
+
+impl Drop for RestartGuard {
+    fn drop(&mut self) {
+        RESTARTING.store(false, Ordering::SeqCst);
+    }
+}
+
+fn default_grace_minutes() -> u32 {
+    30
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RestartSchedule {
+    pub timezone: String, // IANA name, e.g. "Europe/Berlin"
+    #[serde(default)]
+    pub days: Vec<Weekday>, // Every day when empty
+    pub at: NaiveTime,
+    // How long running jobs may take to finish once new rentals are held.
+    #[serde(default = "default_grace_minutes")]
+    pub grace_minutes: u32,
+    // Restart even if jobs are still running after the grace period.
+    #[serde(default)]
+    pub force_after_grace: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RestartOutcome {
+    Restarted,
+    Forced,         // Restarted with jobs still running
+    SkippedJobs,    // Jobs still running after the grace period
+    SkippedOffline, // The daemon wasn't running
+    SkippedLockdown,
+    SkippedBusy, // Another restart was already under way
+    Failed,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ScheduledRestart {
+    pub restart_id: String,
+    pub scheduled_for: String,
+    pub started_at: String,
+    pub finished_at: String,
+    pub outcome: RestartOutcome,
+    pub running_jobs: usize, // When the grace period ended
+    pub detail: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct RestartScheduleStatus {
+    pub next_restart_at: Option<String>,
+    pub history: Vec<ScheduledRestart>,
+}
+
+impl RestartSchedule {
+    pub fn validate(&self) -> Result<(), String> {
+        schedule::parse_tz(&self.timezone)?;
+        if self.grace_minutes > MAX_GRACE_MINUTES {
+            return Err(format!("The restart grace period is limited to {} minutes.", MAX_GRACE_MINUTES));
+        }
+        Ok(())
+    }
+
+    // The first restart time after `after`.
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let tz = schedule::parse_tz(&self.timezone).ok()?;
+        let today = after.with_timezone(&tz).date_naive();
+        today
+            .iter_days()
+            .take(8)
+            .filter(|date| self.days.is_empty() || self.days.contains(&date.weekday()))
+            .map(|date| schedule::resolve(&tz, date.and_time(self.at)))
+            .find(|t| *t > after)
+    }
+}
+
+fn daemon_online(app_handle: &AppHandle) -> bool {
+    app_handle.state::<DaemonState>().status.lock().unwrap().as_str() == "online"
+}
+
+async fn unfinished_jobs(app_handle: &AppHandle) -> Result<usize, String> {
+    let jobs = gpu_ids::local_jobs(app_handle).await?;
+    // A queued job has been accepted and would start, or be cut off, once the daemon is back.
+    Ok(jobs.iter().filter(|j| matches!(j.status.as_str(), "running" | "queued")).count())
+}
+
+// Holds new rentals (see `announcements::hold`) and waits up to the grace period for running
+// and queued jobs. Returns how many are left, or None when shutdown started meanwhile. The
+// caller releases the hold.
+async fn drain(app_handle: &AppHandle, reason: &str, grace: Duration) -> Result<Option<usize>, String> {
+    announcements::hold(app_handle, reason).await.map_err(|e| format!("Failed to hold new rentals: {}", e))?;
+    let deadline = Instant::now() + grace;
+    loop {
+        let running = unfinished_jobs(app_handle).await.map_err(|e| format!("Can't check running jobs: {}", e))?;
+        let left = deadline.saturating_duration_since(Instant::now());
+        if running == 0 || left.is_zero() {
+            return Ok(Some(running));
+        }
+        emit_log_entry(app_handle, "status", format!("{}: waiting for {} running or queued job(s) to finish.", reason, running));
+        if !shutdown::sleep(DRAIN_POLL.min(left)).await {
+            return Ok(None);
+        }
+    }
+}
+
+async fn restart(app_handle: &AppHandle) -> Result<(), String> {
+    stop_daemon(app_handle.clone(), app_handle.state::<DaemonState>()).await?;
+    let deadline = Instant::now() + STOP_TIMEOUT;
+    while !matches!(app_handle.state::<DaemonState>().status.lock().unwrap().as_str(), "offline" | "error") {
+        if Instant::now() > deadline {
+            return Err("The daemon didn't stop in time.".to_string());
+        }
+        tokio::time::sleep(STATUS_POLL).await;
+    }
+    start_daemon(app_handle.clone(), app_handle.state::<DaemonState>()).await.map(|_| ())
+}
+
+// Drains the daemon for up to `grace` and restarts it, skipping the restart when jobs are still
+// running unless `force`. Returns the outcome, the jobs still running when the grace period
+// ended and an error detail, or None when shutdown started meanwhile. Also used by the resource
+// watchdog (see `daemon_resources`); only one drain runs at a time.
+pub async fn drain_and_restart(app_handle: &AppHandle, reason: &str, grace: Duration, force: bool) -> Option<(RestartOutcome, usize, Option<String>)> {
+    if RESTARTING.swap(true, Ordering::SeqCst) {
+        return Some((RestartOutcome::SkippedBusy, 0, None));
+    }
+    let _guard = RestartGuard;
+    if !daemon_online(app_handle) {
+        return Some((RestartOutcome::SkippedOffline, 0, None));
+    }
+    if lockdown::is_active(app_handle) {
+        return Some((RestartOutcome::SkippedLockdown, 0, None));
+    }
+    emit_log_entry(app_handle, "status", format!("{}: holding new rentals while running jobs finish.", reason));
+    let outcome = match drain(app_handle, reason, grace).await {
+        Ok(None) => None,
+        Ok(Some(running)) if running > 0 && !force => Some((RestartOutcome::SkippedJobs, running, None)),
+        Ok(Some(running)) => Some(match restart(app_handle).await {
+            Ok(()) if running > 0 => (RestartOutcome::Forced, running, None),
+            Ok(()) => (RestartOutcome::Restarted, 0, None),
+            Err(e) => (RestartOutcome::Failed, running, Some(e)),
+        }),
+        Err(e) => Some((RestartOutcome::Failed, 0, Some(e))),
+    };
+    announcements::release(app_handle, reason).await;
+    outcome
+}
+
+async fn run(app_handle: &AppHandle, schedule: &RestartSchedule, scheduled_for: DateTime<Utc>) -> Option<ScheduledRestart> {
+    let started_at = get_timestamp();
+    let grace = Duration::from_secs(u64::from(schedule.grace_minutes) * 60);
+    let (outcome, running_jobs, detail) = drain_and_restart(app_handle, "Scheduled daemon restart", grace, schedule.force_after_grace).await?;
+    Some(ScheduledRestart {
+        restart_id: format!("restart-{}", unix_now()),
+        scheduled_for: scheduled_for.to_rfc3339(),
+        started_at,
+        finished_at: get_timestamp(),
+        outcome,
+        running_jobs,
+        detail,
+    })
+}
+
+fn log(app_handle: &AppHandle, record: &ScheduledRestart) {
+    let (level, message) = match record.outcome {
+        RestartOutcome::Restarted => ("status", "Scheduled daemon restart completed.".to_string()),
+        RestartOutcome::Forced => ("status", format!("Scheduled daemon restart forced after the grace period; {} running job(s) were interrupted.", record.running_jobs)),
+        RestartOutcome::SkippedJobs => ("status", format!("Scheduled daemon restart skipped: {} job(s) still running after the grace period.", record.running_jobs)),
+        RestartOutcome::SkippedOffline => ("status", "Scheduled daemon restart skipped: the daemon isn't running.".to_string()),
+        RestartOutcome::SkippedLockdown => ("status", "Scheduled daemon restart skipped: lockdown is active.".to_string()),
+        RestartOutcome::SkippedBusy => ("status", "Scheduled daemon restart skipped: another restart is already under way.".to_string()),
+        RestartOutcome::Failed => ("error", format!("Scheduled daemon restart failed: {}", record.detail.as_deref().unwrap_or("unknown error"))),
+    };
+    emit_log_entry(app_handle, level, message);
+    if let Some(storage) = app_handle.try_state::<Storage>() {
+        if let Err(e) = storage.insert_scheduled_restart(record) {
+            eprintln!("{}", e);
+        }
+    }
+    if let Err(e) = app_handle.emit_all("scheduled_restart", record) {
+        eprintln!("Failed to emit scheduled_restart event: {}", e);
+    }
+}
+
+pub fn spawn_restart_scheduler(app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        // The occurrence being waited for, with the schedule it was computed from.
+        let mut due: Option<(RestartSchedule, DateTime<Utc>)> = None;
+        loop {
+            let configured = app_settings::current(&app_handle).scheduled_restart;
+            if due.as_ref().map(|(s, _)| s) != configured.as_ref() {
+                due = configured.and_then(|s| s.next_after(Utc::now()).map(|t| (s, t)));
+            }
+            let mut wait = MAX_SLEEP;
+            if let Some((schedule, at)) = &due {
+                match (*at - Utc::now()).to_std() {
+                    Ok(left) if !left.is_zero() => wait = left.min(MAX_SLEEP),
+                    _ => {
+                        let Some(record) = run(&app_handle, schedule, *at).await else { break };
+                        log(&app_handle, &record);
+                        due = schedule.next_after(Utc::now()).map(|t| (schedule.clone(), t));
+                        continue;
+                    }
+                }
+            }
+            if !shutdown::sleep(wait).await {
+                break;
+            }
+        }
+    });
+}
+
+#[tauri::command]
+pub async fn get_restart_schedule_status(app_handle: AppHandle) -> Result<RestartScheduleStatus, String> {
+    let next = app_settings::current(&app_handle).scheduled_restart.and_then(|s| s.next_after(Utc::now()));
+    let history = match app_handle.try_state::<Storage>() {
+        Some(storage) => storage.scheduled_restarts(HISTORY_LIMIT)?,
+        None => Vec::new(),
+    };
+    Ok(RestartScheduleStatus { next_restart_at: next.map(|t| t.to_rfc3339()), history })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    fn restart(days: &[Weekday], at: (u32, u32)) -> RestartSchedule {
+        RestartSchedule {
+            timezone: "Europe/Berlin".to_string(),
+            days: days.to_vec(),
+            at: NaiveTime::from_hms_opt(at.0, at.1, 0).unwrap(),
+            grace_minutes: default_grace_minutes(),
+            force_after_grace: false,
+        }
+    }
+
+    #[test]
+    fn passed_time_rolls_to_the_next_day() {
+        let s = restart(&[], (3, 0));
+        assert_eq!(s.next_after(utc("2024-06-05T00:30:00Z")), Some(utc("2024-06-05T01:00:00Z")));
+        assert_eq!(s.next_after(utc("2024-06-05T01:00:00Z")), Some(utc("2024-06-06T01:00:00Z")));
+    }
+
+    #[test]
+    fn day_filter_skips_other_days() {
+        let s = restart(&[Weekday::Mon], (4, 0));
+        // Wednesday 2024-06-05: next Monday, 04:00 CEST.
+        assert_eq!(s.next_after(utc("2024-06-05T12:00:00Z")), Some(utc("2024-06-10T02:00:00Z")));
+        // Monday after the restart time: a full week later.
+        assert_eq!(s.next_after(utc("2024-06-10T03:00:00Z")), Some(utc("2024-06-17T02:00:00Z")));
+    }
+
+    // 2024-03-31: Berlin skips 02:00-03:00 (01:00 UTC).
+    #[test]
+    fn restart_in_skipped_hour_runs_at_the_jump() {
+        let s = restart(&[], (2, 30));
+        assert_eq!(s.next_after(utc("2024-03-30T23:00:00Z")), Some(utc("2024-03-31T01:00:00Z")));
+        assert_eq!(s.next_after(utc("2024-03-31T01:00:00Z")), Some(utc("2024-04-01T00:30:00Z")));
+    }
+
+    // 2024-10-27: Berlin repeats 02:00-03:00, first as CEST (00:00 UTC), then as CET (01:00 UTC).
+    #[test]
+    fn restart_in_repeated_hour_runs_once() {
+        let s = restart(&[], (2, 30));
+        assert_eq!(s.next_after(utc("2024-10-26T23:00:00Z")), Some(utc("2024-10-27T00:30:00Z")));
+        // Not again at the second 02:30.
+        assert_eq!(s.next_after(utc("2024-10-27T00:30:00Z")), Some(utc("2024-10-28T01:30:00Z")));
+    }
+
+    #[test]
+    fn unknown_time_zone_has_no_restart() {
+        let mut s = restart(&[], (3, 0));
+        s.timezone = "Mars/Olympus".to_string();
+        assert_eq!(s.next_after(utc("2024-06-05T00:00:00Z")), None);
+    }
+}
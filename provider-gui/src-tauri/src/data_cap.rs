@@ -0,0 +1,362 @@
+// Monthly data-cap guardrail for providers on metered connections.
+//
+// Job traffic is accounted from the daemon's cumulative byte counters and accumulated per
+// billing cycle. When usage is projected to reach the cap by the end of the cycle we warn; once
+// the projection crosses the cap we optionally stop accepting transfer-heavy jobs or unlist
+// every GPU, and undo that when the next cycle starts. Unlisting is all or nothing: if one GPU
+// can't be unlisted the others are re-listed and the attempt is retried on the next check, with
+// the per-GPU outcome kept for the status view. Only the background monitor accounts usage and
+// enforces; reading the status has no side effects.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager};
+
+use crate::{app_settings, emit_log_entry, get_detected_gpus, invoke_daemon_cli_json_output, set_gpu_rental_config, shutdown};
+
+const STATE_FILE_NAME: &str = "data_cap_state.json";
+const CHECK_INTERVAL: Duration = Duration::from_secs(10 * 60);
+const DAY_SECS: i64 = 24 * 60 * 60;
+const BYTES_PER_GB: f64 = 1024.0 * 1024.0 * 1024.0;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum DataCapAction {
+    #[default]
+    WarnOnly,
+    BlockTransferHeavyJobs,
+    PauseRentals,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct DataCapConfig {
+    pub monthly_cap_gb: Option<f64>, // None disables the guardrail
+    pub billing_cycle_start_day: u8, // 1..=28
+    pub warn_at_percent: f64,
+    pub action: DataCapAction,
+}
+
+impl Default for DataCapConfig {
+    fn default() -> Self {
+        DataCapConfig { monthly_cap_gb: None, billing_cycle_start_day: 1, warn_at_percent: 80.0, action: DataCapAction::WarnOnly }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct BandwidthCounters {
+    bytes_sent: u64,
+    bytes_received: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+struct CycleData {
+    cycle_start: i64,
+    used_bytes: u64,
+    last_counter: Option<u64>,
+    warned: bool,
+    // Enforcement applied this cycle, and the GPUs we unlisted so they can be re-listed.
+    enforced: Option<DataCapAction>,
+    paused_gpu_ids: Vec<String>,
+    // Outcome of the last attempt to unlist every GPU.
+    #[serde(default)]
+    pause_results: Vec<GpuPauseResult>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GpuPauseResult {
+    pub gpu_id: String,
+    pub paused: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct DataCapStatus {
+    pub cap_bytes: Option<u64>,
+    pub used_bytes: u64,
+    pub projected_bytes: u64,
+    pub cycle_start: String,
+    pub cycle_end: String,
+    pub enforced: Option<DataCapAction>,
+    pub pause_results: Vec<GpuPauseResult>,
+}
+
+pub struct DataCapState {
+    data: Mutex<CycleData>,
+    path: Option<PathBuf>,
+}
+
+impl DataCapState {
+    pub fn load(app_handle: &AppHandle) -> Self {
+        let path = app_handle.path_resolver().app_data_dir().map(|dir| dir.join(STATE_FILE_NAME));
+        let data = path
+            .as_ref()
+            .and_then(|p| fs::read_to_string(p).ok())
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        DataCapState { data: Mutex::new(data), path }
+    }
+
+    fn update<T>(&self, f: impl FnOnce(&mut CycleData) -> T) -> T {
+        let mut data = self.data.lock().unwrap();
+        let result = f(&mut data);
+        if let Some(path) = &self.path {
+            if let Ok(json) = serde_json::to_string_pretty(&*data) {
+                if let Err(e) = fs::write(path, json) {
+                    eprintln!("Failed to persist data cap state: {}", e);
+                }
+            }
+        }
+        result
+    }
+}
+
+fn now_unix() -> i64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs() as i64).unwrap_or(0)
+}
+
+fn format_unix(secs: i64) -> String {
+    humantime::format_rfc3339_seconds(UNIX_EPOCH + Duration::from_secs(secs.max(0) as u64)).to_string()
+}
+
+// Proleptic Gregorian conversions between days since the epoch and (year, month, day), UTC.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = (if mp < 10 { mp + 3 } else { mp - 9 }) as u32;
+    (yoe + era * 400 + i64::from(month <= 2), month, day)
+}
+
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = (i64::from(month) + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + i64::from(day) - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+// Start and end (unix seconds, UTC midnight) of the billing cycle containing `now`.
+fn cycle_bounds(now: i64, start_day: u8) -> (i64, i64) {
+    let start_day = u32::from(start_day.clamp(1, 28));
+    let (year, month, day) = civil_from_days(now.div_euclid(DAY_SECS));
+    let (start_year, start_month) = if day >= start_day {
+        (year, month)
+    } else if month == 1 {
+        (year - 1, 12)
+    } else {
+        (year, month - 1)
+    };
+    let (end_year, end_month) = if start_month == 12 { (start_year + 1, 1) } else { (start_year, start_month + 1) };
+    (
+        days_from_civil(start_year, start_month, start_day) * DAY_SECS,
+        days_from_civil(end_year, end_month, start_day) * DAY_SECS,
+    )
+}
+
+// Unlists every listed GPU, or none of them. Returns the outcome per GPU and whether all were
+// unlisted; GPUs that could not be re-listed after a failure are reported as still paused.
+async fn pause_rentals(app_handle: &AppHandle) -> Result<(Vec<GpuPauseResult>, bool), String> {
+    let gpus: Vec<_> = get_detected_gpus(app_handle.clone()).await?.into_iter().filter(|g| g.is_available_for_rent).collect();
+    let mut results = Vec::new();
+    let mut complete = true;
+    for gpu in &gpus {
+        if !complete {
+            results.push(GpuPauseResult { gpu_id: gpu.id.clone(), paused: false, error: Some("Not attempted after another GPU failed.".to_string()) });
+            continue;
+        }
+        let rate = gpu.current_hourly_rate_dgpu.unwrap_or_default();
+        match set_gpu_rental_config(app_handle.clone(), gpu.id.clone(), rate, false, None, None).await {
+            Ok(_) => results.push(GpuPauseResult { gpu_id: gpu.id.clone(), paused: true, error: None }),
+            Err(e) => {
+                results.push(GpuPauseResult { gpu_id: gpu.id.clone(), paused: false, error: Some(e) });
+                complete = false;
+            }
+        }
+    }
+    if !complete {
+        for (gpu, result) in gpus.iter().zip(results.iter_mut()).filter(|(_, r)| r.paused) {
+            let rate = gpu.current_hourly_rate_dgpu.unwrap_or_default();
+            match set_gpu_rental_config(app_handle.clone(), gpu.id.clone(), rate, true, None, None).await {
+                Ok(_) => {
+                    result.paused = false;
+                    result.error = Some("Re-listed because another GPU could not be unlisted.".to_string());
+                }
+                Err(e) => result.error = Some(format!("Left unlisted; re-listing after another GPU failed didn't work: {}", e)),
+            }
+        }
+    }
+    Ok((results, complete))
+}
+
+async fn lift_action(app_handle: &AppHandle, action: Option<DataCapAction>, paused_gpu_ids: &[String]) {
+    if action == Some(DataCapAction::BlockTransferHeavyJobs) {
+        if let Err(e) = invoke_daemon_cli_json_output::<serde_json::Value>(app_handle, &["--set-accept-transfer-heavy-jobs", "true"]).await {
+            emit_log_entry(app_handle, "error", format!("Failed to re-enable transfer-heavy jobs: {}", e));
+        }
+    }
+    if paused_gpu_ids.is_empty() {
+        return;
+    }
+    let gpus = get_detected_gpus(app_handle.clone()).await.unwrap_or_default();
+    for gpu in gpus.into_iter().filter(|g| paused_gpu_ids.contains(&g.id)) {
+        let rate = gpu.current_hourly_rate_dgpu.unwrap_or_default();
+        if let Err(e) = set_gpu_rental_config(app_handle.clone(), gpu.id.clone(), rate, true, None, None).await {
+            emit_log_entry(app_handle, "error", format!("Failed to re-list {} after the data cap reset: {}", gpu.name, e));
+        }
+    }
+}
+
+async fn read_counter(app_handle: &AppHandle) -> Result<u64, String> {
+    let counters: BandwidthCounters = invoke_daemon_cli_json_output(app_handle, &["--get-bandwidth-usage-json"]).await?;
+    Ok(counters.bytes_sent + counters.bytes_received)
+}
+
+// Bytes moved since `last`. Counters reset when the daemon restarts; count from zero in that case.
+fn counter_delta(last: Option<u64>, counter: u64) -> u64 {
+    match last {
+        Some(last) if counter >= last => counter - last,
+        Some(_) => counter,
+        None => 0,
+    }
+}
+
+fn build_status(config: &DataCapConfig, data: &CycleData, used_bytes: u64, now: i64, (cycle_start, cycle_end): (i64, i64)) -> DataCapStatus {
+    let elapsed = (now - cycle_start).max(DAY_SECS / 24);
+    DataCapStatus {
+        cap_bytes: config.monthly_cap_gb.map(|gb| (gb * BYTES_PER_GB) as u64),
+        used_bytes,
+        projected_bytes: (used_bytes as f64 * (cycle_end - cycle_start) as f64 / elapsed as f64) as u64,
+        cycle_start: format_unix(cycle_start),
+        cycle_end: format_unix(cycle_end),
+        enforced: data.enforced,
+        pause_results: data.pause_results.clone(),
+    }
+}
+
+// Current usage without recording it or enforcing anything.
+pub async fn status(app_handle: &AppHandle) -> Result<DataCapStatus, String> {
+    let config = app_settings::current(app_handle).data_cap;
+    let counter = read_counter(app_handle).await?;
+    let now = now_unix();
+    let bounds = cycle_bounds(now, config.billing_cycle_start_day);
+    let data = app_handle.state::<DataCapState>().data.lock().unwrap().clone();
+    // Until the monitor rolls the cycle over, usage recorded so far belongs to the last one.
+    let recorded = if data.cycle_start == bounds.0 { data.used_bytes } else { 0 };
+    let used_bytes = recorded + counter_delta(data.last_counter, counter);
+    Ok(build_status(&config, &data, used_bytes, now, bounds))
+}
+
+// Records usage since the last check and applies or lifts enforcement. Run by the monitor only.
+async fn check(app_handle: &AppHandle) -> Result<(), String> {
+    let config = app_settings::current(app_handle).data_cap;
+    let counter = read_counter(app_handle).await?;
+    let now = now_unix();
+    let (cycle_start, cycle_end) = cycle_bounds(now, config.billing_cycle_start_day);
+    let state = app_handle.state::<DataCapState>();
+
+    // A new cycle lifts whatever the previous one enforced.
+    let lifted = state.update(|data| {
+        if data.cycle_start == cycle_start {
+            return None;
+        }
+        let previous = (data.enforced.is_some() || !data.paused_gpu_ids.is_empty()).then(|| (data.enforced.take(), std::mem::take(&mut data.paused_gpu_ids)));
+        *data = CycleData { cycle_start, last_counter: data.last_counter, ..CycleData::default() };
+        previous
+    });
+    if let Some((action, paused)) = lifted {
+        emit_log_entry(app_handle, "status", "New data cap billing cycle started; lifting restrictions.".to_string());
+        lift_action(app_handle, action, &paused).await;
+    }
+
+    let used_bytes = state.update(|data| {
+        data.used_bytes += counter_delta(data.last_counter, counter);
+        data.last_counter = Some(counter);
+        data.used_bytes
+    });
+    let status = build_status(&config, &state.data.lock().unwrap(), used_bytes, now, (cycle_start, cycle_end));
+
+    if let Some(cap) = status.cap_bytes {
+        let projected_percent = status.projected_bytes as f64 / cap as f64 * 100.0;
+        let (warned, enforced) = {
+            let data = state.data.lock().unwrap();
+            (data.warned, data.enforced)
+        };
+        if projected_percent >= config.warn_at_percent && !warned {
+            let message = format!(
+                "Data usage is projected to reach {:.0}% of the {:.0} GB monthly cap ({:.1} GB used so far).",
+                projected_percent,
+                cap as f64 / BYTES_PER_GB,
+                used_bytes as f64 / BYTES_PER_GB
+            );
+            emit_log_entry(app_handle, "error", message.clone());
+            if let Err(e) = app_handle.emit_all("data_cap_warning", message) {
+                eprintln!("Failed to emit data_cap_warning event: {}", e);
+            }
+            state.update(|data| data.warned = true);
+        }
+        if projected_percent >= 100.0 && enforced.is_none() {
+            enforce(app_handle, config.action, &status.cycle_end).await?;
+        }
+    }
+    Ok(())
+}
+
+async fn enforce(app_handle: &AppHandle, action: DataCapAction, until: &str) -> Result<(), String> {
+    let state = app_handle.state::<DataCapState>();
+    match action {
+        DataCapAction::WarnOnly => return Ok(()),
+        DataCapAction::BlockTransferHeavyJobs => {
+            invoke_daemon_cli_json_output::<serde_json::Value>(app_handle, &["--set-accept-transfer-heavy-jobs", "false"]).await?;
+            state.update(|data| data.enforced = Some(action));
+        }
+        DataCapAction::PauseRentals => {
+            let (results, complete) = pause_rentals(app_handle).await?;
+            // GPUs stuck unlisted after a failed attempt are re-listed with the rest when the cycle ends.
+            let stuck: Vec<String> = results.iter().filter(|r| r.paused).map(|r| r.gpu_id.clone()).collect();
+            state.update(|data| {
+                for id in stuck {
+                    if !data.paused_gpu_ids.contains(&id) {
+                        data.paused_gpu_ids.push(id);
+                    }
+                }
+                data.pause_results = results;
+                if complete {
+                    data.enforced = Some(action);
+                }
+            });
+            if !complete {
+                return Err("Data cap guardrail couldn't unlist every GPU; rentals were left listed and it will try again.".to_string());
+            }
+        }
+    }
+    emit_log_entry(app_handle, "status", format!("Data cap guardrail applied ({:?}) until {}.", action, until));
+    Ok(())
+}
+
+pub fn spawn_data_cap_monitor(app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            if !shutdown::sleep(CHECK_INTERVAL).await {
+                break;
+            }
+            if let Err(e) = check(&app_handle).await {
+                emit_log_entry(&app_handle, "error", format!("Data cap check failed: {}", e));
+            }
+        }
+    });
+}
+
+#[tauri::command]
+pub async fn get_data_cap_status(app_handle: AppHandle) -> Result<DataCapStatus, String> {
+    status(&app_handle).await
+}
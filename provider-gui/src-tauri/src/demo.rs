@@ -0,0 +1,276 @@
+// Demo mode (`--demo`): a simulated daemon for showing the app without hardware or accounts.
+//
+// Daemon CLI calls are answered from an in-memory world instead of the sidecar, and a scripted
+// timeline advances it while the "daemon" is online: jobs arrive, run and complete, earnings
+// accrue and are paid out, and every so often something fails the way it does in the field.
+// The script is deterministic so screenshots and event demos look the same every run.
+
+use serde_json::{json, Value};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+use crate::job_policy::JobCategory;
+use crate::money::Decimal;
+use crate::units::{DgpuPerHour, MegaBytes, Watts};
+use crate::{emit_log_entry, get_timestamp, shutdown, DaemonState, FinancialSummary, GpuInfo, LocalJob, NetworkStatus, ProviderSettings, RentalMode};
+
+const TICK: Duration = Duration::from_secs(2);
+const JOB_ARRIVAL_TICKS: u64 = 20;
+const JOB_DURATION_TICKS: f32 = 45.0;
+const PAYOUT_TICKS: u64 = 150;
+const ERROR_TICKS: u64 = 90;
+const MAX_JOBS: usize = 20;
+
+const JOB_NAMES: [&str; 5] = [
+    "llama-3-8b-finetune",
+    "sdxl-batch-render",
+    "whisper-transcribe",
+    "resnet50-training",
+    "protein-fold-inference",
+];
+
+// Errors the real daemon and its tooling produce; they flow through the normal classification.
+const SIMULATED_ERRORS: [&str; 3] = [
+    "docker: Error response from daemon: manifest for consul:1.15 not found: manifest unknown",
+    "NVIDIA-SMI has failed because it couldn't communicate with the NVIDIA driver",
+    "context deadline exceeded while reporting heartbeat to scheduler",
+];
+
+pub fn enabled() -> bool {
+    static DEMO: OnceLock<bool> = OnceLock::new();
+    *DEMO.get_or_init(|| std::env::args().any(|arg| arg == "--demo"))
+}
+
+struct DemoWorld {
+    tick: u64,
+    jobs_created: u64,
+    gpus: Vec<GpuInfo>,
+    jobs: Vec<LocalJob>,
+    settings: ProviderSettings,
+    financials: FinancialSummary,
+    running: bool,
+}
+
+pub struct DemoState {
+    world: Mutex<DemoWorld>,
+}
+
+fn demo_gpu(id: &str, name: &str, vram_total_mb: u32, rate: Decimal) -> GpuInfo {
+    GpuInfo {
+        id: id.to_string(),
+        name: name.to_string(),
+        model: name.to_string(),
+        vram_total_mb: MegaBytes::new(vram_total_mb),
+        vram_free_mb: MegaBytes::new(vram_total_mb),
+        utilization_gpu_percent: Some(0),
+        temperature_c: Some(38),
+        power_draw_w: Some(Watts::new(30)),
+        is_available_for_rent: true,
+        current_hourly_rate_dgpu: Some(DgpuPerHour::new(rate)),
+        rental_mode: RentalMode::Reserved,
+        ecc_corrected_errors: Some(0),
+        memory_clock_mhz: Some(10_501),
+        fan_speed_percent: Some(0),
+        pcie_link_generation: Some(4),
+        pcie_link_generation_max: Some(4),
+        pcie_link_width: Some(16),
+        pcie_link_width_max: Some(16),
+        throttle_reasons: Some(0),
+        uuid: None,
+        nickname: None,
+        fallback_sourced: false,
+        pci_bus_id: None,
+    }
+}
+
+impl DemoState {
+    pub fn new() -> Self {
+        DemoState {
+            world: Mutex::new(DemoWorld {
+                tick: 0,
+                jobs_created: 0,
+                gpus: vec![
+                    demo_gpu("GPU-demo-0", "NVIDIA GeForce RTX 4090", 24_576, Decimal::new(45, 2)),
+                    demo_gpu("GPU-demo-1", "NVIDIA A100-SXM4-80GB", 81_920, Decimal::new(160, 2)),
+                ],
+                jobs: Vec::new(),
+                settings: ProviderSettings {
+                    default_hourly_rate_dgpu: DgpuPerHour::new(Decimal::new(5, 1)),
+                    preferred_currency: "dGPU".to_string(),
+                    min_job_duration_minutes: 10,
+                    max_concurrent_jobs: 2,
+                },
+                financials: FinancialSummary {
+                    current_balance_dgpu: Decimal::new(1245, 1),
+                    total_earned_dgpu: Decimal::new(183_275, 2),
+                    pending_payout_dgpu: Decimal::ZERO,
+                    last_payout_at: None,
+                },
+                running: false,
+            }),
+        }
+    }
+}
+
+impl DemoWorld {
+    // Advances the script by one tick. Returns a simulated error line when one is due.
+    fn advance(&mut self) -> Option<&'static str> {
+        self.tick += 1;
+        let now = get_timestamp();
+
+        if self.tick % JOB_ARRIVAL_TICKS == 1 {
+            self.jobs_created += 1;
+            let n = self.jobs_created;
+            self.jobs.insert(
+                0,
+                LocalJob {
+                    id: format!("demo-job-{:04}", n),
+                    name: JOB_NAMES[(n as usize) % JOB_NAMES.len()].to_string(),
+                    status: "queued".to_string(),
+                    progress_percent: 0.0,
+                    submitted_at: now.clone(),
+                    started_at: None,
+                    completed_at: None,
+                    estimated_cost_dgpu: Some(Decimal::new(200 + (n % 4) as i64 * 125, 2)),
+                    renter_id: Some(format!("demo-renter-{}", n % 3 + 1)),
+                    category: Some(if n % 2 == 0 { JobCategory::Training } else { JobCategory::Inference }),
+                    framework: Some("pytorch".to_string()),
+                    max_duration_minutes: None,
+                    gpu_id: Some(format!("GPU-demo-{}", n % 2)),
+                    rental_mode: Some(RentalMode::Reserved),
+                    attestation: None,
+                    hardware: None,
+                },
+            );
+            self.jobs.truncate(MAX_JOBS);
+        }
+
+        let max_running = self.settings.max_concurrent_jobs as usize;
+        let mut running = self.jobs.iter().filter(|j| j.status == "running").count();
+        for job in self.jobs.iter_mut().rev() {
+            match job.status.as_str() {
+                "queued" if running < max_running => {
+                    job.status = "running".to_string();
+                    job.started_at = Some(now.clone());
+                    running += 1;
+                }
+                "running" => {
+                    job.progress_percent = (job.progress_percent + 100.0 / JOB_DURATION_TICKS).min(100.0);
+                    // Every fifth job fails partway through.
+                    let fails = job.id.ends_with('5') || job.id.ends_with('0');
+                    if fails && job.progress_percent >= 60.0 {
+                        job.status = "failed".to_string();
+                        job.completed_at = Some(now.clone());
+                    } else if job.progress_percent >= 100.0 {
+                        job.status = "completed".to_string();
+                        job.completed_at = Some(now.clone());
+                        let earned = job.estimated_cost_dgpu.unwrap_or(Decimal::ZERO);
+                        self.financials.total_earned_dgpu += earned;
+                        self.financials.pending_payout_dgpu += earned;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if self.tick % PAYOUT_TICKS == 0 && self.financials.pending_payout_dgpu > Decimal::ZERO {
+            self.financials.current_balance_dgpu += self.financials.pending_payout_dgpu;
+            self.financials.pending_payout_dgpu = Decimal::ZERO;
+            self.financials.last_payout_at = Some(now);
+        }
+
+        let busy = self.jobs.iter().filter(|j| j.status == "running").count();
+        for (i, gpu) in self.gpus.iter_mut().enumerate() {
+            let loaded = i < busy;
+            let wobble = ((self.tick as f32 / 7.0) + i as f32).sin();
+            gpu.utilization_gpu_percent = Some(if loaded { (88.0 + wobble * 8.0) as u32 } else { 0 });
+            gpu.temperature_c = Some(if loaded { (72.0 + wobble * 6.0) as u32 } else { (39.0 + wobble) as u32 });
+            gpu.power_draw_w = Some(Watts::new(if loaded { (320.0 + wobble * 40.0) as u32 } else { 30 }));
+            gpu.fan_speed_percent = Some(if loaded { (55.0 + wobble * 10.0) as u32 } else { 0 });
+            gpu.vram_free_mb = if loaded { gpu.vram_total_mb / 3 } else { gpu.vram_total_mb };
+        }
+
+        (self.tick % ERROR_TICKS == 0).then(|| SIMULATED_ERRORS[((self.tick / ERROR_TICKS) as usize) % SIMULATED_ERRORS.len()])
+    }
+}
+
+fn arg_value<'a>(args: &[&'a str], flag: &str) -> Option<&'a str> {
+    args.iter().position(|a| *a == flag).and_then(|i| args.get(i + 1)).copied()
+}
+
+fn to_json<T: serde::Serialize>(value: &T) -> Result<Value, String> {
+    serde_json::to_value(value).map_err(|e| format!("Failed to serialize demo response: {}", e))
+}
+
+// Answers a daemon CLI invocation from the simulated world.
+pub fn respond(app_handle: &AppHandle, args: &[&str]) -> Result<Value, String> {
+    let state = app_handle.state::<DemoState>();
+    let mut world = state.world.lock().unwrap();
+    match args.first().copied() {
+        Some("--get-gpus-json") => to_json(&world.gpus),
+        Some("--get-settings-json") => to_json(&world.settings),
+        Some("--update-settings-json") => {
+            let settings: ProviderSettings = serde_json::from_str(args.get(1).copied().unwrap_or("{}"))
+                .map_err(|e| format!("Invalid settings JSON: {}", e))?;
+            world.settings = settings;
+            to_json(&world.settings)
+        }
+        Some("--set-gpu-config-json") => {
+            let gpu_id = arg_value(args, "--gpu-id").unwrap_or_default();
+            let rate: Decimal = arg_value(args, "--rate").and_then(|r| r.parse().ok()).unwrap_or(Decimal::ZERO);
+            let available = arg_value(args, "--available") == Some("true");
+            let gpu = world
+                .gpus
+                .iter_mut()
+                .find(|g| g.id == gpu_id)
+                .ok_or_else(|| format!("No GPU with ID '{}'.", gpu_id))?;
+            gpu.current_hourly_rate_dgpu = Some(DgpuPerHour::new(rate));
+            gpu.is_available_for_rent = available;
+            match arg_value(args, "--mode") {
+                Some("spot") => gpu.rental_mode = RentalMode::Spot,
+                Some("reserved") => gpu.rental_mode = RentalMode::Reserved,
+                _ => {}
+            }
+            to_json(gpu)
+        }
+        Some("--get-local-jobs-json") => to_json(&world.jobs),
+        Some("--get-network-status-json") => to_json(&NetworkStatus {
+            connection_type: "Ethernet".to_string(),
+            ip_address: Some("192.0.2.10".to_string()),
+            upload_speed_mbps: 940.0,
+            download_speed_mbps: 930.0,
+            latency_ms: 12,
+        }),
+        Some("--get-financial-summary-json") => to_json(&world.financials),
+        _ => Ok(json!({})),
+    }
+}
+
+// Runs the scripted timeline while the simulated daemon is online.
+pub fn start_timeline(app_handle: &AppHandle) {
+    {
+        let state = app_handle.state::<DemoState>();
+        let mut world = state.world.lock().unwrap();
+        if world.running {
+            return;
+        }
+        world.running = true;
+    }
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            if !shutdown::sleep(TICK).await {
+                break;
+            }
+            if *app_handle.state::<DaemonState>().status.lock().unwrap() != "online" {
+                break;
+            }
+            let error = app_handle.state::<DemoState>().world.lock().unwrap().advance();
+            if let Some(line) = error {
+                emit_log_entry(&app_handle, "stderr", line.to_string());
+            }
+        }
+        app_handle.state::<DemoState>().world.lock().unwrap().running = false;
+    });
+}
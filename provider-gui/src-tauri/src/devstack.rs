@@ -0,0 +1,365 @@
+// The local dante-backend stack, for developers working on the provider app.
+//
+// The repository's docker-compose file is parsed for its services and their dependencies, and
+// the stack is driven through the `docker compose` CLI with a fixed project name so it doesn't
+// collide with a stack started by hand. While the stack runs, its logs are followed and every
+// line goes into the log viewer tagged with its service, the way hook output is.
+//
+// The service graph combines the compose dependencies with container health and Consul's health
+// checks, and traces each service that isn't ready back to the first dependency that is itself
+// failing while everything below it is fine: the place to start looking, rather than the
+// downstream errors it causes. Only available in debug builds or when the app is started with
+// `--devstack`.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::api::process::{Command as TauriCommand, CommandChild, CommandEvent};
+use tauri::AppHandle;
+
+use crate::hooks::run_script;
+use crate::{emit_log_entry, http_client};
+
+const PROJECT_NAME: &str = "dante-devstack";
+const COMPOSE_FILE_NAMES: &[&str] = &["docker-compose.yml", "compose.yml"];
+const UP_TIMEOUT: Duration = Duration::from_secs(15 * 60);
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(120);
+const DEFAULT_CONSUL_URL: &str = "http://localhost:8500";
+
+// The compose file in use and the `docker compose logs -f` follower.
+static COMPOSE_FILE: Mutex<Option<PathBuf>> = Mutex::new(None);
+static LOG_FOLLOWER: Mutex<Option<CommandChild>> = Mutex::new(None);
+
+#[derive(Deserialize, Debug, Default)]
+struct ComposeFile {
+    #[serde(default)]
+    services: BTreeMap<String, ComposeService>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+struct ComposeService {
+    image: Option<String>,
+    #[serde(default)]
+    depends_on: DependsOn,
+}
+
+// Compose accepts a list of names or a map of name to condition.
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+enum DependsOn {
+    List(Vec<String>),
+    Map(BTreeMap<String, serde_yaml::Value>),
+}
+
+impl Default for DependsOn {
+    fn default() -> Self {
+        DependsOn::List(Vec::new())
+    }
+}
+
+impl DependsOn {
+    fn names(&self) -> Vec<String> {
+        match self {
+            DependsOn::List(names) => names.clone(),
+            DependsOn::Map(map) => map.keys().cloned().collect(),
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ServiceStatus {
+    pub service: String,
+    pub image: Option<String>, // None for services built from a Dockerfile
+    pub depends_on: Vec<String>,
+    pub state: String, // "running", "exited", ... or "not_created"
+    pub health: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct StackStatus {
+    pub compose_file: String,
+    pub services: Vec<ServiceStatus>,
+    pub following_logs: bool,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ServiceNode {
+    pub service: String,
+    pub depends_on: Vec<String>,
+    pub state: String,
+    pub health: Option<String>,        // From the container healthcheck
+    pub consul_status: Option<String>, // "passing" | "warning" | "critical"; None if not registered
+    pub ready: bool,
+    // The failing dependency to look at first, if this service is waiting on one.
+    pub root_cause: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ServiceGraph {
+    pub nodes: Vec<ServiceNode>,
+    // Readiness order: each layer only depends on earlier ones.
+    pub layers: Vec<Vec<String>>,
+    // Failing services whose own dependencies are all ready, in readiness order.
+    pub root_causes: Vec<String>,
+    pub cycles: Vec<String>, // Services in a dependency cycle, which never become ready
+    pub consul_reachable: bool,
+}
+
+#[derive(Deserialize, Debug)]
+struct ConsulCheck {
+    #[serde(rename = "ServiceName")]
+    service_name: String,
+    #[serde(rename = "Status")]
+    status: String,
+}
+
+pub fn enabled() -> bool {
+    cfg!(debug_assertions) || std::env::args().any(|arg| arg == "--devstack")
+}
+
+fn require_enabled() -> Result<(), String> {
+    if enabled() {
+        Ok(())
+    } else {
+        Err("The development stack is only available in debug builds or with --devstack.".to_string())
+    }
+}
+
+// The given file, the one used last, or the nearest one above the working directory.
+fn resolve_compose_file(requested: Option<String>) -> Result<PathBuf, String> {
+    if let Some(path) = requested.map(PathBuf::from).or_else(|| COMPOSE_FILE.lock().unwrap().clone()) {
+        return if path.is_file() { Ok(path) } else { Err(format!("{} does not exist.", path.display())) };
+    }
+    let cwd = std::env::current_dir().map_err(|e| e.to_string())?;
+    cwd.ancestors()
+        .flat_map(|dir| COMPOSE_FILE_NAMES.iter().map(move |name| dir.join(name)))
+        .find(|path| path.is_file())
+        .ok_or_else(|| format!("No docker-compose.yml found above {}; pass the compose file explicitly.", cwd.display()))
+}
+
+fn parse(path: &Path) -> Result<ComposeFile, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    serde_yaml::from_str(&text).map_err(|e| format!("{} is not a valid compose file: {}", path.display(), e))
+}
+
+fn compose_args(path: &Path, args: &[&str]) -> Vec<String> {
+    let mut all = vec!["compose".to_string(), "-f".to_string(), path.display().to_string(), "-p".to_string(), PROJECT_NAME.to_string()];
+    all.extend(args.iter().map(|a| a.to_string()));
+    all
+}
+
+async fn compose(path: &Path, args: &[&str], timeout: Duration) -> Result<String, String> {
+    let args = compose_args(path, args);
+    tauri::async_runtime::spawn_blocking(move || run_script("docker", &args, "", timeout, false))
+        .await
+        .map_err(|e| e.to_string())?
+        .map(|output| output.stdout)
+        .map_err(|e| format!("docker compose {}", e))
+}
+
+// `ps --format json` prints an array on older Compose releases and one object per line on newer.
+fn parse_ps(output: &str) -> Vec<Value> {
+    match serde_json::from_str::<Value>(output.trim()) {
+        Ok(Value::Array(items)) => items,
+        Ok(item @ Value::Object(_)) => vec![item],
+        _ => output.lines().filter_map(|line| serde_json::from_str(line).ok()).collect(),
+    }
+}
+
+// Lines look like "auth-service-1  | message".
+fn split_log_line(line: &str) -> (String, &str) {
+    match line.split_once(" | ") {
+        Some((prefix, message)) => {
+            let prefix = prefix.trim();
+            let service = prefix.rsplit_once('-').filter(|(_, n)| n.chars().all(|c| c.is_ascii_digit())).map_or(prefix, |(name, _)| name);
+            (service.to_string(), message)
+        }
+        None => ("stack".to_string(), line),
+    }
+}
+
+fn stop_following() {
+    if let Some(child) = LOG_FOLLOWER.lock().unwrap().take() {
+        let _ = child.kill();
+    }
+}
+
+fn follow_logs(app_handle: &AppHandle, path: &Path) -> Result<(), String> {
+    stop_following();
+    let (mut rx, child) = TauriCommand::new("docker")
+        .args(compose_args(path, &["logs", "--follow", "--no-color", "--tail", "50"]))
+        .spawn()
+        .map_err(|e| format!("Failed to follow stack logs: {}", e))?;
+    *LOG_FOLLOWER.lock().unwrap() = Some(child);
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        while let Some(event) = rx.recv().await {
+            let (log_type, line) = match event {
+                CommandEvent::Stdout(line) => ("stdout", line),
+                CommandEvent::Stderr(line) => ("stderr", line),
+                CommandEvent::Terminated(_) => break,
+                _ => continue,
+            };
+            let (service, message) = split_log_line(&line);
+            emit_log_entry(&app_handle, log_type, format!("[devstack {}] {}", service, message));
+        }
+    });
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn start_stack(app_handle: AppHandle, compose_file: Option<String>, services: Option<Vec<String>>) -> Result<StackStatus, String> {
+    require_enabled()?;
+    let path = resolve_compose_file(compose_file)?;
+    let compose_file = parse(&path)?;
+    let services = services.unwrap_or_default();
+    if let Some(unknown) = services.iter().find(|s| !compose_file.services.contains_key(*s)) {
+        return Err(format!("{} has no service named '{}'.", path.display(), unknown));
+    }
+    *COMPOSE_FILE.lock().unwrap() = Some(path.clone());
+
+    emit_log_entry(&app_handle, "status", format!("Starting the development stack from {}...", path.display()));
+    let mut args = vec!["up", "--detach", "--remove-orphans"];
+    args.extend(services.iter().map(String::as_str));
+    compose(&path, &args, UP_TIMEOUT).await?;
+    follow_logs(&app_handle, &path)?;
+    emit_log_entry(&app_handle, "status", "Development stack started.".to_string());
+    get_stack_status(None).await
+}
+
+#[tauri::command]
+pub async fn stop_stack(app_handle: AppHandle, remove_volumes: bool) -> Result<(), String> {
+    require_enabled()?;
+    let path = resolve_compose_file(None)?;
+    stop_following();
+    let mut args = vec!["down", "--remove-orphans"];
+    if remove_volumes {
+        args.push("--volumes");
+    }
+    compose(&path, &args, COMMAND_TIMEOUT).await?;
+    emit_log_entry(&app_handle, "status", "Development stack stopped.".to_string());
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_stack_status(compose_file: Option<String>) -> Result<StackStatus, String> {
+    require_enabled()?;
+    let path = resolve_compose_file(compose_file)?;
+    let compose_file = parse(&path)?;
+    let containers = parse_ps(&compose(&path, &["ps", "--all", "--format", "json"], COMMAND_TIMEOUT).await?);
+    let services = compose_file
+        .services
+        .iter()
+        .map(|(name, service)| {
+            let container = containers.iter().find(|c| c.get("Service").and_then(Value::as_str) == Some(name.as_str()));
+            let field = |key: &str| container.and_then(|c| c.get(key)).and_then(Value::as_str).filter(|v| !v.is_empty()).map(str::to_string);
+            ServiceStatus {
+                service: name.clone(),
+                image: service.image.clone(),
+                depends_on: service.depends_on.names(),
+                state: field("State").unwrap_or_else(|| "not_created".to_string()),
+                health: field("Health"),
+            }
+        })
+        .collect();
+    Ok(StackStatus { compose_file: path.display().to_string(), services, following_logs: LOG_FOLLOWER.lock().unwrap().is_some() })
+}
+
+// Worst check status per service; checks not tied to a service are ignored.
+async fn consul_health(consul_url: &str) -> Result<HashMap<String, String>, String> {
+    let url = format!("{}/v1/health/state/any", consul_url.trim_end_matches('/'));
+    let checks: Vec<ConsulCheck> = http_client::client()?
+        .get(&url)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| format!("Consul at {} is unreachable: {}", consul_url, e))?
+        .json()
+        .await
+        .map_err(|e| format!("Unexpected response from Consul: {}", e))?;
+    let rank = |status: &str| match status {
+        "critical" => 2,
+        "warning" => 1,
+        _ => 0,
+    };
+    let mut worst: HashMap<String, String> = HashMap::new();
+    for check in checks.into_iter().filter(|c| !c.service_name.is_empty()) {
+        let current = worst.get(&check.service_name).map_or(-1, |s| rank(s));
+        if rank(&check.status) > current {
+            worst.insert(check.service_name, check.status);
+        }
+    }
+    Ok(worst)
+}
+
+// Kahn's algorithm by layers; whatever is left over is in (or behind) a cycle.
+fn layers(nodes: &[ServiceNode]) -> (Vec<Vec<String>>, Vec<String>) {
+    let mut placed: Vec<String> = Vec::new();
+    let mut layers = Vec::new();
+    loop {
+        let layer: Vec<String> = nodes
+            .iter()
+            .filter(|n| !placed.contains(&n.service))
+            .filter(|n| n.depends_on.iter().all(|d| placed.contains(d) || !nodes.iter().any(|m| &m.service == d)))
+            .map(|n| n.service.clone())
+            .collect();
+        if layer.is_empty() {
+            break;
+        }
+        placed.extend(layer.iter().cloned());
+        layers.push(layer);
+    }
+    let rest = nodes.iter().map(|n| n.service.clone()).filter(|s| !placed.contains(s)).collect();
+    (layers, rest)
+}
+
+// Follows failing dependencies down to one whose own dependencies are all ready.
+fn find_root_cause(service: &str, nodes: &HashMap<String, ServiceNode>, seen: &mut Vec<String>) -> Option<String> {
+    let node = nodes.get(service)?;
+    if seen.contains(&node.service) {
+        return None;
+    }
+    seen.push(node.service.clone());
+    for dependency in &node.depends_on {
+        if nodes.get(dependency).is_some_and(|d| !d.ready) {
+            return find_root_cause(dependency, nodes, seen).or_else(|| Some(dependency.clone()));
+        }
+    }
+    (!node.ready).then(|| node.service.clone())
+}
+
+#[tauri::command]
+pub async fn get_service_graph(compose_file: Option<String>, consul_url: Option<String>) -> Result<ServiceGraph, String> {
+    let status = get_stack_status(compose_file).await?;
+    let consul = consul_health(consul_url.as_deref().unwrap_or(DEFAULT_CONSUL_URL)).await;
+    let consul_reachable = consul.is_ok();
+    let consul = consul.unwrap_or_default();
+
+    let mut nodes: Vec<ServiceNode> = status
+        .services
+        .into_iter()
+        .map(|s| {
+            let consul_status = consul.get(&s.service).cloned();
+            let ready = s.state == "running"
+                && !matches!(s.health.as_deref(), Some("unhealthy" | "starting"))
+                && consul_status.as_deref() != Some("critical");
+            ServiceNode { service: s.service, depends_on: s.depends_on, state: s.state, health: s.health, consul_status, ready, root_cause: None }
+        })
+        .collect();
+    let (layers, cycles) = layers(&nodes);
+
+    let by_name: HashMap<String, ServiceNode> = nodes.iter().map(|n| (n.service.clone(), n.clone())).collect();
+    for node in &mut nodes {
+        node.root_cause = find_root_cause(&node.service, &by_name, &mut Vec::new());
+    }
+    let root_causes = layers
+        .iter()
+        .flatten()
+        .filter(|s| by_name.get(*s).is_some_and(|n| !n.ready && n.depends_on.iter().all(|d| by_name.get(d).is_none_or(|dep| dep.ready))))
+        .cloned()
+        .collect();
+    Ok(ServiceGraph { nodes, layers, root_causes, cycles, consul_reachable })
+}
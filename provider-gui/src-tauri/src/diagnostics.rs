@@ -0,0 +1,108 @@
+// Diagnostic bundle for support requests.
+//
+// A gzipped tar archive with the recent log buffer, GUI settings, queued crash reports,
+// basic system information including the daemon's recent memory and CPU samples (see
+// `daemon_resources`) and, when requested, the current session trace. Everything is scrubbed of
+// identifying data before it is written. The operator's maintenance journal (see `journal`) is
+// included on request. Runs as a background task (see `tasks`).
+
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::Serialize;
+use serde_json::json;
+use std::fs::File;
+use tauri::{AppHandle, Manager};
+
+use crate::tasks::{self, TaskContext, TaskRequest};
+
+use crate::backup::append_file;
+use crate::crash_reports::{self, scrub};
+use crate::journal::{self, NoteFilter};
+use crate::session::{self, sanitize};
+use crate::storage::Storage;
+use crate::{app_settings, clock, daemon_resources, emit_log_entry, get_timestamp, redaction, DaemonState};
+
+#[derive(Serialize, Debug, Clone)]
+pub struct DiagnosticBundleSummary {
+    pub path: String,
+    pub entries: Vec<String>,
+}
+
+fn to_json<T: Serialize>(value: &T) -> Result<Vec<u8>, String> {
+    serde_json::to_value(value)
+        .map(|v| sanitize(&v))
+        .and_then(|v| serde_json::to_vec_pretty(&v))
+        .map_err(|e| format!("Failed to serialize diagnostic data: {}", e))
+}
+
+pub async fn write_bundle(
+    app_handle: AppHandle,
+    task: TaskContext,
+    path: String,
+    include_session_trace: bool,
+    include_notes: bool,
+) -> Result<DiagnosticBundleSummary, String> {
+    task.progress(Some(0.0), "Collecting logs and system information")?;
+    let logs: Vec<_> = app_handle
+        .state::<DaemonState>()
+        .log_buffer
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|entry| json!({ "timestamp": entry.timestamp, "type": entry.log_type, "message": scrub(&entry.message) }))
+        .collect();
+    let system = json!({
+        "generated_at": get_timestamp(),
+        "app_version": env!("CARGO_PKG_VERSION"),
+        "os": std::env::consts::OS,
+        "arch": std::env::consts::ARCH,
+        "daemon_status": app_handle.state::<DaemonState>().status.lock().unwrap().clone(),
+        "corrected_time": clock::corrected_timestamp(),
+        "log_redactions": redaction::stats(&app_handle),
+        "daemon_resources": daemon_resources::status(),
+    });
+
+    let mut entries = vec![
+        ("system.json", to_json(&system)?),
+        ("logs.json", to_json(&logs)?),
+        ("app_settings.json", to_json(&app_settings::current(&app_handle))?),
+    ];
+    task.progress(Some(20.0), "Collecting crash reports")?;
+    entries.push(("crash_reports.json", to_json(&crash_reports::get_pending_reports(app_handle.clone()).await?)?));
+    if include_session_trace {
+        if let Some(trace) = session::current_trace(&app_handle) {
+            entries.push(("session_trace.json", to_json(&trace)?));
+        }
+    }
+    if include_notes {
+        if let Some(storage) = app_handle.try_state::<Storage>() {
+            // Free text, like log messages, so it is scrubbed the same way.
+            let notes: Vec<_> = journal::notes(&storage, &NoteFilter::default())?
+                .into_iter()
+                .map(|note| journal::JournalNote { text: scrub(&note.text), ..note })
+                .collect();
+            entries.push(("journal.json", to_json(&notes)?));
+        }
+    }
+
+    let file = File::create(&path).map_err(|e| format!("Failed to create {}: {}", path, e))?;
+    let mut builder = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+    for (i, (name, bytes)) in entries.iter().enumerate() {
+        task.progress(Some(40.0 + 60.0 * i as f32 / entries.len() as f32), format!("Writing {}", name))?;
+        append_file(&mut builder, name, bytes)?;
+    }
+    builder
+        .into_inner()
+        .and_then(|gz| gz.finish())
+        .map_err(|e| format!("Failed to finish diagnostic bundle: {}", e))?;
+
+    emit_log_entry(&app_handle, "status", format!("Diagnostic bundle written to {}.", path));
+    Ok(DiagnosticBundleSummary { path, entries: entries.iter().map(|(name, _)| name.to_string()).collect() })
+}
+
+// Returns the task ID; the `DiagnosticBundleSummary` is the task's result.
+#[tauri::command]
+pub async fn create_diagnostic_bundle(app_handle: AppHandle, path: String, include_session_trace: bool, include_notes: Option<bool>) -> Result<String, String> {
+    let include_notes = include_notes.unwrap_or(false);
+    Ok(tasks::submit(&app_handle, TaskRequest::DiagnosticBundle { path, include_session_trace, include_notes }))
+}
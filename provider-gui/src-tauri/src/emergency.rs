@@ -0,0 +1,161 @@
+// Emergency stop: getting the machine back from renters right now.
+//
+// Every GPU is unlisted first so nothing new is scheduled, then the daemon is told to checkpoint
+// each running job where it can (bounded by a short timeout) and terminate it either way. The
+// outcome, including any step that failed, is kept as an incident with the user's reason, since
+// renters whose jobs were cut short may dispute the charges. The command needs a typed
+// confirmation; the global shortcut needs a second press within a few seconds instead, and the
+// tray item hands over to the frontend to confirm.
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+
+use crate::storage::Storage;
+use crate::{a11y, emit_log_entry, get_detected_gpus, get_timestamp, invoke_daemon_cli_json_output, plugins, set_gpu_rental_config};
+
+pub const CONFIRMATION_PHRASE: &str = "STOP ALL JOBS";
+pub const TRAY_MENU_ID: &str = "emergency_stop";
+const CHECKPOINT_TIMEOUT_SECS: u64 = 30;
+const SHORTCUT_CONFIRM_WINDOW: Duration = Duration::from_secs(3);
+
+// When the emergency-stop shortcut was last pressed without being confirmed.
+static SHORTCUT_ARMED_AT: Mutex<Option<Instant>> = Mutex::new(None);
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StoppedJob {
+    pub job_id: String,
+    pub checkpointed: bool,
+    #[serde(default)]
+    pub checkpoint_error: Option<String>,
+    pub terminated: bool,
+}
+
+#[derive(Deserialize, Debug)]
+struct DaemonStopReport {
+    #[serde(default)]
+    jobs: Vec<StoppedJob>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct EmergencyStopIncident {
+    pub incident_id: String,
+    pub reason: String,
+    pub source: String, // "command" | "shortcut"
+    pub started_at: String,
+    pub finished_at: String,
+    pub gpus_unlisted: Vec<String>,
+    pub jobs: Vec<StoppedJob>,
+    pub errors: Vec<String>,
+}
+
+async fn unlist_all(app_handle: &AppHandle, incident: &mut EmergencyStopIncident) {
+    match get_detected_gpus(app_handle.clone()).await {
+        Ok(gpus) => {
+            for gpu in gpus {
+                let rate = gpu.current_hourly_rate_dgpu.unwrap_or_default();
+                match set_gpu_rental_config(app_handle.clone(), gpu.id.clone(), rate, false, None, None).await {
+                    Ok(_) => incident.gpus_unlisted.push(gpu.id),
+                    Err(e) => incident.errors.push(format!("Couldn't unlist {}: {}", gpu.name, e)),
+                }
+            }
+        }
+        Err(e) => incident.errors.push(format!("Couldn't list GPUs to unlist them: {}", e)),
+    }
+}
+
+pub async fn stop(app_handle: &AppHandle, reason: String, source: &str) -> EmergencyStopIncident {
+    let started_at = get_timestamp();
+    emit_log_entry(app_handle, "error", format!("Emergency stop: {}", reason));
+    a11y::announce(app_handle, "alert", a11y::Severity::Critical, "Emergency stop: ending all rentals.".to_string());
+    let mut incident = EmergencyStopIncident {
+        incident_id: format!("emergency-{}", started_at),
+        reason,
+        source: source.to_string(),
+        started_at,
+        finished_at: String::new(),
+        gpus_unlisted: Vec::new(),
+        jobs: Vec::new(),
+        errors: Vec::new(),
+    };
+
+    unlist_all(app_handle, &mut incident).await;
+    let payload = json!({ "reason": incident.reason, "checkpoint": true, "checkpoint_timeout_secs": CHECKPOINT_TIMEOUT_SECS }).to_string();
+    match invoke_daemon_cli_json_output::<DaemonStopReport>(app_handle, &["--emergency-stop-json", &payload]).await {
+        Ok(report) => incident.jobs = report.jobs,
+        Err(e) => incident.errors.push(format!("The daemon didn't confirm stopping jobs: {}", e)),
+    }
+    for job in incident.jobs.iter().filter(|j| !j.terminated) {
+        incident.errors.push(format!("Job {} is still running.", job.job_id));
+    }
+    incident.finished_at = get_timestamp();
+
+    if let Some(storage) = app_handle.try_state::<Storage>() {
+        if let Err(e) = storage.insert_emergency_stop(&incident) {
+            eprintln!("{}", e);
+        }
+    }
+    let checkpointed = incident.jobs.iter().filter(|j| j.checkpointed).count();
+    let summary = format!("Emergency stop ended {} job(s), {} checkpointed.", incident.jobs.len(), checkpointed);
+    if incident.errors.is_empty() {
+        emit_log_entry(app_handle, "status", summary.clone());
+        a11y::announce(app_handle, "alert", a11y::Severity::Warning, summary);
+    } else {
+        emit_log_entry(app_handle, "error", format!("{} Problems: {}", summary, incident.errors.join(" ")));
+        a11y::announce(app_handle, "alert", a11y::Severity::Critical, format!("{} Some steps failed; check the log.", summary));
+    }
+    let payload = json!(incident);
+    plugins::notify(app_handle, "emergency_stop", &payload, false);
+    if let Err(e) = app_handle.emit_all("emergency_stop_completed", payload) {
+        eprintln!("Failed to emit emergency_stop_completed event: {}", e);
+    }
+    incident
+}
+
+// The first press arms the stop and the second, within a few seconds, runs it.
+pub async fn shortcut_pressed(app_handle: &AppHandle) {
+    let confirmed = {
+        let mut armed = SHORTCUT_ARMED_AT.lock().unwrap();
+        let confirmed = armed.is_some_and(|at| at.elapsed() < SHORTCUT_CONFIRM_WINDOW);
+        *armed = if confirmed { None } else { Some(Instant::now()) };
+        confirmed
+    };
+    if confirmed {
+        stop(app_handle, "Emergency stop shortcut".to_string(), "shortcut").await;
+        return;
+    }
+    let message = format!("Press the emergency stop shortcut again within {} seconds to end all rentals.", SHORTCUT_CONFIRM_WINDOW.as_secs());
+    a11y::announce(app_handle, "alert", a11y::Severity::Critical, message);
+    if let Err(e) = app_handle.emit_all("emergency_stop_armed", json!({ "confirm_within_secs": SHORTCUT_CONFIRM_WINDOW.as_secs() })) {
+        eprintln!("Failed to emit emergency_stop_armed event: {}", e);
+    }
+}
+
+// A tray click is easy to make by mistake, so the frontend confirms and calls `emergency_stop`.
+pub fn tray_clicked(app_handle: &AppHandle) {
+    if let Some(window) = app_handle.get_window("main") {
+        if let Err(e) = window.show().and_then(|_| window.set_focus()) {
+            eprintln!("Failed to show main window: {}", e);
+        }
+    }
+    if let Err(e) = app_handle.emit_all("emergency_stop_requested", json!({ "source": "tray", "confirmation_phrase": CONFIRMATION_PHRASE })) {
+        eprintln!("Failed to emit emergency_stop_requested event: {}", e);
+    }
+}
+
+#[tauri::command]
+pub async fn emergency_stop(app_handle: AppHandle, reason: String, confirmation: String) -> Result<EmergencyStopIncident, String> {
+    if confirmation.trim() != CONFIRMATION_PHRASE {
+        return Err(format!("Type '{}' to confirm the emergency stop.", CONFIRMATION_PHRASE));
+    }
+    let reason = if reason.trim().is_empty() { "No reason given".to_string() } else { reason.trim().to_string() };
+    Ok(stop(&app_handle, reason, "command").await)
+}
+
+#[tauri::command]
+pub async fn get_emergency_stops(app_handle: AppHandle) -> Result<Vec<EmergencyStopIncident>, String> {
+    let storage = app_handle.try_state::<Storage>().ok_or_else(|| "Local storage is unavailable.".to_string())?;
+    storage.emergency_stops()
+}
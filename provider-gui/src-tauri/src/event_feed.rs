@@ -0,0 +1,246 @@
+// Live event feed from the platform over WebSocket.
+//
+// The feed pushes job offers, review notifications, remote approval decisions, payment
+// settlements and announcements as they happen. Each message is wrapped in an `EventEnvelope`
+// and emitted as `platform_event`, then routed to the module that owns it. While the feed is
+// connected, modules that would otherwise poll the REST API for the same information skip
+// their polling. The connection reconnects with backoff and resumes from the last event ID it
+// saw, so nothing is missed across short outages. The cursor only moves past an event once it
+// has been applied, so a failure replays it on the next connection. The WebSocket is upgraded
+// from a request made with the shared HTTP client, so it honours the proxy and TLS settings.
+// Switching accounts restarts the connection with the new account's token and cursor.
+
+use futures_util::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tokio::sync::Notify;
+use tokio_tungstenite::tungstenite::handshake::client::generate_key;
+use tokio_tungstenite::tungstenite::protocol::Role;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::WebSocketStream;
+
+pub use dante_provider_core::events::EventEnvelope;
+
+use crate::approvals::{self, ApprovalDecision};
+use crate::watchdog::{self, Heartbeat};
+use crate::{accounts, announcements, api_client, emit_log_entry, feature_flags, get_timestamp, http_client, plugins, reputation, shutdown};
+
+const CURSOR_FILE_NAME: &str = "event_feed_cursor";
+const MIN_RECONNECT_DELAY: Duration = Duration::from_secs(1);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(60);
+const NO_TOKEN_RETRY: Duration = Duration::from_secs(60);
+// A quiet but healthy connection still beats this often; connecting and dispatching a message
+// must each finish within the grace period.
+const IDLE_BEAT_INTERVAL: Duration = Duration::from_secs(30);
+const FEED_GRACE: Duration = Duration::from_secs(120);
+
+static CONNECTED: AtomicBool = AtomicBool::new(false);
+static RESTART: OnceLock<Notify> = OnceLock::new();
+
+#[derive(Deserialize, Debug)]
+struct FeedMessage {
+    id: String,
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    occurred_at: Option<String>,
+    #[serde(default)]
+    data: Value,
+}
+
+pub fn is_connected() -> bool {
+    CONNECTED.load(Ordering::Relaxed)
+}
+
+fn restart_signal() -> &'static Notify {
+    RESTART.get_or_init(Notify::new)
+}
+
+// Drops the current connection (or cuts a reconnect wait short) so the feed reconnects as the
+// active account.
+pub fn restart() {
+    restart_signal().notify_one();
+}
+
+// Sleeps like `Heartbeat::sleep`, but wakes early on a restart. Returns false on shutdown.
+async fn pause(heartbeat: &Heartbeat, duration: Duration) -> bool {
+    tokio::select! {
+        running = heartbeat.sleep(duration) => running,
+        _ = restart_signal().notified() => true,
+    }
+}
+
+fn read_cursor(app_handle: &AppHandle) -> Option<String> {
+    accounts::data_dir(app_handle).and_then(|dir| fs::read_to_string(dir.join(CURSOR_FILE_NAME)).ok()).map(|s| s.trim().to_string())
+}
+
+fn write_cursor(app_handle: &AppHandle, id: &str) {
+    if let Some(dir) = accounts::data_dir(app_handle) {
+        let _ = fs::create_dir_all(&dir);
+        if let Err(e) = fs::write(dir.join(CURSOR_FILE_NAME), id) {
+            eprintln!("Failed to persist event feed cursor: {}", e);
+        }
+    }
+}
+
+fn feed_url(app_handle: &AppHandle) -> String {
+    let url = api_client::endpoint(app_handle, "/api/v1/providers/me/events");
+    match read_cursor(app_handle) {
+        Some(cursor) => format!("{}?last_event_id={}", url, api_client::encode_component(&cursor)),
+        None => url,
+    }
+}
+
+// Errors mean the event wasn't applied and should be replayed; refreshes that merely failed to
+// fetch are logged instead, since the next poll or event catches them up.
+async fn dispatch(app_handle: &AppHandle, envelope: &EventEnvelope) -> Result<(), String> {
+    match envelope.kind.as_str() {
+        "review_posted" => {
+            if let Err(e) = reputation::refresh_reviews(app_handle).await {
+                eprintln!("Failed to refresh reviews after feed notification: {}", e);
+            }
+        }
+        "approval_decision" => match serde_json::from_value::<ApprovalDecision>(envelope.payload.clone()) {
+            Ok(decision) => {
+                approvals::apply(app_handle, decision).await.map_err(|e| format!("Failed to apply a decision from the phone: {}", e))?;
+            }
+            Err(e) => eprintln!("Malformed approval decision on event feed: {}", e),
+        },
+        "payment_settled" => {
+            emit_log_entry(app_handle, "status", "Payment settled on the platform.".to_string());
+            plugins::notify(app_handle, "payment_settled", &envelope.payload, true);
+        }
+        "announcement" => announcements::receive(app_handle, &envelope.payload).await,
+        "feature_flags_changed" => {
+            if let Err(e) = feature_flags::refresh(app_handle).await {
+                eprintln!("Failed to refresh feature flags after feed notification: {}", e);
+            }
+        }
+        "job_offer" => plugins::notify(app_handle, "job_offer", &envelope.payload, false),
+        _ => {}
+    }
+    Ok(())
+}
+
+async fn connect(app_handle: &AppHandle, token: &str) -> Result<WebSocketStream<reqwest::Upgraded>, String> {
+    let response = http_client::upgrade_client()?
+        .get(feed_url(app_handle))
+        .bearer_auth(token)
+        .header("Connection", "Upgrade")
+        .header("Upgrade", "websocket")
+        .header("Sec-WebSocket-Version", "13")
+        .header("Sec-WebSocket-Key", generate_key())
+        .send()
+        .await
+        .map_err(|e| format!("Failed to connect to the event feed: {}", http_client::classify(&e)))?;
+    if response.status() != reqwest::StatusCode::SWITCHING_PROTOCOLS {
+        return Err(format!("Failed to connect to the event feed: server returned HTTP {}", response.status().as_u16()));
+    }
+    let upgraded = response.upgrade().await.map_err(|e| format!("Failed to upgrade the event feed connection: {}", e))?;
+    Ok(WebSocketStream::from_raw_socket(upgraded, Role::Client, None).await)
+}
+
+async fn run_connection(app_handle: &AppHandle, heartbeat: &Heartbeat, token: &str) -> Result<(), String> {
+    let mut socket = connect(app_handle, token).await?;
+    CONNECTED.store(true, Ordering::Relaxed);
+    emit_log_entry(app_handle, "status", "Connected to the platform event feed.".to_string());
+
+    let mut idle_beat = tokio::time::interval(IDLE_BEAT_INTERVAL);
+    loop {
+        heartbeat.beat();
+        let message = tokio::select! {
+            message = socket.next() => message,
+            _ = idle_beat.tick() => continue,
+            _ = shutdown::wait() => {
+                let _ = socket.close(None).await;
+                break;
+            }
+            _ = restart_signal().notified() => {
+                let _ = socket.close(None).await;
+                emit_log_entry(app_handle, "status", "Reconnecting the platform event feed for the new account.".to_string());
+                break;
+            }
+        };
+        let Some(message) = message else { break };
+        let text = match message.map_err(|e| format!("Event feed error: {}", e))? {
+            Message::Text(text) => text,
+            Message::Ping(data) => {
+                socket.send(Message::Pong(data)).await.map_err(|e| e.to_string())?;
+                continue;
+            }
+            Message::Close(_) => break,
+            _ => continue,
+        };
+        let message: FeedMessage = match serde_json::from_str(&text) {
+            Ok(message) => message,
+            Err(e) => {
+                eprintln!("Ignoring malformed event feed message: {}", e);
+                continue;
+            }
+        };
+        let envelope = EventEnvelope {
+            id: message.id,
+            source: "platform_feed".to_string(),
+            kind: message.kind,
+            occurred_at: message.occurred_at,
+            received_at: get_timestamp(),
+            payload: message.data,
+        };
+        if let Err(e) = app_handle.emit_all("platform_event", &envelope) {
+            eprintln!("Failed to emit platform_event event: {}", e);
+        }
+        match dispatch(app_handle, &envelope).await {
+            Ok(()) => write_cursor(app_handle, &envelope.id),
+            // Leave the cursor where it is so the event is replayed after reconnecting.
+            Err(e) => emit_log_entry(app_handle, "error", e),
+        }
+    }
+    Ok(())
+}
+
+pub fn spawn_event_feed(app_handle: AppHandle) {
+    watchdog::supervise(&app_handle, "event_bridge", FEED_GRACE, run_event_feed);
+}
+
+async fn run_event_feed(app_handle: AppHandle, heartbeat: Heartbeat) {
+    // A previous run may have been stopped by the watchdog while connected.
+    CONNECTED.store(false, Ordering::Relaxed);
+    let mut delay = MIN_RECONNECT_DELAY;
+    loop {
+        heartbeat.beat();
+        let Ok(token) = api_client::token(&app_handle) else {
+            if !pause(&heartbeat, NO_TOKEN_RETRY).await {
+                break;
+            }
+            continue;
+        };
+        let result = run_connection(&app_handle, &heartbeat, &token).await;
+        let was_connected = CONNECTED.swap(false, Ordering::Relaxed);
+        match result {
+            Err(e) if !was_connected => eprintln!("{}", e),
+            Err(e) => emit_log_entry(&app_handle, "error", format!("Platform event feed disconnected: {}", e)),
+            Ok(()) => {}
+        }
+        // A connection that was up starts the backoff over.
+        delay = if was_connected { MIN_RECONNECT_DELAY } else { (delay * 2).min(MAX_RECONNECT_DELAY) };
+        if !pause(&heartbeat, delay).await {
+            break;
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct EventFeedStatus {
+    pub connected: bool,
+    pub last_event_id: Option<String>,
+}
+
+#[tauri::command]
+pub async fn get_event_feed_status(app_handle: AppHandle) -> Result<EventFeedStatus, String> {
+    Ok(EventFeedStatus { connected: is_connected(), last_event_id: read_cursor(&app_handle) })
+}
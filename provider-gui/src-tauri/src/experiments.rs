@@ -0,0 +1,317 @@
+// A/B pricing experiments.
+//
+// An experiment alternates a set of GPUs between two rate configurations ("arms") on a fixed
+// schedule, recording each period in the analytics store. Earnings of jobs on the experiment's
+// GPUs are attributed to the arm that was live when the job completed, and the report compares
+// hourly earnings per period between arms with Welch's t-test. Alternating in time rather than splitting GPUs keeps the
+// arms comparable on a single rig, at the cost of some carry-over from jobs spanning a switch.
+
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+
+use crate::market::MarketState;
+use crate::money::{self, Decimal};
+use crate::storage::{unix_now, Storage};
+use crate::units::DgpuPerHour;
+use crate::{emit_log_entry, get_detected_gpus, get_timestamp, set_gpu_rental_config, shutdown};
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(5 * 60);
+const MIN_SWITCH_HOURS: u32 = 1;
+// Periods shorter than this (e.g. the one cut off by stopping) are left out of the comparison.
+const MIN_PERIOD_SECS: i64 = 30 * 60;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PricingArm {
+    pub name: String,
+    pub hourly_rate_dgpu: DgpuPerHour,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExperimentStatus {
+    Running,
+    Stopped,
+    Failed,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ExperimentConfig {
+    pub name: String,
+    pub gpu_ids: Vec<String>,
+    pub arms: [PricingArm; 2],
+    pub switch_every_hours: u32,
+    pub duration_days: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PricingExperiment {
+    pub id: String,
+    pub config: ExperimentConfig,
+    pub status: ExperimentStatus,
+    pub started_at: i64,
+    pub ends_at: i64,
+    pub current_arm: Option<usize>,
+    // Rates before the experiment, restored when it ends.
+    pub original_rates: Vec<(String, Option<DgpuPerHour>)>,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ArmResult {
+    pub name: String,
+    pub hourly_rate_dgpu: DgpuPerHour,
+    pub periods: usize,
+    pub hours: f64,
+    pub jobs: i64,
+    #[serde(with = "money::amount")]
+    pub earnings_dgpu: Decimal,
+    #[serde(with = "money::amount")]
+    pub hourly_earnings_dgpu: Decimal,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ExperimentReport {
+    pub experiment: PricingExperiment,
+    pub arms: Vec<ArmResult>,
+    pub leader: Option<String>,
+    // Two-sided p-value for "the arms earn the same per hour"; None with fewer than two
+    // periods per arm.
+    pub p_value: Option<f64>,
+    pub significant: bool,
+    pub notes: Vec<String>,
+}
+
+fn storage(app_handle: &AppHandle) -> Result<State<'_, Storage>, String> {
+    app_handle.try_state::<Storage>().ok_or_else(|| "Local storage is unavailable.".to_string())
+}
+
+impl PricingExperiment {
+    fn arm_at(&self, now: i64) -> usize {
+        let period = i64::from(self.config.switch_every_hours) * 60 * 60;
+        (((now - self.started_at) / period) % 2) as usize
+    }
+}
+
+async fn apply_rates(app_handle: &AppHandle, rates: &[(String, DgpuPerHour)]) -> Result<(), String> {
+    let gpus = get_detected_gpus(app_handle.clone()).await?;
+    for (gpu_id, rate) in rates {
+        let gpu = gpus.iter().find(|g| &g.id == gpu_id).ok_or_else(|| format!("GPU {} is no longer detected.", gpu_id))?;
+        set_gpu_rental_config(app_handle.clone(), gpu.id.clone(), *rate, gpu.is_available_for_rent, None, None).await?;
+    }
+    Ok(())
+}
+
+async fn switch_to(app_handle: &AppHandle, experiment: &mut PricingExperiment, arm: usize) -> Result<(), String> {
+    let rate = experiment.config.arms[arm].hourly_rate_dgpu;
+    let rates: Vec<(String, DgpuPerHour)> = experiment.config.gpu_ids.iter().map(|id| (id.clone(), rate)).collect();
+    apply_rates(app_handle, &rates).await?;
+    storage(app_handle)?.switch_experiment_arm(&experiment.id, Some(arm), unix_now())?;
+    experiment.current_arm = Some(arm);
+    emit_log_entry(
+        app_handle,
+        "status",
+        format!("Pricing experiment '{}' switched to arm '{}' ({}).", experiment.config.name, experiment.config.arms[arm].name, rate),
+    );
+    Ok(())
+}
+
+async fn finish(app_handle: &AppHandle, experiment: &mut PricingExperiment, status: ExperimentStatus) -> Result<(), String> {
+    let storage = storage(app_handle)?;
+    storage.switch_experiment_arm(&experiment.id, None, unix_now())?;
+    experiment.status = status;
+    experiment.current_arm = None;
+    storage.upsert_pricing_experiment(experiment)?;
+    let originals: Vec<(String, DgpuPerHour)> = experiment.original_rates.iter().filter_map(|(id, rate)| rate.map(|r| (id.clone(), r))).collect();
+    if let Err(e) = apply_rates(app_handle, &originals).await {
+        emit_log_entry(app_handle, "error", format!("Failed to restore rates after pricing experiment '{}': {}", experiment.config.name, e));
+    }
+    if let Err(e) = app_handle.emit_all("pricing_experiment_finished", &*experiment) {
+        eprintln!("Failed to emit pricing_experiment_finished event: {}", e);
+    }
+    Ok(())
+}
+
+async fn tick(app_handle: &AppHandle) -> Result<(), String> {
+    let experiments = storage(app_handle)?.pricing_experiments()?;
+    let now = unix_now();
+    for mut experiment in experiments.into_iter().filter(|e| e.status == ExperimentStatus::Running) {
+        if now >= experiment.ends_at {
+            emit_log_entry(app_handle, "status", format!("Pricing experiment '{}' has finished.", experiment.config.name));
+            finish(app_handle, &mut experiment, ExperimentStatus::Stopped).await?;
+            continue;
+        }
+        let arm = experiment.arm_at(now);
+        if experiment.current_arm == Some(arm) {
+            continue;
+        }
+        // A failed switch (e.g. a rate now below the price floor) would skew the comparison.
+        if let Err(e) = switch_to(app_handle, &mut experiment, arm).await {
+            emit_log_entry(app_handle, "error", format!("Pricing experiment '{}' stopped: {}", experiment.config.name, e));
+            experiment.error = Some(e);
+            finish(app_handle, &mut experiment, ExperimentStatus::Failed).await?;
+            continue;
+        }
+        storage(app_handle)?.upsert_pricing_experiment(&experiment)?;
+    }
+    Ok(())
+}
+
+pub fn spawn_experiment_runner(app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            if !shutdown::sleep(CHECK_INTERVAL).await {
+                break;
+            }
+            if let Err(e) = tick(&app_handle).await {
+                eprintln!("Pricing experiment check failed: {}", e);
+            }
+        }
+    });
+}
+
+// Abramowitz & Stegun 7.1.26, accurate to about 1.5e-7.
+fn erf(x: f64) -> f64 {
+    let t = 1.0 / (1.0 + 0.327_591_1 * x.abs());
+    let poly = t * (0.254_829_592 + t * (-0.284_496_736 + t * (1.421_413_741 + t * (-1.453_152_027 + t * 1.061_405_429))));
+    (1.0 - poly * (-x * x).exp()).copysign(x)
+}
+
+fn mean_and_variance(samples: &[f64]) -> (f64, f64) {
+    let n = samples.len() as f64;
+    let mean = samples.iter().sum::<f64>() / n;
+    let variance = samples.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / (n - 1.0);
+    (mean, variance)
+}
+
+// Welch's t statistic with a normal approximation for the p-value; optimistic for very few
+// periods, which the report notes.
+fn welch_p_value(a: &[f64], b: &[f64]) -> Option<f64> {
+    if a.len() < 2 || b.len() < 2 {
+        return None;
+    }
+    let (mean_a, var_a) = mean_and_variance(a);
+    let (mean_b, var_b) = mean_and_variance(b);
+    let se = (var_a / a.len() as f64 + var_b / b.len() as f64).sqrt();
+    if se == 0.0 {
+        return Some(if mean_a == mean_b { 1.0 } else { 0.0 });
+    }
+    let t = (mean_a - mean_b) / se;
+    Some(1.0 - erf(t.abs() / std::f64::consts::SQRT_2))
+}
+
+fn build_report(storage: &Storage, experiment: PricingExperiment) -> Result<ExperimentReport, String> {
+    let now = unix_now();
+    let mut samples: [Vec<f64>; 2] = [Vec::new(), Vec::new()];
+    let mut totals = [(0_i64, 0_i64, Decimal::ZERO); 2]; // (seconds, jobs, earnings)
+    for (arm, started_at, ended_at) in storage.experiment_periods(&experiment.id)? {
+        let ended_at = ended_at.unwrap_or(now);
+        if arm > 1 || ended_at - started_at < MIN_PERIOD_SECS {
+            continue;
+        }
+        let (jobs, earnings) = storage.ledger_totals_for_gpus("job_completed", started_at, ended_at, &experiment.config.gpu_ids)?;
+        // The t-test works on floats; the totals stay exact.
+        samples[arm].push(money::to_f64(earnings) / ((ended_at - started_at) as f64 / 3600.0));
+        totals[arm].0 += ended_at - started_at;
+        totals[arm].1 += jobs;
+        totals[arm].2 += earnings;
+    }
+
+    let arms: Vec<ArmResult> = experiment
+        .config
+        .arms
+        .iter()
+        .zip(totals.iter().zip(samples.iter()))
+        .map(|(arm, ((secs, jobs, earnings), periods))| ArmResult {
+            name: arm.name.clone(),
+            hourly_rate_dgpu: arm.hourly_rate_dgpu,
+            periods: periods.len(),
+            hours: *secs as f64 / 3600.0,
+            jobs: *jobs,
+            earnings_dgpu: *earnings,
+            hourly_earnings_dgpu: if *secs > 0 { earnings * Decimal::from(3600) / Decimal::from(*secs) } else { Decimal::ZERO },
+        })
+        .collect();
+    let p_value = welch_p_value(&samples[0], &samples[1]);
+    let leader = (arms.iter().all(|a| a.periods > 0))
+        .then(|| arms.iter().max_by_key(|a| a.hourly_earnings_dgpu).map(|a| a.name.clone()))
+        .flatten();
+
+    let mut notes = vec![
+        "Earnings come from the local event ledger; jobs the daemon didn't report a GPU for aren't counted.".to_string(),
+        "Each job is counted under the arm that was live when it completed.".to_string(),
+    ];
+    if samples.iter().any(|s| s.len() < 5) {
+        notes.push("Fewer than five periods per arm; treat the p-value as a rough indication.".to_string());
+    }
+    Ok(ExperimentReport { significant: p_value.is_some_and(|p| p < 0.05), experiment, arms, leader, p_value, notes })
+}
+
+#[tauri::command]
+pub async fn start_pricing_experiment(app_handle: AppHandle, config: ExperimentConfig) -> Result<PricingExperiment, String> {
+    if config.gpu_ids.is_empty() {
+        return Err("Choose at least one GPU for the experiment.".to_string());
+    }
+    if config.switch_every_hours < MIN_SWITCH_HOURS || config.duration_days == 0 {
+        return Err(format!("Arms must alternate at least every {} hour and the experiment must run at least a day.", MIN_SWITCH_HOURS));
+    }
+    let storage = storage(&app_handle)?;
+    let running = storage.pricing_experiments()?.into_iter().filter(|e| e.status == ExperimentStatus::Running);
+    for other in running {
+        if let Some(gpu_id) = other.config.gpu_ids.iter().find(|id| config.gpu_ids.contains(id)) {
+            return Err(format!("GPU {} is already in experiment '{}'.", gpu_id, other.config.name));
+        }
+    }
+
+    // Below-floor prices need a typed confirmation, which an unattended switch can't give.
+    let gpus = get_detected_gpus(app_handle.clone()).await?;
+    let market = app_handle.state::<MarketState>();
+    let mut original_rates = Vec::new();
+    for gpu_id in &config.gpu_ids {
+        let gpu = gpus.iter().find(|g| &g.id == gpu_id).ok_or_else(|| format!("No GPU with ID '{}'.", gpu_id))?;
+        for arm in &config.arms {
+            if market.check_price(&gpu.model, arm.hourly_rate_dgpu).required_confirmation.is_some() {
+                return Err(format!("Arm '{}' prices {} below the market floor; experiments can't use below-floor rates.", arm.name, gpu.name));
+            }
+        }
+        original_rates.push((gpu.id.clone(), gpu.current_hourly_rate_dgpu));
+    }
+
+    let now = unix_now();
+    let mut experiment = PricingExperiment {
+        id: format!("exp-{}", now),
+        ends_at: now + i64::from(config.duration_days) * 24 * 60 * 60,
+        config,
+        status: ExperimentStatus::Running,
+        started_at: now,
+        current_arm: None,
+        original_rates,
+        error: None,
+    };
+    switch_to(&app_handle, &mut experiment, 0).await?;
+    storage.upsert_pricing_experiment(&experiment)?;
+    emit_log_entry(&app_handle, "status", format!("Pricing experiment '{}' started at {}.", experiment.config.name, get_timestamp()));
+    Ok(experiment)
+}
+
+#[tauri::command]
+pub async fn stop_pricing_experiment(app_handle: AppHandle, experiment_id: String) -> Result<ExperimentReport, String> {
+    let storage = storage(&app_handle)?;
+    let mut experiment = storage
+        .pricing_experiments()?
+        .into_iter()
+        .find(|e| e.id == experiment_id)
+        .ok_or_else(|| format!("No pricing experiment with ID '{}'.", experiment_id))?;
+    if experiment.status == ExperimentStatus::Running {
+        finish(&app_handle, &mut experiment, ExperimentStatus::Stopped).await?;
+        emit_log_entry(&app_handle, "status", format!("Pricing experiment '{}' stopped.", experiment.config.name));
+    }
+    build_report(&storage, experiment)
+}
+
+#[tauri::command]
+pub async fn get_pricing_experiments(app_handle: AppHandle) -> Result<Vec<ExperimentReport>, String> {
+    let storage = storage(&app_handle)?;
+    storage.pricing_experiments()?.into_iter().map(|e| build_report(&storage, e)).collect()
+}
@@ -0,0 +1,176 @@
+// CSV exports of earnings, usage and the hardware inventory for spreadsheets and accounting.
+//
+// Files are written for the locale from `locale`: its field delimiter, decimal separator and
+// date order, with a UTF-8 byte order mark so Excel doesn't misread currency symbols. Numbers
+// are written without grouping separators so they stay numeric when opened. Each export runs
+// as a background task (see `tasks`) whose result is the `ExportSummary`. Raw telemetry for
+// analysis tools is exported separately (see `telemetry_export`).
+
+use serde::Serialize;
+use std::fs::File;
+use std::io::Write;
+use tauri::{AppHandle, Manager};
+
+use crate::analytics::BUSY_UTILIZATION_PERCENT;
+use crate::inventory;
+use crate::locale::{self, LocaleFormat};
+use crate::money::Decimal;
+use crate::storage::{unix_now, Storage};
+use crate::tasks::{self, TaskContext, TaskRequest};
+use crate::{app_settings, emit_log_entry};
+
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+const DEFAULT_USAGE_DAYS: i64 = 30;
+const ROWS_PER_PROGRESS_REPORT: usize = 1000;
+const INVENTORY_HEADER: [&str; 11] = [
+    "GPU",
+    "Name",
+    "Serial",
+    "Purchased",
+    "Price",
+    "Currency",
+    "Warranty until",
+    "Earned (DGPU)",
+    "Earned (purchase currency)",
+    "Payback %",
+    "ROI %",
+];
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ExportSummary {
+    pub path: String,
+    pub rows: usize,
+    pub locale: String,
+}
+
+fn write_csv(task: &TaskContext, path: &str, locale: &LocaleFormat, header: &[&str], rows: Vec<Vec<String>>) -> Result<ExportSummary, String> {
+    let mut file = File::create(path).map_err(|e| format!("Failed to create {}: {}", path, e))?;
+    file.write_all(UTF8_BOM).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+    let mut writer = csv::WriterBuilder::new().delimiter(locale.csv_delimiter as u8).from_writer(file);
+    writer.write_record(header).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+    for (i, row) in rows.iter().enumerate() {
+        if i % ROWS_PER_PROGRESS_REPORT == 0 {
+            task.progress(Some(100.0 * i as f32 / rows.len() as f32), format!("Writing row {} of {}", i + 1, rows.len()))?;
+        }
+        writer.write_record(row).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+    }
+    writer.flush().map_err(|e| format!("Failed to write {}: {}", path, e))?;
+    Ok(ExportSummary { path: path.to_string(), rows: rows.len(), locale: locale.tag.clone() })
+}
+
+fn finish(app_handle: &AppHandle, what: &str, result: Result<ExportSummary, String>) -> Result<ExportSummary, String> {
+    if let Ok(summary) = &result {
+        emit_log_entry(app_handle, "status", format!("Exported {} {} to {} ({} format).", summary.rows, what, summary.path, summary.locale));
+    }
+    result
+}
+
+// Processed payouts and job earnings from the event ledger.
+pub fn ledger_csv(app_handle: &AppHandle, task: &TaskContext, path: &str, since: Option<i64>) -> Result<ExportSummary, String> {
+    let locale = locale::resolve(app_handle);
+    let rounding = app_settings::current(app_handle).amount_rounding;
+    let ledger = app_handle.try_state::<Storage>().ok_or_else(|| "Local storage is unavailable.".to_string())?.ledger(since.unwrap_or(0))?;
+    let rows = ledger
+        .entries
+        .iter()
+        .map(|e| {
+            vec![
+                locale.date_time(e.processed_at),
+                e.kind.clone(),
+                e.amount_dgpu.map(|a| locale.plain_amount(a, &rounding)).unwrap_or_default(),
+                e.idempotency_key.clone(),
+            ]
+        })
+        .collect();
+    let result = write_csv(task, path, &locale, &["Date", "Event", "Amount (DGPU)", "Reference"], rows);
+    finish(app_handle, "ledger entries", result)
+}
+
+pub fn imported_earnings_csv(app_handle: &AppHandle, task: &TaskContext, path: &str) -> Result<ExportSummary, String> {
+    let locale = locale::resolve(app_handle);
+    let rounding = app_settings::current(app_handle).amount_rounding;
+    let rows = app_handle
+        .try_state::<Storage>()
+        .ok_or_else(|| "Local storage is unavailable.".to_string())?
+        .imported_earnings_by_month()?
+        .into_iter()
+        .map(|m| vec![m.month, m.source, m.currency, m.rows.to_string(), locale.plain_number(m.gpu_hours, 2), locale.plain_amount(m.amount, &rounding)])
+        .collect();
+    let result = write_csv(task, path, &locale, &["Month", "Platform", "Currency", "Rows", "GPU hours", "Amount"], rows);
+    finish(app_handle, "months of imported earnings", result)
+}
+
+// Hourly listing and utilization per GPU, by default over the last 30 days.
+pub fn hourly_usage_csv(app_handle: &AppHandle, task: &TaskContext, path: &str, from: Option<i64>, to: Option<i64>) -> Result<ExportSummary, String> {
+    let locale = locale::resolve(app_handle);
+    let rounding = app_settings::current(app_handle).amount_rounding;
+    let to = to.unwrap_or_else(unix_now);
+    let from = from.unwrap_or(to - DEFAULT_USAGE_DAYS * 24 * 60 * 60);
+    let rows = app_handle
+        .try_state::<Storage>()
+        .ok_or_else(|| "Local storage is unavailable.".to_string())?
+        .hourly_usage(from, to, BUSY_UTILIZATION_PERCENT)?
+        .into_iter()
+        .map(|h| {
+            vec![
+                locale.date_time(h.hour_start),
+                h.gpu_id,
+                h.hourly_rate_dgpu.map(|r| locale.plain_amount(r.as_decimal(), &rounding)).unwrap_or_default(),
+                locale.plain_number(h.listed_fraction * 100.0, 1),
+                locale.plain_number(h.busy_fraction * 100.0, 1),
+            ]
+        })
+        .collect();
+    let result = write_csv(task, path, &locale, &["Hour", "GPU", "Rate (DGPU/hr)", "Listed %", "Busy %"], rows);
+    finish(app_handle, "GPU-hours of usage", result)
+}
+
+// The inventory with purchase details, earnings and payback per card.
+pub async fn inventory_csv(app_handle: &AppHandle, task: &TaskContext, path: &str) -> Result<ExportSummary, String> {
+    let locale = locale::resolve(app_handle);
+    let rounding = app_settings::current(app_handle).amount_rounding;
+    let amount = |a: Option<Decimal>| a.map(|a| locale.plain_amount(a, &rounding)).unwrap_or_default();
+    let percent = |p: Option<f64>| p.map(|p| locale.plain_number(p, 1)).unwrap_or_default();
+    let rows = inventory::report(app_handle)
+        .await?
+        .into_iter()
+        .map(|item| {
+            vec![
+                item.gpu_id,
+                item.name.unwrap_or_default(),
+                item.metadata.serial.unwrap_or_default(),
+                item.metadata.purchase_date.unwrap_or_default(),
+                amount(item.metadata.purchase_price),
+                item.metadata.purchase_currency,
+                item.metadata.warranty_until.unwrap_or_default(),
+                amount(Some(item.earned_dgpu)),
+                amount(item.earned_in_purchase_currency),
+                percent(item.payback_percent),
+                percent(item.roi_percent),
+            ]
+        })
+        .collect();
+    let result = write_csv(task, path, &locale, &INVENTORY_HEADER, rows);
+    finish(app_handle, "GPUs of inventory", result)
+}
+
+// The export commands return task IDs.
+#[tauri::command]
+pub async fn export_ledger_csv(app_handle: AppHandle, path: String, since: Option<i64>) -> Result<String, String> {
+    Ok(tasks::submit(&app_handle, TaskRequest::LedgerExport { path, since }))
+}
+
+#[tauri::command]
+pub async fn export_imported_earnings_csv(app_handle: AppHandle, path: String) -> Result<String, String> {
+    Ok(tasks::submit(&app_handle, TaskRequest::ImportedEarningsExport { path }))
+}
+
+#[tauri::command]
+pub async fn export_hourly_usage_csv(app_handle: AppHandle, path: String, from: Option<i64>, to: Option<i64>) -> Result<String, String> {
+    Ok(tasks::submit(&app_handle, TaskRequest::HourlyUsageExport { path, from, to }))
+}
+
+#[tauri::command]
+pub async fn export_inventory_csv(app_handle: AppHandle, path: String) -> Result<String, String> {
+    Ok(tasks::submit(&app_handle, TaskRequest::InventoryExport { path }))
+}
@@ -0,0 +1,214 @@
+// Moving a job off a GPU that faulted mid-run onto another idle, compatible local GPU.
+//
+// The daemon reports faults (Xid errors, a card falling off the bus, uncorrectable ECC) with
+// the job that was running. Depending on the policy we either just record the incident, offer
+// the migration to the user, or perform it straight away using the daemon's checkpoint/restore.
+// Every stage is emitted as `failover_stage` and kept in an incident record. Faults already
+// handled are remembered across restarts, and a migration runs in its own task so the poller's
+// watchdog can't cut it off between checkpoint and restore.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+
+use crate::polling::PollingState;
+use crate::storage::Storage;
+use crate::{app_settings, emit_log_entry, get_detected_gpus, get_timestamp, gpu_ids, invoke_daemon_cli_json_output, GpuInfo};
+
+// A GPU counts as idle below this utilization.
+const IDLE_UTILIZATION_PERCENT: u32 = 10;
+const SEEN_FAULTS_FILE_NAME: &str = "failover_seen_faults.json";
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum FailoverMode {
+    Off, // Record incidents only
+    #[default]
+    Offer,
+    Automatic,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct GpuFault {
+    pub gpu_id: String,
+    pub kind: String, // e.g. "xid", "fell_off_bus", "ecc_uncorrectable"
+    pub detail: Option<String>,
+    pub job_id: Option<String>,
+    pub detected_at: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FailoverStage {
+    Detected,
+    NoCompatibleGpu,
+    Offered,
+    Declined,
+    Checkpointing,
+    Restoring,
+    Completed,
+    Failed,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StageRecord {
+    pub stage: FailoverStage,
+    pub at: String,
+    pub detail: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FailoverIncident {
+    pub incident_id: String,
+    pub job_id: String,
+    pub source_gpu_id: String,
+    pub target_gpu_id: Option<String>,
+    pub fault_kind: String,
+    pub fault_detail: Option<String>,
+    pub stages: Vec<StageRecord>,
+}
+
+impl FailoverIncident {
+    pub fn stage(&self) -> FailoverStage {
+        self.stages.last().map_or(FailoverStage::Detected, |s| s.stage)
+    }
+}
+
+pub struct FailoverState {
+    incidents: Mutex<HashMap<String, FailoverIncident>>,
+    seen_faults: Mutex<HashSet<String>>,
+    path: Option<PathBuf>,
+}
+
+impl FailoverState {
+    pub fn load(app_handle: &AppHandle) -> Self {
+        let path = app_handle.path_resolver().app_data_dir().map(|dir| dir.join(SEEN_FAULTS_FILE_NAME));
+        let seen_faults = path
+            .as_ref()
+            .and_then(|p| fs::read_to_string(p).ok())
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        FailoverState { incidents: Mutex::new(HashMap::new()), seen_faults: Mutex::new(seen_faults), path }
+    }
+
+    fn update_seen<T>(&self, f: impl FnOnce(&mut HashSet<String>) -> T) -> T {
+        let mut seen = self.seen_faults.lock().unwrap();
+        let result = f(&mut seen);
+        if let Some(path) = &self.path {
+            if let Ok(json) = serde_json::to_string(&*seen) {
+                if let Err(e) = fs::write(path, json) {
+                    eprintln!("Failed to persist handled GPU faults: {}", e);
+                }
+            }
+        }
+        result
+    }
+}
+
+fn advance(app_handle: &AppHandle, incident: &mut FailoverIncident, stage: FailoverStage, detail: Option<String>) {
+    incident.stages.push(StageRecord { stage, at: get_timestamp(), detail });
+    if let Some(storage) = app_handle.try_state::<Storage>() {
+        if let Err(e) = storage.upsert_failover_incident(incident) {
+            eprintln!("{}", e);
+        }
+    }
+    app_handle.state::<FailoverState>().incidents.lock().unwrap().insert(incident.incident_id.clone(), incident.clone());
+    if let Err(e) = app_handle.emit_all("failover_stage", incident.clone()) {
+        eprintln!("Failed to emit failover_stage event: {}", e);
+    }
+}
+
+// An idle GPU with at least as much VRAM, preferring the same model.
+fn pick_target<'a>(app_handle: &AppHandle, gpus: &'a [GpuInfo], source_gpu_id: &str) -> Option<&'a GpuInfo> {
+    let source = gpus.iter().find(|g| g.id == source_gpu_id)?;
+    let overheated = app_handle.state::<PollingState>().overheated();
+    gpus.iter()
+        .filter(|g| g.id != source.id && !g.fallback_sourced && !overheated.contains(&g.id))
+        .filter(|g| g.vram_total_mb >= source.vram_total_mb)
+        .filter(|g| g.utilization_gpu_percent.is_some_and(|u| u < IDLE_UTILIZATION_PERCENT))
+        .max_by_key(|g| (g.model == source.model, g.vram_free_mb))
+}
+
+async fn migrate(app_handle: &AppHandle, mut incident: FailoverIncident) {
+    let Some(target) = incident.target_gpu_id.clone() else { return };
+    advance(app_handle, &mut incident, FailoverStage::Checkpointing, None);
+    if let Err(e) = invoke_daemon_cli_json_output::<serde_json::Value>(app_handle, &["--checkpoint-job", "--job-id", &incident.job_id]).await {
+        advance(app_handle, &mut incident, FailoverStage::Failed, Some(format!("Checkpoint failed: {}", e)));
+        return;
+    }
+    advance(app_handle, &mut incident, FailoverStage::Restoring, None);
+    match invoke_daemon_cli_json_output::<serde_json::Value>(app_handle, &["--restore-job", "--job-id", &incident.job_id, "--gpu-id", &gpu_ids::daemon_id(app_handle, &target).await]).await {
+        Ok(_) => {
+            emit_log_entry(app_handle, "status", format!("Job {} moved from GPU {} to GPU {}.", incident.job_id, incident.source_gpu_id, target));
+            advance(app_handle, &mut incident, FailoverStage::Completed, None);
+        }
+        Err(e) => advance(app_handle, &mut incident, FailoverStage::Failed, Some(format!("Restore failed: {}", e))),
+    }
+}
+
+// Called by the poller; a fault is handled once even though the daemon keeps reporting it.
+pub async fn check(app_handle: &AppHandle) {
+    let Ok(faults) = invoke_daemon_cli_json_output::<Vec<GpuFault>>(app_handle, &["--get-gpu-faults-json"]).await else { return };
+    let state = app_handle.state::<FailoverState>();
+    let key = |fault: &GpuFault| fault.job_id.as_ref().map(|job_id| format!("{}:{}:{}", gpu_ids::stable_id(&fault.gpu_id), job_id, fault.detected_at));
+    // Faults the daemon no longer reports can't come back; forget them.
+    let reported: HashSet<String> = faults.iter().filter_map(key).collect();
+    state.update_seen(|seen| seen.retain(|k| reported.contains(k)));
+    for fault in faults {
+        let Some(job_id) = fault.job_id.clone() else { continue };
+        let gpu_id = gpu_ids::stable_id(&fault.gpu_id);
+        let key = format!("{}:{}:{}", gpu_id, job_id, fault.detected_at);
+        if !state.update_seen(|seen| seen.insert(key.clone())) {
+            continue;
+        }
+        emit_log_entry(app_handle, "error", format!("GPU {} faulted ({}) while running job {}.", gpu_id, fault.kind, job_id));
+        let gpus = get_detected_gpus(app_handle.clone()).await.unwrap_or_default();
+        let mut incident = FailoverIncident {
+            incident_id: key,
+            job_id,
+            target_gpu_id: pick_target(app_handle, &gpus, &gpu_id).map(|g| g.id.clone()),
+            source_gpu_id: gpu_id,
+            fault_kind: fault.kind,
+            fault_detail: fault.detail,
+            stages: Vec::new(),
+        };
+        advance(app_handle, &mut incident, FailoverStage::Detected, None);
+        if incident.target_gpu_id.is_none() {
+            advance(app_handle, &mut incident, FailoverStage::NoCompatibleGpu, None);
+            continue;
+        }
+        match app_settings::current(app_handle).failover {
+            FailoverMode::Off => {}
+            FailoverMode::Offer => advance(app_handle, &mut incident, FailoverStage::Offered, None),
+            FailoverMode::Automatic => {
+                let app_handle = app_handle.clone();
+                tauri::async_runtime::spawn(async move { migrate(&app_handle, incident).await });
+            }
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn get_failover_incidents(app_handle: AppHandle, state: State<'_, FailoverState>) -> Result<Vec<FailoverIncident>, String> {
+    if let Some(storage) = app_handle.try_state::<Storage>() {
+        return storage.failover_incidents();
+    }
+    Ok(state.incidents.lock().unwrap().values().cloned().collect())
+}
+
+#[tauri::command]
+pub async fn respond_to_failover(app_handle: AppHandle, state: State<'_, FailoverState>, incident_id: String, accept: bool) -> Result<(), String> {
+    let mut incident = state.incidents.lock().unwrap().get(&incident_id).cloned().ok_or_else(|| format!("No failover incident '{}'.", incident_id))?;
+    if incident.stage() != FailoverStage::Offered {
+        return Err(format!("Failover for job {} is no longer awaiting a decision.", incident.job_id));
+    }
+    if accept {
+        migrate(&app_handle, incident).await;
+    } else {
+        advance(&app_handle, &mut incident, FailoverStage::Declined, None);
+    }
+    Ok(())
+}
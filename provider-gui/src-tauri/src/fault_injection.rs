@@ -0,0 +1,129 @@
+// Fault injection, for exercising the failure-handling paths on purpose.
+//
+// A developer or QA build arms a fault with `inject_fault` and the next matching calls through
+// the daemon IPC helper (or the platform API client, for token expiry) fail the way the real
+// thing would, whether the daemon is real or the demo one. Each fault applies to a fixed number
+// of calls in call order, so a scenario replays the same way every time. Only available in debug
+// builds or when the app is started with `--fault-injection`.
+
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+use crate::{a11y, demo, emit_log_entry, session, DaemonState};
+
+const DEFAULT_DELAY_MS: u64 = 10_000;
+
+static ARMED: Mutex<Vec<ArmedFault>> = Mutex::new(Vec::new());
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FaultKind {
+    DaemonCrash,   // Kills the daemon (or the demo daemon) as if it crashed
+    SlowResponses, // Delays daemon CLI calls
+    MalformedJson, // Truncates daemon CLI output before it is parsed
+    TokenExpiry,   // Platform API answers authenticated requests with 401
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ArmedFault {
+    pub kind: FaultKind,
+    pub remaining_calls: Option<u32>, // None: until cleared
+    pub delay_ms: Option<u64>,        // SlowResponses only
+}
+
+pub fn enabled() -> bool {
+    cfg!(debug_assertions) || std::env::args().any(|arg| arg == "--fault-injection")
+}
+
+// Uses up one call's worth of the armed fault of this kind, if any.
+fn take(kind: FaultKind) -> Option<ArmedFault> {
+    let mut armed = ARMED.lock().unwrap();
+    let index = armed.iter().position(|f| f.kind == kind)?;
+    let fault = armed[index].clone();
+    if let Some(remaining) = &mut armed[index].remaining_calls {
+        *remaining -= 1;
+        if *remaining == 0 {
+            armed.remove(index);
+        }
+    }
+    Some(fault)
+}
+
+pub async fn delay_daemon_call(app_handle: &AppHandle, command_args: &[&str]) {
+    if let Some(fault) = take(FaultKind::SlowResponses) {
+        let delay = Duration::from_millis(fault.delay_ms.unwrap_or(DEFAULT_DELAY_MS));
+        emit_log_entry(app_handle, "status", format!("[fault injection] Delaying {:?} by {} ms.", command_args, delay.as_millis()));
+        tokio::time::sleep(delay).await;
+    }
+}
+
+// Daemon output as the IPC helper should see it.
+pub fn daemon_output(app_handle: &AppHandle, stdout: String) -> String {
+    if take(FaultKind::MalformedJson).is_none() {
+        return stdout;
+    }
+    emit_log_entry(app_handle, "status", "[fault injection] Truncating daemon output.".to_string());
+    let cut = stdout.char_indices().nth(stdout.chars().count() / 2).map_or(0, |(i, _)| i);
+    format!("{}\u{fffd}", &stdout[..cut])
+}
+
+// Whether an authenticated platform request should be answered as if the token had expired.
+pub fn token_expired() -> bool {
+    take(FaultKind::TokenExpiry).is_some()
+}
+
+fn crash_daemon(app_handle: &AppHandle) -> Result<(), String> {
+    let state = app_handle.state::<DaemonState>();
+    if demo::enabled() {
+        let mut status = state.status.lock().unwrap();
+        if *status != "online" {
+            return Err("The demo daemon is not running.".to_string());
+        }
+        // The same transitions the Terminated handler makes for a real crash.
+        *status = "error".to_string();
+        drop(status);
+        emit_log_entry(app_handle, "error", "Daemon terminated unexpectedly (e.g. by signal).".to_string());
+        session::record_status(app_handle, "error");
+        a11y::daemon_status(app_handle, "error");
+        return Ok(());
+    }
+    // The status stays "online", so the Terminated handler treats the kill as a crash.
+    let child = state.process.lock().unwrap().take().ok_or_else(|| "The daemon is not running.".to_string())?;
+    child.kill().map_err(|e| format!("Failed to kill the daemon: {}", e))
+}
+
+#[tauri::command]
+pub async fn inject_fault(app_handle: AppHandle, kind: FaultKind, calls: Option<u32>, delay_ms: Option<u64>) -> Result<Vec<ArmedFault>, String> {
+    if !enabled() {
+        return Err("Fault injection is only available in debug builds or with --fault-injection.".to_string());
+    }
+    if calls == Some(0) {
+        return Err("A fault has to apply to at least one call.".to_string());
+    }
+    emit_log_entry(&app_handle, "status", format!("[fault injection] Injecting {:?}.", kind));
+    if kind == FaultKind::DaemonCrash {
+        crash_daemon(&app_handle)?;
+    } else {
+        let mut armed = ARMED.lock().unwrap();
+        armed.retain(|f| f.kind != kind);
+        armed.push(ArmedFault { kind, remaining_calls: calls, delay_ms: delay_ms.filter(|_| kind == FaultKind::SlowResponses) });
+    }
+    Ok(ARMED.lock().unwrap().clone())
+}
+
+#[tauri::command]
+pub async fn clear_faults(app_handle: AppHandle) -> Result<(), String> {
+    if !enabled() {
+        return Err("Fault injection is only available in debug builds or with --fault-injection.".to_string());
+    }
+    ARMED.lock().unwrap().clear();
+    emit_log_entry(&app_handle, "status", "[fault injection] Cleared all faults.".to_string());
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_armed_faults() -> Result<Vec<ArmedFault>, String> {
+    Ok(ARMED.lock().unwrap().clone())
+}
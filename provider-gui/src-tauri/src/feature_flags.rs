@@ -0,0 +1,196 @@
+// Feature flags for gradually rolling out risky subsystems.
+//
+// Flags are fetched from the platform per provider, so a subsystem can be enabled for a
+// fraction of providers or switched off remotely without shipping a release. The last fetched
+// values are cached per account for offline starts, and every known flag has a built-in
+// default for when nothing has ever been fetched. Local overrides in `AppSettings` win over
+// both, for support cases and for trying a subsystem early.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+
+use crate::app_settings::{self, AppSettingsState};
+use crate::{accounts, api_client, emit_log_entry, get_timestamp, shutdown};
+
+const CACHE_FILE_NAME: &str = "feature_flags.json";
+const REFRESH_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+pub const GRPC_IPC: &str = "grpc_ipc";
+pub const STAKING: &str = "staking";
+pub const SPOT_MODE: &str = "spot_mode";
+pub const COMPUTE_ATTESTATION: &str = "compute_attestation";
+
+// Offline defaults. Spot mode has shipped; the others stay off until the platform enables them.
+const DEFAULTS: &[(&str, bool)] = &[(GRPC_IPC, false), (STAKING, false), (SPOT_MODE, true), (COMPUTE_ATTESTATION, false)];
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+struct FlagCache {
+    flags: HashMap<String, bool>,
+    fetched_at: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FlagSource {
+    Override,
+    Remote,
+    Default,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct FeatureFlag {
+    pub key: String,
+    pub enabled: bool,
+    pub source: FlagSource,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct FeatureFlagReport {
+    pub flags: Vec<FeatureFlag>,
+    pub fetched_at: Option<String>,
+}
+
+pub struct FeatureFlagState {
+    cache: Mutex<FlagCache>,
+    path: Mutex<Option<PathBuf>>,
+}
+
+fn read_cache(app_handle: &AppHandle) -> (FlagCache, Option<PathBuf>) {
+    let path = accounts::data_dir(app_handle).map(|dir| dir.join(CACHE_FILE_NAME));
+    let cache = path
+        .as_ref()
+        .and_then(|p| fs::read_to_string(p).ok())
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default();
+    (cache, path)
+}
+
+impl FeatureFlagState {
+    pub fn load(app_handle: &AppHandle) -> Self {
+        let (cache, path) = read_cache(app_handle);
+        FeatureFlagState { cache: Mutex::new(cache), path: Mutex::new(path) }
+    }
+
+    // Called after the active account changes.
+    pub fn reload(&self, app_handle: &AppHandle) {
+        let (cache, path) = read_cache(app_handle);
+        *self.cache.lock().unwrap() = cache;
+        *self.path.lock().unwrap() = path;
+    }
+
+    fn store(&self, flags: HashMap<String, bool>) {
+        let mut cache = self.cache.lock().unwrap();
+        cache.flags = flags;
+        cache.fetched_at = Some(get_timestamp());
+        if let Some(path) = &*self.path.lock().unwrap() {
+            if let Some(dir) = path.parent() {
+                let _ = fs::create_dir_all(dir);
+            }
+            if let Ok(json) = serde_json::to_string_pretty(&*cache) {
+                if let Err(e) = fs::write(path, json) {
+                    eprintln!("Failed to persist feature flags: {}", e);
+                }
+            }
+        }
+    }
+}
+
+fn resolve(key: &str, overrides: &HashMap<String, bool>, remote: &HashMap<String, bool>) -> FeatureFlag {
+    let (enabled, source) = if let Some(enabled) = overrides.get(key) {
+        (*enabled, FlagSource::Override)
+    } else if let Some(enabled) = remote.get(key) {
+        (*enabled, FlagSource::Remote)
+    } else {
+        (DEFAULTS.iter().find(|(k, _)| *k == key).is_some_and(|(_, d)| *d), FlagSource::Default)
+    };
+    FeatureFlag { key: key.to_string(), enabled, source }
+}
+
+fn report(app_handle: &AppHandle) -> FeatureFlagReport {
+    let overrides = app_settings::current(app_handle).feature_flag_overrides;
+    let state = app_handle.state::<FeatureFlagState>();
+    let cache = state.cache.lock().unwrap();
+    let mut keys: Vec<&str> = DEFAULTS.iter().map(|(k, _)| *k).collect();
+    keys.extend(cache.flags.keys().chain(overrides.keys()).map(String::as_str));
+    keys.sort_unstable();
+    keys.dedup();
+    FeatureFlagReport {
+        flags: keys.into_iter().map(|key| resolve(key, &overrides, &cache.flags)).collect(),
+        fetched_at: cache.fetched_at.clone(),
+    }
+}
+
+// Unknown flags are off.
+pub fn is_enabled(app_handle: &AppHandle, key: &str) -> bool {
+    let overrides = app_settings::current(app_handle).feature_flag_overrides;
+    let state = app_handle.state::<FeatureFlagState>();
+    let cache = state.cache.lock().unwrap();
+    resolve(key, &overrides, &cache.flags).enabled
+}
+
+pub fn require(app_handle: &AppHandle, key: &str, what: &str) -> Result<(), String> {
+    if is_enabled(app_handle, key) {
+        Ok(())
+    } else {
+        Err(format!("{} isn't available for this provider yet.", what))
+    }
+}
+
+pub async fn refresh(app_handle: &AppHandle) -> Result<(), String> {
+    let flags: HashMap<String, bool> = api_client::get_json(app_handle, "/api/v1/providers/me/feature-flags").await?;
+    let before = report(app_handle).flags;
+    app_handle.state::<FeatureFlagState>().store(flags);
+    let after = report(app_handle);
+    let changed = after.flags.len() != before.len() || after.flags.iter().zip(&before).any(|(a, b)| a.key != b.key || a.enabled != b.enabled);
+    if changed {
+        if let Err(e) = app_handle.emit_all("feature_flags_changed", &after) {
+            eprintln!("Failed to emit feature_flags_changed event: {}", e);
+        }
+    }
+    Ok(())
+}
+
+pub fn spawn_refresher(app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            if let Err(e) = refresh(&app_handle).await {
+                eprintln!("Feature flag refresh failed; using cached values: {}", e);
+            }
+            if !shutdown::sleep(REFRESH_INTERVAL).await {
+                break;
+            }
+        }
+    });
+}
+
+#[tauri::command]
+pub async fn get_feature_flags(app_handle: AppHandle) -> Result<FeatureFlagReport, String> {
+    Ok(report(&app_handle))
+}
+
+// `None` removes the override so the platform's value applies again.
+#[tauri::command]
+pub async fn set_feature_flag_override(
+    app_handle: AppHandle,
+    state: State<'_, AppSettingsState>,
+    key: String,
+    enabled: Option<bool>,
+) -> Result<FeatureFlagReport, String> {
+    let mut settings = app_settings::current(&app_handle);
+    match enabled {
+        Some(enabled) => settings.feature_flag_overrides.insert(key.clone(), enabled),
+        None => settings.feature_flag_overrides.remove(&key),
+    };
+    app_settings::update_app_settings(app_handle.clone(), state, settings).await?;
+    emit_log_entry(&app_handle, "status", format!("Feature flag '{}' override set to {:?}.", key, enabled));
+    let report = report(&app_handle);
+    if let Err(e) = app_handle.emit_all("feature_flags_changed", &report) {
+        eprintln!("Failed to emit feature_flags_changed event: {}", e);
+    }
+    Ok(report)
+}
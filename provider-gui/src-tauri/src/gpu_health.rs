@@ -0,0 +1,336 @@
+// Long-term hardware health trends from stored telemetry.
+//
+// Degrading cards rarely fail outright first: idle temperatures creep up as thermal paste dries
+// out and fans wear, corrected ECC errors start appearing as VRAM ages, and memory clocks under
+// load begin to sag. Each GPU gets a 0-100 health score from these trends and a daily check
+// warns, once per finding, with the maintenance that usually helps, before a renter is affected.
+//
+// Fan failure is the exception that can't wait for a trend: a fan reading zero, or far below
+// what the temperature calls for, while the card is hot raises a critical `hardware_alert` on
+// the next poll and, unless disabled, unlists the GPU.
+
+use serde::Serialize;
+use serde_json::json;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+use crate::storage::{unix_now, DailyHealth, Storage};
+use crate::watchdog::{self, Heartbeat};
+use crate::{a11y, app_settings, emit_log_entry, get_detected_gpus, get_timestamp, plugins, set_gpu_rental_config, GpuInfo};
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+// A check reads a month of daily aggregates; longer than this means it is stuck.
+const CHECK_GRACE: Duration = Duration::from_secs(10 * 60);
+const DAY_SECS: i64 = 24 * 60 * 60;
+const TREND_DAYS: i64 = 30;
+const MIN_DAYS_FOR_TREND: usize = 7;
+const IDLE_BELOW_PERCENT: u32 = 10;
+const LOADED_FROM_PERCENT: u32 = 50;
+
+// Findings below these levels are normal variation.
+const IDLE_TEMP_RISE_C_PER_WEEK: f64 = 0.5;
+const HIGH_IDLE_TEMP_C: f64 = 55.0;
+const CLOCK_SAG_PERCENT: f64 = 5.0;
+const WARN_BELOW_SCORE: u32 = 75;
+
+// A fan is suspect when the card is at least this hot and loaded...
+const FAN_CHECK_MIN_TEMP_C: u32 = 60;
+const FAN_CHECK_MIN_UTILIZATION_PERCENT: u32 = 50;
+// ...and its speed is below this share of the reference curve, for this many polls in a row.
+const FAN_CURVE_FRACTION: f64 = 0.3;
+const FAN_SUSPECT_POLLS: u32 = 3;
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthFinding {
+    RisingIdleTemperature,
+    HighIdleTemperature,
+    EccCorrections,
+    MemoryClockInstability,
+}
+
+impl HealthFinding {
+    fn suggestion(self) -> &'static str {
+        match self {
+            HealthFinding::RisingIdleTemperature | HealthFinding::HighIdleTemperature => {
+                "Clean dust from the heatsink and check the fans spin freely; if temperatures stay high, repaste the GPU or replace the fans."
+            }
+            HealthFinding::EccCorrections => {
+                "VRAM is correcting errors. Remove any memory overclock, check VRAM thermal pads, and plan for replacement if the rate keeps rising."
+            }
+            HealthFinding::MemoryClockInstability => {
+                "Memory clocks sag under load, usually from hot VRAM or an unstable overclock. Check thermal pads, airflow and the power supply."
+            }
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct HealthTrendPoint {
+    pub day: String,
+    pub idle_temperature_c: Option<f64>,
+    pub ecc_corrections: Option<i64>,
+    pub memory_clock_sag_percent: Option<f64>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct HealthWarning {
+    pub finding: HealthFinding,
+    pub detail: String,
+    pub suggestion: String,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct GpuHealthTrend {
+    pub gpu_id: String,
+    pub score: u32, // 100 = no signs of degradation
+    pub idle_temperature_trend_c_per_week: Option<f64>,
+    pub ecc_corrections_last_week: Option<i64>,
+    pub worst_memory_clock_sag_percent: Option<f64>,
+    pub warnings: Vec<HealthWarning>,
+    pub days: Vec<HealthTrendPoint>,
+}
+
+pub struct GpuHealthState {
+    // (gpu_id, finding) pairs already notified; cleared when the finding goes away.
+    notified: Mutex<HashSet<(String, HealthFinding)>>,
+    // Consecutive suspect fan readings per GPU, and GPUs already alerted on.
+    fan_suspect_polls: Mutex<HashMap<String, u32>>,
+    fan_alerted: Mutex<HashSet<String>>,
+}
+
+impl GpuHealthState {
+    pub fn new() -> Self {
+        GpuHealthState {
+            notified: Mutex::new(HashSet::new()),
+            fan_suspect_polls: Mutex::new(HashMap::new()),
+            fan_alerted: Mutex::new(HashSet::new()),
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, specta::Type)]
+pub struct HardwareAlert {
+    pub gpu_id: String,
+    pub gpu_name: String,
+    pub severity: String, // "critical"
+    pub kind: String,     // "fan_stopped" | "fan_below_curve"
+    pub message: String,
+    pub temperature_c: u32,
+    pub fan_speed_percent: u32,
+    pub rentals_paused: bool,
+    pub detected_at: String,
+}
+
+// Minimum fan speed a typical stock curve runs at for `temperature_c`: nothing below 50 °C,
+// then rising about 2% per degree.
+fn reference_fan_percent(temperature_c: u32) -> f64 {
+    (f64::from(temperature_c.saturating_sub(50)) * 2.0 + 30.0).min(100.0)
+}
+
+// Returns the alert kind when the reading looks like a failed or failing fan.
+fn fan_suspect(gpu: &GpuInfo) -> Option<&'static str> {
+    let (fan, temperature) = (gpu.fan_speed_percent?, gpu.temperature_c?);
+    let loaded = gpu.utilization_gpu_percent.is_some_and(|u| u >= FAN_CHECK_MIN_UTILIZATION_PERCENT);
+    if temperature < FAN_CHECK_MIN_TEMP_C || !loaded {
+        return None;
+    }
+    if fan == 0 {
+        Some("fan_stopped")
+    } else if f64::from(fan) < reference_fan_percent(temperature) * FAN_CURVE_FRACTION {
+        Some("fan_below_curve")
+    } else {
+        None
+    }
+}
+
+pub async fn check_fans(app_handle: &AppHandle, gpus: &[GpuInfo]) {
+    let state = app_handle.state::<GpuHealthState>();
+    let mut alerts = Vec::new();
+    {
+        let mut polls = state.fan_suspect_polls.lock().unwrap();
+        let mut alerted = state.fan_alerted.lock().unwrap();
+        for gpu in gpus {
+            let Some(kind) = fan_suspect(gpu) else {
+                polls.remove(&gpu.id);
+                // Re-arm once the fan is seen working under load again.
+                if gpu.fan_speed_percent.is_some_and(|f| f > 0) {
+                    alerted.remove(&gpu.id);
+                }
+                continue;
+            };
+            let count = polls.entry(gpu.id.clone()).or_insert(0);
+            *count += 1;
+            if *count >= FAN_SUSPECT_POLLS && alerted.insert(gpu.id.clone()) {
+                alerts.push((gpu.clone(), kind));
+            }
+        }
+    }
+
+    let pause = app_settings::current(app_handle).pause_on_fan_failure;
+    for (gpu, kind) in alerts {
+        let (temperature_c, fan_speed_percent) = (gpu.temperature_c.unwrap_or_default(), gpu.fan_speed_percent.unwrap_or_default());
+        let mut message = match kind {
+            "fan_stopped" => format!("{} fan reads 0% at {} °C under load; the fan may have failed.", gpu.name, temperature_c),
+            _ => format!("{} fan is at {}% at {} °C, far below normal; the fan may be failing.", gpu.name, fan_speed_percent, temperature_c),
+        };
+        let mut rentals_paused = false;
+        if pause && gpu.is_available_for_rent {
+            let rate = gpu.current_hourly_rate_dgpu.unwrap_or_default();
+            match set_gpu_rental_config(app_handle.clone(), gpu.id.clone(), rate, false, None, None).await {
+                Ok(_) => {
+                    rentals_paused = true;
+                    message.push_str(" Rentals on this GPU are paused until you re-list it.");
+                }
+                Err(e) => message.push_str(&format!(" Failed to pause rentals: {}", e)),
+            }
+        }
+        emit_log_entry(app_handle, "error", message.clone());
+        a11y::announce(app_handle, "alert", a11y::Severity::Critical, message.clone());
+        let alert = HardwareAlert {
+            gpu_id: gpu.id.clone(),
+            gpu_name: gpu.name.clone(),
+            severity: "critical".to_string(),
+            kind: kind.to_string(),
+            message,
+            temperature_c,
+            fan_speed_percent,
+            rentals_paused,
+            detected_at: get_timestamp(),
+        };
+        plugins::notify(app_handle, "hardware_alert", &json!(alert), false);
+        if let Err(e) = app_handle.emit_all("hardware_alert", alert) {
+            eprintln!("Failed to emit hardware_alert event: {}", e);
+        }
+    }
+}
+
+fn format_day(unix: i64) -> String {
+    humantime::format_rfc3339_seconds(std::time::UNIX_EPOCH + Duration::from_secs(unix.max(0) as u64))
+        .to_string()
+        .chars()
+        .take(10)
+        .collect()
+}
+
+// Least-squares slope of `points` (x in days), in units per day.
+fn slope(points: &[(f64, f64)]) -> Option<f64> {
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|p| p.0).sum::<f64>() / n;
+    let mean_y = points.iter().map(|p| p.1).sum::<f64>() / n;
+    let var_x: f64 = points.iter().map(|p| (p.0 - mean_x).powi(2)).sum();
+    (var_x > 0.0).then(|| points.iter().map(|p| (p.0 - mean_x) * (p.1 - mean_y)).sum::<f64>() / var_x)
+}
+
+fn clock_sag(day: &DailyHealth) -> Option<f64> {
+    let (min, max) = (day.loaded_memory_clock_min_mhz?, day.loaded_memory_clock_max_mhz?);
+    (max > 0).then(|| (max - min) as f64 / max as f64 * 100.0)
+}
+
+fn analyze(gpu_id: &str, history: &[DailyHealth]) -> GpuHealthTrend {
+    let days: Vec<HealthTrendPoint> = history
+        .iter()
+        .map(|d| HealthTrendPoint {
+            day: format_day(d.day_start),
+            idle_temperature_c: d.idle_temperature_c,
+            ecc_corrections: d.ecc_corrections,
+            memory_clock_sag_percent: clock_sag(d),
+        })
+        .collect();
+
+    let temps: Vec<(f64, f64)> =
+        history.iter().filter_map(|d| d.idle_temperature_c.map(|t| ((d.day_start / DAY_SECS) as f64, t))).collect();
+    let temp_trend = (temps.len() >= MIN_DAYS_FOR_TREND).then(|| slope(&temps)).flatten().map(|per_day| per_day * 7.0);
+    let recent_idle = temps.iter().rev().take(3).map(|t| t.1).fold(None, |acc: Option<f64>, t| Some(acc.map_or(t, |a| a.min(t))));
+    let week_ago = unix_now() - 7 * DAY_SECS;
+    let ecc_last_week = history
+        .iter()
+        .filter(|d| d.day_start >= week_ago)
+        .filter_map(|d| d.ecc_corrections)
+        .reduce(|a, b| a + b);
+    let worst_sag = history.iter().filter(|d| d.day_start >= week_ago).filter_map(clock_sag).reduce(f64::max);
+
+    let mut score = 100.0_f64;
+    let mut warnings = Vec::new();
+    let mut warn = |finding: HealthFinding, detail: String| {
+        warnings.push(HealthWarning { finding, detail, suggestion: finding.suggestion().to_string() })
+    };
+    if let Some(trend) = temp_trend.filter(|t| *t >= IDLE_TEMP_RISE_C_PER_WEEK) {
+        score -= (trend * 10.0).min(30.0);
+        warn(HealthFinding::RisingIdleTemperature, format!("Idle temperature is rising by {:.1} °C per week.", trend));
+    }
+    if let Some(idle) = recent_idle.filter(|t| *t >= HIGH_IDLE_TEMP_C) {
+        score -= ((idle - HIGH_IDLE_TEMP_C) * 2.0 + 5.0).min(20.0);
+        warn(HealthFinding::HighIdleTemperature, format!("Idle temperature is {:.0} °C.", idle));
+    }
+    if let Some(ecc) = ecc_last_week.filter(|n| *n > 0) {
+        score -= ((1.0 + ecc as f64).log10() * 15.0).min(30.0);
+        warn(HealthFinding::EccCorrections, format!("{} corrected ECC errors in the last week.", ecc));
+    }
+    if let Some(sag) = worst_sag.filter(|s| *s >= CLOCK_SAG_PERCENT) {
+        score -= (sag * 1.5).min(30.0);
+        warn(HealthFinding::MemoryClockInstability, format!("Memory clock dipped {:.0}% below its peak under load.", sag));
+    }
+
+    GpuHealthTrend {
+        gpu_id: gpu_id.to_string(),
+        score: score.clamp(0.0, 100.0).round() as u32,
+        idle_temperature_trend_c_per_week: temp_trend,
+        ecc_corrections_last_week: ecc_last_week,
+        worst_memory_clock_sag_percent: worst_sag,
+        warnings,
+        days,
+    }
+}
+
+pub fn health_trend(app_handle: &AppHandle, gpu_id: &str) -> Result<GpuHealthTrend, String> {
+    let storage = app_handle.try_state::<Storage>().ok_or_else(|| "Local storage is unavailable.".to_string())?;
+    let history = storage.daily_health(gpu_id, unix_now() - TREND_DAYS * DAY_SECS, IDLE_BELOW_PERCENT, LOADED_FROM_PERCENT)?;
+    Ok(analyze(gpu_id, &history))
+}
+
+async fn check(app_handle: &AppHandle) -> Result<(), String> {
+    let state = app_handle.state::<GpuHealthState>();
+    for gpu in get_detected_gpus(app_handle.clone()).await? {
+        let trend = health_trend(app_handle, &gpu.id)?;
+        let mut notified = state.notified.lock().unwrap();
+        notified.retain(|(id, finding)| id != &gpu.id || trend.warnings.iter().any(|w| w.finding == *finding));
+        if trend.score >= WARN_BELOW_SCORE {
+            continue;
+        }
+        let fresh: Vec<&HealthWarning> = trend.warnings.iter().filter(|w| notified.insert((gpu.id.clone(), w.finding))).collect();
+        if fresh.is_empty() {
+            continue;
+        }
+        let summary: Vec<&str> = fresh.iter().map(|w| w.detail.as_str()).collect();
+        emit_log_entry(app_handle, "error", format!("{} health score is {}: {}", gpu.name, trend.score, summary.join(" ")));
+        let payload = json!({ "gpu": gpu, "score": trend.score, "warnings": fresh, "checked_at": get_timestamp() });
+        plugins::notify(app_handle, "gpu_health_warning", &payload, false);
+        if let Err(e) = app_handle.emit_all("gpu_health_warning", payload) {
+            eprintln!("Failed to emit gpu_health_warning event: {}", e);
+        }
+    }
+    Ok(())
+}
+
+pub fn spawn_health_monitor(app_handle: AppHandle) {
+    watchdog::supervise(&app_handle, "health_checker", CHECK_GRACE, run_health_monitor);
+}
+
+async fn run_health_monitor(app_handle: AppHandle, heartbeat: Heartbeat) {
+    loop {
+        if !heartbeat.sleep(CHECK_INTERVAL).await {
+            break;
+        }
+        if let Err(e) = check(&app_handle).await {
+            eprintln!("GPU health check failed: {}", e);
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn get_gpu_health_trend(app_handle: AppHandle, gpu_id: String) -> Result<GpuHealthTrend, String> {
+    health_trend(&app_handle, &gpu_id)
+}
@@ -0,0 +1,202 @@
+// Stable GPU identities and provider-assigned nicknames.
+//
+// The daemon numbers GPUs in enumeration order ("GPU-0", "GPU-1"), which a reboot, a driver
+// update or a card changing slots can reshuffle, and two cards of the same model report the same
+// product name. Every GPU list is re-keyed to stable IDs (board UUID, else PCI slot; see
+// `GpuInfo::stable_id`) before anything stores or shows it, so telemetry, jobs, inventory and
+// analytics follow the physical card; the daemon's ID is only used again for calls that take
+// `--gpu-id`; jobs are fetched through `local_jobs`, which re-keys their GPUs too. A GPU's name
+// is its product name, numbered by PCI slot when several cards share it; the provider's nickname
+// is kept beside it in `nickname`, so code matching on product names isn't thrown off. The first
+// time a card is seen, and on the first listing after each start, history recorded under its
+// daemon ID (by earlier versions, or before the GPUs were listed) is moved to its stable ID.
+
+use dante_provider_core::models::{normalize_pci_bus_id, GpuIdMap};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+
+use crate::storage::{unix_now, Storage};
+use crate::{emit_log_entry, invoke_daemon_cli_json_output, nvidia_smi, GpuInfo, LocalJob};
+
+const MAX_NICKNAME_CHARS: usize = 40;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GpuIdentity {
+    pub gpu_id: String, // Stable ID
+    pub uuid: Option<String>,
+    pub pci_bus_id: Option<String>,
+    pub daemon_id: String, // As last reported
+    pub product_name: String,
+    pub nickname: Option<String>,
+    pub first_seen_at: i64,
+    pub last_seen_at: i64,
+}
+
+struct Known {
+    ids: GpuIdMap,
+    names: HashMap<String, String>, // Numbered product name by stable ID, from the last full listing
+    identities: Vec<GpuIdentity>,
+}
+
+static KNOWN: Mutex<Option<Known>> = Mutex::new(None);
+
+fn load_identities(app_handle: &AppHandle) -> Vec<GpuIdentity> {
+    app_handle.try_state::<Storage>().and_then(|s| s.gpu_identities().map_err(|e| eprintln!("{}", e)).ok()).unwrap_or_default()
+}
+
+// "RTX 4090 #1", "RTX 4090 #2" for cards sharing a product name, ordered by PCI slot.
+fn display_names(gpus: &[GpuInfo]) -> HashMap<String, String> {
+    let mut names = HashMap::new();
+    for gpu in gpus {
+        let mut twins: Vec<&GpuInfo> = gpus.iter().filter(|g| g.name == gpu.name).collect();
+        let name = if twins.len() > 1 {
+            twins.sort_by_key(|g| (g.pci_bus_id.as_deref().and_then(normalize_pci_bus_id), g.id.clone()));
+            let position = twins.iter().position(|g| g.id == gpu.id).unwrap_or(0);
+            format!("{} #{}", gpu.name, position + 1)
+        } else {
+            gpu.name.clone()
+        };
+        names.insert(gpu.id.clone(), name);
+    }
+    names
+}
+
+// Re-keys a full GPU listing to stable IDs and applies names and nicknames. Called on every listing
+// from the daemon or nvidia-smi (see `nvidia_smi::detect_gpus`).
+pub fn apply(app_handle: &AppHandle, gpus: &mut [GpuInfo]) {
+    let ids = GpuIdMap::stabilize(gpus);
+    let mut known = KNOWN.lock().unwrap();
+    // Until the first listing, nothing could re-key jobs, so any stored since start carry daemon IDs.
+    let (mut identities, first_listing) = match known.take() {
+        Some(known) => (known.identities, false),
+        None => (load_identities(app_handle), true),
+    };
+    let now = unix_now();
+    let storage = app_handle.try_state::<Storage>();
+    for (gpu, (daemon_id, _)) in gpus.iter().zip(ids.pairs()) {
+        let (changed, is_new) = match identities.iter_mut().find(|i| i.gpu_id == gpu.id) {
+            Some(identity) => {
+                let changed = identity.daemon_id != *daemon_id || identity.product_name != gpu.name || now - identity.last_seen_at > 24 * 60 * 60;
+                if changed {
+                    identity.daemon_id = daemon_id.clone();
+                    identity.product_name = gpu.name.clone();
+                    identity.last_seen_at = now;
+                }
+                (changed, false)
+            }
+            None => {
+                identities.push(GpuIdentity {
+                    gpu_id: gpu.id.clone(),
+                    uuid: gpu.uuid.clone(),
+                    pci_bus_id: gpu.pci_bus_id.as_deref().and_then(normalize_pci_bus_id),
+                    daemon_id: daemon_id.clone(),
+                    product_name: gpu.name.clone(),
+                    nickname: None,
+                    first_seen_at: now,
+                    last_seen_at: now,
+                });
+                (true, true)
+            }
+        };
+        let Some(storage) = &storage else { continue };
+        if (is_new || first_listing) && *daemon_id != gpu.id {
+            match storage.rekey_gpu(daemon_id, &gpu.id) {
+                Ok(0) => {}
+                Ok(moved) => emit_log_entry(app_handle, "status", format!("Moved {} history rows of {} from ID {} to {}.", moved, gpu.name, daemon_id, gpu.id)),
+                Err(e) => eprintln!("{}", e),
+            }
+        }
+        if changed {
+            if let Some(identity) = identities.iter().find(|i| i.gpu_id == gpu.id) {
+                if let Err(e) = storage.upsert_gpu_identity(identity) {
+                    eprintln!("{}", e);
+                }
+            }
+        }
+    }
+    let names = display_names(gpus);
+    for gpu in gpus.iter_mut() {
+        if let Some(name) = names.get(&gpu.id) {
+            gpu.name = name.clone();
+        }
+        gpu.nickname = identities.iter().find(|i| i.gpu_id == gpu.id).and_then(|i| i.nickname.clone());
+    }
+    *known = Some(Known { ids, names, identities });
+}
+
+// For a single GPU the daemon returns outside a full listing, e.g. after a config change.
+pub fn apply_one(gpu: &mut GpuInfo) {
+    let known = KNOWN.lock().unwrap();
+    let stable = gpu.stable_id();
+    if let Some(known) = known.as_ref() {
+        if let Some(name) = known.names.get(&stable) {
+            gpu.name = name.clone();
+        }
+        gpu.nickname = known.identities.iter().find(|i| i.gpu_id == stable).and_then(|i| i.nickname.clone());
+    }
+    gpu.id = stable;
+}
+
+pub fn stabilize_jobs(jobs: &mut [LocalJob]) {
+    if let Some(known) = KNOWN.lock().unwrap().as_ref() {
+        known.ids.stabilize_jobs(jobs);
+    }
+}
+
+// Lists the GPUs if no listing has been seen yet, so the daemon's IDs can be mapped.
+async fn ensure_listed(app_handle: &AppHandle) {
+    if KNOWN.lock().unwrap().is_none() {
+        let _ = nvidia_smi::detect_gpus(app_handle).await;
+    }
+}
+
+// The daemon's jobs with their GPUs under stable IDs. Every job fetch goes through here.
+pub async fn local_jobs(app_handle: &AppHandle) -> Result<Vec<LocalJob>, String> {
+    let mut jobs = invoke_daemon_cli_json_output::<Vec<LocalJob>>(app_handle, &["--get-local-jobs-json"]).await?;
+    ensure_listed(app_handle).await;
+    stabilize_jobs(&mut jobs);
+    Ok(jobs)
+}
+
+// What is known about a GPU by its stable ID, from the last listing.
+pub fn identity(gpu_id: &str) -> Option<GpuIdentity> {
+    KNOWN.lock().unwrap().as_ref()?.identities.iter().find(|i| i.gpu_id == gpu_id).cloned()
+}
+
+// The stable ID for an ID the daemon reported, such as a fault's GPU.
+pub fn stable_id(daemon_id: &str) -> String {
+    KNOWN.lock().unwrap().as_ref().map_or(daemon_id, |k| k.ids.stable(daemon_id)).to_string()
+}
+
+// The daemon's current ID for a stable ID, for `--gpu-id`. Lists the GPUs first if no listing
+// has been seen yet.
+pub async fn daemon_id(app_handle: &AppHandle, gpu_id: &str) -> String {
+    ensure_listed(app_handle).await;
+    KNOWN.lock().unwrap().as_ref().map_or(gpu_id, |k| k.ids.daemon(gpu_id)).to_string()
+}
+
+// Sets (or, with an empty name, clears) the nickname of a GPU. Nicknames must be unique so they
+// actually tell cards apart.
+#[tauri::command]
+pub async fn rename_gpu(app_handle: AppHandle, gpu_id: String, name: String) -> Result<GpuIdentity, String> {
+    let storage = app_handle.try_state::<Storage>().ok_or_else(|| "Local storage is unavailable.".to_string())?;
+    let name = name.trim();
+    if name.chars().count() > MAX_NICKNAME_CHARS {
+        return Err(format!("GPU names are limited to {} characters.", MAX_NICKNAME_CHARS));
+    }
+    ensure_listed(&app_handle).await;
+    let mut known = KNOWN.lock().unwrap();
+    let known = known.as_mut().ok_or_else(|| "GPUs can't be listed right now; try again once the daemon is running.".to_string())?;
+    if !name.is_empty() && known.identities.iter().any(|i| i.gpu_id != gpu_id && i.nickname.as_deref().is_some_and(|n| n.eq_ignore_ascii_case(name))) {
+        return Err(format!("Another GPU is already called '{}'.", name));
+    }
+    let identity = known.identities.iter_mut().find(|i| i.gpu_id == gpu_id).ok_or_else(|| format!("No GPU with ID '{}'.", gpu_id))?;
+    identity.nickname = Some(name.to_string()).filter(|n| !n.is_empty());
+    storage.upsert_gpu_identity(identity)?;
+    let identity = identity.clone();
+    let display = identity.nickname.clone().unwrap_or_else(|| known.names.get(&identity.gpu_id).cloned().unwrap_or_else(|| identity.product_name.clone()));
+    emit_log_entry(&app_handle, "status", format!("GPU {} is now shown as '{}'.", identity.gpu_id, display));
+    Ok(identity)
+}
@@ -0,0 +1,151 @@
+// GPU process inspector: what is using a GPU right now.
+//
+// The daemon is asked first (it knows which containers belong to rentals); if it doesn't
+// support the query we fall back to nvidia-smi. Processes that aren't part of a Dante job can
+// starve rentals of VRAM, so they are flagged, and an optional policy warns when a job is
+// about to start on a GPU where foreign processes hold significant memory.
+
+use dante_provider_core::models::normalize_pci_bus_id;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+use crate::hooks::run_script;
+use crate::units::MegaBytes;
+use crate::{app_settings, emit_log_entry, get_detected_gpus, gpu_ids, invoke_daemon_cli_json_output, wsl, LocalJob};
+
+const NVIDIA_SMI_TIMEOUT: Duration = Duration::from_secs(10);
+const DOCKER_INSPECT_TIMEOUT: Duration = Duration::from_secs(5);
+// Set by the daemon on every task container (see JobIDLabel in the daemon's executor).
+const JOB_ID_LABEL: &str = "com.dantegpu.job-id";
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct ForeignProcessPolicy {
+    pub warn_before_jobs: bool,
+    pub vram_threshold_mb: MegaBytes,
+}
+
+impl Default for ForeignProcessPolicy {
+    fn default() -> Self {
+        ForeignProcessPolicy { warn_before_jobs: true, vram_threshold_mb: MegaBytes::new(2048) }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GpuProcess {
+    pub pid: u32,
+    pub name: String,
+    pub used_vram_mb: MegaBytes,
+    #[serde(default)]
+    pub is_dante: bool, // Part of a rental or the daemon itself
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct GpuProcessReport {
+    pub gpu_id: String,
+    pub source: String, // "daemon" | "nvidia-smi"
+    pub processes: Vec<GpuProcess>,
+    pub foreign_vram_mb: MegaBytes,
+}
+
+// The 64-hex-digit ID of the container a process runs in, from paths like
+// `/docker/<id>` or `/system.slice/docker-<id>.scope`.
+fn container_id(cgroup: &str) -> Option<String> {
+    cgroup.lines().find_map(|line| {
+        line.split(|c: char| !c.is_ascii_hexdigit())
+            .find(|segment| segment.len() == 64)
+            .map(str::to_string)
+    })
+}
+
+// Only the daemon and containers it labelled as jobs count; other containers (a miner, someone's
+// notebook) are as foreign as any host process.
+fn is_dante_process(pid: u32, name: &str) -> bool {
+    if name.contains("provider-daemon") {
+        return true;
+    }
+    let Some(id) = std::fs::read_to_string(format!("/proc/{}/cgroup", pid)).ok().as_deref().and_then(container_id) else {
+        return false;
+    };
+    let args = vec!["inspect".to_string(), "--format".to_string(), format!("{{{{index .Config.Labels \"{}\"}}}}", JOB_ID_LABEL), id];
+    run_script("docker", &args, "", DOCKER_INSPECT_TIMEOUT, false)
+        .map(|output| !matches!(output.stdout.trim(), "" | "<no value>"))
+        .unwrap_or(false)
+}
+
+// nvidia-smi knows cards by UUID and PCI slot, not by the app's stable IDs.
+fn query_nvidia_smi(uuid: Option<&str>, pci_bus_id: Option<&str>) -> Result<Vec<GpuProcess>, String> {
+    let args: Vec<String> = ["--query-compute-apps=gpu_uuid,gpu_bus_id,pid,process_name,used_memory", "--format=csv,noheader,nounits"]
+        .iter()
+        .map(|a| a.to_string())
+        .collect();
+    let output = run_script(wsl::nvidia_smi_program(), &args, "", NVIDIA_SMI_TIMEOUT, false)?;
+    Ok(output
+        .stdout
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            match fields.as_slice() {
+                [gpu_uuid, bus_id, pid, name, used]
+                    if uuid == Some(*gpu_uuid) || (pci_bus_id.is_some() && normalize_pci_bus_id(bus_id).as_deref() == pci_bus_id) =>
+                {
+                    let pid = pid.parse().ok()?;
+                    Some(GpuProcess {
+                        pid,
+                        name: name.to_string(),
+                        used_vram_mb: MegaBytes::new(used.parse().unwrap_or(0)),
+                        is_dante: is_dante_process(pid, name),
+                    })
+                }
+                _ => None,
+            }
+        })
+        .collect())
+}
+
+pub async fn inspect(app_handle: &AppHandle, gpu_id: &str) -> Result<GpuProcessReport, String> {
+    let (source, processes) = match invoke_daemon_cli_json_output::<Vec<GpuProcess>>(app_handle, &["--get-gpu-processes-json", "--gpu-id", &gpu_ids::daemon_id(app_handle, gpu_id).await]).await {
+        Ok(processes) => ("daemon", processes),
+        Err(_) => {
+            let identity = gpu_ids::identity(gpu_id).ok_or_else(|| format!("No GPU with ID '{}'.", gpu_id))?;
+            let processes = tauri::async_runtime::spawn_blocking(move || query_nvidia_smi(identity.uuid.as_deref(), identity.pci_bus_id.as_deref()))
+                .await
+                .map_err(|e| e.to_string())??;
+            ("nvidia-smi", processes)
+        }
+    };
+    let foreign_vram_mb = processes.iter().filter(|p| !p.is_dante).map(|p| p.used_vram_mb).sum();
+    Ok(GpuProcessReport { gpu_id: gpu_id.to_string(), source: source.to_string(), processes, foreign_vram_mb })
+}
+
+// Called when a job shows up in the queue, before the daemon starts it.
+pub async fn warn_if_contended(app_handle: &AppHandle, job: &LocalJob) {
+    let policy = app_settings::current(app_handle).foreign_process_policy;
+    if !policy.warn_before_jobs {
+        return;
+    }
+    let Ok(gpus) = get_detected_gpus(app_handle.clone()).await else { return };
+    for gpu in gpus.iter().filter(|g| g.is_available_for_rent) {
+        let Ok(report) = inspect(app_handle, &gpu.id).await else { continue };
+        if report.foreign_vram_mb >= policy.vram_threshold_mb {
+            emit_log_entry(
+                app_handle,
+                "error",
+                format!(
+                    "Job {} is about to start, but non-Dante processes hold {} of VRAM on {}. The rental may run out of memory.",
+                    job.id, report.foreign_vram_mb, gpu.name
+                ),
+            );
+            if let Err(e) = app_handle.emit_all("foreign_gpu_processes_warning", json!({ "job_id": job.id, "report": report })) {
+                eprintln!("Failed to emit foreign_gpu_processes_warning event: {}", e);
+            }
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn get_gpu_processes(app_handle: AppHandle, gpu_id: String) -> Result<GpuProcessReport, String> {
+    inspect(&app_handle, &gpu_id).await
+}
@@ -0,0 +1,46 @@
+// Typed GraphQL queries against the marketplace endpoint.
+//
+// The GraphQL API offers breakdowns the REST API doesn't (rates per region, demand by hour),
+// which feed the market and analytics views. Queries are checked against the schema subset in
+// `graphql/` at compile time and sent through `api_client` like every other platform call.
+
+use graphql_client::{GraphQLQuery, QueryBody, Response};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tauri::AppHandle;
+
+use crate::api_client;
+
+const GRAPHQL_PATH: &str = "/graphql";
+
+#[derive(GraphQLQuery)]
+#[graphql(schema_path = "graphql/schema.graphql", query_path = "graphql/marketplace.graphql", response_derives = "Debug, Clone, Serialize")]
+pub struct RatePercentilesByRegion;
+
+#[derive(GraphQLQuery)]
+#[graphql(schema_path = "graphql/schema.graphql", query_path = "graphql/marketplace.graphql", response_derives = "Debug, Clone, Serialize")]
+pub struct DemandHeatmap;
+
+pub async fn query<V: Serialize, T: DeserializeOwned>(app_handle: &AppHandle, body: QueryBody<V>) -> Result<T, String> {
+    let response: Response<T> = api_client::send_json(app_handle, reqwest::Method::POST, GRAPHQL_PATH, &body).await?;
+    if let Some(errors) = response.errors.filter(|e| !e.is_empty()) {
+        let messages: Vec<String> = errors.into_iter().map(|e| e.message).collect();
+        return Err(format!("Marketplace query {} failed: {}", body.operation_name, messages.join("; ")));
+    }
+    response.data.ok_or_else(|| format!("Marketplace query {} returned no data.", body.operation_name))
+}
+
+pub async fn rate_percentiles_by_region(
+    app_handle: &AppHandle,
+    model: &str,
+) -> Result<Vec<rate_percentiles_by_region::RatePercentilesByRegionRatePercentiles>, String> {
+    let body = RatePercentilesByRegion::build_query(rate_percentiles_by_region::Variables { model: model.to_string() });
+    let data: rate_percentiles_by_region::ResponseData = query(app_handle, body).await?;
+    Ok(data.rate_percentiles)
+}
+
+pub async fn demand_heatmap(app_handle: &AppHandle, model: Option<&str>, days: i64) -> Result<Vec<demand_heatmap::DemandHeatmapDemandHeatmap>, String> {
+    let body = DemandHeatmap::build_query(demand_heatmap::Variables { model: model.map(str::to_string), days });
+    let data: demand_heatmap::ResponseData = query(app_handle, body).await?;
+    Ok(data.demand_heatmap)
+}
@@ -0,0 +1,216 @@
+// User scripting hooks.
+//
+// Providers can attach their own scripts/binaries to lifecycle events. Each hook receives the
+// event payload as JSON on stdin, is killed if it exceeds its timeout, and has its output
+// captured into the log stream so failures are visible in the GUI.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::io::{Read, Write};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, State};
+
+use crate::app_settings::{self, AppSettingsState};
+use crate::plugins;
+use crate::{emit_log_entry, get_timestamp};
+
+const DEFAULT_HOOK_TIMEOUT_SECS: u64 = 30;
+// Kept when the environment is cleared: without SYSTEMROOT most Windows programs can't even
+// start, and without PATH they can't find an interpreter.
+const KEPT_ENV: &[&str] = &["PATH", "SYSTEMROOT", "TEMP", "TMP"];
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum HookEvent {
+    DaemonStarted,
+    JobCompleted,
+    PayoutReceived,
+    GpuOverheated,
+}
+
+impl HookEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HookEvent::DaemonStarted => "daemon_started",
+            HookEvent::JobCompleted => "job_completed",
+            HookEvent::PayoutReceived => "payout_received",
+            HookEvent::GpuOverheated => "gpu_overheated",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct HookConfig {
+    pub id: String,
+    pub event: HookEvent,
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_timeout_secs() -> u64 {
+    DEFAULT_HOOK_TIMEOUT_SECS
+}
+
+// Hook scripts currently running, so shutdown can wait for them.
+static IN_FLIGHT: AtomicUsize = AtomicUsize::new(0);
+
+pub fn in_flight() -> usize {
+    IN_FLIGHT.load(Ordering::SeqCst)
+}
+
+// Runs every enabled hook registered for `event` in the background, and forwards the event to
+// notification-sink plugins.
+pub fn fire(app_handle: &AppHandle, event: HookEvent, payload: Value) {
+    plugins::notify(app_handle, event.as_str(), &payload, event == HookEvent::PayoutReceived);
+
+    let hooks: Vec<HookConfig> = app_settings::current(app_handle)
+        .hooks
+        .into_iter()
+        .filter(|h| h.enabled && h.event == event)
+        .collect();
+    if hooks.is_empty() {
+        return;
+    }
+
+    let input = json!({
+        "event": event,
+        "timestamp": get_timestamp(),
+        "payload": payload,
+    })
+    .to_string();
+
+    for hook in hooks {
+        let app_handle = app_handle.clone();
+        let input = input.clone();
+        IN_FLIGHT.fetch_add(1, Ordering::SeqCst);
+        tauri::async_runtime::spawn_blocking(move || {
+            if let Err(e) = run_hook(&app_handle, &hook, &input) {
+                emit_log_entry(&app_handle, "error", format!("Hook '{}' failed: {}", hook.id, e));
+            }
+            IN_FLIGHT.fetch_sub(1, Ordering::SeqCst);
+        });
+    }
+}
+
+pub struct ScriptOutput {
+    pub stdout: String,
+    pub stderr: String,
+}
+
+// Runs an external program with `input` on stdin, killing it after `timeout`. Shared with the
+// plugin host, which speaks the same stdin/stdout JSON protocol.
+pub fn run_script(command: &str, args: &[String], input: &str, timeout: Duration, clear_env: bool) -> Result<ScriptOutput, String> {
+    let mut cmd = Command::new(command);
+    cmd.args(args).stdin(Stdio::piped()).stdout(Stdio::piped()).stderr(Stdio::piped());
+    if clear_env {
+        cmd.env_clear();
+        for name in KEPT_ENV {
+            if let Some(value) = std::env::var_os(name) {
+                cmd.env(name, value);
+            }
+        }
+    }
+    let mut child = cmd.spawn().map_err(|e| format!("failed to spawn '{}': {}", command, e))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        // A script that ignores stdin closes the pipe early; that is not an error.
+        let _ = stdin.write_all(input.as_bytes());
+    }
+
+    // Drain output on separate threads so a chatty script can't block on a full pipe.
+    let stdout_reader = child.stdout.take().map(|mut out| {
+        thread::spawn(move || {
+            let mut buf = String::new();
+            let _ = out.read_to_string(&mut buf);
+            buf
+        })
+    });
+    let stderr_reader = child.stderr.take().map(|mut err| {
+        thread::spawn(move || {
+            let mut buf = String::new();
+            let _ = err.read_to_string(&mut buf);
+            buf
+        })
+    });
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        match child.try_wait().map_err(|e| format!("failed to wait for '{}': {}", command, e))? {
+            Some(status) => break Some(status),
+            None if Instant::now() >= deadline => {
+                let _ = child.kill();
+                let _ = child.wait();
+                break None;
+            }
+            None => thread::sleep(Duration::from_millis(100)),
+        }
+    };
+
+    let output = ScriptOutput {
+        stdout: stdout_reader.and_then(|h| h.join().ok()).unwrap_or_default(),
+        stderr: stderr_reader.and_then(|h| h.join().ok()).unwrap_or_default(),
+    };
+    match status {
+        None => Err(format!("timed out after {}s and was killed", timeout.as_secs())),
+        Some(status) if !status.success() => Err(format!("exited with {}. stderr: '{}'", status, output.stderr.trim())),
+        Some(_) => Ok(output),
+    }
+}
+
+fn run_hook(app_handle: &AppHandle, hook: &HookConfig, input: &str) -> Result<(), String> {
+    emit_log_entry(app_handle, "status", format!("Running hook '{}' ({})", hook.id, hook.command));
+
+    let output = run_script(&hook.command, &hook.args, input, Duration::from_secs(hook.timeout_secs), false)?;
+    for line in output.stdout.lines() {
+        emit_log_entry(app_handle, "stdout", format!("[hook {}] {}", hook.id, line));
+    }
+    for line in output.stderr.lines() {
+        emit_log_entry(app_handle, "stderr", format!("[hook {}] {}", hook.id, line));
+    }
+    emit_log_entry(app_handle, "status", format!("Hook '{}' completed.", hook.id));
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn set_hook_enabled(app_handle: AppHandle, state: State<'_, AppSettingsState>, hook_id: String, enabled: bool) -> Result<HookConfig, String> {
+    let mut settings = state.get();
+    let hook = settings
+        .hooks
+        .iter_mut()
+        .find(|h| h.id == hook_id)
+        .ok_or_else(|| format!("Hook '{}' not found.", hook_id))?;
+    hook.enabled = enabled;
+    let updated_hook = hook.clone();
+    app_settings::update_app_settings(app_handle, state, settings).await?;
+    Ok(updated_hook)
+}
+
+#[tauri::command]
+pub async fn test_hook(app_handle: AppHandle, hook_id: String) -> Result<(), String> {
+    let hook = app_settings::current(&app_handle)
+        .hooks
+        .into_iter()
+        .find(|h| h.id == hook_id)
+        .ok_or_else(|| format!("Hook '{}' not found.", hook_id))?;
+    let input = json!({
+        "event": hook.event,
+        "timestamp": get_timestamp(),
+        "payload": { "test": true },
+    })
+    .to_string();
+    tauri::async_runtime::spawn_blocking(move || run_hook(&app_handle, &hook, &input))
+        .await
+        .map_err(|e| format!("Hook task failed: {}", e))?
+}
@@ -0,0 +1,346 @@
+// Shared construction of the HTTP client used for the GUI's own outbound calls.
+//
+// Daemon traffic goes through the sidecar CLI; this client is for things the GUI fetches or
+// uploads directly (known-issue updates, reports, platform endpoints).
+//
+// All of those honour the proxy settings. The route is resolved once whenever settings change
+// (reading the proxy password from the keychain and fetching the PAC file, if any) so that
+// building a client stays synchronous. PAC support is limited to scripts that return a single
+// fixed route; we don't evaluate JavaScript.
+//
+// TLS can be customised in two ways: an extra CA bundle (for corporate networks that re-sign
+// traffic) and certificate pins for the platform and updater hosts. A pin is the SHA-256 of the
+// leaf certificate's DER encoding; several can be listed to stage a rotation. Pin failures are
+// reported as `HttpError::PinMismatch` so the UI can tell them apart from ordinary TLS errors.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::error::Error as _;
+use std::fmt;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant, SystemTime};
+use tauri::AppHandle;
+
+use crate::{app_settings, secrets};
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+pub const PROXY_PASSWORD_KEY: &str = "proxy.password";
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ProxyMode {
+    // Use HTTP_PROXY/HTTPS_PROXY/ALL_PROXY from the environment, if set.
+    #[default]
+    System,
+    Direct,
+    Manual,
+    Pac,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct ProxyConfig {
+    pub mode: ProxyMode,
+    // http://, https:// or socks5:// URL, for manual mode.
+    pub url: Option<String>,
+    pub pac_url: Option<String>,
+    pub username: Option<String>, // The password is kept in the keychain
+    // Comma-separated hosts/domains/CIDRs that bypass the proxy.
+    pub no_proxy: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+enum Route {
+    System,
+    Direct,
+    Proxy { url: String, username: Option<String>, password: Option<String>, no_proxy: Option<String> },
+}
+
+static ROUTE: RwLock<Route> = RwLock::new(Route::System);
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CertPin {
+    pub host: String,
+    pub sha256: Vec<String>, // Hex, any of which is accepted
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct TlsConfig {
+    // PEM bundle trusted in addition to the built-in roots.
+    pub ca_bundle_path: Option<String>,
+    pub pins: Vec<CertPin>,
+}
+
+// Built once per settings change; None means the default TLS stack.
+static TLS: RwLock<Option<Arc<rustls::ClientConfig>>> = RwLock::new(None);
+
+// Carried through rustls' error type so pin failures can be recognised after reqwest wraps them.
+const PIN_MISMATCH_MARKER: &str = "certificate pin mismatch; presented ";
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum HttpError {
+    PinMismatch { host: String, presented_sha256: String },
+    Tls { message: String },
+    Timeout,
+    Connect { message: String },
+    Status { code: u16 },
+    Other { message: String },
+}
+
+impl fmt::Display for HttpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HttpError::PinMismatch { host, presented_sha256 } => write!(
+                f,
+                "{} presented a certificate that doesn't match the pinned fingerprint (got {}). The connection may be intercepted.",
+                host, presented_sha256
+            ),
+            HttpError::Tls { message } => write!(f, "TLS error: {}", message),
+            HttpError::Timeout => write!(f, "Request timed out"),
+            HttpError::Connect { message } => write!(f, "Connection failed: {}", message),
+            HttpError::Status { code } => write!(f, "Server returned HTTP {}", code),
+            HttpError::Other { message } => write!(f, "{}", message),
+        }
+    }
+}
+
+pub fn classify(error: &reqwest::Error) -> HttpError {
+    let host = error.url().and_then(|u| u.host_str()).unwrap_or_default().to_string();
+    let mut chain = Vec::new();
+    let mut source = error.source();
+    while let Some(e) = source {
+        chain.push(e.to_string());
+        source = e.source();
+    }
+    if let Some(presented) = chain.iter().find_map(|m| m.split_once(PIN_MISMATCH_MARKER).map(|(_, rest)| rest.trim().to_string())) {
+        return HttpError::PinMismatch { host, presented_sha256: presented };
+    }
+    if let Some(tls) = chain.iter().find(|m| {
+        let m = m.to_lowercase();
+        m.contains("certificate") || m.contains("tls") || m.contains("handshake")
+    }) {
+        return HttpError::Tls { message: tls.clone() };
+    }
+    if error.is_timeout() {
+        HttpError::Timeout
+    } else if error.is_connect() {
+        HttpError::Connect { message: chain.last().cloned().unwrap_or_else(|| error.to_string()) }
+    } else if let Some(status) = error.status() {
+        HttpError::Status { code: status.as_u16() }
+    } else {
+        HttpError::Other { message: error.to_string() }
+    }
+}
+
+struct PinningVerifier {
+    inner: rustls::client::WebPkiVerifier,
+    pins: HashMap<String, Vec<String>>,
+}
+
+impl rustls::client::ServerCertVerifier for PinningVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &rustls::Certificate,
+        intermediates: &[rustls::Certificate],
+        server_name: &rustls::ServerName,
+        scts: &mut dyn Iterator<Item = &[u8]>,
+        ocsp_response: &[u8],
+        now: SystemTime,
+    ) -> Result<rustls::client::ServerCertVerified, rustls::Error> {
+        let verified = self.inner.verify_server_cert(end_entity, intermediates, server_name, scts, ocsp_response, now)?;
+        if let rustls::ServerName::DnsName(name) = server_name {
+            if let Some(pins) = self.pins.get(&name.as_ref().to_lowercase()) {
+                let presented = hex::encode(Sha256::digest(&end_entity.0));
+                if !pins.iter().any(|pin| pin.eq_ignore_ascii_case(&presented)) {
+                    return Err(rustls::Error::General(format!("{}{}", PIN_MISMATCH_MARKER, presented)));
+                }
+            }
+        }
+        Ok(verified)
+    }
+}
+
+fn build_tls(config: &TlsConfig) -> Result<Option<Arc<rustls::ClientConfig>>, String> {
+    if config.ca_bundle_path.is_none() && config.pins.is_empty() {
+        return Ok(None);
+    }
+    let mut roots = rustls::RootCertStore::empty();
+    roots.add_trust_anchors(webpki_roots::TLS_SERVER_ROOTS.iter().map(|ta| {
+        rustls::OwnedTrustAnchor::from_subject_spki_name_constraints(ta.subject, ta.spki, ta.name_constraints)
+    }));
+    if let Some(path) = &config.ca_bundle_path {
+        let pem = std::fs::read(path).map_err(|e| format!("Failed to read CA bundle {}: {}", path, e))?;
+        let certs = rustls_pemfile::certs(&mut pem.as_slice()).map_err(|e| format!("Invalid CA bundle {}: {}", path, e))?;
+        if certs.is_empty() {
+            return Err(format!("CA bundle {} contains no certificates.", path));
+        }
+        for der in certs {
+            roots.add(&rustls::Certificate(der)).map_err(|e| format!("Invalid certificate in {}: {}", path, e))?;
+        }
+    }
+    let pins = config
+        .pins
+        .iter()
+        .map(|pin| (pin.host.to_lowercase(), pin.sha256.iter().map(|s| s.replace(':', "")).collect()))
+        .collect();
+    let verifier = PinningVerifier { inner: rustls::client::WebPkiVerifier::new(roots.clone(), None), pins };
+    let mut tls = rustls::ClientConfig::builder().with_safe_defaults().with_root_certificates(roots).with_no_client_auth();
+    tls.dangerous().set_certificate_verifier(Arc::new(verifier));
+    Ok(Some(Arc::new(tls)))
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ProxyTestResult {
+    pub ok: bool,
+    pub route: String,
+    pub latency_ms: Option<u64>,
+    pub error: Option<String>,
+}
+
+fn builder(route: &Route) -> Result<reqwest::ClientBuilder, String> {
+    let mut builder = reqwest::Client::builder().user_agent(concat!("dante-provider-gui/", env!("CARGO_PKG_VERSION")));
+    match route {
+        Route::System => {}
+        Route::Direct => builder = builder.no_proxy(),
+        Route::Proxy { url, username, password, no_proxy } => {
+            let mut proxy = reqwest::Proxy::all(url).map_err(|e| format!("Invalid proxy URL '{}': {}", url, e))?;
+            if let Some(username) = username {
+                proxy = proxy.basic_auth(username, password.as_deref().unwrap_or_default());
+            }
+            if let Some(no_proxy) = no_proxy {
+                proxy = proxy.no_proxy(reqwest::NoProxy::from_string(no_proxy));
+            }
+            builder = builder.proxy(proxy);
+        }
+    }
+    if let Some(tls) = TLS.read().unwrap().as_ref() {
+        builder = builder.use_preconfigured_tls((**tls).clone());
+    }
+    Ok(builder)
+}
+
+fn build(route: &Route) -> Result<reqwest::Client, String> {
+    builder(route)?.timeout(REQUEST_TIMEOUT).build().map_err(|e| format!("Failed to build HTTP client: {}", e))
+}
+
+pub fn client() -> Result<reqwest::Client, String> {
+    build(&ROUTE.read().unwrap())
+}
+
+// Client for long-lived connections upgraded from HTTP (the event feed WebSocket). Same route
+// and TLS as `client`, but only connecting is timed and it must speak HTTP/1.1 to upgrade.
+pub fn upgrade_client() -> Result<reqwest::Client, String> {
+    builder(&ROUTE.read().unwrap())?
+        .http1_only()
+        .connect_timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(|e| format!("Failed to build HTTP client: {}", e))
+}
+
+// First route in a PAC script's return value, e.g. `return "PROXY proxy.corp:3128; DIRECT";`.
+fn parse_pac(script: &str) -> Option<Option<String>> {
+    let re = regex::Regex::new(r#"return\s*["']\s*(PROXY|HTTPS|SOCKS5?|DIRECT)\s*([^;"']*)"#).ok()?;
+    let captures = re.captures(script)?;
+    let target = captures.get(2).map(|m| m.as_str().trim()).unwrap_or_default();
+    match &captures[1] {
+        "DIRECT" => Some(None),
+        "PROXY" => Some(Some(format!("http://{}", target))),
+        "HTTPS" => Some(Some(format!("https://{}", target))),
+        _ => Some(Some(format!("socks5://{}", target))),
+    }
+}
+
+async fn resolve(config: &ProxyConfig) -> Result<Route, String> {
+    let password = || secrets::get(PROXY_PASSWORD_KEY).ok().flatten();
+    let proxy_route = |url: String| Route::Proxy { url, username: config.username.clone(), password: password(), no_proxy: config.no_proxy.clone() };
+    match config.mode {
+        ProxyMode::System => Ok(Route::System),
+        ProxyMode::Direct => Ok(Route::Direct),
+        ProxyMode::Manual => config.url.clone().filter(|u| !u.is_empty()).map(proxy_route).ok_or_else(|| "Manual proxy mode needs a proxy URL.".to_string()),
+        ProxyMode::Pac => {
+            let pac_url = config.pac_url.as_deref().ok_or_else(|| "PAC mode needs a PAC file URL.".to_string())?;
+            let script = build(&Route::Direct)?
+                .get(pac_url)
+                .send()
+                .await
+                .and_then(|r| r.error_for_status())
+                .map_err(|e| format!("Failed to fetch PAC file: {}", e))?
+                .text()
+                .await
+                .map_err(|e| format!("Failed to read PAC file: {}", e))?;
+            match parse_pac(&script) {
+                Some(Some(url)) => Ok(proxy_route(url)),
+                Some(None) => Ok(Route::Direct),
+                None => Err("The PAC file has no fixed route we can use; configure the proxy manually.".to_string()),
+            }
+        }
+    }
+}
+
+// Re-resolves the route from the current settings; called at startup and whenever settings
+// are saved. On failure the previous route stays in effect.
+pub async fn apply_settings(app_handle: &AppHandle) -> Result<(), String> {
+    let settings = app_settings::current(app_handle);
+    *TLS.write().unwrap() = build_tls(&settings.tls)?;
+    let route = resolve(&settings.proxy).await?;
+    *ROUTE.write().unwrap() = route;
+    Ok(())
+}
+
+fn describe(route: &Route) -> String {
+    match route {
+        Route::System => "system".to_string(),
+        Route::Direct => "direct".to_string(),
+        Route::Proxy { url, .. } => url.clone(),
+    }
+}
+
+#[tauri::command]
+pub async fn set_proxy_password(app_handle: AppHandle, password: Option<String>) -> Result<(), String> {
+    match password.filter(|p| !p.is_empty()) {
+        Some(password) => secrets::set(PROXY_PASSWORD_KEY, &password)?,
+        None => secrets::delete(PROXY_PASSWORD_KEY)?,
+    }
+    apply_settings(&app_handle).await
+}
+
+// Tries `config` (or the saved settings) against the platform without applying it.
+#[tauri::command]
+pub async fn test_proxy(app_handle: AppHandle, config: Option<ProxyConfig>) -> Result<ProxyTestResult, String> {
+    let settings = app_settings::current(&app_handle);
+    let route = match resolve(&config.unwrap_or(settings.proxy)).await {
+        Ok(route) => route,
+        Err(e) => return Ok(ProxyTestResult { ok: false, route: "unresolved".to_string(), latency_ms: None, error: Some(e) }),
+    };
+    let started = Instant::now();
+    let result = build(&route)?
+        .get(format!("{}/time", settings.platform_url.trim_end_matches('/')))
+        .send()
+        .await
+        .and_then(|r| r.error_for_status());
+    Ok(ProxyTestResult {
+        ok: result.is_ok(),
+        route: describe(&route),
+        latency_ms: result.is_ok().then(|| started.elapsed().as_millis() as u64),
+        error: result.err().map(|e| e.to_string()),
+    })
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct TlsCheck {
+    pub url: String,
+    pub latency_ms: u64,
+}
+
+// Connects to the platform with the current TLS settings, returning the typed error on failure.
+#[tauri::command]
+pub async fn test_platform_tls(app_handle: AppHandle) -> Result<TlsCheck, HttpError> {
+    let url = format!("{}/time", app_settings::current(&app_handle).platform_url.trim_end_matches('/'));
+    let client = client().map_err(|message| HttpError::Other { message })?;
+    let started = Instant::now();
+    client.get(&url).send().await.and_then(|r| r.error_for_status()).map_err(|e| classify(&e))?;
+    Ok(TlsCheck { url, latency_ms: started.elapsed().as_millis() as u64 })
+}
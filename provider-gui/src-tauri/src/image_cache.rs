@@ -0,0 +1,171 @@
+// Container images the daemon keeps for jobs, and keeping them within a storage budget.
+//
+// Job images are pulled on first use and kept, since the next job using the same base image then
+// starts in seconds. Eviction is least recently used first and stops once usage fits the budget;
+// images a running or queued job needs are never evicted, automatically or by hand. If the
+// daemon can't say which images are in use, nothing is evicted automatically. Prefetched images
+// have never been used by a job, so they are kept out of automatic eviction for a week after
+// the pull; otherwise they would be the first to go.
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+use std::fs;
+use std::time::Duration;
+use tauri::AppHandle;
+
+use crate::{app_settings, emit_log_entry, invoke_daemon_cli_json_output, shutdown};
+
+const EVICTION_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+const BYTES_PER_GB: f64 = 1024.0 * 1024.0 * 1024.0;
+const PREFETCHED_FILE_NAME: &str = "prefetched_images.json";
+const PREFETCH_PROTECTION_SECS: i64 = 7 * 24 * 60 * 60;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct ImageCachePolicy {
+    pub storage_budget_gb: Option<f64>, // None means unlimited
+    pub auto_evict: bool,
+}
+
+impl Default for ImageCachePolicy {
+    fn default() -> Self {
+        ImageCachePolicy { storage_budget_gb: Some(100.0), auto_evict: true }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CachedImage {
+    pub id: String,
+    pub image: String, // repository:tag
+    pub size_bytes: u64,
+    #[serde(default)]
+    pub last_used_at: Option<String>, // RFC 3339; None if no job has used it yet
+    #[serde(default)]
+    pub in_use: bool, // Needed by a running or queued job
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ImageCacheListing {
+    pub images: Vec<CachedImage>,
+    pub total_bytes: u64,
+    pub budget_bytes: Option<u64>,
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct EvictionReport {
+    pub evicted: Vec<String>,
+    pub freed_bytes: u64,
+    pub remaining_bytes: u64,
+}
+
+pub fn budget_bytes(app_handle: &AppHandle) -> Option<u64> {
+    app_settings::current(app_handle).image_cache.storage_budget_gb.map(|gb| (gb * BYTES_PER_GB) as u64)
+}
+
+pub async fn list(app_handle: &AppHandle) -> Result<Vec<CachedImage>, String> {
+    invoke_daemon_cli_json_output(app_handle, &["--list-images-json"]).await
+}
+
+pub async fn remove(app_handle: &AppHandle, id: &str) -> Result<(), String> {
+    let payload = json!({ "id": id }).to_string();
+    invoke_daemon_cli_json_output::<serde_json::Value>(app_handle, &["--remove-image-json", &payload]).await.map(|_| ())
+}
+
+// Images prefetched within the protection window, with when they were pulled (unix seconds).
+fn recently_prefetched(app_handle: &AppHandle) -> HashMap<String, i64> {
+    let now = chrono::Utc::now().timestamp();
+    let mut prefetched: HashMap<String, i64> = app_handle
+        .path_resolver()
+        .app_data_dir()
+        .and_then(|dir| fs::read_to_string(dir.join(PREFETCHED_FILE_NAME)).ok())
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default();
+    prefetched.retain(|_, at| now - *at < PREFETCH_PROTECTION_SECS);
+    prefetched
+}
+
+pub fn record_prefetched(app_handle: &AppHandle, image: &str) {
+    let Some(dir) = app_handle.path_resolver().app_data_dir() else { return };
+    let mut prefetched = recently_prefetched(app_handle);
+    prefetched.insert(image.to_string(), chrono::Utc::now().timestamp());
+    if let Ok(json) = serde_json::to_string(&prefetched) {
+        if let Err(e) = fs::write(dir.join(PREFETCHED_FILE_NAME), json) {
+            eprintln!("Failed to persist prefetched images: {}", e);
+        }
+    }
+}
+
+fn last_used(image: &CachedImage) -> i64 {
+    image.last_used_at.as_deref().and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok()).map_or(0, |t| t.timestamp())
+}
+
+// Evicts the least recently used images until usage fits the budget, sparing recent prefetches.
+pub async fn evict_over_budget(app_handle: &AppHandle) -> Result<EvictionReport, String> {
+    let mut images = list(app_handle).await?;
+    let mut report = EvictionReport { remaining_bytes: images.iter().map(|i| i.size_bytes).sum(), ..Default::default() };
+    let Some(budget) = budget_bytes(app_handle) else {
+        return Ok(report);
+    };
+    images.sort_by_key(last_used);
+    let prefetched = recently_prefetched(app_handle);
+    for image in images.iter().filter(|i| !i.in_use && !prefetched.contains_key(&i.image)) {
+        if report.remaining_bytes <= budget {
+            break;
+        }
+        remove(app_handle, &image.id).await?;
+        report.evicted.push(image.image.clone());
+        report.freed_bytes += image.size_bytes;
+        report.remaining_bytes -= image.size_bytes;
+    }
+    if report.remaining_bytes > budget {
+        emit_log_entry(
+            app_handle,
+            "error",
+            format!("Job images still use {} MB, over the storage budget; the rest are needed by running or queued jobs or were prefetched this week.", report.remaining_bytes / (1024 * 1024)),
+        );
+    }
+    Ok(report)
+}
+
+pub fn spawn_eviction_task(app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            if !shutdown::sleep(EVICTION_INTERVAL).await {
+                break;
+            }
+            if !app_settings::current(&app_handle).image_cache.auto_evict {
+                continue;
+            }
+            match evict_over_budget(&app_handle).await {
+                Ok(report) if !report.evicted.is_empty() => emit_log_entry(
+                    &app_handle,
+                    "status",
+                    format!("Evicted {} job image(s), freeing {} MB.", report.evicted.len(), report.freed_bytes / (1024 * 1024)),
+                ),
+                Ok(_) => {}
+                Err(e) => eprintln!("Image eviction skipped: {}", e),
+            }
+        }
+    });
+}
+
+// Most recently used first.
+#[tauri::command]
+pub async fn list_cached_images(app_handle: AppHandle) -> Result<ImageCacheListing, String> {
+    let mut images = list(&app_handle).await?;
+    images.sort_by_key(|i| std::cmp::Reverse(last_used(i)));
+    Ok(ImageCacheListing { total_bytes: images.iter().map(|i| i.size_bytes).sum(), budget_bytes: budget_bytes(&app_handle), images })
+}
+
+#[tauri::command]
+pub async fn evict_image(app_handle: AppHandle, id: String) -> Result<(), String> {
+    let images = list(&app_handle).await?;
+    let image = images.iter().find(|i| i.id == id).ok_or_else(|| format!("No cached image with ID '{}'.", id))?;
+    if image.in_use {
+        return Err(format!("{} is needed by a running or queued job.", image.image));
+    }
+    remove(&app_handle, &id).await?;
+    emit_log_entry(&app_handle, "status", format!("Evicted job image {} ({} MB).", image.image, image.size_bytes / (1024 * 1024)));
+    Ok(())
+}
@@ -0,0 +1,105 @@
+// Where the container runtime pulls images from, and pinned digests for infrastructure images.
+//
+// Tags move and public images get deprecated (the library `consul` image stopped resolving and
+// broke the local stack), so images the provider stack requires can be pinned: the reference the
+// stack asks for is pulled from a named source at an exact digest and tagged locally. A registry
+// mirror or pull-through cache takes load and rate limits off Docker Hub. Both are pushed to the
+// daemon, which writes them into the container runtime's configuration, and are verified by the
+// preflight checks in `troubleshoot`.
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, State};
+
+use crate::app_settings::{self, AppSettingsState};
+use crate::{emit_log_entry, http_client, invoke_daemon_cli_json_output};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PinnedImage {
+    pub image: String,  // Reference the stack uses, e.g. "consul:1.15"
+    pub source: String, // Repository to pull from, e.g. "hashicorp/consul"
+    pub digest: String, // "sha256:<64 hex digits>"
+}
+
+impl PinnedImage {
+    pub fn pull_reference(&self) -> String {
+        format!("{}@{}", self.source, self.digest)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct RegistryConfig {
+    pub mirror: Option<String>, // e.g. "https://mirror.example.com"; Docker Hub directly when unset
+    pub pinned_images: Vec<PinnedImage>,
+}
+
+fn valid_digest(digest: &str) -> bool {
+    digest.strip_prefix("sha256:").is_some_and(|hex| hex.len() == 64 && hex.chars().all(|c| c.is_ascii_digit() || ('a'..='f').contains(&c)))
+}
+
+fn valid_reference(reference: &str) -> bool {
+    !reference.is_empty() && !reference.contains(char::is_whitespace) && !reference.contains('@')
+}
+
+pub fn validate(config: &RegistryConfig) -> Result<(), String> {
+    if let Some(mirror) = &config.mirror {
+        let url = reqwest::Url::parse(mirror).map_err(|e| format!("Registry mirror '{}' is not a valid URL: {}", mirror, e))?;
+        if !matches!(url.scheme(), "http" | "https") || url.host_str().is_none() {
+            return Err(format!("Registry mirror '{}' must be an http(s) URL with a host.", mirror));
+        }
+    }
+    let mut seen: Vec<&str> = Vec::new();
+    for pin in &config.pinned_images {
+        if !valid_reference(&pin.image) || !valid_reference(&pin.source) {
+            return Err(format!("Pinned image '{}' from '{}' is not a valid image reference.", pin.image, pin.source));
+        }
+        if !valid_digest(&pin.digest) {
+            return Err(format!("Digest for '{}' must look like sha256: followed by 64 hex digits.", pin.image));
+        }
+        if seen.contains(&pin.image.as_str()) {
+            return Err(format!("'{}' is pinned more than once.", pin.image));
+        }
+        seen.push(&pin.image);
+    }
+    Ok(())
+}
+
+pub fn pin_for(app_handle: &AppHandle, image: &str) -> Option<PinnedImage> {
+    app_settings::current(app_handle).image_registry.pinned_images.into_iter().find(|p| p.image == image)
+}
+
+// A registry answers `/v2/` with 200, or 401 when it wants credentials; either means it's there.
+pub async fn check_mirror(mirror: &str) -> Result<String, String> {
+    let url = format!("{}/v2/", mirror.trim_end_matches('/'));
+    let response = http_client::client()?.get(&url).send().await.map_err(|e| format!("{} is unreachable: {}", mirror, e))?;
+    match response.status().as_u16() {
+        200 | 401 => Ok(format!("{} answers as a registry.", mirror)),
+        code => Err(format!("{} answered {} to the registry API; check the mirror URL.", mirror, code)),
+    }
+}
+
+pub async fn push_to_daemon(app_handle: &AppHandle, config: &RegistryConfig) -> Result<(), String> {
+    let json = serde_json::to_string(config).map_err(|e| format!("Failed to serialize registry config: {}", e))?;
+    invoke_daemon_cli_json_output::<serde_json::Value>(app_handle, &["--set-registry-config-json", &json]).await.map(|_| ())
+}
+
+pub fn sync_on_start(app_handle: &AppHandle) {
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        let config = app_settings::current(&app_handle).image_registry;
+        if let Err(e) = push_to_daemon(&app_handle, &config).await {
+            emit_log_entry(&app_handle, "error", format!("Failed to push registry config to the daemon: {}", e));
+        }
+    });
+}
+
+#[tauri::command]
+pub async fn set_registry_config(app_handle: AppHandle, state: State<'_, AppSettingsState>, config: RegistryConfig) -> Result<RegistryConfig, String> {
+    let mut settings = state.get();
+    settings.image_registry = config.clone();
+    app_settings::update_app_settings(app_handle.clone(), state, settings).await?;
+    if let Err(e) = push_to_daemon(&app_handle, &config).await {
+        emit_log_entry(&app_handle, "status", format!("Registry config saved; daemon will receive it on next start ({}).", e));
+    }
+    Ok(config)
+}
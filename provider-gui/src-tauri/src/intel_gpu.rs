@@ -0,0 +1,145 @@
+// Intel Arc and integrated Xe GPUs, listed for display only.
+//
+// The daemon can't rent out Intel GPUs yet, but providers with one installed should see it
+// rather than wonder why it's missing. Devices are enumerated through Intel's `xpu-smi` when it
+// is installed (memory and utilization included); otherwise Linux sysfs is scanned for Intel
+// display devices, which gives little more than their presence. They never enter the rental
+// pipeline and always carry a note explaining why.
+
+use serde::Serialize;
+use serde_json::Value;
+use std::fs;
+use std::time::Duration;
+
+use crate::hooks::run_script;
+
+const XPU_SMI_TIMEOUT: Duration = Duration::from_secs(10);
+const INTEL_PCI_VENDOR: &str = "0x8086";
+// PCI class prefixes for VGA and display controllers.
+const DISPLAY_CLASSES: [&str; 2] = ["0x0300", "0x0380"];
+const BYTES_PER_MB: f64 = 1024.0 * 1024.0;
+
+#[derive(Serialize, Debug, Clone)]
+pub struct DisplayOnlyGpu {
+    pub id: String,
+    pub name: String,
+    pub vendor: String,
+    pub integrated: bool,
+    pub vram_total_mb: Option<u32>,
+    pub vram_used_mb: Option<u32>,
+    pub utilization_gpu_percent: Option<u32>,
+    pub source: String, // "xpu-smi" | "sysfs"
+    pub rentable: bool,
+    pub capability_note: String,
+}
+
+fn capability_note(integrated: bool) -> String {
+    if integrated {
+        "Integrated GPUs share system memory and can't be rented out.".to_string()
+    } else {
+        "Intel GPUs are shown for information only; the provider daemon can't rent them out yet.".to_string()
+    }
+}
+
+fn number(value: &Value) -> Option<f64> {
+    value.as_f64().or_else(|| value.as_str().and_then(|s| s.trim().parse().ok()))
+}
+
+fn xpu_smi(args: &[&str]) -> Result<Value, String> {
+    let args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+    let output = run_script("xpu-smi", &args, "", XPU_SMI_TIMEOUT, false)?;
+    serde_json::from_str(&output.stdout).map_err(|e| format!("Unexpected xpu-smi output: {}", e))
+}
+
+// Utilization (%) and memory used (MiB) from `xpu-smi stats`; best effort.
+fn xpu_stats(device_id: &str) -> (Option<u32>, Option<u32>) {
+    let Ok(stats) = xpu_smi(&["stats", "-d", device_id, "-j"]) else { return (None, None) };
+    let metrics = stats.get("device_level").and_then(Value::as_array).cloned().unwrap_or_default();
+    let metric = |kind: &str| {
+        metrics
+            .iter()
+            .find(|m| m.get("metrics_type").and_then(Value::as_str) == Some(kind))
+            .and_then(|m| m.get("value").and_then(number))
+            .map(|v| v.round() as u32)
+    };
+    (metric("XPUM_STATS_GPU_UTILIZATION"), metric("XPUM_STATS_MEMORY_USED"))
+}
+
+fn from_xpu_smi() -> Result<Vec<DisplayOnlyGpu>, String> {
+    let discovery = xpu_smi(&["discovery", "-j"])?;
+    let devices = discovery.get("device_list").and_then(Value::as_array).cloned().unwrap_or_default();
+    Ok(devices
+        .iter()
+        .map(|device| {
+            let device_id = device.get("device_id").map(|v| v.to_string().trim_matches('"').to_string()).unwrap_or_default();
+            let name = device.get("device_name").and_then(Value::as_str).unwrap_or("Intel GPU").to_string();
+            let integrated = device.get("device_function_type").and_then(Value::as_str) == Some("integrated")
+                || !(name.contains("Arc") || name.contains("Data Center") || name.contains("Flex") || name.contains("Max"));
+            let (utilization, used) = xpu_stats(&device_id);
+            DisplayOnlyGpu {
+                id: device.get("uuid").and_then(Value::as_str).map_or_else(|| format!("intel-{}", device_id), str::to_string),
+                name,
+                vendor: "Intel".to_string(),
+                integrated,
+                vram_total_mb: device.get("memory_physical_size_byte").and_then(number).map(|b| (b / BYTES_PER_MB).round() as u32),
+                vram_used_mb: used,
+                utilization_gpu_percent: utilization,
+                source: "xpu-smi".to_string(),
+                rentable: false,
+                capability_note: capability_note(integrated),
+            }
+        })
+        .collect())
+}
+
+// Intel display devices under /sys/class/drm. Discrete cards expose their local memory size
+// through the xe/i915 driver; integrated ones don't have any.
+fn from_sysfs() -> Vec<DisplayOnlyGpu> {
+    let Ok(entries) = fs::read_dir("/sys/class/drm") else { return Vec::new() };
+    let mut gpus = Vec::new();
+    for entry in entries.flatten() {
+        let card = entry.file_name().to_string_lossy().to_string();
+        if !card.starts_with("card") || card.contains('-') {
+            continue;
+        }
+        let device = entry.path().join("device");
+        let read = |name: &str| fs::read_to_string(device.join(name)).map(|s| s.trim().to_string()).ok();
+        if read("vendor").as_deref() != Some(INTEL_PCI_VENDOR) {
+            continue;
+        }
+        if !read("class").is_some_and(|class| DISPLAY_CLASSES.iter().any(|c| class.starts_with(c))) {
+            continue;
+        }
+        let vram_total_mb = read("tile0/gt0/lmem_total_bytes")
+            .or_else(|| read("lmem_total_bytes"))
+            .and_then(|b| b.parse::<f64>().ok())
+            .map(|b| (b / BYTES_PER_MB).round() as u32);
+        let integrated = vram_total_mb.is_none();
+        let pci_id = read("device").unwrap_or_default();
+        gpus.push(DisplayOnlyGpu {
+            id: format!("intel-{}", card),
+            name: if integrated { format!("Intel integrated graphics ({})", pci_id) } else { format!("Intel Arc ({})", pci_id) },
+            vendor: "Intel".to_string(),
+            integrated,
+            vram_total_mb,
+            vram_used_mb: None,
+            utilization_gpu_percent: None,
+            source: "sysfs".to_string(),
+            rentable: false,
+            capability_note: capability_note(integrated),
+        });
+    }
+    gpus
+}
+
+pub fn list() -> Vec<DisplayOnlyGpu> {
+    match from_xpu_smi() {
+        Ok(gpus) if !gpus.is_empty() => gpus,
+        _ => from_sysfs(),
+    }
+}
+
+#[tauri::command]
+pub async fn get_display_only_gpus() -> Result<Vec<DisplayOnlyGpu>, String> {
+    tauri::async_runtime::spawn_blocking(list).await.map_err(|e| e.to_string())
+}
@@ -0,0 +1,206 @@
+// Hardware inventory: what each card cost, when it was bought, and whether it has paid for itself.
+//
+// The provider records serial, purchase date and price, and warranty end per GPU. A card's
+// earnings are summed from the processed-event ledger, which keeps each completed job's amount
+// and GPU, and shown net of the current platform commission. Purchases are usually in a fiat
+// currency; with the provider's own DGPU exchange rate the earnings are converted for payback
+// and ROI, without one those stay unset.
+// The metadata lives in local storage, so backups carry it, and `export` writes it as CSV.
+// Together with the rig's operating costs it gives each card's break-even rate (see `pricing`).
+
+use chrono::{NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::money::{self, Decimal};
+use crate::storage::{unix_now, Storage};
+use crate::wal::WalEventKind;
+use crate::{app_settings, emit_log_entry, nvidia_smi, pricing};
+
+const DGPU: &str = "DGPU";
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct GpuMetadata {
+    pub serial: Option<String>,
+    pub purchase_date: Option<String>, // YYYY-MM-DD
+    #[serde(with = "money::optional_amount")]
+    pub purchase_price: Option<Decimal>,
+    pub purchase_currency: String, // ISO code such as "EUR", or "DGPU"
+    // What one DGPU is worth in `purchase_currency`, as the provider values it.
+    #[serde(with = "money::optional_amount")]
+    pub dgpu_exchange_rate: Option<Decimal>,
+    pub warranty_until: Option<String>, // YYYY-MM-DD
+}
+
+impl GpuMetadata {
+    // What one DGPU is worth in the purchase currency, if known.
+    pub fn dgpu_value(&self) -> Option<Decimal> {
+        if self.purchase_currency.eq_ignore_ascii_case(DGPU) {
+            Some(Decimal::ONE)
+        } else {
+            self.dgpu_exchange_rate
+        }
+    }
+}
+
+// Rig-wide running costs, in the GUI settings.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct OperatingCosts {
+    #[serde(with = "money::optional_amount")]
+    pub electricity_price_per_kwh: Option<Decimal>,
+    pub electricity_currency: String,
+    // Period over which a card's purchase price is written off.
+    pub amortization_months: u32,
+}
+
+impl Default for OperatingCosts {
+    fn default() -> Self {
+        OperatingCosts { electricity_price_per_kwh: None, electricity_currency: String::new(), amortization_months: 36 }
+    }
+}
+
+impl OperatingCosts {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.electricity_price_per_kwh.is_some_and(|p| p < Decimal::ZERO) {
+            return Err("The electricity price can't be negative.".to_string());
+        }
+        if self.electricity_price_per_kwh.is_some() && self.electricity_currency.trim().is_empty() {
+            return Err("Say which currency the electricity price is in.".to_string());
+        }
+        if self.amortization_months == 0 || self.amortization_months > 120 {
+            return Err("The amortization period must be between 1 and 120 months.".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GpuInventoryRecord {
+    pub gpu_id: String,
+    pub metadata: GpuMetadata,
+    pub updated_at: i64,
+}
+
+impl GpuInventoryRecord {
+    pub fn new(gpu_id: &str) -> Self {
+        GpuInventoryRecord { gpu_id: gpu_id.to_string(), metadata: GpuMetadata::default(), updated_at: 0 }
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct InventoryItem {
+    pub gpu_id: String,
+    pub name: Option<String>, // None: the card isn't currently detected
+    pub metadata: GpuMetadata,
+    #[serde(with = "money::amount")]
+    pub earned_dgpu: Decimal, // Net of commission
+    pub earnings_since: Option<i64>, // First completion in the ledger
+    // Earnings in the purchase currency, payback progress (100 = paid off) and return on
+    // investment, when price and exchange rate are known.
+    #[serde(with = "money::optional_amount")]
+    pub earned_in_purchase_currency: Option<Decimal>,
+    pub payback_percent: Option<f64>,
+    pub roi_percent: Option<f64>,
+    pub days_owned: Option<i64>,
+    pub warranty_days_left: Option<i64>, // Negative once expired
+}
+
+fn parse_date(value: &str, field: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d").map_err(|_| format!("The {} '{}' isn't a YYYY-MM-DD date.", field, value))
+}
+
+fn validate(metadata: &GpuMetadata) -> Result<(), String> {
+    let purchased = metadata.purchase_date.as_deref().map(|d| parse_date(d, "purchase date")).transpose()?;
+    if purchased.is_some_and(|d| d > Utc::now().date_naive()) {
+        return Err("The purchase date is in the future.".to_string());
+    }
+    if let Some(warranty) = metadata.warranty_until.as_deref() {
+        let warranty = parse_date(warranty, "warranty end")?;
+        if purchased.is_some_and(|d| warranty < d) {
+            return Err("The warranty ends before the purchase date.".to_string());
+        }
+    }
+    if metadata.purchase_price.is_some_and(|p| p <= Decimal::ZERO) {
+        return Err("The purchase price must be positive.".to_string());
+    }
+    if metadata.dgpu_exchange_rate.is_some_and(|r| r <= Decimal::ZERO) {
+        return Err("The DGPU exchange rate must be positive.".to_string());
+    }
+    if metadata.purchase_price.is_some() && metadata.purchase_currency.trim().is_empty() {
+        return Err("Say which currency the purchase price is in.".to_string());
+    }
+    Ok(())
+}
+
+fn item(record: GpuInventoryRecord, name: Option<String>, earned_dgpu: Decimal, earnings_since: Option<i64>) -> InventoryItem {
+    let metadata = record.metadata;
+    let today = Utc::now().date_naive();
+    let days_until = |date: &Option<String>| date.as_deref().and_then(|d| parse_date(d, "date").ok()).map(|d| (d - today).num_days());
+    let earned_in_purchase_currency = metadata.dgpu_value().map(|value| earned_dgpu * value);
+    let ratio = match (earned_in_purchase_currency, metadata.purchase_price) {
+        (Some(earned), Some(price)) => Some(money::to_f64(earned) / money::to_f64(price)),
+        _ => None,
+    };
+    InventoryItem {
+        gpu_id: record.gpu_id,
+        name,
+        earned_dgpu,
+        earnings_since,
+        earned_in_purchase_currency,
+        payback_percent: ratio.map(|r| r * 100.0),
+        roi_percent: ratio.map(|r| (r - 1.0) * 100.0),
+        days_owned: days_until(&metadata.purchase_date).map(|d| -d),
+        warranty_days_left: days_until(&metadata.warranty_until),
+        metadata,
+    }
+}
+
+// Every card with metadata, plus detected cards without any, in GPU ID order.
+pub async fn report(app_handle: &AppHandle) -> Result<Vec<InventoryItem>, String> {
+    let storage = app_handle.try_state::<Storage>().ok_or_else(|| "Local storage is unavailable.".to_string())?;
+    let mut records = storage.gpu_inventory()?;
+    let detected = nvidia_smi::detect_gpus(app_handle).await.unwrap_or_default();
+    for gpu in &detected {
+        if !records.iter().any(|r| r.gpu_id == gpu.id) {
+            records.push(GpuInventoryRecord::new(&gpu.id));
+        }
+    }
+    records.sort_by(|a, b| a.gpu_id.cmp(&b.gpu_id));
+
+    let earnings = storage.ledger_earnings_by_gpu(WalEventKind::JobCompleted.as_str())?;
+    let (schedule, _) = pricing::fee_schedule(app_handle).await;
+    let rounding = app_settings::current(app_handle).amount_rounding;
+    Ok(records
+        .into_iter()
+        .map(|record| {
+            let name = detected.iter().find(|g| g.id == record.gpu_id).map(|g| g.name.clone());
+            let (gross, since) = earnings.get(&record.gpu_id).copied().map_or((Decimal::ZERO, None), |(gross, since)| (gross, Some(since)));
+            let net = gross - rounding.round(gross * schedule.provider_commission_percent / Decimal::ONE_HUNDRED);
+            item(record, name, net, since)
+        })
+        .collect())
+}
+
+#[tauri::command]
+pub async fn set_gpu_metadata(app_handle: AppHandle, gpu_id: String, metadata: GpuMetadata) -> Result<GpuMetadata, String> {
+    let storage = app_handle.try_state::<Storage>().ok_or_else(|| "Local storage is unavailable.".to_string())?;
+    let metadata = GpuMetadata {
+        serial: metadata.serial.map(|s| s.trim().to_string()).filter(|s| !s.is_empty()),
+        purchase_currency: metadata.purchase_currency.trim().to_uppercase(),
+        ..metadata
+    };
+    validate(&metadata)?;
+    storage.update_gpu_inventory(&gpu_id, |record| {
+        record.metadata = metadata.clone();
+        record.updated_at = unix_now();
+    })?;
+    emit_log_entry(&app_handle, "status", format!("Saved purchase details for GPU {}.", gpu_id));
+    Ok(metadata)
+}
+
+#[tauri::command]
+pub async fn get_gpu_inventory(app_handle: AppHandle) -> Result<Vec<InventoryItem>, String> {
+    report(&app_handle).await
+}
@@ -0,0 +1,63 @@
+// Short status notes from the provider to the renter of a running job.
+//
+// A note (e.g. "brief network hiccup at 14:02, resumed") is handed to the daemon, which passes
+// it to the platform for the renter's dashboard; the renter sees only the latest one. Every
+// note is also kept locally with whether the daemon accepted it, so the provider has a record of
+// what the renter was told. Notes are job metadata for the purposes of purging.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::{AppHandle, Manager};
+
+use crate::storage::Storage;
+use crate::{emit_log_entry, get_timestamp, gpu_ids, invoke_daemon_cli_json_output};
+
+pub const MAX_NOTE_CHARS: usize = 280;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct JobNote {
+    pub job_id: String,
+    pub text: String,
+    pub created_at: String,
+    pub delivered: bool,
+    pub error: Option<String>,
+}
+
+// Hands the note to the daemon and keeps it, delivered or not.
+pub async fn send(app_handle: &AppHandle, storage: &Storage, job_id: String, text: String) -> Result<JobNote, String> {
+    let payload = json!({ "job_id": job_id, "text": text }).to_string();
+    let result = invoke_daemon_cli_json_output::<Value>(app_handle, &["--set-job-note-json", &payload]).await;
+    let note = JobNote { job_id, text, created_at: get_timestamp(), delivered: result.is_ok(), error: result.err() };
+    storage.insert_job_note(&note)?;
+    Ok(note)
+}
+
+#[tauri::command]
+pub async fn set_job_note(app_handle: AppHandle, job_id: String, text: String) -> Result<JobNote, String> {
+    let storage = app_handle.try_state::<Storage>().ok_or_else(|| "Local storage is unavailable.".to_string())?;
+    let text = text.trim().to_string();
+    if text.is_empty() {
+        return Err("The note is empty.".to_string());
+    }
+    if text.chars().count() > MAX_NOTE_CHARS {
+        return Err(format!("Notes are limited to {} characters.", MAX_NOTE_CHARS));
+    }
+    let jobs = gpu_ids::local_jobs(&app_handle).await?;
+    let job = jobs.iter().find(|j| j.id == job_id).ok_or_else(|| format!("No job with ID '{}'.", job_id))?;
+    if job.status != "running" {
+        return Err(format!("Job '{}' is {}; notes can only be sent while it is running.", job.name, job.status));
+    }
+    let note = send(&app_handle, &storage, job_id, text).await?;
+    match &note.error {
+        None => emit_log_entry(&app_handle, "status", format!("Sent a note to the renter of job {}.", note.job_id)),
+        Some(e) => emit_log_entry(&app_handle, "error", format!("Note for job {} was saved but not delivered: {}", note.job_id, e)),
+    }
+    Ok(note)
+}
+
+// Newest first.
+#[tauri::command]
+pub async fn get_job_notes(app_handle: AppHandle, job_id: String) -> Result<Vec<JobNote>, String> {
+    let storage = app_handle.try_state::<Storage>().ok_or_else(|| "Local storage is unavailable.".to_string())?;
+    storage.job_notes(&job_id)
+}
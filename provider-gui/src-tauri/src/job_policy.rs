@@ -0,0 +1,183 @@
+// Which kinds of jobs the provider accepts.
+//
+// The policy lives in the GUI settings and is pushed to the daemon as capability constraints,
+// so the daemon can refuse non-matching requests before accepting them. As a backstop, the
+// poller checks newly queued jobs against the same policy and declines violations; both paths
+// surface as `job_declined` events and are kept for `rejections`.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, State};
+
+use crate::app_settings::{self, AppSettingsState};
+use crate::rejections::{self, RejectionReason};
+use crate::{emit_log_entry, invoke_daemon_cli_json_output, LocalJob};
+
+pub use dante_provider_core::models::JobCategory;
+
+const DECLINE_RETRY_BASE: Duration = Duration::from_secs(30);
+const DECLINE_RETRY_MAX: Duration = Duration::from_secs(30 * 60);
+
+// Queued jobs whose decline failed: (failed attempts, earliest next attempt).
+static DECLINE_RETRIES: Mutex<Option<HashMap<String, (u32, Instant)>>> = Mutex::new(None);
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct JobPolicy {
+    // Empty means every category not explicitly blocked is accepted.
+    pub allowed_categories: Vec<JobCategory>,
+    pub blocked_categories: Vec<JobCategory>,
+    // Framework names as reported by the platform (e.g. "pytorch"), compared case-insensitively.
+    pub allowed_frameworks: Vec<String>,
+    pub blocked_frameworks: Vec<String>,
+    pub max_job_duration_hours: Option<f32>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct DeclinedJob {
+    pub job_id: String,
+    pub job_name: String,
+    pub reason: String,
+    pub source: String, // "daemon" | "gui"
+}
+
+fn contains_ignore_case(list: &[String], value: &str) -> bool {
+    list.iter().any(|item| item.eq_ignore_ascii_case(value))
+}
+
+impl JobPolicy {
+    // Returns why `job` is not acceptable under this policy, if it isn't. Jobs that don't
+    // report a category or framework are only rejected by rules that can be checked.
+    pub fn violation(&self, job: &LocalJob) -> Option<String> {
+        if let Some(category) = job.category {
+            if self.blocked_categories.contains(&category) {
+                return Some(format!("category {:?} is blocked", category));
+            }
+            if !self.allowed_categories.is_empty() && !self.allowed_categories.contains(&category) {
+                return Some(format!("category {:?} is not in the accepted list", category));
+            }
+        }
+        if let Some(framework) = job.framework.as_deref() {
+            if contains_ignore_case(&self.blocked_frameworks, framework) {
+                return Some(format!("framework '{}' is blocked", framework));
+            }
+            if !self.allowed_frameworks.is_empty() && !contains_ignore_case(&self.allowed_frameworks, framework) {
+                return Some(format!("framework '{}' is not in the accepted list", framework));
+            }
+        }
+        if let (Some(max_hours), Some(minutes)) = (self.max_job_duration_hours, job.max_duration_minutes) {
+            if minutes as f32 > max_hours * 60.0 {
+                return Some(format!("requested duration {} min exceeds the {} h limit", minutes, max_hours));
+            }
+        }
+        None
+    }
+}
+
+fn emit_declined(app_handle: &AppHandle, declined: DeclinedJob, reason: RejectionReason) {
+    rejections::record(app_handle, &declined.job_id, &declined.job_name, reason, &declined.reason, &declined.source);
+    announce_declined(app_handle, declined);
+}
+
+fn announce_declined(app_handle: &AppHandle, declined: DeclinedJob) {
+    emit_log_entry(app_handle, "status", format!("Declined job {} ({}): {}", declined.job_id, declined.job_name, declined.reason));
+    if let Err(e) = app_handle.emit_all("job_declined", declined) {
+        eprintln!("Failed to emit job_declined event: {}", e);
+    }
+}
+
+pub async fn push_to_daemon(app_handle: &AppHandle, policy: &JobPolicy) -> Result<(), String> {
+    let policy_json = serde_json::to_string(policy).map_err(|e| format!("Failed to serialize job policy: {}", e))?;
+    invoke_daemon_cli_json_output::<serde_json::Value>(app_handle, &["--set-job-constraints-json", &policy_json])
+        .await
+        .map(|_| ())
+}
+
+// Backstop for jobs the daemon queued despite the constraints (older daemons, or a policy
+// change racing a request).
+pub async fn enforce(app_handle: &AppHandle, jobs: &[LocalJob]) {
+    let policy = app_settings::current(app_handle).job_policy;
+    let queued: Vec<&LocalJob> = jobs.iter().filter(|j| j.status == "queued").collect();
+    // Forget jobs that have left the queue, whichever way.
+    DECLINE_RETRIES
+        .lock()
+        .unwrap()
+        .get_or_insert_with(HashMap::new)
+        .retain(|job_id, _| queued.iter().any(|j| &j.id == job_id));
+    for job in queued {
+        let Some(reason) = policy.violation(job) else { continue };
+        let waiting = DECLINE_RETRIES.lock().unwrap().as_ref().and_then(|retries| retries.get(&job.id)).is_some_and(|(_, next)| Instant::now() < *next);
+        if waiting {
+            continue;
+        }
+        let result = invoke_daemon_cli_json_output::<serde_json::Value>(app_handle, &["--decline-job", "--job-id", &job.id, "--reason", &reason]).await;
+        let mut retries = DECLINE_RETRIES.lock().unwrap();
+        let retries = retries.get_or_insert_with(HashMap::new);
+        match result {
+            Ok(_) => {
+                retries.remove(&job.id);
+                let declined = DeclinedJob { job_id: job.id.clone(), job_name: job.name.clone(), reason, source: "gui".to_string() };
+                emit_declined(app_handle, declined, RejectionReason::JobPolicy);
+            }
+            Err(e) => {
+                // Back off exponentially so a daemon that keeps refusing isn't hammered every poll.
+                let attempts = retries.get(&job.id).map_or(0, |(attempts, _)| *attempts) + 1;
+                let delay = DECLINE_RETRY_BASE.saturating_mul(2u32.saturating_pow(attempts - 1)).min(DECLINE_RETRY_MAX);
+                retries.insert(job.id.clone(), (attempts, Instant::now() + delay));
+                emit_log_entry(app_handle, "error", format!("Failed to decline job {} ({}); retrying in {} s: {}", job.id, reason, delay.as_secs(), e));
+            }
+        }
+    }
+}
+
+// Requests the daemon refused under the pushed constraints since the last call.
+pub async fn collect_daemon_declines(app_handle: &AppHandle) {
+    if let Ok(declined) = invoke_daemon_cli_json_output::<Vec<serde_json::Value>>(app_handle, &["--get-declined-requests-json"]).await {
+        for entry in declined {
+            let declined = DeclinedJob {
+                job_id: entry.get("job_id").and_then(|v| v.as_str()).unwrap_or_default().to_string(),
+                job_name: entry.get("name").and_then(|v| v.as_str()).unwrap_or_default().to_string(),
+                reason: entry.get("reason").and_then(|v| v.as_str()).unwrap_or("policy constraint").to_string(),
+                source: "daemon".to_string(),
+            };
+            let reason = RejectionReason::classify(entry.get("reason_code").and_then(|v| v.as_str()), &declined.reason);
+            let key = rejections::report_key("daemon", &entry);
+            // The daemon may list the same decline again; only announce it the first time.
+            if rejections::record_reported(app_handle, &key, &declined.job_id, &declined.job_name, reason, &declined.reason, &declined.source) {
+                announce_declined(app_handle, declined);
+            }
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn get_job_policy(state: State<'_, AppSettingsState>) -> Result<JobPolicy, String> {
+    Ok(state.get().job_policy)
+}
+
+#[tauri::command]
+pub async fn set_job_policy(app_handle: AppHandle, state: State<'_, AppSettingsState>, policy: JobPolicy) -> Result<JobPolicy, String> {
+    if policy.max_job_duration_hours.is_some_and(|h| h <= 0.0) {
+        return Err("Maximum job duration must be positive.".to_string());
+    }
+    let mut settings = state.get();
+    settings.job_policy = policy.clone();
+    app_settings::update_app_settings(app_handle.clone(), state, settings).await?;
+    // The daemon may be offline; the policy is pushed again when it starts.
+    if let Err(e) = push_to_daemon(&app_handle, &policy).await {
+        emit_log_entry(&app_handle, "status", format!("Job policy saved; daemon will receive it on next start ({}).", e));
+    }
+    Ok(policy)
+}
+
+pub fn sync_on_start(app_handle: &AppHandle) {
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        let policy = app_settings::current(&app_handle).job_policy;
+        if let Err(e) = push_to_daemon(&app_handle, &policy).await {
+            emit_log_entry(&app_handle, "error", format!("Failed to push job policy to the daemon: {}", e));
+        }
+    });
+}
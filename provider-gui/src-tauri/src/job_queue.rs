@@ -0,0 +1,141 @@
+// Preview of the daemon's pending job queue with estimated start times.
+//
+// Estimates come from a simple slot simulation: each running job frees its slot when its
+// progress extrapolates to 100%, and queued jobs take the earliest free slot in submission
+// order. Job durations that can't be extrapolated fall back to the recent historical average.
+
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager};
+
+use crate::storage::Storage;
+use crate::{get_local_jobs, get_provider_settings, LocalJob};
+
+const DEFAULT_JOB_DURATION_SECS: i64 = 60 * 60;
+
+#[derive(Serialize, Debug, Clone)]
+pub struct QueuedJobPreview {
+    pub job: LocalJob,
+    pub position: usize,
+    pub estimated_start_at: Option<String>,
+    pub estimated_wait_secs: Option<i64>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct JobQueuePreview {
+    pub queued: Vec<QueuedJobPreview>,
+    pub running: usize,
+    pub slots: u32,
+    pub assumed_job_duration_secs: i64,
+}
+
+pub struct JobQueueState {
+    last_queue: Mutex<Vec<String>>,
+}
+
+impl JobQueueState {
+    pub fn new() -> Self {
+        JobQueueState { last_queue: Mutex::new(Vec::new()) }
+    }
+}
+
+fn parse_unix(ts: &str) -> Option<i64> {
+    humantime::parse_rfc3339_weak(ts)
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs() as i64)
+}
+
+fn format_unix(secs: i64) -> String {
+    humantime::format_rfc3339_seconds(UNIX_EPOCH + Duration::from_secs(secs.max(0) as u64)).to_string()
+}
+
+fn now_unix() -> i64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs() as i64).unwrap_or(0)
+}
+
+// Remaining seconds for a running job, extrapolated from its progress so far.
+fn remaining_secs(job: &LocalJob, now: i64, fallback: i64) -> i64 {
+    let elapsed = job.started_at.as_deref().and_then(parse_unix).map(|start| (now - start).max(0));
+    match elapsed {
+        Some(elapsed) if job.progress_percent > 1.0 => {
+            let total = elapsed as f64 * 100.0 / f64::from(job.progress_percent);
+            (total as i64 - elapsed).max(0)
+        }
+        Some(elapsed) => (fallback - elapsed).max(0),
+        None => fallback,
+    }
+}
+
+pub fn preview(jobs: &[LocalJob], slots: u32, assumed_duration: i64) -> JobQueuePreview {
+    let now = now_unix();
+    let slots = slots.max(1);
+    let running: Vec<&LocalJob> = jobs.iter().filter(|j| j.status == "running").collect();
+
+    // Min-heap of times at which a slot becomes free.
+    let mut free_at: BinaryHeap<Reverse<i64>> = running.iter().map(|j| Reverse(now + remaining_secs(j, now, assumed_duration))).collect();
+    while free_at.len() < slots as usize {
+        free_at.push(Reverse(now));
+    }
+
+    let mut queued: Vec<&LocalJob> = jobs.iter().filter(|j| j.status == "queued").collect();
+    queued.sort_by(|a, b| a.submitted_at.cmp(&b.submitted_at));
+    let queued = queued
+        .into_iter()
+        .enumerate()
+        .map(|(position, job)| {
+            let Reverse(start) = free_at.pop().unwrap_or(Reverse(now));
+            free_at.push(Reverse(start + assumed_duration));
+            QueuedJobPreview {
+                job: job.clone(),
+                position: position + 1,
+                estimated_start_at: Some(format_unix(start)),
+                estimated_wait_secs: Some(start - now),
+            }
+        })
+        .collect();
+
+    JobQueuePreview { queued, running: running.len(), slots, assumed_job_duration_secs: assumed_duration }
+}
+
+fn assumed_duration(app_handle: &AppHandle) -> i64 {
+    app_handle
+        .try_state::<Storage>()
+        .and_then(|storage| storage.average_job_duration_secs().ok().flatten())
+        .unwrap_or(DEFAULT_JOB_DURATION_SECS)
+}
+
+// Called by the poller with each job snapshot; emits `queue_changed` when the set or order of
+// queued jobs changes.
+pub async fn observe(app_handle: &AppHandle, jobs: &[LocalJob]) {
+    let mut ids: Vec<(String, String)> = jobs
+        .iter()
+        .filter(|j| j.status == "queued")
+        .map(|j| (j.submitted_at.clone(), j.id.clone()))
+        .collect();
+    ids.sort();
+    let ids: Vec<String> = ids.into_iter().map(|(_, id)| id).collect();
+    {
+        let state = app_handle.state::<JobQueueState>();
+        let mut last = state.last_queue.lock().unwrap();
+        if *last == ids {
+            return;
+        }
+        *last = ids;
+    }
+    let slots = get_provider_settings(app_handle.clone()).await.map(|s| s.max_concurrent_jobs).unwrap_or(1);
+    let preview = preview(jobs, slots, assumed_duration(app_handle));
+    if let Err(e) = app_handle.emit_all("queue_changed", preview) {
+        eprintln!("Failed to emit queue_changed event: {}", e);
+    }
+}
+
+#[tauri::command]
+pub async fn get_job_queue(app_handle: AppHandle) -> Result<JobQueuePreview, String> {
+    let jobs = get_local_jobs(app_handle.clone()).await?;
+    let slots = get_provider_settings(app_handle.clone()).await?.max_concurrent_jobs;
+    Ok(preview(&jobs, slots, assumed_duration(&app_handle)))
+}
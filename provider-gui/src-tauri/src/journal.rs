@@ -0,0 +1,99 @@
+// A maintenance journal: free-form notes the operator keeps about the rig.
+//
+// "Replaced fan on GPU1", "repasted GPU0", "moved the rig to the basement" are exactly the events
+// that explain a later change in temperatures or fan speeds, and nothing else records them. Notes
+// are kept locally with optional tags and may reference an incident (connectivity, failover,
+// emergency stop) by ID. They can be included in diagnostic bundles (see `diagnostics`) and are
+// not subject to the privacy purges, since the operator wrote them deliberately.
+
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use tauri::{AppHandle, Manager};
+
+use crate::storage::{unix_now, Storage};
+
+const MAX_TEXT_CHARS: usize = 2000;
+const MAX_TAGS: usize = 10;
+const MAX_TAG_CHARS: usize = 32;
+
+static NOTE_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct JournalNote {
+    pub note_id: String,
+    pub text: String,
+    pub tags: Vec<String>, // Lowercase, e.g. "gpu1", "fan"
+    pub incident_id: Option<String>,
+    pub created_at: i64, // unix seconds
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct NoteFilter {
+    pub tag: Option<String>,
+    pub text: Option<String>, // Case-insensitive substring
+    pub incident_id: Option<String>,
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+}
+
+impl NoteFilter {
+    fn matches(&self, note: &JournalNote) -> bool {
+        self.tag.as_deref().is_none_or(|tag| note.tags.iter().any(|t| t.eq_ignore_ascii_case(tag.trim())))
+            && self.text.as_deref().is_none_or(|text| note.text.to_lowercase().contains(&text.to_lowercase()))
+            && self.incident_id.as_ref().is_none_or(|id| note.incident_id.as_ref() == Some(id))
+            && self.from.is_none_or(|from| note.created_at >= from)
+            && self.to.is_none_or(|to| note.created_at < to)
+    }
+}
+
+fn normalize_tags(tags: Vec<String>) -> Result<Vec<String>, String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = tag.trim().to_lowercase();
+        if tag.is_empty() || normalized.contains(&tag) {
+            continue;
+        }
+        if tag.chars().count() > MAX_TAG_CHARS || tag.contains(char::is_whitespace) {
+            return Err(format!("Tags are single words of at most {} characters; '{}' isn't.", MAX_TAG_CHARS, tag));
+        }
+        normalized.push(tag);
+    }
+    if normalized.len() > MAX_TAGS {
+        return Err(format!("A note can have at most {} tags.", MAX_TAGS));
+    }
+    Ok(normalized)
+}
+
+#[tauri::command]
+pub async fn add_note(app_handle: AppHandle, text: String, tags: Vec<String>, incident_id: Option<String>) -> Result<JournalNote, String> {
+    let storage = app_handle.try_state::<Storage>().ok_or_else(|| "Local storage is unavailable.".to_string())?;
+    let text = text.trim().to_string();
+    if text.is_empty() {
+        return Err("The note is empty.".to_string());
+    }
+    if text.chars().count() > MAX_TEXT_CHARS {
+        return Err(format!("Notes are limited to {} characters.", MAX_TEXT_CHARS));
+    }
+    let created_at = unix_now();
+    let note = JournalNote {
+        note_id: format!("note-{}-{}", created_at, NOTE_COUNTER.fetch_add(1, Ordering::Relaxed) + 1),
+        text,
+        tags: normalize_tags(tags)?,
+        incident_id: incident_id.map(|id| id.trim().to_string()).filter(|id| !id.is_empty()),
+        created_at,
+    };
+    storage.insert_journal_note(&note)?;
+    Ok(note)
+}
+
+// Newest first.
+pub fn notes(storage: &Storage, filter: &NoteFilter) -> Result<Vec<JournalNote>, String> {
+    Ok(storage.journal_notes()?.into_iter().filter(|n| filter.matches(n)).collect())
+}
+
+#[tauri::command]
+pub async fn get_notes(app_handle: AppHandle, filter: Option<NoteFilter>) -> Result<Vec<JournalNote>, String> {
+    let storage = app_handle.try_state::<Storage>().ok_or_else(|| "Local storage is unavailable.".to_string())?;
+    notes(&storage, &filter.unwrap_or_default())
+}
@@ -0,0 +1,177 @@
+// Error fingerprinting and known-issue matching.
+//
+// Daemon errors are normalised (numbers, IDs and paths stripped) and hashed into a stable
+// fingerprint, then matched against a database of known issues. The bundled database can be
+// replaced by a newer copy fetched from `known_issues_url`, so explanations for new failure
+// modes ship without an app release.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{OnceLock, RwLock};
+use tauri::{AppHandle, Manager, State};
+
+use crate::{app_settings, emit_log_entry, http_client};
+
+const BUNDLED_KNOWN_ISSUES: &str = include_str!("../resources/known_issues.json");
+const DOWNLOADED_FILE_NAME: &str = "known_issues.json";
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct KnownIssue {
+    pub id: String,
+    pub title: String,
+    pub patterns: Vec<String>,
+    pub explanation: String,
+    pub fix_id: Option<String>, // Troubleshooter fix that remediates this issue
+    pub docs_url: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct MatchedIssue {
+    pub id: String,
+    pub title: String,
+    pub explanation: String,
+    pub fix_id: Option<String>,
+    pub docs_url: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ErrorClassification {
+    pub fingerprint: String,
+    pub issue: Option<MatchedIssue>,
+}
+
+struct CompiledIssue {
+    issue: KnownIssue,
+    patterns: Vec<Regex>,
+}
+
+pub struct KnownIssuesState {
+    issues: RwLock<Vec<CompiledIssue>>,
+    downloaded_path: Option<PathBuf>,
+}
+
+fn compile(issues: Vec<KnownIssue>) -> Vec<CompiledIssue> {
+    issues
+        .into_iter()
+        .map(|issue| {
+            let patterns = issue
+                .patterns
+                .iter()
+                .filter_map(|p| match Regex::new(&format!("(?i){}", p)) {
+                    Ok(re) => Some(re),
+                    Err(e) => {
+                        eprintln!("Ignoring invalid pattern in known issue '{}': {}", issue.id, e);
+                        None
+                    }
+                })
+                .collect();
+            CompiledIssue { issue, patterns }
+        })
+        .collect()
+}
+
+fn parse(contents: &str) -> Result<Vec<KnownIssue>, String> {
+    serde_json::from_str(contents).map_err(|e| format!("Invalid known-issues database: {}", e))
+}
+
+impl KnownIssuesState {
+    pub fn load(app_handle: &AppHandle) -> Self {
+        let downloaded_path = app_handle.path_resolver().app_data_dir().map(|dir| dir.join(DOWNLOADED_FILE_NAME));
+        // Prefer a previously downloaded database; fall back to the bundled copy if it is
+        // missing or corrupt.
+        let issues = downloaded_path
+            .as_ref()
+            .and_then(|p| fs::read_to_string(p).ok())
+            .and_then(|contents| parse(&contents).ok())
+            .unwrap_or_else(|| parse(BUNDLED_KNOWN_ISSUES).expect("bundled known_issues.json is valid"));
+
+        KnownIssuesState {
+            issues: RwLock::new(compile(issues)),
+            downloaded_path,
+        }
+    }
+
+    fn classify(&self, message: &str) -> ErrorClassification {
+        let issues = self.issues.read().unwrap();
+        let issue = issues
+            .iter()
+            .find(|c| c.patterns.iter().any(|re| re.is_match(message)))
+            .map(|c| MatchedIssue {
+                id: c.issue.id.clone(),
+                title: c.issue.title.clone(),
+                explanation: c.issue.explanation.clone(),
+                fix_id: c.issue.fix_id.clone(),
+                docs_url: c.issue.docs_url.clone(),
+            });
+        ErrorClassification {
+            fingerprint: fingerprint(message),
+            issue,
+        }
+    }
+}
+
+// Strips the parts of an error message that vary between occurrences of the same failure so
+// that repeated errors hash to the same fingerprint.
+fn normalize(message: &str) -> String {
+    static RULES: OnceLock<Vec<(Regex, &'static str)>> = OnceLock::new();
+    let rules = RULES.get_or_init(|| {
+        vec![
+            (Regex::new(r"\d{4}-\d{2}-\d{2}[T ][\d:.]+(Z|[+-]\d{2}:?\d{2})?").unwrap(), "<ts>"),
+            (Regex::new(r"(?i)\b[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}\b").unwrap(), "<uuid>"),
+            (Regex::new(r"(?i)\b(0x)?[0-9a-f]{12,}\b").unwrap(), "<hex>"),
+            (Regex::new(r"([A-Za-z]:\\|/)[^\s:'\x22]+").unwrap(), "<path>"),
+            (Regex::new(r"\d+").unwrap(), "<n>"),
+            (Regex::new(r"\s+").unwrap(), " "),
+        ]
+    });
+    let mut normalized = message.to_string();
+    for (re, replacement) in rules {
+        normalized = re.replace_all(&normalized, *replacement).into_owned();
+    }
+    normalized.trim().to_lowercase()
+}
+
+pub fn fingerprint(message: &str) -> String {
+    let digest = Sha256::digest(normalize(message).as_bytes());
+    digest.iter().take(8).map(|b| format!("{:02x}", b)).collect()
+}
+
+pub fn classify<R: tauri::Runtime>(manager: &impl Manager<R>, message: &str) -> Option<ErrorClassification> {
+    manager.try_state::<KnownIssuesState>().map(|state| state.classify(message))
+}
+
+#[tauri::command]
+pub async fn get_known_issues(state: State<'_, KnownIssuesState>) -> Result<Vec<KnownIssue>, String> {
+    Ok(state.issues.read().unwrap().iter().map(|c| c.issue.clone()).collect())
+}
+
+#[tauri::command]
+pub async fn refresh_known_issues(app_handle: AppHandle, state: State<'_, KnownIssuesState>) -> Result<usize, String> {
+    let url = app_settings::current(&app_handle)
+        .known_issues_url
+        .ok_or_else(|| "No known-issues update URL is configured.".to_string())?;
+    let body = http_client::client()?
+        .get(&url)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| format!("Failed to fetch known issues from {}: {}", url, e))?
+        .text()
+        .await
+        .map_err(|e| format!("Failed to read known issues response: {}", e))?;
+    let issues = parse(&body)?;
+    let count = issues.len();
+
+    if let Some(path) = &state.downloaded_path {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        }
+        fs::write(path, &body).map_err(|e| format!("Failed to save known issues: {}", e))?;
+    }
+    *state.issues.write().unwrap() = compile(issues);
+    emit_log_entry(&app_handle, "status", format!("Known-issues database updated ({} entries).", count));
+    Ok(count)
+}
@@ -0,0 +1,191 @@
+// PCIe link health and clock throttling.
+//
+// A card on a bad riser often negotiates a x1 or Gen1 link and keeps working, only far slower at
+// moving data, which renters notice as a slow machine rather than an error. Each poll compares
+// the negotiated link with what card and slot support and warns once per GPU when it is down to
+// x1, or to Gen1 while loaded (idle cards drop to Gen1 on purpose). While a job runs, each poll
+// also records whether its GPU was thermally or power throttled (NVML's throttle reasons); a job
+// that spends a significant share of its time thermally throttled gets a warning, and the tally
+// is kept with the job as evidence for disputes, subject to the job metadata privacy setting.
+
+use dante_provider_core::models::JobHardwareEvidence;
+use serde::Serialize;
+use serde_json::json;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+
+use crate::storage::Storage;
+use crate::{app_settings, emit_log_entry, get_timestamp, plugins, GpuInfo, LocalJob};
+
+// NVML throttle reason bits, by the suffix nvidia-smi's XML report names them with.
+pub const THROTTLE_REASONS: &[(&str, u64)] = &[
+    ("gpu_idle", 0x1),
+    ("applications_clocks_setting", 0x2),
+    ("sw_power_cap", 0x4),
+    ("hw_slowdown", 0x8),
+    ("sync_boost", 0x10),
+    ("sw_thermal_slowdown", 0x20),
+    ("hw_thermal_slowdown", 0x40),
+    ("hw_power_brake_slowdown", 0x80),
+    ("display_clocks_setting", 0x100),
+];
+const THERMAL_THROTTLE_MASK: u64 = 0x20 | 0x40;
+const POWER_THROTTLE_MASK: u64 = 0x4 | 0x80;
+
+const LOADED_FROM_PERCENT: u32 = 50;
+// A job is warned about once it has been sampled this often and thermally throttled for at
+// least this share of the samples.
+const THROTTLE_WARN_MIN_SAMPLES: u32 = 12;
+const THROTTLE_WARN_PERCENT: u32 = 20;
+
+#[derive(Serialize, Debug, Clone)]
+pub struct HardwareWarning {
+    pub gpu_id: String,
+    pub gpu_name: String,
+    pub kind: String, // "pcie_link_degraded" | "thermal_throttling"
+    pub message: String,
+    pub job_id: Option<String>,
+    pub detected_at: String,
+}
+
+#[derive(Default)]
+struct Tracking {
+    link_warned: HashSet<String>,                   // GPU IDs
+    evidence: HashMap<String, JobHardwareEvidence>, // Running jobs, by job ID
+    throttle_warned: HashSet<String>,               // Job IDs
+}
+
+static TRACKING: Mutex<Option<Tracking>> = Mutex::new(None);
+
+// Why the link looks degraded, if it does.
+fn link_problem(gpu: &GpuInfo) -> Option<String> {
+    if let (Some(1), Some(max)) = (gpu.pcie_link_width, gpu.pcie_link_width_max) {
+        if max > 1 {
+            return Some(format!("is running on a x1 PCIe link (x{} supported)", max));
+        }
+    }
+    let loaded = gpu.utilization_gpu_percent.is_some_and(|u| u >= LOADED_FROM_PERCENT);
+    if let (Some(1), Some(max), true) = (gpu.pcie_link_generation, gpu.pcie_link_generation_max, loaded) {
+        if max > 1 {
+            return Some(format!("stays at PCIe Gen1 under load (Gen{} supported)", max));
+        }
+    }
+    None
+}
+
+fn sample(evidence: &mut JobHardwareEvidence, gpu: &GpuInfo, now: &str) {
+    let reasons = gpu.throttle_reasons.unwrap_or(0);
+    evidence.samples += 1;
+    if reasons & THERMAL_THROTTLE_MASK != 0 {
+        evidence.thermal_throttled_samples += 1;
+    }
+    if reasons & POWER_THROTTLE_MASK != 0 {
+        evidence.power_throttled_samples += 1;
+    }
+    if gpu.utilization_gpu_percent.is_some_and(|u| u >= LOADED_FROM_PERCENT) {
+        let lower = |current: Option<u32>, seen: Option<u32>| match (current, seen) {
+            (Some(c), Some(s)) => Some(c.min(s)),
+            (c, s) => c.or(s),
+        };
+        evidence.min_pcie_link_generation = lower(gpu.pcie_link_generation, evidence.min_pcie_link_generation);
+        evidence.min_pcie_link_width = lower(gpu.pcie_link_width, evidence.min_pcie_link_width);
+    }
+    evidence.max_temperature_c = evidence.max_temperature_c.max(gpu.temperature_c);
+    evidence.last_sampled_at = now.to_string();
+}
+
+fn warn(app_handle: &AppHandle, gpu: &GpuInfo, kind: &str, message: String, job_id: Option<String>) {
+    emit_log_entry(app_handle, "error", message.clone());
+    let warning = HardwareWarning {
+        gpu_id: gpu.id.clone(),
+        gpu_name: gpu.name.clone(),
+        kind: kind.to_string(),
+        message,
+        job_id,
+        detected_at: get_timestamp(),
+    };
+    plugins::notify(app_handle, "hardware_warning", &json!(warning), false);
+    if let Err(e) = app_handle.emit_all("hardware_warning", warning) {
+        eprintln!("Failed to emit hardware_warning event: {}", e);
+    }
+}
+
+// Called by the poller with the latest GPU listing and jobs.
+pub fn observe(app_handle: &AppHandle, gpus: &[GpuInfo], jobs: &[LocalJob]) {
+    let store = app_settings::current(app_handle).privacy.store_job_metadata;
+    let storage = app_handle.try_state::<Storage>();
+    let now = get_timestamp();
+    let mut warnings = Vec::new();
+    {
+        let mut tracking = TRACKING.lock().unwrap();
+        let tracking = tracking.get_or_insert_with(Tracking::default);
+
+        for gpu in gpus {
+            match link_problem(gpu) {
+                Some(problem) if tracking.link_warned.insert(gpu.id.clone()) => warnings.push((
+                    gpu.clone(),
+                    "pcie_link_degraded",
+                    format!("{} {}; check the riser and that the card is fully seated.", gpu.name, problem),
+                    None,
+                )),
+                Some(_) => {}
+                None => {
+                    tracking.link_warned.remove(&gpu.id);
+                }
+            }
+        }
+
+        let running: Vec<&LocalJob> = jobs.iter().filter(|j| j.status == "running").collect();
+        tracking.evidence.retain(|job_id, _| running.iter().any(|j| j.id == *job_id));
+        tracking.throttle_warned.retain(|job_id| running.iter().any(|j| j.id == *job_id));
+        let mut stored: Option<Vec<(String, JobHardwareEvidence)>> = None;
+        for job in running {
+            let Some(gpu) = job.gpu_id.as_deref().and_then(|id| gpus.iter().find(|g| g.id == id)) else { continue };
+            let evidence = tracking.evidence.entry(job.id.clone()).or_insert_with(|| {
+                // Carry on from what was recorded before a restart of the GUI.
+                let stored = stored.get_or_insert_with(|| storage.as_ref().and_then(|s| s.job_hardware().ok()).unwrap_or_default());
+                stored.iter().find(|(id, _)| *id == job.id).map(|(_, e)| e.clone()).unwrap_or_else(|| JobHardwareEvidence {
+                    samples: 0,
+                    thermal_throttled_samples: 0,
+                    power_throttled_samples: 0,
+                    min_pcie_link_generation: None,
+                    min_pcie_link_width: None,
+                    max_temperature_c: None,
+                    first_sampled_at: now.clone(),
+                    last_sampled_at: now.clone(),
+                })
+            });
+            sample(evidence, gpu, &now);
+            if let (true, Some(storage)) = (store, &storage) {
+                if let Err(e) = storage.upsert_job_hardware(&job.id, evidence) {
+                    eprintln!("{}", e);
+                }
+            }
+            let throttled_percent = evidence.thermal_throttled_samples * 100 / evidence.samples;
+            if evidence.samples >= THROTTLE_WARN_MIN_SAMPLES && throttled_percent >= THROTTLE_WARN_PERCENT && tracking.throttle_warned.insert(job.id.clone()) {
+                warnings.push((
+                    gpu.clone(),
+                    "thermal_throttling",
+                    format!(
+                        "{} has been thermally throttled for {}% of job {} so far; improve cooling or the renter may see reduced performance.",
+                        gpu.name, throttled_percent, job.name
+                    ),
+                    Some(job.id.clone()),
+                ));
+            }
+        }
+    }
+    for (gpu, kind, message, job_id) in warnings {
+        warn(app_handle, &gpu, kind, message, job_id);
+    }
+}
+
+// Attaches recorded hardware evidence to `jobs`.
+pub fn annotate(app_handle: &AppHandle, jobs: &mut [LocalJob]) {
+    let Some(storage) = app_handle.try_state::<Storage>() else { return };
+    let Ok(records) = storage.job_hardware() else { return };
+    for job in jobs.iter_mut() {
+        job.hardware = records.iter().find(|(id, _)| *id == job.id).map(|(_, e)| e.clone());
+    }
+}
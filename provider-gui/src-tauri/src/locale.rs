@@ -0,0 +1,236 @@
+// Locale-aware formatting of numbers, dates and amounts for exports and user-facing text.
+//
+// Only the parts of CLDR the GUI needs are kept here, for the locales providers actually use:
+// decimal and grouping separators, date order, and where the currency symbol goes. CSV exports
+// also take their field delimiter from the locale, since Excel splits on `;` wherever `,` is the
+// decimal separator. The locale is the OS one unless `AppSettings::locale` overrides it; tags
+// that aren't listed fall back to their language and then to `en-US`.
+
+use chrono::{Local, TimeZone};
+use serde::Serialize;
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::app_settings;
+use crate::money::{Decimal, RoundingPolicy};
+
+const FALLBACK_LOCALE: &str = "en-US";
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DateOrder {
+    Ymd,
+    Dmy,
+    Mdy,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct LocaleFormat {
+    pub tag: String,
+    pub decimal_separator: char,
+    pub grouping_separator: char,
+    // Digits per group above the first three: 2 in India ("12,34,567"), 3 elsewhere.
+    pub secondary_grouping: usize,
+    pub date_order: DateOrder,
+    pub date_separator: char,
+    pub currency_before: bool, // "$1.00" rather than "1,00 $"
+    pub csv_delimiter: char,
+}
+
+// (tag, decimal, grouping, date order, date separator, currency before)
+const LOCALES: &[(&str, char, char, DateOrder, char, bool)] = &[
+    ("en-US", '.', ',', DateOrder::Mdy, '/', true),
+    ("en-GB", '.', ',', DateOrder::Dmy, '/', true),
+    ("en-CA", '.', ',', DateOrder::Ymd, '-', true),
+    ("en-AU", '.', ',', DateOrder::Dmy, '/', true),
+    ("en-IN", '.', ',', DateOrder::Dmy, '/', true),
+    ("en", '.', ',', DateOrder::Mdy, '/', true),
+    ("de-CH", '.', '\u{2019}', DateOrder::Dmy, '.', true),
+    ("de", ',', '.', DateOrder::Dmy, '.', false),
+    ("fr-CH", ',', '\u{202f}', DateOrder::Dmy, '.', false),
+    ("fr", ',', '\u{202f}', DateOrder::Dmy, '/', false),
+    ("es", ',', '.', DateOrder::Dmy, '/', false),
+    ("it", ',', '.', DateOrder::Dmy, '/', false),
+    ("nl", ',', '.', DateOrder::Dmy, '-', true),
+    ("pt-BR", ',', '.', DateOrder::Dmy, '/', true),
+    ("pt", ',', '\u{a0}', DateOrder::Dmy, '/', false),
+    ("pl", ',', '\u{a0}', DateOrder::Dmy, '.', false),
+    ("cs", ',', '\u{a0}', DateOrder::Dmy, '.', false),
+    ("ru", ',', '\u{a0}', DateOrder::Dmy, '.', false),
+    ("uk", ',', '\u{a0}', DateOrder::Dmy, '.', false),
+    ("tr", ',', '.', DateOrder::Dmy, '.', true),
+    ("sv", ',', '\u{a0}', DateOrder::Ymd, '-', false),
+    ("fi", ',', '\u{a0}', DateOrder::Dmy, '.', false),
+    ("ja", '.', ',', DateOrder::Ymd, '/', true),
+    ("zh", '.', ',', DateOrder::Ymd, '/', true),
+    ("ko", '.', ',', DateOrder::Ymd, '.', true),
+];
+
+fn lookup(tag: &str) -> Option<LocaleFormat> {
+    // OS locales come as "de_DE.UTF-8" on Unix.
+    let tag = tag.split('.').next().unwrap_or(tag).replace('_', "-");
+    let language = tag.split('-').next().unwrap_or(&tag).to_string();
+    [tag.as_str(), language.as_str()].into_iter().find_map(|candidate| {
+        LOCALES.iter().find(|l| l.0.eq_ignore_ascii_case(candidate)).map(|&(matched, decimal, grouping, order, date_sep, before)| LocaleFormat {
+            tag: tag.clone(),
+            decimal_separator: decimal,
+            grouping_separator: grouping,
+            secondary_grouping: if matched == "en-IN" { 2 } else { 3 },
+            date_order: order,
+            date_separator: date_sep,
+            currency_before: before,
+            csv_delimiter: if decimal == ',' { ';' } else { ',' },
+        })
+    })
+}
+
+// Rejects override tags that wouldn't change anything, so a typo doesn't silently mean en-US.
+pub fn validate(tag: Option<&str>) -> Result<(), String> {
+    match tag {
+        Some(tag) if lookup(tag).is_none() => Err(format!("Unsupported locale '{}'.", tag)),
+        _ => Ok(()),
+    }
+}
+
+// The format for an explicit tag, e.g. the locale a file was written in.
+pub fn for_tag(tag: &str) -> Result<LocaleFormat, String> {
+    lookup(tag).ok_or_else(|| format!("Unsupported locale '{}'.", tag))
+}
+
+pub fn resolve<R: Runtime>(manager: &impl Manager<R>) -> LocaleFormat {
+    app_settings::current(manager)
+        .locale
+        .or_else(sys_locale::get_locale)
+        .and_then(|tag| lookup(&tag))
+        .unwrap_or_else(|| lookup(FALLBACK_LOCALE).unwrap())
+}
+
+impl LocaleFormat {
+    pub fn number(&self, value: f64, decimals: usize) -> String {
+        let formatted = format!("{:.*}", decimals, value.abs());
+        let (whole, fraction) = formatted.split_once('.').unwrap_or((formatted.as_str(), ""));
+        let mut grouped = String::new();
+        for (i, digit) in whole.chars().enumerate() {
+            let remaining = whole.len() - i;
+            if i > 0 && remaining >= 3 && (remaining - 3) % self.secondary_grouping == 0 {
+                grouped.push(self.grouping_separator);
+            }
+            grouped.push(digit);
+        }
+        let sign = if value < 0.0 && formatted.chars().any(|c| c.is_ascii_digit() && c != '0') { "-" } else { "" };
+        if fraction.is_empty() {
+            format!("{}{}", sign, grouped)
+        } else {
+            format!("{}{}{}{}", sign, grouped, self.decimal_separator, fraction)
+        }
+    }
+
+    // Numbers as they go into a CSV cell: no grouping, so spreadsheets read them as numbers.
+    pub fn plain_number(&self, value: f64, decimals: usize) -> String {
+        format!("{:.*}", decimals, value).replace('.', &self.decimal_separator.to_string())
+    }
+
+    // A DGPU amount for a CSV cell, rounded by the provider's rounding policy.
+    pub fn plain_amount(&self, amount: Decimal, rounding: &RoundingPolicy) -> String {
+        rounding.format(amount).replace('.', &self.decimal_separator.to_string())
+    }
+
+    pub fn currency(&self, amount: f64, code: &str) -> String {
+        let decimals = match code {
+            "JPY" | "KRW" => 0,
+            "DGPU" => 4,
+            _ => 2,
+        };
+        let symbol = match code {
+            "USD" => "$",
+            "EUR" => "€",
+            "GBP" => "£",
+            "JPY" => "¥",
+            _ => code,
+        };
+        let number = self.number(amount, decimals);
+        // Codes are always written after the amount, as DGPU is everywhere else in the GUI.
+        if self.currency_before && symbol != code {
+            match number.strip_prefix('-') {
+                Some(abs) => format!("-{}{}", symbol, abs),
+                None => format!("{}{}", symbol, number),
+            }
+        } else {
+            format!("{}\u{a0}{}", number, symbol)
+        }
+    }
+
+    // Unix seconds, in the machine's time zone.
+    pub fn date(&self, unix: i64) -> String {
+        let Some(t) = Local.timestamp_opt(unix, 0).single() else { return unix.to_string() };
+        let (d, m, y) = (t.format("%d"), t.format("%m"), t.format("%Y"));
+        let s = self.date_separator;
+        match self.date_order {
+            DateOrder::Ymd => format!("{}{}{}{}{}", y, s, m, s, d),
+            DateOrder::Dmy => format!("{}{}{}{}{}", d, s, m, s, y),
+            DateOrder::Mdy => format!("{}{}{}{}{}", m, s, d, s, y),
+        }
+    }
+
+    pub fn date_time(&self, unix: i64) -> String {
+        match Local.timestamp_opt(unix, 0).single() {
+            Some(t) => format!("{} {}", self.date(unix), t.format("%H:%M")),
+            None => unix.to_string(),
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn get_locale_format(app_handle: AppHandle) -> Result<LocaleFormat, String> {
+    Ok(resolve(&app_handle))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn groups_thousands() {
+        let us = for_tag("en-US").unwrap();
+        assert_eq!(us.number(1_234_567.891, 2), "1,234,567.89");
+        assert_eq!(us.number(999.0, 0), "999");
+        assert_eq!(us.number(-1_000.0, 0), "-1,000");
+    }
+
+    #[test]
+    fn groups_lakhs_and_crores_in_india() {
+        let india = for_tag("en-IN").unwrap();
+        assert_eq!(india.number(1_234_567.891, 2), "12,34,567.89");
+        assert_eq!(india.number(123_456_789.0, 0), "12,34,56,789");
+        assert_eq!(india.number(12_345.0, 0), "12,345");
+        assert_eq!(india.number(999.0, 0), "999");
+    }
+
+    #[test]
+    fn uses_the_locale_separators() {
+        let de = for_tag("de-DE").unwrap();
+        assert_eq!(de.number(1_234.5, 2), "1.234,50");
+        assert_eq!(de.plain_number(1_234.5, 2), "1234,50");
+        assert_eq!(de.csv_delimiter, ';');
+    }
+
+    #[test]
+    fn drops_the_sign_of_values_that_round_to_zero() {
+        assert_eq!(for_tag("en-US").unwrap().number(-0.001, 2), "0.00");
+    }
+
+    #[test]
+    fn places_currency_symbols() {
+        assert_eq!(for_tag("en-US").unwrap().currency(-12.5, "USD"), "-$12.50");
+        assert_eq!(for_tag("fr-FR").unwrap().currency(12.5, "EUR"), "12,50\u{a0}€");
+        assert_eq!(for_tag("en-US").unwrap().currency(1.0, "DGPU"), "1.0000\u{a0}DGPU");
+    }
+
+    #[test]
+    fn falls_back_to_the_language() {
+        let format = for_tag("de_AT.UTF-8").unwrap();
+        assert_eq!(format.tag, "de-AT");
+        assert_eq!(format.decimal_separator, ',');
+        assert!(validate(Some("xx-YY")).is_err());
+        assert!(validate(None).is_ok());
+    }
+}
@@ -0,0 +1,280 @@
+// Lockdown for a rig that may be compromised.
+//
+// Engaging lockdown, by hand or automatically when the anomaly detector flags possible
+// compromise (see `analytics::check_unexplained_load`), runs these steps in order and carries on
+// past any that fail:
+// 1. new rentals are paused in the daemon's scheduler;
+// 2. the daemon's job egress firewall blocks outbound traffic from job containers;
+// 3. logs, telemetry, jobs, GPU processes and the event ledger are snapshotted for forensics,
+//    unscrubbed, into a local archive that is never uploaded;
+// 4. the platform session token is revoked and removed from the keychain.
+// Containment comes before the snapshot so nothing else leaves the rig while it is taken, and
+// the token is revoked last because revoking needs it. Lockdown persists across restarts and is
+// re-applied whenever the daemon starts, until the user lifts it; the token has to be entered
+// again afterwards.
+
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::fs::{self, File};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+
+use crate::backup::append_file;
+use crate::polling::{self, PollingState};
+use crate::storage::{unix_now, Storage};
+use crate::{
+    a11y, accounts, announcements, api_client, app_settings, emit_log_entry, get_detected_gpus, get_timestamp, gpu_ids, gpu_processes, invoke_daemon_cli_json_output, plugins, secrets,
+    session, DaemonState,
+};
+
+const STATE_FILE_NAME: &str = "lockdown.json";
+const FORENSICS_DIR_NAME: &str = "forensics";
+const LEDGER_DAYS: i64 = 7;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct LockdownConfig {
+    // Engage lockdown without asking when the anomaly detector flags possible compromise.
+    // Off by default: lockdown interrupts earnings and signs the rig out.
+    pub automatic: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LockdownTrigger {
+    Manual,
+    Automatic,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LockdownStep {
+    pub name: String,
+    pub ok: bool,
+    pub detail: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct LockdownStatus {
+    pub active: bool,
+    pub engaged_at: Option<String>,
+    pub trigger: Option<LockdownTrigger>,
+    pub reason: Option<String>,
+    pub steps: Vec<LockdownStep>,
+    pub snapshot_path: Option<String>,
+}
+
+pub struct LockdownState {
+    status: Mutex<LockdownStatus>,
+    path: Option<PathBuf>,
+}
+
+impl LockdownState {
+    pub fn load(app_handle: &AppHandle) -> Self {
+        let path = app_handle.path_resolver().app_data_dir().map(|dir| dir.join(STATE_FILE_NAME));
+        let status = path
+            .as_ref()
+            .and_then(|p| fs::read_to_string(p).ok())
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        LockdownState { status: Mutex::new(status), path }
+    }
+
+    fn set(&self, status: LockdownStatus) -> Result<(), String> {
+        let path = self.path.as_ref().ok_or_else(|| "App data directory is unavailable.".to_string())?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        }
+        let json = serde_json::to_string_pretty(&status).map_err(|e| e.to_string())?;
+        fs::write(path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        *self.status.lock().unwrap() = status;
+        Ok(())
+    }
+}
+
+pub fn is_active(app_handle: &AppHandle) -> bool {
+    app_handle.try_state::<LockdownState>().is_some_and(|state| state.status.lock().unwrap().active)
+}
+
+async fn set_rentals_blocked(app_handle: &AppHandle, blocked: bool, reason: &str) -> Result<(), String> {
+    let payload = if blocked { json!({ "blocked": true, "reason": reason }) } else { json!({ "blocked": false }) }.to_string();
+    invoke_daemon_cli_json_output::<Value>(app_handle, &["--set-rentals-blocked-json", &payload]).await.map(|_| ())
+}
+
+async fn set_job_egress_blocked(app_handle: &AppHandle, blocked: bool) -> Result<(), String> {
+    let payload = json!({ "block_job_egress": blocked }).to_string();
+    invoke_daemon_cli_json_output::<Value>(app_handle, &["--set-egress-policy-json", &payload]).await.map(|_| ())
+}
+
+fn to_json<T: Serialize>(value: &T) -> Result<Vec<u8>, String> {
+    serde_json::to_vec_pretty(value).map_err(|e| format!("Failed to serialize forensic data: {}", e))
+}
+
+// Everything that could show what happened, as it was; unlike diagnostic bundles, nothing is
+// scrubbed, since the archive stays on this machine.
+async fn snapshot(app_handle: &AppHandle, status: &LockdownStatus) -> Result<String, String> {
+    let dir = app_handle.path_resolver().app_data_dir().ok_or_else(|| "App data directory is unavailable.".to_string())?.join(FORENSICS_DIR_NAME);
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    let path = dir.join(format!("lockdown-{}.tar.gz", unix_now()));
+
+    let logs = to_json(&*app_handle.state::<DaemonState>().log_buffer.lock().unwrap())?;
+    let mut entries = vec![
+        ("lockdown.json", to_json(status)?),
+        ("logs.json", logs),
+        ("telemetry.json", to_json(&polling::get_telemetry_history(app_handle.state::<PollingState>()).await?)?),
+    ];
+    // The daemon may be part of the problem; what it can't report is noted rather than fatal.
+    match gpu_ids::local_jobs(app_handle).await {
+        Ok(jobs) => entries.push(("jobs.json", to_json(&jobs)?)),
+        Err(e) => entries.push(("jobs.error.txt", e.into_bytes())),
+    }
+    if let Ok(gpus) = get_detected_gpus(app_handle.clone()).await {
+        let mut reports = Vec::new();
+        for gpu in &gpus {
+            match gpu_processes::inspect(app_handle, &gpu.id).await {
+                Ok(report) => reports.push(json!(report)),
+                Err(e) => reports.push(json!({ "gpu_id": gpu.id, "error": e })),
+            }
+        }
+        entries.push(("gpu_processes.json", to_json(&reports)?));
+    }
+    if let Some(storage) = app_handle.try_state::<Storage>() {
+        match storage.ledger(unix_now() - LEDGER_DAYS * 24 * 60 * 60) {
+            Ok(ledger) => entries.push(("event_ledger.json", to_json(&ledger)?)),
+            Err(e) => entries.push(("event_ledger.error.txt", e.into_bytes())),
+        }
+    }
+    if let Some(trace) = session::current_trace(app_handle) {
+        entries.push(("session_trace.json", to_json(&trace)?));
+    }
+
+    let file = File::create(&path).map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+    let mut builder = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+    for (name, bytes) in &entries {
+        append_file(&mut builder, name, bytes)?;
+    }
+    builder
+        .into_inner()
+        .and_then(|gz| gz.finish())
+        .map_err(|e| format!("Failed to finish forensic snapshot: {}", e))?;
+    Ok(path.display().to_string())
+}
+
+async fn revoke_token(app_handle: &AppHandle) -> Result<String, String> {
+    let key = accounts::secret_key(app_handle, api_client::TOKEN_KEY);
+    if secrets::get(&key)?.is_none() {
+        return Ok("No session token was stored.".to_string());
+    }
+    // The local copy goes regardless; a token that couldn't be revoked must be rotated on the platform.
+    let revoked = api_client::send_json::<_, Value>(app_handle, reqwest::Method::POST, "/api/v1/auth/tokens/current/revoke", &json!({})).await;
+    secrets::delete(&key)?;
+    match revoked {
+        Ok(_) => Ok("Session token revoked and removed from the keychain.".to_string()),
+        Err(e) => Err(format!("Token removed from the keychain, but the platform could not revoke it ({}); revoke it from the dashboard.", e)),
+    }
+}
+
+fn step(name: &str, result: Result<String, String>) -> LockdownStep {
+    match result {
+        Ok(detail) => LockdownStep { name: name.to_string(), ok: true, detail },
+        Err(detail) => LockdownStep { name: name.to_string(), ok: false, detail },
+    }
+}
+
+pub async fn engage(app_handle: &AppHandle, reason: String, trigger: LockdownTrigger) -> Result<LockdownStatus, String> {
+    let state = app_handle.state::<LockdownState>();
+    if state.status.lock().unwrap().active {
+        return Err("Lockdown is already engaged.".to_string());
+    }
+    emit_log_entry(app_handle, "error", format!("Engaging lockdown ({:?}): {}", trigger, reason));
+    let mut status = LockdownStatus { active: true, engaged_at: Some(get_timestamp()), trigger: Some(trigger), reason: Some(reason.clone()), ..Default::default() };
+    // Persisted first so a crash mid-way still comes back locked down.
+    state.set(status.clone())?;
+
+    let blocked = set_rentals_blocked(app_handle, true, &format!("Lockdown: {}", reason)).await;
+    status.steps.push(step("pause_rentals", blocked.map(|_| "New rentals are paused.".to_string())));
+    let egress = set_job_egress_blocked(app_handle, true).await;
+    status.steps.push(step("block_job_egress", egress.map(|_| "Outbound traffic from job containers is blocked.".to_string())));
+    let snapshot_result = snapshot(app_handle, &status).await;
+    status.snapshot_path = snapshot_result.as_ref().ok().cloned();
+    status.steps.push(step("forensic_snapshot", snapshot_result.map(|path| format!("Snapshot written to {}.", path))));
+    status.steps.push(step("revoke_token", revoke_token(app_handle).await));
+
+    for failed in status.steps.iter().filter(|s| !s.ok) {
+        emit_log_entry(app_handle, "error", format!("Lockdown step {} failed: {}", failed.name, failed.detail));
+    }
+    state.set(status.clone())?;
+    a11y::announce(app_handle, "alert", a11y::Severity::Critical, "Lockdown engaged: rentals are paused and you have been signed out.".to_string());
+    let payload = json!(status);
+    plugins::notify(app_handle, "lockdown_engaged", &payload, true);
+    if let Err(e) = app_handle.emit_all("lockdown_engaged", payload) {
+        eprintln!("Failed to emit lockdown_engaged event: {}", e);
+    }
+    Ok(status)
+}
+
+// Called by the anomaly detector; only engages when the user has allowed it.
+pub fn engage_automatically(app_handle: &AppHandle, reason: String) {
+    if !app_settings::current(app_handle).lockdown.automatic || is_active(app_handle) {
+        return;
+    }
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = engage(&app_handle, reason, LockdownTrigger::Automatic).await {
+            emit_log_entry(&app_handle, "error", format!("Automatic lockdown failed: {}", e));
+        }
+    });
+}
+
+// The daemon forgets the block and the egress policy when it restarts.
+pub fn sync_on_start(app_handle: &AppHandle) {
+    let status = app_handle.state::<LockdownState>().status.lock().unwrap().clone();
+    if !status.active {
+        return;
+    }
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        let reason = format!("Lockdown: {}", status.reason.unwrap_or_default());
+        if let Err(e) = set_rentals_blocked(&app_handle, true, &reason).await {
+            emit_log_entry(&app_handle, "error", format!("Failed to re-apply the lockdown rental pause: {}", e));
+        }
+        if let Err(e) = set_job_egress_blocked(&app_handle, true).await {
+            emit_log_entry(&app_handle, "error", format!("Failed to re-apply the lockdown egress block: {}", e));
+        }
+    });
+}
+
+#[tauri::command]
+pub async fn get_lockdown_status(state: State<'_, LockdownState>) -> Result<LockdownStatus, String> {
+    Ok(state.status.lock().unwrap().clone())
+}
+
+#[tauri::command]
+pub async fn engage_lockdown(app_handle: AppHandle, reason: Option<String>) -> Result<LockdownStatus, String> {
+    let reason = reason.filter(|r| !r.trim().is_empty()).unwrap_or_else(|| "Engaged by the user".to_string());
+    engage(&app_handle, reason, LockdownTrigger::Manual).await
+}
+
+#[tauri::command]
+pub async fn lift_lockdown(app_handle: AppHandle, state: State<'_, LockdownState>) -> Result<LockdownStatus, String> {
+    if !state.status.lock().unwrap().active {
+        return Err("Lockdown is not engaged.".to_string());
+    }
+    // A daemon that isn't running has already forgotten the block, so this isn't fatal.
+    if let Err(e) = set_job_egress_blocked(&app_handle, false).await {
+        emit_log_entry(&app_handle, "error", format!("Failed to lift the egress block: {}", e));
+    }
+    let mut status = state.status.lock().unwrap().clone();
+    status.active = false;
+    state.set(status.clone())?;
+    // Rentals resume unless a maintenance announcement is still holding them.
+    announcements::sync_on_start(&app_handle);
+    emit_log_entry(&app_handle, "status", "Lockdown lifted. Sign in to the platform again to resume renting.".to_string());
+    a11y::announce(&app_handle, "alert", a11y::Severity::Info, "Lockdown lifted. Sign in again to resume renting.".to_string());
+    if let Err(e) = app_handle.emit_all("lockdown_lifted", &status) {
+        eprintln!("Failed to emit lockdown_lifted event: {}", e);
+    }
+    Ok(status)
+}
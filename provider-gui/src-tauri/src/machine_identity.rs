@@ -0,0 +1,186 @@
+// Hardware-backed machine identity.
+//
+// Each rig gets one ECDSA P-256 keypair generated inside the TPM (Windows, Linux) or the Secure
+// Enclave (macOS), so the private key never exists in memory or on disk and can't be copied to
+// another machine. The daemon owns the key: it is the process that signs registration and payout
+// requests, and Secure Enclave keys are only usable by the process that created them. This
+// module asks the daemon to provision the key, registers the public key with the platform
+// together with the hardware's attestation of it where the backend provides one, and turns on
+// request signing. Rigs without either backend keep working unsigned; the platform decides what
+// that allows.
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+
+use crate::{accounts, api_client, emit_log_entry, get_timestamp, invoke_daemon_cli_json_output};
+
+const REGISTRATIONS_FILE_NAME: &str = "machine_identity.json";
+// Platform requests the daemon signs with the machine key.
+const SIGNED_REQUESTS: &[&str] = &["registration", "payout"];
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum IdentityBackend {
+    Tpm,
+    SecureEnclave,
+}
+
+// Evidence from the hardware that the key was generated in it and can't be exported, e.g. a TPM
+// certification by an attestation key chained to the manufacturer's EK certificate.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct KeyAttestation {
+    pub format: String,
+    pub statement: String, // Base64
+    #[serde(default)]
+    pub certificate_chain: Vec<String>, // PEM, leaf first
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MachineIdentity {
+    pub key_id: String,
+    pub algorithm: String,
+    pub public_key_pem: String,
+    pub backend: IdentityBackend,
+    pub created_at: String,
+    #[serde(default)]
+    pub attestation: Option<KeyAttestation>,
+}
+
+#[derive(Deserialize, Debug)]
+struct DaemonIdentityReport {
+    identity: Option<MachineIdentity>,
+    #[serde(default)]
+    available_backends: Vec<IdentityBackend>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct Registration {
+    key_id: String,
+    registered_at: String,
+    attestation_verified: bool,
+}
+
+#[derive(Deserialize, Debug)]
+struct RegistrationResponse {
+    #[serde(default)]
+    attestation_verified: bool,
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct MachineIdentityStatus {
+    pub identity: Option<MachineIdentity>,
+    pub available_backends: Vec<IdentityBackend>,
+    pub registered_at: Option<String>,
+    // None until the platform has seen the attestation, or when the backend produced none.
+    pub attestation_verified: Option<bool>,
+    pub signed_requests: Vec<String>,
+    pub error: Option<String>,
+}
+
+pub struct MachineIdentityState {
+    status: Mutex<MachineIdentityStatus>,
+    // Per platform account, since each account registers the key separately.
+    registrations: Mutex<HashMap<String, Registration>>,
+    path: Option<PathBuf>,
+}
+
+impl MachineIdentityState {
+    pub fn load(app_handle: &AppHandle) -> Self {
+        let path = app_handle.path_resolver().app_data_dir().map(|dir| dir.join(REGISTRATIONS_FILE_NAME));
+        let registrations = path
+            .as_ref()
+            .and_then(|p| fs::read_to_string(p).ok())
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        MachineIdentityState { status: Mutex::new(MachineIdentityStatus::default()), registrations: Mutex::new(registrations), path }
+    }
+
+    fn save_registration(&self, account: String, registration: Registration) -> Result<(), String> {
+        let mut registrations = self.registrations.lock().unwrap();
+        registrations.insert(account, registration);
+        let path = self.path.as_ref().ok_or_else(|| "App data directory is unavailable.".to_string())?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        }
+        let json = serde_json::to_string_pretty(&*registrations).map_err(|e| e.to_string())?;
+        fs::write(path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+}
+
+fn account_key(app_handle: &AppHandle) -> String {
+    accounts::active_account_id(app_handle).unwrap_or_else(|| "default".to_string())
+}
+
+async fn ensure_key(app_handle: &AppHandle, status: &mut MachineIdentityStatus) -> Result<MachineIdentity, String> {
+    let report = invoke_daemon_cli_json_output::<DaemonIdentityReport>(app_handle, &["--get-machine-identity-json"]).await?;
+    status.available_backends = report.available_backends.clone();
+    if let Some(identity) = report.identity {
+        return Ok(identity);
+    }
+    // The Secure Enclave only exists on Macs, so the order only matters if a daemon reports both.
+    let backend = [IdentityBackend::SecureEnclave, IdentityBackend::Tpm]
+        .into_iter()
+        .find(|b| report.available_backends.contains(b))
+        .ok_or_else(|| "No TPM or Secure Enclave is available; platform requests from this rig are not hardware-signed.".to_string())?;
+    let payload = json!({ "backend": backend }).to_string();
+    let identity = invoke_daemon_cli_json_output::<MachineIdentity>(app_handle, &["--create-machine-identity-json", &payload]).await?;
+    emit_log_entry(app_handle, "status", format!("Created machine identity {} in the {:?}.", identity.key_id, identity.backend));
+    Ok(identity)
+}
+
+async fn register(app_handle: &AppHandle, identity: &MachineIdentity) -> Result<Registration, String> {
+    let state = app_handle.state::<MachineIdentityState>();
+    let account = account_key(app_handle);
+    if let Some(existing) = state.registrations.lock().unwrap().get(&account).filter(|r| r.key_id == identity.key_id) {
+        return Ok(existing.clone());
+    }
+    let response: RegistrationResponse = api_client::send_json(app_handle, reqwest::Method::PUT, "/api/v1/providers/me/machine-identity", identity).await?;
+    let registration = Registration { key_id: identity.key_id.clone(), registered_at: get_timestamp(), attestation_verified: response.attestation_verified };
+    state.save_registration(account, registration.clone())?;
+    Ok(registration)
+}
+
+// Provisions and registers the key if needed, then turns on request signing.
+pub async fn provision(app_handle: &AppHandle) -> MachineIdentityStatus {
+    let mut status = MachineIdentityStatus::default();
+    let result = async {
+        let identity = ensure_key(app_handle, &mut status).await?;
+        status.identity = Some(identity.clone());
+        let registration = register(app_handle, &identity).await?;
+        status.registered_at = Some(registration.registered_at);
+        status.attestation_verified = identity.attestation.as_ref().map(|_| registration.attestation_verified);
+        let payload = json!({ "key_id": identity.key_id, "requests": SIGNED_REQUESTS }).to_string();
+        invoke_daemon_cli_json_output::<serde_json::Value>(app_handle, &["--set-request-signing-json", &payload]).await?;
+        status.signed_requests = SIGNED_REQUESTS.iter().map(|r| r.to_string()).collect();
+        Ok::<_, String>(())
+    }
+    .await;
+    if let Err(e) = result {
+        emit_log_entry(app_handle, "error", format!("Machine identity not in use: {}", e));
+        status.error = Some(e);
+    }
+    *app_handle.state::<MachineIdentityState>().status.lock().unwrap() = status.clone();
+    status
+}
+
+pub fn sync_on_start(app_handle: &AppHandle) {
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        provision(&app_handle).await;
+    });
+}
+
+#[tauri::command]
+pub async fn get_machine_identity(state: State<'_, MachineIdentityState>) -> Result<MachineIdentityStatus, String> {
+    Ok(state.status.lock().unwrap().clone())
+}
+
+#[tauri::command]
+pub async fn provision_machine_identity(app_handle: AppHandle) -> Result<MachineIdentityStatus, String> {
+    Ok(provision(&app_handle).await)
+}
@@ -1,7 +1,7 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 use serde::{Serialize, Deserialize};
-use tauri::{Manager, State, SystemTrayEvent, Window, AppHandle};
+use tauri::{CustomMenuItem, Manager, State, SystemTray, SystemTrayEvent, SystemTrayMenu, Window, AppHandle};
 use tauri::api::process::{Command as TauriCommand, CommandEvent, ExitStatus as TauriExitStatus, Child as TauriChild};
 use std::sync::Mutex;
 use std::process::{Command, Stdio, Child};
@@ -9,50 +9,115 @@ use std::io::{BufReader, BufRead, Write};
 use std::thread;
 use std::time::{SystemTime, UNIX_EPOCH, Duration};
 use std::sync::Arc;
+use std::collections::{HashMap, VecDeque};
+
+use dante_provider_core::models::{GpuInfo, LocalJob, ProviderSettings, RentalMode};
+use dante_provider_core::money::{self, Decimal};
+use dante_provider_core::units::{self, DgpuPerHour};
+
+mod a11y;
+mod accounts;
+mod actions;
+mod analytics;
+mod announcements;
+mod api_client;
+mod app_settings;
+mod approvals;
+mod attestation;
+mod backup;
+#[cfg(debug_assertions)]
+mod bindings;
+mod capabilities;
+mod checkpoints;
+mod clock;
+mod connectivity;
+mod crash_reports;
+mod crypto;
+mod daemon_ipc;
+mod daemon_resources;
+mod daemon_restart;
+mod data_cap;
+mod demo;
+mod devstack;
+mod diagnostics;
+mod emergency;
+mod event_feed;
+mod experiments;
+mod export;
+mod failover;
+mod fault_injection;
+mod feature_flags;
+mod gpu_health;
+mod gpu_ids;
+mod gpu_processes;
+mod graphql;
+mod hooks;
+mod http_client;
+mod image_cache;
+mod image_registry;
+mod intel_gpu;
+mod inventory;
+mod job_notes;
+mod journal;
+mod job_policy;
+mod job_queue;
+mod known_issues;
+mod link_health;
+mod locale;
+mod lockdown;
+mod machine_identity;
+mod market;
+mod middleware;
+mod nvidia_smi;
+mod org;
+mod platform_import;
+mod plugins;
+mod redaction;
+mod schedule;
+mod secrets;
+mod session;
+mod settings_history;
+mod shortcuts;
+mod shutdown;
+mod telemetry_export;
+mod terminal;
+mod transport;
+mod troubleshoot;
+mod wal;
+mod watchdog;
+mod wizard;
+mod wsl;
+mod zombie_jobs;
+mod polling;
+mod prefetch;
+mod pricing;
+mod public_listing;
+mod reconcile;
+mod regions;
+mod rejections;
+mod renter;
+mod reputation;
+mod storage;
+mod subscriptions;
+mod sync;
+mod tasks;
+
+const LOG_BUFFER_CAPACITY: usize = 2000;
+const LIGHTWEIGHT_LOG_BUFFER_CAPACITY: usize = 200;
 
 #[derive(Clone, Serialize)]
 struct LogEntry {
     id: usize,
     message: String,
     timestamp: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    corrected_timestamp: Option<String>, // Platform time, once clock skew has been measured
     log_type: String, // 'status', 'stdout', 'stderr', 'error'
+    #[serde(skip_serializing_if = "Option::is_none")]
+    classification: Option<known_issues::ErrorClassification>, // Set for 'stderr'/'error' entries
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
-struct GpuInfo {
-    id: String,
-    name: String,
-    model: String,
-    vram_total_mb: u32,
-    vram_free_mb: u32,
-    utilization_gpu_percent: Option<u32>,
-    temperature_c: Option<u32>,
-    power_draw_w: Option<u32>,
-    is_available_for_rent: bool,
-    current_hourly_rate_dgpu: Option<f32>,
-}
-
-#[derive(Serialize, Deserialize, Debug, Clone)]
-struct ProviderSettings {
-    default_hourly_rate_dgpu: f32,
-    preferred_currency: String,
-    min_job_duration_minutes: u32,
-    max_concurrent_jobs: u32,
-}
-
-#[derive(Serialize, Deserialize, Debug, Clone)]
-struct LocalJob {
-    id: String,
-    name: String,
-    status: String, // 'running' | 'completed' | 'failed' | 'queued'
-    progress_percent: f32,
-    submitted_at: String,
-    started_at: Option<String>,
-    completed_at: Option<String>,
-    estimated_cost_dgpu: Option<f32>,
-}
-
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, specta::Type)]
 struct NetworkStatus {
     connection_type: String, // "Ethernet", "WiFi", "Disconnected"
     ip_address: Option<String>,
@@ -61,11 +126,17 @@ struct NetworkStatus {
     latency_ms: u32,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, specta::Type)]
 struct FinancialSummary {
-    current_balance_dgpu: f32,
-    total_earned_dgpu: f32,
-    pending_payout_dgpu: f32,
+    #[serde(with = "money::amount")]
+    #[specta(type = f64)]
+    current_balance_dgpu: Decimal,
+    #[serde(with = "money::amount")]
+    #[specta(type = f64)]
+    total_earned_dgpu: Decimal,
+    #[serde(with = "money::amount")]
+    #[specta(type = f64)]
+    pending_payout_dgpu: Decimal,
     last_payout_at: Option<String>,
 }
 
@@ -73,6 +144,7 @@ struct DaemonState {
     process: Mutex<Option<TauriChild>>,
     log_id_counter: Mutex<usize>,
     status: Mutex<String>, // "offline", "starting", "online", "stopping", "error"
+    log_buffer: Mutex<VecDeque<LogEntry>>, // Recent entries so the frontend can backfill after a reload
 }
 
 impl DaemonState {
@@ -81,6 +153,7 @@ impl DaemonState {
             process: Mutex::new(None),
             log_id_counter: Mutex::new(0),
             status: Mutex::new("offline".to_string()),
+            log_buffer: Mutex::new(VecDeque::new()),
         }
     }
 }
@@ -97,19 +170,41 @@ fn emit_log_entry<R: tauri::Runtime>(manager: &impl Manager<R>, log_type: &str,
         *counter += 1;
         *counter
     };
+    let classification = match log_type {
+        "stderr" | "error" => known_issues::classify(manager, &message),
+        _ => None,
+    };
+    // Before the entry is buffered, emitted or bundled, so no copy keeps the secret.
+    let message = redaction::redact(manager, message);
     let log_payload = LogEntry {
         id: current_id,
         message,
         timestamp: get_timestamp(),
+        corrected_timestamp: clock::corrected_timestamp(),
         log_type: log_type.to_string(),
+        classification,
     };
 
-    if let Err(e) = manager.emit_all("daemon_log", log_payload) {
+    let capacity = if app_settings::current(manager).lightweight_mode {
+        LIGHTWEIGHT_LOG_BUFFER_CAPACITY
+    } else {
+        LOG_BUFFER_CAPACITY
+    };
+    {
+        let mut buffer = manager.try_state::<DaemonState>().unwrap().log_buffer.lock().unwrap();
+        buffer.push_back(log_payload.clone());
+        while buffer.len() > capacity {
+            buffer.pop_front();
+        }
+    }
+
+    if let Err(e) = subscriptions::emit(manager, "daemon_log", log_payload) {
         eprintln!("Failed to emit log event: {}", e); // Fallback log to stderr
     }
 }
 
 #[tauri::command]
+#[specta::specta]
 async fn start_daemon(app_handle: AppHandle, state: State<'_, DaemonState>) -> Result<String, String> {
     let mut status_lock = state.status.lock().unwrap();
     if *status_lock == "online" || *status_lock == "starting" {
@@ -120,9 +215,19 @@ async fn start_daemon(app_handle: AppHandle, state: State<'_, DaemonState>) -> R
     *status_lock = "starting".to_string();
     emit_log_entry(&app_handle, "status", "Attempting to start provider daemon...".to_string());
 
+    if demo::enabled() {
+        *status_lock = "online".to_string();
+        drop(status_lock);
+        session::record_status(&app_handle, "online");
+        a11y::daemon_status(&app_handle, "online");
+        demo::start_timeline(&app_handle);
+        emit_log_entry(&app_handle, "status", "Demo daemon started; jobs and earnings are simulated.".to_string());
+        return Ok("Demo daemon started.".to_string());
+    }
+
     let sidecar_name = "provider-daemon"; // This must match an entry in tauri.conf.json sidecar list or externalBin
 
-    let (mut event_rx, child) = TauriCommand::new_sidecar(sidecar_name)
+    let (mut event_rx, child) = wsl::daemon_command(&app_handle, sidecar_name)
         .map_err(|e| {
             let err_msg = format!("Failed to create sidecar command '{}'. Ensure it's in tauri.conf.json under externalBin and/or as a sidecar. Error: {}", sidecar_name, e);
             emit_log_entry(&app_handle, "error", err_msg.clone());
@@ -130,6 +235,7 @@ async fn start_daemon(app_handle: AppHandle, state: State<'_, DaemonState>) -> R
             err_msg
         })?
         // .args(&["--daemon-mode"]) // Add any arguments your daemon needs to start in its operational mode
+        .envs(daemon_ipc::prepare(&app_handle))
         .spawn()
         .map_err(|e| {
             let err_msg = format!("Failed to spawn sidecar '{}': {}", sidecar_name, e);
@@ -141,8 +247,19 @@ async fn start_daemon(app_handle: AppHandle, state: State<'_, DaemonState>) -> R
     let mut process_lock = state.process.lock().unwrap();
     *process_lock = Some(child);
     *status_lock = "online".to_string(); // Set to online once spawn is successful
+    session::record_status(&app_handle, "online");
+    a11y::daemon_status(&app_handle, "online");
 
     emit_log_entry(&app_handle, "status", format!("Daemon process {} started successfully.", sidecar_name));
+    job_policy::sync_on_start(&app_handle);
+    regions::sync_on_start(&app_handle);
+    transport::sync_on_start(&app_handle);
+    image_registry::sync_on_start(&app_handle);
+    announcements::sync_on_start(&app_handle);
+    machine_identity::sync_on_start(&app_handle);
+    lockdown::sync_on_start(&app_handle);
+    redaction::learn_platform_token(&app_handle);
+    hooks::fire(&app_handle, hooks::HookEvent::DaemonStarted, serde_json::json!({ "sidecar": sidecar_name }));
     
     let app_handle_clone = app_handle.clone();
     let status_mutex_clone = state.status.clone(); 
@@ -160,6 +277,7 @@ async fn start_daemon(app_handle: AppHandle, state: State<'_, DaemonState>) -> R
                     emit_log_entry(&app_handle_clone, "error", format!("Daemon execution error: {}", message));
                     let mut status_guard = status_mutex_clone.lock().unwrap();
                     *status_guard = "error".to_string();
+                    a11y::daemon_status(&app_handle_clone, "error");
                 }
                 CommandEvent::Terminated(payload) => {
                     let exit_code_str = payload.code.map_or_else(|| "killed by signal".to_string(), |c| c.to_string());
@@ -171,6 +289,7 @@ async fn start_daemon(app_handle: AppHandle, state: State<'_, DaemonState>) -> R
                     
                     // Always set to offline first, then refine to error if needed
                     *status_guard = "offline".to_string(); 
+                    daemon_ipc::reset();
 
                     if let Some(daemon_state_gaurd) = app_handle_clone.try_state::<DaemonState>() {
                         let mut process_guard = daemon_state_gaurd.process.lock().unwrap();
@@ -191,6 +310,8 @@ async fn start_daemon(app_handle: AppHandle, state: State<'_, DaemonState>) -> R
                          // If it was stopping, and terminated, it's now offline.
                          emit_log_entry(&app_handle_clone, "status", "Daemon stopped as expected.".to_string());
                     }
+                    session::record_status(&app_handle_clone, &status_guard);
+                    a11y::daemon_status(&app_handle_clone, &status_guard);
                     break; // Exit the event loop once terminated
                 }
                 CommandEvent::Completed(_payload) => { 
@@ -208,6 +329,7 @@ async fn start_daemon(app_handle: AppHandle, state: State<'_, DaemonState>) -> R
         if *status_guard == "online" || *status_guard == "starting" { 
             *status_guard = "offline".to_string();
             emit_log_entry(&app_handle_clone, "error", "Daemon event stream ended unexpectedly. Marking as offline.".to_string());
+            a11y::daemon_status(&app_handle_clone, "offline");
         }
     });
 
@@ -215,6 +337,7 @@ async fn start_daemon(app_handle: AppHandle, state: State<'_, DaemonState>) -> R
 }
 
 #[tauri::command]
+#[specta::specta]
 async fn stop_daemon(app_handle: AppHandle, state: State<'_, DaemonState>) -> Result<String, String> {
     let mut status_lock = state.status.lock().unwrap();
     if *status_lock == "offline" || *status_lock == "stopping" {
@@ -227,11 +350,14 @@ async fn stop_daemon(app_handle: AppHandle, state: State<'_, DaemonState>) -> Re
     if let Some(child_to_kill) = process_option_lock.as_ref() { // Borrow to call kill
         emit_log_entry(&app_handle, "status", "Attempting to stop daemon...".to_string());
         *status_lock = "stopping".to_string(); // Set status before attempting to kill
+        session::record_status(&app_handle, "stopping");
+        a11y::daemon_status(&app_handle, "stopping");
         drop(status_lock); // Release status_lock before process_option_lock is potentially held longer
 
         match child_to_kill.kill() {
             Ok(_) => {
                 emit_log_entry(&app_handle, "status", "Daemon kill signal sent.".to_string());
+                wsl::after_stop(&app_handle);
                 // The CommandEvent::Terminated handler will update the status to "offline"
                 // and clear the process from DaemonState.
                 Ok("Daemon stop signal sent successfully. Waiting for termination event.".to_string())
@@ -259,10 +385,26 @@ async fn get_daemon_status(state: State<'_, DaemonState>) -> Result<String, Stri
     Ok(state.status.lock().unwrap().clone())
 }
 
+#[tauri::command]
+async fn get_recent_logs(state: State<'_, DaemonState>) -> Result<Vec<LogEntry>, String> {
+    Ok(state.log_buffer.lock().unwrap().iter().cloned().collect())
+}
+
 // Helper function to call daemon CLI and parse JSON output
 async fn invoke_daemon_cli_json_output<T: for<'de> serde::Deserialize<'de>>(
     app_handle: &tauri::AppHandle,
     command_args: &[&str],
+) -> Result<T, String> {
+    invoke_daemon_cli_with_env(app_handle, command_args, HashMap::new()).await
+}
+
+// Like `invoke_daemon_cli_json_output`, but hands `env` to the daemon in its environment, which
+// is where secrets go: arguments are visible to other local users and end up in the log. The
+// control connection doesn't carry environment variables, so these calls always use the CLI.
+async fn invoke_daemon_cli_with_env<T: for<'de> serde::Deserialize<'de>>(
+    app_handle: &tauri::AppHandle,
+    command_args: &[&str],
+    mut env: HashMap<String, String>,
 ) -> Result<T, String> {
     let sidecar_name = "provider-daemon"; // Matches externalBin if that's the alias for providerd
 
@@ -273,19 +415,50 @@ async fn invoke_daemon_cli_json_output<T: for<'de> serde::Deserialize<'de>>(
     
     emit_log_entry(app_handle, "status", format!("Invoking daemon: {} with args {:?}", sidecar_name, command_args));
 
-    match tauri::api::process::Command::new_sidecar(sidecar_name)
+    let started = std::time::Instant::now();
+    fault_injection::delay_daemon_call(app_handle, command_args).await;
+
+    if demo::enabled() {
+        let value = demo::respond(app_handle, command_args)?;
+        let text = fault_injection::daemon_output(app_handle, value.to_string());
+        return serde_json::from_str(&text).map_err(|e| format!("Demo response for {:?} is invalid: {}", command_args, e));
+    }
+
+    let ipc = if env.is_empty() { daemon_ipc::invoke(app_handle, command_args).await } else { None };
+    if let Some(output) = ipc {
+        let result = output.and_then(|text| {
+            let text = fault_injection::daemon_output(app_handle, text);
+            emit_log_entry(app_handle, "stdout", format!("Daemon response for {:?}: {}", command_args, text));
+            serde_json::from_str(&text).map_err(|e| format!("Failed to parse JSON from daemon for {:?}: {}. Output: '{}'", command_args, e, text))
+        });
+        if let Err(err_msg) = &result {
+            emit_log_entry(app_handle, "error", err_msg.clone());
+            crash_reports::record_command_failure(app_handle, &format!("{:?}", command_args), err_msg);
+        }
+        session::record_daemon_call(app_handle, command_args, started.elapsed(), result.as_ref().map(|_| ()));
+        return result;
+    }
+
+    if !env.is_empty() && wsl::daemon_in_wsl(app_handle) {
+        // WSL only passes on the variables listed here.
+        let names: Vec<String> = env.keys().cloned().collect();
+        env.insert("WSLENV".to_string(), names.join(":"));
+    }
+    let result = match wsl::daemon_command(app_handle, sidecar_name)
         .map_err(|e| format!("Sidecar command '{}' not found or misconfigured. Did you add it to tauri.conf.json externalBin/sidecar? Error: {}", sidecar_name, e))?
-        .args(command_args)
+        .args(wsl::translate_args(app_handle, command_args))
+        .envs(env)
         .output()
     {
         Ok(output) => {
             if output.status.success() {
-                let stdout_str = &output.stdout;
+                let stdout_str = &fault_injection::daemon_output(app_handle, output.stdout);
                 emit_log_entry(app_handle, "stdout", format!("Daemon response for {:?}: {}", command_args, stdout_str));
                 serde_json::from_str(stdout_str)
                     .map_err(|e| {
                         let err_msg = format!("Failed to parse JSON from daemon for {:?}: {}. Output: '{}'", command_args, e, stdout_str);
                         emit_log_entry(app_handle, "error", err_msg.clone());
+                        crash_reports::record_command_failure(app_handle, &format!("{:?}", command_args), &err_msg);
                         err_msg
                     })
             } else {
@@ -296,30 +469,36 @@ async fn invoke_daemon_cli_json_output<T: for<'de> serde::Deserialize<'de>>(
                     command_args, output.status, stderr_str, stdout_str
                 );
                 emit_log_entry(app_handle, "error", err_msg.clone());
+                crash_reports::record_command_failure(app_handle, &format!("{:?}", command_args), &err_msg);
                 Err(err_msg)
             }
         }
         Err(e) => {
             let err_msg = format!("Failed to execute daemon command {:?}: {}", command_args, e);
             emit_log_entry(app_handle, "error", err_msg.clone());
+            crash_reports::record_command_failure(app_handle, &format!("{:?}", command_args), &err_msg);
             Err(err_msg)
         }
-    }
+    };
+    session::record_daemon_call(app_handle, command_args, started.elapsed(), result.as_ref().map(|_| ()));
+    result
 }
 
 // --- New Mock Data Commands ---
 
 #[tauri::command]
+#[specta::specta]
 async fn get_detected_gpus(app_handle: tauri::AppHandle) -> Result<Vec<GpuInfo>, String> {
     // Real implementation: Call provider-daemon CLI
     // The provider-daemon (Go app) needs to implement a command like:
     // providerd --get-gpus-json
     // This command should print a JSON array of GpuInfo objects to stdout.
     emit_log_entry(&app_handle, "status", "Attempting to fetch GPUs from daemon...".to_string());
-    invoke_daemon_cli_json_output::<Vec<GpuInfo>>(&app_handle, &["--get-gpus-json"]).await
+    nvidia_smi::detect_gpus(&app_handle).await
 }
 
 #[tauri::command]
+#[specta::specta]
 async fn get_provider_settings(app_handle: tauri::AppHandle) -> Result<ProviderSettings, String> {
     // Real implementation: Call provider-daemon CLI
     // The provider-daemon (Go app) needs to implement a command like:
@@ -330,6 +509,7 @@ async fn get_provider_settings(app_handle: tauri::AppHandle) -> Result<ProviderS
 }
 
 #[tauri::command]
+#[specta::specta]
 async fn update_provider_settings(app_handle: tauri::AppHandle, settings: ProviderSettings) -> Result<ProviderSettings, String> {
     // Real implementation: Call provider-daemon CLI
     // The provider-daemon (Go app) needs to implement a command like:
@@ -339,37 +519,103 @@ async fn update_provider_settings(app_handle: tauri::AppHandle, settings: Provid
     let settings_json = serde_json::to_string(&settings)
         .map_err(|e| format!("Failed to serialize settings to JSON: {}", e))?;
     
-    invoke_daemon_cli_json_output::<ProviderSettings>(&app_handle, &["--update-settings-json", &settings_json]).await
+    let updated = invoke_daemon_cli_json_output::<ProviderSettings>(&app_handle, &["--update-settings-json", &settings_json]).await?;
+    sync::note_local_change(&app_handle);
+    settings_history::note_change(&app_handle);
+    Ok(updated)
 }
 
 #[tauri::command]
-async fn set_gpu_rental_config(app_handle: tauri::AppHandle, gpu_id: String, hourly_rate: f32, available: bool) -> Result<GpuInfo, String> {
+#[specta::specta]
+async fn set_gpu_rental_config(
+    app_handle: tauri::AppHandle,
+    gpu_id: String,
+    hourly_rate: DgpuPerHour,
+    available: bool,
+    confirmation: Option<String>,
+    mode: Option<RentalMode>, // None keeps the current mode
+) -> Result<GpuInfo, String> {
     // Real implementation: Call provider-daemon CLI
     // The provider-daemon (Go app) needs to implement a command like:
     // providerd --set-gpu-config-json --gpu-id <gpu_id> --rate <hourly_rate> --available <true|false>
     // This command should update the GPU config and print the updated GpuInfo JSON to stdout.
     emit_log_entry(&app_handle, "status", format!("Attempting to set GPU rental config via daemon: GPU ID {}, Rate {}, Available {}", gpu_id, hourly_rate, available));
+
+    let gpus = get_detected_gpus(app_handle.clone()).await?;
+    let gpu = gpus.iter().find(|g| g.id == gpu_id);
+    if gpu.is_some_and(|g| g.fallback_sourced) {
+        return Err("The daemon can't list GPUs right now, so rental settings can't be changed.".to_string());
+    }
+    // Any change that leaves the GPU listed below its floor needs confirming, including listing
+    // it at a below-floor price that was set while it was unlisted.
+    if let (Some(gpu), true) = (gpu, available) {
+        market::enforce_price_floor(&app_handle, &gpu.model, hourly_rate, confirmation.as_deref())?;
+    }
     
-    invoke_daemon_cli_json_output::<GpuInfo>(&app_handle, &[
-        "--set-gpu-config-json",
-        "--gpu-id", &gpu_id,
-        "--rate", &hourly_rate.to_string(),
-        "--available", &available.to_string(),
-    ]).await
+    let rate = hourly_rate.as_decimal().to_string();
+    let available_arg = available.to_string();
+    let daemon_gpu_id = gpu_ids::daemon_id(&app_handle, &gpu_id).await;
+    let mut args = vec!["--set-gpu-config-json", "--gpu-id", daemon_gpu_id.as_str(), "--rate", rate.as_str(), "--available", available_arg.as_str()];
+    if mode == Some(RentalMode::Spot) {
+        feature_flags::require(&app_handle, feature_flags::SPOT_MODE, "Spot listing")?;
+    }
+    if let Some(mode) = mode {
+        args.extend(["--mode", mode.as_str()]);
+    }
+    let mut gpu = invoke_daemon_cli_json_output::<GpuInfo>(&app_handle, &args).await?;
+    gpu_ids::apply_one(&mut gpu);
+    settings_history::note_change(&app_handle);
+    Ok(gpu)
+}
+
+// Takes a spot-listed GPU back from its current renter after `grace_minutes`, so the renter can
+// checkpoint. Reserved rentals are guaranteed and can't be reclaimed. What counts is the mode the
+// running rental was taken under; relisting the GPU as spot doesn't make it reclaimable.
+#[tauri::command]
+#[specta::specta]
+async fn reclaim_gpu(app_handle: tauri::AppHandle, gpu_id: String, grace_minutes: u32) -> Result<serde_json::Value, String> {
+    feature_flags::require(&app_handle, feature_flags::SPOT_MODE, "Spot reclaiming")?;
+    let gpus = get_detected_gpus(app_handle.clone()).await?;
+    let gpu = gpus.iter().find(|g| g.id == gpu_id).ok_or_else(|| format!("No GPU with ID '{}'.", gpu_id))?;
+    let jobs = get_local_jobs(app_handle.clone()).await?;
+    let job = jobs
+        .iter()
+        .find(|j| j.status == "running" && j.gpu_id.as_deref() == Some(gpu_id.as_str()))
+        .ok_or_else(|| format!("{} isn't rented; there's nothing to reclaim.", gpu.name))?;
+    match job.rental_mode {
+        Some(RentalMode::Spot) => {}
+        Some(RentalMode::Reserved) => return Err(format!("{} is rented as reserved; reserved rentals can't be reclaimed.", gpu.name)),
+        None => return Err(format!("The daemon doesn't report how {} was rented, so it can't be reclaimed safely.", gpu.name)),
+    }
+    let result = invoke_daemon_cli_json_output::<serde_json::Value>(&app_handle, &[
+        "--reclaim-gpu",
+        "--gpu-id", &gpu_ids::daemon_id(&app_handle, &gpu_id).await,
+        "--grace-minutes", &grace_minutes.to_string(),
+    ]).await?;
+    emit_log_entry(&app_handle, "status", format!("Reclaiming {} in {} minutes.", gpu.name, grace_minutes));
+    if let Err(e) = app_handle.emit_all("gpu_reclaim_scheduled", serde_json::json!({ "gpu_id": gpu_id, "grace_minutes": grace_minutes })) {
+        eprintln!("Failed to emit gpu_reclaim_scheduled event: {}", e);
+    }
+    Ok(result)
 }
 
 
 #[tauri::command]
+#[specta::specta]
 async fn get_local_jobs(app_handle: tauri::AppHandle) -> Result<Vec<LocalJob>, String> {
     // Real implementation: Call provider-daemon CLI
     // The provider-daemon (Go app) needs to implement a command like:
     // providerd --get-local-jobs-json
     // This command should print a JSON array of LocalJob objects to stdout.
     emit_log_entry(&app_handle, "status", "Attempting to fetch local jobs from daemon...".to_string());
-    invoke_daemon_cli_json_output::<Vec<LocalJob>>(&app_handle, &["--get-local-jobs-json"]).await
+    let mut jobs = gpu_ids::local_jobs(&app_handle).await?;
+    attestation::annotate(&app_handle, &mut jobs);
+    link_health::annotate(&app_handle, &mut jobs);
+    Ok(jobs)
 }
 
 #[tauri::command]
+#[specta::specta]
 async fn get_network_status(app_handle: tauri::AppHandle) -> Result<NetworkStatus, String> {
     // Real implementation: Call provider-daemon CLI or use Rust libraries
     // The provider-daemon (Go app) could implement a command like:
@@ -380,7 +626,34 @@ async fn get_network_status(app_handle: tauri::AppHandle) -> Result<NetworkStatu
     invoke_daemon_cli_json_output::<NetworkStatus>(&app_handle, &["--get-network-status-json"]).await
 }
 
+async fn measure_network(app_handle: &AppHandle, task: &tasks::TaskContext) -> Result<NetworkStatus, String> {
+    task.progress(None, "Measuring latency and throughput")?;
+    invoke_daemon_cli_json_output::<NetworkStatus>(app_handle, &["--get-network-status-json"]).await
+}
+
+async fn benchmark_gpu(app_handle: &AppHandle, task: &tasks::TaskContext, gpu_id: &str) -> Result<serde_json::Value, String> {
+    task.progress(None, format!("Benchmarking {}", gpu_id))?;
+    let report = invoke_daemon_cli_json_output::<serde_json::Value>(app_handle, &["--run-benchmark-json", "--gpu-id", &gpu_ids::daemon_id(app_handle, gpu_id).await]).await?;
+    emit_log_entry(app_handle, "status", format!("Benchmark of {} finished.", gpu_id));
+    Ok(report)
+}
+
+// Same measurement as `get_network_status`, as a background task whose result is the NetworkStatus.
+#[tauri::command]
+#[specta::specta]
+async fn run_network_test(app_handle: tauri::AppHandle) -> Result<String, String> {
+    Ok(tasks::submit(&app_handle, tasks::TaskRequest::NetworkTest))
+}
+
+// Runs the daemon's GPU benchmark as a background task; the daemon's report is the result.
 #[tauri::command]
+#[specta::specta]
+async fn run_benchmark(app_handle: tauri::AppHandle, gpu_id: String) -> Result<String, String> {
+    Ok(tasks::submit(&app_handle, tasks::TaskRequest::Benchmark { gpu_id }))
+}
+
+#[tauri::command]
+#[specta::specta]
 async fn get_financial_summary(app_handle: tauri::AppHandle) -> Result<FinancialSummary, String> {
     // Real implementation: Call provider-daemon CLI
     // The provider-daemon (Go app) would use its billing client to get this info, then expose via:
@@ -392,24 +665,278 @@ async fn get_financial_summary(app_handle: tauri::AppHandle) -> Result<Financial
 
 
 fn main() {
+    #[cfg(debug_assertions)]
+    bindings::export();
+
     let daemon_state = DaemonState::new();
 
     tauri::Builder::default()
         .manage(daemon_state)
-        .invoke_handler(tauri::generate_handler![
+        .manage(polling::PollingState::new())
+        .manage(terminal::TerminalState::new())
+        .manage(demo::DemoState::new())
+        .manage(clock::ClockState::new())
+        .manage(job_queue::JobQueueState::new())
+        .manage(approvals::ApprovalState::new())
+        .manage(gpu_health::GpuHealthState::new())
+        .manage(api_client::ApiClientState::new())
+        .manage(session::SessionState::new())
+        .manage(middleware::MiddlewareState::new())
+        .manage(watchdog::WatchdogState::new())
+        .invoke_system(middleware::INVOKE_INITIALIZATION_SCRIPT.to_string(), middleware::responder)
+        .invoke_handler(middleware::handler(tauri::generate_handler![
             start_daemon, 
             stop_daemon,
             get_daemon_status,
+            get_recent_logs,
             get_detected_gpus,
             get_provider_settings,
             update_provider_settings,
             set_gpu_rental_config,
+            reclaim_gpu,
             get_local_jobs,
             get_network_status,
-            get_financial_summary
-        ])
+            run_network_test,
+            run_benchmark,
+            get_financial_summary,
+            app_settings::get_app_settings,
+            app_settings::update_app_settings,
+            polling::get_telemetry_history,
+            actions::list_actions,
+            actions::invoke_action,
+            hooks::set_hook_enabled,
+            hooks::test_hook,
+            plugins::list_plugins,
+            plugins::enable_plugin,
+            plugins::get_plugin_price_suggestions,
+            terminal::open_terminal_session,
+            terminal::write_terminal,
+            terminal::resize_terminal,
+            terminal::close_terminal_session,
+            troubleshoot::run_troubleshooter,
+            troubleshoot::apply_fix,
+            known_issues::get_known_issues,
+            known_issues::refresh_known_issues,
+            crash_reports::get_pending_reports,
+            crash_reports::delete_report,
+            crash_reports::upload_pending_reports,
+            storage::purge_local_data,
+            storage::run_db_maintenance_now,
+            storage::get_storage_status,
+            storage::get_event_ledger,
+            backup::create_backup,
+            backup::restore_backup,
+            sync::get_sync_status,
+            sync::configure_sync,
+            sync::sync_settings_now,
+            sync::resolve_sync_conflict,
+            wizard::get_wizard_state,
+            wizard::complete_step,
+            wizard::reset_wizard,
+            wal::get_unacked_events,
+            wal::ack_events,
+            clock::get_clock_skew,
+            connectivity::get_connectivity_incidents,
+            reconcile::get_last_reconciliation,
+            settings_history::diff_settings,
+            rejections::get_rejection_stats,
+            analytics::get_utilization_heatmap,
+            journal::add_note,
+            journal::get_notes,
+            gpu_ids::rename_gpu,
+            capabilities::get_capability_manifest,
+            capabilities::update_capability_manifest,
+            capabilities::preview_listing,
+            public_listing::get_public_listing_preview,
+            inventory::set_gpu_metadata,
+            inventory::get_gpu_inventory,
+            pricing::get_break_even_rates,
+            troubleshoot::run_preflight_checklist,
+            session::start_session_recording,
+            session::stop_session_recording,
+            session::get_session_trace,
+            session::export_session_trace,
+            diagnostics::create_diagnostic_bundle,
+            gpu_processes::get_gpu_processes,
+            job_queue::get_job_queue,
+            job_policy::get_job_policy,
+            job_policy::set_job_policy,
+            market::validate_gpu_price,
+            market::refresh_price_floors,
+            analytics::check_earnings_anomaly,
+            market::get_market_position,
+            api_client::set_platform_token,
+            reputation::get_my_reputation,
+            reputation::get_recent_reviews,
+            accounts::list_accounts,
+            accounts::add_account,
+            accounts::remove_account,
+            accounts::switch_account,
+            org::get_org_memberships,
+            org::get_active_org,
+            org::set_active_org,
+            org::get_bound_org,
+            org::unbind_rig,
+            org::get_org_rigs,
+            org::set_org_gpu_rental_config,
+            approvals::register_push_device,
+            approvals::get_pending_rental_requests,
+            approvals::decide_rental_request,
+            pricing::estimate_job_cost,
+            pricing::create_discount,
+            pricing::list_discounts,
+            pricing::deactivate_discount,
+            analytics::get_discount_usage,
+            failover::get_failover_incidents,
+            failover::respond_to_failover,
+            checkpoints::clean_up_checkpoints_now,
+            data_cap::get_data_cap_status,
+            regions::measure_region_latency,
+            regions::get_region_latency,
+            regions::set_region_preference,
+            regions::get_latency_history,
+            transport::get_transport_report,
+            transport::set_transport_preferences,
+            http_client::set_proxy_password,
+            http_client::test_proxy,
+            http_client::test_platform_tls,
+            api_client::get_api_metrics,
+            market::get_regional_rates,
+            analytics::get_demand_heatmap,
+            analytics::simulate_pricing,
+            analytics::get_demand_forecast,
+            event_feed::get_event_feed_status,
+            announcements::get_platform_announcements,
+            announcements::mark_announcement_read,
+            feature_flags::get_feature_flags,
+            feature_flags::set_feature_flag_override,
+            experiments::start_pricing_experiment,
+            experiments::stop_pricing_experiment,
+            experiments::get_pricing_experiments,
+            gpu_health::get_gpu_health_trend,
+            intel_gpu::get_display_only_gpus,
+            wsl::get_wsl_status,
+            middleware::get_command_metrics,
+            middleware::request_confirmation,
+            shutdown::quit_app,
+            watchdog::get_subsystem_status,
+            zombie_jobs::get_zombie_jobs,
+            zombie_jobs::force_cleanup_job,
+            platform_import::import_earnings_csv,
+            platform_import::get_imported_earnings,
+            platform_import::suggest_settings_from_config,
+            renter::search_available_gpus,
+            renter::compare_listing_to_market,
+            attestation::get_job_attestation,
+            attestation::retry_attestation,
+            machine_identity::get_machine_identity,
+            machine_identity::provision_machine_identity,
+            lockdown::get_lockdown_status,
+            lockdown::engage_lockdown,
+            lockdown::lift_lockdown,
+            redaction::get_redaction_stats,
+            schedule::get_schedule_status,
+            daemon_restart::get_restart_schedule_status,
+            daemon_resources::get_daemon_resources,
+            daemon_ipc::get_daemon_ipc_status,
+            locale::get_locale_format,
+            export::export_ledger_csv,
+            export::export_imported_earnings_csv,
+            export::export_hourly_usage_csv,
+            export::export_inventory_csv,
+            telemetry_export::export_telemetry,
+            shortcuts::get_shortcut_status,
+            emergency::emergency_stop,
+            emergency::get_emergency_stops,
+            job_notes::set_job_note,
+            job_notes::get_job_notes,
+            subscriptions::subscribe,
+            subscriptions::unsubscribe,
+            fault_injection::inject_fault,
+            fault_injection::clear_faults,
+            fault_injection::get_armed_faults,
+            tasks::cancel_task,
+            tasks::list_tasks,
+            prefetch::prefetch_images,
+            image_cache::list_cached_images,
+            image_cache::evict_image,
+            image_registry::set_registry_config,
+            devstack::start_stack,
+            devstack::stop_stack,
+            devstack::get_stack_status,
+            devstack::get_service_graph
+        ]))
         .setup(|app| {
+            app.manage(app_settings::AppSettingsState::load(&app.handle()));
+            let proxy_handle = app.handle();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = http_client::apply_settings(&proxy_handle).await {
+                    emit_log_entry(&proxy_handle, "error", format!("Network settings not applied: {}", e));
+                }
+            });
+            app.manage(known_issues::KnownIssuesState::load(&app.handle()));
+            app.manage(market::MarketState::load(&app.handle()));
+            app.manage(sync::SyncState::load(&app.handle()));
+            app.manage(accounts::AccountsState::load(&app.handle()));
+            app.manage(reputation::ReputationState::load(&app.handle()));
+            app.manage(org::OrgState::load(&app.handle()));
+            app.manage(announcements::AnnouncementState::load(&app.handle()));
+            app.manage(feature_flags::FeatureFlagState::load(&app.handle()));
+            app.manage(data_cap::DataCapState::load(&app.handle()));
+            app.manage(failover::FailoverState::load(&app.handle()));
+            app.manage(wizard::WizardState::load(&app.handle()));
+            app.manage(wal::WalState::load(&app.handle()));
+            app.manage(machine_identity::MachineIdentityState::load(&app.handle()));
+            app.manage(lockdown::LockdownState::load(&app.handle()));
+            app.manage(redaction::RedactionState::new());
+            app.manage(shortcuts::ShortcutState::new());
+            app.manage(subscriptions::SubscriptionRegistry::new());
+            app.manage(tasks::TaskRegistry::new());
+            wal::spawn_reconciliation(app.handle());
+            crash_reports::install_panic_hook(app.handle());
+            match storage::Storage::open(&app.handle()) {
+                Ok(storage) => {
+                    app.manage(storage);
+                    tasks::restore(&app.handle());
+                    storage::spawn_retention_task(app.handle());
+                    storage::spawn_maintenance_task(app.handle());
+                }
+                // The GUI still works without history; features that need it report unavailability.
+                Err(e) => emit_log_entry(app, "error", format!("Local storage unavailable: {}", e)),
+            }
+            if app_settings::current(app).crash_reporting_enabled {
+                let handle = app.handle();
+                tauri::async_runtime::spawn(async move {
+                    let _ = crash_reports::upload_pending_reports(handle).await;
+                });
+            }
             emit_log_entry(app, "status", "Provider GUI initialized. Daemon is OFFLINE.".to_string());
+            polling::spawn_poller(app.handle());
+            sync::spawn_sync_task(app.handle());
+            clock::spawn_skew_monitor(app.handle());
+            connectivity::spawn_connectivity_monitor(app.handle());
+            settings_history::spawn_snapshot_task(app.handle());
+            analytics::spawn_anomaly_monitor(app.handle());
+            reputation::spawn_review_watcher(app.handle());
+            approvals::spawn_approval_relay(app.handle());
+            pricing::spawn_discount_expiry(app.handle());
+            checkpoints::spawn_cleanup_task(app.handle());
+            prefetch::spawn_prefetch_scheduler(app.handle());
+            image_cache::spawn_eviction_task(app.handle());
+            regions::spawn_latency_monitor(app.handle());
+            data_cap::spawn_data_cap_monitor(app.handle());
+            event_feed::spawn_event_feed(app.handle());
+            announcements::spawn_block_monitor(app.handle());
+            schedule::spawn_schedule_task(app.handle());
+            daemon_restart::spawn_restart_scheduler(app.handle());
+            daemon_resources::spawn_resource_monitor(app.handle());
+            feature_flags::spawn_refresher(app.handle());
+            experiments::spawn_experiment_runner(app.handle());
+            pricing::spawn_surge_monitor(app.handle());
+            gpu_health::spawn_health_monitor(app.handle());
+            attestation::spawn_attestation_task(app.handle());
+            watchdog::spawn_watchdog(app.handle());
+            shortcuts::apply(&app.handle());
             
              // Example system tray (optional, customize as needed)
             let tray_handle = app.tray_handle();
@@ -420,15 +947,22 @@ fn main() {
 
             Ok(())
         })
+        .on_window_event(|event| {
+            polling::handle_window_event(event.window(), event.event());
+            subscriptions::handle_window_event(event.window(), event.event());
+        })
+        .system_tray(SystemTray::new().with_menu(SystemTrayMenu::new().add_item(CustomMenuItem::new(emergency::TRAY_MENU_ID, "Emergency stop…"))))
         .on_system_tray_event(|app, event| match event {
             SystemTrayEvent::LeftClick { .. } => {
                 let window = app.get_window("main").unwrap();
                 window.show().unwrap();
                 window.set_focus().unwrap();
             }
+            SystemTrayEvent::MenuItemClick { id, .. } if id == emergency::TRAY_MENU_ID => emergency::tray_clicked(app),
             // Add other tray events if needed (e.g., quit, open dashboard)
             _ => {}
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app_handle, event| shutdown::handle_run_event(app_handle, event));
 } 
\ No newline at end of file
@@ -0,0 +1,327 @@
+// Marketplace reference data: per-model price floors and medians.
+//
+// A bundled table ships with the app and is replaced by the platform's current table when it
+// can be fetched. Listing prices are checked against it so a typo can't list an H100 at
+// 0.0001 DGPU/hr: outliers produce a warning, and prices below the floor require the user to
+// type a confirmation phrase.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::RwLock;
+use tauri::{AppHandle, Manager, State};
+
+use crate::money::{self, Decimal};
+use crate::reputation::{self, ReputationState};
+use crate::units::DgpuPerHour;
+use crate::{analytics, api_client, graphql, emit_log_entry, get_detected_gpus, gpu_ids, inventory, invoke_daemon_cli_json_output, pricing};
+
+const BUNDLED_PRICE_FLOORS: &str = include_str!("../resources/price_floors.json");
+const DOWNLOADED_FILE_NAME: &str = "price_floors.json";
+// Prices this far from the median are flagged even when above the floor, in percent.
+const OUTLIER_LOW_PERCENT: i64 = 50;
+const OUTLIER_HIGH_PERCENT: i64 = 300;
+
+// Floor applied to models missing from the table.
+fn default_floor() -> DgpuPerHour {
+    DgpuPerHour::new(Decimal::new(1, 2))
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PriceFloor {
+    pub model: String, // Matched case-insensitively as a substring of the GPU model
+    pub floor_dgpu_per_hour: DgpuPerHour,
+    pub median_dgpu_per_hour: Option<DgpuPerHour>,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PriceCheckSeverity {
+    Ok,
+    Outlier,
+    BelowFloor,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct PriceCheck {
+    pub gpu_model: String,
+    pub rate_dgpu_per_hour: DgpuPerHour,
+    pub floor_dgpu_per_hour: DgpuPerHour,
+    pub median_dgpu_per_hour: Option<DgpuPerHour>,
+    pub severity: PriceCheckSeverity,
+    pub message: Option<String>,
+    // Phrase the user must type to list below the floor.
+    pub required_confirmation: Option<String>,
+}
+
+pub struct MarketState {
+    floors: RwLock<Vec<PriceFloor>>,
+    downloaded_path: Option<PathBuf>,
+}
+
+fn parse(contents: &str) -> Result<Vec<PriceFloor>, String> {
+    serde_json::from_str(contents).map_err(|e| format!("Invalid price floor table: {}", e))
+}
+
+impl MarketState {
+    pub fn load(app_handle: &AppHandle) -> Self {
+        let downloaded_path = app_handle.path_resolver().app_data_dir().map(|dir| dir.join(DOWNLOADED_FILE_NAME));
+        let floors = downloaded_path
+            .as_ref()
+            .and_then(|p| fs::read_to_string(p).ok())
+            .and_then(|contents| parse(&contents).ok())
+            .unwrap_or_else(|| parse(BUNDLED_PRICE_FLOORS).expect("bundled price_floors.json is valid"));
+        MarketState { floors: RwLock::new(floors), downloaded_path }
+    }
+
+    // The most specific (longest) matching entry wins, so "RTX 4090" beats "RTX 40".
+    pub fn floor_for(&self, gpu_model: &str) -> Option<PriceFloor> {
+        let model = gpu_model.to_lowercase();
+        self.floors
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|f| model.contains(&f.model.to_lowercase()))
+            .max_by_key(|f| f.model.len())
+            .cloned()
+    }
+
+    pub fn check_price(&self, gpu_model: &str, rate: DgpuPerHour) -> PriceCheck {
+        let floor = self.floor_for(gpu_model);
+        let floor_rate = floor.as_ref().map_or_else(default_floor, |f| f.floor_dgpu_per_hour);
+        let median = floor.as_ref().and_then(|f| f.median_dgpu_per_hour);
+        let outlier = |m: &DgpuPerHour| {
+            rate < *m * Decimal::new(OUTLIER_LOW_PERCENT, 2) || rate > *m * Decimal::new(OUTLIER_HIGH_PERCENT, 2)
+        };
+
+        let (severity, message) = if rate < floor_rate {
+            (
+                PriceCheckSeverity::BelowFloor,
+                Some(format!("{} is below the {:.2} DGPU/hr floor for {}.", rate, floor_rate.as_decimal(), gpu_model)),
+            )
+        } else if let Some(median) = median.filter(outlier) {
+            (
+                PriceCheckSeverity::Outlier,
+                Some(format!("{} is far from the {:.2} DGPU/hr market median for {}.", rate, median.as_decimal(), gpu_model)),
+            )
+        } else {
+            (PriceCheckSeverity::Ok, None)
+        };
+        PriceCheck {
+            gpu_model: gpu_model.to_string(),
+            rate_dgpu_per_hour: rate,
+            floor_dgpu_per_hour: floor_rate,
+            median_dgpu_per_hour: median,
+            required_confirmation: (severity == PriceCheckSeverity::BelowFloor).then(|| format!("LIST AT {}", rate.as_decimal().normalize())),
+            severity,
+            message,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Copy)]
+pub struct Percentiles {
+    pub p25: f32,
+    pub p50: f32,
+    pub p75: f32,
+}
+
+impl Percentiles {
+    // Rough percentile of `value` within the distribution, interpolating between quartiles.
+    fn position(&self, value: f32) -> f32 {
+        let points = [(self.p25, 0.25), (self.p50, 0.5), (self.p75, 0.75)];
+        if value <= self.p25 {
+            return (0.25 * value / self.p25.max(f32::EPSILON)).clamp(0.0, 0.25);
+        }
+        for pair in points.windows(2) {
+            let ((lo, lo_pct), (hi, hi_pct)) = (pair[0], pair[1]);
+            if value <= hi {
+                let span = (hi - lo).max(f32::EPSILON);
+                return lo_pct + (value - lo) / span * (hi_pct - lo_pct);
+            }
+        }
+        (0.75 + 0.25 * (value - self.p75) / self.p75.max(f32::EPSILON)).min(1.0)
+    }
+}
+
+// Anonymized aggregates over all active listings of one GPU model.
+#[derive(Deserialize, Debug, Clone)]
+pub struct MarketAggregates {
+    pub listings: u32,
+    pub rate_dgpu_per_hour: Percentiles,
+    pub benchmark_score: Option<Percentiles>,
+    pub uptime_percent: Option<Percentiles>,
+    pub reputation: Option<Percentiles>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct PositionFactor {
+    pub name: String,
+    pub value: Option<f32>,
+    pub market_median: Option<f32>,
+    pub percentile: Option<f32>, // 0..1, higher is more competitive
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct MarketPosition {
+    pub gpu_id: String,
+    pub gpu_model: String,
+    pub listings: u32,
+    pub rank_estimate: Option<u32>, // 1 is the most competitive listing
+    pub factors: Vec<PositionFactor>,
+    pub suggestions: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct Score {
+    score: f32,
+}
+
+async fn fetch_aggregates(app_handle: &AppHandle, model: &str) -> Result<MarketAggregates, String> {
+    let path = format!("/api/v1/market/aggregates?model={}", api_client::encode_component(model));
+    api_client::get_public_json(app_handle, &path).await
+}
+
+// Weights for the composite score; dimensions we have no data for are left out.
+const PRICE_WEIGHT: f32 = 0.4;
+const UPTIME_WEIGHT: f32 = 0.25;
+const BENCHMARK_WEIGHT: f32 = 0.2;
+const REPUTATION_WEIGHT: f32 = 0.15;
+
+#[tauri::command]
+pub async fn get_market_position(app_handle: AppHandle, gpu_id: String) -> Result<MarketPosition, String> {
+    let gpus = get_detected_gpus(app_handle.clone()).await?;
+    let gpu = gpus.into_iter().find(|g| g.id == gpu_id).ok_or_else(|| format!("No GPU with ID '{}'.", gpu_id))?;
+    let aggregates = fetch_aggregates(&app_handle, &gpu.model).await?;
+
+    let benchmark = invoke_daemon_cli_json_output::<Score>(&app_handle, &["--get-benchmark-json", "--gpu-id", &gpu_ids::daemon_id(&app_handle, &gpu_id).await]).await.ok().map(|s| s.score);
+    let reputation = match reputation::refresh_reputation(&app_handle).await {
+        Ok(r) => Some(r.score),
+        Err(_) => app_handle.state::<ReputationState>().cached_score(),
+    };
+    let uptime = analytics::uptime_percent(&app_handle, &gpu_id);
+    // Positions are statistics over the market, so the rate is compared as a float here.
+    let rate = gpu.current_hourly_rate_dgpu.map(|r| r.as_f64() as f32);
+
+    // For price, cheaper is more competitive, so the percentile is inverted.
+    let dimensions = [
+        ("price", rate, Some(aggregates.rate_dgpu_per_hour), PRICE_WEIGHT, true),
+        ("uptime", uptime, aggregates.uptime_percent, UPTIME_WEIGHT, false),
+        ("benchmark", benchmark, aggregates.benchmark_score, BENCHMARK_WEIGHT, false),
+        ("reputation", reputation, aggregates.reputation, REPUTATION_WEIGHT, false),
+    ];
+    let mut factors = Vec::new();
+    let (mut weighted, mut total_weight) = (0.0, 0.0);
+    for (name, value, dist, weight, lower_is_better) in dimensions {
+        let percentile = match (value, dist) {
+            (Some(v), Some(d)) => Some(if lower_is_better { 1.0 - d.position(v) } else { d.position(v) }),
+            _ => None,
+        };
+        if let Some(p) = percentile {
+            weighted += p * weight;
+            total_weight += weight;
+        }
+        factors.push(PositionFactor { name: name.to_string(), value, market_median: dist.map(|d| d.p50), percentile });
+    }
+    let rank_estimate = (total_weight > 0.0 && aggregates.listings > 0)
+        .then(|| ((1.0 - weighted / total_weight) * aggregates.listings as f32).round() as u32 + 1)
+        .map(|rank| rank.min(aggregates.listings));
+
+    let mut suggestions = Vec::new();
+    let market_rate = aggregates.rate_dgpu_per_hour;
+    match rate {
+        Some(r) if r > market_rate.p75 => suggestions.push(format!(
+            "Your rate is in the most expensive quarter of {} listings; pricing near the {:.2} DGPU/hr median should win more jobs.",
+            gpu.model, market_rate.p50
+        )),
+        Some(r) if r < market_rate.p25 => suggestions.push(format!(
+            "Your rate is in the cheapest quarter; you could raise it toward {:.2} DGPU/hr without losing much demand.",
+            market_rate.p50
+        )),
+        None => suggestions.push("Set an hourly rate for this GPU so it can be listed.".to_string()),
+        _ => {}
+    }
+    let item = inventory::report(&app_handle).await.ok().and_then(|items| items.into_iter().find(|i| i.gpu_id == gpu_id));
+    if let Some(item) = item {
+        let (schedule, _) = pricing::fee_schedule(&app_handle).await;
+        let break_even = pricing::break_even(&app_handle, &gpu, &item, schedule.provider_commission_percent).await;
+        if let Some(minimum) = break_even.minimum_rate_dgpu.map(money::to_f64) {
+            if f64::from(market_rate.p50) < minimum {
+                suggestions.push(format!(
+                    "At the {:.2} DGPU/hr median this card can't cover its power and amortization (it needs {:.2}); consider retiring or repurposing it.",
+                    market_rate.p50, minimum
+                ));
+            } else if rate.is_some_and(|r| f64::from(r) < minimum) {
+                suggestions.push(format!("Your rate is below the {:.2} DGPU/hr this card needs to cover power and amortization.", minimum));
+            }
+        }
+    }
+    if let (Some(u), Some(d)) = (uptime, aggregates.uptime_percent) {
+        if u < d.p50 {
+            suggestions.push(format!("Uptime of {:.0}% trails the {:.0}% median; keep the rig online longer to rank higher.", u, d.p50));
+        }
+    }
+    if benchmark.is_none() {
+        suggestions.push("Run the GPU benchmark so renters can compare your card's performance.".to_string());
+    }
+    if let (Some(r), Some(d)) = (reputation, aggregates.reputation) {
+        if r < d.p50 {
+            suggestions.push("Your reputation is below the median; review recent failed or cancelled jobs.".to_string());
+        }
+    }
+
+    Ok(MarketPosition { gpu_id, gpu_model: gpu.model, listings: aggregates.listings, rank_estimate, factors, suggestions })
+}
+
+// Rate distribution per region for the GPU's model, from the marketplace GraphQL API.
+#[tauri::command]
+pub async fn get_regional_rates(
+    app_handle: AppHandle,
+    gpu_id: String,
+) -> Result<Vec<graphql::rate_percentiles_by_region::RatePercentilesByRegionRatePercentiles>, String> {
+    let gpus = get_detected_gpus(app_handle.clone()).await?;
+    let gpu = gpus.iter().find(|g| g.id == gpu_id).ok_or_else(|| format!("No GPU with ID '{}'.", gpu_id))?;
+    let mut rates = graphql::rate_percentiles_by_region(&app_handle, &gpu.model).await?;
+    rates.sort_by(|a, b| a.p50.total_cmp(&b.p50));
+    Ok(rates)
+}
+
+// Rejects below-floor prices unless `confirmation` matches the required phrase exactly.
+pub fn enforce_price_floor(app_handle: &AppHandle, gpu_model: &str, rate: DgpuPerHour, confirmation: Option<&str>) -> Result<PriceCheck, String> {
+    let check = app_handle.state::<MarketState>().check_price(gpu_model, rate);
+    if let Some(required) = &check.required_confirmation {
+        if confirmation != Some(required.as_str()) {
+            return Err(format!(
+                "{} Type \"{}\" to confirm this price.",
+                check.message.clone().unwrap_or_default(),
+                required
+            ));
+        }
+        emit_log_entry(app_handle, "status", format!("Below-floor price confirmed for {}: {}.", gpu_model, rate));
+    }
+    Ok(check)
+}
+
+#[tauri::command]
+pub async fn validate_gpu_price(app_handle: AppHandle, state: State<'_, MarketState>, gpu_id: String, hourly_rate: DgpuPerHour) -> Result<PriceCheck, String> {
+    let gpus = get_detected_gpus(app_handle).await?;
+    let gpu = gpus.iter().find(|g| g.id == gpu_id).ok_or_else(|| format!("No GPU with ID '{}'.", gpu_id))?;
+    Ok(state.check_price(&gpu.model, hourly_rate))
+}
+
+#[tauri::command]
+pub async fn refresh_price_floors(app_handle: AppHandle, state: State<'_, MarketState>) -> Result<usize, String> {
+    let body = api_client::get_public_text(&app_handle, "/api/v1/market/price-floors").await?;
+    let floors = parse(&body)?;
+    let count = floors.len();
+
+    if let Some(path) = &state.downloaded_path {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        }
+        fs::write(path, &body).map_err(|e| format!("Failed to save price floors: {}", e))?;
+    }
+    *state.floors.write().unwrap() = floors;
+    emit_log_entry(&app_handle, "status", format!("Price floor table updated ({} models).", count));
+    Ok(count)
+}
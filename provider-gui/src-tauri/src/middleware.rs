@@ -0,0 +1,326 @@
+// Checks and bookkeeping applied to every command the frontend invokes, before it reaches the
+// generated handler:
+// - the invocation is added to the session trace while recording (see `session`);
+// - arguments of state-changing commands are written to the log, sanitized like session traces;
+// - while an organization is selected, commands that change the rig's account, credentials or
+//   extensions require an owner or admin role;
+// - each command is rate limited, with tighter limits for those that do expensive work;
+// - destructive commands only run with a single-use `confirmationToken` argument, obtained from
+//   `request_confirmation` for that command shortly before. The token is only issued once the
+//   user accepts a native dialog, which page scripts can't answer, so neither a stray click nor
+//   a script can trigger them;
+// - panics during dispatch are caught, logged and reported instead of unwinding into Tauri;
+// - per-command metrics are kept for diagnostics.
+//
+// Async commands finish on the runtime after dispatch returns, so completion is timed where the
+// result is handed to the webview: `responder`, installed with `Builder::invoke_system`. Its
+// initialization script copies each call's callback ID into the arguments, which is how a
+// response is matched to the command that produced it. `average_dispatch_ms` covers argument
+// parsing and synchronous work only; `average_ms` and `max_ms`, the whole command.
+
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::OsRng;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::api::ipc::{format_callback, format_callback_result, CallbackFn};
+use tauri::{Invoke, InvokeResponse, Manager, Runtime, State, Window};
+
+use crate::org::{OrgRole, OrgState};
+use crate::{crash_reports, emit_log_entry, get_timestamp, session};
+
+// Generous enough for polling, but stops a runaway frontend loop from hammering the daemon.
+const DEFAULT_RATE_LIMIT: RateLimit = RateLimit { calls: 30, per: Duration::from_secs(10) };
+
+// Commands that reach out to the platform, spawn tools or build large files.
+const RATE_LIMITS: &[(&str, RateLimit)] = &[
+    ("refresh_known_issues", RateLimit { calls: 2, per: Duration::from_secs(60) }),
+    ("refresh_price_floors", RateLimit { calls: 2, per: Duration::from_secs(60) }),
+    ("upload_pending_reports", RateLimit { calls: 2, per: Duration::from_secs(60) }),
+    ("create_diagnostic_bundle", RateLimit { calls: 2, per: Duration::from_secs(60) }),
+    ("create_backup", RateLimit { calls: 2, per: Duration::from_secs(60) }),
+    ("run_troubleshooter", RateLimit { calls: 3, per: Duration::from_secs(60) }),
+    ("run_preflight_checklist", RateLimit { calls: 3, per: Duration::from_secs(60) }),
+    ("run_db_maintenance_now", RateLimit { calls: 1, per: Duration::from_secs(60) }),
+    ("measure_region_latency", RateLimit { calls: 3, per: Duration::from_secs(60) }),
+    ("search_available_gpus", RateLimit { calls: 10, per: Duration::from_secs(60) }),
+    ("compare_listing_to_market", RateLimit { calls: 10, per: Duration::from_secs(60) }),
+    ("sync_settings_now", RateLimit { calls: 5, per: Duration::from_secs(60) }),
+    ("test_proxy", RateLimit { calls: 5, per: Duration::from_secs(60) }),
+    ("test_platform_tls", RateLimit { calls: 5, per: Duration::from_secs(60) }),
+    // Keystrokes arrive one call at a time.
+    ("write_terminal", RateLimit { calls: 500, per: Duration::from_secs(1) }),
+];
+
+// Require an owner or admin role in the organization the rig is bound to (see `org`): on a shared
+// rig, members may operate it but not change who it earns for, its credentials or what code it
+// runs. Which organization is currently selected doesn't matter.
+const ADMIN_COMMANDS: &[&str] = &[
+    "add_account",
+    "remove_account",
+    "switch_account",
+    "set_platform_token",
+    "restore_backup",
+    "purge_local_data",
+    "configure_sync",
+    "enable_plugin",
+    "set_hook_enabled",
+    "set_feature_flag_override",
+    "provision_machine_identity",
+    "lift_lockdown",
+    "unbind_rig",
+];
+
+// Need a confirmation token (see `request_confirmation`).
+// `uninstall_daemon_service` belongs here once the app can install the daemon as a service.
+const CONFIRMED_COMMANDS: &[&str] = &["emergency_stop", "force_cleanup_job"];
+// Shown in the native confirmation dialog, by command.
+const CONFIRMATION_PROMPTS: &[(&str, &str)] = &[
+    ("emergency_stop", "Unlist every GPU and stop all running jobs now? Renters' jobs will be cut short."),
+    ("force_cleanup_job", "Kill this job's container and report it to the platform as failed?"),
+];
+const CONFIRMATION_TTL: Duration = Duration::from_secs(60);
+
+// Tauri's default IPC bridge, plus the callback ID under `CALLBACK_ARG` so `responder` can tell
+// which command a response belongs to.
+pub const INVOKE_INITIALIZATION_SCRIPT: &str = "Object.defineProperty(window, '__TAURI_POST_MESSAGE__', { value: (message) => window.ipc.postMessage(JSON.stringify({ ...message, __invokeCallback: message.callback })) })";
+const CALLBACK_ARG: &str = "__invokeCallback";
+
+// Never logged: terminal input may contain passwords, and the payload is the point.
+const UNLOGGED_COMMANDS: &[&str] = &["write_terminal", "resize_terminal"];
+
+#[derive(Debug, Clone, Copy)]
+struct RateLimit {
+    calls: u32,
+    per: Duration,
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct CommandMetrics {
+    pub invocations: u64,
+    pub rejected_by_role: u64,
+    pub rate_limited: u64,
+    pub rejected_unconfirmed: u64,
+    pub panics: u64,
+    pub average_dispatch_ms: f64,
+    pub completed: u64,
+    pub average_ms: f64, // From dispatch until the result reached the webview
+    pub max_ms: f64,
+    #[serde(skip)]
+    total_dispatch: Duration,
+    #[serde(skip)]
+    total: Duration,
+}
+
+pub struct MiddlewareState {
+    metrics: Mutex<HashMap<String, CommandMetrics>>,
+    // Start of the current window and calls made in it, per command.
+    windows: Mutex<HashMap<String, (Instant, u32)>>,
+    // Outstanding confirmation tokens: the command each is for, and when it expires.
+    confirmations: Mutex<HashMap<String, (String, Instant)>>,
+    // Dispatched commands awaiting their response, by callback ID.
+    in_flight: Mutex<HashMap<usize, (String, Instant)>>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ConfirmationToken {
+    pub token: String,
+    pub action: String,
+    pub expires_in_secs: u64,
+    pub issued_at: String,
+}
+
+impl MiddlewareState {
+    pub fn new() -> Self {
+        MiddlewareState {
+            metrics: Mutex::new(HashMap::new()),
+            windows: Mutex::new(HashMap::new()),
+            confirmations: Mutex::new(HashMap::new()),
+            in_flight: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn metric(&self, command: &str, f: impl FnOnce(&mut CommandMetrics)) {
+        f(self.metrics.lock().unwrap().entry(command.to_string()).or_default());
+    }
+
+    fn check_rate(&self, command: &str) -> Result<(), String> {
+        let limit = RATE_LIMITS.iter().find(|(name, _)| *name == command).map_or(DEFAULT_RATE_LIMIT, |(_, limit)| *limit);
+        let now = Instant::now();
+        let mut windows = self.windows.lock().unwrap();
+        let window = windows.entry(command.to_string()).or_insert((now, 0));
+        if now.duration_since(window.0) >= limit.per {
+            *window = (now, 0);
+        }
+        if window.1 >= limit.calls {
+            let retry_in = limit.per.saturating_sub(now.duration_since(window.0));
+            return Err(format!("'{}' was called too often; try again in {} s.", command, retry_in.as_secs().max(1)));
+        }
+        window.1 += 1;
+        Ok(())
+    }
+
+    // Consumes the token in the payload if it was issued for `command` and hasn't expired.
+    fn check_confirmation(&self, command: &str, payload: &Value) -> Result<(), String> {
+        if !CONFIRMED_COMMANDS.contains(&command) {
+            return Ok(());
+        }
+        let now = Instant::now();
+        let mut confirmations = self.confirmations.lock().unwrap();
+        confirmations.retain(|_, (_, expires)| *expires > now);
+        let token = payload.get("confirmationToken").and_then(Value::as_str).unwrap_or_default();
+        match confirmations.remove(token) {
+            Some((action, _)) if action == command => Ok(()),
+            _ => Err(format!("'{}' needs confirmation; request a token with request_confirmation and pass it as confirmationToken.", command)),
+        }
+    }
+}
+
+fn check_role<R: Runtime>(manager: &impl Manager<R>, command: &str) -> Result<(), String> {
+    if !ADMIN_COMMANDS.contains(&command) {
+        return Ok(());
+    }
+    let Some(state) = manager.try_state::<OrgState>() else { return Ok(()) };
+    match state.bound_role() {
+        None | Some((_, Some(OrgRole::Owner | OrgRole::Admin))) => Ok(()),
+        Some((org_id, Some(OrgRole::Member))) => Err(format!("'{}' needs an owner or admin role in organization '{}'.", command, org_id)),
+        Some((org_id, None)) => Err(format!("Your role in organization '{}' isn't known yet; reload your organization memberships and try again.", org_id)),
+    }
+}
+
+// Only state-changing commands are logged; reads are polled constantly and would drown the log.
+fn log_arguments(app_handle: &tauri::AppHandle, command: &str, payload: &Value) {
+    if command.starts_with("get_") || command.starts_with("list_") || UNLOGGED_COMMANDS.contains(&command) {
+        return;
+    }
+    emit_log_entry(app_handle, "status", format!("Command {} invoked with {}", command, session::sanitize(payload)));
+}
+
+fn reject<R: Runtime>(invoke: Invoke<R>, command: &str, message: String) {
+    emit_log_entry(&invoke.message.window().app_handle(), "error", format!("Command {} rejected: {}", command, message));
+    invoke.resolver.reject(message);
+}
+
+// Wraps the generated command handler with the checks above.
+pub fn handler<R: Runtime>(inner: impl Fn(Invoke<R>) + Send + Sync + 'static) -> impl Fn(Invoke<R>) + Send + Sync + 'static {
+    move |invoke: Invoke<R>| {
+        let command = invoke.message.command().to_string();
+        let window = invoke.message.window();
+        let app_handle = window.app_handle();
+        session::record_command(&window, &command, invoke.message.payload());
+
+        let Some(state) = app_handle.try_state::<MiddlewareState>() else {
+            inner(invoke);
+            return;
+        };
+        state.metric(&command, |m| m.invocations += 1);
+        if let Err(e) = check_role(&window, &command) {
+            state.metric(&command, |m| m.rejected_by_role += 1);
+            return reject(invoke, &command, e);
+        }
+        if let Err(e) = state.check_rate(&command) {
+            state.metric(&command, |m| m.rate_limited += 1);
+            return reject(invoke, &command, e);
+        }
+        if let Err(e) = state.check_confirmation(&command, invoke.message.payload()) {
+            state.metric(&command, |m| m.rejected_unconfirmed += 1);
+            return reject(invoke, &command, e);
+        }
+        log_arguments(&app_handle, &command, invoke.message.payload());
+
+        let started = Instant::now();
+        let callback = invoke.message.payload().get(CALLBACK_ARG).and_then(Value::as_u64).map(|id| id as usize);
+        if let Some(callback) = callback {
+            state.in_flight.lock().unwrap().insert(callback, (command.clone(), started));
+        }
+        let outcome = panic::catch_unwind(AssertUnwindSafe(|| inner(invoke)));
+        let elapsed = started.elapsed();
+        state.metric(&command, |m| {
+            m.total_dispatch += elapsed;
+            m.average_dispatch_ms = m.total_dispatch.as_secs_f64() * 1000.0 / m.invocations as f64;
+        });
+        if let Err(panic) = outcome {
+            let message = panic
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| panic.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown panic".to_string());
+            state.metric(&command, |m| m.panics += 1);
+            if let Some(callback) = callback {
+                state.in_flight.lock().unwrap().remove(&callback);
+            }
+            emit_log_entry(&app_handle, "error", format!("Command {} panicked: {}", command, message));
+            crash_reports::record_command_failure(&app_handle, &command, &format!("panic: {}", message));
+            // The invocation was consumed by the panicking handler, so the frontend is told here.
+            if let Err(e) = app_handle.emit_all("command_panicked", json!({ "command": command, "message": message })) {
+                eprintln!("Failed to emit command_panicked event: {}", e);
+            }
+        }
+    }
+}
+
+// Delivers a command's result to the webview as Tauri's default responder does, recording how
+// long the command took.
+pub fn responder<R: Runtime>(window: Window<R>, response: InvokeResponse, success_callback: CallbackFn, error_callback: CallbackFn) {
+    if let Some(state) = window.try_state::<MiddlewareState>() {
+        let dispatched = state.in_flight.lock().unwrap().remove(&success_callback.0);
+        if let Some((command, started)) = dispatched {
+            let elapsed = started.elapsed();
+            state.metric(&command, |m| {
+                m.completed += 1;
+                m.total += elapsed;
+                m.average_ms = m.total.as_secs_f64() * 1000.0 / m.completed as f64;
+                m.max_ms = m.max_ms.max(elapsed.as_secs_f64() * 1000.0);
+            });
+        }
+    }
+    let js = match format_callback_result(response.into_result(), success_callback, error_callback) {
+        Ok(js) => js,
+        Err(e) => match format_callback(error_callback, &e.to_string()) {
+            Ok(js) => js,
+            Err(e) => {
+                eprintln!("Failed to serialize a command response: {}", e);
+                return;
+            }
+        },
+    };
+    if let Err(e) = window.eval(&js) {
+        eprintln!("Failed to deliver a command response: {}", e);
+    }
+}
+
+// Asks the user in a native dialog and, if they accept, issues a single-use token for one
+// destructive command, valid for `CONFIRMATION_TTL`, which the frontend passes with the command.
+#[tauri::command]
+pub async fn request_confirmation(
+    app_handle: tauri::AppHandle,
+    window: tauri::Window,
+    state: State<'_, MiddlewareState>,
+    action: String,
+) -> Result<ConfirmationToken, String> {
+    let Some((_, prompt)) = CONFIRMATION_PROMPTS.iter().find(|(command, _)| *command == action) else {
+        return Err(format!("'{}' doesn't need confirmation.", action));
+    };
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    tauri::api::dialog::confirm(Some(&window), "Confirm", *prompt, move |accepted| {
+        let _ = tx.send(accepted);
+    });
+    if !rx.await.unwrap_or(false) {
+        emit_log_entry(&app_handle, "status", format!("Confirmation for {} declined.", action));
+        return Err("Cancelled.".to_string());
+    }
+    let mut bytes = [0u8; 16];
+    OsRng.fill_bytes(&mut bytes);
+    let token = hex::encode(bytes);
+    state.confirmations.lock().unwrap().insert(token.clone(), (action.clone(), Instant::now() + CONFIRMATION_TTL));
+    emit_log_entry(&app_handle, "status", format!("Confirmation requested for {}.", action));
+    Ok(ConfirmationToken { token, action, expires_in_secs: CONFIRMATION_TTL.as_secs(), issued_at: get_timestamp() })
+}
+
+#[tauri::command]
+pub async fn get_command_metrics(state: State<'_, MiddlewareState>) -> Result<HashMap<String, CommandMetrics>, String> {
+    Ok(state.metrics.lock().unwrap().clone())
+}
@@ -0,0 +1,237 @@
+// GPU probe through the nvidia-smi CLI, for when the daemon can't enumerate GPUs itself.
+//
+// The daemon links NVML; on systems where that fails (mismatched driver libraries, containers
+// without the NVML stub) the GUI falls back to shelling out to nvidia-smi. The CSV query is
+// tried first; older drivers reject unknown query fields, in which case the full XML report
+// (`-q -x`) is parsed instead. Values are read with their units and normalized, and any field
+// the driver reports as "[N/A]" or "[Not Supported]" becomes None. GPUs found this way are shown
+// like any other but are marked `fallback_sourced`: they carry no rental state, which only the
+// daemon knows, so they aren't recorded as telemetry or listed, rented or failed over to. While
+// the daemon keeps failing, nvidia-smi is run at most once every `PROBE_INTERVAL` and callers get
+// the last result in between.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::AppHandle;
+
+use crate::hooks::run_script;
+use crate::units::{MegaBytes, Watts};
+use crate::{demo, emit_log_entry, gpu_ids, link_health, invoke_daemon_cli_json_output, wsl, GpuInfo, RentalMode};
+
+const NVIDIA_SMI_TIMEOUT: Duration = Duration::from_secs(10);
+const CSV_FIELDS: &str = "uuid,name,memory.total,memory.free,utilization.gpu,temperature.gpu,power.draw,fan.speed,clocks.mem,ecc.errors.corrected.volatile.total,pci.bus_id,pcie.link.gen.current,pcie.link.gen.max,pcie.link.width.current,pcie.link.width.max,clocks_throttle_reasons.active";
+const CSV_FIELD_COUNT: usize = 16;
+const PROBE_INTERVAL: Duration = Duration::from_secs(60);
+
+static FALLBACK_ANNOUNCED: AtomicBool = AtomicBool::new(false);
+static LAST_PROBE: Mutex<Option<(Instant, Result<Vec<GpuInfo>, String>)>> = Mutex::new(None);
+
+// Parses "24564 MiB", "45 %", "250.13 W", "1000", etc. into a number in the unit's base, with
+// memory normalized to MiB.
+fn quantity(raw: &str) -> Option<f64> {
+    let raw = raw.trim();
+    if raw.is_empty() || raw.starts_with('[') || raw.eq_ignore_ascii_case("N/A") {
+        return None;
+    }
+    let mut parts = raw.split_whitespace();
+    let value: f64 = parts.next()?.parse().ok()?;
+    let scale = match parts.next().unwrap_or("") {
+        "KiB" => 1.0 / 1024.0,
+        "GiB" => 1024.0,
+        "TiB" => 1024.0 * 1024.0,
+        _ => 1.0,
+    };
+    Some(value * scale)
+}
+
+fn whole(raw: &str) -> Option<u32> {
+    quantity(raw).map(|v| v.round().max(0.0) as u32)
+}
+
+// Link widths read "16" in CSV and "16x" in the XML report.
+fn lanes(raw: &str) -> Option<u32> {
+    whole(raw.trim().trim_end_matches('x'))
+}
+
+fn hex_mask(raw: &str) -> Option<u64> {
+    u64::from_str_radix(raw.trim().trim_start_matches("0x"), 16).ok()
+}
+
+fn gpu(uuid: &str, name: &str) -> GpuInfo {
+    GpuInfo {
+        id: uuid.to_string(),
+        name: name.to_string(),
+        model: name.to_string(),
+        vram_total_mb: MegaBytes::new(0),
+        vram_free_mb: MegaBytes::new(0),
+        utilization_gpu_percent: None,
+        temperature_c: None,
+        power_draw_w: None,
+        is_available_for_rent: false,
+        current_hourly_rate_dgpu: None,
+        rental_mode: RentalMode::default(),
+        ecc_corrected_errors: None,
+        memory_clock_mhz: None,
+        fan_speed_percent: None,
+        pcie_link_generation: None,
+        pcie_link_generation_max: None,
+        pcie_link_width: None,
+        pcie_link_width_max: None,
+        throttle_reasons: None,
+        uuid: Some(uuid.to_string()).filter(|u| !u.is_empty()),
+        pci_bus_id: None,
+        nickname: None,
+        fallback_sourced: true,
+    }
+}
+
+fn parse_csv(stdout: &str) -> Result<Vec<GpuInfo>, String> {
+    let mut gpus = Vec::new();
+    for line in stdout.lines().filter(|l| !l.trim().is_empty()) {
+        let fields: Vec<&str> = line.split(", ").collect();
+        if fields.len() < CSV_FIELD_COUNT {
+            return Err(format!("Unexpected nvidia-smi output: {}", line));
+        }
+        // A product name containing ", " splits into extra fields; fold them back into the name.
+        let extra = fields.len() - CSV_FIELD_COUNT;
+        let name = fields[1..=1 + extra].join(", ");
+        let values = &fields[2 + extra..];
+        let mut info = gpu(fields[0].trim(), name.trim());
+        info.vram_total_mb = MegaBytes::new(whole(values[0]).unwrap_or(0));
+        info.vram_free_mb = MegaBytes::new(whole(values[1]).unwrap_or(0));
+        info.utilization_gpu_percent = whole(values[2]);
+        info.temperature_c = whole(values[3]);
+        info.power_draw_w = whole(values[4]).map(Watts::new);
+        info.fan_speed_percent = whole(values[5]);
+        info.memory_clock_mhz = whole(values[6]);
+        info.ecc_corrected_errors = quantity(values[7]).map(|v| v as u64);
+        info.pci_bus_id = Some(values[8].trim().to_string()).filter(|b| !b.is_empty() && !b.starts_with('['));
+        info.pcie_link_generation = whole(values[9]);
+        info.pcie_link_generation_max = whole(values[10]);
+        info.pcie_link_width = lanes(values[11]);
+        info.pcie_link_width_max = lanes(values[12]);
+        info.throttle_reasons = hex_mask(values[13]);
+        gpus.push(info);
+    }
+    Ok(gpus)
+}
+
+fn text<'a>(node: roxmltree::Node<'a, 'a>, path: &[&str]) -> Option<&'a str> {
+    let mut current = node;
+    for name in path {
+        current = current.children().find(|c| c.has_tag_name(*name))?;
+    }
+    current.text()
+}
+
+// Renamed from clocks_throttle_reasons to clocks_event_reasons in newer drivers, with each
+// reason's tag prefix following suit.
+fn throttle_mask(node: roxmltree::Node) -> Option<u64> {
+    let reasons = node.children().find(|c| c.has_tag_name("clocks_throttle_reasons") || c.has_tag_name("clocks_event_reasons"))?;
+    let mut mask = 0;
+    for reason in reasons.children().filter(|c| c.is_element()) {
+        let name = reason.tag_name().name();
+        let Some(&(_, bit)) = link_health::THROTTLE_REASONS.iter().find(|(suffix, _)| name.ends_with(suffix)) else { continue };
+        if reason.text().is_some_and(|t| t.trim() == "Active") {
+            mask |= bit;
+        }
+    }
+    Some(mask)
+}
+
+fn parse_xml(stdout: &str) -> Result<Vec<GpuInfo>, String> {
+    let doc = roxmltree::Document::parse(stdout).map_err(|e| format!("Invalid nvidia-smi XML: {}", e))?;
+    let mut gpus = Vec::new();
+    for node in doc.root_element().children().filter(|n| n.has_tag_name("gpu")) {
+        let uuid = text(node, &["uuid"]).unwrap_or_default();
+        let mut info = gpu(uuid, text(node, &["product_name"]).unwrap_or("NVIDIA GPU"));
+        info.vram_total_mb = MegaBytes::new(text(node, &["fb_memory_usage", "total"]).and_then(whole).unwrap_or(0));
+        info.vram_free_mb = MegaBytes::new(text(node, &["fb_memory_usage", "free"]).and_then(whole).unwrap_or(0));
+        info.utilization_gpu_percent = text(node, &["utilization", "gpu_util"]).and_then(whole);
+        info.temperature_c = text(node, &["temperature", "gpu_temp"]).and_then(whole);
+        // Renamed from power_readings in newer drivers.
+        info.power_draw_w = text(node, &["gpu_power_readings", "power_draw"])
+            .or_else(|| text(node, &["power_readings", "power_draw"]))
+            .and_then(whole)
+            .map(Watts::new);
+        info.fan_speed_percent = text(node, &["fan_speed"]).and_then(whole);
+        info.memory_clock_mhz = text(node, &["clocks", "mem_clock"]).and_then(whole);
+        info.ecc_corrected_errors = text(node, &["ecc_errors", "volatile", "sram_correctable"])
+            .and_then(quantity)
+            .into_iter()
+            .chain(text(node, &["ecc_errors", "volatile", "dram_correctable"]).and_then(quantity))
+            .chain(text(node, &["ecc_errors", "volatile", "single_bit", "total"]).and_then(quantity))
+            .reduce(|a, b| a + b)
+            .map(|v| v as u64);
+        info.pci_bus_id = node.attribute("id").or_else(|| text(node, &["pci", "pci_bus_id"])).map(str::to_string);
+        info.pcie_link_generation = text(node, &["pci", "pci_gpu_link_info", "pcie_gen", "current_link_gen"]).and_then(whole);
+        info.pcie_link_generation_max = text(node, &["pci", "pci_gpu_link_info", "pcie_gen", "max_link_gen"]).and_then(whole);
+        info.pcie_link_width = text(node, &["pci", "pci_gpu_link_info", "link_widths", "current_link_width"]).and_then(lanes);
+        info.pcie_link_width_max = text(node, &["pci", "pci_gpu_link_info", "link_widths", "max_link_width"]).and_then(lanes);
+        info.throttle_reasons = throttle_mask(node);
+        gpus.push(info);
+    }
+    Ok(gpus)
+}
+
+pub fn probe() -> Result<Vec<GpuInfo>, String> {
+    let csv_args = vec![format!("--query-gpu={}", CSV_FIELDS), "--format=csv,noheader".to_string()];
+    let csv = run_script(wsl::nvidia_smi_program(), &csv_args, "", NVIDIA_SMI_TIMEOUT, false).and_then(|output| parse_csv(&output.stdout));
+    match csv {
+        Ok(gpus) => Ok(gpus),
+        Err(csv_error) => {
+            let xml_args = vec!["-q".to_string(), "-x".to_string()];
+            run_script(wsl::nvidia_smi_program(), &xml_args, "", NVIDIA_SMI_TIMEOUT, false)
+                .and_then(|output| parse_xml(&output.stdout))
+                .map_err(|xml_error| format!("nvidia-smi CSV query failed ({}) and XML report failed ({})", csv_error, xml_error))
+        }
+    }
+}
+
+// The last probe while it's recent, else a new one.
+async fn throttled_probe() -> Result<Vec<GpuInfo>, String> {
+    if let Some((at, result)) = LAST_PROBE.lock().unwrap().as_ref() {
+        if at.elapsed() < PROBE_INTERVAL {
+            return result.clone();
+        }
+    }
+    let result = tauri::async_runtime::spawn_blocking(probe).await.map_err(|e| e.to_string())?;
+    *LAST_PROBE.lock().unwrap() = Some((Instant::now(), result.clone()));
+    result
+}
+
+// Asks the daemon for its GPUs and, if it can't answer, probes nvidia-smi directly. Either way
+// the GPUs come back under their stable IDs and display names (see `gpu_ids`).
+pub async fn detect_gpus(app_handle: &AppHandle) -> Result<Vec<GpuInfo>, String> {
+    let mut gpus = detect(app_handle).await?;
+    gpu_ids::apply(app_handle, &mut gpus);
+    Ok(gpus)
+}
+
+async fn detect(app_handle: &AppHandle) -> Result<Vec<GpuInfo>, String> {
+    let daemon_error = match invoke_daemon_cli_json_output::<Vec<GpuInfo>>(app_handle, &["--get-gpus-json"]).await {
+        Ok(gpus) => {
+            *LAST_PROBE.lock().unwrap() = None;
+            return Ok(gpus);
+        }
+        Err(e) => e,
+    };
+    if demo::enabled() {
+        return Err(daemon_error);
+    }
+    match throttled_probe().await {
+        Ok(gpus) if !gpus.is_empty() => {
+            if !FALLBACK_ANNOUNCED.swap(true, Ordering::Relaxed) {
+                emit_log_entry(
+                    app_handle,
+                    "status",
+                    format!("The daemon couldn't list GPUs ({}); reading them from nvidia-smi instead. Rental state is unavailable until it recovers.", daemon_error),
+                );
+            }
+            Ok(gpus)
+        }
+        Ok(_) => Err(daemon_error),
+        Err(e) => Err(format!("{}; fallback probe also failed: {}", daemon_error, e)),
+    }
+}
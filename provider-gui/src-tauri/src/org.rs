@@ -0,0 +1,235 @@
+// Organization (team) context for small GPU businesses sharing a fleet.
+//
+// When an organization is selected, teammates' rigs are listed alongside the local one. They
+// are read-only unless the platform grants this member management rights on a rig, and every
+// change to a remote rig goes through the organization-scoped API rather than a local daemon.
+//
+// The first organization selected on a rig also binds the rig to it. The binding, not the current
+// selection, decides who may run admin commands here (see `middleware`), so a member can't get
+// around them by clearing the selection; only an owner or admin can unbind the rig.
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+
+use crate::units::DgpuPerHour;
+use crate::{accounts, api_client, emit_log_entry, market};
+
+const ORG_CONTEXT_FILE_NAME: &str = "org_context.json";
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum OrgRole {
+    Owner,
+    Admin,
+    Member,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OrgMembership {
+    pub org_id: String,
+    pub org_name: String,
+    pub role: OrgRole,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OrgRigGpu {
+    pub gpu_id: String,
+    pub model: String,
+    pub is_available_for_rent: bool,
+    pub current_hourly_rate_dgpu: Option<DgpuPerHour>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OrgRig {
+    pub rig_id: String,
+    pub name: String,
+    pub owner_display_name: String,
+    pub status: String,
+    pub gpus: Vec<OrgRigGpu>,
+    #[serde(default)]
+    pub can_manage: bool, // Granted by the rig owner or implied by an owner/admin role
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+struct OrgContext {
+    org_id: Option<String>,
+    // The member's role in `org_id`, kept so role checks work before the platform is reachable.
+    role: Option<OrgRole>,
+    #[serde(default)]
+    bound_org_id: Option<String>,
+    #[serde(default)]
+    bound_role: Option<OrgRole>, // The member's role in `bound_org_id`
+}
+
+pub struct OrgState {
+    context: Mutex<OrgContext>,
+    path: Mutex<Option<PathBuf>>,
+}
+
+fn read_context(app_handle: &AppHandle) -> (OrgContext, Option<PathBuf>) {
+    let path = accounts::data_dir(app_handle).map(|dir| dir.join(ORG_CONTEXT_FILE_NAME));
+    let contents = path.as_ref().and_then(|p| fs::read_to_string(p).ok());
+    let context = contents
+        .as_deref()
+        .and_then(|contents| {
+            serde_json::from_str::<OrgContext>(contents)
+                .ok()
+                // Earlier versions stored only the organization ID.
+                .or_else(|| serde_json::from_str::<Option<String>>(contents).ok().map(|org_id| OrgContext { org_id, ..OrgContext::default() }))
+        })
+        .map(|mut context| {
+            // Earlier versions had no binding; the organization selected then governed the rig.
+            if context.bound_org_id.is_none() && context.org_id.is_some() {
+                context.bound_org_id = context.org_id.clone();
+                context.bound_role = context.role;
+            }
+            context
+        })
+        .unwrap_or_default();
+    (context, path)
+}
+
+impl OrgState {
+    pub fn load(app_handle: &AppHandle) -> Self {
+        let (context, path) = read_context(app_handle);
+        OrgState { context: Mutex::new(context), path: Mutex::new(path) }
+    }
+
+    // Organizations belong to the account, so the selection is reloaded after a switch.
+    pub fn reload(&self, app_handle: &AppHandle) {
+        let (context, path) = read_context(app_handle);
+        *self.context.lock().unwrap() = context;
+        *self.path.lock().unwrap() = path;
+    }
+
+    fn set_active(&self, context: OrgContext) -> Result<(), String> {
+        let path = self.path.lock().unwrap().clone().ok_or_else(|| "App data directory is unavailable.".to_string())?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        }
+        let json = serde_json::to_string(&context).map_err(|e| format!("Failed to serialize organization context: {}", e))?;
+        fs::write(&path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        *self.context.lock().unwrap() = context;
+        Ok(())
+    }
+
+    fn require_active(&self) -> Result<String, String> {
+        self.context.lock().unwrap().org_id.clone().ok_or_else(|| "No organization is selected.".to_string())
+    }
+
+    // The organization the rig is bound to and this member's role in it, if it is bound.
+    pub fn bound_role(&self) -> Option<(String, Option<OrgRole>)> {
+        let context = self.context.lock().unwrap();
+        context.bound_org_id.clone().map(|id| (id, context.bound_role))
+    }
+}
+
+async fn fetch_rigs(app_handle: &AppHandle, org_id: &str) -> Result<Vec<OrgRig>, String> {
+    api_client::get_json(app_handle, &format!("/api/v1/orgs/{}/rigs", org_id)).await
+}
+
+#[tauri::command]
+pub async fn get_org_memberships(app_handle: AppHandle, state: State<'_, OrgState>) -> Result<Vec<OrgMembership>, String> {
+    let memberships: Vec<OrgMembership> = api_client::get_json(&app_handle, "/api/v1/orgs/me").await?;
+    // Pick up role changes made on the platform since the organization was selected.
+    let current = state.context.lock().unwrap().clone();
+    let role_in = |org_id: &Option<String>| memberships.iter().find(|m| org_id.as_deref() == Some(m.org_id.as_str())).map(|m| m.role);
+    let updated = OrgContext {
+        role: role_in(&current.org_id).or(current.role),
+        // Leaving the organization on the platform ends the rig's binding to it.
+        bound_role: role_in(&current.bound_org_id),
+        bound_org_id: current.bound_org_id.clone().filter(|_| role_in(&current.bound_org_id).is_some()),
+        org_id: current.org_id.clone(),
+    };
+    if updated.role != current.role || updated.bound_role != current.bound_role || updated.bound_org_id != current.bound_org_id {
+        state.set_active(updated)?;
+    }
+    Ok(memberships)
+}
+
+#[tauri::command]
+pub async fn get_active_org(state: State<'_, OrgState>) -> Result<Option<String>, String> {
+    Ok(state.context.lock().unwrap().org_id.clone())
+}
+
+#[tauri::command]
+pub async fn set_active_org(app_handle: AppHandle, state: State<'_, OrgState>, org_id: Option<String>) -> Result<Option<OrgMembership>, String> {
+    let membership = match &org_id {
+        Some(id) => {
+            let memberships: Vec<OrgMembership> = api_client::get_json(&app_handle, "/api/v1/orgs/me").await?;
+            Some(memberships.into_iter().find(|m| &m.org_id == id).ok_or_else(|| format!("You are not a member of organization '{}'.", id))?)
+        }
+        None => None,
+    };
+    let current = state.context.lock().unwrap().clone();
+    let (bound_org_id, bound_role) = match current.bound_org_id {
+        Some(bound) => (Some(bound), current.bound_role),
+        None => (membership.as_ref().map(|m| m.org_id.clone()), membership.as_ref().map(|m| m.role)),
+    };
+    if current.bound_org_id.is_none() && bound_org_id.is_some() {
+        emit_log_entry(&app_handle, "status", format!("This rig is now bound to organization '{}'.", bound_org_id.as_deref().unwrap_or_default()));
+    }
+    state.set_active(OrgContext { role: membership.as_ref().map(|m| m.role), org_id, bound_org_id, bound_role })?;
+    if let Err(e) = app_handle.emit_all("org_context_changed", &membership) {
+        eprintln!("Failed to emit org_context_changed event: {}", e);
+    }
+    Ok(membership)
+}
+
+// The organization the rig is bound to, if any.
+#[tauri::command]
+pub async fn get_bound_org(state: State<'_, OrgState>) -> Result<Option<String>, String> {
+    Ok(state.context.lock().unwrap().bound_org_id.clone())
+}
+
+// Releases the rig from its organization; an admin command (see `middleware`).
+#[tauri::command]
+pub async fn unbind_rig(app_handle: AppHandle, state: State<'_, OrgState>) -> Result<(), String> {
+    let current = state.context.lock().unwrap().clone();
+    let Some(org_id) = current.bound_org_id.clone() else { return Ok(()) };
+    state.set_active(OrgContext { bound_org_id: None, bound_role: None, ..current })?;
+    emit_log_entry(&app_handle, "status", format!("This rig is no longer bound to organization '{}'.", org_id));
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_org_rigs(app_handle: AppHandle, state: State<'_, OrgState>) -> Result<Vec<OrgRig>, String> {
+    let org_id = state.require_active()?;
+    fetch_rigs(&app_handle, &org_id).await
+}
+
+#[tauri::command]
+pub async fn set_org_gpu_rental_config(
+    app_handle: AppHandle,
+    state: State<'_, OrgState>,
+    rig_id: String,
+    gpu_id: String,
+    hourly_rate: DgpuPerHour,
+    available: bool,
+    confirmation: Option<String>,
+) -> Result<OrgRigGpu, String> {
+    let org_id = state.require_active()?;
+    let rigs = fetch_rigs(&app_handle, &org_id).await?;
+    let rig = rigs.iter().find(|r| r.rig_id == rig_id).ok_or_else(|| format!("No rig with ID '{}' in this organization.", rig_id))?;
+    if !rig.can_manage {
+        return Err(format!("Rig '{}' is owned by {} and is read-only for you.", rig.name, rig.owner_display_name));
+    }
+    let gpu = rig.gpus.iter().find(|g| g.gpu_id == gpu_id).ok_or_else(|| format!("No GPU with ID '{}' on rig '{}'.", gpu_id, rig.name))?;
+    if available {
+        market::enforce_price_floor(&app_handle, &gpu.model, hourly_rate, confirmation.as_deref())?;
+    }
+
+    let path = format!("/api/v1/orgs/{}/rigs/{}/gpus/{}/rental", org_id, rig_id, gpu_id);
+    let body = json!({ "hourly_rate_dgpu": hourly_rate, "is_available_for_rent": available });
+    let updated: OrgRigGpu = api_client::send_json(&app_handle, reqwest::Method::PUT, &path, &body).await?;
+    emit_log_entry(
+        &app_handle,
+        "status",
+        format!("Updated {} on rig '{}': {} at {}.", gpu.model, rig.name, if available { "listed" } else { "unlisted" }, hourly_rate),
+    );
+    Ok(updated)
+}
@@ -0,0 +1,431 @@
+// Importing history and setup from other GPU rental platforms.
+//
+// Providers moving to Dante usually have months of earnings on another marketplace and machines
+// already priced there. Earnings exports (CSV) are stored in the local database, in the currency
+// they were paid in, so analytics can show the history next to Dante earnings. Machine config
+// exports (JSON) are matched against the local GPUs and turned into suggested settings the user
+// reviews and applies; nothing is changed automatically.
+//
+// Export formats differ between platforms and change over time, so columns and fields are found
+// by name, case-insensitively, from a list of common spellings per platform. Numbers and
+// numeric dates are read in the locale the file was written in (the user's, unless the import
+// names another), so "1.234,50" and "03/04/2024" mean what the spreadsheet that saved them meant.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::str::FromStr;
+use std::time::UNIX_EPOCH;
+use tauri::{AppHandle, Manager};
+
+use crate::locale::{self, DateOrder, LocaleFormat};
+use crate::market::MarketState;
+use crate::money::{self, Decimal};
+use crate::storage::Storage;
+use crate::units::DgpuPerHour;
+use crate::{emit_log_entry, get_detected_gpus, invoke_daemon_cli_json_output, ProviderSettings};
+
+// Row errors beyond this are counted but not listed.
+const MAX_REPORTED_ERRORS: usize = 20;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportSource {
+    VastAi,
+    RunPod,
+    TensorDock,
+    // Any other export with recognisable column names.
+    Other,
+}
+
+impl ImportSource {
+    fn as_str(&self) -> &'static str {
+        match self {
+            ImportSource::VastAi => "vast_ai",
+            ImportSource::RunPod => "runpod",
+            ImportSource::TensorDock => "tensordock",
+            ImportSource::Other => "other",
+        }
+    }
+
+    fn date_columns(&self) -> &'static [&'static str] {
+        match self {
+            ImportSource::VastAi => &["end_date", "start_date", "date", "timestamp"],
+            ImportSource::RunPod => &["date", "time", "created_at", "timestamp"],
+            ImportSource::TensorDock | ImportSource::Other => &["date", "day", "timestamp", "created_at", "period_end", "end_date"],
+        }
+    }
+
+    fn amount_columns(&self) -> &'static [&'static str] {
+        match self {
+            ImportSource::VastAi => &["earnings", "amount", "total", "credit"],
+            ImportSource::RunPod => &["amount", "earnings", "payout", "total"],
+            ImportSource::TensorDock | ImportSource::Other => &["earnings", "amount", "payout", "revenue", "total", "earnings_usd", "amount_usd"],
+        }
+    }
+}
+
+const MACHINE_COLUMNS: &[&str] = &["machine_id", "machine", "host_id", "pod_id", "hostname", "server"];
+const GPU_COLUMNS: &[&str] = &["gpu_name", "gpu_model", "gpu_type", "gpu"];
+const HOURS_COLUMNS: &[&str] = &["gpu_hours", "hours", "duration_hours", "runtime_hours"];
+const CURRENCY_COLUMNS: &[&str] = &["currency", "unit"];
+
+const CONFIG_GPU_FIELDS: &[&str] = &["gpu_name", "gpu_model", "gpu_type", "gpu"];
+const CONFIG_COUNT_FIELDS: &[&str] = &["num_gpus", "gpu_count", "gpus"];
+const CONFIG_RATE_FIELDS: &[&str] = &["price_per_gpu_hour", "dph_base", "price_per_hour", "hourly_price", "gpu_price", "price"];
+const CONFIG_MIN_HOURS_FIELDS: &[&str] = &["min_duration_hours", "min_rental_hours", "min_hours"];
+const CONFIG_CURRENCY_FIELDS: &[&str] = &["currency"];
+
+#[derive(Debug, Clone)]
+pub struct ImportedEarning {
+    pub source: String,
+    // Hash of the raw row and its position, so importing the same file twice adds nothing while
+    // identical rows within a file are all kept.
+    pub row_key: String,
+    pub earned_at: i64,
+    pub machine: Option<String>,
+    pub gpu_model: Option<String>,
+    pub gpu_hours: Option<f64>,
+    pub amount: Decimal,
+    pub currency: String,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ImportedEarningsMonth {
+    pub source: String,
+    pub month: String, // YYYY-MM
+    pub currency: String,
+    pub rows: i64,
+    pub gpu_hours: f64,
+    #[serde(with = "money::amount")]
+    pub amount: Decimal,
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct EarningsImportSummary {
+    pub rows_read: usize,
+    pub rows_imported: usize,
+    pub rows_already_imported: usize,
+    pub rows_failed: usize,
+    pub errors: Vec<String>,
+    pub first_earned_at: Option<i64>,
+    pub last_earned_at: Option<i64>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct SuggestedRate {
+    pub gpu_id: String,
+    pub gpu_model: String,
+    pub imported_model: String,
+    pub imported_rate: Option<f64>,
+    pub imported_currency: String,
+    // The Dante market median for the model; prices in other currencies aren't converted.
+    pub suggested_rate_dgpu: Option<DgpuPerHour>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct SuggestedSettings {
+    pub source: ImportSource,
+    pub machines_read: usize,
+    pub provider_settings: Option<ProviderSettings>,
+    pub gpu_rates: Vec<SuggestedRate>,
+    // Imported GPU models with no matching local GPU.
+    pub unmatched_models: Vec<String>,
+    pub notes: Vec<String>,
+}
+
+fn column(headers: &csv::StringRecord, names: &[&str]) -> Option<usize> {
+    names.iter().find_map(|name| headers.iter().position(|h| h.trim().eq_ignore_ascii_case(name)))
+}
+
+fn field<'a>(record: &'a csv::StringRecord, index: Option<usize>) -> Option<&'a str> {
+    index.and_then(|i| record.get(i)).map(str::trim).filter(|v| !v.is_empty())
+}
+
+fn unix_seconds(value: &str) -> Option<i64> {
+    humantime::parse_rfc3339_weak(value).ok()?.duration_since(UNIX_EPOCH).ok().map(|d| d.as_secs() as i64)
+}
+
+// Unix seconds, RFC 3339, or a numeric date with an optional time, e.g. "2024-03-04",
+// "04.03.2024 13:30" or "3/4/2024". Day and month order in a date ending in the year follows
+// `locale`; one starting with the year is always year-month-day.
+fn parse_date(value: &str, locale: &LocaleFormat) -> Option<i64> {
+    if let Ok(secs) = value.parse::<i64>() {
+        return Some(secs);
+    }
+    if let Some(secs) = unix_seconds(value) {
+        return Some(secs);
+    }
+    let (date, time) = value.split_once(' ').map_or((value, "00:00:00"), |(date, time)| (date, time.trim()));
+    let [a, b, c] = date.split(['/', '.', '-']).collect::<Vec<_>>()[..] else { return None };
+    if ![a, b, c].iter().all(|part| !part.is_empty() && part.chars().all(|ch| ch.is_ascii_digit())) {
+        return None;
+    }
+    let (year, month, day) = match (a.len(), c.len(), locale.date_order) {
+        (4, _, _) => (a, b, c),
+        (_, 4, DateOrder::Mdy) => (c, a, b),
+        (_, 4, _) => (c, b, a),
+        _ => return None,
+    };
+    let time = if time.matches(':').count() == 1 { format!("{}:00", time) } else { time.to_string() };
+    unix_seconds(&format!("{}-{:0>2}-{:0>2} {}", year, month, day, time))
+}
+
+// Tolerates currency symbols and grouping separators, e.g. "$1,234.50" or "1.234,50 €", reading
+// the decimal separator from `locale`.
+fn clean_number(value: &str, locale: &LocaleFormat) -> String {
+    value
+        .chars()
+        .filter(|c| c.is_ascii_digit() || *c == locale.decimal_separator || *c == '-')
+        .map(|c| if c == locale.decimal_separator { '.' } else { c })
+        .collect()
+}
+
+// Exact, for amounts of money.
+fn parse_amount(value: &str, locale: &LocaleFormat) -> Option<Decimal> {
+    Decimal::from_str(&clean_number(value, locale)).ok()
+}
+
+fn parse_number(value: &str, locale: &LocaleFormat) -> Option<f64> {
+    clean_number(value, locale).parse().ok()
+}
+
+// Model names differ by vendor prefix and spacing ("NVIDIA GeForce RTX 4090" vs "RTX-4090"), so
+// they are compared without those.
+fn normalize_model(model: &str) -> String {
+    model.to_lowercase().replace("nvidia", "").replace("geforce", "").chars().filter(char::is_ascii_alphanumeric).collect()
+}
+
+fn parse_earnings(source: ImportSource, contents: &str, locale: &LocaleFormat) -> Result<(Vec<ImportedEarning>, EarningsImportSummary), String> {
+    let mut reader = csv::ReaderBuilder::new().flexible(true).from_reader(contents.as_bytes());
+    let headers = reader.headers().map_err(|e| format!("Failed to read CSV header: {}", e))?.clone();
+    let date_col = column(&headers, source.date_columns()).ok_or_else(|| format!("No date column found; expected one of: {}", source.date_columns().join(", ")))?;
+    let amount_col = column(&headers, source.amount_columns()).ok_or_else(|| format!("No earnings column found; expected one of: {}", source.amount_columns().join(", ")))?;
+    let machine_col = column(&headers, MACHINE_COLUMNS);
+    let gpu_col = column(&headers, GPU_COLUMNS);
+    let hours_col = column(&headers, HOURS_COLUMNS);
+    let currency_col = column(&headers, CURRENCY_COLUMNS);
+
+    let mut rows = Vec::new();
+    let mut summary = EarningsImportSummary::default();
+    for (line, record) in reader.records().enumerate() {
+        summary.rows_read += 1;
+        // Header is line 1.
+        let line = line + 2;
+        let parsed = record.map_err(|e| e.to_string()).and_then(|record| {
+            let date = field(&record, Some(date_col)).ok_or("missing date")?;
+            let earned_at = parse_date(date, locale).ok_or_else(|| format!("unrecognised date '{}'", date))?;
+            let amount = field(&record, Some(amount_col)).ok_or("missing earnings")?;
+            let amount = parse_amount(amount, locale).ok_or_else(|| format!("unrecognised amount '{}'", amount))?;
+            let raw = record.iter().collect::<Vec<_>>().join("\u{1f}");
+            let row_key = hex::encode(Sha256::digest(format!("{}\u{1f}{}", line, raw).as_bytes()));
+            Ok(ImportedEarning {
+                source: source.as_str().to_string(),
+                row_key,
+                earned_at,
+                machine: field(&record, machine_col).map(str::to_string),
+                gpu_model: field(&record, gpu_col).map(str::to_string),
+                gpu_hours: field(&record, hours_col).and_then(|hours| parse_number(hours, locale)),
+                amount,
+                currency: field(&record, currency_col).unwrap_or("USD").to_uppercase(),
+            })
+        });
+        match parsed {
+            Ok(row) => {
+                summary.first_earned_at = Some(summary.first_earned_at.map_or(row.earned_at, |t| t.min(row.earned_at)));
+                summary.last_earned_at = Some(summary.last_earned_at.map_or(row.earned_at, |t| t.max(row.earned_at)));
+                rows.push(row);
+            }
+            Err(e) => {
+                summary.rows_failed += 1;
+                if summary.errors.len() < MAX_REPORTED_ERRORS {
+                    summary.errors.push(format!("Line {}: {}", line, e));
+                }
+            }
+        }
+    }
+    Ok((rows, summary))
+}
+
+fn lookup<'a>(machine: &'a Value, names: &[&str]) -> Option<&'a Value> {
+    let object = machine.as_object()?;
+    names.iter().find_map(|name| object.iter().find(|(key, _)| key.eq_ignore_ascii_case(name)).map(|(_, v)| v))
+}
+
+// JSON numbers in strings are written with a decimal point whatever the locale.
+fn lookup_f64(machine: &Value, names: &[&str]) -> Option<f64> {
+    match lookup(machine, names)? {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => parse_number(s, &locale::for_tag("en-US").ok()?),
+        _ => None,
+    }
+}
+
+// Accepts a single machine, an array of machines, or an object with a "machines" array.
+fn machines(config: &Value) -> Vec<&Value> {
+    match config {
+        Value::Array(items) => items.iter().collect(),
+        Value::Object(object) => match object.get("machines") {
+            Some(Value::Array(items)) => items.iter().collect(),
+            _ => vec![config],
+        },
+        _ => Vec::new(),
+    }
+}
+
+#[tauri::command]
+pub async fn import_earnings_csv(app_handle: AppHandle, source: ImportSource, path: String, locale: Option<String>) -> Result<EarningsImportSummary, String> {
+    let storage = app_handle.try_state::<Storage>().ok_or_else(|| "Local storage is unavailable.".to_string())?;
+    let locale = match locale {
+        Some(tag) => locale::for_tag(&tag)?,
+        None => locale::resolve(&app_handle),
+    };
+    let contents = fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let (rows, mut summary) = parse_earnings(source, &contents, &locale)?;
+    summary.rows_imported = storage.insert_imported_earnings(&rows)?;
+    summary.rows_already_imported = rows.len() - summary.rows_imported;
+    emit_log_entry(
+        &app_handle,
+        "status",
+        format!(
+            "Imported {} earnings rows from {} ({} already imported, {} failed).",
+            summary.rows_imported,
+            source.as_str(),
+            summary.rows_already_imported,
+            summary.rows_failed
+        ),
+    );
+    Ok(summary)
+}
+
+#[tauri::command]
+pub async fn get_imported_earnings(app_handle: AppHandle) -> Result<Vec<ImportedEarningsMonth>, String> {
+    let storage = app_handle.try_state::<Storage>().ok_or_else(|| "Local storage is unavailable.".to_string())?;
+    storage.imported_earnings_by_month()
+}
+
+#[tauri::command]
+pub async fn suggest_settings_from_config(app_handle: AppHandle, source: ImportSource, path: String) -> Result<SuggestedSettings, String> {
+    let contents = fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let config: Value = serde_json::from_str(&contents).map_err(|e| format!("{} is not a JSON machine config: {}", path, e))?;
+    let machines = machines(&config);
+    if machines.is_empty() {
+        return Err("No machines found in the config export.".to_string());
+    }
+
+    let local_gpus = get_detected_gpus(app_handle.clone()).await?;
+    let market = app_handle.state::<MarketState>();
+    let mut gpu_rates: Vec<SuggestedRate> = Vec::new();
+    let mut unmatched_models = Vec::new();
+    let mut notes = Vec::new();
+    let mut imported_gpu_count = 0;
+    let mut min_hours: Option<f64> = None;
+
+    for machine in &machines {
+        let Some(model) = lookup(machine, CONFIG_GPU_FIELDS).and_then(Value::as_str) else {
+            notes.push("Skipped a machine without a GPU model.".to_string());
+            continue;
+        };
+        imported_gpu_count += lookup_f64(machine, CONFIG_COUNT_FIELDS).map_or(1, |n| n.max(1.0) as usize);
+        if let Some(hours) = lookup_f64(machine, CONFIG_MIN_HOURS_FIELDS) {
+            min_hours = Some(min_hours.map_or(hours, |h| h.min(hours)));
+        }
+        let currency = lookup(machine, CONFIG_CURRENCY_FIELDS).and_then(Value::as_str).unwrap_or("USD").to_uppercase();
+        let rate = lookup_f64(machine, CONFIG_RATE_FIELDS);
+        let wanted = normalize_model(model);
+        let matches: Vec<_> = local_gpus
+            .iter()
+            .filter(|gpu| !wanted.is_empty() && (normalize_model(&gpu.model) == wanted || normalize_model(&gpu.name) == wanted))
+            .filter(|gpu| !gpu_rates.iter().any(|r| r.gpu_id == gpu.id))
+            .collect();
+        if matches.is_empty() {
+            unmatched_models.push(model.to_string());
+            continue;
+        }
+        for gpu in matches {
+            gpu_rates.push(SuggestedRate {
+                gpu_id: gpu.id.clone(),
+                gpu_model: gpu.model.clone(),
+                imported_model: model.to_string(),
+                imported_rate: rate,
+                imported_currency: currency.clone(),
+                suggested_rate_dgpu: market.floor_for(&gpu.model).and_then(|f| f.median_dgpu_per_hour.or(Some(f.floor_dgpu_per_hour))),
+            });
+        }
+    }
+
+    // Daemon settings are only suggested when the daemon can report the current ones.
+    let provider_settings = match invoke_daemon_cli_json_output::<ProviderSettings>(&app_handle, &["--get-settings-json"]).await {
+        Ok(mut settings) => {
+            if let Some(hours) = min_hours {
+                settings.min_job_duration_minutes = (hours * 60.0).round() as u32;
+            }
+            settings.max_concurrent_jobs = settings.max_concurrent_jobs.max(imported_gpu_count.min(local_gpus.len()) as u32);
+            Some(settings)
+        }
+        Err(e) => {
+            notes.push(format!("Provider settings weren't suggested because the daemon is unavailable: {}", e));
+            None
+        }
+    };
+    if gpu_rates.iter().any(|r| r.imported_rate.is_some()) {
+        notes.push("Imported prices are shown for reference only; suggested rates come from the Dante market median.".to_string());
+    }
+
+    Ok(SuggestedSettings { source, machines_read: machines.len(), provider_settings, gpu_rates, unmatched_models, notes })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn locale(tag: &str) -> LocaleFormat {
+        locale::for_tag(tag).unwrap()
+    }
+
+    #[test]
+    fn amounts_use_the_locale_decimal_separator() {
+        assert_eq!(parse_amount("$1,234.50", &locale("en-US")), Some(Decimal::new(123450, 2)));
+        assert_eq!(parse_amount("1.234,50 €", &locale("de")), Some(Decimal::new(123450, 2)));
+        assert_eq!(parse_amount("0,75", &locale("fr")), Some(Decimal::new(75, 2)));
+        assert_eq!(parse_amount("-12.5", &locale("en-GB")), Some(Decimal::new(-125, 1)));
+        assert_eq!(parse_amount("n/a", &locale("en-US")), None);
+    }
+
+    #[test]
+    fn day_and_month_order_follow_the_locale() {
+        // 2024-03-04T00:00:00Z
+        let march_4 = 1_709_510_400;
+        assert_eq!(parse_date("3/4/2024", &locale("en-US")), Some(march_4));
+        assert_eq!(parse_date("04/03/2024", &locale("en-GB")), Some(march_4));
+        assert_eq!(parse_date("04.03.2024", &locale("de")), Some(march_4));
+        assert_eq!(parse_date("04.03.2024 13:30", &locale("de")), Some(march_4 + 13 * 3600 + 30 * 60));
+    }
+
+    #[test]
+    fn iso_dates_and_unix_seconds_ignore_the_locale() {
+        let march_4 = 1_709_510_400;
+        for tag in ["en-US", "de"] {
+            assert_eq!(parse_date("2024-03-04", &locale(tag)), Some(march_4));
+            assert_eq!(parse_date("2024-03-04T00:00:00Z", &locale(tag)), Some(march_4));
+            assert_eq!(parse_date("1709510400", &locale(tag)), Some(march_4));
+        }
+        assert_eq!(parse_date("March 4", &locale("en-US")), None);
+    }
+
+    #[test]
+    fn models_match_without_vendor_prefix_or_spacing() {
+        assert_eq!(normalize_model("NVIDIA GeForce RTX 4090"), normalize_model("RTX-4090"));
+        assert_ne!(normalize_model("RTX 4090"), normalize_model("RTX 4090 D"));
+        assert_eq!(normalize_model("NVIDIA"), "");
+    }
+
+    #[test]
+    fn identical_rows_are_kept_apart() {
+        let csv = "date,earnings\n2024-03-04,1.00\n2024-03-04,1.00\n";
+        let (rows, summary) = parse_earnings(ImportSource::Other, csv, &locale("en-US")).unwrap();
+        assert_eq!(summary.rows_read, 2);
+        assert_ne!(rows[0].row_key, rows[1].row_key);
+    }
+}
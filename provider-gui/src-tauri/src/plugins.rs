@@ -0,0 +1,254 @@
+// Third-party plugin host.
+//
+// Plugins live in `<app data>/plugins/<name>/` with a `plugin.json` manifest next to an
+// executable. The executable is invoked with the contribution point as its only argument,
+// receives a JSON request on stdin and answers with JSON on stdout. A plugin only runs once the
+// user has enabled it, and only receives the data and events its manifest asks for.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+
+use crate::app_settings::{self, AppSettingsState};
+use crate::hooks::run_script;
+use crate::{emit_log_entry, get_detected_gpus, schedule, subscriptions, GpuInfo};
+
+const MANIFEST_FILE_NAME: &str = "plugin.json";
+const PLUGIN_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Contribution {
+    TelemetrySource,
+    NotificationSink,
+    PricingStrategy,
+}
+
+impl Contribution {
+    fn as_arg(&self) -> &'static str {
+        match self {
+            Contribution::TelemetrySource => "telemetry_source",
+            Contribution::NotificationSink => "notification_sink",
+            Contribution::PricingStrategy => "pricing_strategy",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Permission {
+    ReadGpuInfo,    // GPU inventory and telemetry in requests
+    ReadFinancials, // earnings and payout payloads
+    Environment,    // inherit the GUI's environment variables instead of a minimal set
+    Network,        // declared for user consent; not enforceable from here
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PluginManifest {
+    pub id: String,
+    pub name: String,
+    pub version: String,
+    #[serde(default)]
+    pub description: String,
+    pub executable: String, // relative to the plugin directory
+    pub contributes: Vec<Contribution>,
+    #[serde(default)]
+    pub permissions: Vec<Permission>,
+    // Lifecycle events a notification sink receives (e.g. "job_completed"); nothing else is sent.
+    #[serde(default)]
+    pub events: Vec<String>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct PluginInfo {
+    pub directory: String,
+    pub manifest: Option<PluginManifest>,
+    pub enabled: bool,
+    pub error: Option<String>, // Manifest problems, so broken plugins are still listed
+}
+
+struct LoadedPlugin {
+    executable: PathBuf,
+    manifest: PluginManifest,
+}
+
+impl LoadedPlugin {
+    fn has(&self, permission: Permission) -> bool {
+        self.manifest.permissions.contains(&permission)
+    }
+
+    fn invoke(&self, contribution: Contribution, request: &Value) -> Result<Value, String> {
+        let output = run_script(
+            &self.executable.to_string_lossy(),
+            &[contribution.as_arg().to_string()],
+            &request.to_string(),
+            PLUGIN_TIMEOUT,
+            !self.has(Permission::Environment),
+        )?;
+        if output.stdout.trim().is_empty() {
+            return Ok(Value::Null);
+        }
+        serde_json::from_str(&output.stdout).map_err(|e| format!("invalid JSON from plugin: {}", e))
+    }
+}
+
+fn plugins_dir(app_handle: &AppHandle) -> Option<PathBuf> {
+    app_handle.path_resolver().app_data_dir().map(|dir| dir.join("plugins"))
+}
+
+fn load_manifest(dir: &Path) -> Result<LoadedPlugin, String> {
+    let contents = fs::read_to_string(dir.join(MANIFEST_FILE_NAME))
+        .map_err(|e| format!("Failed to read {}: {}", MANIFEST_FILE_NAME, e))?;
+    let manifest: PluginManifest = serde_json::from_str(&contents)
+        .map_err(|e| format!("Invalid {}: {}", MANIFEST_FILE_NAME, e))?;
+    // Keep plugins from pointing at arbitrary binaries elsewhere on the system.
+    let relative = Path::new(&manifest.executable);
+    if relative.components().any(|c| !matches!(c, Component::Normal(_))) {
+        return Err(format!("Executable '{}' must be a relative path inside the plugin directory.", manifest.executable));
+    }
+    Ok(LoadedPlugin {
+        executable: dir.join(relative),
+        manifest,
+    })
+}
+
+fn discover(app_handle: &AppHandle) -> Vec<(PathBuf, Result<LoadedPlugin, String>)> {
+    let Some(dir) = plugins_dir(app_handle) else { return Vec::new() };
+    let Ok(entries) = fs::read_dir(&dir) else { return Vec::new() };
+    let mut plugins: Vec<_> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .map(|path| {
+            let loaded = load_manifest(&path);
+            (path, loaded)
+        })
+        .collect();
+    plugins.sort_by(|a, b| a.0.cmp(&b.0));
+    plugins
+}
+
+fn enabled_plugins(app_handle: &AppHandle, contribution: Contribution) -> Vec<LoadedPlugin> {
+    let enabled = app_settings::current(app_handle).enabled_plugins;
+    discover(app_handle)
+        .into_iter()
+        .filter_map(|(_, loaded)| loaded.ok())
+        .filter(|p| enabled.contains(&p.manifest.id) && p.manifest.contributes.contains(&contribution))
+        .collect()
+}
+
+// Runs every enabled telemetry source and emits its output as a `plugin_telemetry` event.
+pub async fn collect_telemetry(app_handle: &AppHandle, gpus: &[GpuInfo]) {
+    for plugin in enabled_plugins(app_handle, Contribution::TelemetrySource) {
+        let request = if plugin.has(Permission::ReadGpuInfo) { json!({ "gpus": gpus }) } else { json!({}) };
+        let id = plugin.manifest.id.clone();
+        let result = tauri::async_runtime::spawn_blocking(move || plugin.invoke(Contribution::TelemetrySource, &request))
+            .await
+            .map_err(|e| e.to_string())
+            .and_then(|r| r);
+        match result {
+            Ok(data) => {
+                if let Err(e) = subscriptions::emit(app_handle, "plugin_telemetry", json!({ "plugin_id": id, "data": data })) {
+                    eprintln!("Failed to emit plugin_telemetry event: {}", e);
+                }
+            }
+            Err(e) => emit_log_entry(app_handle, "error", format!("Telemetry plugin '{}' failed: {}", id, e)),
+        }
+    }
+}
+
+// Forwards a lifecycle event to every enabled notification sink in the background.
+pub fn notify(app_handle: &AppHandle, event: &str, payload: &Value, contains_financials: bool) {
+    if schedule::quiet_hours_active(app_handle) && !schedule::URGENT_EVENTS.contains(&event) {
+        return;
+    }
+    for plugin in enabled_plugins(app_handle, Contribution::NotificationSink) {
+        if !plugin.manifest.events.iter().any(|e| e == event) || (contains_financials && !plugin.has(Permission::ReadFinancials)) {
+            continue;
+        }
+        let app_handle = app_handle.clone();
+        let request = json!({ "event": event, "payload": payload });
+        tauri::async_runtime::spawn_blocking(move || {
+            if let Err(e) = plugin.invoke(Contribution::NotificationSink, &request) {
+                emit_log_entry(&app_handle, "error", format!("Notification plugin '{}' failed: {}", plugin.manifest.id, e));
+            }
+        });
+    }
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct PluginPriceSuggestion {
+    pub plugin_id: String,
+    pub suggestion: Value,
+}
+
+#[tauri::command]
+pub async fn list_plugins(app_handle: AppHandle) -> Result<Vec<PluginInfo>, String> {
+    let enabled = app_settings::current(&app_handle).enabled_plugins;
+    Ok(discover(&app_handle)
+        .into_iter()
+        .map(|(dir, loaded)| match loaded {
+            Ok(plugin) => PluginInfo {
+                directory: dir.to_string_lossy().to_string(),
+                enabled: enabled.contains(&plugin.manifest.id),
+                manifest: Some(plugin.manifest),
+                error: None,
+            },
+            Err(e) => PluginInfo {
+                directory: dir.to_string_lossy().to_string(),
+                manifest: None,
+                enabled: false,
+                error: Some(e),
+            },
+        })
+        .collect())
+}
+
+#[tauri::command]
+pub async fn enable_plugin(app_handle: AppHandle, state: State<'_, AppSettingsState>, plugin_id: String, enabled: bool) -> Result<(), String> {
+    let known = discover(&app_handle)
+        .into_iter()
+        .any(|(_, loaded)| loaded.map(|p| p.manifest.id == plugin_id).unwrap_or(false));
+    if enabled && !known {
+        return Err(format!("Plugin '{}' not found or has an invalid manifest.", plugin_id));
+    }
+
+    let mut settings = state.get();
+    settings.enabled_plugins.retain(|id| id != &plugin_id);
+    if enabled {
+        settings.enabled_plugins.push(plugin_id.clone());
+    }
+    app_settings::update_app_settings(app_handle.clone(), state, settings).await?;
+    emit_log_entry(&app_handle, "status", format!("Plugin '{}' {}.", plugin_id, if enabled { "enabled" } else { "disabled" }));
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_plugin_price_suggestions(app_handle: AppHandle, gpu_id: String) -> Result<Vec<PluginPriceSuggestion>, String> {
+    let gpu = get_detected_gpus(app_handle.clone())
+        .await?
+        .into_iter()
+        .find(|g| g.id == gpu_id)
+        .ok_or_else(|| format!("GPU '{}' not found.", gpu_id))?;
+
+    let mut suggestions = Vec::new();
+    for plugin in enabled_plugins(&app_handle, Contribution::PricingStrategy) {
+        if !plugin.has(Permission::ReadGpuInfo) {
+            continue;
+        }
+        let id = plugin.manifest.id.clone();
+        let request = json!({ "gpu": gpu });
+        let result = tauri::async_runtime::spawn_blocking(move || plugin.invoke(Contribution::PricingStrategy, &request))
+            .await
+            .map_err(|e| e.to_string())
+            .and_then(|r| r);
+        match result {
+            Ok(suggestion) => suggestions.push(PluginPriceSuggestion { plugin_id: id, suggestion }),
+            Err(e) => emit_log_entry(&app_handle, "error", format!("Pricing plugin '{}' failed: {}", id, e)),
+        }
+    }
+    Ok(suggestions)
+}
@@ -0,0 +1,315 @@
+// Background polling of daemon telemetry and jobs.
+//
+// The cadence adapts to window visibility: while the main window is visible we poll
+// frequently so the dashboard feels live, and when it is minimized or hidden to the tray
+// we drop to a low-power cadence to avoid CPU wakeups and daemon CLI churn on laptops.
+// Recent GPU samples are kept in memory for the dashboard charts unless lightweight mode is on.
+
+use serde::Serialize;
+use serde_json::json;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State, Window, WindowEvent};
+use tokio::sync::Notify;
+
+use crate::analytics;
+use crate::attestation;
+use crate::failover;
+use crate::gpu_health;
+use crate::gpu_ids;
+use crate::gpu_processes;
+use crate::job_policy;
+use crate::job_queue;
+use crate::link_health;
+use crate::nvidia_smi;
+use crate::rejections;
+use crate::hooks::{self, HookEvent};
+use crate::money::Decimal;
+use crate::storage::Storage;
+use crate::wal::{self, WalEventKind};
+use crate::watchdog::{self, Heartbeat};
+use crate::zombie_jobs;
+use crate::{a11y, app_settings, emit_log_entry, get_timestamp, plugins, subscriptions, invoke_daemon_cli_json_output, shutdown, DaemonState, FinancialSummary, GpuInfo, LocalJob};
+
+const FOREGROUND_POLL_INTERVAL: Duration = Duration::from_secs(5);
+const BACKGROUND_POLL_INTERVAL: Duration = Duration::from_secs(60);
+// Lightweight mode trades dashboard freshness for fewer wakeups next to heavy training jobs.
+const LIGHTWEIGHT_FOREGROUND_POLL_INTERVAL: Duration = Duration::from_secs(15);
+const LIGHTWEIGHT_BACKGROUND_POLL_INTERVAL: Duration = Duration::from_secs(300);
+// A poll makes several daemon CLI calls, none of which has a timeout of its own; one taking
+// longer than this is treated as stuck by the watchdog.
+const POLL_GRACE: Duration = Duration::from_secs(120);
+
+// One hour of samples at the foreground cadence.
+const TELEMETRY_HISTORY_CAPACITY: usize = 720;
+
+// A GPU is reported overheated once it crosses the upper threshold and re-armed only after it
+// cools below the lower one, so a card hovering around the limit doesn't spam hooks.
+const OVERHEAT_THRESHOLD_C: u32 = 85;
+const OVERHEAT_CLEAR_C: u32 = 80;
+
+#[derive(Serialize, Clone)]
+pub struct TelemetrySample {
+    pub timestamp: String,
+    pub gpus: Vec<GpuInfo>,
+}
+
+pub struct PollingState {
+    foreground: AtomicBool,
+    wake: Notify,
+    history: Mutex<VecDeque<TelemetrySample>>,
+    // Previous observations, used to turn snapshots into lifecycle events for hooks.
+    job_statuses: Mutex<Option<HashMap<String, String>>>,
+    overheated_gpus: Mutex<HashSet<String>>,
+    last_payout_at: Mutex<Option<String>>,
+    last_total_earned: Mutex<Option<Decimal>>,
+}
+
+impl PollingState {
+    pub fn new() -> Self {
+        PollingState {
+            foreground: AtomicBool::new(true),
+            wake: Notify::new(),
+            history: Mutex::new(VecDeque::new()),
+            job_statuses: Mutex::new(None),
+            overheated_gpus: Mutex::new(HashSet::new()),
+            last_payout_at: Mutex::new(None),
+            last_total_earned: Mutex::new(None),
+        }
+    }
+
+    fn is_foreground(&self) -> bool {
+        self.foreground.load(Ordering::Relaxed)
+    }
+
+    fn interval(&self, lightweight: bool) -> Duration {
+        match (self.is_foreground(), lightweight) {
+            (true, false) => FOREGROUND_POLL_INTERVAL,
+            (false, false) => BACKGROUND_POLL_INTERVAL,
+            (true, true) => LIGHTWEIGHT_FOREGROUND_POLL_INTERVAL,
+            (false, true) => LIGHTWEIGHT_BACKGROUND_POLL_INTERVAL,
+        }
+    }
+
+    fn record_sample(&self, gpus: &[GpuInfo]) {
+        let mut history = self.history.lock().unwrap();
+        history.push_back(TelemetrySample {
+            timestamp: get_timestamp(),
+            gpus: gpus.to_vec(),
+        });
+        while history.len() > TELEMETRY_HISTORY_CAPACITY {
+            history.pop_front();
+        }
+    }
+
+    pub fn overheated(&self) -> Vec<String> {
+        self.overheated_gpus.lock().unwrap().iter().cloned().collect()
+    }
+
+    fn set_foreground(&self, foreground: bool) {
+        let was_foreground = self.foreground.swap(foreground, Ordering::Relaxed);
+        // Ramp back up immediately instead of waiting out the long background sleep.
+        if foreground && !was_foreground {
+            self.wake.notify_one();
+        }
+    }
+}
+
+pub fn handle_window_event(window: &Window, event: &WindowEvent) {
+    if window.label() != "main" {
+        return;
+    }
+    let state = window.state::<PollingState>();
+    match event {
+        WindowEvent::Focused(true) => state.set_foreground(true),
+        WindowEvent::Focused(false) | WindowEvent::Resized(_) => {
+            // Losing focus to another app keeps the dashboard visible, so only a hidden
+            // (tray-only) or minimized window counts as background.
+            let hidden = !window.is_visible().unwrap_or(true) || window.is_minimized().unwrap_or(false);
+            state.set_foreground(!hidden);
+        }
+        _ => {}
+    }
+}
+
+pub fn spawn_poller(app_handle: AppHandle) {
+    watchdog::supervise(&app_handle, "telemetry_sampler", POLL_GRACE, run_poller);
+}
+
+async fn run_poller(app_handle: AppHandle, heartbeat: Heartbeat) {
+    loop {
+        heartbeat.beat();
+        let lightweight = app_settings::current(&app_handle).lightweight_mode;
+        let state = app_handle.state::<PollingState>();
+        let online = *app_handle.state::<DaemonState>().status.lock().unwrap() == "online";
+        // A hidden window in lightweight mode still polls, slowly: the fault, stall and payout
+        // detectors hang off this loop and must keep running while nobody is looking.
+        if online {
+            poll_once(&app_handle, lightweight).await;
+        }
+
+        let interval = state.interval(lightweight);
+        heartbeat.beat_within(interval);
+        tokio::select! {
+            _ = tokio::time::sleep(interval) => {}
+            _ = state.wake.notified() => {}
+            _ = shutdown::wait() => break,
+        }
+    }
+}
+
+async fn poll_once(app_handle: &AppHandle, lightweight: bool) {
+    let privacy = app_settings::current(app_handle).privacy;
+    let storage = if lightweight { None } else { app_handle.try_state::<Storage>() };
+    // Failures are already logged by the CLI helper; the next tick will retry.
+    let mut latest_gpus = None;
+    if let Ok(gpus) = nvidia_smi::detect_gpus(app_handle).await {
+        let state = app_handle.state::<PollingState>();
+        // GPUs read from nvidia-smi while the daemon is failing aren't telemetry of the rig's
+        // rentals (see `nvidia_smi`).
+        let measured: Vec<GpuInfo> = gpus.iter().filter(|g| !g.fallback_sourced).cloned().collect();
+        if lightweight {
+            state.history.lock().unwrap().clear();
+        } else {
+            state.record_sample(&measured);
+        }
+        if let (Some(storage), true) = (&storage, privacy.store_telemetry_history) {
+            if let Err(e) = storage.record_telemetry(&measured) {
+                eprintln!("{}", e);
+            }
+        }
+        detect_overheating(app_handle, &state, &gpus);
+        gpu_health::check_fans(app_handle, &gpus).await;
+        failover::check(app_handle).await;
+        if !lightweight {
+            plugins::collect_telemetry(app_handle, &measured).await;
+        }
+        latest_gpus = Some(gpus.clone());
+        if let Err(e) = subscriptions::emit(app_handle, "gpus_updated", gpus) {
+            eprintln!("Failed to emit gpus_updated event: {}", e);
+        }
+    }
+    if let Ok(mut jobs) = gpu_ids::local_jobs(app_handle).await {
+        let queued = detect_job_transitions(app_handle, &app_handle.state::<PollingState>(), &jobs);
+        for job in &queued {
+            gpu_processes::warn_if_contended(app_handle, job).await;
+        }
+        job_policy::enforce(app_handle, &jobs).await;
+        job_policy::collect_daemon_declines(app_handle).await;
+        rejections::collect_match_misses(app_handle).await;
+        job_queue::observe(app_handle, &jobs).await;
+        attestation::observe(app_handle, &jobs);
+        if let Some(gpus) = &latest_gpus {
+            analytics::check_unexplained_load(app_handle, gpus, &jobs);
+            link_health::observe(app_handle, gpus, &jobs);
+            zombie_jobs::observe(app_handle, gpus, &jobs);
+        }
+        if let (Some(storage), true) = (&storage, privacy.store_job_metadata) {
+            if let Err(e) = storage.upsert_jobs(&jobs, &privacy) {
+                eprintln!("{}", e);
+            }
+        }
+        attestation::annotate(app_handle, &mut jobs);
+        if let Err(e) = subscriptions::emit(app_handle, "jobs_updated", jobs) {
+            eprintln!("Failed to emit jobs_updated event: {}", e);
+        }
+    }
+    if let Ok(summary) = invoke_daemon_cli_json_output::<FinancialSummary>(app_handle, &["--get-financial-summary-json"]).await {
+        detect_payout(app_handle, &app_handle.state::<PollingState>(), &summary);
+        detect_earnings(app_handle, &app_handle.state::<PollingState>(), &summary);
+        if let Err(e) = subscriptions::emit(app_handle, "financial_summary_updated", summary) {
+            eprintln!("Failed to emit financial_summary_updated event: {}", e);
+        }
+    }
+}
+
+fn detect_overheating(app_handle: &AppHandle, state: &PollingState, gpus: &[GpuInfo]) {
+    let mut overheated = state.overheated_gpus.lock().unwrap();
+    for gpu in gpus {
+        let Some(temperature) = gpu.temperature_c else { continue };
+        if temperature >= OVERHEAT_THRESHOLD_C && overheated.insert(gpu.id.clone()) {
+            hooks::fire(app_handle, HookEvent::GpuOverheated, json!({ "gpu": gpu, "threshold_c": OVERHEAT_THRESHOLD_C }));
+        } else if temperature < OVERHEAT_CLEAR_C {
+            overheated.remove(&gpu.id);
+        }
+    }
+}
+
+// Returns jobs that newly entered the queue, so callers can check the rig before they start.
+fn detect_job_transitions(app_handle: &AppHandle, state: &PollingState, jobs: &[LocalJob]) -> Vec<LocalJob> {
+    let mut newly_queued = Vec::new();
+    let mut guard = state.job_statuses.lock().unwrap();
+    // The first snapshot only seeds the map; jobs that finished before we started watching
+    // must not trigger hooks. Those the ledger is missing (completed while the GUI was down)
+    // are still recorded.
+    let seeded = guard.is_some();
+    let statuses = guard.get_or_insert_with(HashMap::new);
+    let mut reconciled = 0;
+    for job in jobs {
+        let previous = statuses.insert(job.id.clone(), job.status.clone());
+        let newly_completed = job.status == "completed" && previous.as_deref() != Some("completed");
+        if !seeded && newly_completed && !wal::is_recorded(app_handle, WalEventKind::JobCompleted, &job.id) && record_completion(app_handle, job) {
+            reconciled += 1;
+        }
+        if seeded && newly_completed {
+            record_completion(app_handle, job);
+            hooks::fire(app_handle, HookEvent::JobCompleted, json!({ "job": job }));
+            a11y::announce(app_handle, "job", a11y::Severity::Success, format!("Job {} completed.", job.name));
+        }
+        if seeded && job.status == "failed" && previous.as_deref() != Some("failed") {
+            a11y::announce(app_handle, "job", a11y::Severity::Warning, format!("Job {} failed.", job.name));
+        }
+        if seeded && previous.is_none() && job.status == "queued" {
+            newly_queued.push(job.clone());
+        }
+    }
+    if reconciled > 0 {
+        emit_log_entry(app_handle, "status", format!("Recorded {} job completion(s) missed while the app was not running.", reconciled));
+    }
+    newly_queued
+}
+
+fn record_completion(app_handle: &AppHandle, job: &LocalJob) -> bool {
+    wal::record(app_handle, WalEventKind::JobCompleted, job.id.clone(), job.estimated_cost_dgpu, job.gpu_id.as_deref(), json!({ "job": job }))
+}
+
+fn detect_payout(app_handle: &AppHandle, state: &PollingState, summary: &FinancialSummary) {
+    let mut last_payout_at = state.last_payout_at.lock().unwrap();
+    if summary.last_payout_at.is_some() && *last_payout_at != summary.last_payout_at {
+        let key = summary.last_payout_at.clone().unwrap_or_default();
+        // As with jobs, the first observed payout is historical: recorded if the ledger is
+        // missing it, but without hooks.
+        if last_payout_at.is_some() {
+            wal::record(app_handle, WalEventKind::PayoutReceived, key, None, None, json!({ "summary": summary }));
+            hooks::fire(app_handle, HookEvent::PayoutReceived, json!({ "summary": summary }));
+        } else if !wal::is_recorded(app_handle, WalEventKind::PayoutReceived, &key) {
+            wal::record(app_handle, WalEventKind::PayoutReceived, key, None, None, json!({ "summary": summary }));
+        }
+        *last_payout_at = summary.last_payout_at.clone();
+    }
+}
+
+fn detect_earnings(app_handle: &AppHandle, state: &PollingState, summary: &FinancialSummary) {
+    let mut last_total = state.last_total_earned.lock().unwrap();
+    match *last_total {
+        Some(previous) if previous != summary.total_earned_dgpu => {
+            wal::record(
+                app_handle,
+                WalEventKind::EarningsUpdated,
+                summary.total_earned_dgpu.to_string(),
+                Some(summary.total_earned_dgpu - previous),
+                None,
+                json!({ "previous_total_earned_dgpu": previous, "summary": summary }),
+            );
+        }
+        _ => {}
+    }
+    *last_total = Some(summary.total_earned_dgpu);
+}
+
+#[tauri::command]
+pub async fn get_telemetry_history(state: State<'_, PollingState>) -> Result<Vec<TelemetrySample>, String> {
+    Ok(state.history.lock().unwrap().iter().cloned().collect())
+}
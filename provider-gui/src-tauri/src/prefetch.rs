@@ -0,0 +1,193 @@
+// Pre-pulling popular job images so rentals don't start with a long image download.
+//
+// The platform publishes the base images most jobs use; pulling them ahead of time, together
+// with any the provider lists in `PrefetchPolicy::images`, cuts the cold start renters see and
+// that matchmaking penalises. Pulls go through the daemon, which owns the container runtime,
+// and run as a background task (see `tasks`). The scheduler only starts one while no job is
+// running, inside the configured schedule, at most once a day, and not when the data cap is
+// close; a prefetch resumed after a restart checks the same again. Pulls stop at the image storage budget (see `image_cache`); an image that pushes usage
+// over it is removed again.
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::AppHandle;
+
+use crate::image_cache::{self, CachedImage};
+use crate::schedule::{self, ZonedSchedule};
+use crate::tasks::{self, TaskContext, TaskRequest};
+use crate::{api_client, app_settings, data_cap, emit_log_entry, get_local_jobs, invoke_daemon_cli_json_output, shutdown};
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(30 * 60);
+const MIN_HOURS_BETWEEN_RUNS: i64 = 24;
+
+// When the last scheduled prefetch started, as unix seconds.
+static LAST_SCHEDULED_RUN: Mutex<Option<i64>> = Mutex::new(None);
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct PrefetchPolicy {
+    pub enabled: bool, // Scheduled prefetching; `prefetch_images` works either way
+    pub popular_count: usize,
+    pub images: Vec<String>, // Always included, e.g. images of the provider's own templates
+    pub schedule: Option<ZonedSchedule>, // Whenever the rig is idle when unset
+}
+
+impl Default for PrefetchPolicy {
+    fn default() -> Self {
+        PrefetchPolicy { enabled: false, popular_count: 5, images: Vec::new(), schedule: None }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct PopularImage {
+    image: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PrefetchedImage {
+    pub image: String,
+    pub pulled: bool,
+    pub size_bytes: Option<u64>,
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct PrefetchReport {
+    pub images: Vec<PrefetchedImage>,
+    pub used_bytes: u64,
+    pub budget_bytes: Option<u64>,
+    pub stopped_at_budget: bool,
+}
+
+// The provider's own images first, then the platform's most used ones.
+async fn default_images(app_handle: &AppHandle) -> Result<Vec<String>, String> {
+    let policy = app_settings::current(app_handle).prefetch;
+    let path = format!("/api/v1/market/popular-images?limit={}", policy.popular_count);
+    let popular: Vec<PopularImage> = api_client::get_public_json(app_handle, &path).await?;
+    let mut images = policy.images;
+    for image in popular.into_iter().map(|p| p.image) {
+        if !images.contains(&image) {
+            images.push(image);
+        }
+    }
+    Ok(images)
+}
+
+pub async fn pull_images(app_handle: &AppHandle, task: &TaskContext, images: Vec<String>) -> Result<PrefetchReport, String> {
+    if task.resumed() {
+        if let Some(reason) = held_back_by(app_handle).await? {
+            return Err(format!("Not resumed after the restart: {}", reason));
+        }
+    }
+    let budget_bytes = image_cache::budget_bytes(app_handle);
+    let cached = image_cache::list(app_handle).await?;
+    let mut report = PrefetchReport { used_bytes: cached.iter().map(|c| c.size_bytes).sum(), budget_bytes, ..Default::default() };
+
+    for (i, image) in images.iter().enumerate() {
+        if cached.iter().any(|c| &c.image == image) {
+            continue;
+        }
+        if budget_bytes.is_some_and(|b| report.used_bytes >= b) {
+            report.stopped_at_budget = true;
+            break;
+        }
+        task.progress(Some(100.0 * i as f32 / images.len() as f32), format!("Pulling {}", image))?;
+        let payload = json!({ "image": image }).to_string();
+        let pulled = match invoke_daemon_cli_json_output::<CachedImage>(app_handle, &["--pull-image-json", &payload]).await {
+            Ok(pulled) => pulled,
+            Err(e) => {
+                report.images.push(PrefetchedImage { image: image.clone(), pulled: false, size_bytes: None, error: Some(e) });
+                continue;
+            }
+        };
+        if budget_bytes.is_some_and(|b| report.used_bytes + pulled.size_bytes > b) {
+            if let Err(e) = image_cache::remove(app_handle, &pulled.id).await {
+                emit_log_entry(app_handle, "error", format!("Couldn't remove {} after it went over the image storage budget: {}", image, e));
+                report.used_bytes += pulled.size_bytes;
+            }
+            report.images.push(PrefetchedImage {
+                image: image.clone(),
+                pulled: false,
+                size_bytes: Some(pulled.size_bytes),
+                error: Some("Over the image storage budget.".to_string()),
+            });
+            report.stopped_at_budget = true;
+            break;
+        }
+        image_cache::record_prefetched(app_handle, image);
+        report.used_bytes += pulled.size_bytes;
+        report.images.push(PrefetchedImage { image: image.clone(), pulled: true, size_bytes: Some(pulled.size_bytes), error: None });
+    }
+
+    let pulled = report.images.iter().filter(|i| i.pulled).count();
+    emit_log_entry(
+        app_handle,
+        "status",
+        format!("Prefetched {} image(s); images use {} MB.{}", pulled, report.used_bytes / (1024 * 1024), if report.stopped_at_budget { " Stopped at the storage budget." } else { "" }),
+    );
+    Ok(report)
+}
+
+// Why pulling shouldn't happen right now, if anything speaks against it.
+async fn held_back_by(app_handle: &AppHandle) -> Result<Option<String>, String> {
+    let policy = app_settings::current(app_handle).prefetch;
+    if !schedule::active(policy.schedule.as_ref(), true) {
+        return Ok(Some("outside the prefetch schedule".to_string()));
+    }
+    if get_local_jobs(app_handle.clone()).await?.iter().any(|j| j.status == "running") {
+        return Ok(Some("a job is running".to_string()));
+    }
+    // Pulls are gigabytes each; a metered connection near its cap needs them least.
+    let cap = data_cap::status(app_handle).await?;
+    if cap.enforced.is_some() || cap.cap_bytes.is_some_and(|c| cap.projected_bytes >= c) {
+        return Ok(Some("the data cap is close".to_string()));
+    }
+    Ok(None)
+}
+
+async fn should_run(app_handle: &AppHandle) -> Result<bool, String> {
+    let policy = app_settings::current(app_handle).prefetch;
+    let now = Utc::now().timestamp();
+    let recently = LAST_SCHEDULED_RUN.lock().unwrap().is_some_and(|at| now - at < MIN_HOURS_BETWEEN_RUNS * 60 * 60);
+    if !policy.enabled || recently {
+        return Ok(false);
+    }
+    Ok(held_back_by(app_handle).await?.is_none())
+}
+
+pub fn spawn_prefetch_scheduler(app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            if !shutdown::sleep(CHECK_INTERVAL).await {
+                break;
+            }
+            match should_run(&app_handle).await {
+                Ok(false) => {}
+                Ok(true) => match default_images(&app_handle).await {
+                    Ok(images) => {
+                        *LAST_SCHEDULED_RUN.lock().unwrap() = Some(Utc::now().timestamp());
+                        tasks::submit(&app_handle, TaskRequest::ImagePrefetch { images });
+                    }
+                    Err(e) => eprintln!("Image prefetch skipped: {}", e),
+                },
+                Err(e) => eprintln!("Image prefetch skipped: {}", e),
+            }
+        }
+    });
+}
+
+// Returns the task ID; without a list, the provider's images and the platform's popular ones.
+#[tauri::command]
+pub async fn prefetch_images(app_handle: AppHandle, images: Option<Vec<String>>) -> Result<String, String> {
+    let images = match images {
+        Some(images) => images.into_iter().map(|i| i.trim().to_string()).filter(|i| !i.is_empty()).collect(),
+        None => default_images(&app_handle).await?,
+    };
+    if images.is_empty() {
+        return Err("No images to prefetch.".to_string());
+    }
+    Ok(tasks::submit(&app_handle, TaskRequest::ImagePrefetch { images }))
+}
@@ -0,0 +1,547 @@
+// What a renter would pay for a job on one of this provider's GPUs, and what the provider
+// would net after platform fees.
+//
+// Uses the GPU's listed rate (or the provider's default), active promotional discounts, the
+// platform's current fee schedule and the minimum billable duration, so providers can see the
+// effect of a price change before making it. Amounts are decimals rounded per line by the
+// provider's rounding policy, so the itemized lines always add up to the totals.
+//
+// Discounts are validated here and registered with the platform through the daemon, which
+// also counts redemptions. Expired discounts are deactivated by an hourly sweep.
+//
+// Break-even rates: the lowest rate at which a card still covers its power while rented plus the
+// part of its purchase price not yet earned back, written off over the amortization period.
+//
+// Surge pricing raises a listed GPU's rate while the demand forecast for its model expects
+// notably more arrivals than an average hour, and puts the rate back once demand normalizes.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager};
+
+use crate::inventory::{self, InventoryItem};
+use crate::job_policy::JobCategory;
+use crate::market::MarketState;
+use crate::money::{self, Decimal};
+use crate::storage::{unix_now, Storage};
+use crate::units::DgpuPerHour;
+use crate::{analytics, api_client, app_settings, emit_log_entry, get_detected_gpus, get_provider_settings, invoke_daemon_cli_json_output, set_gpu_rental_config, shutdown, GpuInfo};
+
+const EXPIRY_SWEEP_INTERVAL: Duration = Duration::from_secs(60 * 60);
+const SURGE_CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+const SURGE_STATE_FILE_NAME: &str = "surge_state.json";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum DiscountKind {
+    // e.g. 20% off the first 10 hours of each rental.
+    FirstHours {
+        #[serde(with = "money::amount")]
+        hours: Decimal,
+    },
+    // Applies to jobs starting within [start_hour_utc, end_hour_utc); the window may wrap midnight.
+    OffPeak { start_hour_utc: u8, end_hour_utc: u8 },
+    // Applies to the whole rental.
+    Flat,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DiscountRule {
+    pub name: String,
+    #[serde(flatten)]
+    pub kind: DiscountKind,
+    #[serde(with = "money::amount")]
+    pub percent_off: Decimal,
+    #[serde(default)]
+    pub gpu_ids: Vec<String>, // Empty means every GPU
+    #[serde(default)]
+    pub workloads: Vec<JobCategory>, // Empty means every workload
+    pub expires_at: Option<String>,
+    pub max_redemptions: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Discount {
+    pub id: String,
+    pub rule: DiscountRule,
+    pub active: bool,
+    #[serde(default)]
+    pub redemptions: u32,
+    #[serde(default, with = "money::amount")]
+    pub discounted_dgpu: Decimal, // Total given away so far
+    pub created_at: String,
+}
+
+fn percent(value: Decimal) -> Decimal {
+    value / Decimal::ONE_HUNDRED
+}
+
+fn parse_unix(ts: &str) -> Option<u64> {
+    humantime::parse_rfc3339_weak(ts).ok().and_then(|t| t.duration_since(UNIX_EPOCH).ok()).map(|d| d.as_secs())
+}
+
+fn now_unix() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+impl DiscountRule {
+    fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("Discount name must not be empty.".to_string());
+        }
+        if !(self.percent_off > Decimal::ZERO && self.percent_off <= Decimal::from(90)) {
+            return Err("Discounts must be between 0% and 90% off.".to_string());
+        }
+        match self.kind {
+            DiscountKind::FirstHours { hours } if hours <= Decimal::ZERO => return Err("First-hours discounts need a positive number of hours.".to_string()),
+            DiscountKind::OffPeak { start_hour_utc, end_hour_utc } if start_hour_utc > 23 || end_hour_utc > 23 || start_hour_utc == end_hour_utc => {
+                return Err("Off-peak windows need distinct start and end hours between 0 and 23.".to_string())
+            }
+            _ => {}
+        }
+        if let Some(expires_at) = &self.expires_at {
+            let expires = parse_unix(expires_at).ok_or_else(|| format!("Invalid expiry time '{}'.", expires_at))?;
+            if expires <= now_unix() {
+                return Err("The expiry time is in the past.".to_string());
+            }
+        }
+        if self.max_redemptions == Some(0) {
+            return Err("Maximum redemptions must be at least 1.".to_string());
+        }
+        Ok(())
+    }
+}
+
+impl Discount {
+    pub fn is_expired(&self) -> bool {
+        let past_expiry = self.rule.expires_at.as_deref().and_then(parse_unix).is_some_and(|t| t <= now_unix());
+        past_expiry || self.rule.max_redemptions.is_some_and(|max| self.redemptions >= max)
+    }
+
+    // Amount taken off `gross` for a job of `billed_hours` starting now, if the discount applies.
+    fn reduction(&self, gpu_id: &str, workload: Option<JobCategory>, hourly_rate: Decimal, billed_hours: Decimal, gross: Decimal) -> Option<Decimal> {
+        let rule = &self.rule;
+        if !self.active || self.is_expired() {
+            return None;
+        }
+        if !rule.gpu_ids.is_empty() && !rule.gpu_ids.iter().any(|id| id == gpu_id) {
+            return None;
+        }
+        if !rule.workloads.is_empty() && !workload.is_some_and(|w| rule.workloads.contains(&w)) {
+            return None;
+        }
+        let discounted = match rule.kind {
+            DiscountKind::FirstHours { hours } => hourly_rate * hours.min(billed_hours),
+            DiscountKind::OffPeak { start_hour_utc, end_hour_utc } => {
+                let hour = ((now_unix() / 3600) % 24) as u8;
+                let in_window = if start_hour_utc < end_hour_utc {
+                    hour >= start_hour_utc && hour < end_hour_utc
+                } else {
+                    hour >= start_hour_utc || hour < end_hour_utc
+                };
+                if !in_window {
+                    return None;
+                }
+                gross
+            }
+            DiscountKind::Flat => gross,
+        };
+        Some(discounted * percent(rule.percent_off))
+    }
+}
+
+pub async fn list(app_handle: &AppHandle) -> Result<Vec<Discount>, String> {
+    invoke_daemon_cli_json_output(app_handle, &["--list-discounts-json"]).await
+}
+
+async fn deactivate_expired(app_handle: &AppHandle) -> Result<usize, String> {
+    let mut deactivated = 0;
+    for discount in list(app_handle).await?.into_iter().filter(|d| d.active && d.is_expired()) {
+        invoke_daemon_cli_json_output::<serde_json::Value>(app_handle, &["--deactivate-discount", "--discount-id", &discount.id]).await?;
+        emit_log_entry(app_handle, "status", format!("Discount '{}' expired and was deactivated.", discount.rule.name));
+        deactivated += 1;
+    }
+    Ok(deactivated)
+}
+
+pub fn spawn_discount_expiry(app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            if !shutdown::sleep(EXPIRY_SWEEP_INTERVAL).await {
+                break;
+            }
+            if let Err(e) = deactivate_expired(&app_handle).await {
+                eprintln!("Discount expiry sweep failed: {}", e);
+            }
+        }
+    });
+}
+
+#[tauri::command]
+pub async fn create_discount(app_handle: AppHandle, rule: DiscountRule) -> Result<Discount, String> {
+    rule.validate()?;
+    let rule_json = serde_json::to_string(&rule).map_err(|e| format!("Failed to serialize discount: {}", e))?;
+    let discount: Discount = invoke_daemon_cli_json_output(&app_handle, &["--create-discount-json", &rule_json]).await?;
+    emit_log_entry(&app_handle, "status", format!("Created discount '{}' ({}% off).", discount.rule.name, discount.rule.percent_off));
+    Ok(discount)
+}
+
+#[tauri::command]
+pub async fn list_discounts(app_handle: AppHandle) -> Result<Vec<Discount>, String> {
+    list(&app_handle).await
+}
+
+#[tauri::command]
+pub async fn deactivate_discount(app_handle: AppHandle, discount_id: String) -> Result<(), String> {
+    invoke_daemon_cli_json_output::<serde_json::Value>(&app_handle, &["--deactivate-discount", "--discount-id", &discount_id])
+        .await
+        .map(|_| ())
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FeeSchedule {
+    // Added on top of the provider's price and paid by the renter.
+    #[serde(with = "money::amount")]
+    pub renter_fee_percent: Decimal,
+    // Withheld from the provider's gross earnings.
+    #[serde(with = "money::amount")]
+    pub provider_commission_percent: Decimal,
+    #[serde(with = "money::amount")]
+    pub minimum_charge_dgpu: Decimal,
+}
+
+// Used when the platform's current schedule can't be fetched.
+fn default_fee_schedule() -> FeeSchedule {
+    FeeSchedule { renter_fee_percent: Decimal::new(25, 1), provider_commission_percent: Decimal::from(10), minimum_charge_dgpu: Decimal::new(1, 2) }
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct CostLine {
+    pub label: String,
+    #[serde(with = "money::amount")]
+    pub amount_dgpu: Decimal,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct JobCostEstimate {
+    pub gpu_id: String,
+    pub workload: Option<JobCategory>,
+    pub requested_hours: f32,
+    pub billed_hours: f32,
+    #[serde(with = "money::amount")]
+    pub hourly_rate_dgpu: Decimal,
+    #[serde(with = "money::amount")]
+    pub renter_total_dgpu: Decimal,
+    #[serde(with = "money::amount")]
+    pub provider_net_dgpu: Decimal,
+    pub lines: Vec<CostLine>, // Itemized, in the order they apply
+    pub fee_schedule: FeeSchedule,
+    pub fee_schedule_is_default: bool,
+}
+
+pub async fn fee_schedule(app_handle: &AppHandle) -> (FeeSchedule, bool) {
+    match api_client::get_public_json::<FeeSchedule>(app_handle, "/api/v1/market/fee-schedule").await {
+        Ok(schedule) => (schedule, false),
+        Err(_) => (default_fee_schedule(), true),
+    }
+}
+
+#[tauri::command]
+pub async fn estimate_job_cost(
+    app_handle: AppHandle,
+    gpu_id: String,
+    duration_hours: f32,
+    workload: Option<JobCategory>,
+) -> Result<JobCostEstimate, String> {
+    if !(duration_hours > 0.0) {
+        return Err("Duration must be positive.".to_string());
+    }
+    let gpus = get_detected_gpus(app_handle.clone()).await?;
+    let gpu = gpus.iter().find(|g| g.id == gpu_id).ok_or_else(|| format!("No GPU with ID '{}'.", gpu_id))?;
+    let settings = get_provider_settings(app_handle.clone()).await?;
+    let hourly_rate = gpu.current_hourly_rate_dgpu.unwrap_or(settings.default_hourly_rate_dgpu).as_decimal();
+    let billed_hours = duration_hours.max(settings.min_job_duration_minutes as f32 / 60.0);
+    let billed = money::from_f32(billed_hours)?;
+    let (schedule, fee_schedule_is_default) = fee_schedule(&app_handle).await;
+    let rounding = app_settings::current(&app_handle).amount_rounding;
+
+    let mut lines = Vec::new();
+    let mut gross = rounding.round((hourly_rate * billed).max(schedule.minimum_charge_dgpu));
+    lines.push(CostLine { label: format!("{:.2} h at {:.4} DGPU/hr", billed_hours, hourly_rate), amount_dgpu: gross });
+    // Discounts don't stack; the renter gets the best one that applies.
+    let best_discount = list(&app_handle)
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|d| d.reduction(&gpu_id, workload, hourly_rate, billed, gross).map(|amount| (d, amount)))
+        .max_by_key(|(_, amount)| *amount);
+    if let Some((discount, amount)) = best_discount {
+        let amount = rounding.round(amount.min(gross));
+        lines.push(CostLine { label: format!("Discount '{}' ({}% off)", discount.rule.name, discount.rule.percent_off), amount_dgpu: -amount });
+        gross -= amount;
+    }
+    let renter_fee = rounding.round(gross * percent(schedule.renter_fee_percent));
+    lines.push(CostLine { label: format!("Platform fee ({}%)", schedule.renter_fee_percent), amount_dgpu: renter_fee });
+    let commission = rounding.round(gross * percent(schedule.provider_commission_percent));
+    lines.push(CostLine { label: format!("Provider commission ({}%)", schedule.provider_commission_percent), amount_dgpu: -commission });
+
+    Ok(JobCostEstimate {
+        gpu_id,
+        workload,
+        requested_hours: duration_hours,
+        billed_hours,
+        hourly_rate_dgpu: hourly_rate,
+        renter_total_dgpu: gross + renter_fee,
+        provider_net_dgpu: gross - commission,
+        lines,
+        fee_schedule: schedule,
+        fee_schedule_is_default,
+    })
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct SurgeRule {
+    pub enabled: bool,
+    // Surge when next hour's forecast is at least this multiple of the average hour.
+    pub demand_ratio: f64,
+    #[serde(with = "money::amount")]
+    pub multiplier: Decimal,
+    pub max_rate_dgpu: Option<DgpuPerHour>,
+}
+
+impl Default for SurgeRule {
+    fn default() -> Self {
+        SurgeRule { enabled: false, demand_ratio: 1.5, multiplier: Decimal::new(125, 2), max_rate_dgpu: None }
+    }
+}
+
+// Base rate of each GPU currently surging, persisted so a restart mid-surge can still restore it.
+type SurgedRates = HashMap<String, DgpuPerHour>;
+
+fn surge_state_path(app_handle: &AppHandle) -> Option<std::path::PathBuf> {
+    app_handle.path_resolver().app_data_dir().map(|dir| dir.join(SURGE_STATE_FILE_NAME))
+}
+
+fn read_surged(app_handle: &AppHandle) -> SurgedRates {
+    surge_state_path(app_handle)
+        .and_then(|p| fs::read_to_string(p).ok())
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+fn write_surged(app_handle: &AppHandle, surged: &SurgedRates) {
+    let Some(path) = surge_state_path(app_handle) else { return };
+    if let Ok(json) = serde_json::to_string_pretty(surged) {
+        if let Err(e) = fs::write(path, json) {
+            eprintln!("Failed to persist surge state: {}", e);
+        }
+    }
+}
+
+async fn evaluate_surge(app_handle: &AppHandle) -> Result<(), String> {
+    let rule = app_settings::current(app_handle).surge_pricing;
+    let mut surged = read_surged(app_handle);
+    let mut ratios: HashMap<String, Option<f64>> = HashMap::new();
+    for gpu in get_detected_gpus(app_handle.clone()).await? {
+        let Some(rate) = gpu.current_hourly_rate_dgpu else { continue };
+        if !ratios.contains_key(&gpu.model) {
+            let ratio = analytics::demand_forecast(app_handle, &gpu.model, 1).await.ok().and_then(|f| f.next_hour_ratio());
+            ratios.insert(gpu.model.clone(), ratio);
+        }
+        let surging = rule.enabled && gpu.is_available_for_rent && ratios[&gpu.model].is_some_and(|r| r >= rule.demand_ratio);
+
+        match (surging, surged.get(&gpu.id).copied()) {
+            (true, None) => {
+                let target = rule.max_rate_dgpu.map_or(rate * rule.multiplier, |max| (rate * rule.multiplier).min(max));
+                if target <= rate {
+                    continue;
+                }
+                set_gpu_rental_config(app_handle.clone(), gpu.id.clone(), target, true, None, None).await?;
+                surged.insert(gpu.id.clone(), rate);
+                emit_log_entry(app_handle, "status", format!("Surge pricing {} at {} (was {}).", gpu.name, target, rate));
+            }
+            (false, Some(base)) => {
+                surged.remove(&gpu.id);
+                // A rate the provider changed by hand during the surge is left alone.
+                let expected = rule.max_rate_dgpu.map_or(base * rule.multiplier, |max| (base * rule.multiplier).min(max));
+                if rate == expected {
+                    set_gpu_rental_config(app_handle.clone(), gpu.id.clone(), base, gpu.is_available_for_rent, None, None).await?;
+                    emit_log_entry(app_handle, "status", format!("Surge over; {} back at {}.", gpu.name, base));
+                }
+            }
+            _ => continue,
+        }
+        write_surged(app_handle, &surged);
+        if let Err(e) = app_handle.emit_all("surge_pricing_changed", &surged) {
+            eprintln!("Failed to emit surge_pricing_changed event: {}", e);
+        }
+    }
+    Ok(())
+}
+
+pub fn spawn_surge_monitor(app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            if !shutdown::sleep(SURGE_CHECK_INTERVAL).await {
+                break;
+            }
+            if let Err(e) = evaluate_surge(&app_handle).await {
+                eprintln!("Surge pricing check failed: {}", e);
+            }
+        }
+    });
+}
+
+// --- Break-even rates ---
+
+const BREAK_EVEN_HISTORY_DAYS: i64 = 30;
+// Below this share of hours rented, amortization per rented hour stops meaning much.
+const MIN_RENTED_FRACTION: f64 = 0.05;
+const ASSUMED_RENTED_FRACTION: f64 = 0.5;
+const HOURS_PER_MONTH: f64 = 730.0;
+
+#[derive(Serialize, Debug, Clone)]
+pub struct BreakEvenRate {
+    pub gpu_id: String,
+    pub gpu_model: String,
+    pub currency: String, // Of the cost lines below: the card's purchase currency
+    pub loaded_power_w: Option<f64>,
+    // Per rented hour, in `currency`.
+    #[serde(with = "money::optional_amount")]
+    pub power_cost_per_hour: Option<Decimal>,
+    #[serde(with = "money::optional_amount")]
+    pub amortization_per_hour: Option<Decimal>,
+    pub rented_fraction: f64,
+    // Listed rate needed to net the costs after the provider commission.
+    #[serde(with = "money::optional_amount")]
+    pub minimum_rate_dgpu: Option<Decimal>,
+    pub market_median_dgpu: Option<DgpuPerHour>,
+    pub breaks_even_at_market: Option<bool>,
+    // Power is priced in another currency and left out, so the minimum rate is too low.
+    pub incomplete: bool,
+    pub assumptions: Vec<String>,
+}
+
+pub async fn break_even(app_handle: &AppHandle, gpu: &GpuInfo, item: &InventoryItem, commission_percent: Decimal) -> BreakEvenRate {
+    let settings = app_settings::current(app_handle);
+    let (costs, rounding) = (settings.operating_costs, settings.amount_rounding);
+    let metadata = &item.metadata;
+    let since = unix_now() - BREAK_EVEN_HISTORY_DAYS * 24 * 60 * 60;
+    let mut assumptions = Vec::new();
+
+    let storage = app_handle.try_state::<Storage>();
+    let history = storage.as_ref().map(|s| {
+        let power = s.average_loaded_power_w(&gpu.id, since, analytics::BUSY_UTILIZATION_PERCENT).ok().flatten();
+        let hours: Vec<_> = s
+            .hourly_usage(since, unix_now(), analytics::BUSY_UTILIZATION_PERCENT)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|h| h.gpu_id == gpu.id)
+            .collect();
+        let busy = (!hours.is_empty()).then(|| hours.iter().map(|h| h.busy_fraction).sum::<f64>() / hours.len() as f64);
+        (power, busy)
+    });
+    let (history_power, history_busy) = history.unwrap_or((None, None));
+    let loaded_power_w = history_power.or_else(|| gpu.power_draw_w.map(|w| f64::from(w.as_watts())));
+    if history_power.is_none() && loaded_power_w.is_some() {
+        assumptions.push("No power history under load; using the current power draw.".to_string());
+    }
+    let rented_fraction = match history_busy {
+        Some(busy) => busy.max(MIN_RENTED_FRACTION),
+        None => {
+            assumptions.push(format!("No usage history; assuming the card is rented {:.0}% of the time.", ASSUMED_RENTED_FRACTION * 100.0));
+            ASSUMED_RENTED_FRACTION
+        }
+    };
+
+    let mut incomplete = false;
+    let power_cost_per_hour = match (costs.electricity_price_per_kwh, loaded_power_w) {
+        (Some(price), Some(watts)) if costs.electricity_currency.eq_ignore_ascii_case(&metadata.purchase_currency) => {
+            money::from_f64(watts / 1000.0).ok().map(|kw| rounding.round(kw * price))
+        }
+        (Some(_), Some(_)) => {
+            incomplete = true;
+            assumptions.push(format!(
+                "Electricity is priced in {} but the card in {}; power isn't counted, so the minimum rate is too low.",
+                costs.electricity_currency, metadata.purchase_currency
+            ));
+            None
+        }
+        (None, _) => {
+            assumptions.push("No electricity price set; power isn't counted.".to_string());
+            None
+        }
+        (Some(_), None) => None,
+    };
+
+    // What is still to be earned back, over what is left of the amortization period. A card past
+    // the period is written off and only has to cover its power.
+    let amortization_per_hour = metadata.purchase_price.and_then(|price| {
+        let left_hours = f64::from(costs.amortization_months) * HOURS_PER_MONTH - item.days_owned.unwrap_or(0) as f64 * 24.0;
+        let outstanding = (price - item.earned_in_purchase_currency.unwrap_or(Decimal::ZERO)).max(Decimal::ZERO);
+        if left_hours <= 0.0 || outstanding.is_zero() {
+            Some(Decimal::ZERO)
+        } else {
+            money::from_f64(left_hours * rented_fraction).ok().map(|rented_hours| rounding.round(outstanding / rented_hours))
+        }
+    });
+    if metadata.purchase_price.is_none() {
+        assumptions.push("No purchase price recorded; amortization isn't counted.".to_string());
+    } else if item.earned_in_purchase_currency.is_none() {
+        assumptions.push("Without a DGPU exchange rate, past earnings aren't deducted from the purchase price.".to_string());
+    }
+
+    let cost = match (power_cost_per_hour, amortization_per_hour) {
+        (None, None) => None,
+        (power, amortization) => Some(power.unwrap_or(Decimal::ZERO) + amortization.unwrap_or(Decimal::ZERO)),
+    };
+    let net_share = Decimal::ONE - percent(commission_percent);
+    let minimum_rate_dgpu = match (cost, metadata.dgpu_value()) {
+        (Some(cost), Some(value)) if net_share > Decimal::ZERO => Some(rounding.round(cost / value / net_share)),
+        (Some(_), None) => {
+            assumptions.push("Set the card's DGPU exchange rate to turn its costs into a DGPU rate.".to_string());
+            None
+        }
+        _ => None,
+    };
+    let market_median_dgpu = app_handle.state::<MarketState>().floor_for(&gpu.model).and_then(|f| f.median_dgpu_per_hour);
+    let breaks_even_at_market = match (minimum_rate_dgpu, market_median_dgpu) {
+        // Without power the minimum is only a floor: falling short of the market is still certain.
+        (Some(minimum), Some(median)) if minimum > median.as_decimal() => Some(false),
+        (Some(_), Some(_)) => (!incomplete).then_some(true),
+        _ => None,
+    };
+
+    BreakEvenRate {
+        gpu_id: gpu.id.clone(),
+        gpu_model: gpu.model.clone(),
+        currency: metadata.purchase_currency.clone(),
+        loaded_power_w,
+        power_cost_per_hour,
+        amortization_per_hour,
+        rented_fraction,
+        minimum_rate_dgpu,
+        market_median_dgpu,
+        breaks_even_at_market,
+        incomplete,
+        assumptions,
+    }
+}
+
+// One entry per detected GPU.
+#[tauri::command]
+pub async fn get_break_even_rates(app_handle: AppHandle) -> Result<Vec<BreakEvenRate>, String> {
+    let gpus = get_detected_gpus(app_handle.clone()).await?;
+    let inventory = inventory::report(&app_handle).await?;
+    let (schedule, _) = fee_schedule(&app_handle).await;
+    let mut rates = Vec::new();
+    for gpu in &gpus {
+        if let Some(item) = inventory.iter().find(|i| i.gpu_id == gpu.id) {
+            rates.push(break_even(&app_handle, gpu, item, schedule.provider_commission_percent).await);
+        }
+    }
+    Ok(rates)
+}
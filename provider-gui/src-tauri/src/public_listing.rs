@@ -0,0 +1,160 @@
+// The provider's listing as the marketplace actually shows it, compared with local configuration.
+//
+// Rates, availability and capabilities reach the marketplace through the daemon's heartbeat, so
+// a change shows up for renters a little later, and occasionally not at all (a heartbeat that
+// keeps failing, a platform-side cache). `get_public_listing_preview` fetches the public listing
+// and lines it up field by field with the GPUs and capability manifest configured here. A
+// difference made shortly after a local settings change is reported as still propagating; one
+// that outlives the grace period is a mismatch worth investigating. Capabilities the daemon
+// couldn't report are marked unknown rather than compared.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::time::UNIX_EPOCH;
+use tauri::{AppHandle, Manager};
+
+use crate::storage::{unix_now, Storage};
+use crate::units::DgpuPerHour;
+use crate::{api_client, gpu_ids, invoke_daemon_cli_json_output, nvidia_smi, settings_history, RentalMode};
+
+// Heartbeats run every minute or so; allow a few to be missed before calling it a mismatch.
+const PROPAGATION_GRACE_SECS: i64 = 10 * 60;
+const RATE_TOLERANCE: f32 = 0.0001;
+
+#[derive(Deserialize, Debug, Clone)]
+struct PublicGpu {
+    gpu_id: String,
+    model: String,
+    hourly_rate_dgpu: Option<DgpuPerHour>,
+    #[serde(default)]
+    rental_mode: RentalMode,
+    available: bool,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+struct PublicListing {
+    updated_at: Option<String>,
+    gpus: Vec<PublicGpu>,
+    cuda_version: Option<String>,
+    driver_features: Vec<String>,
+    container_runtimes: Vec<String>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+struct Advertised {
+    advertised: Value,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldStatus {
+    Match,
+    Propagating, // Differs, but local settings changed within the grace period
+    Mismatch,
+    Unknown, // The local value couldn't be read
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ListingField {
+    pub path: String, // e.g. "gpus.<id>.hourly_rate_dgpu"
+    pub local: Value,
+    pub public: Value,
+    pub status: FieldStatus,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct PublicListingPreview {
+    pub listing_updated_at: Option<String>,
+    pub local_changed_at: Option<i64>, // Last recorded settings change, unix seconds
+    pub fields: Vec<ListingField>,
+    pub mismatches: usize,
+    pub propagating: usize,
+    pub unknown: usize,
+}
+
+fn parse_time(value: &str) -> Option<i64> {
+    humantime::parse_rfc3339_weak(value).ok()?.duration_since(UNIX_EPOCH).ok().map(|d| d.as_secs() as i64)
+}
+
+fn same(local: &Value, public: &Value) -> bool {
+    match (local.as_f64(), public.as_f64()) {
+        (Some(a), Some(b)) => (a - b).abs() < f64::from(RATE_TOLERANCE),
+        _ => local == public,
+    }
+}
+
+// Compares the public listing with local state. A difference is still propagating when local
+// settings changed after the listing was last updated and less than the grace period ago.
+#[tauri::command]
+pub async fn get_public_listing_preview(app_handle: AppHandle) -> Result<PublicListingPreview, String> {
+    let listing: PublicListing = api_client::get_json(&app_handle, "/api/v1/providers/me/public-listing").await?;
+    let gpus = nvidia_smi::detect_gpus(&app_handle).await?;
+    if gpus.iter().any(|g| g.fallback_sourced) {
+        return Err("The daemon can't list GPUs right now, so there's no local listing to compare.".to_string());
+    }
+    let manifest = invoke_daemon_cli_json_output::<Advertised>(&app_handle, &["--get-capabilities-json"]).await.map(|m| m.advertised);
+
+    // Dates a change made since the last periodic snapshot; without the daemon, the stored one stands.
+    if let Err(e) = settings_history::record(&app_handle).await {
+        eprintln!("Settings snapshot skipped: {}", e);
+    }
+    let local_changed_at = app_handle
+        .try_state::<Storage>()
+        .and_then(|s| s.settings_snapshot_at(i64::MAX).ok().flatten())
+        .map(|snapshot| snapshot.taken_at);
+    let listing_updated_at = listing.updated_at.as_deref().and_then(parse_time);
+    let propagating = match (local_changed_at, listing_updated_at) {
+        (Some(changed), Some(updated)) => changed > updated && unix_now() - changed < PROPAGATION_GRACE_SECS,
+        (Some(changed), None) => unix_now() - changed < PROPAGATION_GRACE_SECS,
+        _ => false,
+    };
+
+    let mut fields = Vec::new();
+    // A `local` of None couldn't be read and isn't compared.
+    let mut compare = |path: String, local: Option<Value>, public: Value| {
+        let Some(local) = local else {
+            fields.push(ListingField { path, local: Value::Null, public, status: FieldStatus::Unknown });
+            return;
+        };
+        let status = if same(&local, &public) {
+            FieldStatus::Match
+        } else if propagating {
+            FieldStatus::Propagating
+        } else {
+            FieldStatus::Mismatch
+        };
+        fields.push(ListingField { path, local, public, status });
+    };
+
+    let public_gpus: Vec<(String, &PublicGpu)> = listing.gpus.iter().map(|g| (gpu_ids::stable_id(&g.gpu_id), g)).collect();
+    for gpu in &gpus {
+        let public = public_gpus.iter().find(|(id, _)| *id == gpu.id).map(|(_, g)| *g);
+        let path = |field: &str| format!("gpus.{}.{}", gpu.id, field);
+        let Some(public) = public else {
+            // Unlisted GPUs are simply absent from the public listing.
+            compare(path("listed"), Some(json!(gpu.is_available_for_rent)), json!(false));
+            continue;
+        };
+        compare(path("model"), Some(json!(gpu.model)), json!(public.model));
+        compare(path("hourly_rate_dgpu"), Some(json!(gpu.current_hourly_rate_dgpu)), json!(public.hourly_rate_dgpu));
+        compare(path("rental_mode"), Some(json!(gpu.rental_mode)), json!(public.rental_mode));
+        compare(path("available"), Some(json!(gpu.is_available_for_rent)), json!(public.available));
+    }
+    for (id, public) in public_gpus.iter().filter(|(id, _)| !gpus.iter().any(|g| g.id == *id)) {
+        compare(format!("gpus.{}.listed", id), Some(json!(false)), json!(public.available));
+    }
+    for (field, public) in [
+        ("cuda_version", json!(listing.cuda_version)),
+        ("driver_features", json!(listing.driver_features)),
+        ("container_runtimes", json!(listing.container_runtimes)),
+    ] {
+        let local = manifest.as_ref().ok().map(|m| m.get(field).cloned().unwrap_or(Value::Null));
+        compare(format!("capabilities.{}", field), local, public);
+    }
+
+    let count = |status: FieldStatus| fields.iter().filter(|f| f.status == status).count();
+    let (mismatches, propagating, unknown) = (count(FieldStatus::Mismatch), count(FieldStatus::Propagating), count(FieldStatus::Unknown));
+    Ok(PublicListingPreview { listing_updated_at: listing.updated_at, local_changed_at, fields, mismatches, propagating, unknown })
+}
@@ -0,0 +1,174 @@
+// Reconciling job and balance state after a connectivity outage.
+//
+// When `connectivity` first fails to reach the platform we remember the jobs and balance as they
+// stood. Once the platform answers again the daemon is asked to re-register with it, the clock is
+// re-measured, and the authoritative state is fetched again and compared with what we had.
+// Anything that changed while we were offline (jobs finished, cancelled or newly assigned,
+// payouts) is emitted as a `state_reconciled` report.
+
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+use crate::connectivity::ConnectivityIncident;
+use crate::money::{self, Decimal};
+use crate::{clock, emit_log_entry, get_timestamp, gpu_ids, invoke_daemon_cli_json_output, shutdown, FinancialSummary, LocalJob};
+
+const HANDSHAKE_ATTEMPTS: u32 = 3;
+const HANDSHAKE_RETRY_DELAY: Duration = Duration::from_secs(5);
+
+struct Expected {
+    jobs: Vec<LocalJob>,
+    financials: Option<FinancialSummary>,
+}
+
+static EXPECTED: Mutex<Option<Expected>> = Mutex::new(None);
+static LAST_REPORT: Mutex<Option<StateReconciliation>> = Mutex::new(None);
+
+#[derive(Serialize, Debug, Clone)]
+pub struct JobChange {
+    pub job_id: String,
+    pub name: String,
+    pub status_before: Option<String>, // None: the job appeared while offline
+    pub status_after: Option<String>,  // None: the job is gone
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct StateReconciliation {
+    pub incident_id: String,
+    pub offline_since: i64, // unix seconds
+    pub reconciled_at: String,
+    pub job_changes: Vec<JobChange>,
+    #[serde(with = "money::optional_amount")]
+    pub balance_before_dgpu: Option<Decimal>,
+    #[serde(with = "money::optional_amount")]
+    pub balance_after_dgpu: Option<Decimal>,
+    #[serde(with = "money::optional_amount")]
+    pub pending_payout_before_dgpu: Option<Decimal>,
+    #[serde(with = "money::optional_amount")]
+    pub pending_payout_after_dgpu: Option<Decimal>,
+    pub errors: Vec<String>, // Steps that failed; their part of the report is missing
+}
+
+impl StateReconciliation {
+    pub fn changed(&self) -> bool {
+        !self.job_changes.is_empty()
+            || self.balance_before_dgpu != self.balance_after_dgpu
+            || self.pending_payout_before_dgpu != self.pending_payout_after_dgpu
+    }
+}
+
+async fn fetch_jobs(app_handle: &AppHandle) -> Result<Vec<LocalJob>, String> {
+    gpu_ids::local_jobs(app_handle).await
+}
+
+async fn fetch_financials(app_handle: &AppHandle) -> Result<FinancialSummary, String> {
+    invoke_daemon_cli_json_output(app_handle, &["--get-financial-summary-json"]).await
+}
+
+// Called on the first failed check. The daemon still answers locally, so this is the last state
+// it had confirmed with the platform; a balance it can't report is left out of the comparison.
+pub async fn capture(app_handle: &AppHandle) {
+    let jobs = fetch_jobs(app_handle).await.unwrap_or_default();
+    let financials = fetch_financials(app_handle).await.ok();
+    *EXPECTED.lock().unwrap() = Some(Expected { jobs, financials });
+}
+
+fn diff_jobs(before: &[LocalJob], after: &[LocalJob]) -> Vec<JobChange> {
+    let mut changes: Vec<JobChange> = after
+        .iter()
+        .filter_map(|job| {
+            let previous = before.iter().find(|b| b.id == job.id);
+            (previous.map(|p| &p.status) != Some(&job.status)).then(|| JobChange {
+                job_id: job.id.clone(),
+                name: job.name.clone(),
+                status_before: previous.map(|p| p.status.clone()),
+                status_after: Some(job.status.clone()),
+            })
+        })
+        .collect();
+    changes.extend(before.iter().filter(|b| !after.iter().any(|a| a.id == b.id)).map(|job| JobChange {
+        job_id: job.id.clone(),
+        name: job.name.clone(),
+        status_before: Some(job.status.clone()),
+        status_after: None,
+    }));
+    changes
+}
+
+// Asks the daemon to re-register with the platform, retrying briefly since the link may still be
+// settling.
+async fn handshake(app_handle: &AppHandle) -> Result<(), String> {
+    let mut last_error = String::new();
+    for attempt in 1..=HANDSHAKE_ATTEMPTS {
+        match invoke_daemon_cli_json_output::<serde_json::Value>(app_handle, &["--reconnect-platform-json"]).await {
+            Ok(_) => return Ok(()),
+            Err(e) => last_error = e,
+        }
+        if attempt < HANDSHAKE_ATTEMPTS && !shutdown::sleep(HANDSHAKE_RETRY_DELAY).await {
+            break;
+        }
+    }
+    Err(format!("The daemon couldn't re-register with the platform: {}", last_error))
+}
+
+pub async fn run(app_handle: &AppHandle, incident: &ConnectivityIncident) -> StateReconciliation {
+    let expected = EXPECTED.lock().unwrap().take();
+    let mut errors = Vec::new();
+    if let Err(e) = handshake(app_handle).await {
+        errors.push(e);
+    }
+    if let Err(e) = clock::measure(app_handle).await {
+        errors.push(e);
+    }
+
+    let (jobs_before, financials_before) = expected.map_or((None, None), |e| (Some(e.jobs), e.financials));
+    let job_changes = match (jobs_before, fetch_jobs(app_handle).await) {
+        (Some(before), Ok(after)) => diff_jobs(&before, &after),
+        (None, Ok(_)) => {
+            errors.push("No job state was captured when the outage began.".to_string());
+            Vec::new()
+        }
+        (_, Err(e)) => {
+            errors.push(format!("Failed to fetch jobs: {}", e));
+            Vec::new()
+        }
+    };
+    let financials_after = match fetch_financials(app_handle).await {
+        Ok(financials) => Some(financials),
+        Err(e) => {
+            errors.push(format!("Failed to fetch the balance: {}", e));
+            None
+        }
+    };
+
+    let report = StateReconciliation {
+        incident_id: incident.incident_id.clone(),
+        offline_since: incident.started_at,
+        reconciled_at: get_timestamp(),
+        job_changes,
+        balance_before_dgpu: financials_before.as_ref().map(|f| f.current_balance_dgpu),
+        balance_after_dgpu: financials_after.as_ref().map(|f| f.current_balance_dgpu),
+        pending_payout_before_dgpu: financials_before.as_ref().map(|f| f.pending_payout_dgpu),
+        pending_payout_after_dgpu: financials_after.as_ref().map(|f| f.pending_payout_dgpu),
+        errors,
+    };
+
+    if !report.errors.is_empty() {
+        emit_log_entry(app_handle, "error", format!("State reconciliation after the outage was incomplete: {}", report.errors.join("; ")));
+    } else if report.changed() {
+        emit_log_entry(app_handle, "status", format!("Reconciled state after the outage: {} job(s) changed while offline.", report.job_changes.len()));
+    }
+    *LAST_REPORT.lock().unwrap() = Some(report.clone());
+    if let Err(e) = app_handle.emit_all("state_reconciled", report.clone()) {
+        eprintln!("Failed to emit state_reconciled event: {}", e);
+    }
+    report
+}
+
+// The report from the most recent reconnection, for a frontend that missed the event.
+#[tauri::command]
+pub async fn get_last_reconciliation() -> Result<Option<StateReconciliation>, String> {
+    Ok(LAST_REPORT.lock().unwrap().clone())
+}
@@ -0,0 +1,182 @@
+// Redaction of secrets from log lines.
+//
+// Daemon output and error messages occasionally include API tokens or wallet addresses. Every
+// log entry passes through `redact` before it is buffered, emitted to the frontend or written to
+// a diagnostic bundle, so none of those ever hold the original. Two passes run:
+// - built-in patterns for tokens, JWTs, key=value secrets and wallet addresses, plus any
+//   patterns the user adds in settings;
+// - exact matching of secrets this process has read from or written to the keychain, which
+//   catches credentials no pattern would recognise.
+// Known secrets are held in memory only. Counts per rule are kept for `get_redaction_stats`.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock, RwLock};
+use tauri::{AppHandle, Manager, Runtime, State};
+
+use crate::{accounts, api_client, app_settings, secrets};
+
+// Shorter values would match ordinary words in log lines.
+const MIN_KNOWN_SECRET_LEN: usize = 8;
+const KNOWN_SECRET_RULE: &str = "keychain_secret";
+
+// (name, pattern, replacement). Shared with crash report scrubbing.
+pub const BUILTIN_RULES: &[(&str, &str, &str)] = &[
+    ("bearer_token", r"(?i)bearer\s+[A-Za-z0-9._~+/=-]+", "Bearer <redacted>"),
+    ("jwt", r"eyJ[A-Za-z0-9_-]+\.[A-Za-z0-9_-]+\.[A-Za-z0-9_-]*", "<jwt>"),
+    ("key_value_secret", r#"(?i)((?:api[_-]?key|token|secret|password)["']?\s*[:=]\s*["']?)[^\s"',}]+"#, "${1}<redacted>"),
+    // EVM and base58 (Solana-style) wallet addresses.
+    ("evm_wallet", r"\b0x[0-9a-fA-F]{40}\b", "<wallet>"),
+    ("base58_wallet", r"\b[1-9A-HJ-NP-Za-km-z]{32,44}\b", "<wallet>"),
+];
+
+static KNOWN_SECRETS: RwLock<Vec<String>> = RwLock::new(Vec::new());
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RedactionPattern {
+    pub name: String,
+    pub pattern: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct RedactionConfig {
+    pub enabled: bool,
+    // Wallet addresses are public on-chain, but tie the logs to the provider.
+    pub redact_wallets: bool,
+    pub extra_patterns: Vec<RedactionPattern>,
+}
+
+impl Default for RedactionConfig {
+    fn default() -> Self {
+        RedactionConfig { enabled: true, redact_wallets: true, extra_patterns: Vec::new() }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct RedactionStats {
+    pub lines_redacted: u64,
+    pub redactions: u64,
+    pub by_rule: HashMap<String, u64>,
+}
+
+struct Rule {
+    name: String,
+    re: Regex,
+    replacement: String,
+}
+
+pub struct RedactionState {
+    // Compiled user patterns, with the config they were compiled from.
+    extra: Mutex<(Vec<RedactionPattern>, Vec<Rule>)>,
+    stats: Mutex<RedactionStats>,
+}
+
+impl RedactionState {
+    pub fn new() -> Self {
+        RedactionState { extra: Mutex::new((Vec::new(), Vec::new())), stats: Mutex::new(RedactionStats::default()) }
+    }
+}
+
+fn builtin() -> &'static [Rule] {
+    static RULES: OnceLock<Vec<Rule>> = OnceLock::new();
+    RULES.get_or_init(|| {
+        BUILTIN_RULES
+            .iter()
+            .map(|(name, pattern, replacement)| Rule { name: name.to_string(), re: Regex::new(pattern).unwrap(), replacement: replacement.to_string() })
+            .collect()
+    })
+}
+
+fn compile(patterns: &[RedactionPattern]) -> Result<Vec<Rule>, String> {
+    patterns
+        .iter()
+        .map(|p| {
+            Regex::new(&p.pattern)
+                .map(|re| Rule { name: p.name.clone(), re, replacement: "<redacted>".to_string() })
+                .map_err(|e| format!("Invalid redaction pattern '{}': {}", p.name, e))
+        })
+        .collect()
+}
+
+// Checked when settings are saved, so a bad pattern is reported instead of silently skipped.
+pub fn validate(config: &RedactionConfig) -> Result<(), String> {
+    compile(&config.extra_patterns).map(|_| ())
+}
+
+// Called by `secrets` for every value that passes through the keychain.
+pub fn remember(secret: &str) {
+    if secret.len() < MIN_KNOWN_SECRET_LEN {
+        return;
+    }
+    let mut known = KNOWN_SECRETS.write().unwrap();
+    if !known.iter().any(|s| s == secret) {
+        known.push(secret.to_string());
+        // Longest first, so a secret that contains another is replaced whole.
+        known.sort_by_key(|s| std::cmp::Reverse(s.len()));
+    }
+}
+
+// The daemon can print the token before the GUI has had a reason to read it.
+pub fn learn_platform_token(app_handle: &AppHandle) {
+    let _ = secrets::get(&accounts::secret_key(app_handle, api_client::TOKEN_KEY));
+}
+
+fn apply(rule: &Rule, text: &mut String, counts: &mut Vec<(String, u64)>) {
+    let found = rule.re.find_iter(text).count() as u64;
+    if found > 0 {
+        *text = rule.re.replace_all(text, rule.replacement.as_str()).into_owned();
+        counts.push((rule.name.clone(), found));
+    }
+}
+
+pub fn redact<R: Runtime>(manager: &impl Manager<R>, message: String) -> String {
+    let Some(state) = manager.try_state::<RedactionState>() else { return message };
+    let config = app_settings::current(manager).log_redaction;
+    if !config.enabled {
+        return message;
+    }
+
+    let mut text = message;
+    let mut counts = Vec::new();
+    for secret in KNOWN_SECRETS.read().unwrap().iter() {
+        let found = text.matches(secret.as_str()).count() as u64;
+        if found > 0 {
+            text = text.replace(secret.as_str(), "<secret>");
+            counts.push((KNOWN_SECRET_RULE.to_string(), found));
+        }
+    }
+    for rule in builtin().iter().filter(|r| config.redact_wallets || !r.name.ends_with("_wallet")) {
+        apply(rule, &mut text, &mut counts);
+    }
+    {
+        let mut extra = state.extra.lock().unwrap();
+        if extra.0 != config.extra_patterns {
+            // Validated on save; a pattern that still fails to compile is dropped.
+            *extra = (config.extra_patterns.clone(), compile(&config.extra_patterns).unwrap_or_default());
+        }
+        for rule in &extra.1 {
+            apply(rule, &mut text, &mut counts);
+        }
+    }
+
+    if !counts.is_empty() {
+        let mut stats = state.stats.lock().unwrap();
+        stats.lines_redacted += 1;
+        for (rule, found) in counts {
+            stats.redactions += found;
+            *stats.by_rule.entry(rule).or_default() += found;
+        }
+    }
+    text
+}
+
+pub fn stats<R: Runtime>(manager: &impl Manager<R>) -> Option<RedactionStats> {
+    manager.try_state::<RedactionState>().map(|state| state.stats.lock().unwrap().clone())
+}
+
+#[tauri::command]
+pub async fn get_redaction_stats(state: State<'_, RedactionState>) -> Result<RedactionStats, String> {
+    Ok(state.stats.lock().unwrap().clone())
+}
@@ -0,0 +1,237 @@
+// Regional matchmaking preferences based on measured latency.
+//
+// The platform lists its regional gateways; we time a few requests to each, keep the results,
+// and let the provider pin regions (only match jobs from these) or order them by priority.
+// The preference is pushed to the daemon, which passes it on when registering for matching.
+// Measurements are repeated in the background and kept with the telemetry history, so the
+// latency to each gateway can be read by weekday and hour to spot time-of-day congestion.
+
+use chrono::{Datelike, Local, TimeZone, Timelike};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, State};
+
+use crate::app_settings::{self, AppSettingsState};
+use crate::storage::{unix_now, Storage};
+use crate::{api_client, emit_log_entry, get_timestamp, http_client, invoke_daemon_cli_json_output, shutdown};
+
+const RESULTS_FILE_NAME: &str = "region_latency.json";
+const SAMPLES_PER_REGION: usize = 3;
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+const MONITOR_INTERVAL: Duration = Duration::from_secs(15 * 60);
+const DEFAULT_HISTORY_DAYS: i64 = 14;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct RegionPreference {
+    // When non-empty, only jobs from these regions are matched.
+    pub pinned: Vec<String>,
+    // Regions to prefer, most preferred first; unlisted regions come after.
+    pub priority: Vec<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+struct Region {
+    id: String,
+    name: String,
+    gateway_url: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RegionLatency {
+    pub region_id: String,
+    pub name: String,
+    pub median_ms: Option<u64>, // None when every probe failed
+    pub samples_ms: Vec<u64>,
+    pub measured_at: String,
+}
+
+async fn probe(client: &reqwest::Client, gateway_url: &str) -> Option<u64> {
+    let started = Instant::now();
+    let response = tokio::time::timeout(PROBE_TIMEOUT, client.get(format!("{}/health", gateway_url.trim_end_matches('/'))).send()).await;
+    match response {
+        Ok(Ok(r)) if r.status().is_success() => Some(started.elapsed().as_millis() as u64),
+        _ => None,
+    }
+}
+
+fn results_path(app_handle: &AppHandle) -> Option<std::path::PathBuf> {
+    app_handle.path_resolver().app_data_dir().map(|dir| dir.join(RESULTS_FILE_NAME))
+}
+
+// One measurement in the history.
+#[derive(Serialize, Debug, Clone)]
+pub struct LatencyPoint {
+    pub measured_at: i64, // unix seconds
+    pub median_ms: Option<u64>,
+}
+
+// A cell of the heatmap, in the provider's local time.
+#[derive(Serialize, Debug, Clone)]
+pub struct LatencyBucket {
+    pub weekday: u32, // 0 = Monday
+    pub hour: u32,
+    pub median_ms: Option<u64>, // Over the measurements that succeeded
+    pub measurements: usize,
+    pub failures: usize,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct LatencyHistory {
+    pub region_id: String,
+    pub from: i64,
+    pub to: i64,
+    pub points: Vec<LatencyPoint>,
+    pub heatmap: Vec<LatencyBucket>, // Only cells with measurements, by weekday then hour
+}
+
+pub async fn measure(app_handle: &AppHandle) -> Result<Vec<RegionLatency>, String> {
+    let regions: Vec<Region> = api_client::get_json(app_handle, "/api/v1/regions").await?;
+    let client = http_client::client()?;
+    let mut results = Vec::new();
+    for region in regions {
+        let mut samples = Vec::new();
+        for _ in 0..SAMPLES_PER_REGION {
+            if let Some(ms) = probe(&client, &region.gateway_url).await {
+                samples.push(ms);
+            }
+        }
+        results.push(RegionLatency {
+            region_id: region.id,
+            name: region.name,
+            median_ms: median(&mut samples.clone()),
+            samples_ms: samples,
+            measured_at: get_timestamp(),
+        });
+    }
+    results.sort_by_key(|r| r.median_ms.unwrap_or(u64::MAX));
+
+    if let Some(path) = results_path(app_handle) {
+        if let Ok(json) = serde_json::to_string_pretty(&results) {
+            if let Err(e) = fs::write(&path, json) {
+                eprintln!("Failed to persist region latency results: {}", e);
+            }
+        }
+    }
+    // History follows the telemetry privacy setting and retention window.
+    if app_settings::current(app_handle).privacy.store_telemetry_history {
+        if let Some(storage) = app_handle.try_state::<Storage>() {
+            if let Err(e) = storage.insert_region_latency(&results) {
+                eprintln!("{}", e);
+            }
+        }
+    }
+    Ok(results)
+}
+
+#[tauri::command]
+pub async fn measure_region_latency(app_handle: AppHandle) -> Result<Vec<RegionLatency>, String> {
+    measure(&app_handle).await
+}
+
+pub fn spawn_latency_monitor(app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            if !shutdown::sleep(MONITOR_INTERVAL).await {
+                break;
+            }
+            if let Err(e) = measure(&app_handle).await {
+                eprintln!("Region latency measurement skipped: {}", e);
+            }
+        }
+    });
+}
+
+fn median(values: &mut [u64]) -> Option<u64> {
+    values.sort_unstable();
+    values.get(values.len() / 2).copied()
+}
+
+// `from` and `to` are unix seconds; the last two weeks by default.
+#[tauri::command]
+pub async fn get_latency_history(app_handle: AppHandle, region_id: String, from: Option<i64>, to: Option<i64>) -> Result<LatencyHistory, String> {
+    let storage = app_handle.try_state::<Storage>().ok_or_else(|| "Local storage is unavailable.".to_string())?;
+    let to = to.unwrap_or_else(unix_now);
+    let from = from.unwrap_or(to - DEFAULT_HISTORY_DAYS * 24 * 60 * 60);
+    if from >= to {
+        return Err("The start of the range must be before its end.".to_string());
+    }
+    let points: Vec<LatencyPoint> = storage
+        .region_latency_history(&region_id, from, to)?
+        .into_iter()
+        .map(|(measured_at, median_ms)| LatencyPoint { measured_at, median_ms })
+        .collect();
+
+    let mut cells: Vec<(u32, u32, Vec<u64>, usize)> = Vec::new();
+    for point in &points {
+        let Some(local) = Local.timestamp_opt(point.measured_at, 0).single() else {
+            continue;
+        };
+        let key = (local.weekday().num_days_from_monday(), local.hour());
+        let index = match cells.iter().position(|c| (c.0, c.1) == key) {
+            Some(i) => i,
+            None => {
+                cells.push((key.0, key.1, Vec::new(), 0));
+                cells.len() - 1
+            }
+        };
+        match point.median_ms {
+            Some(ms) => cells[index].2.push(ms),
+            None => cells[index].3 += 1,
+        }
+    }
+    cells.sort_by_key(|c| (c.0, c.1));
+    let heatmap = cells
+        .into_iter()
+        .map(|(weekday, hour, mut samples, failures)| LatencyBucket {
+            weekday,
+            hour,
+            median_ms: median(&mut samples),
+            measurements: samples.len() + failures,
+            failures,
+        })
+        .collect();
+    Ok(LatencyHistory { region_id, from, to, points, heatmap })
+}
+
+#[tauri::command]
+pub async fn get_region_latency(app_handle: AppHandle) -> Result<Vec<RegionLatency>, String> {
+    Ok(results_path(&app_handle)
+        .and_then(|p| fs::read_to_string(p).ok())
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default())
+}
+
+pub async fn push_to_daemon(app_handle: &AppHandle, preference: &RegionPreference) -> Result<(), String> {
+    let json = serde_json::to_string(preference).map_err(|e| format!("Failed to serialize region preference: {}", e))?;
+    invoke_daemon_cli_json_output::<serde_json::Value>(app_handle, &["--set-region-preference-json", &json])
+        .await
+        .map(|_| ())
+}
+
+#[tauri::command]
+pub async fn set_region_preference(
+    app_handle: AppHandle,
+    state: State<'_, AppSettingsState>,
+    preference: RegionPreference,
+) -> Result<RegionPreference, String> {
+    let mut settings = state.get();
+    settings.region_preference = preference.clone();
+    app_settings::update_app_settings(app_handle.clone(), state, settings).await?;
+    // As with the job policy, an offline daemon receives the preference when it starts.
+    if let Err(e) = push_to_daemon(&app_handle, &preference).await {
+        emit_log_entry(&app_handle, "status", format!("Region preference saved; daemon will receive it on next start ({}).", e));
+    }
+    Ok(preference)
+}
+
+pub fn sync_on_start(app_handle: &AppHandle) {
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        let preference = app_settings::current(&app_handle).region_preference;
+        if let Err(e) = push_to_daemon(&app_handle, &preference).await {
+            emit_log_entry(&app_handle, "error", format!("Failed to push region preference to the daemon: {}", e));
+        }
+    });
+}
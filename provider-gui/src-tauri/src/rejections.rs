@@ -0,0 +1,208 @@
+// Why work isn't arriving: every job or request that was turned away, and by whom.
+//
+// Rejections come from four places: the provider's job policy (see `job_policy`), the daemon
+// refusing requests under the pushed constraints, the platform declining to match this machine
+// to a renter's request, and the provider rejecting a manual-accept request (see `approvals`).
+// Each is classified into a small set of reasons and kept in local storage, subject to the job
+// metadata privacy setting, so `get_rejection_stats` can say which reason dominates. The daemon
+// and platform may list the same decline or miss on every call, so theirs are stored once per
+// reported ID.
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::storage::{unix_now, Storage};
+use crate::{app_settings, invoke_daemon_cli_json_output};
+
+const DEFAULT_RANGE_DAYS: i64 = 30;
+const RECENT_LIMIT: usize = 20;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum RejectionReason {
+    PriceTooHigh,
+    CapabilityMismatch, // VRAM, GPU model, CUDA or driver version, transports
+    ScheduleConflict,   // Outside the rental schedule, or the GPU is reserved or busy
+    JobPolicy,          // The provider's category/framework/duration rules
+    Manual,             // The provider rejected a manual-accept request
+    Other,
+}
+
+impl RejectionReason {
+    // The daemon and platform send a `reason_code` where they have one; older versions only send
+    // free text, which is matched against the phrases they are known to use.
+    pub fn classify(code: Option<&str>, text: &str) -> Self {
+        if let Some(reason) = code.and_then(|c| serde_json::from_value(serde_json::Value::String(c.to_string())).ok()) {
+            return reason;
+        }
+        let text = text.to_ascii_lowercase();
+        let mentions = |words: &[&str]| words.iter().any(|w| text.contains(w));
+        if mentions(&["price", "hourly rate", "budget", "expensive"]) {
+            RejectionReason::PriceTooHigh
+        } else if mentions(&["vram", "memory", "capabilit", "cuda", "driver", "model", "transport"]) {
+            RejectionReason::CapabilityMismatch
+        } else if mentions(&["schedule", "reserved", "busy", "unavailable", "outside"]) {
+            RejectionReason::ScheduleConflict
+        } else if mentions(&["category", "framework", "duration", "policy", "constraint"]) {
+            RejectionReason::JobPolicy
+        } else {
+            RejectionReason::Other
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct JobRejection {
+    pub job_id: String, // Request ID for requests that never became jobs
+    pub job_name: String,
+    pub reason: RejectionReason,
+    pub detail: String,
+    pub source: String, // "gui" | "daemon" | "platform" | "provider"
+    pub rejected_at: i64, // unix seconds
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ReasonCount {
+    pub reason: RejectionReason,
+    pub count: usize,
+    pub share_percent: f64,
+    pub latest_detail: String,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct RejectionStats {
+    pub from: i64,
+    pub to: i64,
+    pub total: usize,
+    pub by_reason: Vec<ReasonCount>, // Most frequent first
+    pub recent: Vec<JobRejection>,   // Newest first
+}
+
+pub fn record(app_handle: &AppHandle, job_id: &str, job_name: &str, reason: RejectionReason, detail: &str, source: &str) {
+    insert(app_handle, None, job_id, job_name, reason, detail, source);
+}
+
+// Records a rejection the daemon or platform reported under `report_key`. Returns false if it
+// was already recorded, so callers don't announce it again. Without storage there's nothing to
+// compare against, and every report counts as new.
+pub fn record_reported(app_handle: &AppHandle, report_key: &str, job_id: &str, job_name: &str, reason: RejectionReason, detail: &str, source: &str) -> bool {
+    insert(app_handle, Some(report_key), job_id, job_name, reason, detail, source)
+}
+
+// The daemon's or platform's ID for a reported decline or miss. Entries from versions without
+// one are keyed on their full contents, which repeat identically when the list does.
+pub fn report_key(source: &str, entry: &serde_json::Value) -> String {
+    match entry.get("id").and_then(|v| v.as_str()) {
+        Some(id) => format!("{}:{}", source, id),
+        None => format!("{}:{}", source, entry),
+    }
+}
+
+fn insert(app_handle: &AppHandle, report_key: Option<&str>, job_id: &str, job_name: &str, reason: RejectionReason, detail: &str, source: &str) -> bool {
+    if !app_settings::current(app_handle).privacy.store_job_metadata {
+        return true;
+    }
+    let Some(storage) = app_handle.try_state::<Storage>() else { return true };
+    let rejection = JobRejection {
+        job_id: job_id.to_string(),
+        job_name: job_name.to_string(),
+        reason,
+        detail: detail.to_string(),
+        source: source.to_string(),
+        rejected_at: unix_now(),
+    };
+    storage.insert_job_rejection(&rejection, report_key).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        true
+    })
+}
+
+// Renter requests the platform didn't match to this machine since the last call.
+pub async fn collect_match_misses(app_handle: &AppHandle) {
+    let Ok(misses) = invoke_daemon_cli_json_output::<Vec<serde_json::Value>>(app_handle, &["--get-match-misses-json"]).await else {
+        return;
+    };
+    for miss in misses {
+        let field = |name: &str| miss.get(name).and_then(|v| v.as_str()).unwrap_or_default();
+        let detail = miss.get("reason").and_then(|v| v.as_str()).unwrap_or("not matched");
+        let code = miss.get("reason_code").and_then(|v| v.as_str());
+        let key = report_key("platform", &miss);
+        record_reported(app_handle, &key, field("request_id"), field("name"), RejectionReason::classify(code, detail), detail, "platform");
+    }
+}
+
+pub fn stats(rejections: Vec<JobRejection>, from: i64, to: i64) -> RejectionStats {
+    let mut by_reason: Vec<ReasonCount> = Vec::new();
+    for rejection in &rejections {
+        match by_reason.iter_mut().find(|c| c.reason == rejection.reason) {
+            Some(count) => count.count += 1,
+            // Rejections arrive newest first, so the first one seen has the latest detail.
+            None => by_reason.push(ReasonCount { reason: rejection.reason, count: 1, share_percent: 0.0, latest_detail: rejection.detail.clone() }),
+        }
+    }
+    let total = rejections.len();
+    for count in &mut by_reason {
+        count.share_percent = count.count as f64 * 100.0 / total as f64;
+    }
+    by_reason.sort_by(|a, b| b.count.cmp(&a.count));
+    RejectionStats { from, to, total, by_reason, recent: rejections.into_iter().take(RECENT_LIMIT).collect() }
+}
+
+// Rejections in [from, to), unix seconds; the last 30 days by default.
+#[tauri::command]
+pub async fn get_rejection_stats(app_handle: AppHandle, from: Option<i64>, to: Option<i64>) -> Result<RejectionStats, String> {
+    let storage = app_handle.try_state::<Storage>().ok_or_else(|| "Local storage is unavailable.".to_string())?;
+    let to = to.unwrap_or_else(unix_now);
+    let from = from.unwrap_or(to - DEFAULT_RANGE_DAYS * 24 * 60 * 60);
+    if from >= to {
+        return Err("The start of the range must be before its end.".to_string());
+    }
+    Ok(stats(storage.job_rejections(from, to)?, from, to))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reason_code_wins_over_text() {
+        assert_eq!(RejectionReason::classify(Some("schedule_conflict"), "price too high"), RejectionReason::ScheduleConflict);
+    }
+
+    #[test]
+    fn unknown_code_falls_back_to_text() {
+        assert_eq!(RejectionReason::classify(Some("brand_new_code"), "Hourly rate above renter budget"), RejectionReason::PriceTooHigh);
+    }
+
+    #[test]
+    fn free_text_is_classified_by_known_phrases() {
+        let cases = [
+            ("Requires 48 GB VRAM", RejectionReason::CapabilityMismatch),
+            ("CUDA 12.2 driver required", RejectionReason::CapabilityMismatch),
+            ("GPU is reserved", RejectionReason::ScheduleConflict),
+            ("Outside rental schedule", RejectionReason::ScheduleConflict),
+            ("Category not allowed by policy", RejectionReason::JobPolicy),
+            ("Max duration exceeded", RejectionReason::JobPolicy),
+            ("", RejectionReason::Other),
+            ("renter cancelled", RejectionReason::Other),
+        ];
+        for (text, expected) in cases {
+            assert_eq!(RejectionReason::classify(None, text), expected, "{}", text);
+        }
+    }
+
+    #[test]
+    fn price_is_checked_before_capability() {
+        // "model" alone would mean a capability mismatch.
+        assert_eq!(RejectionReason::classify(None, "Price too high for this model"), RejectionReason::PriceTooHigh);
+    }
+
+    #[test]
+    fn report_keys_prefer_the_reported_id() {
+        let entry = serde_json::json!({ "id": "d-1", "job_id": "j-1" });
+        assert_eq!(report_key("daemon", &entry), "daemon:d-1");
+        let unkeyed = serde_json::json!({ "job_id": "j-1", "reason": "busy" });
+        assert_eq!(report_key("daemon", &unkeyed), report_key("daemon", &unkeyed.clone()));
+        assert_ne!(report_key("daemon", &unkeyed), report_key("platform", &unkeyed));
+    }
+}
@@ -0,0 +1,140 @@
+// Read-only view of the marketplace as renters see it.
+//
+// Providers rent now and then too, and the most direct way to judge a listing is to run the
+// search a renter would. Searches use the public marketplace endpoint, without the provider's
+// token, so results match what an anonymous renter gets; nothing here can rent or reserve.
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::units::{DgpuPerHour, MegaBytes};
+use crate::{api_client, get_detected_gpus, RentalMode};
+
+const DEFAULT_LIMIT: u32 = 50;
+const MAX_LIMIT: u32 = 200;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchSort {
+    #[default]
+    PriceAsc,
+    PriceDesc,
+    Reliability,
+    Performance,
+}
+
+impl SearchSort {
+    fn as_str(&self) -> &'static str {
+        match self {
+            SearchSort::PriceAsc => "price_asc",
+            SearchSort::PriceDesc => "price_desc",
+            SearchSort::Reliability => "reliability",
+            SearchSort::Performance => "performance",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct GpuSearchFilters {
+    pub gpu_model: Option<String>, // Substring match, e.g. "4090"
+    pub min_vram_mb: Option<MegaBytes>,
+    pub max_hourly_rate_dgpu: Option<DgpuPerHour>,
+    pub min_gpu_count: Option<u32>,
+    pub rental_mode: Option<RentalMode>,
+    pub region: Option<String>,
+    pub sort: SearchSort,
+    pub limit: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AvailableGpu {
+    pub listing_id: String,
+    pub gpu_model: String,
+    pub vram_total_mb: MegaBytes,
+    #[serde(default = "one")]
+    pub gpu_count: u32,
+    pub hourly_rate_dgpu: DgpuPerHour,
+    #[serde(default)]
+    pub rental_mode: RentalMode,
+    #[serde(default)]
+    pub region: Option<String>,
+    #[serde(default)]
+    pub reliability_percent: Option<f32>,
+    #[serde(default)]
+    pub benchmark_score: Option<f32>,
+}
+
+fn one() -> u32 {
+    1
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ListingComparison {
+    pub gpu_id: String,
+    pub gpu_model: String,
+    pub own_rate_dgpu: Option<DgpuPerHour>,
+    // Listings of the same model a renter would see, cheapest first.
+    pub comparable: Vec<AvailableGpu>,
+    pub cheaper_listings: usize,
+    // Where this GPU would appear in a price-sorted search, 1 being first.
+    pub price_rank: Option<usize>,
+    pub median_rate_dgpu: Option<DgpuPerHour>,
+}
+
+fn search_path(filters: &GpuSearchFilters) -> String {
+    let mut query = vec![
+        format!("sort={}", filters.sort.as_str()),
+        format!("limit={}", filters.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT)),
+    ];
+    if let Some(model) = filters.gpu_model.as_deref().map(str::trim).filter(|m| !m.is_empty()) {
+        query.push(format!("model={}", api_client::encode_component(model)));
+    }
+    if let Some(vram) = filters.min_vram_mb {
+        query.push(format!("min_vram_mb={}", vram.as_mb()));
+    }
+    if let Some(rate) = filters.max_hourly_rate_dgpu {
+        query.push(format!("max_rate_dgpu={}", rate.as_decimal()));
+    }
+    if let Some(count) = filters.min_gpu_count {
+        query.push(format!("min_gpu_count={}", count));
+    }
+    if let Some(mode) = filters.rental_mode {
+        query.push(format!("rental_mode={}", mode.as_str()));
+    }
+    if let Some(region) = filters.region.as_deref().filter(|r| !r.is_empty()) {
+        query.push(format!("region={}", api_client::encode_component(region)));
+    }
+    format!("/api/v1/marketplace/gpus?{}", query.join("&"))
+}
+
+pub async fn search(app_handle: &AppHandle, filters: &GpuSearchFilters) -> Result<Vec<AvailableGpu>, String> {
+    api_client::get_public_json(app_handle, &search_path(filters)).await
+}
+
+#[tauri::command]
+pub async fn search_available_gpus(app_handle: AppHandle, filters: GpuSearchFilters) -> Result<Vec<AvailableGpu>, String> {
+    search(&app_handle, &filters).await
+}
+
+#[tauri::command]
+pub async fn compare_listing_to_market(app_handle: AppHandle, gpu_id: String) -> Result<ListingComparison, String> {
+    let gpus = get_detected_gpus(app_handle.clone()).await?;
+    let gpu = gpus.into_iter().find(|g| g.id == gpu_id).ok_or_else(|| format!("No GPU with ID '{}'.", gpu_id))?;
+    let filters = GpuSearchFilters { gpu_model: Some(gpu.model.clone()), limit: Some(MAX_LIMIT), ..GpuSearchFilters::default() };
+    let mut comparable = search(&app_handle, &filters).await?;
+    comparable.sort_by_key(|l| l.hourly_rate_dgpu);
+
+    let own_rate = gpu.current_hourly_rate_dgpu;
+    let cheaper_listings = own_rate.map_or(0, |rate| comparable.iter().filter(|l| l.hourly_rate_dgpu < rate).count());
+    let median_rate_dgpu = (!comparable.is_empty()).then(|| comparable[comparable.len() / 2].hourly_rate_dgpu);
+    Ok(ListingComparison {
+        gpu_id,
+        gpu_model: gpu.model,
+        own_rate_dgpu: own_rate,
+        price_rank: own_rate.map(|_| cheaper_listings + 1),
+        cheaper_listings,
+        median_rate_dgpu,
+        comparable,
+    })
+}
@@ -0,0 +1,163 @@
+// Provider reputation and renter reviews from the platform.
+//
+// Both are cached in the app data directory so the last known values are shown while offline.
+// A background watcher polls for reviews and emits `new_review` for each one not seen before,
+// carrying the job ID so it can be matched against that job's logs. The cache is per account.
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+
+use crate::{accounts, api_client, emit_log_entry, event_feed, get_timestamp, plugins, shutdown};
+
+const CACHE_FILE_NAME: &str = "reputation_cache.json";
+const POLL_INTERVAL: Duration = Duration::from_secs(15 * 60);
+const REVIEW_PAGE_SIZE: u32 = 50;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Reputation {
+    pub score: f32, // 0..5
+    pub total_reviews: u32,
+    pub completed_jobs: u32,
+    #[serde(default)]
+    pub completion_rate: Option<f32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Review {
+    pub id: String,
+    pub job_id: String,
+    pub rating: u8, // 1..5
+    #[serde(default)]
+    pub comment: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+struct ReputationCache {
+    reputation: Option<Reputation>,
+    reviews: Vec<Review>,
+    reviews_fetched_at: Option<String>,
+}
+
+pub struct ReputationState {
+    cache: Mutex<ReputationCache>,
+    path: Mutex<Option<PathBuf>>,
+}
+
+fn read_cache(app_handle: &AppHandle) -> (ReputationCache, Option<PathBuf>) {
+    let path = accounts::data_dir(app_handle).map(|dir| dir.join(CACHE_FILE_NAME));
+    let cache = path
+        .as_ref()
+        .and_then(|p| fs::read_to_string(p).ok())
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default();
+    (cache, path)
+}
+
+impl ReputationState {
+    pub fn load(app_handle: &AppHandle) -> Self {
+        let (cache, path) = read_cache(app_handle);
+        ReputationState { cache: Mutex::new(cache), path: Mutex::new(path) }
+    }
+
+    // Called after the active account changes.
+    pub fn reload(&self, app_handle: &AppHandle) {
+        let (cache, path) = read_cache(app_handle);
+        *self.cache.lock().unwrap() = cache;
+        *self.path.lock().unwrap() = path;
+    }
+
+    fn update<T>(&self, f: impl FnOnce(&mut ReputationCache) -> T) -> T {
+        let mut cache = self.cache.lock().unwrap();
+        let result = f(&mut cache);
+        if let Some(path) = &*self.path.lock().unwrap() {
+            if let Some(dir) = path.parent() {
+                let _ = fs::create_dir_all(dir);
+            }
+            if let Ok(json) = serde_json::to_string_pretty(&*cache) {
+                if let Err(e) = fs::write(path, json) {
+                    eprintln!("Failed to persist reputation cache: {}", e);
+                }
+            }
+        }
+        result
+    }
+
+    pub fn cached_score(&self) -> Option<f32> {
+        self.cache.lock().unwrap().reputation.as_ref().map(|r| r.score)
+    }
+}
+
+pub async fn refresh_reputation(app_handle: &AppHandle) -> Result<Reputation, String> {
+    let reputation: Reputation = api_client::get_json(app_handle, "/api/v1/providers/me/reputation").await?;
+    app_handle.state::<ReputationState>().update(|cache| cache.reputation = Some(reputation.clone()));
+    Ok(reputation)
+}
+
+// Fetches the latest reviews and returns those not seen before. The first fetch only seeds the
+// cache, so installing the app doesn't replay the provider's entire review history.
+pub async fn refresh_reviews(app_handle: &AppHandle) -> Result<(Vec<Review>, Vec<Review>), String> {
+    let path = format!("/api/v1/providers/me/reviews?limit={}", REVIEW_PAGE_SIZE);
+    let latest: Vec<Review> = api_client::get_json(app_handle, &path).await?;
+    let new_reviews = app_handle.state::<ReputationState>().update(|cache| {
+        let new_reviews: Vec<Review> = match cache.reviews_fetched_at {
+            Some(_) => latest.iter().filter(|r| !cache.reviews.iter().any(|c| c.id == r.id)).cloned().collect(),
+            None => Vec::new(),
+        };
+        cache.reviews = latest.clone();
+        cache.reviews_fetched_at = Some(get_timestamp());
+        new_reviews
+    });
+    for review in &new_reviews {
+        emit_log_entry(app_handle, "status", format!("New {}-star review for job {}.", review.rating, review.job_id));
+        plugins::notify(app_handle, "new_review", &json!(review), false);
+        if let Err(e) = app_handle.emit_all("new_review", review) {
+            eprintln!("Failed to emit new_review event: {}", e);
+        }
+    }
+    Ok((latest, new_reviews))
+}
+
+pub fn spawn_review_watcher(app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            // The event feed announces new reviews while it is connected.
+            if !event_feed::is_connected() {
+                if let Err(e) = refresh_reviews(&app_handle).await {
+                    eprintln!("Review poll failed: {}", e);
+                }
+            }
+            if !shutdown::sleep(POLL_INTERVAL).await {
+                break;
+            }
+        }
+    });
+}
+
+// Falls back to the cached values when the platform can't be reached.
+#[tauri::command]
+pub async fn get_my_reputation(app_handle: AppHandle, state: State<'_, ReputationState>) -> Result<Reputation, String> {
+    match refresh_reputation(&app_handle).await {
+        Ok(reputation) => Ok(reputation),
+        Err(e) => state.cache.lock().unwrap().reputation.clone().ok_or(e),
+    }
+}
+
+#[tauri::command]
+pub async fn get_recent_reviews(app_handle: AppHandle, state: State<'_, ReputationState>) -> Result<Vec<Review>, String> {
+    match refresh_reviews(&app_handle).await {
+        Ok((reviews, _)) => Ok(reviews),
+        Err(e) => {
+            let cache = state.cache.lock().unwrap();
+            match cache.reviews_fetched_at {
+                Some(_) => Ok(cache.reviews.clone()),
+                None => Err(e),
+            }
+        }
+    }
+}
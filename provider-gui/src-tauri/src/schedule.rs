@@ -0,0 +1,300 @@
+// Weekly rental schedule and notification quiet hours.
+//
+// Both are sets of weekly windows in wall-clock time, stored with an explicit IANA zone rather
+// than the machine's, so moving the rig or changing its time zone doesn't shift them. Windows
+// are turned into UTC instants afresh every time they are evaluated, which is what keeps them
+// right across DST changes. Two edge cases need a rule:
+// - a local time skipped by a spring-forward jump resolves to the moment of the jump, so a
+//   window starting at 02:30 on that day opens at 03:00, and one entirely inside the gap is
+//   skipped;
+// - a local time that occurs twice after a fall-back resolves to its first occurrence, so no
+//   window opens or closes twice.
+// Outside the rental schedule the daemon is told to hold new rentals (see `announcements`);
+// during quiet hours, notification plugins only receive urgent events. Scheduled daemon restarts
+// resolve their times the same way (see `daemon_restart`).
+
+use chrono::{DateTime, Datelike, Duration as ChronoDuration, LocalResult, NaiveDateTime, NaiveTime, TimeZone, Utc, Weekday};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::app_settings::{self, AppSettings};
+use crate::{announcements, emit_log_entry, shutdown};
+
+// Boundaries are exact; this only bounds how late a machine time zone change is noticed.
+const MAX_SLEEP: Duration = Duration::from_secs(60);
+// Longer than any real DST or zone-change gap.
+const MAX_GAP_MINUTES: i64 = 24 * 60;
+// Still delivered during quiet hours.
+pub const URGENT_EVENTS: &[&str] = &["security_anomaly", "lockdown_engaged", "hardware_alert"];
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ScheduleWindow {
+    pub days: Vec<Weekday>, // The day each window starts on
+    pub start: NaiveTime,
+    pub end: NaiveTime, // Before `start` runs past midnight; equal to it covers the whole day
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ZonedSchedule {
+    pub timezone: String, // IANA name, e.g. "Europe/Berlin"
+    pub windows: Vec<ScheduleWindow>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ScheduleStatus {
+    pub system_timezone: Option<String>,
+    pub rentals_open: bool,
+    pub rentals_next_change: Option<String>,
+    pub quiet_hours_active: bool,
+    pub quiet_hours_next_change: Option<String>,
+}
+
+pub fn resolve(tz: &Tz, local: NaiveDateTime) -> DateTime<Utc> {
+    match tz.from_local_datetime(&local) {
+        LocalResult::Single(t) => t.with_timezone(&Utc),
+        LocalResult::Ambiguous(first, _) => first.with_timezone(&Utc),
+        // In a gap: the first minute that exists again is the moment of the jump.
+        LocalResult::None => (1..=MAX_GAP_MINUTES)
+            .find_map(|m| tz.from_local_datetime(&(local + ChronoDuration::minutes(m))).earliest())
+            .map(|t| t.with_timezone(&Utc))
+            .unwrap_or_else(|| Utc.from_utc_datetime(&local)),
+    }
+}
+
+pub fn parse_tz(name: &str) -> Result<Tz, String> {
+    name.parse::<Tz>().map_err(|_| format!("Unknown time zone '{}'; use an IANA name such as 'Europe/Berlin'.", name))
+}
+
+impl ZonedSchedule {
+    pub fn tz(&self) -> Result<Tz, String> {
+        parse_tz(&self.timezone)
+    }
+
+    // The windows overlapping [from, to), as UTC intervals.
+    fn intervals(&self, tz: &Tz, from: DateTime<Utc>, to: DateTime<Utc>) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+        // A day earlier, for windows that started yesterday and run past midnight.
+        let first = from.with_timezone(tz).date_naive() - ChronoDuration::days(1);
+        let last = to.with_timezone(tz).date_naive();
+        let mut intervals = Vec::new();
+        for date in first.iter_days().take_while(|d| *d <= last) {
+            for window in self.windows.iter().filter(|w| w.days.contains(&date.weekday())) {
+                let end_date = if window.end > window.start { date } else { date + ChronoDuration::days(1) };
+                let start = resolve(tz, date.and_time(window.start));
+                let end = resolve(tz, end_date.and_time(window.end));
+                if start < end && end > from && start < to {
+                    intervals.push((start, end));
+                }
+            }
+        }
+        intervals
+    }
+
+    pub fn contains(&self, tz: &Tz, at: DateTime<Utc>) -> bool {
+        !self.intervals(tz, at, at + ChronoDuration::seconds(1)).is_empty()
+    }
+
+    // The next instant the schedule switches on or off, looking a week ahead. Back-to-back
+    // windows don't count as a change.
+    pub fn next_change(&self, tz: &Tz, at: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let active = self.contains(tz, at);
+        let mut edges: Vec<DateTime<Utc>> =
+            self.intervals(tz, at, at + ChronoDuration::days(8)).into_iter().flat_map(|(start, end)| [start, end]).filter(|t| *t > at).collect();
+        edges.sort();
+        edges.into_iter().find(|t| self.contains(tz, *t) != active)
+    }
+}
+
+// A missing schedule means `unset`, with no change coming.
+fn evaluate(schedule: Option<&ZonedSchedule>, unset: bool, at: DateTime<Utc>) -> (bool, Option<DateTime<Utc>>) {
+    // Zones are checked on save, so an unknown one only comes from a hand-edited settings file.
+    match schedule.map(|s| (s, s.tz())) {
+        Some((schedule, Ok(tz))) => (schedule.contains(&tz, at), schedule.next_change(&tz, at)),
+        _ => (unset, None),
+    }
+}
+
+pub fn validate(settings: &AppSettings) -> Result<(), String> {
+    for schedule in [&settings.rental_schedule, &settings.quiet_hours, &settings.prefetch.schedule].into_iter().flatten() {
+        schedule.tz()?;
+    }
+    Ok(())
+}
+
+pub fn system_timezone() -> Option<String> {
+    iana_time_zone::get_timezone().ok()
+}
+
+pub fn status<R: Runtime>(manager: &impl Manager<R>) -> ScheduleStatus {
+    let settings = app_settings::current(manager);
+    let now = Utc::now();
+    let (rentals_open, rentals_next) = evaluate(settings.rental_schedule.as_ref(), true, now);
+    let (quiet_hours_active, quiet_next) = evaluate(settings.quiet_hours.as_ref(), false, now);
+    ScheduleStatus {
+        system_timezone: system_timezone(),
+        rentals_open,
+        rentals_next_change: rentals_next.map(|t| t.to_rfc3339()),
+        quiet_hours_active,
+        quiet_hours_next_change: quiet_next.map(|t| t.to_rfc3339()),
+    }
+}
+
+pub fn rentals_open<R: Runtime>(manager: &impl Manager<R>) -> bool {
+    evaluate(app_settings::current(manager).rental_schedule.as_ref(), true, Utc::now()).0
+}
+
+pub fn quiet_hours_active<R: Runtime>(manager: &impl Manager<R>) -> bool {
+    evaluate(app_settings::current(manager).quiet_hours.as_ref(), false, Utc::now()).0
+}
+
+// Whether any other schedule is on right now; `unset` when there is none.
+pub fn active(schedule: Option<&ZonedSchedule>, unset: bool) -> bool {
+    evaluate(schedule, unset, Utc::now()).0
+}
+
+fn until_next_change(status: &ScheduleStatus) -> Duration {
+    [&status.rentals_next_change, &status.quiet_hours_next_change]
+        .into_iter()
+        .flatten()
+        .filter_map(|t| DateTime::parse_from_rfc3339(t).ok())
+        .filter_map(|t| (t.with_timezone(&Utc) - Utc::now()).to_std().ok())
+        .min()
+        .unwrap_or(MAX_SLEEP)
+        .clamp(Duration::from_secs(1), MAX_SLEEP)
+}
+
+// Wakes at each boundary, and at least every minute to notice machine time zone changes.
+pub fn spawn_schedule_task(app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut last: Option<ScheduleStatus> = None;
+        loop {
+            let current = status(&app_handle);
+            if let Some(previous) = &last {
+                if previous.system_timezone != current.system_timezone {
+                    emit_log_entry(
+                        &app_handle,
+                        "status",
+                        format!(
+                            "System time zone changed from {} to {}; schedules keep their own zones.",
+                            previous.system_timezone.as_deref().unwrap_or("unknown"),
+                            current.system_timezone.as_deref().unwrap_or("unknown")
+                        ),
+                    );
+                    if let Err(e) = app_handle.emit_all("system_timezone_changed", json!({ "timezone": current.system_timezone })) {
+                        eprintln!("Failed to emit system_timezone_changed event: {}", e);
+                    }
+                }
+            }
+            if last.as_ref().map(|p| (p.rentals_open, p.quiet_hours_active)) != Some((current.rentals_open, current.quiet_hours_active)) {
+                if last.as_ref().is_some_and(|p| p.rentals_open != current.rentals_open) {
+                    announcements::enforce_block(&app_handle).await;
+                }
+                if let Err(e) = app_handle.emit_all("schedule_changed", current.clone()) {
+                    eprintln!("Failed to emit schedule_changed event: {}", e);
+                }
+            }
+            let wait = until_next_change(&current);
+            last = Some(current);
+            if !shutdown::sleep(wait).await {
+                break;
+            }
+        }
+    });
+}
+
+#[tauri::command]
+pub async fn get_schedule_status(app_handle: AppHandle) -> Result<ScheduleStatus, String> {
+    Ok(status(&app_handle))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    fn schedule(days: &[Weekday], start: (u32, u32), end: (u32, u32)) -> ZonedSchedule {
+        ZonedSchedule {
+            timezone: "America/New_York".to_string(),
+            windows: vec![ScheduleWindow {
+                days: days.to_vec(),
+                start: NaiveTime::from_hms_opt(start.0, start.1, 0).unwrap(),
+                end: NaiveTime::from_hms_opt(end.0, end.1, 0).unwrap(),
+            }],
+        }
+    }
+
+    // 2024-03-10: New York skips 02:00-03:00 (07:00 UTC).
+    #[test]
+    fn window_starting_in_skipped_hour_opens_at_the_jump() {
+        let s = schedule(&[Weekday::Sun], (2, 30), (4, 0));
+        let tz = s.tz().unwrap();
+        assert!(!s.contains(&tz, utc("2024-03-10T06:59:00Z")));
+        assert!(s.contains(&tz, utc("2024-03-10T07:00:00Z")));
+        assert_eq!(s.next_change(&tz, utc("2024-03-10T06:00:00Z")), Some(utc("2024-03-10T07:00:00Z")));
+        assert_eq!(s.next_change(&tz, utc("2024-03-10T07:00:00Z")), Some(utc("2024-03-10T08:00:00Z")));
+    }
+
+    #[test]
+    fn window_inside_skipped_hour_is_skipped() {
+        let s = schedule(&[Weekday::Sun], (2, 10), (2, 50));
+        let tz = s.tz().unwrap();
+        assert!(!s.contains(&tz, utc("2024-03-10T07:00:00Z")));
+        // Next opening is the following Sunday, at 02:10 EDT.
+        assert_eq!(s.next_change(&tz, utc("2024-03-10T06:00:00Z")), Some(utc("2024-03-17T06:10:00Z")));
+    }
+
+    // 2024-11-03: New York repeats 01:00-02:00, first as EDT (05:00 UTC), then as EST (06:00 UTC).
+    #[test]
+    fn window_in_repeated_hour_uses_first_occurrence() {
+        let s = schedule(&[Weekday::Sun], (1, 15), (1, 45));
+        let tz = s.tz().unwrap();
+        assert!(s.contains(&tz, utc("2024-11-03T05:30:00Z")));
+        assert!(!s.contains(&tz, utc("2024-11-03T06:30:00Z")));
+        assert_eq!(s.next_change(&tz, utc("2024-11-03T05:30:00Z")), Some(utc("2024-11-03T05:45:00Z")));
+        assert_eq!(s.next_change(&tz, utc("2024-11-03T05:45:00Z")), Some(utc("2024-11-10T06:15:00Z")));
+    }
+
+    #[test]
+    fn window_ending_after_repeated_hour_runs_the_extra_hour() {
+        let s = schedule(&[Weekday::Sat], (22, 0), (3, 0));
+        let tz = s.tz().unwrap();
+        // 22:00 EDT to 03:00 EST is six real hours.
+        assert_eq!(s.next_change(&tz, utc("2024-11-03T02:30:00Z")), Some(utc("2024-11-03T08:00:00Z")));
+        assert!(s.contains(&tz, utc("2024-11-03T06:30:00Z")));
+    }
+
+    #[test]
+    fn whole_day_window_follows_the_local_day() {
+        let s = schedule(&[Weekday::Sun], (0, 0), (0, 0));
+        let tz = s.tz().unwrap();
+        // The Sunday of the spring-forward change is 23 hours long.
+        assert!(s.contains(&tz, utc("2024-03-10T05:00:00Z")));
+        assert!(s.contains(&tz, utc("2024-03-11T03:59:00Z")));
+        assert!(!s.contains(&tz, utc("2024-03-11T04:00:00Z")));
+    }
+
+    #[test]
+    fn back_to_back_windows_are_one_change() {
+        let mut s = schedule(&[Weekday::Mon], (22, 0), (0, 0));
+        s.windows.push(ScheduleWindow {
+            days: vec![Weekday::Tue],
+            start: NaiveTime::from_hms_opt(0, 0, 0).unwrap(),
+            end: NaiveTime::from_hms_opt(6, 0, 0).unwrap(),
+        });
+        let tz = s.tz().unwrap();
+        // Monday 2024-06-03 23:00 EDT.
+        assert_eq!(s.next_change(&tz, utc("2024-06-04T03:00:00Z")), Some(utc("2024-06-04T10:00:00Z")));
+    }
+
+    #[test]
+    fn unknown_zone_is_rejected() {
+        let mut s = schedule(&[Weekday::Mon], (9, 0), (17, 0));
+        s.timezone = "Mars/Olympus_Mons".to_string();
+        assert!(s.tz().is_err());
+    }
+}
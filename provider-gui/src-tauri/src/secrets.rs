@@ -0,0 +1,38 @@
+// OS keychain access for credentials the GUI needs to keep (sync passphrases, API tokens).
+//
+// Secrets never go into `app_settings.json`; settings only reference them by key.
+
+use keyring::Entry;
+
+use crate::redaction;
+
+const KEYCHAIN_SERVICE: &str = "com.dantegpu.provider.gui";
+
+fn entry(key: &str) -> Result<Entry, String> {
+    Entry::new(KEYCHAIN_SERVICE, key).map_err(|e| format!("Keychain unavailable: {}", e))
+}
+
+pub fn get(key: &str) -> Result<Option<String>, String> {
+    match entry(key)?.get_password() {
+        Ok(value) => {
+            redaction::remember(&value);
+            Ok(Some(value))
+        }
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(format!("Failed to read '{}' from keychain: {}", key, e)),
+    }
+}
+
+pub fn set(key: &str, value: &str) -> Result<(), String> {
+    redaction::remember(value);
+    entry(key)?
+        .set_password(value)
+        .map_err(|e| format!("Failed to store '{}' in keychain: {}", key, e))
+}
+
+pub fn delete(key: &str) -> Result<(), String> {
+    match entry(key)?.delete_password() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(format!("Failed to remove '{}' from keychain: {}", key, e)),
+    }
+}
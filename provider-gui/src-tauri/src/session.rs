@@ -0,0 +1,194 @@
+// Opt-in session recording for support.
+//
+// While recording, every command the frontend invokes, every daemon CLI call and every daemon
+// status transition is appended to an in-memory trace with its timing. Parameters are
+// sanitized before they are stored. The trace is plain JSON that support can replay command
+// by command, and it can be attached to a diagnostic bundle.
+
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::fs;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, Runtime, State};
+
+use crate::crash_reports::scrub;
+use crate::{emit_log_entry, get_timestamp};
+
+const TRACE_FORMAT_VERSION: u32 = 1;
+// Enough for a long support session without letting a forgotten recording grow unbounded.
+const MAX_TRACE_EVENTS: usize = 10_000;
+const SENSITIVE_KEYS: &[&str] = &["passphrase", "password", "credential", "token", "secret", "private_key"];
+
+#[derive(Serialize, Debug, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum TraceEventKind {
+    Command,
+    DaemonCall,
+    StatusChange,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct TraceEvent {
+    pub offset_ms: u64, // Since the recording started, for replay pacing
+    pub at: String,
+    pub kind: TraceEventKind,
+    pub name: String,
+    pub params: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub outcome: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct SessionTrace {
+    pub format_version: u32,
+    pub app_version: String,
+    pub os: String,
+    pub started_at: Option<String>,
+    pub recording: bool,
+    pub truncated: bool,
+    pub events: Vec<TraceEvent>,
+}
+
+struct Recording {
+    started: Instant,
+    started_at: String,
+    events: Vec<TraceEvent>,
+    truncated: bool,
+}
+
+pub struct SessionState {
+    active: AtomicBool,
+    recording: Mutex<Option<Recording>>,
+}
+
+impl SessionState {
+    pub fn new() -> Self {
+        SessionState { active: AtomicBool::new(false), recording: Mutex::new(None) }
+    }
+
+    fn trace(&self) -> SessionTrace {
+        let recording = self.recording.lock().unwrap();
+        SessionTrace {
+            format_version: TRACE_FORMAT_VERSION,
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            os: format!("{} {}", std::env::consts::OS, std::env::consts::ARCH),
+            started_at: recording.as_ref().map(|r| r.started_at.clone()),
+            recording: self.active.load(Ordering::Relaxed),
+            truncated: recording.as_ref().is_some_and(|r| r.truncated),
+            events: recording.as_ref().map(|r| r.events.clone()).unwrap_or_default(),
+        }
+    }
+}
+
+// Redacts values under sensitive keys and scrubs identifying data from everything else.
+pub fn sanitize(value: &Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, v)| {
+                    let lowered = key.to_lowercase();
+                    if SENSITIVE_KEYS.iter().any(|s| lowered.contains(s)) {
+                        (key.clone(), json!("<redacted>"))
+                    } else {
+                        (key.clone(), sanitize(v))
+                    }
+                })
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.iter().map(sanitize).collect()),
+        Value::String(s) => Value::String(scrub(s)),
+        other => other.clone(),
+    }
+}
+
+fn push<R: Runtime>(
+    manager: &impl Manager<R>,
+    kind: TraceEventKind,
+    name: &str,
+    params: Value,
+    duration: Option<Duration>,
+    outcome: Option<String>,
+) {
+    let Some(state) = manager.try_state::<SessionState>() else { return };
+    // Cheap early-out: this sits on the path of every command.
+    if !state.active.load(Ordering::Relaxed) {
+        return;
+    }
+    let mut guard = state.recording.lock().unwrap();
+    let Some(recording) = guard.as_mut() else { return };
+    if recording.events.len() >= MAX_TRACE_EVENTS {
+        recording.truncated = true;
+        return;
+    }
+    recording.events.push(TraceEvent {
+        offset_ms: recording.started.elapsed().as_millis() as u64,
+        at: get_timestamp(),
+        kind,
+        name: name.to_string(),
+        params: sanitize(&params),
+        duration_ms: duration.map(|d| d.as_millis() as u64),
+        outcome: outcome.map(|o| scrub(&o)),
+    });
+}
+
+pub fn record_daemon_call(app_handle: &AppHandle, args: &[&str], elapsed: Duration, result: Result<(), &String>) {
+    let outcome = match result {
+        Ok(()) => "ok".to_string(),
+        Err(e) => format!("error: {}", e),
+    };
+    push(app_handle, TraceEventKind::DaemonCall, args.first().copied().unwrap_or(""), json!(args), Some(elapsed), Some(outcome));
+}
+
+pub fn record_status<R: Runtime>(manager: &impl Manager<R>, status: &str) {
+    push(manager, TraceEventKind::StatusChange, status, Value::Null, None, None);
+}
+
+// Called by the command middleware for every invocation (see `middleware`).
+pub fn record_command<R: Runtime>(manager: &impl Manager<R>, command: &str, payload: &Value) {
+    push(manager, TraceEventKind::Command, command, payload.clone(), None, None);
+}
+
+#[tauri::command]
+pub async fn start_session_recording(app_handle: AppHandle, state: State<'_, SessionState>) -> Result<(), String> {
+    *state.recording.lock().unwrap() = Some(Recording {
+        started: Instant::now(),
+        started_at: get_timestamp(),
+        events: Vec::new(),
+        truncated: false,
+    });
+    state.active.store(true, Ordering::Relaxed);
+    emit_log_entry(&app_handle, "status", "Session recording started.".to_string());
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn stop_session_recording(app_handle: AppHandle, state: State<'_, SessionState>) -> Result<SessionTrace, String> {
+    state.active.store(false, Ordering::Relaxed);
+    let trace = state.trace();
+    emit_log_entry(&app_handle, "status", format!("Session recording stopped ({} events).", trace.events.len()));
+    Ok(trace)
+}
+
+#[tauri::command]
+pub async fn get_session_trace(state: State<'_, SessionState>) -> Result<SessionTrace, String> {
+    Ok(state.trace())
+}
+
+#[tauri::command]
+pub async fn export_session_trace(state: State<'_, SessionState>, path: String) -> Result<usize, String> {
+    let trace = state.trace();
+    let json = serde_json::to_string_pretty(&trace).map_err(|e| format!("Failed to serialize session trace: {}", e))?;
+    fs::write(&path, json).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+    Ok(trace.events.len())
+}
+
+pub fn current_trace(app_handle: &AppHandle) -> Option<SessionTrace> {
+    app_handle
+        .try_state::<SessionState>()
+        .map(|state| state.trace())
+        .filter(|trace| !trace.events.is_empty())
+}
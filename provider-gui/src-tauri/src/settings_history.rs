@@ -0,0 +1,253 @@
+// Periodic snapshots of the provider's settings, and diffs between any two points in time.
+//
+// When earnings drop the first question is usually "did I change something?". Every few minutes
+// the daemon's provider settings, each GPU's listing (rate, availability, rental mode) and the
+// GUI settings are captured; a snapshot identical to the previous one isn't stored, so each row
+// marks a change. `diff_settings` compares the snapshots in effect at two times field by field.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+use crate::app_settings::{self, AppSettings};
+use crate::storage::{unix_now, Storage};
+use crate::units::DgpuPerHour;
+use crate::{invoke_daemon_cli_json_output, nvidia_smi, shutdown, ProviderSettings, RentalMode};
+
+const SNAPSHOT_INTERVAL: Duration = Duration::from_secs(15 * 60);
+const RETENTION_DAYS: i64 = 90;
+const DEFAULT_WINDOW_SECS: i64 = 24 * 60 * 60;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct GpuListing {
+    pub name: String,
+    pub hourly_rate_dgpu: Option<DgpuPerHour>,
+    pub is_available_for_rent: bool,
+    pub rental_mode: RentalMode,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SettingsSnapshot {
+    pub taken_at: i64, // unix seconds
+    pub provider: ProviderSettings,
+    pub gpus: BTreeMap<String, GpuListing>, // by GPU id
+    pub app: AppSettings,
+}
+
+impl SettingsSnapshot {
+    // Everything but the timestamp, flattened to dotted paths such as `gpus.GPU-0.is_available_for_rent`.
+    // Arrays are compared whole: a schedule window list either changed or it didn't.
+    fn fields(&self) -> BTreeMap<String, Value> {
+        let mut fields = BTreeMap::new();
+        for (section, value) in [
+            ("provider", serde_json::to_value(&self.provider)),
+            ("gpus", serde_json::to_value(&self.gpus)),
+            ("app", serde_json::to_value(&self.app)),
+        ] {
+            flatten(section.to_string(), value.unwrap_or(Value::Null), &mut fields);
+        }
+        fields
+    }
+}
+
+fn flatten(path: String, value: Value, out: &mut BTreeMap<String, Value>) {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            for (key, value) in map {
+                flatten(format!("{}.{}", path, key), value, out);
+            }
+        }
+        Value::Object(_) => {
+            out.insert(path, Value::Object(Map::new()));
+        }
+        other => {
+            out.insert(path, other);
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct SettingChange {
+    pub path: String,
+    pub before: Option<Value>, // None: the field (or GPU) didn't exist yet
+    pub after: Option<Value>,  // None: the field (or GPU) is gone
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct SettingsDiff {
+    pub from_snapshot_at: i64,
+    pub to_snapshot_at: i64,
+    pub changes: Vec<SettingChange>,
+}
+
+pub fn diff(before: &SettingsSnapshot, after: &SettingsSnapshot) -> Vec<SettingChange> {
+    let before_fields = before.fields();
+    let mut after_fields = after.fields();
+    let mut changes = Vec::new();
+    for (path, old) in before_fields {
+        match after_fields.remove(&path) {
+            Some(new) if new == old => {}
+            new => changes.push(SettingChange { path, before: Some(old), after: new }),
+        }
+    }
+    changes.extend(after_fields.into_iter().map(|(path, new)| SettingChange { path, before: None, after: Some(new) }));
+    changes.sort_by(|a, b| a.path.cmp(&b.path));
+    changes
+}
+
+// The current settings, or None while the daemon can't be asked for its half.
+async fn capture(app_handle: &AppHandle) -> Option<SettingsSnapshot> {
+    let provider = invoke_daemon_cli_json_output::<ProviderSettings>(app_handle, &["--get-settings-json"]).await.ok()?;
+    let gpus = nvidia_smi::detect_gpus(app_handle).await.ok()?;
+    Some(SettingsSnapshot {
+        taken_at: unix_now(),
+        provider,
+        gpus: gpus
+            .into_iter()
+            .map(|g| {
+                let listing = GpuListing {
+                    name: g.name,
+                    hourly_rate_dgpu: g.current_hourly_rate_dgpu,
+                    is_available_for_rent: g.is_available_for_rent,
+                    rental_mode: g.rental_mode,
+                };
+                (g.id, listing)
+            })
+            .collect(),
+        app: app_settings::current(app_handle),
+    })
+}
+
+// Stores the current settings unless they match the latest snapshot. Returns the snapshot now in
+// effect, stored or not.
+pub async fn record(app_handle: &AppHandle) -> Result<SettingsSnapshot, String> {
+    let storage = app_handle.try_state::<Storage>().ok_or_else(|| "Local storage is unavailable.".to_string())?;
+    let snapshot = capture(app_handle).await.ok_or_else(|| "The daemon isn't answering, so the current settings can't be read.".to_string())?;
+    let unchanged = storage.settings_snapshot_at(i64::MAX)?.is_some_and(|latest| diff(&latest, &snapshot).is_empty());
+    if !unchanged {
+        storage.insert_settings_snapshot(&snapshot)?;
+    }
+    Ok(snapshot)
+}
+
+// Records a snapshot right after a settings save, so the change is dated when it was made rather
+// than at the next periodic snapshot.
+pub fn note_change(app_handle: &AppHandle) {
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = record(&app_handle).await {
+            eprintln!("Settings snapshot skipped: {}", e);
+        }
+    });
+}
+
+pub fn spawn_snapshot_task(app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            if let Err(e) = record(&app_handle).await {
+                eprintln!("Settings snapshot skipped: {}", e);
+            }
+            if let Some(storage) = app_handle.try_state::<Storage>() {
+                if let Err(e) = storage.prune_settings_snapshots(unix_now() - RETENTION_DAYS * 24 * 60 * 60) {
+                    eprintln!("{}", e);
+                }
+            }
+            if !shutdown::sleep(SNAPSHOT_INTERVAL).await {
+                break;
+            }
+        }
+    });
+}
+
+// What changed between the settings in effect at `from` (default: a day before `to`) and at `to`
+// (default: now, read live, or the latest snapshot while the daemon is offline; `to_snapshot_at`
+// tells which). If nothing was recorded as early as `from`, the oldest snapshot is
+// used and `from_snapshot_at` says so.
+#[tauri::command]
+pub async fn diff_settings(app_handle: AppHandle, from: Option<i64>, to: Option<i64>) -> Result<SettingsDiff, String> {
+    let storage = app_handle.try_state::<Storage>().ok_or_else(|| "Local storage is unavailable.".to_string())?;
+    let after = match to {
+        Some(to) => storage.settings_snapshot_at(to)?.ok_or_else(|| "No settings were recorded that early.".to_string())?,
+        None => match record(&app_handle).await {
+            Ok(snapshot) => snapshot,
+            Err(e) => storage.settings_snapshot_at(i64::MAX)?.ok_or(e)?,
+        },
+    };
+    let to = to.unwrap_or(after.taken_at);
+    let from = from.unwrap_or(to - DEFAULT_WINDOW_SECS);
+    if from >= to {
+        return Err("The start of the range must be before its end.".to_string());
+    }
+    let before = match storage.settings_snapshot_at(from)? {
+        Some(snapshot) => snapshot,
+        None => storage.oldest_settings_snapshot()?.ok_or_else(|| "No settings snapshots have been recorded yet.".to_string())?,
+    };
+    Ok(SettingsDiff { from_snapshot_at: before.taken_at, to_snapshot_at: after.taken_at, changes: diff(&before, &after) })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::money::Decimal;
+    use serde_json::json;
+
+    fn snapshot(gpus: &[(&str, i64, bool)], lightweight_mode: bool) -> SettingsSnapshot {
+        SettingsSnapshot {
+            taken_at: 0,
+            provider: ProviderSettings {
+                default_hourly_rate_dgpu: DgpuPerHour::new(Decimal::new(5, 1)),
+                preferred_currency: "DGPU".to_string(),
+                min_job_duration_minutes: 10,
+                max_concurrent_jobs: 1,
+            },
+            gpus: gpus
+                .iter()
+                .map(|&(id, rate_tenths, available)| {
+                    let listing = GpuListing {
+                        name: "RTX 4090".to_string(),
+                        hourly_rate_dgpu: Some(DgpuPerHour::new(Decimal::new(rate_tenths, 1))),
+                        is_available_for_rent: available,
+                        rental_mode: RentalMode::default(),
+                    };
+                    (id.to_string(), listing)
+                })
+                .collect(),
+            app: AppSettings { lightweight_mode, ..AppSettings::default() },
+        }
+    }
+
+    #[test]
+    fn flattens_nested_objects_to_dotted_paths() {
+        let mut out = BTreeMap::new();
+        flatten("app".to_string(), json!({ "a": { "b": 1, "c": [1, 2] }, "empty": {}, "d": null }), &mut out);
+        assert_eq!(out.get("app.a.b"), Some(&json!(1)));
+        assert_eq!(out.get("app.a.c"), Some(&json!([1, 2])));
+        assert_eq!(out.get("app.empty"), Some(&json!({})));
+        assert_eq!(out.get("app.d"), Some(&Value::Null));
+        assert_eq!(out.len(), 4);
+    }
+
+    #[test]
+    fn identical_snapshots_have_no_changes() {
+        let a = snapshot(&[("GPU-0", 5, true)], false);
+        assert!(diff(&a, &a.clone()).is_empty());
+    }
+
+    #[test]
+    fn reports_changed_added_and_removed_fields() {
+        let before = snapshot(&[("GPU-0", 5, true), ("GPU-1", 5, true)], false);
+        let after = snapshot(&[("GPU-0", 5, false), ("GPU-2", 7, true)], true);
+        let changes = diff(&before, &after);
+        let find = |path: &str| changes.iter().find(|c| c.path == path);
+
+        let unlisted = find("gpus.GPU-0.is_available_for_rent").unwrap();
+        assert_eq!((unlisted.before.clone(), unlisted.after.clone()), (Some(json!(true)), Some(json!(false))));
+        assert!(find("gpus.GPU-0.hourly_rate_dgpu").is_none());
+        assert!(find("gpus.GPU-1.name").is_some_and(|c| c.before.is_some() && c.after.is_none()));
+        assert!(find("gpus.GPU-2.name").is_some_and(|c| c.before.is_none() && c.after.is_some()));
+        assert!(find("app.lightweight_mode").is_some());
+        assert!(changes.windows(2).all(|pair| pair[0].path <= pair[1].path));
+    }
+}
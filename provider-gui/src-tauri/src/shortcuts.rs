@@ -0,0 +1,239 @@
+// System-wide keyboard shortcuts.
+//
+// Bindings live in `AppSettings::shortcuts` and are registered with the OS when the app starts
+// and whenever they are saved. Two kinds of conflict are caught:
+// - combinations the OS itself reserves (app switching, screenshots, lock screen) are rejected
+//   on save, since the OS would win and the shortcut would silently never fire;
+// - combinations another running application already holds make registration fail, which is
+//   reported per action by `get_shortcut_status`.
+// Global shortcuts without a modifier would swallow ordinary typing, so one is required.
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::Mutex;
+use tauri::{AppHandle, GlobalShortcutManager, Manager, State};
+
+use crate::units::DgpuPerHour;
+use crate::{a11y, app_settings, emergency, emit_log_entry, get_detected_gpus, get_provider_settings, set_gpu_rental_config};
+
+// Also the order modifiers are written in below.
+const MODIFIERS: &[&str] = &["CTRL", "ALT", "SHIFT", "SUPER"];
+const NAMED_KEYS: &[&str] = &[
+    "SPACE", "TAB", "ENTER", "ESCAPE", "BACKSPACE", "DELETE", "INSERT", "HOME", "END", "PAGEUP", "PAGEDOWN", "UP", "DOWN", "LEFT", "RIGHT", "PLUS",
+    "MINUS", "COMMA", "PERIOD", "SLASH", "BACKQUOTE",
+];
+
+#[cfg(target_os = "macos")]
+const RESERVED: &[&str] = &[
+    "SUPER+SPACE", "SUPER+TAB", "SUPER+Q", "SUPER+W", "SUPER+H", "SUPER+M", "ALT+SUPER+ESCAPE", "CTRL+SUPER+Q", "CTRL+SPACE", "SHIFT+SUPER+3",
+    "SHIFT+SUPER+4", "SHIFT+SUPER+5",
+];
+#[cfg(target_os = "windows")]
+const RESERVED: &[&str] = &[
+    "ALT+TAB", "ALT+F4", "CTRL+ALT+DELETE", "CTRL+SHIFT+ESCAPE", "SUPER+L", "SUPER+D", "SUPER+E", "SUPER+R", "SUPER+TAB", "SHIFT+SUPER+S",
+];
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+const RESERVED: &[&str] = &["ALT+TAB", "ALT+F4", "CTRL+ALT+T", "CTRL+ALT+DELETE", "CTRL+ALT+L", "SUPER+L", "SUPER+TAB"];
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ShortcutAction {
+    ToggleAvailability,
+    ShowOverlay,
+    EmergencyStop,
+}
+
+// Accelerators in Tauri's syntax, e.g. "CmdOrCtrl+Shift+A"; None leaves the action unbound.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct ShortcutBindings {
+    pub toggle_availability: Option<String>,
+    pub show_overlay: Option<String>,
+    // Unbound by default; when bound, it still takes a second press (see `emergency`).
+    pub emergency_stop: Option<String>,
+}
+
+impl Default for ShortcutBindings {
+    fn default() -> Self {
+        ShortcutBindings {
+            toggle_availability: Some("CmdOrCtrl+Shift+Alt+A".to_string()),
+            show_overlay: Some("CmdOrCtrl+Shift+Alt+O".to_string()),
+            emergency_stop: None,
+        }
+    }
+}
+
+impl ShortcutBindings {
+    fn bound(&self) -> Vec<(ShortcutAction, &str)> {
+        [
+            (ShortcutAction::ToggleAvailability, &self.toggle_availability),
+            (ShortcutAction::ShowOverlay, &self.show_overlay),
+            (ShortcutAction::EmergencyStop, &self.emergency_stop),
+        ]
+        .into_iter()
+        .filter_map(|(action, accelerator)| accelerator.as_deref().map(|a| (action, a)))
+        .collect()
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ShortcutStatus {
+    pub action: ShortcutAction,
+    pub accelerator: String,
+    pub registered: bool,
+    pub conflict: Option<String>,
+}
+
+pub struct ShortcutState {
+    status: Mutex<Vec<ShortcutStatus>>,
+}
+
+impl ShortcutState {
+    pub fn new() -> Self {
+        ShortcutState { status: Mutex::new(Vec::new()) }
+    }
+}
+
+// Canonical form for comparing combinations: modifiers in a fixed order, then the key.
+fn normalize(accelerator: &str) -> Result<String, String> {
+    let mut modifiers = Vec::new();
+    let mut key = None;
+    for part in accelerator.split('+').map(|p| p.trim().to_ascii_uppercase()) {
+        let modifier = match part.as_str() {
+            "CMDORCTRL" | "COMMANDORCONTROL" if cfg!(target_os = "macos") => "SUPER",
+            "CMDORCTRL" | "COMMANDORCONTROL" | "CTRL" | "CONTROL" => "CTRL",
+            "CMD" | "COMMAND" | "SUPER" | "META" => "SUPER",
+            "ALT" | "OPTION" => "ALT",
+            "SHIFT" => "SHIFT",
+            _ => {
+                let valid = (part.len() == 1 && part.chars().all(|c| c.is_ascii_alphanumeric()))
+                    || NAMED_KEYS.contains(&part.as_str())
+                    || part.strip_prefix('F').and_then(|n| n.parse::<u8>().ok()).is_some_and(|n| (1..=24).contains(&n));
+                if !valid || key.replace(part.clone()).is_some() {
+                    return Err(format!("'{}' is not a valid shortcut.", accelerator));
+                }
+                continue;
+            }
+        };
+        if !modifiers.contains(&modifier) {
+            modifiers.push(modifier);
+        }
+    }
+    let key = key.ok_or_else(|| format!("'{}' has no key besides modifiers.", accelerator))?;
+    if modifiers.is_empty() {
+        return Err(format!("'{}' needs at least one modifier such as Ctrl or Alt.", accelerator));
+    }
+    let mut parts: Vec<&str> = MODIFIERS.iter().copied().filter(|m| modifiers.contains(m)).collect();
+    parts.push(&key);
+    Ok(parts.join("+"))
+}
+
+// Checks syntax, duplicates and OS-reserved combinations before bindings are saved.
+pub fn validate(bindings: &ShortcutBindings) -> Result<(), String> {
+    let mut seen: Vec<String> = Vec::new();
+    for (_, accelerator) in bindings.bound() {
+        let canonical = normalize(accelerator)?;
+        if RESERVED.contains(&canonical.as_str()) {
+            return Err(format!("'{}' is reserved by the operating system.", accelerator));
+        }
+        if seen.contains(&canonical) {
+            return Err(format!("'{}' is bound to more than one action.", accelerator));
+        }
+        seen.push(canonical);
+    }
+    Ok(())
+}
+
+// Changes every GPU or none: if one can't be changed, those already changed are put back.
+// GPUs without their own rate are listed at the default rate, and skipped if there is none.
+async fn toggle_availability(app_handle: &AppHandle) -> Result<(), String> {
+    let gpus = get_detected_gpus(app_handle.clone()).await?;
+    // Any listed GPU means the rig counts as available, so the toggle takes everything offline.
+    let available = !gpus.iter().any(|g| g.is_available_for_rent);
+    let default_rate = get_provider_settings(app_handle.clone()).await?.default_hourly_rate_dgpu;
+    let mut changed = Vec::new();
+    let mut skipped = Vec::new();
+    let mut failure = None;
+    for gpu in gpus.iter().filter(|g| g.is_available_for_rent != available) {
+        let rate = gpu.current_hourly_rate_dgpu.unwrap_or(default_rate);
+        if available && rate <= DgpuPerHour::default() {
+            skipped.push(gpu.name.clone());
+            continue;
+        }
+        match set_gpu_rental_config(app_handle.clone(), gpu.id.clone(), rate, available, None, None).await {
+            Ok(_) => changed.push((gpu, rate)),
+            Err(e) => {
+                failure = Some(format!("{}: {}", gpu.name, e));
+                break;
+            }
+        }
+    }
+    if let Some(failure) = failure {
+        for (gpu, rate) in changed {
+            if let Err(e) = set_gpu_rental_config(app_handle.clone(), gpu.id.clone(), rate, !available, None, None).await {
+                emit_log_entry(app_handle, "error", format!("Failed to put {} back after the shortcut failed: {}", gpu.name, e));
+            }
+        }
+        return Err(format!("Couldn't change {}; the other GPUs were put back.", failure));
+    }
+    let mut message = if available { "All GPUs are listed for rent." } else { "All GPUs are unlisted." }.to_string();
+    if !skipped.is_empty() {
+        message = format!("{} Skipped {} (no hourly rate set).", message, skipped.join(", "));
+    }
+    emit_log_entry(app_handle, "status", format!("Shortcut: {}", message));
+    a11y::announce(app_handle, "shortcut", a11y::Severity::Info, message);
+    Ok(())
+}
+
+fn show_overlay(app_handle: &AppHandle) -> Result<(), String> {
+    let window = app_handle.get_window("main").ok_or_else(|| "The main window is not open.".to_string())?;
+    window.show().and_then(|_| window.set_focus()).map_err(|e| e.to_string())?;
+    window.emit("overlay_requested", json!({})).map_err(|e| e.to_string())
+}
+
+async fn run(app_handle: &AppHandle, action: ShortcutAction) {
+    let result = match action {
+        ShortcutAction::ToggleAvailability => toggle_availability(app_handle).await,
+        ShortcutAction::ShowOverlay => show_overlay(app_handle),
+        ShortcutAction::EmergencyStop => {
+            emergency::shortcut_pressed(app_handle).await;
+            Ok(())
+        }
+    };
+    if let Err(e) = result {
+        emit_log_entry(app_handle, "error", format!("Shortcut {:?} failed: {}", action, e));
+    }
+}
+
+// Replaces all registrations with the current bindings.
+pub fn apply(app_handle: &AppHandle) {
+    let bindings = app_settings::current(app_handle).shortcuts;
+    let mut manager = app_handle.global_shortcut_manager();
+    if let Err(e) = manager.unregister_all() {
+        eprintln!("Failed to unregister global shortcuts: {}", e);
+    }
+    let mut statuses = Vec::new();
+    for (action, accelerator) in bindings.bound() {
+        let handle = app_handle.clone();
+        let conflict = match normalize(accelerator) {
+            Err(e) => Some(e),
+            Ok(_) => manager
+                .register(accelerator, move || {
+                    let handle = handle.clone();
+                    tauri::async_runtime::spawn(async move { run(&handle, action).await });
+                })
+                .err()
+                .map(|e| format!("Couldn't register '{}'; another application may already use it ({}).", accelerator, e)),
+        };
+        if let Some(conflict) = &conflict {
+            emit_log_entry(app_handle, "error", conflict.clone());
+        }
+        statuses.push(ShortcutStatus { action, accelerator: accelerator.to_string(), registered: conflict.is_none(), conflict });
+    }
+    *app_handle.state::<ShortcutState>().status.lock().unwrap() = statuses;
+}
+
+#[tauri::command]
+pub async fn get_shortcut_status(state: State<'_, ShortcutState>) -> Result<Vec<ShortcutStatus>, String> {
+    Ok(state.status.lock().unwrap().clone())
+}
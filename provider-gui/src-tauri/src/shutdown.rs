@@ -0,0 +1,179 @@
+// Orderly shutdown when the app quits.
+//
+// Quitting is intercepted and run through one coordinator with a bounded total time:
+// 1. background tasks are signalled and stop at their next sleep (see `sleep`);
+// 2. the daemon is stopped, or first drained of running jobs, per the shutdown policy; a drain
+//    gets what is left of the time bar `STOP_RESERVE`, and the daemon is stopped either way;
+// 3. hook scripts still running get the remaining time to finish;
+// 4. the event WAL and the database are flushed;
+// then the app exits, whether or not steps 2-3 finished in time.
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, RunEvent};
+use tokio::sync::watch;
+
+use crate::storage::Storage;
+use crate::wal::WalState;
+use crate::{app_settings, emit_log_entry, gpu_ids, hooks, invoke_daemon_cli_json_output, stop_daemon, DaemonState};
+
+// Kept back from the total for flushing, which must always run.
+const FLUSH_RESERVE: Duration = Duration::from_secs(2);
+// Kept back from a drain for stopping the daemon afterwards.
+const STOP_RESERVE: Duration = Duration::from_secs(5);
+const PROGRESS_POLL: Duration = Duration::from_millis(250);
+const DRAIN_POLL: Duration = Duration::from_secs(5);
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum DaemonOnQuit {
+    // Stop the daemon right away; running jobs are interrupted.
+    #[default]
+    Stop,
+    // Stop taking new rentals and wait for running jobs to finish before stopping.
+    Drain,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct ShutdownPolicy {
+    pub daemon: DaemonOnQuit,
+    // Upper bound on the whole shutdown, including a drain.
+    pub timeout_secs: u64,
+}
+
+impl Default for ShutdownPolicy {
+    fn default() -> Self {
+        ShutdownPolicy { daemon: DaemonOnQuit::Stop, timeout_secs: 20 }
+    }
+}
+
+static SIGNAL: OnceLock<watch::Sender<bool>> = OnceLock::new();
+static STARTED: AtomicBool = AtomicBool::new(false);
+
+fn signal() -> &'static watch::Sender<bool> {
+    SIGNAL.get_or_init(|| watch::channel(false).0)
+}
+
+pub fn requested() -> bool {
+    *signal().borrow()
+}
+
+// Resolves once shutdown has started.
+pub async fn wait() {
+    let mut receiver = signal().subscribe();
+    while !*receiver.borrow() {
+        if receiver.changed().await.is_err() {
+            return;
+        }
+    }
+}
+
+// Sleeps for `duration` unless shutdown starts first. Returns false once the app is shutting
+// down, so background loops end with `if !shutdown::sleep(INTERVAL).await { break; }`.
+pub async fn sleep(duration: Duration) -> bool {
+    tokio::select! {
+        _ = tokio::time::sleep(duration) => !requested(),
+        _ = wait() => false,
+    }
+}
+
+fn daemon_online(app_handle: &AppHandle) -> bool {
+    matches!(app_handle.state::<DaemonState>().status.lock().unwrap().as_str(), "online" | "starting" | "stopping")
+}
+
+async fn drain(app_handle: &AppHandle) {
+    let payload = json!({ "blocked": true, "reason": "Provider app is shutting down" }).to_string();
+    if let Err(e) = invoke_daemon_cli_json_output::<serde_json::Value>(app_handle, &["--set-rentals-blocked-json", &payload]).await {
+        emit_log_entry(app_handle, "error", format!("Failed to stop new rentals before shutdown: {}", e));
+        return;
+    }
+    loop {
+        match gpu_ids::local_jobs(app_handle).await {
+            Ok(jobs) => {
+                let running = jobs.iter().filter(|j| j.status == "running").count();
+                if running == 0 {
+                    return;
+                }
+                emit_log_entry(app_handle, "status", format!("Waiting for {} running job(s) to finish before shutting down.", running));
+            }
+            Err(e) => {
+                emit_log_entry(app_handle, "error", format!("Can't check running jobs; stopping the daemon now: {}", e));
+                return;
+            }
+        }
+        tokio::time::sleep(DRAIN_POLL).await;
+    }
+}
+
+async fn stop_work(app_handle: &AppHandle, policy: DaemonOnQuit, drain_for: Duration) {
+    if daemon_online(app_handle) {
+        if policy == DaemonOnQuit::Drain && tokio::time::timeout(drain_for, drain(app_handle)).await.is_err() {
+            emit_log_entry(app_handle, "error", "Jobs were still running when the shutdown drain ran out of time; stopping the daemon now.".to_string());
+        }
+        if let Err(e) = stop_daemon(app_handle.clone(), app_handle.state::<DaemonState>()).await {
+            emit_log_entry(app_handle, "error", format!("Failed to stop the daemon during shutdown: {}", e));
+        }
+        while daemon_online(app_handle) {
+            tokio::time::sleep(PROGRESS_POLL).await;
+        }
+    }
+    while hooks::in_flight() > 0 {
+        tokio::time::sleep(PROGRESS_POLL).await;
+    }
+}
+
+fn flush(app_handle: &AppHandle) {
+    if let Some(wal) = app_handle.try_state::<WalState>() {
+        if let Err(e) = wal.flush() {
+            eprintln!("Failed to flush the event WAL: {}", e);
+        }
+    }
+    if let Some(storage) = app_handle.try_state::<Storage>() {
+        if let Err(e) = storage.flush() {
+            eprintln!("Failed to flush the database: {}", e);
+        }
+    }
+}
+
+// Runs the shutdown sequence and exits the process. Only the first call does anything.
+pub fn begin(app_handle: &AppHandle) {
+    if STARTED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    let policy = app_settings::current(app_handle).shutdown;
+    let total = Duration::from_secs(policy.timeout_secs.max(FLUSH_RESERVE.as_secs() + 1));
+    let drain_for = (total - FLUSH_RESERVE).saturating_sub(STOP_RESERVE);
+    emit_log_entry(app_handle, "status", format!("Shutting down (daemon: {:?}, at most {} s).", policy.daemon, total.as_secs()));
+    if let Err(e) = app_handle.emit_all("app_shutting_down", json!({ "daemon": policy.daemon, "timeout_secs": total.as_secs() })) {
+        eprintln!("Failed to emit app_shutting_down event: {}", e);
+    }
+    signal().send_replace(true);
+
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        let started = Instant::now();
+        if tokio::time::timeout(total - FLUSH_RESERVE, stop_work(&app_handle, policy.daemon, drain_for)).await.is_err() {
+            eprintln!("Shutdown did not finish within {} s; exiting anyway.", total.as_secs());
+        }
+        flush(&app_handle);
+        eprintln!("Shutdown finished in {} ms.", started.elapsed().as_millis());
+        app_handle.exit(0);
+    });
+}
+
+pub fn handle_run_event(app_handle: &AppHandle, event: RunEvent) {
+    if let RunEvent::ExitRequested { api, .. } = event {
+        api.prevent_exit();
+        begin(app_handle);
+    }
+}
+
+#[tauri::command]
+pub async fn quit_app(app_handle: AppHandle) -> Result<(), String> {
+    begin(&app_handle);
+    Ok(())
+}
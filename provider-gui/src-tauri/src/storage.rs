@@ -0,0 +1,1600 @@
+// Local SQLite store for history the GUI keeps between sessions.
+//
+// Everything written here is governed by the privacy settings: categories the user has
+// switched off are never stored, and the retention task deletes rows older than the
+// configured windows.
+
+use dante_provider_core::models::JobHardwareEvidence;
+use dante_provider_core::storage as core_storage;
+use rusqlite::{params, Connection, DatabaseName, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager, State};
+
+use crate::app_settings::{self, PrivacySettings};
+use crate::attestation::AttestationRecord;
+use crate::checkpoints::{self, CheckpointUsage};
+use crate::connectivity::ConnectivityIncident;
+use crate::daemon_restart::ScheduledRestart;
+use crate::emergency::EmergencyStopIncident;
+use crate::experiments::PricingExperiment;
+use crate::failover::FailoverIncident;
+use crate::gpu_ids::GpuIdentity;
+use crate::inventory::GpuInventoryRecord;
+use crate::job_notes::JobNote;
+use crate::journal::JournalNote;
+use crate::money::{self, Decimal};
+use crate::units::DgpuPerHour;
+use crate::platform_import::{ImportedEarning, ImportedEarningsMonth};
+use crate::regions::RegionLatency;
+use crate::rejections::JobRejection;
+use crate::settings_history::SettingsSnapshot;
+use crate::tasks::TaskInfo;
+use crate::{accounts, clock, emit_log_entry, get_timestamp, shutdown, DaemonState, GpuInfo, LocalJob};
+
+const DATABASE_FILE_NAME: &str = "provider.db";
+const RETENTION_INTERVAL: Duration = Duration::from_secs(60 * 60);
+const MAINTENANCE_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+const MAINTENANCE_STARTUP_DELAY: Duration = Duration::from_secs(10 * 60);
+// Raw samples older than this are downsampled into one averaged row per GPU per bucket.
+const COMPACT_AFTER_SECS: i64 = 24 * 60 * 60;
+const COMPACT_BUCKET_SECS: i64 = 5 * 60;
+// VACUUM only pays off once a meaningful share of the file is free pages.
+const VACUUM_FREE_RATIO: f64 = 0.1;
+
+// Tables specific to the GUI; telemetry and job history come from `dante_provider_core::storage`,
+// which the headless agent shares. Amounts are TEXT, as there (see `core_storage::amount_at`).
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS processed_events (
+    idempotency_key TEXT PRIMARY KEY,
+    kind TEXT NOT NULL,
+    amount_dgpu TEXT,
+    processed_at INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS failover_incidents (
+    incident_id TEXT PRIMARY KEY,
+    job_id TEXT NOT NULL,
+    record TEXT NOT NULL,
+    updated_at INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS job_notes (
+    job_id TEXT NOT NULL,
+    record TEXT NOT NULL,
+    created_at INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS job_notes_by_job ON job_notes (job_id, created_at);
+
+CREATE TABLE IF NOT EXISTS region_latency (
+    region_id TEXT NOT NULL,
+    measured_at INTEGER NOT NULL,
+    median_ms INTEGER
+);
+CREATE INDEX IF NOT EXISTS region_latency_by_region ON region_latency (region_id, measured_at);
+
+CREATE TABLE IF NOT EXISTS connectivity_incidents (
+    incident_id TEXT PRIMARY KEY,
+    record TEXT NOT NULL,
+    started_at INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS settings_snapshots (
+    taken_at INTEGER NOT NULL,
+    record TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS settings_snapshots_by_time ON settings_snapshots (taken_at);
+
+CREATE TABLE IF NOT EXISTS job_rejections (
+    rejected_at INTEGER NOT NULL,
+    record TEXT NOT NULL,
+    report_key TEXT
+);
+CREATE INDEX IF NOT EXISTS job_rejections_by_time ON job_rejections (rejected_at);
+
+CREATE TABLE IF NOT EXISTS journal_notes (
+    note_id TEXT PRIMARY KEY,
+    record TEXT NOT NULL,
+    created_at INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS gpu_inventory (
+    gpu_id TEXT PRIMARY KEY,
+    record TEXT NOT NULL,
+    updated_at INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS gpu_identities (
+    gpu_id TEXT PRIMARY KEY,
+    record TEXT NOT NULL,
+    updated_at INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS job_hardware (
+    job_id TEXT PRIMARY KEY,
+    record TEXT NOT NULL,
+    updated_at INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS scheduled_restarts (
+    restart_id TEXT PRIMARY KEY,
+    record TEXT NOT NULL,
+    created_at INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS tasks (
+    task_id TEXT PRIMARY KEY,
+    record TEXT NOT NULL,
+    created_at INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS emergency_stops (
+    incident_id TEXT PRIMARY KEY,
+    record TEXT NOT NULL,
+    created_at INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS pricing_experiments (
+    experiment_id TEXT PRIMARY KEY,
+    record TEXT NOT NULL,
+    updated_at INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS experiment_periods (
+    experiment_id TEXT NOT NULL,
+    arm INTEGER NOT NULL,
+    started_at INTEGER NOT NULL,
+    ended_at INTEGER
+);
+CREATE INDEX IF NOT EXISTS idx_experiment_periods ON experiment_periods (experiment_id, started_at);
+
+CREATE TABLE IF NOT EXISTS job_attestations (
+    job_id TEXT PRIMARY KEY,
+    record TEXT NOT NULL,
+    updated_at INTEGER NOT NULL
+);
+
+-- Earnings history imported from other rental platforms, in the currency they paid in.
+CREATE TABLE IF NOT EXISTS imported_earnings (
+    source TEXT NOT NULL,
+    row_key TEXT NOT NULL,
+    earned_at INTEGER NOT NULL,
+    machine TEXT,
+    gpu_model TEXT,
+    gpu_hours REAL,
+    amount TEXT NOT NULL,
+    currency TEXT NOT NULL,
+    imported_at INTEGER NOT NULL,
+    PRIMARY KEY (source, row_key)
+);
+";
+
+// Columns added to the GUI's tables after they first shipped; see `core_storage::ADDED_COLUMNS`.
+const ADDED_COLUMNS: &[&str] = &[
+    "ALTER TABLE job_rejections ADD COLUMN report_key TEXT",
+    "ALTER TABLE processed_events ADD COLUMN gpu_id TEXT",
+    "ALTER TABLE processed_events ADD COLUMN processed_at_corrected INTEGER",
+];
+
+// Indexes on added columns, which only exist once the columns do.
+const ADDED_INDEXES: &str = "
+CREATE UNIQUE INDEX IF NOT EXISTS job_rejections_by_report ON job_rejections (report_key);
+";
+
+fn migrate(conn: &Connection) -> Result<(), String> {
+    core_storage::migrate(conn)?;
+    conn.execute_batch(SCHEMA).map_err(|e| format!("Failed to initialise database schema: {}", e))?;
+    for statement in ADDED_COLUMNS {
+        if let Err(e) = conn.execute(statement, []) {
+            if !e.to_string().contains("duplicate column") {
+                return Err(format!("Failed to migrate database schema: {}", e));
+            }
+        }
+    }
+    conn.execute_batch(ADDED_INDEXES).map_err(|e| format!("Failed to migrate database schema: {}", e))?;
+    core_storage::retype_as_text(conn, "processed_events", "amount_dgpu", "TEXT")?;
+    core_storage::retype_as_text(conn, "imported_earnings", "amount", "TEXT NOT NULL DEFAULT '0'")?;
+    Ok(())
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DataCategory {
+    TelemetryHistory,
+    JobMetadata,
+    RenterIds,
+    Logs,
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct PurgeSummary {
+    pub telemetry_samples_deleted: usize,
+    pub jobs_deleted: usize,
+    pub renter_ids_cleared: usize,
+    pub log_entries_cleared: usize,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct LedgerEntry {
+    pub idempotency_key: String,
+    pub kind: String,
+    #[serde(with = "money::optional_amount")]
+    pub amount_dgpu: Option<Decimal>,
+    pub processed_at: i64,
+    pub processed_at_corrected: Option<i64>, // Platform time, when the clock offset was known
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct LedgerSummary {
+    #[serde(with = "money::amount")]
+    pub completed_job_earnings_dgpu: Decimal,
+    pub entries: Vec<LedgerEntry>,
+}
+
+// One GPU-day of telemetry, aggregated for the hardware health trend.
+#[derive(Debug, Clone)]
+pub struct DailyHealth {
+    pub day_start: i64,
+    pub idle_temperature_c: Option<f64>,
+    pub ecc_corrections: Option<i64>, // None when no sample that day reported ECC
+    // Memory clock while loaded; idle clocks drop by design and would mask instability.
+    pub loaded_memory_clock_min_mhz: Option<i64>,
+    pub loaded_memory_clock_max_mhz: Option<i64>,
+}
+
+// One GPU-hour of telemetry, aggregated for pricing analysis.
+#[derive(Debug, Clone)]
+pub struct HourlyUsage {
+    pub gpu_id: String,
+    pub hour_start: i64,
+    pub hourly_rate_dgpu: Option<DgpuPerHour>, // Average over the hour
+    pub listed_fraction: f64,
+    pub busy_fraction: f64,
+}
+
+// One raw telemetry sample, for exports.
+#[derive(Debug, Clone)]
+pub struct TelemetryRow {
+    pub row_id: i64,
+    pub recorded_at: i64,
+    pub gpu_id: String,
+    pub utilization_gpu_percent: Option<i32>,
+    pub temperature_c: Option<i32>,
+    pub power_draw_w: Option<i32>,
+    pub vram_free_mb: i64,
+    pub is_available_for_rent: bool,
+    pub hourly_rate_dgpu: Option<DgpuPerHour>,
+    pub ecc_corrected_errors: Option<i64>,
+    pub memory_clock_mhz: Option<i32>,
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct MaintenanceReport {
+    pub ran_at: String,
+    pub samples_compacted: usize,
+    pub samples_written: usize,
+    pub retention: PurgeSummary,
+    pub vacuumed: bool,
+    pub integrity: String,
+    pub bytes_before: u64,
+    pub bytes_after: u64,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct StorageStatus {
+    pub path: String,
+    pub size_bytes: u64,
+    pub page_count: i64,
+    pub freelist_count: i64,
+    pub telemetry_samples: i64,
+    pub jobs: i64,
+    pub oldest_sample_at: Option<i64>,
+    pub newest_sample_at: Option<i64>,
+    pub last_maintenance: Option<MaintenanceReport>,
+    // Daemon-managed job checkpoints; None when the daemon can't be asked.
+    pub checkpoints: Option<CheckpointUsage>,
+}
+
+pub struct Storage {
+    conn: Mutex<Connection>,
+    // Each account has its own database, in its data directory; see `reopen`.
+    path: Mutex<PathBuf>,
+    last_maintenance: Mutex<Option<MaintenanceReport>>,
+}
+
+pub fn unix_now() -> i64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs() as i64).unwrap_or(0)
+}
+
+fn days_ago(days: u32) -> i64 {
+    unix_now() - i64::from(days) * 24 * 60 * 60
+}
+
+// Consistent copy of the database behind `conn`, taken without blocking writers for long.
+fn snapshot_of(conn: &Connection) -> Result<Vec<u8>, String> {
+    let tmp = std::env::temp_dir().join(format!("dante-provider-snapshot-{}.db", std::process::id()));
+    let _ = fs::remove_file(&tmp);
+    conn.execute("VACUUM INTO ?1", params![tmp.to_string_lossy()])
+        .map_err(|e| format!("Failed to snapshot database: {}", e))?;
+    let bytes = fs::read(&tmp).map_err(|e| format!("Failed to read database snapshot: {}", e));
+    let _ = fs::remove_file(&tmp);
+    bytes
+}
+
+// Replaces the database behind `conn` with `bytes` after checking it is a healthy SQLite file.
+fn restore_into(conn: &mut Connection, bytes: &[u8]) -> Result<(), String> {
+    let tmp = std::env::temp_dir().join(format!("dante-provider-restore-{}.db", std::process::id()));
+    fs::write(&tmp, bytes).map_err(|e| format!("Failed to stage database restore: {}", e))?;
+    let result = (|| {
+        let integrity: String = Connection::open(&tmp)
+            .and_then(|candidate| candidate.query_row("PRAGMA integrity_check", [], |row| row.get(0)))
+            .map_err(|e| format!("Backup database is unreadable: {}", e))?;
+        if integrity != "ok" {
+            return Err(format!("Backup database failed integrity check: {}", integrity));
+        }
+        conn.restore(DatabaseName::Main, &tmp, None::<fn(rusqlite::backup::Progress)>)
+            .map_err(|e| format!("Failed to restore database: {}", e))?;
+        // Backups from older versions may predate newer tables.
+        migrate(conn)
+    })();
+    let _ = fs::remove_file(&tmp);
+    result
+}
+
+pub fn database_path(dir: &Path) -> PathBuf {
+    dir.join(DATABASE_FILE_NAME)
+}
+
+// Copy of a database that isn't the live one. Other accounts' databases are checkpointed
+// whenever they stop being active, so the file is self-contained.
+pub fn snapshot_file(path: &Path) -> Result<Vec<u8>, String> {
+    let conn = Connection::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    snapshot_of(&conn)
+}
+
+// Replaces a database that isn't the live one, creating it if needed.
+pub fn restore_file(path: &Path, bytes: &[u8]) -> Result<(), String> {
+    let dir = path.parent().ok_or_else(|| format!("Invalid database path {}.", path.display()))?;
+    let (mut conn, _) = open_in(dir)?;
+    restore_into(&mut conn, bytes)
+}
+
+fn open_in(dir: &Path) -> Result<(Connection, PathBuf), String> {
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    let path = database_path(dir);
+    let conn = Connection::open(&path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    migrate(&conn)?;
+    Ok((conn, path))
+}
+
+impl Storage {
+    pub fn open(app_handle: &AppHandle) -> Result<Self, String> {
+        let dir = accounts::data_dir(app_handle).ok_or_else(|| "App data directory is unavailable.".to_string())?;
+        let (conn, path) = open_in(&dir)?;
+        Ok(Storage { conn: Mutex::new(conn), path: Mutex::new(path), last_maintenance: Mutex::new(None) })
+    }
+
+    // Switches to the active account's database. The previous one is checkpointed first so it
+    // is self-contained while another account is in use.
+    pub fn reopen(&self, app_handle: &AppHandle) -> Result<(), String> {
+        let dir = accounts::data_dir(app_handle).ok_or_else(|| "App data directory is unavailable.".to_string())?;
+        let (conn, path) = open_in(&dir)?;
+        if let Err(e) = self.flush() {
+            eprintln!("{}", e);
+        }
+        *self.conn.lock().unwrap() = conn;
+        *self.path.lock().unwrap() = path;
+        *self.last_maintenance.lock().unwrap() = None;
+        Ok(())
+    }
+
+    // Consistent copy of the database for backups, taken without blocking writers for long.
+    pub fn snapshot(&self) -> Result<Vec<u8>, String> {
+        snapshot_of(&self.conn.lock().unwrap())
+    }
+
+    // Replaces the live database with `bytes` after checking it is a healthy SQLite file.
+    pub fn restore(&self, bytes: &[u8]) -> Result<(), String> {
+        restore_into(&mut self.conn.lock().unwrap(), bytes)
+    }
+
+    pub fn is_live(&self, path: &Path) -> bool {
+        *self.path.lock().unwrap() == path
+    }
+
+    // Checkpoints any write-ahead journal into the main file and refreshes query statistics,
+    // so the database is self-contained when the app exits.
+    pub fn flush(&self) -> Result<(), String> {
+        let conn = self.conn.lock().unwrap();
+        conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(())).map_err(|e| format!("Failed to checkpoint database: {}", e))?;
+        conn.execute_batch("PRAGMA optimize;").map_err(|e| format!("Failed to optimize database: {}", e))
+    }
+
+    pub fn record_telemetry(&self, gpus: &[GpuInfo]) -> Result<(), String> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction().map_err(|e| e.to_string())?;
+        let now = unix_now();
+        for gpu in gpus {
+            core_storage::insert_telemetry(&tx, now, gpu)?;
+        }
+        tx.commit().map_err(|e| e.to_string())
+    }
+
+    pub fn upsert_jobs(&self, jobs: &[LocalJob], privacy: &PrivacySettings) -> Result<(), String> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction().map_err(|e| e.to_string())?;
+        let now = unix_now();
+        for job in jobs {
+            core_storage::upsert_job(&tx, now, job, privacy.store_renter_ids)?;
+        }
+        tx.commit().map_err(|e| e.to_string())
+    }
+
+    // Mean wall-clock duration of recently completed jobs, for queue estimates.
+    pub fn average_job_duration_secs(&self) -> Result<Option<i64>, String> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn
+            .prepare(
+                "SELECT started_at, completed_at FROM jobs
+                 WHERE status = 'completed' AND started_at IS NOT NULL AND completed_at IS NOT NULL
+                 ORDER BY updated_at DESC LIMIT 50",
+            )
+            .map_err(|e| e.to_string())?;
+        let durations: Vec<i64> = stmt
+            .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+            .map_err(|e| format!("Failed to read job durations: {}", e))?
+            .iter()
+            .filter_map(|(started, completed)| {
+                let started = humantime::parse_rfc3339_weak(started).ok()?;
+                humantime::parse_rfc3339_weak(completed).ok()?.duration_since(started).ok()
+            })
+            .map(|d| d.as_secs() as i64)
+            .collect();
+        Ok((!durations.is_empty()).then(|| durations.iter().sum::<i64>() / durations.len() as i64))
+    }
+
+    // Submission times (unix seconds) of stored jobs that ran on any of `gpu_ids`, since `since`.
+    // A job is last updated no earlier than it was submitted, so the indexed `updated_at` range
+    // narrows the scan before the text timestamps are parsed.
+    pub fn job_arrivals(&self, gpu_ids: &[String], since: i64) -> Result<Vec<i64>, String> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT submitted_at FROM jobs WHERE updated_at >= ?1 AND gpu_id = ?2").map_err(|e| e.to_string())?;
+        let mut rows = Vec::new();
+        for gpu_id in gpu_ids {
+            let submitted = stmt
+                .query_map(params![since, gpu_id], |row| row.get::<_, String>(0))
+                .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+                .map_err(|e| format!("Failed to read job arrivals: {}", e))?;
+            rows.extend(submitted);
+        }
+        Ok(rows
+            .into_iter()
+            .filter_map(|submitted_at| humantime::parse_rfc3339_weak(&submitted_at).ok())
+            .filter_map(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_secs() as i64)
+            .filter(|t| *t >= since)
+            .collect())
+    }
+
+    // (start, end) unix seconds of stored jobs on `gpu_id` that ran during [from, to). A job still
+    // running counts until it was last seen running, and no later than the GPU's last sample; one
+    // that stopped without a completion time, until its last update.
+    pub fn job_intervals(&self, gpu_id: &str, from: i64, to: i64) -> Result<Vec<(i64, i64)>, String> {
+        let conn = self.conn.lock().unwrap();
+        // A row left "running" by a job that ended while nothing was watching mustn't count as
+        // rented past what was observed.
+        let last_sample: Option<i64> = conn
+            .query_row("SELECT MAX(recorded_at) FROM telemetry_samples WHERE gpu_id = ?1 AND recorded_at < ?2", params![gpu_id, to], |row| row.get(0))
+            .map_err(|e| format!("Failed to read telemetry history: {}", e))?;
+        let mut stmt = conn
+            .prepare("SELECT started_at, completed_at, status, updated_at FROM jobs WHERE gpu_id = ?1 AND started_at IS NOT NULL")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(params![gpu_id], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, Option<String>>(1)?, row.get::<_, String>(2)?, row.get::<_, i64>(3)?))
+            })
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+            .map_err(|e| format!("Failed to read job history: {}", e))?;
+        let unix = |ts: &str| humantime::parse_rfc3339_weak(ts).ok()?.duration_since(UNIX_EPOCH).ok().map(|d| d.as_secs() as i64);
+        Ok(rows
+            .iter()
+            .filter_map(|(started, completed, status, updated_at)| {
+                let start = unix(started)?;
+                let end = match completed.as_deref() {
+                    Some(completed) => unix(completed)?,
+                    None if status == "running" => last_sample.map_or(*updated_at, |sample| sample.min(*updated_at)),
+                    None => *updated_at,
+                };
+                Some((start.max(from), end.min(to)))
+            })
+            .filter(|(start, end)| start < end)
+            .collect())
+    }
+
+    // Reads, changes and writes back one card's record under the connection lock, so a metadata
+    // edit and an earnings credit can't overwrite each other. A card without a record starts empty.
+    pub fn update_gpu_inventory(&self, gpu_id: &str, update: impl FnOnce(&mut GpuInventoryRecord)) -> Result<GpuInventoryRecord, String> {
+        let conn = self.conn.lock().unwrap();
+        let existing: Option<String> = conn
+            .query_row("SELECT record FROM gpu_inventory WHERE gpu_id = ?1", params![gpu_id], |row| row.get(0))
+            .optional()
+            .map_err(|e| e.to_string())?;
+        let mut record = existing.and_then(|r| serde_json::from_str(&r).ok()).unwrap_or_else(|| GpuInventoryRecord::new(gpu_id));
+        update(&mut record);
+        let json = serde_json::to_string(&record).map_err(|e| e.to_string())?;
+        conn.execute(
+            "INSERT INTO gpu_inventory (gpu_id, record, updated_at) VALUES (?1, ?2, ?3)
+             ON CONFLICT(gpu_id) DO UPDATE SET record = excluded.record, updated_at = excluded.updated_at",
+            params![gpu_id, json, record.updated_at],
+        )
+        .map_err(|e| format!("Failed to save inventory details for {}: {}", gpu_id, e))?;
+        Ok(record)
+    }
+
+    pub fn gpu_inventory(&self) -> Result<Vec<GpuInventoryRecord>, String> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT record FROM gpu_inventory ORDER BY gpu_id").map_err(|e| e.to_string())?;
+        let records = stmt
+            .query_map([], |row| row.get::<_, String>(0))
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+            .map_err(|e| format!("Failed to read the GPU inventory: {}", e))?;
+        Ok(records.iter().filter_map(|r| serde_json::from_str(r).ok()).collect())
+    }
+
+    pub fn upsert_gpu_identity(&self, identity: &GpuIdentity) -> Result<(), String> {
+        let json = serde_json::to_string(identity).map_err(|e| e.to_string())?;
+        self.conn
+            .lock()
+            .unwrap()
+            .execute(
+                "INSERT INTO gpu_identities (gpu_id, record, updated_at) VALUES (?1, ?2, ?3)
+                 ON CONFLICT(gpu_id) DO UPDATE SET record = excluded.record, updated_at = excluded.updated_at",
+                params![identity.gpu_id, json, unix_now()],
+            )
+            .map(|_| ())
+            .map_err(|e| format!("Failed to save identity of GPU {}: {}", identity.gpu_id, e))
+    }
+
+    pub fn gpu_identities(&self) -> Result<Vec<GpuIdentity>, String> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT record FROM gpu_identities ORDER BY gpu_id").map_err(|e| e.to_string())?;
+        let records = stmt
+            .query_map([], |row| row.get::<_, String>(0))
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+            .map_err(|e| format!("Failed to read GPU identities: {}", e))?;
+        Ok(records.iter().filter_map(|r| serde_json::from_str(r).ok()).collect())
+    }
+
+    // Moves telemetry, job history, ledger events and inventory details recorded under `from` to
+    // `to`. Inventory details already saved under `to` win.
+    pub fn rekey_gpu(&self, from: &str, to: &str) -> Result<usize, String> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction().map_err(|e| e.to_string())?;
+        let mut moved = core_storage::rekey_gpu(&tx, from, to).map_err(|e| e.to_string())?;
+        moved += tx.execute("UPDATE processed_events SET gpu_id = ?2 WHERE gpu_id = ?1", params![from, to]).map_err(|e| e.to_string())?;
+        let inventory: Option<String> = tx
+            .query_row("SELECT record FROM gpu_inventory WHERE gpu_id = ?1", params![from], |row| row.get(0))
+            .optional()
+            .map_err(|e| e.to_string())?;
+        if let Some(mut record) = inventory.and_then(|r| serde_json::from_str::<GpuInventoryRecord>(&r).ok()) {
+            let kept: Option<String> = tx
+                .query_row("SELECT record FROM gpu_inventory WHERE gpu_id = ?1", params![to], |row| row.get(0))
+                .optional()
+                .map_err(|e| e.to_string())?;
+            if let Some(kept) = kept.and_then(|r| serde_json::from_str::<GpuInventoryRecord>(&r).ok()) {
+                record = kept;
+            }
+            record.gpu_id = to.to_string();
+            let json = serde_json::to_string(&record).map_err(|e| e.to_string())?;
+            moved += tx
+                .execute(
+                    "INSERT INTO gpu_inventory (gpu_id, record, updated_at) VALUES (?1, ?2, ?3)
+                     ON CONFLICT(gpu_id) DO UPDATE SET record = excluded.record",
+                    params![to, json, record.updated_at],
+                )
+                .map_err(|e| e.to_string())?;
+            tx.execute("DELETE FROM gpu_inventory WHERE gpu_id = ?1", params![from]).map_err(|e| e.to_string())?;
+        }
+        tx.commit().map_err(|e| format!("Failed to move history of GPU {}: {}", from, e))?;
+        Ok(moved)
+    }
+
+    // Records a billing-related event in the processed-event ledger. Returns false if an event
+    // with the same idempotency key was already processed, in which case the caller must not
+    // count it again. The amount is stored as decimal text, exactly as given; `gpu_id` is the
+    // GPU that earned it, for events that belong to one. The platform-corrected time is kept
+    // next to the local one for billing disputes with a drifted clock.
+    pub fn mark_processed(&self, idempotency_key: &str, kind: &str, amount_dgpu: Option<Decimal>, gpu_id: Option<&str>) -> Result<bool, String> {
+        let inserted = self
+            .conn
+            .lock()
+            .unwrap()
+            .execute(
+                "INSERT OR IGNORE INTO processed_events (idempotency_key, kind, amount_dgpu, processed_at, gpu_id, processed_at_corrected)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![idempotency_key, kind, amount_dgpu.map(|a| a.to_string()), unix_now(), gpu_id, clock::corrected_unix_secs()],
+            )
+            .map_err(|e| format!("Failed to record processed event {}: {}", idempotency_key, e))?;
+        Ok(inserted == 1)
+    }
+
+    pub fn is_processed(&self, idempotency_key: &str) -> Result<bool, String> {
+        self.conn
+            .lock()
+            .unwrap()
+            .query_row("SELECT 1 FROM processed_events WHERE idempotency_key = ?1", params![idempotency_key], |_| Ok(()))
+            .optional()
+            .map(|row| row.is_some())
+            .map_err(|e| format!("Failed to look up processed event {}: {}", idempotency_key, e))
+    }
+
+    // Inserts imported earnings rows, skipping ones already imported. Returns how many were new.
+    pub fn insert_imported_earnings(&self, rows: &[ImportedEarning]) -> Result<usize, String> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction().map_err(|e| e.to_string())?;
+        let now = unix_now();
+        let mut inserted = 0;
+        for row in rows {
+            inserted += tx
+                .execute(
+                    "INSERT OR IGNORE INTO imported_earnings (source, row_key, earned_at, machine, gpu_model, gpu_hours, amount, currency, imported_at)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                    params![row.source, row.row_key, row.earned_at, row.machine, row.gpu_model, row.gpu_hours, row.amount.to_string(), row.currency, now],
+                )
+                .map_err(|e| format!("Failed to store imported earnings: {}", e))?;
+        }
+        tx.commit().map_err(|e| format!("Failed to store imported earnings: {}", e))?;
+        Ok(inserted)
+    }
+
+    pub fn imported_earnings_by_month(&self) -> Result<Vec<ImportedEarningsMonth>, String> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn
+            .prepare(
+                "SELECT source, strftime('%Y-%m', earned_at, 'unixepoch') AS month, currency, COALESCE(gpu_hours, 0), amount
+                 FROM imported_earnings ORDER BY month, source, currency",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok(ImportedEarningsMonth {
+                    source: row.get(0)?,
+                    month: row.get(1)?,
+                    currency: row.get(2)?,
+                    rows: 1,
+                    gpu_hours: row.get(3)?,
+                    amount: core_storage::amount_at(row, 4)?.unwrap_or_default(),
+                })
+            })
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+            .map_err(|e| format!("Failed to read imported earnings: {}", e))?;
+        let mut months: Vec<ImportedEarningsMonth> = Vec::new();
+        for row in rows {
+            match months.last_mut() {
+                Some(m) if m.month == row.month && m.source == row.source && m.currency == row.currency => {
+                    m.rows += 1;
+                    m.gpu_hours += row.gpu_hours;
+                    m.amount += row.amount;
+                }
+                _ => months.push(row),
+            }
+        }
+        Ok(months)
+    }
+
+    pub fn upsert_failover_incident(&self, incident: &FailoverIncident) -> Result<(), String> {
+        let record = serde_json::to_string(incident).map_err(|e| e.to_string())?;
+        self.conn
+            .lock()
+            .unwrap()
+            .execute(
+                "INSERT INTO failover_incidents (incident_id, job_id, record, updated_at) VALUES (?1, ?2, ?3, ?4)
+                 ON CONFLICT(incident_id) DO UPDATE SET record = excluded.record, updated_at = excluded.updated_at",
+                params![incident.incident_id, incident.job_id, record, unix_now()],
+            )
+            .map(|_| ())
+            .map_err(|e| format!("Failed to record failover incident {}: {}", incident.incident_id, e))
+    }
+
+    pub fn failover_incidents(&self) -> Result<Vec<FailoverIncident>, String> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn
+            .prepare("SELECT record FROM failover_incidents ORDER BY updated_at DESC")
+            .map_err(|e| e.to_string())?;
+        let records = stmt
+            .query_map([], |row| row.get::<_, String>(0))
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+            .map_err(|e| format!("Failed to read failover incidents: {}", e))?;
+        Ok(records.iter().filter_map(|r| serde_json::from_str(r).ok()).collect())
+    }
+
+    pub fn upsert_connectivity_incident(&self, incident: &ConnectivityIncident) -> Result<(), String> {
+        let record = serde_json::to_string(incident).map_err(|e| e.to_string())?;
+        self.conn
+            .lock()
+            .unwrap()
+            .execute(
+                "INSERT INTO connectivity_incidents (incident_id, record, started_at) VALUES (?1, ?2, ?3)
+                 ON CONFLICT(incident_id) DO UPDATE SET record = excluded.record",
+                params![incident.incident_id, record, incident.started_at],
+            )
+            .map(|_| ())
+            .map_err(|e| format!("Failed to record connectivity incident {}: {}", incident.incident_id, e))
+    }
+
+    // Incidents that overlap [from, to), newest first. Open incidents always overlap `to`.
+    pub fn connectivity_incidents(&self, from: i64, to: i64) -> Result<Vec<ConnectivityIncident>, String> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn
+            .prepare("SELECT record FROM connectivity_incidents WHERE started_at < ?1 ORDER BY started_at DESC")
+            .map_err(|e| e.to_string())?;
+        let records = stmt
+            .query_map(params![to], |row| row.get::<_, String>(0))
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+            .map_err(|e| format!("Failed to read connectivity incidents: {}", e))?;
+        Ok(records
+            .iter()
+            .filter_map(|r| serde_json::from_str::<ConnectivityIncident>(r).ok())
+            .filter(|i| i.ended_at.is_none_or(|end| end > from))
+            .collect())
+    }
+
+    pub fn insert_settings_snapshot(&self, snapshot: &SettingsSnapshot) -> Result<(), String> {
+        let record = serde_json::to_string(snapshot).map_err(|e| e.to_string())?;
+        self.conn
+            .lock()
+            .unwrap()
+            .execute("INSERT INTO settings_snapshots (taken_at, record) VALUES (?1, ?2)", params![snapshot.taken_at, record])
+            .map(|_| ())
+            .map_err(|e| format!("Failed to record settings snapshot: {}", e))
+    }
+
+    fn settings_snapshot(&self, query: &str, at: i64) -> Result<Option<SettingsSnapshot>, String> {
+        let conn = self.conn.lock().unwrap();
+        let record = conn
+            .query_row(query, params![at], |row| row.get::<_, String>(0))
+            .optional()
+            .map_err(|e| format!("Failed to read settings snapshots: {}", e))?;
+        Ok(record.and_then(|r| serde_json::from_str(&r).ok()))
+    }
+
+    // The snapshot in effect at `at`: the latest taken at or before it.
+    pub fn settings_snapshot_at(&self, at: i64) -> Result<Option<SettingsSnapshot>, String> {
+        self.settings_snapshot("SELECT record FROM settings_snapshots WHERE taken_at <= ?1 ORDER BY taken_at DESC LIMIT 1", at)
+    }
+
+    pub fn oldest_settings_snapshot(&self) -> Result<Option<SettingsSnapshot>, String> {
+        self.settings_snapshot("SELECT record FROM settings_snapshots WHERE taken_at <= ?1 ORDER BY taken_at ASC LIMIT 1", i64::MAX)
+    }
+
+    // Drops snapshots taken before `cutoff`, except the newest of them: it is still the state in
+    // effect from `cutoff` until the next change.
+    pub fn prune_settings_snapshots(&self, cutoff: i64) -> Result<usize, String> {
+        self.conn
+            .lock()
+            .unwrap()
+            .execute(
+                "DELETE FROM settings_snapshots WHERE taken_at < ?1
+                 AND taken_at < (SELECT MAX(taken_at) FROM settings_snapshots WHERE taken_at < ?1)",
+                params![cutoff],
+            )
+            .map_err(|e| format!("Failed to prune settings snapshots: {}", e))
+    }
+
+    // `report_key` identifies a rejection the daemon or platform reported, which they may report
+    // again; returns false if one with the same key is already stored.
+    pub fn insert_job_rejection(&self, rejection: &JobRejection, report_key: Option<&str>) -> Result<bool, String> {
+        let record = serde_json::to_string(rejection).map_err(|e| e.to_string())?;
+        self.conn
+            .lock()
+            .unwrap()
+            .execute(
+                "INSERT OR IGNORE INTO job_rejections (rejected_at, record, report_key) VALUES (?1, ?2, ?3)",
+                params![rejection.rejected_at, record, report_key],
+            )
+            .map(|inserted| inserted > 0)
+            .map_err(|e| format!("Failed to record rejection of {}: {}", rejection.job_id, e))
+    }
+
+    // Rejections in [from, to), newest first.
+    pub fn job_rejections(&self, from: i64, to: i64) -> Result<Vec<JobRejection>, String> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn
+            .prepare("SELECT record FROM job_rejections WHERE rejected_at >= ?1 AND rejected_at < ?2 ORDER BY rejected_at DESC")
+            .map_err(|e| e.to_string())?;
+        let records = stmt
+            .query_map(params![from, to], |row| row.get::<_, String>(0))
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+            .map_err(|e| format!("Failed to read job rejections: {}", e))?;
+        Ok(records.iter().filter_map(|r| serde_json::from_str(r).ok()).collect())
+    }
+
+    pub fn insert_journal_note(&self, note: &JournalNote) -> Result<(), String> {
+        let record = serde_json::to_string(note).map_err(|e| e.to_string())?;
+        self.conn
+            .lock()
+            .unwrap()
+            .execute(
+                "INSERT INTO journal_notes (note_id, record, created_at) VALUES (?1, ?2, ?3)",
+                params![note.note_id, record, note.created_at],
+            )
+            .map(|_| ())
+            .map_err(|e| format!("Failed to save note: {}", e))
+    }
+
+    // Newest first.
+    pub fn journal_notes(&self) -> Result<Vec<JournalNote>, String> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT record FROM journal_notes ORDER BY created_at DESC").map_err(|e| e.to_string())?;
+        let records = stmt
+            .query_map([], |row| row.get::<_, String>(0))
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+            .map_err(|e| format!("Failed to read notes: {}", e))?;
+        Ok(records.iter().filter_map(|r| serde_json::from_str(r).ok()).collect())
+    }
+
+    pub fn insert_job_note(&self, note: &JobNote) -> Result<(), String> {
+        let record = serde_json::to_string(note).map_err(|e| e.to_string())?;
+        self.conn
+            .lock()
+            .unwrap()
+            .execute("INSERT INTO job_notes (job_id, record, created_at) VALUES (?1, ?2, ?3)", params![note.job_id, record, unix_now()])
+            .map(|_| ())
+            .map_err(|e| format!("Failed to store note for job {}: {}", note.job_id, e))
+    }
+
+    pub fn job_notes(&self, job_id: &str) -> Result<Vec<JobNote>, String> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn
+            .prepare("SELECT record FROM job_notes WHERE job_id = ?1 ORDER BY created_at DESC, rowid DESC")
+            .map_err(|e| e.to_string())?;
+        let records = stmt
+            .query_map(params![job_id], |row| row.get::<_, String>(0))
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+            .map_err(|e| format!("Failed to read job notes: {}", e))?;
+        Ok(records.iter().filter_map(|r| serde_json::from_str(r).ok()).collect())
+    }
+
+    pub fn insert_emergency_stop(&self, incident: &EmergencyStopIncident) -> Result<(), String> {
+        let record = serde_json::to_string(incident).map_err(|e| e.to_string())?;
+        self.conn
+            .lock()
+            .unwrap()
+            .execute(
+                "INSERT OR REPLACE INTO emergency_stops (incident_id, record, created_at) VALUES (?1, ?2, ?3)",
+                params![incident.incident_id, record, unix_now()],
+            )
+            .map(|_| ())
+            .map_err(|e| format!("Failed to record emergency stop {}: {}", incident.incident_id, e))
+    }
+
+    pub fn emergency_stops(&self) -> Result<Vec<EmergencyStopIncident>, String> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn
+            .prepare("SELECT record FROM emergency_stops ORDER BY created_at DESC")
+            .map_err(|e| e.to_string())?;
+        let records = stmt
+            .query_map([], |row| row.get::<_, String>(0))
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+            .map_err(|e| format!("Failed to read emergency stops: {}", e))?;
+        Ok(records.iter().filter_map(|r| serde_json::from_str(r).ok()).collect())
+    }
+
+    pub fn insert_scheduled_restart(&self, restart: &ScheduledRestart) -> Result<(), String> {
+        let record = serde_json::to_string(restart).map_err(|e| e.to_string())?;
+        self.conn
+            .lock()
+            .unwrap()
+            .execute(
+                "INSERT OR REPLACE INTO scheduled_restarts (restart_id, record, created_at) VALUES (?1, ?2, ?3)",
+                params![restart.restart_id, record, unix_now()],
+            )
+            .map(|_| ())
+            .map_err(|e| format!("Failed to record scheduled restart {}: {}", restart.restart_id, e))
+    }
+
+    // Most recent first.
+    pub fn scheduled_restarts(&self, limit: usize) -> Result<Vec<ScheduledRestart>, String> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn
+            .prepare("SELECT record FROM scheduled_restarts ORDER BY created_at DESC LIMIT ?1")
+            .map_err(|e| e.to_string())?;
+        let records = stmt
+            .query_map(params![limit as i64], |row| row.get::<_, String>(0))
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+            .map_err(|e| format!("Failed to read scheduled restarts: {}", e))?;
+        Ok(records.iter().filter_map(|r| serde_json::from_str(r).ok()).collect())
+    }
+
+    pub fn insert_region_latency(&self, results: &[RegionLatency]) -> Result<(), String> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction().map_err(|e| e.to_string())?;
+        let now = unix_now();
+        for result in results {
+            tx.execute(
+                "INSERT INTO region_latency (region_id, measured_at, median_ms) VALUES (?1, ?2, ?3)",
+                params![result.region_id, now, result.median_ms.map(|ms| ms as i64)],
+            )
+            .map_err(|e| format!("Failed to record region latency: {}", e))?;
+        }
+        tx.commit().map_err(|e| e.to_string())
+    }
+
+    // (measured_at, median_ms) pairs, oldest first; None where every probe failed.
+    pub fn region_latency_history(&self, region_id: &str, from: i64, to: i64) -> Result<Vec<(i64, Option<u64>)>, String> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn
+            .prepare("SELECT measured_at, median_ms FROM region_latency WHERE region_id = ?1 AND measured_at >= ?2 AND measured_at < ?3 ORDER BY measured_at")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(params![region_id, from, to], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, Option<i64>>(1)?.map(|ms| ms as u64))))
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+            .map_err(|e| format!("Failed to read region latency history: {}", e));
+        rows
+    }
+
+    // Keeps the original creation time so tasks list in the order they were started.
+    pub fn upsert_task(&self, task: &TaskInfo) -> Result<(), String> {
+        let record = serde_json::to_string(task).map_err(|e| e.to_string())?;
+        self.conn
+            .lock()
+            .unwrap()
+            .execute(
+                "INSERT INTO tasks (task_id, record, created_at) VALUES (?1, ?2, ?3) ON CONFLICT(task_id) DO UPDATE SET record = excluded.record",
+                params![task.id, record, unix_now()],
+            )
+            .map(|_| ())
+            .map_err(|e| format!("Failed to record task {}: {}", task.id, e))
+    }
+
+    // Oldest first.
+    pub fn tasks(&self) -> Result<Vec<TaskInfo>, String> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT record FROM tasks ORDER BY created_at, rowid").map_err(|e| e.to_string())?;
+        let records = stmt
+            .query_map([], |row| row.get::<_, String>(0))
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+            .map_err(|e| format!("Failed to read tasks: {}", e))?;
+        Ok(records.iter().filter_map(|r| serde_json::from_str(r).ok()).collect())
+    }
+
+    pub fn delete_task(&self, task_id: &str) -> Result<(), String> {
+        self.conn
+            .lock()
+            .unwrap()
+            .execute("DELETE FROM tasks WHERE task_id = ?1", params![task_id])
+            .map(|_| ())
+            .map_err(|e| format!("Failed to delete task {}: {}", task_id, e))
+    }
+
+    // Returns false if the job already has a record.
+    pub fn insert_attestation(&self, record: &AttestationRecord) -> Result<bool, String> {
+        let json = serde_json::to_string(record).map_err(|e| e.to_string())?;
+        self.conn
+            .lock()
+            .unwrap()
+            .execute(
+                "INSERT OR IGNORE INTO job_attestations (job_id, record, updated_at) VALUES (?1, ?2, ?3)",
+                params![record.job_id, json, unix_now()],
+            )
+            .map(|inserted| inserted == 1)
+            .map_err(|e| format!("Failed to record attestation for job {}: {}", record.job_id, e))
+    }
+
+    pub fn upsert_attestation(&self, record: &AttestationRecord) -> Result<(), String> {
+        let json = serde_json::to_string(record).map_err(|e| e.to_string())?;
+        self.conn
+            .lock()
+            .unwrap()
+            .execute(
+                "INSERT INTO job_attestations (job_id, record, updated_at) VALUES (?1, ?2, ?3)
+                 ON CONFLICT(job_id) DO UPDATE SET record = excluded.record, updated_at = excluded.updated_at",
+                params![record.job_id, json, unix_now()],
+            )
+            .map(|_| ())
+            .map_err(|e| format!("Failed to record attestation for job {}: {}", record.job_id, e))
+    }
+
+    pub fn attestations(&self) -> Result<Vec<AttestationRecord>, String> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn
+            .prepare("SELECT record FROM job_attestations ORDER BY updated_at DESC")
+            .map_err(|e| e.to_string())?;
+        let records = stmt
+            .query_map([], |row| row.get::<_, String>(0))
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+            .map_err(|e| format!("Failed to read attestations: {}", e))?;
+        Ok(records.iter().filter_map(|r| serde_json::from_str(r).ok()).collect())
+    }
+
+    pub fn upsert_job_hardware(&self, job_id: &str, evidence: &JobHardwareEvidence) -> Result<(), String> {
+        let json = serde_json::to_string(evidence).map_err(|e| e.to_string())?;
+        self.conn
+            .lock()
+            .unwrap()
+            .execute(
+                "INSERT INTO job_hardware (job_id, record, updated_at) VALUES (?1, ?2, ?3)
+                 ON CONFLICT(job_id) DO UPDATE SET record = excluded.record, updated_at = excluded.updated_at",
+                params![job_id, json, unix_now()],
+            )
+            .map(|_| ())
+            .map_err(|e| format!("Failed to record hardware evidence for job {}: {}", job_id, e))
+    }
+
+    // (job ID, evidence) for every job with recorded evidence.
+    pub fn job_hardware(&self) -> Result<Vec<(String, JobHardwareEvidence)>, String> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT job_id, record FROM job_hardware").map_err(|e| e.to_string())?;
+        let records = stmt
+            .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+            .map_err(|e| format!("Failed to read job hardware evidence: {}", e))?;
+        Ok(records.into_iter().filter_map(|(job_id, r)| Some((job_id, serde_json::from_str(&r).ok()?))).collect())
+    }
+
+    pub fn upsert_pricing_experiment(&self, experiment: &PricingExperiment) -> Result<(), String> {
+        let record = serde_json::to_string(experiment).map_err(|e| e.to_string())?;
+        self.conn
+            .lock()
+            .unwrap()
+            .execute(
+                "INSERT INTO pricing_experiments (experiment_id, record, updated_at) VALUES (?1, ?2, ?3)
+                 ON CONFLICT(experiment_id) DO UPDATE SET record = excluded.record, updated_at = excluded.updated_at",
+                params![experiment.id, record, unix_now()],
+            )
+            .map(|_| ())
+            .map_err(|e| format!("Failed to record pricing experiment {}: {}", experiment.id, e))
+    }
+
+    pub fn pricing_experiments(&self) -> Result<Vec<PricingExperiment>, String> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn
+            .prepare("SELECT record FROM pricing_experiments ORDER BY updated_at DESC")
+            .map_err(|e| e.to_string())?;
+        let records = stmt
+            .query_map([], |row| row.get::<_, String>(0))
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+            .map_err(|e| format!("Failed to read pricing experiments: {}", e))?;
+        Ok(records.iter().filter_map(|r| serde_json::from_str(r).ok()).collect())
+    }
+
+    // Closes the experiment's open period (if any) at `at` and, when `arm` is given, opens a new one.
+    pub fn switch_experiment_arm(&self, experiment_id: &str, arm: Option<usize>, at: i64) -> Result<(), String> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction().map_err(|e| e.to_string())?;
+        tx.execute(
+            "UPDATE experiment_periods SET ended_at = ?2 WHERE experiment_id = ?1 AND ended_at IS NULL",
+            params![experiment_id, at],
+        )
+        .map_err(|e| format!("Failed to close experiment period: {}", e))?;
+        if let Some(arm) = arm {
+            tx.execute(
+                "INSERT INTO experiment_periods (experiment_id, arm, started_at) VALUES (?1, ?2, ?3)",
+                params![experiment_id, arm as i64, at],
+            )
+            .map_err(|e| format!("Failed to open experiment period: {}", e))?;
+        }
+        tx.commit().map_err(|e| e.to_string())
+    }
+
+    // (arm, started_at, ended_at) for each period of the experiment, oldest first.
+    pub fn experiment_periods(&self, experiment_id: &str) -> Result<Vec<(usize, i64, Option<i64>)>, String> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn
+            .prepare("SELECT arm, started_at, ended_at FROM experiment_periods WHERE experiment_id = ?1 ORDER BY started_at")
+            .map_err(|e| e.to_string())?;
+        stmt.query_map(params![experiment_id], |row| Ok((row.get::<_, i64>(0)? as usize, row.get(1)?, row.get(2)?)))
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+            .map_err(|e| format!("Failed to read experiment periods: {}", e))
+    }
+
+    // Number of distinct `bucket_secs` buckets in [from, to) with at least one sample for `gpu_id`.
+    pub fn sampled_buckets(&self, gpu_id: &str, from: i64, to: i64, bucket_secs: i64) -> Result<i64, String> {
+        self.conn
+            .lock()
+            .unwrap()
+            .query_row(
+                "SELECT COUNT(DISTINCT recorded_at / ?4) FROM telemetry_samples WHERE gpu_id = ?1 AND recorded_at >= ?2 AND recorded_at < ?3",
+                params![gpu_id, from, to, bucket_secs],
+                |row| row.get(0),
+            )
+            .map_err(|e| format!("Failed to read telemetry coverage: {}", e))
+    }
+
+    pub fn telemetry_sample_count(&self, from: i64, to: i64) -> Result<i64, String> {
+        self.conn
+            .lock()
+            .unwrap()
+            .query_row("SELECT COUNT(*) FROM telemetry_samples WHERE recorded_at >= ?1 AND recorded_at < ?2", params![from, to], |row| row.get(0))
+            .map_err(|e| format!("Failed to count telemetry samples: {}", e))
+    }
+
+    // Up to `limit` samples in [from, to) following `after` (recorded_at, row ID) in time order,
+    // so long exports can read in pages without holding the connection.
+    pub fn telemetry_page(&self, from: i64, to: i64, after: (i64, i64), limit: usize) -> Result<Vec<TelemetryRow>, String> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn
+            .prepare(
+                "SELECT rowid, recorded_at, gpu_id, utilization_gpu_percent, temperature_c, power_draw_w, vram_free_mb,
+                        is_available_for_rent, hourly_rate_dgpu, ecc_corrected_errors, memory_clock_mhz
+                 FROM telemetry_samples
+                 WHERE recorded_at >= ?1 AND recorded_at < ?2 AND (recorded_at > ?3 OR (recorded_at = ?3 AND rowid > ?4))
+                 ORDER BY recorded_at, rowid LIMIT ?5",
+            )
+            .map_err(|e| e.to_string())?;
+        stmt.query_map(params![from, to, after.0, after.1, limit as i64], |row| {
+            Ok(TelemetryRow {
+                row_id: row.get(0)?,
+                recorded_at: row.get(1)?,
+                gpu_id: row.get(2)?,
+                utilization_gpu_percent: row.get(3)?,
+                temperature_c: row.get(4)?,
+                power_draw_w: row.get(5)?,
+                vram_free_mb: row.get(6)?,
+                is_available_for_rent: row.get(7)?,
+                hourly_rate_dgpu: core_storage::amount_at(row, 8)?.map(DgpuPerHour::new),
+                ecc_corrected_errors: row.get(9)?,
+                memory_clock_mhz: row.get(10)?,
+            })
+        })
+        .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| format!("Failed to read telemetry: {}", e))
+    }
+
+    // Per GPU and hour in [from, to): average listed rate, share of samples listed for rent, and
+    // share of samples with utilization at or above `busy_utilization_percent`.
+    pub fn hourly_usage(&self, from: i64, to: i64, busy_utilization_percent: u32) -> Result<Vec<HourlyUsage>, String> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn
+            .prepare(
+                "SELECT gpu_id, (recorded_at / 3600) * 3600 AS hour_start, AVG(hourly_rate_dgpu),
+                        AVG(is_available_for_rent), AVG(CASE WHEN utilization_gpu_percent >= ?3 THEN 1.0 ELSE 0.0 END)
+                 FROM telemetry_samples WHERE recorded_at >= ?1 AND recorded_at < ?2
+                 GROUP BY gpu_id, hour_start ORDER BY hour_start",
+            )
+            .map_err(|e| e.to_string())?;
+        stmt.query_map(params![from, to, busy_utilization_percent], |row| {
+            Ok(HourlyUsage {
+                gpu_id: row.get(0)?,
+                hour_start: row.get(1)?,
+                hourly_rate_dgpu: core_storage::amount_at(row, 2)?.map(DgpuPerHour::new),
+                listed_fraction: row.get(3)?,
+                busy_fraction: row.get(4)?,
+            })
+        })
+        .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| format!("Failed to read hourly usage: {}", e))
+    }
+
+    // Mean power draw of `gpu_id` since `from` while at or above `busy_utilization_percent`.
+    pub fn average_loaded_power_w(&self, gpu_id: &str, from: i64, busy_utilization_percent: u32) -> Result<Option<f64>, String> {
+        self.conn
+            .lock()
+            .unwrap()
+            .query_row(
+                "SELECT AVG(power_draw_w) FROM telemetry_samples WHERE gpu_id = ?1 AND recorded_at >= ?2 AND utilization_gpu_percent >= ?3",
+                params![gpu_id, from, busy_utilization_percent],
+                |row| row.get(0),
+            )
+            .map_err(|e| format!("Failed to read power history: {}", e))
+    }
+
+    // Per-day health aggregates for `gpu_id` since `from`. Idle means below `idle_below_percent`
+    // utilization, loaded at or above `loaded_from_percent`. ECC counters are cumulative since the
+    // driver loaded, so corrections are the increases between samples; a lower reading means the
+    // counter was reset, and all of it is new since.
+    pub fn daily_health(&self, gpu_id: &str, from: i64, idle_below_percent: u32, loaded_from_percent: u32) -> Result<Vec<DailyHealth>, String> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn
+            .prepare(
+                "SELECT (recorded_at / 86400) * 86400 AS day_start,
+                        AVG(CASE WHEN utilization_gpu_percent < ?3 THEN temperature_c END),
+                        SUM(CASE WHEN ecc_corrected_errors IS NULL THEN NULL
+                                 WHEN previous_ecc IS NULL THEN 0
+                                 WHEN ecc_corrected_errors >= previous_ecc THEN ecc_corrected_errors - previous_ecc
+                                 ELSE ecc_corrected_errors END),
+                        MIN(CASE WHEN utilization_gpu_percent >= ?4 THEN memory_clock_mhz END),
+                        MAX(CASE WHEN utilization_gpu_percent >= ?4 THEN memory_clock_mhz END)
+                 FROM (SELECT *, LAG(ecc_corrected_errors) OVER (PARTITION BY ecc_corrected_errors IS NULL ORDER BY recorded_at) AS previous_ecc
+                       FROM telemetry_samples WHERE gpu_id = ?1 AND recorded_at >= ?2)
+                 GROUP BY day_start ORDER BY day_start",
+            )
+            .map_err(|e| e.to_string())?;
+        stmt.query_map(params![gpu_id, from, idle_below_percent, loaded_from_percent], |row| {
+            Ok(DailyHealth {
+                day_start: row.get(0)?,
+                idle_temperature_c: row.get(1)?,
+                ecc_corrections: row.get(2)?,
+                loaded_memory_clock_min_mhz: row.get(3)?,
+                loaded_memory_clock_max_mhz: row.get(4)?,
+            })
+        })
+        .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| format!("Failed to read GPU health history: {}", e))
+    }
+
+    // Count and summed amount of ledger events of `kind` processed in [from, to).
+    pub fn ledger_totals(&self, kind: &str, from: i64, to: i64) -> Result<(i64, Decimal), String> {
+        self.ledger_totals_where(kind, from, to, |_| true)
+    }
+
+    // Like `ledger_totals`, but only events earned on one of `gpu_ids`. Events recorded without
+    // a GPU (older daemons, or from before the ledger kept it) aren't counted.
+    pub fn ledger_totals_for_gpus(&self, kind: &str, from: i64, to: i64, gpu_ids: &[String]) -> Result<(i64, Decimal), String> {
+        self.ledger_totals_where(kind, from, to, |gpu_id| gpu_id.is_some_and(|id| gpu_ids.iter().any(|g| g == id)))
+    }
+
+    fn ledger_totals_where(&self, kind: &str, from: i64, to: i64, include: impl Fn(Option<&str>) -> bool) -> Result<(i64, Decimal), String> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn
+            .prepare("SELECT amount_dgpu, gpu_id FROM processed_events WHERE kind = ?1 AND processed_at >= ?2 AND processed_at < ?3")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(params![kind, from, to], |row| Ok((core_storage::amount_at(row, 0)?, row.get::<_, Option<String>>(1)?)))
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+            .map_err(|e| format!("Failed to total event ledger: {}", e))?;
+        let amounts: Vec<Option<Decimal>> = rows.into_iter().filter(|(_, gpu_id)| include(gpu_id.as_deref())).map(|(amount, _)| amount).collect();
+        Ok((amounts.len() as i64, amounts.into_iter().flatten().sum()))
+    }
+
+    // Summed amount of `kind` events per GPU, with when the first was processed. Events without a
+    // GPU aren't counted.
+    pub fn ledger_earnings_by_gpu(&self, kind: &str) -> Result<HashMap<String, (Decimal, i64)>, String> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn
+            .prepare("SELECT gpu_id, amount_dgpu, processed_at FROM processed_events WHERE kind = ?1 AND gpu_id IS NOT NULL")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(params![kind], |row| Ok((row.get::<_, String>(0)?, core_storage::amount_at(row, 1)?, row.get::<_, i64>(2)?)))
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+            .map_err(|e| format!("Failed to total event ledger: {}", e))?;
+        let mut totals: HashMap<String, (Decimal, i64)> = HashMap::new();
+        for (gpu_id, amount, processed_at) in rows {
+            let total = totals.entry(gpu_id).or_insert((Decimal::ZERO, processed_at));
+            total.0 += amount.unwrap_or(Decimal::ZERO);
+            total.1 = total.1.min(processed_at);
+        }
+        Ok(totals)
+    }
+
+    pub fn ledger(&self, since: i64) -> Result<LedgerSummary, String> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn
+            .prepare(
+                "SELECT idempotency_key, kind, amount_dgpu, processed_at, processed_at_corrected FROM processed_events
+                 WHERE processed_at >= ?1 ORDER BY processed_at",
+            )
+            .map_err(|e| e.to_string())?;
+        let entries = stmt
+            .query_map(params![since], |row| {
+                Ok(LedgerEntry {
+                    idempotency_key: row.get(0)?,
+                    kind: row.get(1)?,
+                    amount_dgpu: core_storage::amount_at(row, 2)?,
+                    processed_at: row.get(3)?,
+                    processed_at_corrected: row.get(4)?,
+                })
+            })
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+            .map_err(|e| format!("Failed to read event ledger: {}", e))?;
+        let completed_job_earnings_dgpu = entries
+            .iter()
+            .filter(|e| e.kind == "job_completed")
+            .filter_map(|e| e.amount_dgpu)
+            .sum();
+        Ok(LedgerSummary { completed_job_earnings_dgpu, entries })
+    }
+
+    // Deletes data in `categories`, optionally only rows last touched before `older_than`
+    // (unix seconds).
+    pub fn purge(&self, categories: &[DataCategory], older_than: Option<i64>) -> Result<PurgeSummary, String> {
+        let cutoff = older_than.unwrap_or(i64::MAX);
+        let conn = self.conn.lock().unwrap();
+        let mut summary = PurgeSummary::default();
+        for category in categories {
+            match category {
+                DataCategory::TelemetryHistory => {
+                    summary.telemetry_samples_deleted = conn
+                        .execute("DELETE FROM telemetry_samples WHERE recorded_at < ?1", params![cutoff])
+                        .map_err(|e| format!("Failed to purge telemetry: {}", e))?;
+                    conn.execute("DELETE FROM region_latency WHERE measured_at < ?1", params![cutoff])
+                        .map_err(|e| format!("Failed to purge region latency history: {}", e))?;
+                    conn.execute("DELETE FROM connectivity_incidents WHERE started_at < ?1", params![cutoff])
+                        .map_err(|e| format!("Failed to purge connectivity incidents: {}", e))?;
+                }
+                DataCategory::JobMetadata => {
+                    summary.jobs_deleted = conn
+                        .execute("DELETE FROM jobs WHERE updated_at < ?1", params![cutoff])
+                        .map_err(|e| format!("Failed to purge jobs: {}", e))?;
+                    conn.execute("DELETE FROM job_notes WHERE created_at < ?1", params![cutoff])
+                        .map_err(|e| format!("Failed to purge job notes: {}", e))?;
+                    conn.execute("DELETE FROM job_rejections WHERE rejected_at < ?1", params![cutoff])
+                        .map_err(|e| format!("Failed to purge job rejections: {}", e))?;
+                    conn.execute("DELETE FROM job_hardware WHERE updated_at < ?1", params![cutoff])
+                        .map_err(|e| format!("Failed to purge job hardware evidence: {}", e))?;
+                }
+                DataCategory::RenterIds => {
+                    summary.renter_ids_cleared = conn
+                        .execute("UPDATE jobs SET renter_id = NULL WHERE renter_id IS NOT NULL AND updated_at < ?1", params![cutoff])
+                        .map_err(|e| format!("Failed to clear renter IDs: {}", e))?;
+                }
+                // The log buffer lives in memory; the command clears it directly.
+                DataCategory::Logs => {}
+            }
+        }
+        Ok(summary)
+    }
+
+    // Applies the retention windows from the privacy settings. Categories the user has turned
+    // off entirely are purged in full.
+    pub fn enforce_retention(&self, privacy: &PrivacySettings) -> Result<PurgeSummary, String> {
+        let telemetry_cutoff = if privacy.store_telemetry_history { days_ago(privacy.telemetry_retention_days) } else { i64::MAX };
+        let jobs_cutoff = if privacy.store_job_metadata { days_ago(privacy.job_retention_days) } else { i64::MAX };
+        let telemetry = self.purge(&[DataCategory::TelemetryHistory], Some(telemetry_cutoff))?;
+        let jobs = self.purge(&[DataCategory::JobMetadata], Some(jobs_cutoff))?;
+        let renters = if privacy.store_renter_ids { PurgeSummary::default() } else { self.purge(&[DataCategory::RenterIds], None)? };
+        Ok(PurgeSummary {
+            telemetry_samples_deleted: telemetry.telemetry_samples_deleted,
+            jobs_deleted: jobs.jobs_deleted,
+            renter_ids_cleared: renters.renter_ids_cleared,
+            log_entries_cleared: 0,
+        })
+    }
+
+    fn file_size(&self) -> u64 {
+        fs::metadata(&*self.path.lock().unwrap()).map(|m| m.len()).unwrap_or(0)
+    }
+
+    // Replaces raw samples older than the compaction window with per-bucket averages. Buckets
+    // that already hold a single row are left alone, so repeated runs are cheap.
+    fn compact_telemetry(&self) -> Result<(usize, usize), String> {
+        let cutoff = unix_now() - COMPACT_AFTER_SECS;
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction().map_err(|e| e.to_string())?;
+        tx.execute_batch("DROP TABLE IF EXISTS temp.compacted")
+            .map_err(|e| e.to_string())?;
+        tx.execute(
+            "CREATE TEMP TABLE compacted AS
+             SELECT (recorded_at / ?2) * ?2 AS bucket, gpu_id,
+                    CAST(AVG(utilization_gpu_percent) AS INTEGER) AS utilization_gpu_percent,
+                    CAST(AVG(temperature_c) AS INTEGER) AS temperature_c,
+                    CAST(AVG(power_draw_w) AS INTEGER) AS power_draw_w,
+                    MIN(vram_free_mb) AS vram_free_mb,
+                    MAX(is_available_for_rent) AS is_available_for_rent,
+                    -- Rates rarely change within a bucket; the average comes back as decimal text.
+                    AVG(hourly_rate_dgpu) AS hourly_rate_dgpu,
+                    MAX(ecc_corrected_errors) AS ecc_corrected_errors,
+                    -- The minimum keeps clock dips visible to the health trend.
+                    MIN(memory_clock_mhz) AS memory_clock_mhz
+             FROM telemetry_samples WHERE recorded_at < ?1
+             GROUP BY gpu_id, recorded_at / ?2 HAVING COUNT(*) > 1",
+            params![cutoff, COMPACT_BUCKET_SECS],
+        )
+        .map_err(|e| format!("Failed to aggregate telemetry: {}", e))?;
+        let removed = tx
+            .execute(
+                "DELETE FROM telemetry_samples WHERE recorded_at < ?1
+                 AND EXISTS (SELECT 1 FROM temp.compacted c
+                             WHERE c.gpu_id = telemetry_samples.gpu_id AND c.bucket = (telemetry_samples.recorded_at / ?2) * ?2)",
+                params![cutoff, COMPACT_BUCKET_SECS],
+            )
+            .map_err(|e| format!("Failed to remove raw telemetry: {}", e))?;
+        let written = tx
+            .execute(
+                "INSERT INTO telemetry_samples (recorded_at, gpu_id, utilization_gpu_percent, temperature_c, power_draw_w, vram_free_mb, is_available_for_rent, hourly_rate_dgpu, ecc_corrected_errors, memory_clock_mhz)
+                 SELECT bucket, gpu_id, utilization_gpu_percent, temperature_c, power_draw_w, vram_free_mb, is_available_for_rent, hourly_rate_dgpu, ecc_corrected_errors, memory_clock_mhz
+                 FROM temp.compacted",
+                [],
+            )
+            .map_err(|e| format!("Failed to write compacted telemetry: {}", e))?;
+        tx.execute_batch("DROP TABLE temp.compacted").map_err(|e| e.to_string())?;
+        tx.commit().map_err(|e| e.to_string())?;
+        Ok((removed, written))
+    }
+
+    fn pragma_i64(&self, pragma: &str) -> Result<i64, String> {
+        self.conn
+            .lock()
+            .unwrap()
+            .query_row(&format!("PRAGMA {}", pragma), [], |row| row.get(0))
+            .map_err(|e| format!("Failed to read PRAGMA {}: {}", pragma, e))
+    }
+
+    pub fn run_maintenance(&self, privacy: &PrivacySettings) -> Result<MaintenanceReport, String> {
+        let bytes_before = self.file_size();
+        let (samples_compacted, samples_written) = self.compact_telemetry()?;
+        let retention = self.enforce_retention(privacy)?;
+
+        let integrity: String = {
+            let conn = self.conn.lock().unwrap();
+            // Refresh planner statistics so the indexes keep getting used as the data shifts.
+            conn.execute_batch("ANALYZE; PRAGMA optimize;")
+                .map_err(|e| format!("Failed to analyze database: {}", e))?;
+            conn.query_row("PRAGMA quick_check", [], |row| row.get(0))
+                .map_err(|e| format!("Failed to check database integrity: {}", e))?
+        };
+        if integrity != "ok" {
+            // Rebuilding indexes fixes the common case of a corrupt index over intact tables.
+            self.conn
+                .lock()
+                .unwrap()
+                .execute_batch("REINDEX")
+                .map_err(|e| format!("Failed to rebuild indexes: {}", e))?;
+        }
+
+        let page_count = self.pragma_i64("page_count")?;
+        let freelist_count = self.pragma_i64("freelist_count")?;
+        let vacuumed = page_count > 0 && freelist_count as f64 / page_count as f64 >= VACUUM_FREE_RATIO;
+        if vacuumed {
+            self.conn
+                .lock()
+                .unwrap()
+                .execute_batch("VACUUM")
+                .map_err(|e| format!("Failed to vacuum database: {}", e))?;
+        }
+
+        let report = MaintenanceReport {
+            ran_at: get_timestamp(),
+            samples_compacted,
+            samples_written,
+            retention,
+            vacuumed,
+            integrity,
+            bytes_before,
+            bytes_after: self.file_size(),
+        };
+        *self.last_maintenance.lock().unwrap() = Some(report.clone());
+        Ok(report)
+    }
+
+    pub fn status(&self) -> Result<StorageStatus, String> {
+        let (telemetry_samples, oldest_sample_at, newest_sample_at, jobs) = {
+            let conn = self.conn.lock().unwrap();
+            let (count, oldest, newest) = conn
+                .query_row("SELECT COUNT(*), MIN(recorded_at), MAX(recorded_at) FROM telemetry_samples", [], |row| {
+                    Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+                })
+                .map_err(|e| format!("Failed to read telemetry stats: {}", e))?;
+            let jobs = conn
+                .query_row("SELECT COUNT(*) FROM jobs", [], |row| row.get(0))
+                .map_err(|e| format!("Failed to read job stats: {}", e))?;
+            (count, oldest, newest, jobs)
+        };
+        Ok(StorageStatus {
+            path: self.path.lock().unwrap().display().to_string(),
+            size_bytes: self.file_size(),
+            page_count: self.pragma_i64("page_count")?,
+            freelist_count: self.pragma_i64("freelist_count")?,
+            telemetry_samples,
+            jobs,
+            oldest_sample_at,
+            newest_sample_at,
+            last_maintenance: self.last_maintenance.lock().unwrap().clone(),
+            checkpoints: None,
+        })
+    }
+}
+
+pub fn spawn_retention_task(app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            if let Some(storage) = app_handle.try_state::<Storage>() {
+                let privacy = app_settings::current(&app_handle).privacy;
+                match storage.enforce_retention(&privacy) {
+                    Ok(summary) if summary.telemetry_samples_deleted + summary.jobs_deleted + summary.renter_ids_cleared > 0 => {
+                        emit_log_entry(&app_handle, "status", format!("Retention task purged old data: {:?}", summary));
+                    }
+                    Ok(_) => {}
+                    Err(e) => emit_log_entry(&app_handle, "error", format!("Retention task failed: {}", e)),
+                }
+            }
+            if !shutdown::sleep(RETENTION_INTERVAL).await {
+                break;
+            }
+        }
+    });
+}
+
+pub fn spawn_maintenance_task(app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        // Let startup settle before the first (potentially slow) run, but don't wait a full day:
+        // an app that is restarted daily would otherwise never get one.
+        let mut delay = MAINTENANCE_STARTUP_DELAY;
+        loop {
+            if !shutdown::sleep(delay).await {
+                break;
+            }
+            delay = MAINTENANCE_INTERVAL;
+            let handle = app_handle.clone();
+            let result = tauri::async_runtime::spawn_blocking(move || {
+                let privacy = app_settings::current(&handle).privacy;
+                handle.try_state::<Storage>().map(|storage| storage.run_maintenance(&privacy))
+            })
+            .await;
+            match result {
+                Ok(Some(Ok(report))) => emit_log_entry(&app_handle, "status", format!("Database maintenance finished: {:?}", report)),
+                Ok(Some(Err(e))) => emit_log_entry(&app_handle, "error", format!("Database maintenance failed: {}", e)),
+                Ok(None) => {}
+                Err(e) => emit_log_entry(&app_handle, "error", format!("Database maintenance task panicked: {}", e)),
+            }
+        }
+    });
+}
+
+#[tauri::command]
+pub async fn run_db_maintenance_now(app_handle: AppHandle) -> Result<MaintenanceReport, String> {
+    let handle = app_handle.clone();
+    let report = tauri::async_runtime::spawn_blocking(move || {
+        let privacy = app_settings::current(&handle).privacy;
+        handle
+            .try_state::<Storage>()
+            .ok_or_else(|| "Local storage is unavailable.".to_string())
+            .and_then(|storage| storage.run_maintenance(&privacy))
+    })
+    .await
+    .map_err(|e| format!("Database maintenance task failed: {}", e))??;
+    emit_log_entry(&app_handle, "status", format!("Database maintenance finished: {:?}", report));
+    Ok(report)
+}
+
+#[tauri::command]
+pub async fn get_event_ledger(app_handle: AppHandle, since: Option<String>) -> Result<LedgerSummary, String> {
+    let storage = app_handle.try_state::<Storage>().ok_or_else(|| "Local storage is unavailable.".to_string())?;
+    let since = since
+        .map(|ts| {
+            humantime::parse_rfc3339_weak(&ts)
+                .map_err(|e| format!("Invalid since timestamp '{}': {}", ts, e))
+                .map(|t| t.duration_since(UNIX_EPOCH).map(|d| d.as_secs() as i64).unwrap_or(0))
+        })
+        .transpose()?
+        .unwrap_or(0);
+    storage.ledger(since)
+}
+
+#[tauri::command]
+pub async fn get_storage_status(app_handle: AppHandle) -> Result<StorageStatus, String> {
+    let storage = app_handle.try_state::<Storage>().ok_or_else(|| "Local storage is unavailable.".to_string())?;
+    let mut status = storage.status()?;
+    status.checkpoints = checkpoints::usage(&app_handle).await.ok();
+    Ok(status)
+}
+
+#[tauri::command]
+pub async fn purge_local_data(
+    app_handle: AppHandle,
+    daemon_state: State<'_, DaemonState>,
+    categories: Vec<DataCategory>,
+    older_than: Option<String>,
+) -> Result<PurgeSummary, String> {
+    let storage = app_handle.try_state::<Storage>().ok_or_else(|| "Local storage is unavailable.".to_string())?;
+    let cutoff = older_than
+        .map(|ts| {
+            humantime::parse_rfc3339_weak(&ts)
+                .map_err(|e| format!("Invalid older_than timestamp '{}': {}", ts, e))
+                .map(|t| t.duration_since(UNIX_EPOCH).map(|d| d.as_secs() as i64).unwrap_or(0))
+        })
+        .transpose()?;
+
+    let mut summary = storage.purge(&categories, cutoff)?;
+    if categories.contains(&DataCategory::Logs) {
+        let mut buffer = daemon_state.log_buffer.lock().unwrap();
+        let before = buffer.len();
+        match cutoff {
+            Some(cutoff) => buffer.retain(|entry| {
+                humantime::parse_rfc3339_weak(&entry.timestamp)
+                    .ok()
+                    .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                    .is_some_and(|d| d.as_secs() as i64 >= cutoff)
+            }),
+            None => buffer.clear(),
+        }
+        summary.log_entries_cleared = before - buffer.len();
+    }
+    emit_log_entry(&app_handle, "status", format!("Purged local data {:?}: {:?}", categories, summary));
+    Ok(summary)
+}
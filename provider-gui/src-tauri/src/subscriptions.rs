@@ -0,0 +1,145 @@
+// Event subscriptions, so the webview only receives what it is displaying.
+//
+// The high-volume events (telemetry, job lists, finance summaries, logs, plugin telemetry) go
+// out through `emit`, which checks them against the registry. A window subscribes to channels
+// with optional filters and gets a token back to unsubscribe with; its subscriptions are also
+// dropped when it closes. A window with no subscriptions receives every event, so a frontend
+// that doesn't use subscriptions keeps working unchanged. Low-volume events (alerts,
+// settings changes and the like) are always emitted directly.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use tauri::{Manager, Runtime, State, Window, WindowEvent};
+
+static TOKEN_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Channel {
+    Telemetry,       // gpus_updated
+    Jobs,            // jobs_updated
+    Finance,         // financial_summary_updated
+    Logs,            // daemon_log
+    PluginTelemetry, // plugin_telemetry
+}
+
+impl Channel {
+    fn for_event(event: &str) -> Option<Channel> {
+        match event {
+            "gpus_updated" => Some(Channel::Telemetry),
+            "jobs_updated" => Some(Channel::Jobs),
+            "financial_summary_updated" => Some(Channel::Finance),
+            "daemon_log" => Some(Channel::Logs),
+            "plugin_telemetry" => Some(Channel::PluginTelemetry),
+            _ => None,
+        }
+    }
+}
+
+// Each filter narrows the channels it applies to; unset means everything.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct SubscriptionFilters {
+    pub gpu_ids: Option<Vec<String>>,    // Telemetry
+    pub job_ids: Option<Vec<String>>,    // Jobs
+    pub log_types: Option<Vec<String>>,  // Logs, e.g. ["error", "stderr"]
+    pub plugin_ids: Option<Vec<String>>, // PluginTelemetry
+}
+
+#[derive(Debug, Clone)]
+struct Subscription {
+    window: String,
+    channels: Vec<Channel>,
+    filters: SubscriptionFilters,
+}
+
+pub struct SubscriptionRegistry {
+    subscriptions: Mutex<HashMap<String, Subscription>>,
+}
+
+impl SubscriptionRegistry {
+    pub fn new() -> Self {
+        SubscriptionRegistry { subscriptions: Mutex::new(HashMap::new()) }
+    }
+}
+
+fn matches(ids: &Option<Vec<String>>, value: Option<&str>) -> bool {
+    match ids {
+        None => true,
+        Some(ids) => value.is_some_and(|v| ids.iter().any(|id| id == v)),
+    }
+}
+
+// The part of `payload` a subscription wants, or None if it wants none of it.
+fn narrow(channel: Channel, filters: &SubscriptionFilters, payload: &Value) -> Option<Value> {
+    let (ids, key) = match channel {
+        Channel::Telemetry => (&filters.gpu_ids, "id"),
+        Channel::Jobs => (&filters.job_ids, "id"),
+        Channel::Logs => (&filters.log_types, "log_type"),
+        Channel::PluginTelemetry => (&filters.plugin_ids, "plugin_id"),
+        Channel::Finance => return Some(payload.clone()),
+    };
+    match payload {
+        // Lists are narrowed item by item; an empty list still goes out, since it is news too.
+        Value::Array(items) => Some(Value::Array(items.iter().filter(|item| matches(ids, item.get(key).and_then(Value::as_str))).cloned().collect())),
+        _ => matches(ids, payload.get(key).and_then(Value::as_str)).then(|| payload.clone()),
+    }
+}
+
+pub fn emit<R: Runtime, S: Serialize>(manager: &impl Manager<R>, event: &str, payload: S) -> Result<(), String> {
+    let registry = manager.try_state::<SubscriptionRegistry>();
+    let Some(channel) = Channel::for_event(event).filter(|_| registry.is_some()) else {
+        return manager.emit_all(event, payload).map_err(|e| e.to_string());
+    };
+    let payload = serde_json::to_value(payload).map_err(|e| e.to_string())?;
+    let subscriptions: Vec<Subscription> = registry.unwrap().subscriptions.lock().unwrap().values().cloned().collect();
+    for (label, window) in manager.windows() {
+        let own: Vec<&Subscription> = subscriptions.iter().filter(|s| s.window == label).collect();
+        let narrowed = if own.is_empty() {
+            Some(payload.clone())
+        } else {
+            // With several matching subscriptions, the least narrow one wins.
+            own.iter()
+                .filter(|s| s.channels.contains(&channel))
+                .filter_map(|s| narrow(channel, &s.filters, &payload))
+                .max_by_key(|v| v.as_array().map_or(1, Vec::len))
+        };
+        if let Some(narrowed) = narrowed {
+            window.emit(event, narrowed).map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(())
+}
+
+pub fn handle_window_event(window: &Window, event: &WindowEvent) {
+    if let WindowEvent::Destroyed = event {
+        if let Some(registry) = window.try_state::<SubscriptionRegistry>() {
+            registry.subscriptions.lock().unwrap().retain(|_, s| s.window != window.label());
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn subscribe(window: Window, registry: State<'_, SubscriptionRegistry>, channels: Vec<Channel>, filters: Option<SubscriptionFilters>) -> Result<String, String> {
+    if channels.is_empty() {
+        return Err("Subscribe to at least one channel.".to_string());
+    }
+    let token = format!("sub-{}", TOKEN_COUNTER.fetch_add(1, Ordering::Relaxed) + 1);
+    let subscription = Subscription { window: window.label().to_string(), channels, filters: filters.unwrap_or_default() };
+    registry.subscriptions.lock().unwrap().insert(token.clone(), subscription);
+    Ok(token)
+}
+
+#[tauri::command]
+pub async fn unsubscribe(registry: State<'_, SubscriptionRegistry>, token: String) -> Result<(), String> {
+    registry
+        .subscriptions
+        .lock()
+        .unwrap()
+        .remove(&token)
+        .map(|_| ())
+        .ok_or_else(|| format!("No subscription with token '{}'.", token))
+}
@@ -0,0 +1,492 @@
+// Optional encrypted settings sync across machines.
+//
+// The synced document holds shared preferences and the daemon's provider settings. It is
+// encrypted client-side with the user's sync passphrase before it is stored on the platform,
+// a WebDAV server or an S3 bucket, so the storage backend never sees plaintext.
+//
+// Conflict handling: if only one side changed since the last sync, it wins outright. If both
+// changed, non-pricing fields are last-writer-wins, while diverging pricing rules are held
+// back as a pending conflict for the user to resolve manually.
+
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager, State};
+
+use crate::app_settings::{self, AppSettingsState, PrivacySettings};
+use crate::crypto::{self, EncryptedEnvelope};
+use crate::units::DgpuPerHour;
+use crate::{emit_log_entry, get_provider_settings, get_timestamp, http_client, secrets, shutdown, update_provider_settings, ProviderSettings};
+
+const SYNC_STATE_FILE_NAME: &str = "sync_state.json";
+const SYNC_INTERVAL: Duration = Duration::from_secs(15 * 60);
+const PASSPHRASE_KEY: &str = "sync.passphrase";
+const CREDENTIAL_KEY: &str = "sync.credential"; // Platform token, WebDAV password or S3 secret key
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SyncBackend {
+    Platform { base_url: String },
+    WebDav { url: String, username: String },
+    S3 { endpoint: String, bucket: String, region: String, access_key_id: String, object_key: String },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct SyncConfig {
+    pub enabled: bool,
+    pub backend: Option<SyncBackend>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct SyncedPreferences {
+    privacy: PrivacySettings,
+    crash_reporting_enabled: bool,
+    known_issues_url: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PricingRules {
+    pub default_hourly_rate_dgpu: DgpuPerHour,
+    pub preferred_currency: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct SyncDocument {
+    updated_at: i64, // unix millis
+    device: String,
+    preferences: SyncedPreferences,
+    provider: Option<ProviderSettings>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PricingConflict {
+    pub local: PricingRules,
+    pub remote: PricingRules,
+    pub remote_device: String,
+    pub remote_updated_at: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+struct SyncStateData {
+    last_remote_updated_at: Option<i64>,
+    last_content_hash: Option<String>,
+    local_updated_at: i64,
+    last_synced_at: Option<String>,
+    pending_conflict: Option<PricingConflict>,
+}
+
+pub struct SyncState {
+    data: Mutex<SyncStateData>,
+    path: Option<PathBuf>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct SyncStatus {
+    pub config: SyncConfig,
+    pub has_passphrase: bool,
+    pub last_synced_at: Option<String>,
+    pub pending_conflict: Option<PricingConflict>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncOutcome {
+    UpToDate,
+    Pushed,
+    Pulled,
+    Merged,
+    Conflict(PricingConflict),
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(tag = "choice", rename_all = "snake_case")]
+pub enum PricingResolution {
+    Local,
+    Remote,
+    Custom { rules: PricingRules },
+}
+
+fn now_millis() -> i64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis() as i64).unwrap_or(0)
+}
+
+pub fn device_name() -> String {
+    std::env::var("COMPUTERNAME")
+        .or_else(|_| std::env::var("HOSTNAME"))
+        .unwrap_or_else(|_| std::env::consts::OS.to_string())
+}
+
+impl SyncState {
+    pub fn load(app_handle: &AppHandle) -> Self {
+        let path = app_handle.path_resolver().app_data_dir().map(|dir| dir.join(SYNC_STATE_FILE_NAME));
+        let data = path
+            .as_ref()
+            .and_then(|p| fs::read_to_string(p).ok())
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        SyncState { data: Mutex::new(data), path }
+    }
+
+    fn update(&self, f: impl FnOnce(&mut SyncStateData)) {
+        let mut data = self.data.lock().unwrap();
+        f(&mut data);
+        if let Some(path) = &self.path {
+            if let Ok(json) = serde_json::to_string_pretty(&*data) {
+                if let Err(e) = fs::write(path, json) {
+                    eprintln!("Failed to persist sync state: {}", e);
+                }
+            }
+        }
+    }
+}
+
+// Called whenever synced settings change locally, so last-writer-wins has a local timestamp.
+pub fn note_local_change(app_handle: &AppHandle) {
+    if let Some(state) = app_handle.try_state::<SyncState>() {
+        state.update(|data| data.local_updated_at = now_millis());
+    }
+}
+
+fn pricing_of(settings: &ProviderSettings) -> PricingRules {
+    PricingRules {
+        default_hourly_rate_dgpu: settings.default_hourly_rate_dgpu,
+        preferred_currency: settings.preferred_currency.clone(),
+    }
+}
+
+fn with_pricing(settings: &ProviderSettings, pricing: &PricingRules) -> ProviderSettings {
+    ProviderSettings {
+        default_hourly_rate_dgpu: pricing.default_hourly_rate_dgpu,
+        preferred_currency: pricing.preferred_currency.clone(),
+        ..settings.clone()
+    }
+}
+
+fn content_hash(doc: &SyncDocument) -> String {
+    let content = serde_json::json!({ "preferences": doc.preferences, "provider": doc.provider });
+    sha256_hex(content.to_string().as_bytes())
+}
+
+async fn local_document(app_handle: &AppHandle) -> SyncDocument {
+    let settings = app_settings::current(app_handle);
+    SyncDocument {
+        updated_at: now_millis(),
+        device: device_name(),
+        preferences: SyncedPreferences {
+            privacy: settings.privacy,
+            crash_reporting_enabled: settings.crash_reporting_enabled,
+            known_issues_url: settings.known_issues_url,
+        },
+        // Offline daemon: sync preferences only and leave the remote provider settings alone.
+        provider: get_provider_settings(app_handle.clone()).await.ok(),
+    }
+}
+
+// --- Transport ---
+
+type HmacSha256 = Hmac<Sha256>;
+
+fn hmac(key: &[u8], data: &str) -> Vec<u8> {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(data.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    hex::encode(Sha256::digest(bytes))
+}
+
+// Minimal AWS Signature V4 for single-object GET/PUT with path-style addressing.
+fn s3_request(
+    client: &reqwest::Client,
+    method: reqwest::Method,
+    endpoint: &str,
+    bucket: &str,
+    region: &str,
+    access_key_id: &str,
+    secret_key: &str,
+    object_key: &str,
+    body: Vec<u8>,
+) -> Result<reqwest::RequestBuilder, String> {
+    let url = reqwest::Url::parse(&format!("{}/{}/{}", endpoint.trim_end_matches('/'), bucket, object_key))
+        .map_err(|e| format!("Invalid S3 endpoint: {}", e))?;
+    let host = match (url.host_str(), url.port()) {
+        (Some(host), Some(port)) => format!("{}:{}", host, port),
+        (Some(host), None) => host.to_string(),
+        _ => return Err("S3 endpoint has no host.".to_string()),
+    };
+    let amz_date: String = humantime::format_rfc3339_seconds(SystemTime::now())
+        .to_string()
+        .chars()
+        .filter(|c| *c != '-' && *c != ':')
+        .collect();
+    let date = &amz_date[..8];
+    let payload_hash = sha256_hex(&body);
+    let canonical_request = format!(
+        "{}\n{}\n\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\nhost;x-amz-content-sha256;x-amz-date\n{}",
+        method, url.path(), host, payload_hash, amz_date, payload_hash
+    );
+    let scope = format!("{}/{}/s3/aws4_request", date, region);
+    let string_to_sign = format!("AWS4-HMAC-SHA256\n{}\n{}\n{}", amz_date, scope, sha256_hex(canonical_request.as_bytes()));
+    let signing_key = ["s3", "aws4_request"]
+        .iter()
+        .fold(hmac(hmac(format!("AWS4{}", secret_key).as_bytes(), date).as_slice(), region), |key, part| hmac(&key, part));
+    let signature = hex::encode(hmac(&signing_key, &string_to_sign));
+    let authorization = format!(
+        "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders=host;x-amz-content-sha256;x-amz-date, Signature={}",
+        access_key_id, scope, signature
+    );
+    Ok(client
+        .request(method, url)
+        .header("x-amz-date", amz_date)
+        .header("x-amz-content-sha256", payload_hash)
+        .header("Authorization", authorization)
+        .body(body))
+}
+
+fn build_request(backend: &SyncBackend, method: reqwest::Method, body: Vec<u8>) -> Result<reqwest::RequestBuilder, String> {
+    let client = http_client::client()?;
+    let credential = secrets::get(CREDENTIAL_KEY)?.unwrap_or_default();
+    match backend {
+        SyncBackend::Platform { base_url } => Ok(client
+            .request(method, format!("{}/api/v1/providers/me/settings-sync", base_url.trim_end_matches('/')))
+            .bearer_auth(credential)
+            .header("Content-Type", "application/json")
+            .body(body)),
+        SyncBackend::WebDav { url, username } => Ok(client
+            .request(method, url)
+            .basic_auth(username, Some(credential))
+            .header("Content-Type", "application/json")
+            .body(body)),
+        SyncBackend::S3 { endpoint, bucket, region, access_key_id, object_key } => {
+            s3_request(&client, method, endpoint, bucket, region, access_key_id, &credential, object_key, body)
+        }
+    }
+}
+
+async fn fetch_remote(backend: &SyncBackend, passphrase: &str) -> Result<Option<SyncDocument>, String> {
+    let response = build_request(backend, reqwest::Method::GET, Vec::new())?
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch synced settings: {}", e))?;
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    let envelope: EncryptedEnvelope = response
+        .error_for_status()
+        .map_err(|e| format!("Sync backend rejected the request: {}", e))?
+        .json()
+        .await
+        .map_err(|e| format!("Synced settings are not a valid envelope: {}", e))?;
+    let plaintext = crypto::decrypt(&envelope, passphrase)?;
+    serde_json::from_slice(&plaintext)
+        .map(Some)
+        .map_err(|e| format!("Synced settings are malformed: {}", e))
+}
+
+async fn push_remote(backend: &SyncBackend, passphrase: &str, doc: &SyncDocument) -> Result<(), String> {
+    let plaintext = serde_json::to_vec(doc).map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    let envelope = crypto::encrypt(&plaintext, passphrase)?;
+    let body = serde_json::to_vec(&envelope).map_err(|e| format!("Failed to serialize envelope: {}", e))?;
+    build_request(backend, reqwest::Method::PUT, body)?
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map(|_| ())
+        .map_err(|e| format!("Failed to upload synced settings: {}", e))
+}
+
+// --- Sync logic ---
+
+async fn apply_document(app_handle: &AppHandle, doc: &SyncDocument) -> Result<(), String> {
+    let state = app_handle.state::<AppSettingsState>();
+    let mut settings = state.get();
+    settings.privacy = doc.preferences.privacy.clone();
+    settings.crash_reporting_enabled = doc.preferences.crash_reporting_enabled;
+    settings.known_issues_url = doc.preferences.known_issues_url.clone();
+    app_settings::update_app_settings(app_handle.clone(), state, settings).await?;
+    if let Some(provider) = &doc.provider {
+        update_provider_settings(app_handle.clone(), provider.clone()).await?;
+    }
+    Ok(())
+}
+
+async fn push_and_record(app_handle: &AppHandle, backend: &SyncBackend, passphrase: &str, doc: SyncDocument) -> Result<(), String> {
+    push_remote(backend, passphrase, &doc).await?;
+    let hash = content_hash(&doc);
+    app_handle.state::<SyncState>().update(|data| {
+        data.last_remote_updated_at = Some(doc.updated_at);
+        data.last_content_hash = Some(hash);
+        data.last_synced_at = Some(get_timestamp());
+    });
+    Ok(())
+}
+
+fn sync_credentials(app_handle: &AppHandle) -> Result<(SyncBackend, String), String> {
+    let config = app_settings::current(app_handle).sync;
+    let backend = config.backend.ok_or_else(|| "No sync backend is configured.".to_string())?;
+    let passphrase = secrets::get(PASSPHRASE_KEY)?.ok_or_else(|| "No sync passphrase is set.".to_string())?;
+    Ok((backend, passphrase))
+}
+
+async fn sync_now(app_handle: &AppHandle) -> Result<SyncOutcome, String> {
+    let (backend, passphrase) = sync_credentials(app_handle)?;
+    let local = local_document(app_handle).await;
+    let remote = fetch_remote(&backend, &passphrase).await?;
+    let data = app_handle.state::<SyncState>().data.lock().unwrap().clone();
+
+    let local_changed = data.last_content_hash.as_deref() != Some(content_hash(&local).as_str());
+    let Some(remote) = remote else {
+        push_and_record(app_handle, &backend, &passphrase, local).await?;
+        return Ok(SyncOutcome::Pushed);
+    };
+    let remote_changed = data.last_remote_updated_at != Some(remote.updated_at);
+
+    match (local_changed, remote_changed) {
+        (false, false) => Ok(SyncOutcome::UpToDate),
+        (true, false) => {
+            push_and_record(app_handle, &backend, &passphrase, local).await?;
+            Ok(SyncOutcome::Pushed)
+        }
+        (false, true) => {
+            apply_document(app_handle, &remote).await?;
+            let hash = content_hash(&remote);
+            app_handle.state::<SyncState>().update(|d| {
+                d.last_remote_updated_at = Some(remote.updated_at);
+                d.last_content_hash = Some(hash);
+                d.last_synced_at = Some(get_timestamp());
+            });
+            Ok(SyncOutcome::Pulled)
+        }
+        (true, true) => {
+            // Last writer wins for everything except pricing.
+            let local_newer = data.local_updated_at > remote.updated_at;
+            let newer = if local_newer { &local } else { &remote };
+            let mut merged = SyncDocument {
+                updated_at: now_millis(),
+                device: device_name(),
+                preferences: newer.preferences.clone(),
+                provider: newer.provider.clone().or_else(|| local.provider.clone()).or_else(|| remote.provider.clone()),
+            };
+
+            if let (Some(local_provider), Some(remote_provider)) = (&local.provider, &remote.provider) {
+                let (local_pricing, remote_pricing) = (pricing_of(local_provider), pricing_of(remote_provider));
+                if local_pricing != remote_pricing {
+                    // Keep local pricing in effect until the user decides.
+                    let conflict = PricingConflict {
+                        local: local_pricing.clone(),
+                        remote: remote_pricing,
+                        remote_device: remote.device.clone(),
+                        remote_updated_at: remote.updated_at,
+                    };
+                    merged.provider = merged.provider.map(|p| with_pricing(&p, &local_pricing));
+                    apply_document(app_handle, &merged).await?;
+                    app_handle.state::<SyncState>().update(|d| d.pending_conflict = Some(conflict.clone()));
+                    emit_log_entry(app_handle, "status", "Settings sync found conflicting pricing rules; waiting for manual resolution.".to_string());
+                    return Ok(SyncOutcome::Conflict(conflict));
+                }
+            }
+
+            apply_document(app_handle, &merged).await?;
+            push_and_record(app_handle, &backend, &passphrase, merged).await?;
+            Ok(SyncOutcome::Merged)
+        }
+    }
+}
+
+pub fn spawn_sync_task(app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let config = app_settings::current(&app_handle).sync;
+            let blocked = app_handle.state::<SyncState>().data.lock().unwrap().pending_conflict.is_some();
+            if config.enabled && !blocked {
+                if let Err(e) = sync_now(&app_handle).await {
+                    emit_log_entry(&app_handle, "error", format!("Settings sync failed: {}", e));
+                }
+            }
+            if !shutdown::sleep(SYNC_INTERVAL).await {
+                break;
+            }
+        }
+    });
+}
+
+#[tauri::command]
+pub async fn get_sync_status(app_handle: AppHandle, state: State<'_, SyncState>) -> Result<SyncStatus, String> {
+    let data = state.data.lock().unwrap().clone();
+    Ok(SyncStatus {
+        config: app_settings::current(&app_handle).sync,
+        has_passphrase: secrets::get(PASSPHRASE_KEY)?.is_some(),
+        last_synced_at: data.last_synced_at,
+        pending_conflict: data.pending_conflict,
+    })
+}
+
+#[tauri::command]
+pub async fn configure_sync(
+    app_handle: AppHandle,
+    settings_state: State<'_, AppSettingsState>,
+    config: SyncConfig,
+    passphrase: Option<String>,
+    credential: Option<String>,
+) -> Result<(), String> {
+    if let Some(passphrase) = passphrase {
+        secrets::set(PASSPHRASE_KEY, &passphrase)?;
+        // A new passphrase means the remote copy must be rewritten.
+        app_handle.state::<SyncState>().update(|d| d.last_content_hash = None);
+    }
+    if let Some(credential) = credential {
+        secrets::set(CREDENTIAL_KEY, &credential)?;
+    }
+    if config.backend.is_none() {
+        secrets::delete(PASSPHRASE_KEY)?;
+        secrets::delete(CREDENTIAL_KEY)?;
+    }
+    let mut settings = settings_state.get();
+    settings.sync = config;
+    app_settings::update_app_settings(app_handle, settings_state, settings).await?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn sync_settings_now(app_handle: AppHandle) -> Result<SyncOutcome, String> {
+    if app_handle.state::<SyncState>().data.lock().unwrap().pending_conflict.is_some() {
+        return Err("Resolve the pending pricing conflict before syncing again.".to_string());
+    }
+    let outcome = sync_now(&app_handle).await?;
+    emit_log_entry(&app_handle, "status", format!("Settings sync finished: {:?}", outcome));
+    Ok(outcome)
+}
+
+#[tauri::command]
+pub async fn resolve_sync_conflict(app_handle: AppHandle, resolution: PricingResolution) -> Result<SyncOutcome, String> {
+    let conflict = app_handle
+        .state::<SyncState>()
+        .data
+        .lock()
+        .unwrap()
+        .pending_conflict
+        .clone()
+        .ok_or_else(|| "There is no pending sync conflict.".to_string())?;
+    let pricing = match resolution {
+        PricingResolution::Local => conflict.local,
+        PricingResolution::Remote => conflict.remote,
+        PricingResolution::Custom { rules } => rules,
+    };
+
+    let (backend, passphrase) = sync_credentials(&app_handle)?;
+    let current = get_provider_settings(app_handle.clone()).await?;
+    update_provider_settings(app_handle.clone(), with_pricing(&current, &pricing)).await?;
+
+    app_handle.state::<SyncState>().update(|d| d.pending_conflict = None);
+    let doc = local_document(&app_handle).await;
+    push_and_record(&app_handle, &backend, &passphrase, doc).await?;
+    emit_log_entry(&app_handle, "status", "Pricing conflict resolved and settings synced.".to_string());
+    Ok(SyncOutcome::Pushed)
+}
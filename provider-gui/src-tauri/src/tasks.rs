@@ -0,0 +1,380 @@
+// Background tasks for commands that take longer than a UI should wait on.
+//
+// A long command (bundles, backups, exports, benchmarks, network tests, image prefetches)
+// submits a `TaskRequest` and returns the task ID straight away. The work runs in the background
+// and reports through its `TaskContext`; every change to a task, including the final result or
+// error, is emitted as the whole `TaskInfo` on `task_progress`. Cancelling sets a flag the work
+// sees at its next progress report and aborts it if it is waiting on something, so no task
+// outlives its cancellation.
+//
+// Tasks are kept in the local store when they start and when they finish, so one cut short by
+// the app closing is found again on the next start: tasks that only write files or pull images
+// are run again from the start, and the rest (measurements, which mean little later and load
+// the GPU without anyone asking) are reported as interrupted. A task is started at most
+// `MAX_ATTEMPTS` times, so one that takes the app down with it isn't retried forever, and a task
+// that panics fails like any other. Finished tasks are kept for a while so a reloaded frontend
+// can pick up the results.
+//
+// Daemon updates aren't tasks: the daemon is the sidecar bundled with the app and is only ever
+// replaced together with it, so the app never has a daemon update of its own in flight to resume.
+// Should the app learn to update the daemon separately, that belongs here as a resumable request.
+
+use futures_util::future::FutureExt;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::panic::AssertUnwindSafe;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Manager, State};
+
+use crate::crypto::EncryptedEnvelope;
+use crate::storage::{unix_now, Storage};
+use crate::telemetry_export::{self, TelemetryFormat};
+use crate::{backup, diagnostics, emit_log_entry, export, get_timestamp, prefetch};
+
+const MAX_FINISHED_TASKS: usize = 50;
+const MAX_ATTEMPTS: u32 = 3;
+
+static TASK_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TaskRequest {
+    DiagnosticBundle {
+        path: String,
+        include_session_trace: bool,
+        #[serde(default)]
+        include_notes: bool,
+    },
+    // Secrets are sealed when the backup is requested, so the passphrase is never stored here.
+    Backup {
+        path: String,
+        #[serde(default)]
+        secrets: Option<EncryptedEnvelope>,
+    },
+    LedgerExport { path: String, since: Option<i64> },
+    ImportedEarningsExport { path: String },
+    HourlyUsageExport { path: String, from: Option<i64>, to: Option<i64> },
+    InventoryExport { path: String },
+    TelemetryExport { path: String, format: TelemetryFormat, from: Option<i64>, to: Option<i64> },
+    NetworkTest,
+    Benchmark { gpu_id: String },
+    ImagePrefetch { images: Vec<String> },
+}
+
+impl TaskRequest {
+    fn kind(&self) -> &'static str {
+        match self {
+            TaskRequest::DiagnosticBundle { .. } => "diagnostic_bundle",
+            TaskRequest::Backup { .. } => "backup",
+            TaskRequest::LedgerExport { .. }
+            | TaskRequest::ImportedEarningsExport { .. }
+            | TaskRequest::HourlyUsageExport { .. }
+            | TaskRequest::InventoryExport { .. }
+            | TaskRequest::TelemetryExport { .. } => "export",
+            TaskRequest::NetworkTest => "network_test",
+            TaskRequest::Benchmark { .. } => "benchmark",
+            TaskRequest::ImagePrefetch { .. } => "image_prefetch",
+        }
+    }
+
+    // Whether running it again after a restart gives what the user asked for.
+    fn resumable(&self) -> bool {
+        !matches!(self, TaskRequest::NetworkTest | TaskRequest::Benchmark { .. })
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskStatus {
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+    Interrupted, // The app closed while it ran and it wasn't resumed
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TaskInfo {
+    pub id: String,
+    pub kind: String, // e.g. "backup", "diagnostic_bundle", "benchmark"
+    pub request: TaskRequest,
+    pub status: TaskStatus,
+    pub progress_percent: Option<f32>, // None while the amount of work is unknown
+    pub message: Option<String>,
+    pub started_at: String,
+    pub finished_at: Option<String>,
+    pub result: Option<Value>,
+    pub error: Option<String>,
+    #[serde(default)]
+    pub resumed: bool, // Started again after the app restarted
+    #[serde(default)]
+    pub attempts: u32, // Times it was started, counting resumes
+}
+
+struct TaskEntry {
+    info: TaskInfo,
+    cancelled: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+pub struct TaskRegistry {
+    tasks: Mutex<Vec<TaskEntry>>,
+}
+
+impl TaskRegistry {
+    pub fn new() -> Self {
+        TaskRegistry { tasks: Mutex::new(Vec::new()) }
+    }
+
+    // Applies `f` to a running task and emits the result; finished tasks don't change again.
+    fn update(&self, app_handle: &AppHandle, id: &str, f: impl FnOnce(&mut TaskInfo)) {
+        let (info, pruned) = {
+            let mut tasks = self.tasks.lock().unwrap();
+            let Some(entry) = tasks.iter_mut().find(|t| t.info.id == id && t.info.status == TaskStatus::Running) else {
+                return;
+            };
+            f(&mut entry.info);
+            let info = entry.info.clone();
+            let mut pruned = Vec::new();
+            if info.status != TaskStatus::Running {
+                entry.handle = None;
+                pruned = prune(&mut tasks);
+            }
+            (info, pruned)
+        };
+        // Progress isn't worth a write; only the start and the outcome need to survive a restart.
+        if info.status != TaskStatus::Running {
+            persist(app_handle, &info, &pruned);
+        }
+        if let Err(e) = app_handle.emit_all("task_progress", info) {
+            eprintln!("Failed to emit task_progress event: {}", e);
+        }
+    }
+}
+
+// Drops the oldest finished tasks beyond the limit and returns their IDs.
+fn prune(tasks: &mut Vec<TaskEntry>) -> Vec<String> {
+    let finished = tasks.iter().filter(|t| t.info.status != TaskStatus::Running).count();
+    let mut excess = finished.saturating_sub(MAX_FINISHED_TASKS);
+    let mut pruned = Vec::new();
+    tasks.retain(|t| {
+        let drop = excess > 0 && t.info.status != TaskStatus::Running;
+        if drop {
+            excess -= 1;
+            pruned.push(t.info.id.clone());
+        }
+        !drop
+    });
+    pruned
+}
+
+// Tasks still work without local storage; they just don't survive a restart.
+fn persist(app_handle: &AppHandle, info: &TaskInfo, pruned: &[String]) {
+    let Some(storage) = app_handle.try_state::<Storage>() else {
+        return;
+    };
+    if let Err(e) = storage.upsert_task(info) {
+        eprintln!("{}", e);
+    }
+    for id in pruned {
+        if let Err(e) = storage.delete_task(id) {
+            eprintln!("{}", e);
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct TaskContext {
+    app_handle: AppHandle,
+    id: String,
+    cancelled: Arc<AtomicBool>,
+    resumed: bool,
+}
+
+impl TaskContext {
+    // Whether this run picks the task up after a restart, when whatever allowed it to start
+    // may no longer hold.
+    pub fn resumed(&self) -> bool {
+        self.resumed
+    }
+
+    // Reports progress; fails once the task is cancelled, so work can stop with `?`.
+    pub fn progress(&self, percent: Option<f32>, message: impl Into<String>) -> Result<(), String> {
+        if self.cancelled.load(Ordering::Relaxed) {
+            return Err("Cancelled.".to_string());
+        }
+        let message = message.into();
+        self.app_handle.state::<TaskRegistry>().update(&self.app_handle, &self.id, |info| {
+            info.progress_percent = percent.map(|p| p.clamp(0.0, 100.0));
+            info.message = Some(message);
+        });
+        Ok(())
+    }
+}
+
+fn to_value<T: Serialize>(result: Result<T, String>) -> Result<Value, String> {
+    result.and_then(|value| serde_json::to_value(value).map_err(|e| format!("Failed to serialize task result: {}", e)))
+}
+
+async fn execute(app_handle: AppHandle, task: TaskContext, request: TaskRequest) -> Result<Value, String> {
+    match request {
+        TaskRequest::DiagnosticBundle { path, include_session_trace, include_notes } => {
+            to_value(diagnostics::write_bundle(app_handle, task, path, include_session_trace, include_notes).await)
+        }
+        TaskRequest::Backup { path, secrets } => to_value(backup::write_backup(app_handle, task, path, secrets).await),
+        TaskRequest::LedgerExport { path, since } => to_value(export::ledger_csv(&app_handle, &task, &path, since)),
+        TaskRequest::ImportedEarningsExport { path } => to_value(export::imported_earnings_csv(&app_handle, &task, &path)),
+        TaskRequest::HourlyUsageExport { path, from, to } => to_value(export::hourly_usage_csv(&app_handle, &task, &path, from, to)),
+        TaskRequest::InventoryExport { path } => to_value(export::inventory_csv(&app_handle, &task, &path).await),
+        TaskRequest::TelemetryExport { path, format, from, to } => to_value(telemetry_export::write_telemetry(&app_handle, &task, &path, format, from, to)),
+        TaskRequest::NetworkTest => to_value(crate::measure_network(&app_handle, &task).await),
+        TaskRequest::Benchmark { gpu_id } => to_value(crate::benchmark_gpu(&app_handle, &task, &gpu_id).await),
+        TaskRequest::ImagePrefetch { images } => to_value(prefetch::pull_images(&app_handle, &task, images).await),
+    }
+}
+
+fn launch(app_handle: &AppHandle, mut info: TaskInfo) {
+    info.attempts += 1;
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let registry = app_handle.state::<TaskRegistry>();
+    registry.tasks.lock().unwrap().push(TaskEntry { info: info.clone(), cancelled: cancelled.clone(), handle: None });
+    persist(app_handle, &info, &[]);
+    if let Err(e) = app_handle.emit_all("task_progress", &info) {
+        eprintln!("Failed to emit task_progress event: {}", e);
+    }
+
+    let context = TaskContext { app_handle: app_handle.clone(), id: info.id.clone(), cancelled: cancelled.clone(), resumed: info.resumed };
+    let handle_for_task = app_handle.clone();
+    let task_id = info.id.clone();
+    let request = info.request;
+    let handle = tauri::async_runtime::spawn(async move {
+        let outcome = match AssertUnwindSafe(execute(handle_for_task.clone(), context, request)).catch_unwind().await {
+            Ok(outcome) => outcome,
+            Err(panic) => {
+                let message = panic
+                    .downcast_ref::<&str>()
+                    .map(|s| s.to_string())
+                    .or_else(|| panic.downcast_ref::<String>().cloned())
+                    .unwrap_or_else(|| "unknown panic".to_string());
+                Err(format!("The task crashed: {}", message))
+            }
+        };
+        handle_for_task.state::<TaskRegistry>().update(&handle_for_task, &task_id, |info| {
+            info.finished_at = Some(get_timestamp());
+            match outcome {
+                _ if cancelled.load(Ordering::Relaxed) => info.status = TaskStatus::Cancelled,
+                Ok(value) => {
+                    info.status = TaskStatus::Completed;
+                    info.progress_percent = Some(100.0);
+                    info.result = Some(value);
+                }
+                Err(e) => {
+                    info.status = TaskStatus::Failed;
+                    info.error = Some(e);
+                }
+            }
+        });
+    });
+    // Only kept while the task runs; it may already have finished.
+    if let Some(entry) = registry.tasks.lock().unwrap().iter_mut().find(|t| t.info.id == info.id && t.info.status == TaskStatus::Running) {
+        entry.handle = Some(handle);
+    }
+}
+
+pub fn submit(app_handle: &AppHandle, request: TaskRequest) -> String {
+    // The start time keeps IDs unique across restarts.
+    let id = format!("task-{}-{}", unix_now(), TASK_COUNTER.fetch_add(1, Ordering::Relaxed) + 1);
+    launch(
+        app_handle,
+        TaskInfo {
+            id: id.clone(),
+            kind: request.kind().to_string(),
+            request,
+            status: TaskStatus::Running,
+            progress_percent: Some(0.0),
+            message: None,
+            started_at: get_timestamp(),
+            finished_at: None,
+            result: None,
+            error: None,
+            resumed: false,
+            attempts: 0,
+        },
+    );
+    id
+}
+
+// Picks up the tasks stored by the previous run; called once local storage is available.
+pub fn restore(app_handle: &AppHandle) {
+    let Some(storage) = app_handle.try_state::<Storage>() else {
+        return;
+    };
+    let stored = match storage.tasks() {
+        Ok(stored) => stored,
+        Err(e) => {
+            emit_log_entry(app_handle, "error", e);
+            return;
+        }
+    };
+    for mut info in stored {
+        if info.status != TaskStatus::Running {
+            app_handle.state::<TaskRegistry>().tasks.lock().unwrap().push(TaskEntry { info, cancelled: Arc::default(), handle: None });
+            continue;
+        }
+        if info.request.resumable() && info.attempts >= MAX_ATTEMPTS {
+            emit_log_entry(app_handle, "error", format!("Giving up on {} task {} after {} attempts.", info.kind, info.id, info.attempts));
+            info.status = TaskStatus::Failed;
+            info.finished_at = Some(get_timestamp());
+            info.error = Some(format!("The app closed before this task finished, {} times.", info.attempts));
+            persist(app_handle, &info, &[]);
+            app_handle.state::<TaskRegistry>().tasks.lock().unwrap().push(TaskEntry { info, cancelled: Arc::default(), handle: None });
+        } else if info.request.resumable() {
+            emit_log_entry(app_handle, "status", format!("Resuming {} task {} after restart.", info.kind, info.id));
+            info.progress_percent = Some(0.0);
+            info.message = Some("Resumed after restart".to_string());
+            info.resumed = true;
+            launch(app_handle, info);
+        } else {
+            emit_log_entry(app_handle, "error", format!("The {} task {} was interrupted when the app closed.", info.kind, info.id));
+            info.status = TaskStatus::Interrupted;
+            info.finished_at = Some(get_timestamp());
+            info.error = Some("The app closed before this task finished.".to_string());
+            persist(app_handle, &info, &[]);
+            app_handle.state::<TaskRegistry>().tasks.lock().unwrap().push(TaskEntry { info, cancelled: Arc::default(), handle: None });
+        }
+    }
+    let pruned = prune(&mut app_handle.state::<TaskRegistry>().tasks.lock().unwrap());
+    for id in pruned {
+        if let Err(e) = storage.delete_task(&id) {
+            eprintln!("{}", e);
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn cancel_task(app_handle: AppHandle, registry: State<'_, TaskRegistry>, id: String) -> Result<(), String> {
+    {
+        let mut tasks = registry.tasks.lock().unwrap();
+        let entry = tasks.iter_mut().find(|t| t.info.id == id).ok_or_else(|| format!("No task with ID '{}'.", id))?;
+        if entry.info.status != TaskStatus::Running {
+            return Err(format!("Task '{}' has already finished.", id));
+        }
+        entry.cancelled.store(true, Ordering::Relaxed);
+        if let Some(handle) = entry.handle.take() {
+            handle.abort();
+        }
+    }
+    registry.update(&app_handle, &id, |info| {
+        info.status = TaskStatus::Cancelled;
+        info.finished_at = Some(get_timestamp());
+    });
+    Ok(())
+}
+
+// Running and recently finished tasks, oldest first.
+#[tauri::command]
+pub async fn list_tasks(registry: State<'_, TaskRegistry>) -> Result<Vec<TaskInfo>, String> {
+    Ok(registry.tasks.lock().unwrap().iter().map(|t| t.info.clone()).collect())
+}
@@ -0,0 +1,211 @@
+// Raw telemetry samples for analysis outside the app, e.g. with pandas or polars.
+//
+// Unlike the spreadsheet exports (see `export`), these are meant for programs: CSV is written
+// with RFC 3339 UTC timestamps, '.' decimals and no byte order mark whatever the locale, and
+// Parquet (Snappy-compressed) and Arrow IPC files carry typed, nullable columns with timestamps
+// in UTC seconds and rates as 18-place decimals. Samples are read in pages and streamed to disk
+// in time order, so an export of months of history never sits in memory at once. Runs as a
+// background task (see `tasks`); a cancelled or failed export removes its partial file.
+
+use arrow::array::{ArrayRef, BooleanArray, Decimal128Array, Int32Array, Int64Array, StringArray, TimestampSecondArray};
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use arrow::ipc::writer::FileWriter;
+use arrow::record_batch::RecordBatch;
+use chrono::{SecondsFormat, TimeZone, Utc};
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::BufWriter;
+use std::sync::Arc;
+use tauri::{AppHandle, Manager};
+
+use crate::emit_log_entry;
+use crate::money::MAX_DECIMAL_PLACES;
+use crate::storage::{unix_now, Storage, TelemetryRow};
+use crate::tasks::{self, TaskContext, TaskRequest};
+use crate::units::DgpuPerHour;
+
+const PAGE_SIZE: usize = 10_000;
+const COLUMNS: [&str; 10] = [
+    "recorded_at",
+    "gpu_id",
+    "utilization_gpu_percent",
+    "temperature_c",
+    "power_draw_w",
+    "vram_free_mb",
+    "is_available_for_rent",
+    "hourly_rate_dgpu",
+    "ecc_corrected_errors",
+    "memory_clock_mhz",
+];
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TelemetryFormat {
+    Csv,
+    Parquet,
+    ArrowIpc,
+}
+
+// Unix seconds; all recorded history up to now when unset.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct TelemetryRange {
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct TelemetryExportSummary {
+    pub path: String,
+    pub rows: usize,
+    pub format: TelemetryFormat,
+}
+
+enum Sink {
+    Csv(csv::Writer<BufWriter<File>>),
+    ArrowIpc(FileWriter<BufWriter<File>>),
+    Parquet(ArrowWriter<BufWriter<File>>),
+}
+
+fn schema() -> SchemaRef {
+    let nullable = |name: &str, data_type: DataType| Field::new(name, data_type, true);
+    Arc::new(Schema::new(vec![
+        Field::new(COLUMNS[0], DataType::Timestamp(TimeUnit::Second, Some("UTC".into())), false),
+        Field::new(COLUMNS[1], DataType::Utf8, false),
+        nullable(COLUMNS[2], DataType::Int32),
+        nullable(COLUMNS[3], DataType::Int32),
+        nullable(COLUMNS[4], DataType::Int32),
+        Field::new(COLUMNS[5], DataType::Int64, false),
+        Field::new(COLUMNS[6], DataType::Boolean, false),
+        nullable(COLUMNS[7], DataType::Decimal128(38, MAX_DECIMAL_PLACES as i8)),
+        nullable(COLUMNS[8], DataType::Int64),
+        nullable(COLUMNS[9], DataType::Int32),
+    ]))
+}
+
+// The rate as an integer count of 10^-18 DGPU/hr, the Decimal128 column's representation.
+fn rate_mantissa(rate: DgpuPerHour) -> i128 {
+    let mut rate = rate.as_decimal();
+    rate.rescale(MAX_DECIMAL_PLACES);
+    rate.mantissa()
+}
+
+fn batch(schema: &SchemaRef, rows: &[TelemetryRow]) -> Result<RecordBatch, String> {
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(TimestampSecondArray::from(rows.iter().map(|r| r.recorded_at).collect::<Vec<_>>()).with_timezone("UTC")),
+        Arc::new(StringArray::from(rows.iter().map(|r| r.gpu_id.as_str()).collect::<Vec<_>>())),
+        Arc::new(Int32Array::from(rows.iter().map(|r| r.utilization_gpu_percent).collect::<Vec<_>>())),
+        Arc::new(Int32Array::from(rows.iter().map(|r| r.temperature_c).collect::<Vec<_>>())),
+        Arc::new(Int32Array::from(rows.iter().map(|r| r.power_draw_w).collect::<Vec<_>>())),
+        Arc::new(Int64Array::from(rows.iter().map(|r| r.vram_free_mb).collect::<Vec<_>>())),
+        Arc::new(BooleanArray::from(rows.iter().map(|r| r.is_available_for_rent).collect::<Vec<_>>())),
+        Arc::new(
+            Decimal128Array::from(rows.iter().map(|r| r.hourly_rate_dgpu.map(rate_mantissa)).collect::<Vec<_>>())
+                .with_precision_and_scale(38, MAX_DECIMAL_PLACES as i8)
+                .map_err(|e| format!("Failed to build telemetry batch: {}", e))?,
+        ),
+        Arc::new(Int64Array::from(rows.iter().map(|r| r.ecc_corrected_errors).collect::<Vec<_>>())),
+        Arc::new(Int32Array::from(rows.iter().map(|r| r.memory_clock_mhz).collect::<Vec<_>>())),
+    ];
+    RecordBatch::try_new(schema.clone(), columns).map_err(|e| format!("Failed to build telemetry batch: {}", e))
+}
+
+fn csv_record(row: &TelemetryRow) -> Vec<String> {
+    let opt = |v: Option<String>| v.unwrap_or_default();
+    vec![
+        Utc.timestamp_opt(row.recorded_at, 0).single().map_or_else(|| row.recorded_at.to_string(), |t| t.to_rfc3339_opts(SecondsFormat::Secs, true)),
+        row.gpu_id.clone(),
+        opt(row.utilization_gpu_percent.map(|v| v.to_string())),
+        opt(row.temperature_c.map(|v| v.to_string())),
+        opt(row.power_draw_w.map(|v| v.to_string())),
+        row.vram_free_mb.to_string(),
+        row.is_available_for_rent.to_string(),
+        opt(row.hourly_rate_dgpu.map(|v| v.as_decimal().to_string())),
+        opt(row.ecc_corrected_errors.map(|v| v.to_string())),
+        opt(row.memory_clock_mhz.map(|v| v.to_string())),
+    ]
+}
+
+impl Sink {
+    fn create(path: &str, format: TelemetryFormat, schema: &SchemaRef) -> Result<Sink, String> {
+        let file = BufWriter::new(File::create(path).map_err(|e| format!("Failed to create {}: {}", path, e))?);
+        let failed = |e: &dyn std::fmt::Display| format!("Failed to write {}: {}", path, e);
+        Ok(match format {
+            TelemetryFormat::Csv => {
+                let mut writer = csv::Writer::from_writer(file);
+                writer.write_record(COLUMNS).map_err(|e| failed(&e))?;
+                Sink::Csv(writer)
+            }
+            TelemetryFormat::ArrowIpc => Sink::ArrowIpc(FileWriter::try_new(file, schema).map_err(|e| failed(&e))?),
+            TelemetryFormat::Parquet => {
+                let properties = WriterProperties::builder().set_compression(Compression::SNAPPY).build();
+                Sink::Parquet(ArrowWriter::try_new(file, schema.clone(), Some(properties)).map_err(|e| failed(&e))?)
+            }
+        })
+    }
+
+    fn write(&mut self, schema: &SchemaRef, rows: &[TelemetryRow]) -> Result<(), String> {
+        match self {
+            Sink::Csv(writer) => rows.iter().try_for_each(|row| writer.write_record(csv_record(row)).map_err(|e| e.to_string())),
+            Sink::ArrowIpc(writer) => writer.write(&batch(schema, rows)?).map_err(|e| e.to_string()),
+            Sink::Parquet(writer) => writer.write(&batch(schema, rows)?).map_err(|e| e.to_string()),
+        }
+    }
+
+    fn finish(self) -> Result<(), String> {
+        match self {
+            Sink::Csv(mut writer) => writer.flush().map_err(|e| e.to_string()),
+            Sink::ArrowIpc(mut writer) => writer.finish().map_err(|e| e.to_string()),
+            Sink::Parquet(writer) => writer.close().map(|_| ()).map_err(|e| e.to_string()),
+        }
+    }
+}
+
+fn stream(storage: &Storage, task: &TaskContext, path: &str, format: TelemetryFormat, from: i64, to: i64) -> Result<usize, String> {
+    let total = storage.telemetry_sample_count(from, to)?.max(0) as usize;
+    let schema = schema();
+    let mut sink = Sink::create(path, format, &schema)?;
+    let mut after = (i64::MIN, i64::MIN);
+    let mut written = 0;
+    loop {
+        task.progress(Some(100.0 * written as f32 / total.max(1) as f32), format!("Writing sample {} of {}", written + 1, total))?;
+        let page = storage.telemetry_page(from, to, after, PAGE_SIZE)?;
+        let Some(last) = page.last() else { break };
+        after = (last.recorded_at, last.row_id);
+        sink.write(&schema, &page).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+        written += page.len();
+    }
+    sink.finish().map_err(|e| format!("Failed to write {}: {}", path, e))?;
+    Ok(written)
+}
+
+pub fn write_telemetry(app_handle: &AppHandle, task: &TaskContext, path: &str, format: TelemetryFormat, from: Option<i64>, to: Option<i64>) -> Result<TelemetryExportSummary, String> {
+    let storage = app_handle.try_state::<Storage>().ok_or_else(|| "Local storage is unavailable.".to_string())?;
+    let result = stream(&storage, task, path, format, from.unwrap_or(0), to.unwrap_or_else(|| unix_now() + 1));
+    match result {
+        Ok(rows) => {
+            emit_log_entry(app_handle, "status", format!("Exported {} telemetry samples to {}.", rows, path));
+            Ok(TelemetryExportSummary { path: path.to_string(), rows, format })
+        }
+        Err(e) => {
+            // A truncated Parquet or Arrow file can't be read at all; don't leave one behind.
+            let _ = std::fs::remove_file(path);
+            Err(e)
+        }
+    }
+}
+
+// Returns the task ID; the `TelemetryExportSummary` is the task's result.
+#[tauri::command]
+pub async fn export_telemetry(app_handle: AppHandle, path: String, range: Option<TelemetryRange>, format: TelemetryFormat) -> Result<String, String> {
+    let range = range.unwrap_or_default();
+    if let (Some(from), Some(to)) = (range.from, range.to) {
+        if from >= to {
+            return Err("The export range ends before it starts.".to_string());
+        }
+    }
+    Ok(tasks::submit(&app_handle, TaskRequest::TelemetryExport { path, format, from: range.from, to: range.to }))
+}
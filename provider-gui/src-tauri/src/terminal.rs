@@ -0,0 +1,178 @@
+// PTY-backed terminal sessions for running diagnostics on the rig from inside the app.
+//
+// Each session spawns the user's shell (or `ssh -t <host>` for a remote rig) inside a pseudo
+// terminal. Output is streamed to the frontend as `terminal_output` events and input arrives
+// through `write_terminal`, so a terminal emulator in the webview can drive it directly.
+
+use portable_pty::{native_pty_system, Child, ChildKiller, CommandBuilder, MasterPty, PtySize};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use tauri::{AppHandle, Manager, State};
+
+use crate::emit_log_entry;
+
+struct TerminalSession {
+    master: Box<dyn MasterPty + Send>,
+    writer: Box<dyn Write + Send>,
+    child: Box<dyn Child + Send + Sync>,
+}
+
+pub struct TerminalState {
+    sessions: Mutex<HashMap<u32, TerminalSession>>,
+    next_id: AtomicU32,
+}
+
+impl TerminalState {
+    pub fn new() -> Self {
+        TerminalState {
+            sessions: Mutex::new(HashMap::new()),
+            next_id: AtomicU32::new(1),
+        }
+    }
+}
+
+#[derive(Serialize, Clone)]
+struct TerminalOutput {
+    session_id: u32,
+    data: String,
+}
+
+fn default_shell() -> String {
+    if cfg!(windows) {
+        "powershell.exe".to_string()
+    } else {
+        std::env::var("SHELL").unwrap_or_else(|_| "/bin/sh".to_string())
+    }
+}
+
+fn pty_size(rows: u16, cols: u16) -> PtySize {
+    PtySize {
+        rows,
+        cols,
+        pixel_width: 0,
+        pixel_height: 0,
+    }
+}
+
+// Forwards PTY output until the shell exits. Multi-byte UTF-8 sequences split across reads
+// are held back until complete so the frontend never sees replacement characters mid-stream.
+fn spawn_reader(app_handle: AppHandle, session_id: u32, mut reader: Box<dyn Read + Send>) {
+    thread::spawn(move || {
+        let mut buf = [0u8; 4096];
+        let mut pending: Vec<u8> = Vec::new();
+        loop {
+            match reader.read(&mut buf) {
+                Ok(0) | Err(_) => break,
+                Ok(n) => {
+                    pending.extend_from_slice(&buf[..n]);
+                    let valid_up_to = match std::str::from_utf8(&pending) {
+                        Ok(_) => pending.len(),
+                        Err(e) if e.error_len().is_none() => e.valid_up_to(),
+                        Err(_) => pending.len(), // Genuinely invalid bytes: emit lossily.
+                    };
+                    let data = String::from_utf8_lossy(&pending[..valid_up_to]).to_string();
+                    pending.drain(..valid_up_to);
+                    if !data.is_empty() {
+                        let _ = app_handle.emit_all("terminal_output", TerminalOutput { session_id, data });
+                    }
+                }
+            }
+        }
+
+        if let Some(state) = app_handle.try_state::<TerminalState>() {
+            state.sessions.lock().unwrap().remove(&session_id);
+        }
+        let _ = app_handle.emit_all("terminal_exited", session_id);
+        emit_log_entry(&app_handle, "status", format!("Terminal session {} closed.", session_id));
+    });
+}
+
+#[tauri::command]
+pub async fn open_terminal_session(
+    app_handle: AppHandle,
+    state: State<'_, TerminalState>,
+    rows: u16,
+    cols: u16,
+    ssh_host: Option<String>,
+) -> Result<u32, String> {
+    let pair = native_pty_system()
+        .openpty(pty_size(rows, cols))
+        .map_err(|e| format!("Failed to open PTY: {}", e))?;
+
+    let cmd = match &ssh_host {
+        Some(host) => {
+            let mut cmd = CommandBuilder::new("ssh");
+            cmd.args(["-t", host.as_str()]);
+            cmd
+        }
+        None => CommandBuilder::new(default_shell()),
+    };
+    let child = pair
+        .slave
+        .spawn_command(cmd)
+        .map_err(|e| format!("Failed to start terminal process: {}", e))?;
+    // The child holds its own handle to the slave side; ours would keep the PTY open after exit.
+    drop(pair.slave);
+
+    let reader = pair.master.try_clone_reader().map_err(|e| format!("Failed to read from PTY: {}", e))?;
+    let writer = pair.master.take_writer().map_err(|e| format!("Failed to write to PTY: {}", e))?;
+
+    let session_id = state.next_id.fetch_add(1, Ordering::Relaxed);
+    state.sessions.lock().unwrap().insert(
+        session_id,
+        TerminalSession {
+            master: pair.master,
+            writer,
+            child,
+        },
+    );
+    spawn_reader(app_handle.clone(), session_id, reader);
+
+    let target = ssh_host.map_or_else(|| "local shell".to_string(), |host| format!("ssh {}", host));
+    emit_log_entry(&app_handle, "status", format!("Terminal session {} opened ({}).", session_id, target));
+    Ok(session_id)
+}
+
+#[tauri::command]
+pub async fn write_terminal(state: State<'_, TerminalState>, session_id: u32, data: String) -> Result<(), String> {
+    let mut sessions = state.sessions.lock().unwrap();
+    let session = sessions
+        .get_mut(&session_id)
+        .ok_or_else(|| format!("Terminal session {} not found.", session_id))?;
+    session
+        .writer
+        .write_all(data.as_bytes())
+        .and_then(|_| session.writer.flush())
+        .map_err(|e| format!("Failed to write to terminal session {}: {}", session_id, e))
+}
+
+#[tauri::command]
+pub async fn resize_terminal(state: State<'_, TerminalState>, session_id: u32, rows: u16, cols: u16) -> Result<(), String> {
+    let sessions = state.sessions.lock().unwrap();
+    let session = sessions
+        .get(&session_id)
+        .ok_or_else(|| format!("Terminal session {} not found.", session_id))?;
+    session
+        .master
+        .resize(pty_size(rows, cols))
+        .map_err(|e| format!("Failed to resize terminal session {}: {}", session_id, e))
+}
+
+#[tauri::command]
+pub async fn close_terminal_session(state: State<'_, TerminalState>, session_id: u32) -> Result<(), String> {
+    let mut session = state
+        .sessions
+        .lock()
+        .unwrap()
+        .remove(&session_id)
+        .ok_or_else(|| format!("Terminal session {} not found.", session_id))?;
+    // The reader thread notices EOF and emits `terminal_exited`.
+    session
+        .child
+        .kill()
+        .map_err(|e| format!("Failed to terminate terminal session {}: {}", session_id, e))
+}
@@ -0,0 +1,199 @@
+// Data-channel transport preferences and diagnostics.
+//
+// Newer daemons can serve renter data channels over QUIC in addition to TLS over TCP. QUIC only
+// helps if renters can reach the rig over UDP and the path carries full-size QUIC packets, so
+// alongside the preference we test inbound UDP reachability (the platform sends a datagram to
+// an ephemeral port of ours, since the daemon owns the QUIC port while it runs) and probe the
+// largest UDP payload that survives a round trip to the platform's echo service. The report combines these with daemon support to show what renters will actually get.
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::net::UdpSocket;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, State};
+
+use crate::app_settings::{self, AppSettingsState};
+use crate::{api_client, emit_log_entry, invoke_daemon_cli_json_output};
+
+const UDP_PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+// QUIC requires paths to carry at least 1200-byte datagrams.
+const QUIC_MIN_DATAGRAM: usize = 1200;
+const MTU_PROBE_SIZES: &[usize] = &[1472, 1452, 1400, 1350, 1280, 1200];
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Transport {
+    Quic,
+    TcpTls,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct TransportPreferences {
+    // In order of preference; transports not listed are not offered.
+    pub enabled: Vec<Transport>,
+    pub quic_port: u16,
+}
+
+impl Default for TransportPreferences {
+    fn default() -> Self {
+        TransportPreferences { enabled: vec![Transport::Quic, Transport::TcpTls], quic_port: 4433 }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+struct DaemonTransports {
+    quic_supported: bool,
+}
+
+#[derive(Deserialize, Debug)]
+struct UdpEcho {
+    host: String,
+    port: u16,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct UdpDiagnostics {
+    pub inbound_reachable: bool,
+    pub max_datagram_bytes: Option<usize>, // Largest payload echoed back intact
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct TransportReport {
+    pub daemon_supports_quic: bool,
+    pub preferences: TransportPreferences,
+    pub udp: Option<UdpDiagnostics>,
+    // What renters will be offered, in order; the first entry is what most will use.
+    pub effective: Vec<Transport>,
+    pub notes: Vec<String>,
+}
+
+fn nonce() -> String {
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or(0);
+    format!("dante-probe-{}", nanos)
+}
+
+// Asks the platform to send a datagram carrying `nonce` to our public address on an ephemeral
+// port, which shows whether unsolicited inbound UDP gets through to this machine.
+async fn test_inbound_udp(app_handle: &AppHandle) -> Result<bool, String> {
+    let socket = UdpSocket::bind(("0.0.0.0", 0)).map_err(|e| e.to_string())?;
+    let port = socket.local_addr().map_err(|e| e.to_string())?.port();
+    socket.set_read_timeout(Some(UDP_PROBE_TIMEOUT)).map_err(|e| e.to_string())?;
+    let nonce = nonce();
+    api_client::send_json::<_, serde_json::Value>(app_handle, reqwest::Method::POST, "/api/v1/network/udp-probe", &json!({ "port": port, "nonce": nonce }))
+        .await?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut buf = [0u8; 512];
+        while let Ok((len, _)) = socket.recv_from(&mut buf) {
+            if &buf[..len] == nonce.as_bytes() {
+                return true;
+            }
+        }
+        false
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
+
+// Largest payload from `MTU_PROBE_SIZES` that the echo service returns intact.
+fn probe_max_datagram(echo: &UdpEcho) -> Result<Option<usize>, String> {
+    let socket = UdpSocket::bind(("0.0.0.0", 0)).map_err(|e| e.to_string())?;
+    socket.set_read_timeout(Some(UDP_PROBE_TIMEOUT)).map_err(|e| e.to_string())?;
+    socket.connect((echo.host.as_str(), echo.port)).map_err(|e| format!("Cannot reach the UDP echo service: {}", e))?;
+    let mut buf = vec![0u8; 2048];
+    for &size in MTU_PROBE_SIZES {
+        let payload: Vec<u8> = (0..size).map(|i| (i % 251) as u8).collect();
+        if socket.send(&payload).is_err() {
+            continue;
+        }
+        if matches!(socket.recv(&mut buf), Ok(len) if len == size && buf[..len] == payload[..]) {
+            return Ok(Some(size));
+        }
+    }
+    Ok(None)
+}
+
+async fn udp_diagnostics(app_handle: &AppHandle) -> UdpDiagnostics {
+    let inbound = test_inbound_udp(app_handle).await;
+    let max_datagram = match api_client::get_json::<UdpEcho>(app_handle, "/api/v1/network/udp-echo").await {
+        Ok(echo) => tauri::async_runtime::spawn_blocking(move || probe_max_datagram(&echo)).await.map_err(|e| e.to_string()).and_then(|r| r),
+        Err(e) => Err(e),
+    };
+    UdpDiagnostics {
+        inbound_reachable: *inbound.as_ref().unwrap_or(&false),
+        max_datagram_bytes: max_datagram.as_ref().ok().copied().flatten(),
+        error: inbound.err().or(max_datagram.err()),
+    }
+}
+
+#[tauri::command]
+pub async fn get_transport_report(app_handle: AppHandle) -> Result<TransportReport, String> {
+    let preferences = app_settings::current(&app_handle).transport_preferences;
+    let daemon = invoke_daemon_cli_json_output::<DaemonTransports>(&app_handle, &["--get-transport-capabilities-json"]).await.unwrap_or_default();
+    let quic_wanted = preferences.enabled.contains(&Transport::Quic);
+    let udp = if daemon.quic_supported && quic_wanted { Some(udp_diagnostics(&app_handle).await) } else { None };
+
+    let mut notes = Vec::new();
+    if quic_wanted && !daemon.quic_supported {
+        notes.push("The installed daemon does not support QUIC; update it to offer QUIC data channels.".to_string());
+    }
+    let quic_usable = match &udp {
+        Some(udp) if !udp.inbound_reachable => {
+            notes.push(format!("Inbound UDP doesn't reach this machine from the internet; forward UDP port {} on your router to enable QUIC.", preferences.quic_port));
+            false
+        }
+        Some(udp) if udp.max_datagram_bytes.is_none_or(|b| b < QUIC_MIN_DATAGRAM) => {
+            notes.push("The network path drops full-size UDP datagrams, which QUIC needs.".to_string());
+            false
+        }
+        Some(_) => true,
+        None => false,
+    };
+    let effective = preferences
+        .enabled
+        .iter()
+        .copied()
+        .filter(|t| *t != Transport::Quic || quic_usable)
+        .collect::<Vec<_>>();
+    if effective.is_empty() {
+        notes.push("No usable transport is enabled; renters will not be able to transfer data.".to_string());
+    }
+    Ok(TransportReport { daemon_supports_quic: daemon.quic_supported, preferences, udp, effective, notes })
+}
+
+pub async fn push_to_daemon(app_handle: &AppHandle, preferences: &TransportPreferences) -> Result<(), String> {
+    let json = serde_json::to_string(preferences).map_err(|e| format!("Failed to serialize transport preferences: {}", e))?;
+    invoke_daemon_cli_json_output::<serde_json::Value>(app_handle, &["--set-transport-preferences-json", &json])
+        .await
+        .map(|_| ())
+}
+
+#[tauri::command]
+pub async fn set_transport_preferences(
+    app_handle: AppHandle,
+    state: State<'_, AppSettingsState>,
+    preferences: TransportPreferences,
+) -> Result<TransportPreferences, String> {
+    if preferences.enabled.is_empty() {
+        return Err("Enable at least one transport.".to_string());
+    }
+    let mut settings = state.get();
+    settings.transport_preferences = preferences.clone();
+    app_settings::update_app_settings(app_handle.clone(), state, settings).await?;
+    if let Err(e) = push_to_daemon(&app_handle, &preferences).await {
+        emit_log_entry(&app_handle, "status", format!("Transport preferences saved; daemon will receive them on next start ({}).", e));
+    }
+    Ok(preferences)
+}
+
+pub fn sync_on_start(app_handle: &AppHandle) {
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        let preferences = app_settings::current(&app_handle).transport_preferences;
+        if let Err(e) = push_to_daemon(&app_handle, &preferences).await {
+            emit_log_entry(&app_handle, "error", format!("Failed to push transport preferences to the daemon: {}", e));
+        }
+    });
+}
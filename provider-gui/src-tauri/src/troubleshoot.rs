@@ -0,0 +1,393 @@
+// Rules-driven troubleshooter.
+//
+// For a reported problem we run the diagnostics known to matter for it, return the findings,
+// and offer the automated fixes attached to failing checks. Applying a fix re-runs the checks
+// it is meant to resolve so the user sees a before/after comparison rather than a promise.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::time::Duration;
+use sysinfo::{Pid, ProcessRefreshKind, System, UpdateKind};
+use tauri::api::process::Command as TauriCommand;
+use tauri::{AppHandle, Manager};
+
+use crate::clock;
+use crate::image_registry;
+use crate::hooks::run_script;
+use crate::{app_settings, emit_log_entry, get_detected_gpus, invoke_daemon_cli_json_output, start_daemon, stop_daemon, wsl, DaemonState};
+
+const CHECK_TIMEOUT: Duration = Duration::from_secs(20);
+const PULL_TIMEOUT: Duration = Duration::from_secs(600);
+
+// The library `consul` image on Docker Hub is deprecated and its tags no longer resolve, which
+// breaks the local backend stack with a "manifest not found" error. HashiCorp publishes the
+// same image under its own namespace. A pin for it in the registry config (see
+// `image_registry`) takes precedence.
+const CONSUL_IMAGE: &str = "consul:1.15";
+const CONSUL_PINNED_SOURCE: &str = "hashicorp/consul:1.15";
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Problem {
+    DaemonWontStart,
+    GpusNotDetected,
+    ImagePullFails,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct Finding {
+    pub check_id: &'static str,
+    pub title: &'static str,
+    pub status: CheckStatus,
+    pub detail: String,
+    pub fix_id: Option<&'static str>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct FixDescriptor {
+    pub id: &'static str,
+    pub title: &'static str,
+    pub description: &'static str,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct TroubleshootReport {
+    pub problem: Problem,
+    pub findings: Vec<Finding>,
+    pub available_fixes: Vec<FixDescriptor>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct FixResult {
+    pub fix_id: String,
+    pub output: String,
+    pub before: Vec<Finding>,
+    pub after: Vec<Finding>,
+    pub resolved: bool,
+}
+
+fn checks_for(problem: Problem) -> &'static [&'static str] {
+    match problem {
+        Problem::DaemonWontStart => &["sidecar_available", "stale_daemon_process", "daemon_responds"],
+        Problem::GpusNotDetected => &["nvidia_driver", "daemon_responds", "daemon_detects_gpus"],
+        Problem::ImagePullFails => &["docker_running", "registry_mirror", "pinned_images", "consul_image_available"],
+    }
+}
+
+fn fixes() -> Vec<FixDescriptor> {
+    vec![
+        FixDescriptor {
+            id: "restart_daemon",
+            title: "Restart the daemon",
+            description: "Stop and start the provider daemon so it re-reads its configuration and re-probes GPUs.",
+        },
+        FixDescriptor {
+            id: "kill_stale_daemon",
+            title: "Terminate stale daemon processes",
+            description: "Kill provider-daemon processes left over from a previous session that hold its ports or GPUs.",
+        },
+        FixDescriptor {
+            id: "pull_pinned_consul",
+            title: "Pull Consul from the HashiCorp namespace",
+            description: "Pull hashicorp/consul:1.15 and tag it as consul:1.15 so the local stack no longer hits the deprecated library image.",
+        },
+        FixDescriptor {
+            id: "pull_pinned_images",
+            title: "Pull pinned images",
+            description: "Pull each pinned image at its digest and tag it under the reference the stack uses.",
+        },
+    ]
+}
+
+fn verification_checks(fix_id: &str) -> Option<&'static [&'static str]> {
+    match fix_id {
+        "restart_daemon" => Some(&["daemon_responds", "daemon_detects_gpus"]),
+        "kill_stale_daemon" => Some(&["stale_daemon_process"]),
+        "pull_pinned_consul" => Some(&["consul_image_available"]),
+        "pull_pinned_images" => Some(&["pinned_images"]),
+        _ => None,
+    }
+}
+
+fn finding(check_id: &'static str, title: &'static str, result: Result<String, String>, failure: CheckStatus, fix_id: Option<&'static str>) -> Finding {
+    match result {
+        Ok(detail) => Finding { check_id, title, status: CheckStatus::Pass, detail, fix_id: None },
+        Err(detail) => Finding { check_id, title, status: failure, detail, fix_id },
+    }
+}
+
+fn run_tool(program: &str, args: &[&str], timeout: Duration) -> Result<String, String> {
+    let args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+    run_script(program, &args, "", timeout, false).map(|out| out.stdout.trim().to_string())
+}
+
+// Each pinned image must be present locally and resolve to its pinned digest.
+fn check_pinned_images(app_handle: &AppHandle) -> Result<String, String> {
+    let pins = app_settings::current(app_handle).image_registry.pinned_images;
+    if pins.is_empty() {
+        return Ok("No images are pinned.".to_string());
+    }
+    let mut problems = Vec::new();
+    for pin in &pins {
+        match run_tool("docker", &["image", "inspect", "--format", "{{join .RepoDigests \" \"}}", &pin.image], CHECK_TIMEOUT) {
+            Ok(digests) if digests.split_whitespace().any(|d| d.ends_with(&format!("@{}", pin.digest))) => {}
+            Ok(_) => problems.push(format!("{} doesn't match its pinned digest", pin.image)),
+            Err(_) => problems.push(format!("{} is missing", pin.image)),
+        }
+    }
+    if problems.is_empty() {
+        Ok(format!("All {} pinned image(s) match their digests.", pins.len()))
+    } else {
+        Err(format!("{}.", problems.join("; ")))
+    }
+}
+
+// Daemon processes this app doesn't own: not its managed sidecar and not one of its children,
+// such as a one-shot CLI call still in flight. Only the current user's processes count; another
+// user's daemon isn't ours to report or kill.
+fn stale_daemon_processes(app_handle: &AppHandle, system: &mut System) -> Result<Vec<Pid>, String> {
+    let managed = app_handle.state::<DaemonState>().process.lock().unwrap().as_ref().map(|child| child.pid());
+    system.refresh_processes_specifics(ProcessRefreshKind::new().with_user(UpdateKind::OnlyIfNotSet));
+    let own_pid = sysinfo::get_current_pid().map_err(|e| format!("Could not identify this process: {}", e))?;
+    let own_user = system.process(own_pid).and_then(|p| p.user_id()).cloned();
+    Ok(system
+        .processes()
+        .iter()
+        .filter(|(pid, process)| {
+            process.name().contains("provider-daemon")
+                && Some(pid.as_u32()) != managed
+                && process.parent() != Some(own_pid)
+                && own_user.is_some()
+                && process.user_id() == own_user.as_ref()
+        })
+        .map(|(pid, _)| *pid)
+        .collect())
+}
+
+// Kills stale daemon processes. Refused while the GUI's own daemon is online, since a rental
+// could be running on either.
+fn kill_stale_daemons(app_handle: &AppHandle) -> Result<String, String> {
+    if app_handle.state::<DaemonState>().status.lock().unwrap().as_str() == "online" {
+        return Err("The daemon is online; stop it before killing stale daemon processes.".to_string());
+    }
+    let mut system = System::new();
+    let mut killed = Vec::new();
+    for pid in stale_daemon_processes(app_handle, &mut system)? {
+        if !system.process(pid).is_some_and(|p| p.kill()) {
+            return Err(format!("Failed to kill daemon process {}.", pid));
+        }
+        killed.push(pid.to_string());
+    }
+    Ok(if killed.is_empty() { "No stale daemon processes were running.".to_string() } else { format!("Killed stale daemon process(es) {}.", killed.join(", ")) })
+}
+
+async fn run_check(app_handle: &AppHandle, check_id: &str) -> Finding {
+    match check_id {
+        "sidecar_available" => finding(
+            "sidecar_available",
+            "Daemon binary is bundled",
+            TauriCommand::new_sidecar("provider-daemon")
+                .map(|_| "provider-daemon sidecar resolved.".to_string())
+                .map_err(|e| format!("Sidecar could not be resolved: {}. Reinstall the application.", e)),
+            CheckStatus::Fail,
+            None,
+        ),
+        "stale_daemon_process" => {
+            let mut system = System::new();
+            let result = match stale_daemon_processes(app_handle, &mut system) {
+                Ok(pids) if pids.is_empty() => Ok("No stale daemon processes found.".to_string()),
+                Ok(pids) => {
+                    let listing: Vec<String> = pids
+                        .iter()
+                        .filter_map(|pid| system.process(*pid).map(|p| format!("{} {}", pid, p.name())))
+                        .collect();
+                    Err(format!("Daemon processes are running outside the GUI's control:\n{}", listing.join("\n")))
+                }
+                Err(e) => Err(format!("Could not list processes: {}", e)),
+            };
+            finding("stale_daemon_process", "No orphaned daemon processes", result, CheckStatus::Fail, Some("kill_stale_daemon"))
+        }
+        "daemon_responds" => finding(
+            "daemon_responds",
+            "Daemon CLI responds",
+            invoke_daemon_cli_json_output::<Value>(app_handle, &["--get-settings-json"])
+                .await
+                .map(|_| "Daemon returned its settings.".to_string()),
+            CheckStatus::Fail,
+            Some("restart_daemon"),
+        ),
+        "nvidia_driver" => finding(
+            "nvidia_driver",
+            "NVIDIA driver is installed",
+            run_tool(wsl::nvidia_smi_program(), &["-L"], CHECK_TIMEOUT)
+                .and_then(|out| if out.is_empty() { Err("nvidia-smi listed no GPUs.".to_string()) } else { Ok(out) })
+                .map_err(|e| format!("{}. Install or reinstall the NVIDIA driver and reboot.", e)),
+            CheckStatus::Fail,
+            None,
+        ),
+        "daemon_detects_gpus" => finding(
+            "daemon_detects_gpus",
+            "Daemon detects GPUs",
+            get_detected_gpus(app_handle.clone()).await.and_then(|gpus| {
+                if gpus.is_empty() {
+                    Err("The daemon reported no GPUs.".to_string())
+                } else {
+                    Ok(format!("{} GPU(s) detected.", gpus.len()))
+                }
+            }),
+            CheckStatus::Fail,
+            Some("restart_daemon"),
+        ),
+        "docker_running" => finding(
+            "docker_running",
+            "Docker engine is running",
+            run_tool("docker", &["info", "--format", "{{.ServerVersion}}"], CHECK_TIMEOUT)
+                .map(|version| format!("Docker {} is running.", version))
+                .map_err(|e| format!("Docker is not reachable: {}. Start Docker Desktop or the docker service.", e)),
+            CheckStatus::Fail,
+            None,
+        ),
+        "registry_mirror" => {
+            let result = match app_settings::current(app_handle).image_registry.mirror {
+                Some(mirror) => image_registry::check_mirror(&mirror).await,
+                None => Ok("No mirror configured; images come from Docker Hub.".to_string()),
+            };
+            finding("registry_mirror", "Registry mirror is reachable", result, CheckStatus::Warn, None)
+        }
+        "pinned_images" => finding("pinned_images", "Pinned images match their digests", check_pinned_images(app_handle), CheckStatus::Fail, Some("pull_pinned_images")),
+        "consul_image_available" => finding(
+            "consul_image_available",
+            "Consul image is available locally",
+            run_tool("docker", &["image", "inspect", "--format", "{{.Id}}", CONSUL_IMAGE], CHECK_TIMEOUT)
+                .map(|id| format!("{} present ({}).", CONSUL_IMAGE, id))
+                .map_err(|_| format!("{} is missing; pulling it from Docker Hub fails because the library image is deprecated.", CONSUL_IMAGE)),
+            CheckStatus::Fail,
+            Some("pull_pinned_consul"),
+        ),
+        "wsl_gpu_paravirtualization" => finding(
+            "wsl_gpu_paravirtualization",
+            "GPU paravirtualization is enabled in WSL",
+            wsl::check_gpu_paravirtualization(app_handle),
+            CheckStatus::Fail,
+            None,
+        ),
+        "wsl_nvidia_smi" => finding("wsl_nvidia_smi", "nvidia-smi works inside WSL", wsl::check_nvidia_smi(app_handle), CheckStatus::Fail, None),
+        "wsl_localhost_forwarding" => finding(
+            "wsl_localhost_forwarding",
+            "WSL localhost forwarding is enabled",
+            wsl::check_localhost_forwarding(),
+            CheckStatus::Warn,
+            None,
+        ),
+        "clock_skew" => {
+            let (status, detail) = match clock::measure(app_handle).await {
+                Ok(skew) if skew.exceeds_threshold => (
+                    CheckStatus::Warn,
+                    format!(
+                        "Local clock is off by {} ms (threshold {} ms). Enable automatic time sync so job and payout records match the platform.",
+                        skew.offset_ms, skew.threshold_ms
+                    ),
+                ),
+                Ok(skew) => (CheckStatus::Pass, format!("Local clock is within {} ms of platform time.", skew.offset_ms.abs())),
+                Err(e) => (CheckStatus::Warn, format!("Could not compare against platform time: {}", e)),
+            };
+            Finding { check_id: "clock_skew", title: "System clock matches platform time", status, detail, fix_id: None }
+        }
+        _ => Finding {
+            check_id: "unknown",
+            title: "Unknown check",
+            status: CheckStatus::Warn,
+            detail: format!("No diagnostic named '{}'.", check_id),
+            fix_id: None,
+        },
+    }
+}
+
+async fn run_checks(app_handle: &AppHandle, check_ids: &[&str]) -> Vec<Finding> {
+    let mut findings = Vec::with_capacity(check_ids.len());
+    for id in check_ids {
+        findings.push(run_check(app_handle, id).await);
+    }
+    findings
+}
+
+async fn perform_fix(app_handle: &AppHandle, fix_id: &str) -> Result<String, String> {
+    match fix_id {
+        "restart_daemon" => {
+            let _ = stop_daemon(app_handle.clone(), app_handle.state::<DaemonState>()).await;
+            tokio::time::sleep(Duration::from_secs(2)).await;
+            start_daemon(app_handle.clone(), app_handle.state::<DaemonState>()).await?;
+            // Give the daemon a moment to probe hardware before verifying.
+            tokio::time::sleep(Duration::from_secs(3)).await;
+            Ok("Daemon restarted.".to_string())
+        }
+        "kill_stale_daemon" => kill_stale_daemons(app_handle),
+        "pull_pinned_consul" => {
+            let source = image_registry::pin_for(app_handle, CONSUL_IMAGE).map_or_else(|| CONSUL_PINNED_SOURCE.to_string(), |pin| pin.pull_reference());
+            let pulled = run_tool("docker", &["pull", &source], PULL_TIMEOUT)?;
+            run_tool("docker", &["tag", &source, CONSUL_IMAGE], CHECK_TIMEOUT)?;
+            Ok(format!("{}\nTagged {} as {}.", pulled, source, CONSUL_IMAGE))
+        }
+        "pull_pinned_images" => {
+            let mut output = Vec::new();
+            for pin in app_settings::current(app_handle).image_registry.pinned_images {
+                let source = pin.pull_reference();
+                run_tool("docker", &["pull", &source], PULL_TIMEOUT)?;
+                run_tool("docker", &["tag", &source, &pin.image], CHECK_TIMEOUT)?;
+                output.push(format!("Tagged {} as {}.", source, pin.image));
+            }
+            Ok(output.join("\n"))
+        }
+        _ => Err(format!("Unknown fix '{}'.", fix_id)),
+    }
+}
+
+#[tauri::command]
+pub async fn run_troubleshooter(app_handle: AppHandle, problem: Problem) -> Result<TroubleshootReport, String> {
+    emit_log_entry(&app_handle, "status", format!("Running troubleshooter for {:?}...", problem));
+    let findings = run_checks(&app_handle, checks_for(problem)).await;
+    let available_fixes = fixes()
+        .into_iter()
+        .filter(|fix| findings.iter().any(|f| f.status != CheckStatus::Pass && f.fix_id == Some(fix.id)))
+        .collect();
+    Ok(TroubleshootReport { problem, findings, available_fixes })
+}
+
+// Everything a provider should have green before going live.
+const PREFLIGHT_CHECKS: &[&str] = &["sidecar_available", "daemon_responds", "daemon_detects_gpus", "docker_running", "registry_mirror", "pinned_images", "clock_skew"];
+const WSL_PREFLIGHT_CHECKS: &[&str] = &["wsl_gpu_paravirtualization", "wsl_nvidia_smi", "wsl_localhost_forwarding"];
+
+#[tauri::command]
+pub async fn run_preflight_checklist(app_handle: AppHandle) -> Result<Vec<Finding>, String> {
+    let mut findings = run_checks(&app_handle, PREFLIGHT_CHECKS).await;
+    if wsl::active(&app_handle) {
+        findings.extend(run_checks(&app_handle, WSL_PREFLIGHT_CHECKS).await);
+    }
+    Ok(findings)
+}
+
+#[tauri::command]
+pub async fn apply_fix(app_handle: AppHandle, fix_id: String) -> Result<FixResult, String> {
+    let checks = verification_checks(&fix_id).ok_or_else(|| format!("Unknown fix '{}'.", fix_id))?;
+    emit_log_entry(&app_handle, "status", format!("Applying fix '{}'...", fix_id));
+
+    let before = run_checks(&app_handle, checks).await;
+    let output = perform_fix(&app_handle, &fix_id).await.unwrap_or_else(|e| format!("Fix reported an error: {}", e));
+    let after = run_checks(&app_handle, checks).await;
+    let resolved = after.iter().all(|f| f.status == CheckStatus::Pass);
+
+    emit_log_entry(
+        &app_handle,
+        if resolved { "status" } else { "error" },
+        format!("Fix '{}' {}.", fix_id, if resolved { "resolved the issue" } else { "did not resolve the issue" }),
+    );
+    Ok(FixResult { fix_id, output, before, after, resolved })
+}
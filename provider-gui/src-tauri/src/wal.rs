@@ -0,0 +1,322 @@
+// Write-ahead log for financially relevant events.
+//
+// Job completions, payouts and earnings changes are appended (and synced) to a small
+// JSON-lines file before they are emitted to the frontend. The frontend acknowledges each
+// event once it has recorded it; anything still unacknowledged after a crash is reconciled
+// against the daemon on the next start and delivered again.
+//
+// An event only enters the processed-event ledger once it is in the WAL, and only leaves the
+// WAL once it is in the ledger, so a crash between the two never drops it as a duplicate.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+
+use crate::clock;
+use crate::money::{self, Decimal};
+use crate::storage::Storage;
+use crate::{emit_log_entry, get_timestamp, gpu_ids, invoke_daemon_cli_json_output, shutdown, DaemonState, FinancialSummary};
+
+const WAL_FILE_NAME: &str = "events.wal";
+// Rewrite the file once this many lines are only there to record acknowledgements.
+const COMPACT_THRESHOLD: usize = 500;
+const RECONCILE_RETRY: Duration = Duration::from_secs(10);
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum WalEventKind {
+    JobCompleted,
+    PayoutReceived,
+    EarningsUpdated,
+}
+
+impl WalEventKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WalEventKind::JobCompleted => "job_completed",
+            WalEventKind::PayoutReceived => "payout_received",
+            WalEventKind::EarningsUpdated => "earnings_updated",
+        }
+    }
+
+    pub fn idempotency_key(&self, key: &str) -> String {
+        format!("{}:{}", self.as_str(), key)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct WalEntry {
+    pub seq: u64,
+    pub kind: WalEventKind,
+    pub key: String, // Stable identity of the event, e.g. the job ID or payout timestamp
+    pub payload: Value,
+    #[serde(default, with = "money::optional_amount")]
+    pub amount_dgpu: Option<Decimal>,
+    #[serde(default)]
+    pub gpu_id: Option<String>,
+    pub created_at: String,
+    #[serde(default)]
+    pub corrected_at: Option<String>, // Platform time when the clock offset is known
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum WalLine {
+    Entry(WalEntry),
+    Ack { ack: u64 },
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct DeliveredEvent {
+    #[serde(flatten)]
+    pub entry: WalEntry,
+    pub replayed: bool,
+    pub confirmed_by_daemon: Option<bool>, // Only set for replayed events
+}
+
+struct WalInner {
+    next_seq: u64,
+    pending: BTreeMap<u64, WalEntry>,
+    ack_lines: usize,
+}
+
+pub struct WalState {
+    inner: Mutex<WalInner>,
+    path: Option<PathBuf>,
+}
+
+fn append_lines(path: &PathBuf, lines: &[WalLine]) -> Result<(), String> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    for line in lines {
+        let json = serde_json::to_string(line).map_err(|e| format!("Failed to serialize WAL line: {}", e))?;
+        writeln!(file, "{}", json).map_err(|e| format!("Failed to append to WAL: {}", e))?;
+    }
+    // The whole point is surviving a crash right after this returns.
+    file.sync_data().map_err(|e| format!("Failed to sync WAL: {}", e))
+}
+
+impl WalState {
+    pub fn load(app_handle: &AppHandle) -> Self {
+        let path = app_handle.path_resolver().app_data_dir().map(|dir| {
+            let _ = fs::create_dir_all(&dir);
+            dir.join(WAL_FILE_NAME)
+        });
+        let mut pending = BTreeMap::new();
+        let mut next_seq = 1;
+        if let Some(file) = path.as_ref().and_then(|p| File::open(p).ok()) {
+            // A torn final line from a crash mid-write is skipped; its event was never emitted.
+            for line in BufReader::new(file).lines().map_while(Result::ok) {
+                match serde_json::from_str::<WalLine>(&line) {
+                    Ok(WalLine::Entry(entry)) => {
+                        next_seq = next_seq.max(entry.seq + 1);
+                        pending.insert(entry.seq, entry);
+                    }
+                    Ok(WalLine::Ack { ack }) => {
+                        pending.remove(&ack);
+                    }
+                    Err(_) => {}
+                }
+            }
+        }
+        let state = WalState { inner: Mutex::new(WalInner { next_seq, pending, ack_lines: 0 }), path };
+        if let Err(e) = state.compact() {
+            eprintln!("{}", e);
+        }
+        state
+    }
+
+    // Rewrites the file with only the unacknowledged entries.
+    fn compact(&self) -> Result<(), String> {
+        let Some(path) = &self.path else { return Ok(()) };
+        let mut inner = self.inner.lock().unwrap();
+        let tmp = path.with_extension("wal.tmp");
+        let _ = fs::remove_file(&tmp);
+        let lines: Vec<WalLine> = inner.pending.values().cloned().map(WalLine::Entry).collect();
+        append_lines(&tmp, &lines)?;
+        fs::rename(&tmp, path).map_err(|e| format!("Failed to replace WAL: {}", e))?;
+        inner.ack_lines = 0;
+        Ok(())
+    }
+
+    fn append(&self, entry: WalEntry) -> Result<WalEntry, String> {
+        let mut inner = self.inner.lock().unwrap();
+        let entry = WalEntry { seq: inner.next_seq, ..entry };
+        if let Some(path) = &self.path {
+            append_lines(path, &[WalLine::Entry(entry.clone())])?;
+        }
+        inner.next_seq += 1;
+        inner.pending.insert(entry.seq, entry.clone());
+        Ok(entry)
+    }
+
+    fn ack(&self, seqs: &[u64]) -> Result<usize, String> {
+        let (acked_count, needs_compaction) = {
+            let mut inner = self.inner.lock().unwrap();
+            let acked: Vec<u64> = seqs.iter().copied().filter(|seq| inner.pending.remove(seq).is_some()).collect();
+            if let Some(path) = &self.path {
+                append_lines(path, &acked.iter().map(|seq| WalLine::Ack { ack: *seq }).collect::<Vec<_>>())?;
+            }
+            inner.ack_lines += acked.len();
+            (acked.len(), inner.ack_lines >= COMPACT_THRESHOLD)
+        };
+        if needs_compaction {
+            self.compact()?;
+        }
+        Ok(acked_count)
+    }
+
+    // Compacts away acknowledgement lines before exit; entries are already synced as written.
+    pub fn flush(&self) -> Result<(), String> {
+        if self.inner.lock().unwrap().ack_lines == 0 {
+            return Ok(());
+        }
+        self.compact()
+    }
+
+    pub fn pending(&self) -> Vec<WalEntry> {
+        self.inner.lock().unwrap().pending.values().cloned().collect()
+    }
+
+    fn has_pending(&self, kind: WalEventKind, key: &str) -> bool {
+        self.inner.lock().unwrap().pending.values().any(|e| e.kind == kind && e.key == key)
+    }
+}
+
+// Whether the event is already in the ledger or waiting in the WAL, without logging a repeat.
+pub fn is_recorded(app_handle: &AppHandle, kind: WalEventKind, key: &str) -> bool {
+    let processed = app_handle
+        .try_state::<Storage>()
+        .is_some_and(|s| s.is_processed(&kind.idempotency_key(key)).unwrap_or(false));
+    processed || app_handle.try_state::<WalState>().is_some_and(|s| s.has_pending(kind, key))
+}
+
+// Ledger rows are insert-or-ignore, so marking an entry that is already there is harmless.
+fn mark_in_ledger(app_handle: &AppHandle, entry: &WalEntry) -> Result<(), String> {
+    match app_handle.try_state::<Storage>() {
+        Some(storage) => storage
+            .mark_processed(&entry.kind.idempotency_key(&entry.key), entry.kind.as_str(), entry.amount_dgpu, entry.gpu_id.as_deref())
+            .map(|_| ()),
+        None => Ok(()),
+    }
+}
+
+fn deliver(app_handle: &AppHandle, event: DeliveredEvent) {
+    if let Err(e) = app_handle.emit_all("financial_event", event) {
+        eprintln!("Failed to emit financial_event: {}", e);
+    }
+}
+
+// Logs the event durably, emits it, then marks it in the processed-event ledger. If the WAL
+// write fails the event is still emitted: losing durability is better than losing the live
+// update.
+//
+// Events are first checked against the ledger and the WAL, so the same payout or job
+// completion observed twice (daemon restarts, reconnect storms) is only ever counted once.
+// Returns false for such a repeat, which callers must not count either.
+pub fn record(app_handle: &AppHandle, kind: WalEventKind, key: String, amount_dgpu: Option<Decimal>, gpu_id: Option<&str>, payload: Value) -> bool {
+    let Some(state) = app_handle.try_state::<WalState>() else { return true };
+    let processed = match app_handle.try_state::<Storage>().map(|s| s.is_processed(&kind.idempotency_key(&key))) {
+        Some(Ok(processed)) => processed,
+        // Without the ledger we can't dedupe, but dropping a real event would be worse.
+        Some(Err(e)) => {
+            emit_log_entry(app_handle, "error", e);
+            false
+        }
+        None => false,
+    };
+    if processed || state.has_pending(kind, &key) {
+        emit_log_entry(app_handle, "status", format!("Ignoring duplicate {} event '{}'.", kind.as_str(), key));
+        return false;
+    }
+    let entry = WalEntry {
+        seq: 0,
+        kind,
+        key,
+        payload,
+        amount_dgpu,
+        gpu_id: gpu_id.map(str::to_string),
+        created_at: get_timestamp(),
+        corrected_at: clock::corrected_timestamp(),
+    };
+    let entry = match state.append(entry.clone()) {
+        Ok(entry) => entry,
+        Err(e) => {
+            emit_log_entry(app_handle, "error", format!("Failed to write {:?} event to the WAL: {}", kind, e));
+            entry
+        }
+    };
+    deliver(app_handle, DeliveredEvent { entry: entry.clone(), replayed: false, confirmed_by_daemon: None });
+    // A failure here leaves the entry pending; it is marked again on acknowledgement or replay.
+    if let Err(e) = mark_in_ledger(app_handle, &entry) {
+        emit_log_entry(app_handle, "error", e);
+    }
+    true
+}
+
+// Re-delivers events left unacknowledged by a previous session once the daemon can vouch for
+// them. Events the daemon doesn't know about are still delivered, flagged as unconfirmed.
+pub fn spawn_reconciliation(app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let pending = app_handle.state::<WalState>().pending();
+        if pending.is_empty() {
+            return;
+        }
+        emit_log_entry(&app_handle, "status", format!("{} event(s) from a previous session were not acknowledged; reconciling.", pending.len()));
+        // The previous session may have crashed after writing an entry but before marking it.
+        for entry in &pending {
+            if let Err(e) = mark_in_ledger(&app_handle, entry) {
+                emit_log_entry(&app_handle, "error", e);
+            }
+        }
+
+        while *app_handle.state::<DaemonState>().status.lock().unwrap() != "online" {
+            if !shutdown::sleep(RECONCILE_RETRY).await {
+                return;
+            }
+        }
+        let jobs = gpu_ids::local_jobs(&app_handle).await.ok();
+        let summary = invoke_daemon_cli_json_output::<FinancialSummary>(&app_handle, &["--get-financial-summary-json"]).await.ok();
+
+        for entry in pending {
+            let confirmed = match entry.kind {
+                WalEventKind::JobCompleted => jobs
+                    .as_ref()
+                    .map(|jobs| jobs.iter().any(|j| j.id == entry.key && j.status == "completed")),
+                // Later payouts supersede the recorded one, so compare ordering not equality.
+                WalEventKind::PayoutReceived => summary
+                    .as_ref()
+                    .map(|s| s.last_payout_at.as_deref().is_some_and(|last| last >= entry.key.as_str())),
+                WalEventKind::EarningsUpdated => summary.as_ref().map(|_| true),
+            };
+            deliver(&app_handle, DeliveredEvent { entry, replayed: true, confirmed_by_daemon: confirmed });
+        }
+    });
+}
+
+#[tauri::command]
+pub async fn get_unacked_events(state: State<'_, WalState>) -> Result<Vec<WalEntry>, String> {
+    Ok(state.pending())
+}
+
+// Entries that can't be marked in the ledger stay pending rather than leave the WAL unrecorded.
+#[tauri::command]
+pub async fn ack_events(app_handle: AppHandle, state: State<'_, WalState>, seqs: Vec<u64>) -> Result<usize, String> {
+    let mut marked = Vec::new();
+    for entry in state.pending().iter().filter(|e| seqs.contains(&e.seq)) {
+        match mark_in_ledger(&app_handle, entry) {
+            Ok(()) => marked.push(entry.seq),
+            Err(e) => emit_log_entry(&app_handle, "error", e),
+        }
+    }
+    state.ack(&marked)
+}
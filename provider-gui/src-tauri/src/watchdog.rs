@@ -0,0 +1,218 @@
+// Supervision of long-running background tasks.
+//
+// The telemetry sampler, the platform event bridge and the GPU health checker run for the life
+// of the app, and a hung daemon CLI call or a dropped socket that never errors can leave one of
+// them stuck without anything noticing. Each supervised task gets a `Heartbeat` and reports
+// through it whenever it makes progress, announcing long sleeps in advance. The watchdog restarts
+// a task that exits, panics or misses its heartbeat, and once a task has been restarted
+// `DEGRADED_AFTER_RESTARTS` times within `RESTART_WINDOW` a `subsystem_degraded` event names it.
+
+use futures_util::future::{BoxFuture, FutureExt};
+use serde::Serialize;
+use serde_json::json;
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Manager, State};
+
+use crate::{a11y, emit_log_entry, get_timestamp, shutdown};
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+const RESTART_WINDOW: Duration = Duration::from_secs(15 * 60);
+const DEGRADED_AFTER_RESTARTS: usize = 3;
+
+type TaskFactory = Arc<dyn Fn(AppHandle, Heartbeat) -> BoxFuture<'static, ()> + Send + Sync>;
+
+// Handed to a supervised task; the task is considered stuck once the latest deadline passes.
+#[derive(Clone)]
+pub struct Heartbeat {
+    due: Arc<Mutex<Instant>>,
+    grace: Duration,
+}
+
+impl Heartbeat {
+    fn new(grace: Duration) -> Self {
+        Heartbeat { due: Arc::new(Mutex::new(Instant::now() + grace)), grace }
+    }
+
+    // Reports progress; the task must beat again within its grace period.
+    pub fn beat(&self) {
+        self.beat_within(Duration::ZERO);
+    }
+
+    // Reports progress ahead of a wait of up to `wait`, e.g. before a select on several events.
+    pub fn beat_within(&self, wait: Duration) {
+        *self.due.lock().unwrap() = Instant::now() + wait + self.grace;
+    }
+
+    // `shutdown::sleep` for supervised tasks, so long intervals don't count as missed beats.
+    pub async fn sleep(&self, duration: Duration) -> bool {
+        self.beat_within(duration);
+        let keep_running = shutdown::sleep(duration).await;
+        self.beat();
+        keep_running
+    }
+
+    fn overdue(&self) -> bool {
+        Instant::now() > *self.due.lock().unwrap()
+    }
+}
+
+struct Supervised {
+    factory: TaskFactory,
+    grace: Duration,
+    heartbeat: Heartbeat,
+    handle: JoinHandle<()>,
+    // Set by the task wrapper when the task ends, with the reason.
+    exit: Arc<Mutex<Option<String>>>,
+    recent_restarts: VecDeque<Instant>,
+    total_restarts: u64,
+    last_failure: Option<String>,
+    last_restart_at: Option<String>,
+    degraded: bool,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct SubsystemStatus {
+    pub name: String,
+    pub degraded: bool,
+    pub restarts_in_window: usize,
+    pub total_restarts: u64,
+    pub last_failure: Option<String>,
+    pub last_restart_at: Option<String>,
+}
+
+pub struct WatchdogState {
+    tasks: Mutex<HashMap<&'static str, Supervised>>,
+}
+
+impl WatchdogState {
+    pub fn new() -> Self {
+        WatchdogState { tasks: Mutex::new(HashMap::new()) }
+    }
+}
+
+fn start(app_handle: &AppHandle, name: &'static str, factory: &TaskFactory, heartbeat: Heartbeat) -> (JoinHandle<()>, Arc<Mutex<Option<String>>>) {
+    let exit = Arc::new(Mutex::new(None));
+    let task = factory(app_handle.clone(), heartbeat);
+    let exit_slot = exit.clone();
+    let handle = tauri::async_runtime::spawn(async move {
+        let reason = match AssertUnwindSafe(task).catch_unwind().await {
+            Ok(()) => "exited unexpectedly".to_string(),
+            Err(panic) => {
+                let message = panic
+                    .downcast_ref::<&str>()
+                    .map(|s| s.to_string())
+                    .or_else(|| panic.downcast_ref::<String>().cloned())
+                    .unwrap_or_else(|| "unknown panic".to_string());
+                format!("panicked: {}", message)
+            }
+        };
+        eprintln!("Background task {} {}", name, reason);
+        *exit_slot.lock().unwrap() = Some(reason);
+    });
+    (handle, exit)
+}
+
+// Runs `task` under the watchdog. The task must beat at least every `grace` while working and
+// use `Heartbeat::sleep` (or `beat_within`) for longer waits.
+pub fn supervise<F, Fut>(app_handle: &AppHandle, name: &'static str, grace: Duration, task: F)
+where
+    F: Fn(AppHandle, Heartbeat) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    let factory: TaskFactory = Arc::new(move |app_handle, heartbeat| task(app_handle, heartbeat).boxed());
+    let heartbeat = Heartbeat::new(grace);
+    let (handle, exit) = start(app_handle, name, &factory, heartbeat.clone());
+    let supervised = Supervised {
+        factory,
+        grace,
+        heartbeat,
+        handle,
+        exit,
+        recent_restarts: VecDeque::new(),
+        total_restarts: 0,
+        last_failure: None,
+        last_restart_at: None,
+        degraded: false,
+    };
+    app_handle.state::<WatchdogState>().tasks.lock().unwrap().insert(name, supervised);
+}
+
+fn check(app_handle: &AppHandle) {
+    let state = app_handle.state::<WatchdogState>();
+    let mut tasks = state.tasks.lock().unwrap();
+    let now = Instant::now();
+    for (name, task) in tasks.iter_mut() {
+        while task.recent_restarts.front().is_some_and(|at| now.duration_since(*at) > RESTART_WINDOW) {
+            task.recent_restarts.pop_front();
+        }
+        if task.degraded && task.recent_restarts.is_empty() {
+            task.degraded = false;
+            emit_log_entry(app_handle, "status", format!("Background task {} has been stable again for {} minutes.", name, RESTART_WINDOW.as_secs() / 60));
+        }
+
+        let exited = task.exit.lock().unwrap().take();
+        let failure = match exited {
+            Some(reason) => reason,
+            None if task.heartbeat.overdue() => "stopped reporting its heartbeat".to_string(),
+            None => continue,
+        };
+        task.handle.abort();
+        emit_log_entry(app_handle, "error", format!("Background task {} {}; restarting it.", name, failure));
+        task.heartbeat = Heartbeat::new(task.grace);
+        let (handle, exit) = start(app_handle, name, &task.factory, task.heartbeat.clone());
+        task.handle = handle;
+        task.exit = exit;
+        task.recent_restarts.push_back(now);
+        task.total_restarts += 1;
+        task.last_failure = Some(failure.clone());
+        task.last_restart_at = Some(get_timestamp());
+
+        if !task.degraded && task.recent_restarts.len() >= DEGRADED_AFTER_RESTARTS {
+            task.degraded = true;
+            emit_log_entry(
+                app_handle,
+                "error",
+                format!("Background task {} was restarted {} times in {} minutes and is degraded: {}", name, task.recent_restarts.len(), RESTART_WINDOW.as_secs() / 60, failure),
+            );
+            a11y::announce(app_handle, "alert", a11y::Severity::Warning, format!("Background task {} keeps failing; some status may be out of date.", name));
+            let payload = json!({ "task": name, "restarts": task.recent_restarts.len(), "last_failure": failure, "degraded_at": get_timestamp() });
+            if let Err(e) = app_handle.emit_all("subsystem_degraded", payload) {
+                eprintln!("Failed to emit subsystem_degraded event: {}", e);
+            }
+        }
+    }
+}
+
+pub fn spawn_watchdog(app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            if !shutdown::sleep(CHECK_INTERVAL).await {
+                break;
+            }
+            check(&app_handle);
+        }
+    });
+}
+
+#[tauri::command]
+pub async fn get_subsystem_status(state: State<'_, WatchdogState>) -> Result<Vec<SubsystemStatus>, String> {
+    let tasks = state.tasks.lock().unwrap();
+    let mut statuses: Vec<SubsystemStatus> = tasks
+        .iter()
+        .map(|(name, task)| SubsystemStatus {
+            name: name.to_string(),
+            degraded: task.degraded,
+            restarts_in_window: task.recent_restarts.len(),
+            total_restarts: task.total_restarts,
+            last_failure: task.last_failure.clone(),
+            last_restart_at: task.last_restart_at.clone(),
+        })
+        .collect();
+    statuses.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(statuses)
+}
@@ -0,0 +1,150 @@
+// First-run onboarding wizard.
+//
+// Step completion is tracked here and persisted to the app data directory, so onboarding
+// resumes where it left off after a restart and the frontend renders whatever the backend
+// says is next. Steps the backend can check for itself (daemon, GPUs, pricing) are verified
+// before they are marked complete.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::api::process::Command as TauriCommand;
+use tauri::{AppHandle, Manager, State};
+
+use crate::units::DgpuPerHour;
+use crate::{emit_log_entry, get_detected_gpus, get_provider_settings, get_timestamp};
+
+const WIZARD_FILE_NAME: &str = "wizard_state.json";
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum WizardStep {
+    Account,
+    Wallet,
+    DaemonInstall,
+    GpuDetection,
+    FirstPrice,
+}
+
+const STEP_ORDER: [WizardStep; 5] = [
+    WizardStep::Account,
+    WizardStep::Wallet,
+    WizardStep::DaemonInstall,
+    WizardStep::GpuDetection,
+    WizardStep::FirstPrice,
+];
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StepRecord {
+    pub step: WizardStep,
+    pub completed_at: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct WizardSnapshot {
+    pub steps: Vec<StepRecord>,
+    pub current_step: Option<WizardStep>, // None once onboarding is finished
+    pub finished: bool,
+}
+
+pub struct WizardState {
+    steps: Mutex<Vec<StepRecord>>,
+    path: Option<PathBuf>,
+}
+
+impl WizardState {
+    pub fn load(app_handle: &AppHandle) -> Self {
+        let path = app_handle.path_resolver().app_data_dir().map(|dir| dir.join(WIZARD_FILE_NAME));
+        let saved: Vec<StepRecord> = path
+            .as_ref()
+            .and_then(|p| fs::read_to_string(p).ok())
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        // Rebuild in canonical order so steps added in later versions show up as incomplete.
+        let steps = STEP_ORDER
+            .iter()
+            .map(|step| {
+                saved
+                    .iter()
+                    .find(|r| r.step == *step)
+                    .cloned()
+                    .unwrap_or(StepRecord { step: *step, completed_at: None })
+            })
+            .collect();
+        WizardState { steps: Mutex::new(steps), path }
+    }
+
+    fn snapshot(&self) -> WizardSnapshot {
+        let steps = self.steps.lock().unwrap().clone();
+        let current_step = steps.iter().find(|r| r.completed_at.is_none()).map(|r| r.step);
+        WizardSnapshot { finished: current_step.is_none(), current_step, steps }
+    }
+
+    fn set_completed(&self, step: WizardStep, completed_at: Option<String>) -> Result<(), String> {
+        let mut steps = self.steps.lock().unwrap();
+        if let Some(record) = steps.iter_mut().find(|r| r.step == step) {
+            record.completed_at = completed_at;
+        }
+        let path = self.path.as_ref().ok_or_else(|| "App data directory is unavailable.".to_string())?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        }
+        let json = serde_json::to_string_pretty(&*steps).map_err(|e| format!("Failed to serialize wizard state: {}", e))?;
+        fs::write(path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+}
+
+// Account and wallet setup happen on the platform, so the frontend's word is taken for those.
+async fn verify_step(app_handle: &AppHandle, step: WizardStep) -> Result<(), String> {
+    match step {
+        WizardStep::Account | WizardStep::Wallet => Ok(()),
+        WizardStep::DaemonInstall => TauriCommand::new_sidecar("provider-daemon")
+            .map(|_| ())
+            .map_err(|e| format!("The provider daemon is not installed: {}", e)),
+        WizardStep::GpuDetection => match get_detected_gpus(app_handle.clone()).await? {
+            gpus if gpus.is_empty() => Err("The daemon has not detected any GPUs yet.".to_string()),
+            _ => Ok(()),
+        },
+        WizardStep::FirstPrice => match get_provider_settings(app_handle.clone()).await? {
+            settings if settings.default_hourly_rate_dgpu > DgpuPerHour::default() => Ok(()),
+            _ => Err("Set a default hourly rate before finishing onboarding.".to_string()),
+        },
+    }
+}
+
+#[tauri::command]
+pub async fn get_wizard_state(state: State<'_, WizardState>) -> Result<WizardSnapshot, String> {
+    Ok(state.snapshot())
+}
+
+#[tauri::command]
+pub async fn complete_step(app_handle: AppHandle, step: WizardStep) -> Result<WizardSnapshot, String> {
+    let state = app_handle.state::<WizardState>();
+    let snapshot = state.snapshot();
+    let position = STEP_ORDER.iter().position(|s| *s == step).unwrap_or(0);
+    if let Some(pending) = snapshot.steps[..position].iter().find(|r| r.completed_at.is_none()) {
+        return Err(format!("Complete the {:?} step first.", pending.step));
+    }
+
+    verify_step(&app_handle, step).await?;
+    state.set_completed(step, Some(get_timestamp()))?;
+    let snapshot = state.snapshot();
+    emit_log_entry(&app_handle, "status", format!("Onboarding step {:?} completed.", step));
+    if let Err(e) = app_handle.emit_all("wizard_state_changed", snapshot.clone()) {
+        eprintln!("Failed to emit wizard_state_changed event: {}", e);
+    }
+    Ok(snapshot)
+}
+
+#[tauri::command]
+pub async fn reset_wizard(app_handle: AppHandle, state: State<'_, WizardState>) -> Result<WizardSnapshot, String> {
+    for step in STEP_ORDER {
+        state.set_completed(step, None)?;
+    }
+    let snapshot = state.snapshot();
+    if let Err(e) = app_handle.emit_all("wizard_state_changed", snapshot.clone()) {
+        eprintln!("Failed to emit wizard_state_changed event: {}", e);
+    }
+    Ok(snapshot)
+}
@@ -0,0 +1,194 @@
+// Running the provider daemon under WSL2.
+//
+// Two setups are handled. On a Windows host, the daemon can be configured to run inside a WSL2
+// distribution: it is launched and queried through `wsl.exe --exec` instead of the bundled
+// sidecar, Windows paths in its arguments are translated to their /mnt/<drive> form, and
+// stopping it also ends the Linux process, which doesn't reliably exit when wsl.exe is killed.
+// When the GUI itself runs inside WSL (e.g. under WSLg), nvidia-smi is found in the WSL driver
+// directory, which is often missing from PATH.
+//
+// WSL-specific preflight checks verify GPU paravirtualization (/dev/dxg), that nvidia-smi sees a
+// GPU inside the distribution, and that localhost forwarding hasn't been turned off, since the
+// platform's local tooling expects to reach WSL services on localhost.
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::Duration;
+use tauri::api::process::Command as TauriCommand;
+use tauri::AppHandle;
+
+use crate::hooks::run_script;
+use crate::{app_settings, emit_log_entry};
+
+const WSL_EXE: &str = "wsl.exe";
+const WSL_NVIDIA_SMI: &str = "/usr/lib/wsl/lib/nvidia-smi";
+const CHECK_TIMEOUT: Duration = Duration::from_secs(20);
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct WslDaemonConfig {
+    pub enabled: bool,
+    // None uses the default distribution.
+    pub distro: Option<String>,
+    // Path of the daemon binary inside the distribution.
+    pub daemon_path: String,
+}
+
+impl Default for WslDaemonConfig {
+    fn default() -> Self {
+        WslDaemonConfig { enabled: false, distro: None, daemon_path: "provider-daemon".to_string() }
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct WslStatus {
+    pub inside_wsl: bool,
+    pub distro: Option<String>,
+    pub daemon_in_wsl: bool,
+    pub installed_distros: Vec<String>,
+    pub localhost_forwarding: bool,
+}
+
+pub fn inside_wsl() -> bool {
+    cfg!(target_os = "linux")
+        && (std::env::var_os("WSL_DISTRO_NAME").is_some()
+            || std::fs::read_to_string("/proc/sys/kernel/osrelease").is_ok_and(|r| r.to_lowercase().contains("microsoft")))
+}
+
+pub fn daemon_in_wsl(app_handle: &AppHandle) -> bool {
+    cfg!(windows) && app_settings::current(app_handle).wsl_daemon.enabled
+}
+
+// Whether the WSL preflight checks apply.
+pub fn active(app_handle: &AppHandle) -> bool {
+    inside_wsl() || daemon_in_wsl(app_handle)
+}
+
+fn wsl_prefix(config: &WslDaemonConfig) -> Vec<String> {
+    let mut args = Vec::new();
+    if let Some(distro) = &config.distro {
+        args.extend(["-d".to_string(), distro.clone()]);
+    }
+    args.push("--exec".to_string());
+    args
+}
+
+// `C:\Users\me\file` -> `/mnt/c/Users/me/file`; anything else is returned unchanged.
+pub fn to_wsl_path(arg: &str) -> String {
+    let bytes = arg.as_bytes();
+    if bytes.len() >= 3 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':' && (bytes[2] == b'\\' || bytes[2] == b'/') {
+        format!("/mnt/{}/{}", (bytes[0] as char).to_ascii_lowercase(), arg[3..].replace('\\', "/"))
+    } else {
+        arg.to_string()
+    }
+}
+
+// The command that runs the daemon: the bundled sidecar, or the configured binary inside WSL.
+pub fn daemon_command(app_handle: &AppHandle, sidecar_name: &str) -> Result<TauriCommand, String> {
+    if !daemon_in_wsl(app_handle) {
+        return TauriCommand::new_sidecar(sidecar_name).map_err(|e| e.to_string());
+    }
+    let config = app_settings::current(app_handle).wsl_daemon;
+    let mut args = wsl_prefix(&config);
+    args.push(config.daemon_path.clone());
+    Ok(TauriCommand::new(WSL_EXE).args(args))
+}
+
+pub fn translate_args(app_handle: &AppHandle, args: &[&str]) -> Vec<String> {
+    if daemon_in_wsl(app_handle) {
+        args.iter().map(|a| to_wsl_path(a)).collect()
+    } else {
+        args.iter().map(|a| a.to_string()).collect()
+    }
+}
+
+// Killing wsl.exe doesn't always stop the process it started inside the distribution.
+pub fn after_stop(app_handle: &AppHandle) {
+    if !daemon_in_wsl(app_handle) {
+        return;
+    }
+    let config = app_settings::current(app_handle).wsl_daemon;
+    let mut args = wsl_prefix(&config);
+    args.extend(["pkill".to_string(), "-f".to_string(), config.daemon_path.clone()]);
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        // pkill exits non-zero when nothing was left running, which is the normal case.
+        if let Err(e) = run_script(WSL_EXE, &args, "", CHECK_TIMEOUT, false) {
+            if !e.starts_with("exited") {
+                emit_log_entry(&app_handle, "error", format!("Failed to stop the daemon inside WSL: {}", e));
+            }
+        }
+    });
+}
+
+pub fn nvidia_smi_program() -> &'static str {
+    if inside_wsl() && Path::new(WSL_NVIDIA_SMI).exists() {
+        WSL_NVIDIA_SMI
+    } else {
+        "nvidia-smi"
+    }
+}
+
+// Runs `program args` inside the WSL distribution the daemon uses (or directly when we are in it).
+fn run_in_wsl(app_handle: &AppHandle, program: &str, args: &[&str]) -> Result<String, String> {
+    let (command, full_args) = if inside_wsl() {
+        (program.to_string(), args.iter().map(|a| a.to_string()).collect())
+    } else {
+        let mut full_args = wsl_prefix(&app_settings::current(app_handle).wsl_daemon);
+        full_args.push(program.to_string());
+        full_args.extend(args.iter().map(|a| a.to_string()));
+        (WSL_EXE.to_string(), full_args)
+    };
+    run_script(&command, &full_args, "", CHECK_TIMEOUT, false).map(|out| out.stdout.trim().to_string())
+}
+
+pub fn check_gpu_paravirtualization(app_handle: &AppHandle) -> Result<String, String> {
+    run_in_wsl(app_handle, "test", &["-e", "/dev/dxg"])
+        .map(|_| "GPU paravirtualization is available (/dev/dxg present).".to_string())
+        .map_err(|_| "/dev/dxg is missing inside WSL. Update WSL (`wsl --update`), use WSL2 rather than WSL1, and install a current Windows NVIDIA driver.".to_string())
+}
+
+pub fn check_nvidia_smi(app_handle: &AppHandle) -> Result<String, String> {
+    run_in_wsl(app_handle, WSL_NVIDIA_SMI, &["-L"])
+        .and_then(|out| if out.is_empty() { Err("no GPUs listed".to_string()) } else { Ok(out) })
+        .map_err(|e| {
+            format!("nvidia-smi doesn't see a GPU inside WSL ({}). Install the NVIDIA driver on Windows only; a Linux driver inside WSL breaks GPU passthrough.", e)
+        })
+}
+
+// Reads %USERPROFILE%\.wslconfig; forwarding is on unless explicitly disabled.
+fn localhost_forwarding_enabled() -> bool {
+    let Some(profile) = std::env::var_os("USERPROFILE") else { return true };
+    let Ok(config) = std::fs::read_to_string(Path::new(&profile).join(".wslconfig")) else { return true };
+    !config.lines().any(|line| {
+        let line: String = line.chars().filter(|c| !c.is_whitespace()).collect::<String>().to_lowercase();
+        line == "localhostforwarding=false"
+    })
+}
+
+pub fn check_localhost_forwarding() -> Result<String, String> {
+    if localhost_forwarding_enabled() {
+        Ok("WSL localhost forwarding is enabled.".to_string())
+    } else {
+        Err("localhostForwarding=false in .wslconfig; services in WSL won't be reachable on localhost from Windows. Remove the line and run `wsl --shutdown`.".to_string())
+    }
+}
+
+fn installed_distros() -> Vec<String> {
+    if !cfg!(windows) {
+        return Vec::new();
+    }
+    // wsl.exe writes UTF-16 to pipes; strip the NULs that come through as text.
+    run_script(WSL_EXE, &["--list".to_string(), "--quiet".to_string()], "", CHECK_TIMEOUT, false)
+        .map(|out| out.stdout.replace('\0', "").lines().map(str::trim).filter(|l| !l.is_empty()).map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
+#[tauri::command]
+pub async fn get_wsl_status(app_handle: AppHandle) -> Result<WslStatus, String> {
+    let inside = inside_wsl();
+    let daemon_in_wsl = daemon_in_wsl(&app_handle);
+    let distro = if inside { std::env::var("WSL_DISTRO_NAME").ok() } else { app_settings::current(&app_handle).wsl_daemon.distro };
+    let installed_distros = tauri::async_runtime::spawn_blocking(installed_distros).await.map_err(|e| e.to_string())?;
+    Ok(WslStatus { inside_wsl: inside, distro, daemon_in_wsl, installed_distros, localhost_forwarding: localhost_forwarding_enabled() })
+}
@@ -0,0 +1,187 @@
+// Zombie jobs: running as far as the daemon knows, but doing nothing.
+//
+// A container whose process hung, or that lost its GPU context, can sit in "running" for days,
+// holding the GPU and billing time nobody benefits from. Each poll compares every running job's
+// progress and its GPU's utilization with the previous ones; a job whose progress hasn't moved
+// while its GPU stayed idle for the configured period is flagged once with a `zombie_job` event.
+// Inference servers sit idle between requests and never report progress, so inference jobs,
+// and any job that hasn't reported progress yet, are left out: a flat line says nothing there.
+// Flagged jobs aren't touched automatically. `force_cleanup_job` has the daemon kill the
+// container, release the GPU and report the job as failed to the platform, which then applies
+// its usual rules for provider-side failures.
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+
+use crate::job_policy::JobCategory;
+use crate::{app_settings, emit_log_entry, get_timestamp, gpu_ids, invoke_daemon_cli_json_output, plugins, GpuInfo, LocalJob};
+
+const PROGRESS_EPSILON: f32 = 0.01;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct ZombieJobPolicy {
+    pub enabled: bool,
+    // How long progress must stay put with the GPU idle.
+    pub stalled_minutes: u32,
+    // GPU utilization at or below this counts as idle.
+    pub idle_below_percent: u32,
+}
+
+impl Default for ZombieJobPolicy {
+    fn default() -> Self {
+        ZombieJobPolicy { enabled: true, stalled_minutes: 30, idle_below_percent: 5 }
+    }
+}
+
+impl ZombieJobPolicy {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.stalled_minutes < 5 {
+            return Err("Jobs must be stalled for at least 5 minutes before they're flagged.".to_string());
+        }
+        if self.idle_below_percent > 100 {
+            return Err("The idle threshold is a GPU utilization percentage.".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ZombieJob {
+    pub job_id: String,
+    pub job_name: String,
+    pub gpu_id: Option<String>,
+    pub progress_percent: f32,
+    pub stalled_since: String,
+    pub detected_at: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct CleanupResult {
+    pub job_id: String,
+    pub container_removed: bool,
+    pub gpu_released: bool,
+    pub reported_to_platform: bool,
+}
+
+struct Watch {
+    progress: f32,
+    since: Instant,
+    since_at: String,
+}
+
+#[derive(Default)]
+struct Tracking {
+    watched: HashMap<String, Watch>, // Running jobs, by job ID
+    flagged: Vec<ZombieJob>,
+}
+
+static TRACKING: Mutex<Option<Tracking>> = Mutex::new(None);
+
+fn gpu_idle(policy: &ZombieJobPolicy, job: &LocalJob, gpus: &[GpuInfo]) -> bool {
+    // Without a GPU reading there's nothing to go on, so the job isn't suspected.
+    job.gpu_id
+        .as_deref()
+        .and_then(|id| gpus.iter().find(|g| g.id == id))
+        .and_then(|g| g.utilization_gpu_percent)
+        .is_some_and(|u| u <= policy.idle_below_percent)
+}
+
+fn reports_progress(job: &LocalJob) -> bool {
+    job.category != Some(JobCategory::Inference) && job.progress_percent > PROGRESS_EPSILON
+}
+
+// Called by the poller with the latest GPU listing and jobs.
+pub fn observe(app_handle: &AppHandle, gpus: &[GpuInfo], jobs: &[LocalJob]) {
+    let policy = app_settings::current(app_handle).zombie_jobs;
+    let stalled_for = Duration::from_secs(u64::from(policy.stalled_minutes) * 60);
+    let mut detected = Vec::new();
+    {
+        let mut tracking = TRACKING.lock().unwrap();
+        let tracking = tracking.get_or_insert_with(Tracking::default);
+        let running: Vec<&LocalJob> = jobs.iter().filter(|j| j.status == "running" && reports_progress(j)).collect();
+        tracking.watched.retain(|id, _| running.iter().any(|j| j.id == *id));
+        tracking.flagged.retain(|z| running.iter().any(|j| j.id == z.job_id));
+        if !policy.enabled {
+            tracking.watched.clear();
+            return;
+        }
+        for job in running {
+            let stalled = gpu_idle(&policy, job, gpus);
+            let watch = tracking.watched.entry(job.id.clone()).or_insert_with(|| Watch { progress: job.progress_percent, since: Instant::now(), since_at: get_timestamp() });
+            if !stalled || (job.progress_percent - watch.progress).abs() > PROGRESS_EPSILON {
+                *watch = Watch { progress: job.progress_percent, since: Instant::now(), since_at: get_timestamp() };
+                tracking.flagged.retain(|z| z.job_id != job.id);
+                continue;
+            }
+            if watch.since.elapsed() >= stalled_for && !tracking.flagged.iter().any(|z| z.job_id == job.id) {
+                let zombie = ZombieJob {
+                    job_id: job.id.clone(),
+                    job_name: job.name.clone(),
+                    gpu_id: job.gpu_id.clone(),
+                    progress_percent: job.progress_percent,
+                    stalled_since: watch.since_at.clone(),
+                    detected_at: get_timestamp(),
+                };
+                tracking.flagged.push(zombie.clone());
+                detected.push(zombie);
+            }
+        }
+    }
+    for zombie in detected {
+        emit_log_entry(
+            app_handle,
+            "error",
+            format!(
+                "Job {} has been stuck at {:.1}% with its GPU idle since {}; it may need a forced cleanup.",
+                zombie.job_name, zombie.progress_percent, zombie.stalled_since
+            ),
+        );
+        plugins::notify(app_handle, "zombie_job", &json!(zombie), false);
+        if let Err(e) = app_handle.emit_all("zombie_job", zombie) {
+            eprintln!("Failed to emit zombie_job event: {}", e);
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn get_zombie_jobs() -> Result<Vec<ZombieJob>, String> {
+    Ok(TRACKING.lock().unwrap().as_ref().map(|t| t.flagged.clone()).unwrap_or_default())
+}
+
+// Kills the job's container and releases its GPU through the daemon, which reports the job as
+// failed. Works on any running job, flagged or not.
+#[tauri::command]
+pub async fn force_cleanup_job(app_handle: AppHandle, job_id: String) -> Result<CleanupResult, String> {
+    let jobs = gpu_ids::local_jobs(&app_handle).await?;
+    let job = jobs.iter().find(|j| j.id == job_id).ok_or_else(|| format!("No job with ID '{}'.", job_id))?;
+    if job.status != "running" {
+        return Err(format!("Job {} isn't running ({}).", job.name, job.status));
+    }
+    let flagged = TRACKING.lock().unwrap().as_ref().is_some_and(|t| t.flagged.iter().any(|z| z.job_id == job_id));
+    let reason = if flagged { "stalled" } else { "provider_forced" };
+    let mut result =
+        invoke_daemon_cli_json_output::<CleanupResult>(&app_handle, &["--force-cleanup-job", "--job-id", &job_id, "--reason", reason]).await?;
+    result.job_id = job_id.clone();
+    if let Some(tracking) = TRACKING.lock().unwrap().as_mut() {
+        tracking.flagged.retain(|z| z.job_id != job_id);
+        tracking.watched.remove(&job_id);
+    }
+    // `local_jobs` already reports stable GPU IDs.
+    let gpu = job.gpu_id.clone().unwrap_or_else(|| "its GPU".to_string());
+    let message = if result.gpu_released {
+        format!("Job {} was force-cleaned; {} is free again.", job.name, gpu)
+    } else {
+        format!("Job {} was force-cleaned, but the daemon couldn't confirm {} was released.", job.name, gpu)
+    };
+    emit_log_entry(&app_handle, if result.gpu_released { "status" } else { "error" }, message);
+    if let Err(e) = app_handle.emit_all("job_force_cleaned", &result) {
+        eprintln!("Failed to emit job_force_cleaned event: {}", e);
+    }
+    Ok(result)
+}
@@ -1,6 +1,21 @@
 import React, { useState, useEffect, useRef } from 'react';
-import { invoke } from '@tauri-apps/api/tauri';
 import { listen, Event as TauriEvent } from '@tauri-apps/api/event';
+import {
+  FinancialSummary,
+  GpuInfo,
+  LocalJob,
+  NetworkStatus,
+  ProviderSettings,
+  getDetectedGpus,
+  getFinancialSummary,
+  getLocalJobs,
+  getNetworkStatus,
+  getProviderSettings,
+  setGpuRentalConfig,
+  startDaemon,
+  stopDaemon,
+  updateProviderSettings,
+} from './bindings';
 
 interface LogEntry {
   id: number;
@@ -9,51 +24,6 @@ interface LogEntry {
   timestamp: string;
 }
 
-// --- BEGIN NEW INTERFACES ---
-interface GpuInfo {
-  id: string;
-  name: string;
-  model: string;
-  vram_total_mb: number;
-  vram_free_mb: number;
-  utilization_gpu_percent?: number;
-  temperature_c?: number;
-  power_draw_w?: number;
-  is_available_for_rent: boolean;
-  current_hourly_rate_dgpu: number | null;
-}
-
-interface ProviderSettings {
-  default_hourly_rate_dgpu: number;
-  preferred_currency: string; // e.g., "USD", "EUR", "DCORE"
-  min_job_duration_minutes: number;
-  max_concurrent_jobs: number;
-}
-
-interface LocalJob {
-  id: string;
-  name: string;
-  status: 'running' | 'completed' | 'failed' | 'queued';
-  progress_percent: number;
-  started_at: string;
-  estimated_time_remaining_seconds?: number;
-}
-
-interface NetworkInfo {
-  connection_status: 'connected' | 'disconnected' | 'connecting';
-  ip_address?: string;
-  upload_speed_mbps?: number;
-  download_speed_mbps?: number;
-}
-
-interface FinancialSummary {
-  wallet_balance_dgpu: number;
-  total_earned_dgpu: number;
-  pending_payout_dgpu: number;
-  last_payout_at?: string;
-}
-// --- END NEW INTERFACES ---
-
 let logIdCounter = 0;
 
 function App() {
@@ -67,7 +37,7 @@ function App() {
   const [gpus, setGpus] = useState<GpuInfo[]>([]);
   const [providerSettings, setProviderSettings] = useState<ProviderSettings | null>(null);
   const [localJobs, setLocalJobs] = useState<LocalJob[]>([]);
-  const [networkStatus, setNetworkStatus] = useState<NetworkInfo | null>(null);
+  const [networkStatus, setNetworkStatus] = useState<NetworkStatus | null>(null);
   const [financialSummary, setFinancialSummary] = useState<FinancialSummary | null>(null);
   
   const [selectedGpu, setSelectedGpu] = useState<GpuInfo | null>(null);
@@ -131,7 +101,7 @@ function App() {
     const fetchInitialData = async () => {
       if (daemonActive) {
         try {
-          const detectedGpus = await invoke<GpuInfo[]>('get_detected_gpus');
+          const detectedGpus = await getDetectedGpus();
           setGpus(detectedGpus);
           addLog('status', `Fetched ${detectedGpus.length} GPUs.`);
         } catch (err) {
@@ -139,7 +109,7 @@ function App() {
         }
 
         try {
-          const settings = await invoke<ProviderSettings>('get_provider_settings');
+          const settings = await getProviderSettings();
           setProviderSettings(settings);
           addLog('status', 'Fetched provider settings.');
         } catch (err) {
@@ -147,7 +117,7 @@ function App() {
         }
         
         try {
-          const jobs = await invoke<LocalJob[]>('get_local_jobs');
+          const jobs = await getLocalJobs();
           setLocalJobs(jobs);
           addLog('status', `Fetched ${jobs.length} local jobs.`);
         } catch (err) {
@@ -155,7 +125,7 @@ function App() {
         }
 
         try {
-          const netInfo = await invoke<NetworkInfo>('get_network_status');
+          const netInfo = await getNetworkStatus();
           setNetworkStatus(netInfo);
           addLog('status', 'Fetched network status.');
         } catch (err) {
@@ -163,7 +133,7 @@ function App() {
         }
 
         try {
-          const finSummary = await invoke<FinancialSummary>('get_financial_summary');
+          const finSummary = await getFinancialSummary();
           setFinancialSummary(finSummary);
           addLog('status', 'Fetched financial summary.');
         } catch (err) {
@@ -188,7 +158,7 @@ function App() {
     addLog('status', 'Attempting to start daemon...');
     setDaemonError(null);
     try {
-      await invoke('start_daemon');
+      await startDaemon();
     } catch (error) {
       const errorMessage = error instanceof Error ? error.message : String(error);
       const UImessage = `Failed to send start command: ${errorMessage}`;
@@ -203,7 +173,7 @@ function App() {
     addLog('status', 'Attempting to stop daemon...');
     setDaemonError(null);
     try {
-      await invoke('stop_daemon');
+      await stopDaemon();
     } catch (error) {
       const errorMessage = error instanceof Error ? error.message : String(error);
       const UImessage = `Failed to send stop command: ${errorMessage}`;
@@ -250,14 +220,10 @@ function App() {
       return;
     }
     try {
-      await invoke('update_gpu_rental_settings', { 
-        gpuId: selectedGpu.id, 
-        isAvailable: !selectedGpu.is_available_for_rent, // Toggle availability
-        hourlyRate: rate 
-      });
-      addLog('status', `Successfully updated rental settings for GPU ${selectedGpu.name}. Toggled availability.`);
+      await setGpuRentalConfig(selectedGpu.id, rate, true, null, null);
+      addLog('status', `Successfully updated rental settings for GPU ${selectedGpu.name}.`);
       // Refresh GPU list
-      const updatedGpus = await invoke<GpuInfo[]>('get_detected_gpus');
+      const updatedGpus = await getDetectedGpus();
       setGpus(updatedGpus);
     } catch (err) {
       addLog('error', `Failed to update GPU ${selectedGpu.name} rental settings: ${err}`);
@@ -267,13 +233,10 @@ function App() {
   
   const handleToggleGpuAvailability = async (gpu: GpuInfo) => {
     try {
-      await invoke('update_gpu_rental_settings', { 
-        gpuId: gpu.id, 
-        isAvailable: !gpu.is_available_for_rent,
-        hourlyRate: gpu.current_hourly_rate_dgpu // Keep current rate or use default if null
-      });
+      const rate = gpu.current_hourly_rate_dgpu ?? providerSettings?.default_hourly_rate_dgpu ?? 0; // Keep current rate or use default if null
+      await setGpuRentalConfig(gpu.id, rate, !gpu.is_available_for_rent, null, null);
       addLog('status', `Toggled availability for GPU ${gpu.name}.`);
-      const updatedGpus = await invoke<GpuInfo[]>('get_detected_gpus');
+      const updatedGpus = await getDetectedGpus();
       setGpus(updatedGpus);
     } catch (err) {
       addLog('error', `Failed to toggle availability for GPU ${gpu.name}: ${err}`);
@@ -283,7 +246,7 @@ function App() {
   const handleSaveProviderSettings = async () => {
     if (!providerSettings) return;
     try {
-      await invoke('save_provider_settings', { settings: providerSettings });
+      await updateProviderSettings(providerSettings);
       addLog('status', 'Provider settings saved.');
     } catch (err) {
       addLog('error', `Failed to save provider settings: ${err}`);
@@ -322,9 +285,9 @@ function App() {
                 <div key={gpu.id} className="gpu-item card">
                   <h3>{gpu.name} ({gpu.model})</h3>
                   <p>VRAM: {gpu.vram_free_mb}MB Free / {gpu.vram_total_mb}MB Total</p>
-                  {gpu.utilization_gpu_percent !== undefined && <p>Util: {gpu.utilization_gpu_percent}%</p>}
-                  {gpu.temperature_c !== undefined && <p>Temp: {gpu.temperature_c}°C</p>}
-                  {gpu.power_draw_w !== undefined && <p>Power: {gpu.power_draw_w}W</p>}
+                  {gpu.utilization_gpu_percent != null && <p>Util: {gpu.utilization_gpu_percent}%</p>}
+                  {gpu.temperature_c != null && <p>Temp: {gpu.temperature_c}°C</p>}
+                  {gpu.power_draw_w != null && <p>Power: {gpu.power_draw_w}W</p>}
                   <p>Status: {gpu.is_available_for_rent ? 
                     <span className="status-rentable">Rentable @ {gpu.current_hourly_rate_dgpu} dGPU/hr</span> : 
                     <span className="status-private">Private</span>}
@@ -374,13 +337,9 @@ function App() {
                  <button onClick={async () => {
                     if (!selectedGpu) return;
                     try {
-                      await invoke('update_gpu_rental_settings', { 
-                        gpuId: selectedGpu.id, 
-                        isAvailable: false, 
-                        hourlyRate: selectedGpu.current_hourly_rate_dgpu 
-                      });
+                      await setGpuRentalConfig(selectedGpu.id, selectedGpu.current_hourly_rate_dgpu ?? 0, false, null, null);
                       addLog('status', `GPU ${selectedGpu.name} set to Private.`);
-                      const updatedGpus = await invoke<GpuInfo[]>('get_detected_gpus');
+                      const updatedGpus = await getDetectedGpus();
                       setGpus(updatedGpus);
                     } catch (err) {
                       addLog('error', `Failed to set GPU ${selectedGpu.name} to private: ${err}`);
@@ -412,8 +371,7 @@ function App() {
                   <div style={{ width: '100%', backgroundColor: '#eee' }}>
                     <div style={{ width: `${job.progress_percent}%`, backgroundColor: 'green', height: '10px' }}></div>
                   </div>
-                  <p>Started: {new Date(job.started_at).toLocaleString()}</p>
-                  {job.estimated_time_remaining_seconds && <p>ETA: {job.estimated_time_remaining_seconds}s</p>}
+                  {job.started_at && <p>Started: {new Date(job.started_at).toLocaleString()}</p>}
                 </div>
               ))}
             </div>
@@ -435,7 +393,7 @@ function App() {
               <h4>Network Status</h4>
               {networkStatus ? (
                 <>
-                  <p>Connection: {networkStatus.connection_status}</p>
+                  <p>Connection: {networkStatus.connection_type}</p>
                   {networkStatus.ip_address && <p>IP: {networkStatus.ip_address}</p>}
                   {/* Add more network details if available */}
                 </>
@@ -445,7 +403,7 @@ function App() {
               <h4>Financial Summary</h4>
               {financialSummary ? (
                 <>
-                  <p>Wallet Balance: {financialSummary.wallet_balance_dgpu.toFixed(2)} dGPU</p>
+                  <p>Wallet Balance: {financialSummary.current_balance_dgpu.toFixed(2)} dGPU</p>
                   <p>Total Earned: {financialSummary.total_earned_dgpu.toFixed(2)} dGPU</p>
                   <p>Pending Payout: {financialSummary.pending_payout_dgpu.toFixed(2)} dGPU</p>
                   {financialSummary.last_payout_at && <p>Last Payout: {new Date(financialSummary.last_payout_at).toLocaleString()}</p>}
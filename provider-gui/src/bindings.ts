@@ -0,0 +1,66 @@
+// This file was generated by [tauri-specta](https://github.com/oscartbeaumont/tauri-specta). Do not edit this file manually.
+
+declare global {
+    interface Window {
+        __TAURI_INVOKE__<T>(cmd: string, args?: Record<string, unknown>): Promise<T>;
+    }
+}
+
+// Function avoids 'window not defined' in SSR
+const invoke = () => window.__TAURI_INVOKE__;
+
+export function startDaemon() {
+    return invoke()<string>("start_daemon")
+}
+
+export function stopDaemon() {
+    return invoke()<string>("stop_daemon")
+}
+
+export function getDetectedGpus() {
+    return invoke()<GpuInfo[]>("get_detected_gpus")
+}
+
+export function getProviderSettings() {
+    return invoke()<ProviderSettings>("get_provider_settings")
+}
+
+export function updateProviderSettings(settings: ProviderSettings) {
+    return invoke()<ProviderSettings>("update_provider_settings", { settings })
+}
+
+export function setGpuRentalConfig(gpuId: string, hourlyRate: DgpuPerHour, available: boolean, confirmation: string | null, mode: RentalMode | null) {
+    return invoke()<GpuInfo>("set_gpu_rental_config", { gpuId,hourlyRate,available,confirmation,mode })
+}
+
+export function getLocalJobs() {
+    return invoke()<LocalJob[]>("get_local_jobs")
+}
+
+export function getNetworkStatus() {
+    return invoke()<NetworkStatus>("get_network_status")
+}
+
+export function getFinancialSummary() {
+    return invoke()<FinancialSummary>("get_financial_summary")
+}
+
+export type AttestationState = "pending" | "invalid" | "submitted" | "accepted" | "rejected" | "failed"
+export type DgpuPerHour = number
+export type FinancialSummary = { current_balance_dgpu: number; total_earned_dgpu: number; pending_payout_dgpu: number; last_payout_at: string | null }
+export type GpuInfo = { id: string; name: string; model: string; vram_total_mb: MegaBytes; vram_free_mb: MegaBytes; utilization_gpu_percent: number | null; temperature_c: number | null; power_draw_w: Watts | null; is_available_for_rent: boolean; current_hourly_rate_dgpu: DgpuPerHour | null; rental_mode: RentalMode; ecc_corrected_errors: number | null; memory_clock_mhz: number | null; fan_speed_percent: number | null; pcie_link_generation: number | null; pcie_link_generation_max: number | null; pcie_link_width: number | null; pcie_link_width_max: number | null; throttle_reasons: number | null; uuid?: string; pci_bus_id?: string }
+export type JobAttestation = { state: AttestationState; proofs: number; attempts: number; submitted_at: string | null; decided_at: string | null; detail: string | null }
+export type JobCategory = "training" | "inference" | "rendering" | "data_processing" | "crypto_mining" | "other"
+export type JobHardwareEvidence = { samples: number; thermal_throttled_samples: number; power_throttled_samples: number; min_pcie_link_generation: number | null; min_pcie_link_width: number | null; max_temperature_c: number | null; first_sampled_at: string; last_sampled_at: string }
+export type LocalJob = { id: string; name: string; status: string; progress_percent: number; submitted_at: string; started_at: string | null; completed_at: string | null; estimated_cost_dgpu: number | null; renter_id: string | null; category: JobCategory | null; framework: string | null; max_duration_minutes: number | null; gpu_id: string | null; attestation?: JobAttestation; hardware?: JobHardwareEvidence }
+export type MegaBytes = number
+export type NetworkStatus = { connection_type: string; ip_address: string | null; upload_speed_mbps: number; download_speed_mbps: number; latency_ms: number }
+export type ProviderSettings = { default_hourly_rate_dgpu: DgpuPerHour; preferred_currency: string; min_job_duration_minutes: number; max_concurrent_jobs: number }
+export type RentalMode = "spot" | "reserved"
+export type Watts = number
+
+// Event payloads and errors
+
+export type ProviderError = { kind: "daemon_unavailable"; program: string; message: string } | { kind: "daemon_failed"; command: string; status: string; stderr: string; stdout: string } | { kind: "invalid_response"; command: string; message: string; output: string } | { kind: "storage"; message: string }
+export type EventEnvelope = { id: string; source: string; kind: string; occurred_at: string | null; received_at: string; payload: any }
+export type HardwareAlert = { gpu_id: string; gpu_name: string; severity: string; kind: string; message: string; temperature_c: number; fan_speed_percent: number; rentals_paused: boolean; detected_at: string }